@@ -29,13 +29,17 @@ use itertools::Itertools;
 use numfmt::{Formatter, Scales};
 use quickwit_common::tower::{Rate, RateEstimator, SmaRateEstimator};
 use quickwit_common::uri::Uri;
-use quickwit_config::{ConfigFormat, IndexConfig};
+use quickwit_config::{ConfigFormat, IndexConfig, TransformConfig};
 use quickwit_metastore::{IndexMetadata, Split, SplitState};
 use quickwit_proto::search::{CountHits, SortField, SortOrder};
 use quickwit_proto::types::IndexId;
-use quickwit_rest_client::models::{IngestSource, SearchResponseRestClient};
+use quickwit_rest_client::models::{IngestSource, SearchResponseRestClient, Timeout};
 use quickwit_rest_client::rest_client::{CommitType, IngestEvent};
-use quickwit_serve::{ListSplitsQueryParams, SearchRequestQueryString, SortBy};
+use quickwit_search::{SqlFormat, SqlRequest};
+use quickwit_serve::{
+    storage_tier_stats, ListSplitsQueryParams, SearchRequestQueryString, SortBy, SplitFilesMode,
+    StorageTierStats,
+};
 use quickwit_storage::{load_file, StorageResolver};
 use tabled::settings::object::{FirstRow, Rows, Segment};
 use tabled::settings::panel::Footer;
@@ -187,6 +191,74 @@ pub fn build_index_command() -> Command {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("snapshot")
+                .display_order(9)
+                .about("Takes a snapshot of an index.")
+                .long_about("Takes a snapshot of an index: its config, its sources and their checkpoints, and the manifest of its published splits. The split files are copied to the snapshot URI unless `--reference-splits` is set, in which case the snapshot only references the split files of the index and can no longer be restored once they are garbage collected.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--"snapshot-uri" <SNAPSHOT_URI> "URI of the storage receiving the snapshot.")
+                        .display_order(2)
+                        .required(true),
+                    arg!(--"reference-splits" "References the split files of the index instead of copying them.")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("restore")
+                .display_order(10)
+                .about("Restores an index from a snapshot.")
+                .long_about("Recreates an index from a snapshot, optionally under a new index ID, and registers its splits in the metastore. Shards and ingest queues are not restored.")
+                .args(&[
+                    arg!(--"snapshot-uri" <SNAPSHOT_URI> "URI of the snapshot to restore.")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--index <INDEX> "ID of the restored index. Defaults to the ID of the snapshotted index.")
+                        .display_order(2)
+                        .required(false),
+                    arg!(--"index-uri" <INDEX_URI> "URI of the restored index. Defaults to the URI of the snapshotted index if the index ID is unchanged, to the default index root URI of the cluster otherwise.")
+                        .display_order(3)
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("reindex")
+                .display_order(11)
+                .about("Reindexes the documents of an index into another index.")
+                .long_about("Creates a reindex source on the target index that reads the documents of the source index matching a query, optionally transforms them with a VRL script, and indexes them into the target index. The reindex runs in the background and resumes from its checkpoint on failure. Only stored fields are reindexed.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--"source-index" <SOURCE_INDEX> "ID of the index to read the documents from.")
+                        .display_order(2)
+                        .required(true),
+                    arg!(--query <QUERY> "Query selecting the documents to reindex. Defaults to all the documents.")
+                        .display_order(3)
+                        .required(false),
+                    arg!(--"transform-script" <TRANSFORM_SCRIPT> "VRL script applied to the documents before they are indexed.")
+                        .display_order(4)
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("sql")
+                .display_order(12)
+                .about("Runs a SQL query on one or several indexes.")
+                .long_about("Runs a SQL `SELECT` statement. The indexes are given in the `FROM` clause, and can be index ID patterns such as `logs-*`. Learn more on https://quickwit.io/docs/reference/rest-api#sql.")
+                .args(&[
+                    arg!(--query <QUERY> "SQL query, e.g. \"SELECT service, COUNT(*) FROM logs WHERE severity_text = 'ERROR' GROUP BY service\".")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--format <FORMAT> "Output format of the rows: `json` or `csv`.")
+                        .display_order(2)
+                        .default_value("json")
+                        .required(false),
+                ])
+            )
         .arg_required_else_help(true)
 }
 
@@ -244,6 +316,13 @@ pub struct SearchIndexArgs {
     pub sort_by_score: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct SqlArgs {
+    pub client_args: ClientArgs,
+    pub query: String,
+    pub format: SqlFormat,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct DeleteIndexArgs {
     pub client_args: ClientArgs,
@@ -257,6 +336,31 @@ pub struct ListIndexesArgs {
     pub client_args: ClientArgs,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct SnapshotIndexArgs {
+    pub client_args: ClientArgs,
+    pub index_id: IndexId,
+    pub snapshot_uri: Uri,
+    pub split_files_mode: SplitFilesMode,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct RestoreIndexArgs {
+    pub client_args: ClientArgs,
+    pub snapshot_uri: Uri,
+    pub index_id_opt: Option<IndexId>,
+    pub index_uri_opt: Option<Uri>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReindexIndexArgs {
+    pub client_args: ClientArgs,
+    pub index_id: IndexId,
+    pub source_index_id: IndexId,
+    pub query: String,
+    pub transform_script_opt: Option<String>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum IndexCliCommand {
     Clear(ClearIndexArgs),
@@ -266,13 +370,17 @@ pub enum IndexCliCommand {
     Describe(DescribeIndexArgs),
     Ingest(IngestDocsArgs),
     List(ListIndexesArgs),
+    Reindex(ReindexIndexArgs),
+    Restore(RestoreIndexArgs),
     Search(SearchIndexArgs),
+    Snapshot(SnapshotIndexArgs),
+    Sql(SqlArgs),
 }
 
 impl IndexCliCommand {
     pub fn default_log_level(&self) -> Level {
         match self {
-            Self::Search(_) => Level::ERROR,
+            Self::Search(_) | Self::Sql(_) => Level::ERROR,
             _ => Level::INFO,
         }
     }
@@ -288,7 +396,11 @@ impl IndexCliCommand {
             "describe" => Self::parse_describe_args(submatches),
             "ingest" => Self::parse_ingest_args(submatches),
             "list" => Self::parse_list_args(submatches),
+            "reindex" => Self::parse_reindex_args(submatches),
+            "restore" => Self::parse_restore_args(submatches),
             "search" => Self::parse_search_args(submatches),
+            "snapshot" => Self::parse_snapshot_args(submatches),
+            "sql" => Self::parse_sql_args(submatches),
             "update" => Self::parse_update_args(submatches),
             _ => bail!("unknown index subcommand `{subcommand}`"),
         }
@@ -446,6 +558,27 @@ impl IndexCliCommand {
         }))
     }
 
+    fn parse_sql_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let query = matches
+            .remove_one::<String>("query")
+            .expect("`query` should be a required arg.");
+        let format = match matches
+            .remove_one::<String>("format")
+            .expect("`format` should have a default value.")
+            .as_str()
+        {
+            "json" => SqlFormat::Json,
+            "csv" => SqlFormat::Csv,
+            format => bail!("unknown SQL output format `{format}`: expected `json` or `csv`"),
+        };
+        let client_args = ClientArgs::parse(&mut matches)?;
+        Ok(Self::Sql(SqlArgs {
+            client_args,
+            query,
+            format,
+        }))
+    }
+
     fn parse_delete_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
         let client_args = ClientArgs::parse(&mut matches)?;
         let index_id = matches
@@ -461,6 +594,68 @@ impl IndexCliCommand {
         }))
     }
 
+    fn parse_snapshot_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let snapshot_uri = matches
+            .remove_one::<String>("snapshot-uri")
+            .map(|uri| Uri::from_str(&uri))
+            .expect("`snapshot-uri` should be a required arg.")?;
+        let split_files_mode = if matches.get_flag("reference-splits") {
+            SplitFilesMode::Reference
+        } else {
+            SplitFilesMode::Copy
+        };
+        Ok(Self::Snapshot(SnapshotIndexArgs {
+            client_args,
+            index_id,
+            snapshot_uri,
+            split_files_mode,
+        }))
+    }
+
+    fn parse_restore_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let snapshot_uri = matches
+            .remove_one::<String>("snapshot-uri")
+            .map(|uri| Uri::from_str(&uri))
+            .expect("`snapshot-uri` should be a required arg.")?;
+        let index_id_opt = matches.remove_one::<String>("index");
+        let index_uri_opt = matches
+            .remove_one::<String>("index-uri")
+            .map(|uri| Uri::from_str(&uri))
+            .transpose()?;
+        Ok(Self::Restore(RestoreIndexArgs {
+            client_args,
+            snapshot_uri,
+            index_id_opt,
+            index_uri_opt,
+        }))
+    }
+
+    fn parse_reindex_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let source_index_id = matches
+            .remove_one::<String>("source-index")
+            .expect("`source-index` should be a required arg.");
+        let query = matches
+            .remove_one::<String>("query")
+            .unwrap_or_else(|| "*".to_string());
+        let transform_script_opt = matches.remove_one::<String>("transform-script");
+        Ok(Self::Reindex(ReindexIndexArgs {
+            client_args,
+            index_id,
+            source_index_id,
+            query,
+            transform_script_opt,
+        }))
+    }
+
     pub async fn execute(self) -> anyhow::Result<()> {
         match self {
             Self::Clear(args) => clear_index_cli(args).await,
@@ -469,7 +664,11 @@ impl IndexCliCommand {
             Self::Describe(args) => describe_index_cli(args).await,
             Self::Ingest(args) => ingest_docs_cli(args).await,
             Self::List(args) => list_index_cli(args).await,
+            Self::Reindex(args) => reindex_index_cli(args).await,
+            Self::Restore(args) => restore_index_cli(args).await,
             Self::Search(args) => search_index_cli(args).await,
+            Self::Snapshot(args) => snapshot_index_cli(args).await,
+            Self::Sql(args) => sql_cli(args).await,
             Self::Update(args) => update_index_cli(args).await,
         }
     }
@@ -493,6 +692,76 @@ pub async fn clear_index_cli(args: ClearIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub async fn snapshot_index_cli(mut args: SnapshotIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "snapshot-index");
+    println!("❯ Taking snapshot of index...");
+    // Copying the split files can take a while, so the default timeout is disabled.
+    args.client_args.timeout.get_or_insert(Timeout::none());
+    let qw_client = args.client_args.client();
+    let snapshot_summary = qw_client
+        .indexes()
+        .snapshot(
+            &args.index_id,
+            args.snapshot_uri.as_str(),
+            args.split_files_mode,
+        )
+        .await?;
+    println!(
+        "{} Snapshot of index `{}` successfully taken: {} splits ({}).",
+        "✔".color(GREEN_COLOR),
+        snapshot_summary.index_id,
+        snapshot_summary.num_splits,
+        ByteSize(snapshot_summary.num_bytes)
+    );
+    Ok(())
+}
+
+pub async fn restore_index_cli(mut args: RestoreIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "restore-index");
+    println!("❯ Restoring index...");
+    args.client_args.timeout.get_or_insert(Timeout::none());
+    let qw_client = args.client_args.client();
+    let index_metadata = qw_client
+        .indexes()
+        .restore(
+            args.snapshot_uri.as_str(),
+            args.index_id_opt.as_deref(),
+            args.index_uri_opt.as_ref().map(Uri::as_str),
+        )
+        .await?;
+    println!(
+        "{} Index `{}` successfully restored.",
+        "✔".color(GREEN_COLOR),
+        index_metadata.index_id()
+    );
+    Ok(())
+}
+
+pub async fn reindex_index_cli(args: ReindexIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "reindex-index");
+    let transform_config_opt = args
+        .transform_script_opt
+        .map(|transform_script| TransformConfig::new(transform_script, None));
+    let qw_client = args.client_args.client();
+    let source_config = qw_client
+        .indexes()
+        .reindex(
+            &args.index_id,
+            &args.source_index_id,
+            &args.query,
+            transform_config_opt.as_ref(),
+        )
+        .await?;
+    println!(
+        "{} Reindex of index `{}` into index `{}` successfully started with source `{}`.",
+        "✔".color(GREEN_COLOR),
+        args.source_index_id,
+        args.index_id,
+        source_config.source_id
+    );
+    Ok(())
+}
+
 pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "create-index");
     println!("❯ Creating index...");
@@ -609,6 +878,19 @@ pub struct IndexStats {
     pub timestamp_range: Option<(i64, i64)>,
     pub num_docs_descriptive: Option<DescriptiveStats>,
     pub num_bytes_descriptive: Option<DescriptiveStats>,
+    pub storage_tiers: Vec<StorageTierStats>,
+}
+
+#[derive(Tabled)]
+struct StorageTierRow {
+    #[tabled(rename = "Tier")]
+    name: String,
+    #[tabled(rename = "Storage URI")]
+    storage_uri: Uri,
+    #[tabled(rename = "Number of published splits")]
+    num_published_splits: String,
+    #[tabled(rename = "Size of published splits")]
+    size_published_splits: ByteSize,
 }
 
 impl Tabled for IndexStats {
@@ -751,6 +1033,7 @@ impl IndexStats {
             (None, None)
         };
         let index_config = index_metadata.into_index_config();
+        let storage_tiers = storage_tier_stats(&index_config, &published_splits);
 
         Ok(Self {
             index_id: index_config.index_id.clone(),
@@ -763,6 +1046,7 @@ impl IndexStats {
             timestamp_range,
             num_docs_descriptive,
             num_bytes_descriptive,
+            storage_tiers,
         })
     }
 
@@ -782,6 +1066,17 @@ impl IndexStats {
             tables.push(size_stats_table);
         }
 
+        if !self.storage_tiers.is_empty() {
+            let storage_tier_rows = self.storage_tiers.iter().map(|tier| StorageTierRow {
+                name: tier.name.clone(),
+                storage_uri: tier.storage_uri.clone(),
+                num_published_splits: separate_thousands(tier.num_published_splits),
+                size_published_splits: ByteSize(tier.size_published_splits),
+            });
+            let storage_tiers_table = make_table("Storage tiers", storage_tier_rows, false);
+            tables.push(storage_tiers_table);
+        }
+
         let table = Table::builder(tables.into_iter().map(|table| table.to_string()))
             .build()
             .with(Modify::new(Segment::all()).with(Alignment::center_vertical()))
@@ -1125,6 +1420,21 @@ pub async fn search_index_cli(args: SearchIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub async fn sql_cli(args: SqlArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "sql");
+    let sql_request = SqlRequest {
+        query: args.query,
+        format: SqlFormat::Json,
+    };
+    let qw_client = args.client_args.client();
+    let sql_response = qw_client.sql(sql_request).await?;
+    match args.format {
+        SqlFormat::Json => println!("{}", serde_json::to_string_pretty(&sql_response)?),
+        SqlFormat::Csv => print!("{}", sql_response.to_csv()),
+    }
+    Ok(())
+}
+
 pub async fn delete_index_cli(args: DeleteIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "delete-index");
     if !args.dry_run && !args.assume_yes {
@@ -1226,6 +1536,7 @@ mod test {
             Some("timestamp".to_string())
         );
         assert_eq!(index_stats.timestamp_range, Some((1111, 2222)));
+        assert!(index_stats.storage_tiers.is_empty());
 
         Ok(())
     }
@@ -16,6 +16,7 @@
 
 use std::collections::HashSet;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Context;
 use clap::{arg, Arg, ArgMatches};
@@ -25,7 +26,7 @@ use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::Uri;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
-    ConfigFormat, MetastoreConfigs, NodeConfig, SourceConfig, StorageConfigs,
+    ConfigFormat, EncryptionConfig, MetastoreConfigs, NodeConfig, SourceConfig, StorageConfigs,
     DEFAULT_QW_CONFIG_PATH,
 };
 use quickwit_indexing::check_source_connectivity;
@@ -33,7 +34,7 @@ use quickwit_metastore::{IndexMetadataResponseExt, MetastoreResolver};
 use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService, MetastoreServiceClient};
 use quickwit_rest_client::models::Timeout;
 use quickwit_rest_client::rest_client::{QuickwitClient, QuickwitClientBuilder, DEFAULT_BASE_URL};
-use quickwit_storage::{load_file, StorageResolver};
+use quickwit_storage::{load_file, LocalKeyProvider, StorageResolver};
 use reqwest::Url;
 use tabled::settings::object::Rows;
 use tabled::settings::panel::Header;
@@ -219,19 +220,28 @@ async fn load_node_config(config_uri: &Uri) -> anyhow::Result<NodeConfig> {
 fn get_resolvers(
     storage_configs: &StorageConfigs,
     metastore_configs: &MetastoreConfigs,
-) -> (StorageResolver, MetastoreResolver) {
+    encryption_config: &EncryptionConfig,
+) -> anyhow::Result<(StorageResolver, MetastoreResolver)> {
     // The CLI tests rely on the unconfigured singleton resolvers, so it's better to return them if
-    // the storage and metastore configs are not set.
-    if storage_configs.is_empty() && metastore_configs.is_empty() {
-        return (
+    // the storage, metastore, and encryption configs are not set.
+    if storage_configs.is_empty()
+        && metastore_configs.is_empty()
+        && encryption_config.keyfile_path_opt.is_none()
+    {
+        return Ok((
             StorageResolver::unconfigured(),
             MetastoreResolver::unconfigured(),
-        );
+        ));
+    }
+    let mut storage_resolver = StorageResolver::configured(storage_configs);
+
+    if let Some(keyfile_path) = &encryption_config.keyfile_path_opt {
+        let key_provider = LocalKeyProvider::from_keyfile(keyfile_path)?;
+        storage_resolver = storage_resolver.with_key_provider(Arc::new(key_provider));
     }
-    let storage_resolver = StorageResolver::configured(storage_configs);
     let metastore_resolver =
         MetastoreResolver::configured(storage_resolver.clone(), metastore_configs);
-    (storage_resolver, metastore_resolver)
+    Ok((storage_resolver, metastore_resolver))
 }
 
 /// Runs connectivity checks for a given `metastore_uri` and `index_id`.
@@ -429,7 +439,8 @@ mod tests {
         };
         let storage_configs = StorageConfigs::new(vec![s3_storage_config.into()]);
         let metastore_configs = MetastoreConfigs::default();
+        let encryption_config = EncryptionConfig::default();
         let (_storage_resolver, _metastore_resolver) =
-            get_resolvers(&storage_configs, &metastore_configs);
+            get_resolvers(&storage_configs, &metastore_configs, &encryption_config).unwrap();
     }
 }
@@ -131,7 +131,8 @@ mod tests {
     use quickwit_cli::cli::{build_cli, CliCommand};
     use quickwit_cli::index::{
         ClearIndexArgs, CreateIndexArgs, DeleteIndexArgs, DescribeIndexArgs, IndexCliCommand,
-        IngestDocsArgs, SearchIndexArgs,
+        IngestDocsArgs, ReindexIndexArgs, RestoreIndexArgs, SearchIndexArgs, SnapshotIndexArgs,
+        SqlArgs,
     };
     use quickwit_cli::split::{DescribeSplitArgs, SplitCliCommand};
     use quickwit_cli::tool::{
@@ -143,6 +144,8 @@ mod tests {
     use quickwit_config::SourceInputFormat;
     use quickwit_rest_client::models::Timeout;
     use quickwit_rest_client::rest_client::CommitType;
+    use quickwit_search::SqlFormat;
+    use quickwit_serve::SplitFilesMode;
     use reqwest::Url;
 
     #[test]
@@ -495,6 +498,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_sql_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "sql",
+            "--query",
+            "SELECT COUNT(*) FROM wikipedia",
+            "--format",
+            "csv",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Sql(SqlArgs {
+                query,
+                format: SqlFormat::Csv,
+                ..
+            })) if query == "SELECT COUNT(*) FROM wikipedia"
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "sql",
+            "--query",
+            "SELECT * FROM wikipedia",
+            "--format",
+            "xml",
+        ])?;
+        assert!(CliCommand::parse_cli_args(matches).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_parse_local_search_args() {
         let app = build_cli().no_binary_name(true);
@@ -604,6 +641,142 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_snapshot_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "snapshot",
+                "--index",
+                "wikipedia",
+                "--snapshot-uri",
+                "s3://backups/wikipedia",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Snapshot(SnapshotIndexArgs {
+            client_args: ClientArgs::default(),
+            index_id: "wikipedia".to_string(),
+            snapshot_uri: Uri::for_test("s3://backups/wikipedia"),
+            split_files_mode: SplitFilesMode::Copy,
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "snapshot",
+                "--index",
+                "wikipedia",
+                "--snapshot-uri",
+                "s3://backups/wikipedia",
+                "--reference-splits",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Snapshot(SnapshotIndexArgs {
+                split_files_mode: SplitFilesMode::Reference,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_restore_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "restore",
+                "--snapshot-uri",
+                "s3://backups/wikipedia",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Restore(RestoreIndexArgs {
+            client_args: ClientArgs::default(),
+            snapshot_uri: Uri::for_test("s3://backups/wikipedia"),
+            index_id_opt: None,
+            index_uri_opt: None,
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "restore",
+                "--snapshot-uri",
+                "s3://backups/wikipedia",
+                "--index",
+                "wikipedia-restored",
+                "--index-uri",
+                "s3://indexes/wikipedia-restored",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Restore(RestoreIndexArgs {
+            client_args: ClientArgs::default(),
+            snapshot_uri: Uri::for_test("s3://backups/wikipedia"),
+            index_id_opt: Some("wikipedia-restored".to_string()),
+            index_uri_opt: Some(Uri::for_test("s3://indexes/wikipedia-restored")),
+        }));
+        assert_eq!(command, expected_cmd);
+    }
+
+    #[test]
+    fn test_parse_reindex_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "reindex",
+                "--index",
+                "wikipedia-v2",
+                "--source-index",
+                "wikipedia",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Reindex(ReindexIndexArgs {
+            client_args: ClientArgs::default(),
+            index_id: "wikipedia-v2".to_string(),
+            source_index_id: "wikipedia".to_string(),
+            query: "*".to_string(),
+            transform_script_opt: None,
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "reindex",
+                "--index",
+                "wikipedia-v2",
+                "--source-index",
+                "wikipedia",
+                "--query",
+                "title:Paris",
+                "--transform-script",
+                ".title = upcase!(.title)",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Reindex(ReindexIndexArgs {
+            client_args: ClientArgs::default(),
+            index_id: "wikipedia-v2".to_string(),
+            source_index_id: "wikipedia".to_string(),
+            query: "title:Paris".to_string(),
+            transform_script_opt: Some(".title = upcase!(.title)".to_string()),
+        }));
+        assert_eq!(command, expected_cmd);
+    }
+
     #[test]
     fn test_parse_describe_index_args() {
         let app = build_cli().no_binary_name(true);
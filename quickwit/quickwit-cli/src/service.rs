@@ -16,6 +16,7 @@ use std::collections::HashSet;
 use std::pin::pin;
 use std::str::FromStr;
 
+use anyhow::Context;
 use clap::{arg, ArgAction, ArgMatches, Command};
 use colored::Colorize;
 use futures::future::select;
@@ -23,10 +24,11 @@ use itertools::Itertools;
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::{Protocol, Uri};
 use quickwit_config::service::QuickwitService;
-use quickwit_config::NodeConfig;
+use quickwit_config::{NodeConfig, TelemetryConfig, TelemetrySinkConfig};
 use quickwit_serve::tcp_listener::DefaultTcpListenerResolver;
 use quickwit_serve::{serve_quickwit, BuildInfo, EnvFilterReloadFn};
 use quickwit_telemetry::payload::{QuickwitFeature, QuickwitTelemetryInfo, TelemetryEvent};
+use quickwit_telemetry::sink::{FileSink, HttpClient, PrometheusSink, Sink};
 use tokio::signal;
 use tracing::{debug, info};
 
@@ -110,16 +112,22 @@ impl RunCliCommand {
         let version_text = BuildInfo::get_version_text();
         info!("quickwit version: {version_text}");
         let mut node_config = load_node_config(&self.config_uri).await?;
-        let (storage_resolver, metastore_resolver) =
-            get_resolvers(&node_config.storage_configs, &node_config.metastore_configs);
+        let (storage_resolver, metastore_resolver) = get_resolvers(
+            &node_config.storage_configs,
+            &node_config.metastore_configs,
+            &node_config.encryption_config,
+        )?;
         crate::busy_detector::set_enabled(true);
 
         if let Some(services) = &self.services {
             info!(services = %services.iter().join(", "), "setting services from override");
             node_config.enabled_services.clone_from(services);
         }
-        let telemetry_handle_opt =
-            quickwit_telemetry::start_telemetry_loop(quickwit_telemetry_info(&node_config));
+        let telemetry_sinks = telemetry_sinks(&node_config.telemetry_config)?;
+        let telemetry_handle_opt = quickwit_telemetry::start_telemetry_loop(
+            quickwit_telemetry_info(&node_config),
+            telemetry_sinks,
+        );
         quickwit_telemetry::send_telemetry_event(TelemetryEvent::RunCommand).await;
         // TODO move in serve quickwit?
         let runtimes_config = RuntimesConfig::default();
@@ -176,6 +184,32 @@ fn quickwit_telemetry_info(config: &NodeConfig) -> QuickwitTelemetryInfo {
     QuickwitTelemetryInfo::new(services, features)
 }
 
+fn telemetry_sinks(telemetry_config: &TelemetryConfig) -> anyhow::Result<Vec<Box<dyn Sink>>> {
+    let mut sinks: Vec<Box<dyn Sink>> = Vec::with_capacity(telemetry_config.sinks.len());
+
+    for sink_config in &telemetry_config.sinks {
+        match sink_config {
+            TelemetrySinkConfig::File { path } => {
+                info!(path=%path.display(), "telemetry to file is enabled");
+                sinks.push(Box::new(FileSink::new(path.clone())));
+            }
+            TelemetrySinkConfig::Http { endpoint, headers } => {
+                let http_client = HttpClient::try_with_endpoint(endpoint.clone(), headers)
+                    .with_context(|| {
+                        format!("failed to create telemetry HTTP client for `{endpoint}`")
+                    })?;
+                info!("telemetry to {endpoint} is enabled");
+                sinks.push(Box::new(http_client));
+            }
+            TelemetrySinkConfig::Prometheus => {
+                info!("telemetry to prometheus is enabled");
+                sinks.push(Box::new(PrometheusSink));
+            }
+        }
+    }
+    Ok(sinks)
+}
+
 #[cfg(test)]
 mod tests {
 
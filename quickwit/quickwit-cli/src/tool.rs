@@ -399,8 +399,11 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
     println!("❯ Ingesting documents locally...");
 
     let config = load_node_config(&args.config_uri).await?;
-    let (storage_resolver, metastore_resolver) =
-        get_resolvers(&config.storage_configs, &config.metastore_configs);
+    let (storage_resolver, metastore_resolver) = get_resolvers(
+        &config.storage_configs,
+        &config.metastore_configs,
+        &config.encryption_config,
+    )?;
     let mut metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
 
     let source_params = if let Some(uri) = args.input_path_opt.as_ref() {
@@ -528,8 +531,11 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
     debug!(args=?args, "local-search");
     println!("❯ Searching directly on the index storage (without calling REST API)...");
     let config = load_node_config(&args.config_uri).await?;
-    let (storage_resolver, metastore_resolver) =
-        get_resolvers(&config.storage_configs, &config.metastore_configs);
+    let (storage_resolver, metastore_resolver) = get_resolvers(
+        &config.storage_configs,
+        &config.metastore_configs,
+        &config.encryption_config,
+    )?;
     let metastore: MetastoreServiceClient =
         metastore_resolver.resolve(&config.metastore_uri).await?;
     let aggs = args
@@ -546,6 +552,7 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
         start_timestamp: args.start_timestamp,
         end_timestamp: args.end_timestamp,
         aggs,
+        runtime_mappings: None,
         format: BodyFormat::Json,
         sort_by,
         count_all: CountHits::CountAll,
@@ -566,8 +573,11 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
     debug!(args=?args, "run-merge-operations");
     println!("❯ Merging splits locally...");
     let config = load_node_config(&args.config_uri).await?;
-    let (storage_resolver, metastore_resolver) =
-        get_resolvers(&config.storage_configs, &config.metastore_configs);
+    let (storage_resolver, metastore_resolver) = get_resolvers(
+        &config.storage_configs,
+        &config.metastore_configs,
+        &config.encryption_config,
+    )?;
     let mut metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
     run_index_checklist(&mut metastore, &storage_resolver, &args.index_id, None).await?;
     // The indexing service needs to update its cluster chitchat state so that the control plane is
@@ -653,8 +663,11 @@ pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow:
     println!("❯ Garbage collecting index...");
 
     let config = load_node_config(&args.config_uri).await?;
-    let (storage_resolver, metastore_resolver) =
-        get_resolvers(&config.storage_configs, &config.metastore_configs);
+    let (storage_resolver, metastore_resolver) = get_resolvers(
+        &config.storage_configs,
+        &config.metastore_configs,
+        &config.encryption_config,
+    )?;
     let metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
     let mut index_service = IndexService::new(metastore, storage_resolver);
     let removal_info = index_service
@@ -718,14 +731,22 @@ async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
     println!("❯ Extracting split...");
 
     let config = load_node_config(&args.config_uri).await?;
-    let (storage_resolver, metastore_resolver) =
-        get_resolvers(&config.storage_configs, &config.metastore_configs);
+    let (storage_resolver, metastore_resolver) = get_resolvers(
+        &config.storage_configs,
+        &config.metastore_configs,
+        &config.encryption_config,
+    )?;
     let metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
     let index_metadata = metastore
         .index_metadata(IndexMetadataRequest::for_index_id(args.index_id))
         .await?
         .deserialize_index_metadata()?;
     let index_storage = storage_resolver.resolve(index_metadata.index_uri()).await?;
+    let index_storage = storage_resolver.wrap_split_storage(
+        index_storage,
+        None,
+        index_metadata.index_config.requires_encryption(),
+    )?;
     let split_file = PathBuf::from(format!("{}.split", args.split_id));
     let split_data = index_storage.get_all(split_file.as_path()).await?;
     let (_hotcache_bytes, bundle_storage) = BundleStorage::open_from_split_data_with_owned_bytes(
@@ -21,6 +21,9 @@ use tracing::warn;
 /// Field name reserved for storing the dynamically indexed fields.
 pub const FIELD_PRESENCE_FIELD_NAME: &str = "_field_presence";
 
+/// Field name reserved for storing the path of the hidden documents indexed for `nested` fields.
+pub const NESTED_PATH_FIELD_NAME: &str = "_nested_path";
+
 pub const MINIMUM_DELETION_GRACE_PERIOD: Duration = Duration::from_secs(5 * 60); // 5mn
 const MAXIMUM_DELETION_GRACE_PERIOD: Duration = Duration::from_secs(2 * 24 * 3600); // 2 days
 
@@ -38,6 +38,11 @@ pub enum Protocol {
     Ram = 6,
     S3 = 7,
     Google = 8,
+    Hdfs = 9,
+    Http = 10,
+    Https = 11,
+    Webdav = 12,
+    Webdavs = 13,
 }
 
 impl Protocol {
@@ -51,6 +56,11 @@ impl Protocol {
             Protocol::Ram => "ram",
             Protocol::S3 => "s3",
             Protocol::Google => "gs",
+            Protocol::Hdfs => "hdfs",
+            Protocol::Http => "http",
+            Protocol::Https => "https",
+            Protocol::Webdav => "webdav",
+            Protocol::Webdavs => "webdavs",
         }
     }
 
@@ -69,6 +79,19 @@ impl Protocol {
     pub fn is_database(&self) -> bool {
         matches!(&self, Protocol::PostgreSQL)
     }
+
+    /// Returns whether the first component of the path of URIs with this protocol is an
+    /// authority, i.e. a host and optionally a port.
+    fn has_authority(&self) -> bool {
+        matches!(
+            &self,
+            Protocol::Hdfs
+                | Protocol::Http
+                | Protocol::Https
+                | Protocol::Webdav
+                | Protocol::Webdavs
+        )
+    }
 }
 
 impl Display for Protocol {
@@ -90,6 +113,11 @@ impl FromStr for Protocol {
             "ram" => Ok(Protocol::Ram),
             "s3" => Ok(Protocol::S3),
             "gs" => Ok(Protocol::Google),
+            "hdfs" => Ok(Protocol::Hdfs),
+            "http" => Ok(Protocol::Http),
+            "https" => Ok(Protocol::Https),
+            "webdav" => Ok(Protocol::Webdav),
+            "webdavs" => Ok(Protocol::Webdavs),
             _ => bail!("unknown URI protocol `{protocol}`"),
         }
     }
@@ -186,6 +214,9 @@ impl Uri {
         if protocol == Protocol::Google && path.components().count() < 2 {
             return None;
         }
+        if protocol.has_authority() && path.components().count() < 2 {
+            return None;
+        }
         let parent_path = path.parent()?;
 
         Some(Self {
@@ -214,6 +245,9 @@ impl Uri {
         if self.protocol() == Protocol::Google && path.components().count() < 2 {
             return None;
         }
+        if self.protocol().has_authority() && path.components().count() < 2 {
+            return None;
+        }
         path.file_name().map(Path::new)
     }
 
@@ -511,6 +545,26 @@ mod tests {
             Uri::for_test("gs://bucket/key").protocol(),
             Protocol::Google
         );
+        assert_eq!(
+            Uri::for_test("hdfs://namenode:8020/indexes").protocol(),
+            Protocol::Hdfs
+        );
+        assert_eq!(
+            Uri::for_test("http://localhost:8080/indexes").protocol(),
+            Protocol::Http
+        );
+        assert_eq!(
+            Uri::for_test("https://splits.example.com/indexes").protocol(),
+            Protocol::Https
+        );
+        assert_eq!(
+            Uri::for_test("webdav://localhost:8080/indexes").protocol(),
+            Protocol::Webdav
+        );
+        assert_eq!(
+            Uri::for_test("webdavs://dav.example.com/indexes").protocol(),
+            Protocol::Webdavs
+        );
         assert_eq!(
             Uri::for_test("postgres://localhost:5432/metastore").protocol(),
             Protocol::PostgreSQL
@@ -658,6 +712,18 @@ mod tests {
             Uri::for_test("gs://bucket/foo/bar/").parent().unwrap(),
             "gs://bucket/foo"
         );
+        assert!(Uri::for_test("hdfs://namenode:8020").parent().is_none());
+        assert!(Uri::for_test("https://host/").parent().is_none());
+        assert_eq!(
+            Uri::for_test("hdfs://namenode:8020/foo").parent().unwrap(),
+            "hdfs://namenode:8020"
+        );
+        assert_eq!(
+            Uri::for_test("webdav://host:8080/foo/bar")
+                .parent()
+                .unwrap(),
+            "webdav://host:8080/foo"
+        );
     }
 
     #[test]
@@ -724,6 +790,11 @@ mod tests {
             Uri::for_test("gs://bucket/foo/").file_name().unwrap(),
             Path::new("foo"),
         );
+        assert!(Uri::for_test("http://host:8080").file_name().is_none());
+        assert_eq!(
+            Uri::for_test("http://host:8080/foo").file_name().unwrap(),
+            Path::new("foo"),
+        );
     }
 
     #[test]
@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod rollup_policy;
 pub(crate) mod serialize;
+mod tiering_policy;
 
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
@@ -24,12 +26,15 @@ use bytesize::ByteSize;
 use chrono::Utc;
 use cron::Schedule;
 use humantime::parse_duration;
+use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
 use quickwit_doc_mapper::{DocMapper, DocMapperBuilder, DocMapping};
 use quickwit_proto::types::IndexId;
+pub use rollup_policy::{RollupAggregation, RollupMetric, RollupPolicy, ROLLUP_DOC_COUNT_FIELD};
 use serde::{Deserialize, Serialize};
 pub use serialize::{load_index_config_from_user_config, load_index_config_update};
 use siphasher::sip::SipHasher;
+pub use tiering_policy::{StorageTier, TieringPolicy, HOT_STORAGE_TIER_NAME};
 use tracing::warn;
 
 use crate::index_config::serialize::VersionedIndexConfig;
@@ -93,6 +98,26 @@ impl Default for IndexingResources {
     }
 }
 
+/// Layout of the stored fields in the split files.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DocStoreLayout {
+    /// Documents are stored row by row: fetching a document decompresses all its fields.
+    #[default]
+    Row,
+    /// Instead of the row doc store, the values of each stored field are written in a dedicated
+    /// doc store, so that fetching a subset of the fields only reads the corresponding columns.
+    Columnar,
+}
+
+impl DocStoreLayout {
+    pub fn is_row(&self) -> bool {
+        *self == DocStoreLayout::Row
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Hash, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct IndexingSettings {
@@ -105,6 +130,8 @@ pub struct IndexingSettings {
     #[schema(default = 1_000_000)]
     #[serde(default = "IndexingSettings::default_docstore_blocksize")]
     pub docstore_blocksize: usize,
+    #[serde(default)]
+    pub docstore_layout: DocStoreLayout,
     /// The merge policy aims to eventually produce mature splits that have a larger size but
     /// are within close range of `split_num_docs_target`.
     ///
@@ -154,6 +181,7 @@ impl Default for IndexingSettings {
             commit_timeout_secs: Self::default_commit_timeout_secs(),
             docstore_blocksize: Self::default_docstore_blocksize(),
             docstore_compression_level: Self::default_docstore_compression_level(),
+            docstore_layout: DocStoreLayout::default(),
             split_num_docs_target: Self::default_split_num_docs_target(),
             merge_policy: MergePolicyConfig::default(),
             resources: IndexingResources::default(),
@@ -228,9 +256,33 @@ impl RetentionPolicy {
     }
 }
 
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptionSettings {
+    /// ID of the master key, looked up in the key provider of the nodes, used to encrypt the data
+    /// keys of the split files of the index.
+    pub key_id: String,
+    /// Whether the split files of the index that are not encrypted are rejected on read instead
+    /// of being returned as-is.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub require_encryption: bool,
+}
+
+impl EncryptionSettings {
+    pub(super) fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            !self.key_id.is_empty() && self.key_id.len() <= u8::MAX as usize,
+            "encryption key ID must be between 1 and {} bytes long",
+            u8::MAX
+        );
+        Ok(())
+    }
+}
+
 /// Prepends an `@` char at the start of the cron expression if necessary:
 /// `hourly` -> `@hourly`
-fn prepend_at_char(schedule: &str) -> String {
+pub(crate) fn prepend_at_char(schedule: &str) -> String {
     let trimmed_schedule = schedule.trim();
 
     if !trimmed_schedule.is_empty()
@@ -253,6 +305,9 @@ pub struct IndexConfig {
     pub indexing_settings: IndexingSettings,
     pub search_settings: SearchSettings,
     pub retention_policy_opt: Option<RetentionPolicy>,
+    pub rollup_policy_opt: Option<RollupPolicy>,
+    pub tiering_policy_opt: Option<TieringPolicy>,
+    pub encryption_settings_opt: Option<EncryptionSettings>,
 }
 
 impl IndexConfig {
@@ -278,6 +333,21 @@ impl IndexConfig {
         self.indexing_params_fingerprint() == other.indexing_params_fingerprint()
     }
 
+    /// Returns the ID of the master key used to encrypt the splits of the index, if any.
+    pub fn encryption_key_id(&self) -> Option<&str> {
+        self.encryption_settings_opt
+            .as_ref()
+            .map(|encryption_settings| encryption_settings.key_id.as_str())
+    }
+
+    /// Returns whether the split files of the index must be encrypted, in which case the split
+    /// files written in clear are rejected on read.
+    pub fn requires_encryption(&self) -> bool {
+        self.encryption_settings_opt
+            .as_ref()
+            .is_some_and(|encryption_settings| encryption_settings.require_encryption)
+    }
+
     #[cfg(any(test, feature = "testsuite"))]
     pub fn for_test(index_id: &str, index_uri: &str) -> Self {
         let index_uri = Uri::from_str(index_uri).unwrap();
@@ -361,6 +431,9 @@ impl IndexConfig {
             indexing_settings,
             search_settings,
             retention_policy_opt: Default::default(),
+            rollup_policy_opt: Default::default(),
+            tiering_policy_opt: Default::default(),
+            encryption_settings_opt: Default::default(),
         }
     }
 }
@@ -465,6 +538,9 @@ impl crate::TestableForRegression for IndexConfig {
             doc_mapping,
             indexing_settings,
             retention_policy_opt: retention_policy,
+            rollup_policy_opt: None,
+            tiering_policy_opt: None,
+            encryption_settings_opt: None,
             search_settings,
         }
     }
@@ -582,6 +658,10 @@ mod tests {
             "timestamp"
         );
         assert_eq!(index_config.indexing_settings.commit_timeout_secs, 61);
+        assert_eq!(
+            index_config.indexing_settings.docstore_layout,
+            DocStoreLayout::Columnar
+        );
         assert_eq!(
             index_config.indexing_settings.merge_policy,
             MergePolicyConfig::StableLog(crate::StableLogMergePolicyConfig {
@@ -0,0 +1,322 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, ensure, Context};
+use chrono::Utc;
+use cron::Schedule;
+use humantime::parse_duration;
+use quickwit_proto::types::IndexId;
+use serde::{Deserialize, Serialize};
+
+use super::prepend_at_char;
+use crate::validate_identifier;
+
+/// Name of the field holding the number of raw documents summarized by a rolled-up document.
+pub const ROLLUP_DOC_COUNT_FIELD: &str = "doc_count";
+
+/// A rollup policy periodically summarizes the documents of an index older than `rollup_after`
+/// into a target index: documents are grouped by time bucket of width `interval` and by the
+/// values of the `group_by` fields, and each group produces one document holding the configured
+/// metrics.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RollupPolicy {
+    /// ID of the index receiving the rolled-up documents. The index is created with a generated
+    /// doc mapping if it does not exist.
+    #[schema(value_type = String)]
+    pub target_index_id: IndexId,
+
+    /// Minimum age of the documents rolled up, expressed in a human-friendly way (`1 day`,
+    /// `2 weeks`, ...). Documents arriving later than this delay are not rolled up.
+    pub rollup_after: String,
+
+    /// Width of the time buckets, expressed in a human-friendly way (`1 hour`, `1 day`, ...).
+    pub interval: String,
+
+    /// Fast fields whose values are used to group documents within a time bucket.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<String>,
+
+    /// Metrics computed for each group of documents.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<RollupMetric>,
+
+    /// Whether the splits of the index are marked for deletion once all their documents have been
+    /// rolled up.
+    #[serde(default)]
+    pub delete_raw_splits: bool,
+
+    /// Defines the frequency at which the rollup policy is evaluated and applied, expressed in a
+    /// human-friendly way (`hourly`, `daily`, ...) or as a cron expression (`0 0 * * * *`,
+    /// `0 0 0 * * *`).
+    #[serde(default = "RollupPolicy::default_schedule")]
+    #[serde(rename = "schedule")]
+    pub evaluation_schedule: String,
+}
+
+/// Metrics computed over a numeric fast field for each group of rolled-up documents.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RollupMetric {
+    pub field: String,
+    pub aggregations: Vec<RollupAggregation>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupAggregation {
+    Min,
+    Max,
+    Sum,
+    Avg,
+    ValueCount,
+}
+
+impl RollupAggregation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupAggregation::Min => "min",
+            RollupAggregation::Max => "max",
+            RollupAggregation::Sum => "sum",
+            RollupAggregation::Avg => "avg",
+            RollupAggregation::ValueCount => "value_count",
+        }
+    }
+}
+
+impl RollupMetric {
+    /// Returns the name of the field of the target index holding the `aggregation` of this
+    /// metric, for instance `latency_ms_max`.
+    pub fn target_field_name(&self, aggregation: RollupAggregation) -> String {
+        format!(
+            "{}_{}",
+            RollupPolicy::target_field_name(&self.field),
+            aggregation.as_str()
+        )
+    }
+}
+
+impl RollupPolicy {
+    pub fn default_schedule() -> String {
+        "hourly".to_string()
+    }
+
+    /// Returns the name of the field of the target index holding the values of the field
+    /// `field_name` of the rolled-up index. Object fields are flattened: `resource.service`
+    /// becomes `resource_service`.
+    pub fn target_field_name(field_name: &str) -> String {
+        field_name.replace("\\.", ".").replace('.', "_")
+    }
+
+    pub fn rollup_after(&self) -> anyhow::Result<Duration> {
+        parse_duration(&self.rollup_after)
+            .with_context(|| format!("failed to parse rollup delay `{}`", self.rollup_after))
+    }
+
+    pub fn interval(&self) -> anyhow::Result<Duration> {
+        let interval = parse_duration(&self.interval)
+            .with_context(|| format!("failed to parse rollup interval `{}`", self.interval))?;
+        ensure!(
+            interval.as_secs() > 0 && interval.subsec_nanos() == 0,
+            "rollup interval `{}` must be a whole number of seconds",
+            self.interval
+        );
+        Ok(interval)
+    }
+
+    pub fn evaluation_schedule(&self) -> anyhow::Result<Schedule> {
+        let evaluation_schedule = prepend_at_char(&self.evaluation_schedule);
+
+        Schedule::from_str(&evaluation_schedule).with_context(|| {
+            format!(
+                "failed to parse rollup evaluation schedule `{}`",
+                self.evaluation_schedule
+            )
+        })
+    }
+
+    pub fn duration_until_next_evaluation(&self) -> anyhow::Result<Duration> {
+        let schedule = self.evaluation_schedule()?;
+        let future_date = schedule
+            .upcoming(Utc)
+            .next()
+            .expect("Failed to obtain next evaluation date.");
+        let duration = (future_date - Utc::now())
+            .to_std()
+            .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        Ok(duration)
+    }
+
+    /// Returns the names of the fields of the target index, excluding its timestamp field.
+    pub fn target_field_names(&self) -> Vec<String> {
+        let mut target_field_names = Vec::new();
+
+        for field_name in &self.group_by {
+            target_field_names.push(Self::target_field_name(field_name));
+        }
+        target_field_names.push(ROLLUP_DOC_COUNT_FIELD.to_string());
+
+        for metric in &self.metrics {
+            for aggregation in &metric.aggregations {
+                target_field_names.push(metric.target_field_name(*aggregation));
+            }
+        }
+        target_field_names
+    }
+
+    pub(super) fn validate(&self, index_id: &str) -> anyhow::Result<()> {
+        validate_identifier("index", &self.target_index_id)?;
+        ensure!(
+            self.target_index_id != index_id,
+            "rollup target index must be different from the rolled-up index `{index_id}`"
+        );
+        let rollup_after = self.rollup_after()?;
+        let interval = self.interval()?;
+        ensure!(
+            rollup_after >= interval,
+            "rollup delay `{}` must be greater than or equal to the rollup interval `{}`",
+            self.rollup_after,
+            self.interval
+        );
+        self.evaluation_schedule()?;
+
+        for metric in &self.metrics {
+            ensure!(
+                !metric.aggregations.is_empty(),
+                "rollup metric on field `{}` must define at least one aggregation",
+                metric.field
+            );
+        }
+        let mut target_field_names = HashSet::new();
+
+        for target_field_name in self.target_field_names() {
+            if !target_field_names.insert(target_field_name.clone()) {
+                bail!("rollup policy defines field `{target_field_name}` more than once");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollup_policy_for_test() -> RollupPolicy {
+        let rollup_policy_yaml = r#"
+            target_index_id: logs-hourly
+            rollup_after: 7 days
+            interval: 1 hour
+            group_by: [service_name, resource.host]
+            metrics:
+              - field: latency_ms
+                aggregations: [min, max, value_count]
+            delete_raw_splits: true
+        "#;
+        serde_yaml::from_str(rollup_policy_yaml).unwrap()
+    }
+
+    #[test]
+    fn test_rollup_policy_deserialization() {
+        let rollup_policy = rollup_policy_for_test();
+        assert_eq!(rollup_policy.target_index_id, "logs-hourly");
+        assert_eq!(
+            rollup_policy.rollup_after().unwrap(),
+            Duration::from_secs(7 * 24 * 3600)
+        );
+        assert_eq!(rollup_policy.interval().unwrap(), Duration::from_secs(3600));
+        assert_eq!(rollup_policy.group_by, ["service_name", "resource.host"]);
+        assert_eq!(
+            rollup_policy.metrics,
+            [RollupMetric {
+                field: "latency_ms".to_string(),
+                aggregations: vec![
+                    RollupAggregation::Min,
+                    RollupAggregation::Max,
+                    RollupAggregation::ValueCount
+                ],
+            }]
+        );
+        assert!(rollup_policy.delete_raw_splits);
+        assert_eq!(rollup_policy.evaluation_schedule, "hourly");
+
+        let rollup_policy_yaml = serde_yaml::to_string(&rollup_policy).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<RollupPolicy>(&rollup_policy_yaml).unwrap(),
+            rollup_policy
+        );
+    }
+
+    #[test]
+    fn test_rollup_policy_target_field_names() {
+        let rollup_policy = rollup_policy_for_test();
+        assert_eq!(
+            rollup_policy.target_field_names(),
+            [
+                "service_name",
+                "resource_host",
+                "doc_count",
+                "latency_ms_min",
+                "latency_ms_max",
+                "latency_ms_value_count"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rollup_policy_validate() {
+        let rollup_policy = rollup_policy_for_test();
+        rollup_policy.validate("logs").unwrap();
+
+        let error = rollup_policy.validate("logs-hourly").unwrap_err();
+        assert!(error.to_string().contains("must be different"));
+        {
+            let mut rollup_policy = rollup_policy.clone();
+            rollup_policy.interval = "500ms".to_string();
+            let error = rollup_policy.validate("logs").unwrap_err();
+            assert!(error.to_string().contains("whole number of seconds"));
+        }
+        {
+            let mut rollup_policy = rollup_policy.clone();
+            rollup_policy.rollup_after = "30 minutes".to_string();
+            let error = rollup_policy.validate("logs").unwrap_err();
+            assert!(error.to_string().contains("must be greater than or equal"));
+        }
+        {
+            let mut rollup_policy = rollup_policy.clone();
+            rollup_policy.evaluation_schedule = "foo".to_string();
+            rollup_policy.validate("logs").unwrap_err();
+        }
+        {
+            let mut rollup_policy = rollup_policy.clone();
+            rollup_policy.group_by.push("doc_count".to_string());
+            let error = rollup_policy.validate("logs").unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "rollup policy defines field `doc_count` more than once"
+            );
+        }
+        {
+            let mut rollup_policy = rollup_policy.clone();
+            rollup_policy.metrics[0].aggregations.clear();
+            rollup_policy.validate("logs").unwrap_err();
+        }
+    }
+}
@@ -23,8 +23,8 @@ use tracing::info;
 
 use super::validate_index_config;
 use crate::{
-    validate_identifier, ConfigFormat, DocMapping, IndexConfig, IndexingSettings, RetentionPolicy,
-    SearchSettings,
+    validate_identifier, ConfigFormat, DocMapping, EncryptionSettings, IndexConfig,
+    IndexingSettings, RetentionPolicy, RollupPolicy, SearchSettings, TieringPolicy,
 };
 
 /// Alias for the latest serialization format.
@@ -96,6 +96,10 @@ pub fn load_index_config_update(
         current_index_config.index_uri,
         new_index_config.index_uri
     );
+    ensure!(
+        current_index_config.encryption_settings_opt == new_index_config.encryption_settings_opt,
+        "`encryption` cannot be updated"
+    );
 
     // verify the new mapping is coherent
     let doc_mapper_builder = DocMapperBuilder {
@@ -191,6 +195,9 @@ impl IndexConfigForSerialization {
             indexing_settings: self.indexing_settings,
             search_settings: self.search_settings,
             retention_policy_opt: self.retention_policy_opt,
+            rollup_policy_opt: self.rollup_policy_opt,
+            tiering_policy_opt: self.tiering_policy_opt,
+            encryption_settings_opt: self.encryption_settings_opt,
         };
         validate_index_config(
             &index_config.doc_mapping,
@@ -198,6 +205,20 @@ impl IndexConfigForSerialization {
             &index_config.search_settings,
             &index_config.retention_policy_opt,
         )?;
+        if let Some(rollup_policy) = &index_config.rollup_policy_opt {
+            rollup_policy.validate(&index_config.index_id)?;
+
+            ensure!(
+                index_config.doc_mapping.timestamp_field.is_some(),
+                "rollup policy requires a timestamp field, but doc mapping does not declare one"
+            );
+        }
+        if let Some(tiering_policy) = &index_config.tiering_policy_opt {
+            tiering_policy.validate(&index_config.index_uri)?;
+        }
+        if let Some(encryption_settings) = &index_config.encryption_settings_opt {
+            encryption_settings.validate()?;
+        }
         Ok(index_config)
     }
 }
@@ -239,6 +260,18 @@ pub struct IndexConfigV0_8 {
     #[serde(rename = "retention")]
     #[serde(default)]
     pub retention_policy_opt: Option<RetentionPolicy>,
+    #[serde(rename = "rollup")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rollup_policy_opt: Option<RollupPolicy>,
+    #[serde(rename = "tiering")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tiering_policy_opt: Option<TieringPolicy>,
+    #[serde(rename = "encryption")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_settings_opt: Option<EncryptionSettings>,
 }
 
 impl From<IndexConfig> for IndexConfigV0_8 {
@@ -250,6 +283,9 @@ impl From<IndexConfig> for IndexConfigV0_8 {
             indexing_settings: index_config.indexing_settings,
             search_settings: index_config.search_settings,
             retention_policy_opt: index_config.retention_policy_opt,
+            rollup_policy_opt: index_config.rollup_policy_opt,
+            tiering_policy_opt: index_config.tiering_policy_opt,
+            encryption_settings_opt: index_config.encryption_settings_opt,
         }
     }
 }
@@ -317,6 +353,50 @@ mod test {
         assert!(validation_err.contains("retention policy requires a timestamp field"));
     }
 
+    #[test]
+    fn test_validate_rollup_policy() {
+        let mut invalid_index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        invalid_index_config.rollup_policy_opt = Some(RollupPolicy {
+            target_index_id: "hdfs-logs-hourly".to_string(),
+            rollup_after: "7 days".to_string(),
+            interval: "1 hour".to_string(),
+            group_by: Vec::new(),
+            metrics: Vec::new(),
+            delete_raw_splits: false,
+            evaluation_schedule: "hourly".to_string(),
+        });
+        let validation_err = invalid_index_config
+            .build_and_validate(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("rollup policy requires a timestamp field"));
+    }
+
+    #[test]
+    fn test_validate_encryption_settings() {
+        let mut index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        index_config.encryption_settings_opt = Some(EncryptionSettings {
+            key_id: "".to_string(),
+            require_encryption: false,
+        });
+        let validation_err = index_config
+            .clone()
+            .build_and_validate(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("encryption key ID must be between 1 and 255 bytes long"));
+
+        index_config.encryption_settings_opt = Some(EncryptionSettings {
+            key_id: "logs-key".to_string(),
+            require_encryption: true,
+        });
+        let index_config = index_config.build_and_validate(None).unwrap();
+        assert_eq!(index_config.encryption_key_id(), Some("logs-key"));
+        assert!(index_config.requires_encryption());
+    }
+
     #[test]
     fn test_minimal_index_config_missing_root_uri_no_default_uri() {
         let config_yaml = r#"
@@ -449,6 +529,44 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_update_encryption_settings() {
+        let original_config_yaml = r#"
+            version: 0.8
+            index_id: hdfs-logs
+            doc_mapping: {}
+            encryption:
+                key_id: logs-key
+        "#;
+        let original_config: IndexConfig = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            original_config_yaml.as_bytes(),
+            &Uri::for_test("s3://mybucket"),
+        )
+        .unwrap();
+        assert_eq!(
+            original_config.encryption_settings_opt,
+            Some(EncryptionSettings {
+                key_id: "logs-key".to_string(),
+                require_encryption: false,
+            })
+        );
+        let updated_config_yaml = r#"
+            version: 0.8
+            index_id: hdfs-logs
+            doc_mapping: {}
+            encryption:
+                key_id: other-key
+        "#;
+        let load_error = load_index_config_update(
+            ConfigFormat::Yaml,
+            updated_config_yaml.as_bytes(),
+            &original_config,
+        )
+        .unwrap_err();
+        assert!(format!("{:?}", load_error).contains("`encryption` cannot be updated"));
+    }
+
     #[test]
     fn test_update_reset_defaults() {
         let original_config_yaml = r#"
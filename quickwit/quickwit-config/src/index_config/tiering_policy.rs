@@ -0,0 +1,272 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, ensure, Context};
+use chrono::Utc;
+use cron::Schedule;
+use humantime::parse_duration;
+use quickwit_common::uri::Uri;
+use serde::{Deserialize, Serialize};
+
+use super::prepend_at_char;
+use crate::validate_identifier;
+
+/// Name of the implicit tier holding the splits stored under the index URI.
+pub const HOT_STORAGE_TIER_NAME: &str = "hot";
+
+/// A tiering policy periodically moves the splits of an index older than a given age from the
+/// index URI to cheaper storage tiers.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TieringPolicy {
+    /// Storage tiers, ordered from the warmest to the coldest.
+    pub tiers: Vec<StorageTier>,
+
+    /// Defines the frequency at which the tiering policy is evaluated and applied, expressed in a
+    /// human-friendly way (`hourly`, `daily`, ...) or as a cron expression (`0 0 * * * *`,
+    /// `0 0 0 * * *`).
+    #[serde(default = "TieringPolicy::default_schedule")]
+    #[serde(rename = "schedule")]
+    pub evaluation_schedule: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StorageTier {
+    /// Name of the tier, reported in the index description.
+    pub name: String,
+
+    /// URI of the storage receiving the splits of the tier.
+    #[schema(value_type = String)]
+    pub storage_uri: Uri,
+
+    /// Minimum age of the splits moved to this tier, expressed in a human-friendly way
+    /// (`30 days`, `6 months`, ...). The age of a split is computed from the end of its time
+    /// range if the index has a timestamp field, from its creation date otherwise.
+    pub move_after: String,
+}
+
+impl StorageTier {
+    pub fn move_after(&self) -> anyhow::Result<Duration> {
+        parse_duration(&self.move_after).with_context(|| {
+            format!(
+                "failed to parse delay `{}` of storage tier `{}`",
+                self.move_after, self.name
+            )
+        })
+    }
+}
+
+impl TieringPolicy {
+    pub fn default_schedule() -> String {
+        "hourly".to_string()
+    }
+
+    /// Returns the position of the tier whose storage URI is `storage_uri`, starting at 1 for the
+    /// warmest tier. The hot tier, i.e. the index URI, has rank 0.
+    pub fn tier_rank(&self, storage_uri_opt: Option<&Uri>) -> Option<usize> {
+        let Some(storage_uri) = storage_uri_opt else {
+            return Some(0);
+        };
+        self.tiers
+            .iter()
+            .position(|tier| tier.storage_uri == *storage_uri)
+            .map(|position| position + 1)
+    }
+
+    /// Returns the name of the tier whose storage URI is `storage_uri`, if any.
+    pub fn tier_name(&self, storage_uri_opt: Option<&Uri>) -> Option<&str> {
+        let Some(storage_uri) = storage_uri_opt else {
+            return Some(HOT_STORAGE_TIER_NAME);
+        };
+        self.tiers
+            .iter()
+            .find(|tier| tier.storage_uri == *storage_uri)
+            .map(|tier| tier.name.as_str())
+    }
+
+    pub fn evaluation_schedule(&self) -> anyhow::Result<Schedule> {
+        let evaluation_schedule = prepend_at_char(&self.evaluation_schedule);
+
+        Schedule::from_str(&evaluation_schedule).with_context(|| {
+            format!(
+                "failed to parse tiering evaluation schedule `{}`",
+                self.evaluation_schedule
+            )
+        })
+    }
+
+    pub fn duration_until_next_evaluation(&self) -> anyhow::Result<Duration> {
+        let schedule = self.evaluation_schedule()?;
+        let future_date = schedule
+            .upcoming(Utc)
+            .next()
+            .expect("Failed to obtain next evaluation date.");
+        let duration = (future_date - Utc::now())
+            .to_std()
+            .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        Ok(duration)
+    }
+
+    pub(super) fn validate(&self, index_uri: &Uri) -> anyhow::Result<()> {
+        ensure!(
+            !self.tiers.is_empty(),
+            "tiering policy must define at least one storage tier"
+        );
+        let mut tier_names = HashSet::new();
+        let mut storage_uris = HashSet::new();
+        let mut previous_move_after_opt: Option<Duration> = None;
+
+        for tier in &self.tiers {
+            validate_identifier("storage tier", &tier.name)?;
+
+            if tier.name == HOT_STORAGE_TIER_NAME {
+                bail!("storage tier name `{HOT_STORAGE_TIER_NAME}` is reserved");
+            }
+            if !tier_names.insert(&tier.name) {
+                bail!(
+                    "tiering policy defines storage tier `{}` more than once",
+                    tier.name
+                );
+            }
+            ensure!(
+                tier.storage_uri != *index_uri,
+                "URI of storage tier `{}` must be different from the index URI",
+                tier.name
+            );
+            if !storage_uris.insert(&tier.storage_uri) {
+                bail!(
+                    "tiering policy defines storage URI `{}` more than once",
+                    tier.storage_uri
+                );
+            }
+            let move_after = tier.move_after()?;
+
+            if let Some(previous_move_after) = previous_move_after_opt {
+                ensure!(
+                    move_after > previous_move_after,
+                    "storage tiers must be ordered by increasing delay, but tier `{}` is moved \
+                     after `{}`",
+                    tier.name,
+                    tier.move_after
+                );
+            }
+            previous_move_after_opt = Some(move_after);
+        }
+        self.evaluation_schedule()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiering_policy_for_test() -> TieringPolicy {
+        let tiering_policy_yaml = r#"
+            tiers:
+              - name: warm
+                storage_uri: s3://warm-bucket/indexes/logs
+                move_after: 7 days
+              - name: cold
+                storage_uri: s3://cold-bucket/indexes/logs
+                move_after: 90 days
+        "#;
+        serde_yaml::from_str(tiering_policy_yaml).unwrap()
+    }
+
+    #[test]
+    fn test_tiering_policy_deserialization() {
+        let tiering_policy = tiering_policy_for_test();
+        assert_eq!(tiering_policy.tiers.len(), 2);
+        assert_eq!(tiering_policy.tiers[0].name, "warm");
+        assert_eq!(
+            tiering_policy.tiers[0].storage_uri,
+            "s3://warm-bucket/indexes/logs"
+        );
+        assert_eq!(
+            tiering_policy.tiers[1].move_after().unwrap(),
+            Duration::from_secs(90 * 24 * 3600)
+        );
+        assert_eq!(tiering_policy.evaluation_schedule, "hourly");
+
+        let tiering_policy_yaml = serde_yaml::to_string(&tiering_policy).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<TieringPolicy>(&tiering_policy_yaml).unwrap(),
+            tiering_policy
+        );
+    }
+
+    #[test]
+    fn test_tiering_policy_tier_rank_and_name() {
+        let tiering_policy = tiering_policy_for_test();
+        let cold_uri = Uri::for_test("s3://cold-bucket/indexes/logs");
+        let unknown_uri = Uri::for_test("s3://other-bucket/indexes/logs");
+
+        assert_eq!(tiering_policy.tier_rank(None), Some(0));
+        assert_eq!(tiering_policy.tier_rank(Some(&cold_uri)), Some(2));
+        assert_eq!(tiering_policy.tier_rank(Some(&unknown_uri)), None);
+
+        assert_eq!(tiering_policy.tier_name(None), Some("hot"));
+        assert_eq!(tiering_policy.tier_name(Some(&cold_uri)), Some("cold"));
+        assert_eq!(tiering_policy.tier_name(Some(&unknown_uri)), None);
+    }
+
+    #[test]
+    fn test_tiering_policy_validate() {
+        let index_uri = Uri::for_test("s3://hot-bucket/indexes/logs");
+        let tiering_policy = tiering_policy_for_test();
+        tiering_policy.validate(&index_uri).unwrap();
+        {
+            let mut tiering_policy = tiering_policy.clone();
+            tiering_policy.tiers.clear();
+            tiering_policy.validate(&index_uri).unwrap_err();
+        }
+        {
+            let mut tiering_policy = tiering_policy.clone();
+            tiering_policy.tiers[1].name = "hot".to_string();
+            let error = tiering_policy.validate(&index_uri).unwrap_err();
+            assert_eq!(error.to_string(), "storage tier name `hot` is reserved");
+        }
+        {
+            let mut tiering_policy = tiering_policy.clone();
+            tiering_policy.tiers[1].name = "warm".to_string();
+            let error = tiering_policy.validate(&index_uri).unwrap_err();
+            assert!(error.to_string().contains("more than once"));
+        }
+        {
+            let mut tiering_policy = tiering_policy.clone();
+            tiering_policy.tiers[0].storage_uri = index_uri.clone();
+            let error = tiering_policy.validate(&index_uri).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("must be different from the index URI"));
+        }
+        {
+            let mut tiering_policy = tiering_policy.clone();
+            tiering_policy.tiers[1].move_after = "1 day".to_string();
+            let error = tiering_policy.validate(&index_uri).unwrap_err();
+            assert!(error.to_string().contains("increasing delay"));
+        }
+        {
+            let mut tiering_policy = tiering_policy.clone();
+            tiering_policy.evaluation_schedule = "foo".to_string();
+            tiering_policy.validate(&index_uri).unwrap_err();
+        }
+    }
+}
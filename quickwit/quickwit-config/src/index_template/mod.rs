@@ -74,6 +74,12 @@ impl IndexTemplate {
             indexing_settings: self.indexing_settings.clone(),
             search_settings: self.search_settings.clone(),
             retention_policy_opt: self.retention_policy_opt.clone(),
+            // Templates may match several indexes, which cannot be rolled up into the same
+            // target index.
+            rollup_policy_opt: None,
+            // Storage tier URIs must not be shared by the indexes matching the template.
+            tiering_policy_opt: None,
+            encryption_settings_opt: None,
         };
         Ok(index_config)
     }
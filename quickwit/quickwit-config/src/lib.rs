@@ -32,6 +32,7 @@ mod index_config;
 mod index_template;
 pub mod merge_policy_config;
 mod metastore_config;
+mod monitor_config;
 mod node_config;
 mod qw_env_vars;
 pub mod service;
@@ -44,8 +45,10 @@ pub use cluster_config::ClusterConfig;
 // See #2048
 use index_config::serialize::{IndexConfigV0_8, VersionedIndexConfig};
 pub use index_config::{
-    build_doc_mapper, load_index_config_from_user_config, load_index_config_update, IndexConfig,
-    IndexingResources, IndexingSettings, RetentionPolicy, SearchSettings,
+    build_doc_mapper, load_index_config_from_user_config, load_index_config_update, DocStoreLayout,
+    EncryptionSettings, IndexConfig, IndexingResources, IndexingSettings, RetentionPolicy,
+    RollupAggregation, RollupMetric, RollupPolicy, SearchSettings, StorageTier, TieringPolicy,
+    HOT_STORAGE_TIER_NAME, ROLLUP_DOC_COUNT_FIELD,
 };
 pub use quickwit_doc_mapper::DocMapping;
 use serde::de::DeserializeOwned;
@@ -57,8 +60,9 @@ pub use source_config::{
     load_source_config_from_user_config, load_source_config_update, FileSourceMessageType,
     FileSourceNotification, FileSourceParams, FileSourceSqs, KafkaSourceParams,
     KinesisSourceParams, PubSubSourceParams, PulsarSourceAuth, PulsarSourceParams,
-    RegionOrEndpoint, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
-    VecSourceParams, VoidSourceParams, CLI_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_V2_SOURCE_ID,
+    RegionOrEndpoint, ReindexSourceParams, SourceConfig, SourceInputFormat, SourceParams,
+    TransformConfig, VecSourceParams, VoidSourceParams, CLI_SOURCE_ID, INGEST_API_SOURCE_ID,
+    INGEST_V2_SOURCE_ID, ROLLUP_SOURCE_ID,
 };
 use tracing::warn;
 
@@ -70,14 +74,21 @@ use crate::merge_policy_config::{
 pub use crate::metastore_config::{
     MetastoreBackend, MetastoreConfig, MetastoreConfigs, PostgresMetastoreConfig,
 };
+use crate::monitor_config::MonitorConfigV0_9;
+pub use crate::monitor_config::{
+    KafkaActionParams, MonitorAction, MonitorCondition, MonitorConfig, MonitorId,
+    ThresholdOperator, VersionedMonitorConfig, WebhookActionParams,
+};
 pub use crate::node_config::{
-    IndexerConfig, IngestApiConfig, JaegerConfig, NodeConfig, SearcherConfig, SplitCacheLimits,
-    StorageTimeoutPolicy, DEFAULT_QW_CONFIG_PATH,
+    EncryptionConfig, IndexerConfig, IngestApiConfig, JaegerConfig, LambdaLeafSearchConfig,
+    NodeConfig, SearcherConfig, SplitCacheLimits, StorageTimeoutPolicy, TelemetryConfig,
+    TelemetrySinkConfig, DEFAULT_QW_CONFIG_PATH,
 };
 use crate::source_config::serialize::{SourceConfigV0_7, SourceConfigV0_8, VersionedSourceConfig};
 pub use crate::storage_config::{
-    AzureStorageConfig, FileStorageConfig, GoogleCloudStorageConfig, RamStorageConfig,
-    S3StorageConfig, StorageBackend, StorageBackendFlavor, StorageConfig, StorageConfigs,
+    AzureStorageConfig, FileStorageConfig, GoogleCloudStorageConfig, HdfsStorageConfig,
+    HttpStorageConfig, RamStorageConfig, S3StorageConfig, StorageBackend, StorageBackendFlavor,
+    StorageConfig, StorageConfigs, WebdavStorageConfig,
 };
 
 /// Returns true if the ingest API v2 is enabled.
@@ -98,8 +109,15 @@ pub fn disable_ingest_v1() -> bool {
 #[openapi(components(schemas(
     IndexingResources,
     IndexingSettings,
+    DocStoreLayout,
     SearchSettings,
     RetentionPolicy,
+    RollupPolicy,
+    RollupMetric,
+    RollupAggregation,
+    TieringPolicy,
+    StorageTier,
+    EncryptionSettings,
     MergePolicyConfig,
     DocMapping,
     VersionedSourceConfig,
@@ -109,6 +127,13 @@ pub fn disable_ingest_v1() -> bool {
     IndexConfigV0_8,
     VersionedIndexTemplate,
     IndexTemplateV0_8,
+    VersionedMonitorConfig,
+    MonitorConfigV0_9,
+    MonitorCondition,
+    ThresholdOperator,
+    MonitorAction,
+    WebhookActionParams,
+    KafkaActionParams,
     SourceInputFormat,
     SourceParams,
     FileSourceMessageType,
@@ -121,6 +146,7 @@ pub fn disable_ingest_v1() -> bool {
     PulsarSourceParams,
     PulsarSourceAuth,
     RegionOrEndpoint,
+    ReindexSourceParams,
     ConstWriteAmplificationMergePolicyConfig,
     StableLogMergePolicyConfig,
     TransformConfig,
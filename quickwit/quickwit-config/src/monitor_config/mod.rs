@@ -0,0 +1,410 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod serialize;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, ensure, Context};
+use chrono::Utc;
+use cron::Schedule;
+use quickwit_proto::types::IndexId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+pub use serialize::{MonitorConfigV0_9, VersionedMonitorConfig};
+
+use crate::index_config::prepend_at_char;
+use crate::validate_identifier;
+
+pub type MonitorId = String;
+
+/// A monitor is a saved search periodically evaluated by the janitor against the splits newly
+/// published to an index. When the value computed by the search meets the monitor's threshold
+/// condition, the monitor's actions are triggered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "VersionedMonitorConfig")]
+#[serde(from = "VersionedMonitorConfig")]
+pub struct MonitorConfig {
+    pub monitor_id: MonitorId,
+    pub index_id: IndexId,
+    pub description: Option<String>,
+    /// Disabled monitors are kept in the metastore but never evaluated.
+    pub enabled: bool,
+    /// Query expressed in Quickwit's query language.
+    pub query: String,
+    /// Fields searched by the query when it does not target a field explicitly. When empty, the
+    /// default search fields of the index are used.
+    pub search_fields: Vec<String>,
+    /// Optional aggregation request expressed in the Elasticsearch aggregation syntax.
+    pub aggregation_opt: Option<JsonValue>,
+    /// Defines the frequency at which the monitor is evaluated, expressed in a human-friendly way
+    /// (`hourly`, `daily`, ...) or as a cron expression (`0 */5 * * * *`).
+    pub evaluation_schedule: String,
+    pub condition: MonitorCondition,
+    pub actions: Vec<MonitorAction>,
+}
+
+impl MonitorConfig {
+    pub fn evaluation_schedule(&self) -> anyhow::Result<Schedule> {
+        let evaluation_schedule = prepend_at_char(&self.evaluation_schedule);
+
+        Schedule::from_str(&evaluation_schedule).with_context(|| {
+            format!(
+                "failed to parse monitor evaluation schedule `{}`",
+                self.evaluation_schedule
+            )
+        })
+    }
+
+    pub fn duration_until_next_evaluation(&self) -> anyhow::Result<Duration> {
+        let schedule = self.evaluation_schedule()?;
+        let future_date = schedule
+            .upcoming(Utc)
+            .next()
+            .context("failed to obtain next evaluation date")?;
+        let duration = (future_date - Utc::now())
+            .to_std()
+            .map_err(|error| anyhow::anyhow!(error.to_string()))?;
+        Ok(duration)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        validate_identifier("monitor", &self.monitor_id)?;
+        validate_identifier("index", &self.index_id)?;
+
+        ensure!(!self.query.trim().is_empty(), "`query` must not be empty");
+
+        if let Some(aggregation) = &self.aggregation_opt {
+            ensure!(
+                aggregation.is_object(),
+                "`aggregation` must be a JSON object"
+            );
+        }
+        self.evaluation_schedule()?;
+        self.condition.validate()?;
+
+        if self.condition.value_path.is_some() && self.aggregation_opt.is_none() {
+            bail!("`condition.value_path` requires an aggregation to be defined");
+        }
+        ensure!(!self.actions.is_empty(), "`actions` must not be empty");
+
+        for action in &self.actions {
+            action.validate()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "testsuite"))]
+    pub fn for_test(monitor_id: &str, index_id: &str) -> Self {
+        MonitorConfig {
+            monitor_id: monitor_id.to_string(),
+            index_id: index_id.to_string(),
+            description: Some("Test description.".to_string()),
+            enabled: true,
+            query: "severity_text:ERROR".to_string(),
+            search_fields: Vec::new(),
+            aggregation_opt: None,
+            evaluation_schedule: "0 */5 * * * *".to_string(),
+            condition: MonitorCondition {
+                value_path: None,
+                operator: ThresholdOperator::Gt,
+                threshold: 0.0,
+            },
+            actions: vec![MonitorAction::Log],
+        }
+    }
+}
+
+/// Threshold condition evaluated against the value computed by a monitor's search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorCondition {
+    /// Dot-separated path of the evaluated value within the aggregation results, for instance
+    /// `max_latency.value`. When unset, the condition is evaluated against the number of
+    /// documents matching the query.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_path: Option<String>,
+    pub operator: ThresholdOperator,
+    pub threshold: f64,
+}
+
+impl MonitorCondition {
+    /// Returns whether `value` meets the condition.
+    pub fn is_met(&self, value: f64) -> bool {
+        self.operator.compare(value, self.threshold)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.threshold.is_finite(),
+            "`condition.threshold` must be a finite number"
+        );
+        if let Some(value_path) = &self.value_path {
+            ensure!(
+                !value_path.is_empty() && value_path.split('.').all(|key| !key.is_empty()),
+                "`condition.value_path` `{value_path}` is invalid"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdOperator {
+    #[serde(alias = ">")]
+    Gt,
+    #[serde(alias = ">=")]
+    Gte,
+    #[serde(alias = "<")]
+    Lt,
+    #[serde(alias = "<=")]
+    Lte,
+    #[serde(alias = "==")]
+    Eq,
+}
+
+impl ThresholdOperator {
+    fn compare(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            ThresholdOperator::Gt => value > threshold,
+            ThresholdOperator::Gte => value >= threshold,
+            ThresholdOperator::Lt => value < threshold,
+            ThresholdOperator::Lte => value <= threshold,
+            ThresholdOperator::Eq => value == threshold,
+        }
+    }
+}
+
+/// Action executed when a monitor's condition is met.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MonitorAction {
+    /// Posts the monitor's alert as a JSON payload to an HTTP endpoint.
+    Webhook(WebhookActionParams),
+    /// Produces the monitor's alert as a JSON message to a Kafka topic.
+    Kafka(KafkaActionParams),
+    /// Logs the monitor's alert on the janitor node.
+    Log,
+}
+
+impl MonitorAction {
+    pub fn action_type(&self) -> &'static str {
+        match self {
+            MonitorAction::Webhook(_) => "webhook",
+            MonitorAction::Kafka(_) => "kafka",
+            MonitorAction::Log => "log",
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            MonitorAction::Webhook(params) => {
+                let uri = http::Uri::from_str(&params.url)
+                    .with_context(|| format!("webhook URL `{}` is invalid", params.url))?;
+                ensure!(
+                    matches!(uri.scheme_str(), Some("http") | Some("https")),
+                    "webhook URL `{}` must use the `http` or `https` scheme",
+                    params.url
+                );
+            }
+            MonitorAction::Kafka(params) => {
+                ensure!(
+                    !params.topic.is_empty(),
+                    "Kafka action topic must not be empty"
+                );
+                ensure!(
+                    params.client_params.is_null() || params.client_params.is_object(),
+                    "Kafka action `client_params` must be a JSON object"
+                );
+            }
+            MonitorAction::Log => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookActionParams {
+    pub url: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaActionParams {
+    pub topic: String,
+    #[schema(value_type = Object)]
+    #[serde(default = "serde_json::Value::default")]
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub client_params: JsonValue,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_config_serde() {
+        let monitor_config_yaml = r#"
+            version: 0.9
+
+            monitor_id: test-monitor
+            index_id: test-index
+            query: "severity_text:ERROR"
+            aggregation:
+              max_latency:
+                max:
+                  field: latency_ms
+            schedule: hourly
+            condition:
+              value_path: max_latency.value
+              operator: ">="
+              threshold: 500
+            actions:
+              - type: webhook
+                url: https://hooks.example.com/alerts
+                headers:
+                  Authorization: Bearer secret
+              - type: kafka
+                topic: alerts
+                client_params:
+                  bootstrap.servers: localhost:9092
+              - type: log
+        "#;
+        let monitor_config: MonitorConfig = serde_yaml::from_str(monitor_config_yaml).unwrap();
+        assert_eq!(monitor_config.monitor_id, "test-monitor");
+        assert_eq!(monitor_config.index_id, "test-index");
+        assert!(monitor_config.enabled);
+        assert_eq!(monitor_config.query, "severity_text:ERROR");
+        assert!(monitor_config.search_fields.is_empty());
+        assert!(monitor_config.aggregation_opt.is_some());
+        assert_eq!(monitor_config.evaluation_schedule, "hourly");
+        assert_eq!(
+            monitor_config.condition,
+            MonitorCondition {
+                value_path: Some("max_latency.value".to_string()),
+                operator: ThresholdOperator::Gte,
+                threshold: 500.0,
+            }
+        );
+        assert_eq!(monitor_config.actions.len(), 3);
+        assert_eq!(
+            monitor_config.actions[0],
+            MonitorAction::Webhook(WebhookActionParams {
+                url: "https://hooks.example.com/alerts".to_string(),
+                headers: BTreeMap::from_iter([(
+                    "Authorization".to_string(),
+                    "Bearer secret".to_string()
+                )]),
+            })
+        );
+        let MonitorAction::Kafka(kafka_params) = &monitor_config.actions[1] else {
+            panic!("expected Kafka action");
+        };
+        assert_eq!(kafka_params.topic, "alerts");
+        assert_eq!(
+            kafka_params.client_params["bootstrap.servers"],
+            "localhost:9092"
+        );
+        assert_eq!(monitor_config.actions[2], MonitorAction::Log);
+        monitor_config.validate().unwrap();
+
+        let monitor_config_json = serde_json::to_string(&monitor_config).unwrap();
+        let monitor_config_deser: MonitorConfig =
+            serde_json::from_str(&monitor_config_json).unwrap();
+        assert_eq!(monitor_config, monitor_config_deser);
+    }
+
+    #[test]
+    fn test_monitor_condition_is_met() {
+        let mut condition = MonitorCondition {
+            value_path: None,
+            operator: ThresholdOperator::Gt,
+            threshold: 10.0,
+        };
+        assert!(!condition.is_met(10.0));
+        assert!(condition.is_met(11.0));
+
+        condition.operator = ThresholdOperator::Gte;
+        assert!(condition.is_met(10.0));
+        assert!(!condition.is_met(9.0));
+
+        condition.operator = ThresholdOperator::Lt;
+        assert!(condition.is_met(9.0));
+        assert!(!condition.is_met(10.0));
+
+        condition.operator = ThresholdOperator::Lte;
+        assert!(condition.is_met(10.0));
+        assert!(!condition.is_met(11.0));
+
+        condition.operator = ThresholdOperator::Eq;
+        assert!(condition.is_met(10.0));
+        assert!(!condition.is_met(11.0));
+    }
+
+    #[test]
+    fn test_monitor_config_validate() {
+        let monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.validate().unwrap();
+
+        let monitor_config = MonitorConfig::for_test("", "test-index");
+        let error = monitor_config.validate().unwrap_err();
+        assert!(error.to_string().contains("monitor ID `` is invalid"));
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.query = " ".to_string();
+        let error = monitor_config.validate().unwrap_err();
+        assert!(error.to_string().contains("`query` must not be empty"));
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.evaluation_schedule = "every full moon".to_string();
+        let error = monitor_config.validate().unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("failed to parse monitor evaluation schedule"));
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.condition.value_path = Some("max_latency.value".to_string());
+        let error = monitor_config.validate().unwrap_err();
+        assert!(error.to_string().contains("requires an aggregation"));
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.actions.clear();
+        let error = monitor_config.validate().unwrap_err();
+        assert!(error.to_string().contains("`actions` must not be empty"));
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.actions = vec![MonitorAction::Webhook(WebhookActionParams {
+            url: "ftp://hooks.example.com".to_string(),
+            headers: BTreeMap::new(),
+        })];
+        let error = monitor_config.validate().unwrap_err();
+        assert!(error.to_string().contains("`http` or `https` scheme"));
+    }
+
+    #[test]
+    fn test_monitor_config_duration_until_next_evaluation() {
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.evaluation_schedule = "hourly".to_string();
+        let duration = monitor_config.duration_until_next_evaluation().unwrap();
+        assert!(duration <= Duration::from_secs(60 * 60));
+    }
+}
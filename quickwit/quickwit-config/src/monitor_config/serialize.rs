@@ -0,0 +1,102 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_proto::types::IndexId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::{MonitorAction, MonitorCondition, MonitorConfig, MonitorId};
+
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "version")]
+pub enum VersionedMonitorConfig {
+    #[serde(rename = "0.9")]
+    V0_9(MonitorConfigV0_9),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorConfigV0_9 {
+    #[schema(value_type = String)]
+    pub monitor_id: MonitorId,
+    #[schema(value_type = String)]
+    pub index_id: IndexId,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub query: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub search_fields: Vec<String>,
+    #[schema(value_type = Object)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggregation: Option<JsonValue>,
+    pub schedule: String,
+    pub condition: MonitorCondition,
+    pub actions: Vec<MonitorAction>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl From<VersionedMonitorConfig> for MonitorConfig {
+    fn from(versioned_monitor_config: VersionedMonitorConfig) -> Self {
+        match versioned_monitor_config {
+            VersionedMonitorConfig::V0_9(v0_9) => v0_9.into(),
+        }
+    }
+}
+
+impl From<MonitorConfig> for VersionedMonitorConfig {
+    fn from(monitor_config: MonitorConfig) -> Self {
+        VersionedMonitorConfig::V0_9(monitor_config.into())
+    }
+}
+
+impl From<MonitorConfigV0_9> for MonitorConfig {
+    fn from(monitor_config_v0_9: MonitorConfigV0_9) -> Self {
+        MonitorConfig {
+            monitor_id: monitor_config_v0_9.monitor_id,
+            index_id: monitor_config_v0_9.index_id,
+            description: monitor_config_v0_9.description,
+            enabled: monitor_config_v0_9.enabled,
+            query: monitor_config_v0_9.query,
+            search_fields: monitor_config_v0_9.search_fields,
+            aggregation_opt: monitor_config_v0_9.aggregation,
+            evaluation_schedule: monitor_config_v0_9.schedule,
+            condition: monitor_config_v0_9.condition,
+            actions: monitor_config_v0_9.actions,
+        }
+    }
+}
+
+impl From<MonitorConfig> for MonitorConfigV0_9 {
+    fn from(monitor_config: MonitorConfig) -> Self {
+        MonitorConfigV0_9 {
+            monitor_id: monitor_config.monitor_id,
+            index_id: monitor_config.index_id,
+            description: monitor_config.description,
+            enabled: monitor_config.enabled,
+            query: monitor_config.query,
+            search_fields: monitor_config.search_fields,
+            aggregation: monitor_config.aggregation_opt,
+            schedule: monitor_config.evaluation_schedule,
+            condition: monitor_config.condition,
+            actions: monitor_config.actions,
+        }
+    }
+}
@@ -14,7 +14,7 @@
 
 mod serialize;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::net::SocketAddr;
 use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
@@ -201,6 +201,24 @@ impl SplitCacheLimits {
     }
 }
 
+/// Configuration of the AWS Lambda function the leaf searches are offloaded to.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LambdaLeafSearchConfig {
+    /// Name or ARN of the leaf search Lambda function.
+    pub function_name: String,
+    /// Maximum number of splits searched by a single invocation. Larger leaf search requests
+    /// are split into several concurrent invocations.
+    #[serde(default = "LambdaLeafSearchConfig::default_max_splits_per_invocation")]
+    pub max_splits_per_invocation: NonZeroU32,
+}
+
+impl LambdaLeafSearchConfig {
+    fn default_max_splits_per_invocation() -> NonZeroU32 {
+        NonZeroU32::new(10).unwrap()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct SearcherConfig {
@@ -216,11 +234,30 @@ pub struct SearcherConfig {
     // TODO document and fix if necessary.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub split_cache: Option<SplitCacheLimits>,
+    /// Capacity of the on disk cache of split footers and fast field slices, which survives
+    /// restarts. Disabled if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_slice_cache_capacity: Option<ByteSize>,
+    /// Bandwidth allotted to warming the footers of newly published splits on the searcher
+    /// owning them. Published splits are not warmed if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_split_warmup_bandwidth: Option<ByteSize>,
+    /// Offloads the leaf searches dispatched by this node to an AWS Lambda function instead of
+    /// the searchers of the cluster.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lambda_leaf_search: Option<LambdaLeafSearchConfig>,
     #[serde(default = "SearcherConfig::default_request_timeout_secs")]
     request_timeout_secs: NonZeroU64,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_timeout_policy: Option<StorageTimeoutPolicy>,
+    /// Maximum number of bytes separating two concurrent reads of a split file for them to be
+    /// merged into a single range request. Reads are not coalesced if unset.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_read_coalescing_gap: Option<ByteSize>,
+    /// Maximum size of a range request resulting from coalescing reads.
+    pub storage_read_coalescing_max_size: ByteSize,
     pub warmup_memory_budget: ByteSize,
     pub warmup_single_split_initial_allocation: ByteSize,
 }
@@ -269,8 +306,13 @@ impl Default for SearcherConfig {
             aggregation_memory_limit: ByteSize::mb(500),
             aggregation_bucket_limit: 65000,
             split_cache: None,
+            disk_slice_cache_capacity: None,
+            published_split_warmup_bandwidth: None,
+            lambda_leaf_search: None,
             request_timeout_secs: Self::default_request_timeout_secs(),
             storage_timeout_policy: None,
+            storage_read_coalescing_gap: None,
+            storage_read_coalescing_max_size: ByteSize::mb(8),
             warmup_memory_budget: ByteSize::gb(100),
             warmup_single_split_initial_allocation: ByteSize::gb(1),
         }
@@ -462,6 +504,80 @@ impl Default for JaegerConfig {
     }
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptionConfig {
+    /// Path to the keyfile holding the master keys used to encrypt and decrypt the data keys of
+    /// the split files of encrypted indexes.
+    #[serde(rename = "keyfile")]
+    #[serde(default)]
+    pub keyfile_path_opt: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// Sinks receiving the telemetry events of the node in addition to Quickwit's telemetry
+    /// server. Unlike the latter, they are not affected by `QW_DISABLE_TELEMETRY`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<TelemetrySinkConfig>,
+}
+
+impl TelemetryConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        for sink_config in &self.sinks {
+            sink_config.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Destination of the telemetry events of the node.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TelemetrySinkConfig {
+    /// Appends the telemetry payloads as JSON lines to a local file.
+    File { path: PathBuf },
+    /// Posts the telemetry payloads as JSON to an HTTP endpoint.
+    Http {
+        endpoint: String,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+    /// Counts the telemetry events in the `quickwit_telemetry_*` Prometheus metrics of the node.
+    Prometheus,
+}
+
+impl TelemetrySinkConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            TelemetrySinkConfig::File { path } => {
+                ensure!(
+                    !path.as_os_str().is_empty(),
+                    "telemetry file sink path must not be empty"
+                );
+            }
+            TelemetrySinkConfig::Http { endpoint, headers } => {
+                ensure!(
+                    endpoint.starts_with("http://") || endpoint.starts_with("https://"),
+                    "telemetry HTTP sink endpoint must be an HTTP(S) URL, got `{endpoint}`"
+                );
+                for (header_name, header_value) in headers {
+                    ensure!(
+                        http::HeaderName::from_bytes(header_name.as_bytes()).is_ok()
+                            && http::HeaderValue::from_str(header_value).is_ok(),
+                        "invalid telemetry HTTP sink header `{header_name}`"
+                    );
+                }
+            }
+            TelemetrySinkConfig::Prometheus => {}
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct NodeConfig {
     pub cluster_id: String,
@@ -484,6 +600,8 @@ pub struct NodeConfig {
     pub searcher_config: SearcherConfig,
     pub ingest_api_config: IngestApiConfig,
     pub jaeger_config: JaegerConfig,
+    pub encryption_config: EncryptionConfig,
+    pub telemetry_config: TelemetryConfig,
 }
 
 impl NodeConfig {
@@ -665,6 +783,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validate_telemetry_config() {
+        let telemetry_config: TelemetryConfig = serde_yaml::from_str(
+            r#"
+                sinks:
+                  - type: prometheus
+                  - type: http
+                    endpoint: telemetry.example.com
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            telemetry_config.validate().unwrap_err().to_string(),
+            "telemetry HTTP sink endpoint must be an HTTP(S) URL, got `telemetry.example.com`"
+        );
+
+        let telemetry_config: TelemetryConfig = serde_yaml::from_str(
+            r#"
+                sinks:
+                  - type: http
+                    endpoint: https://telemetry.example.com
+                    headers:
+                      "Invalid Header": value
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            telemetry_config.validate().unwrap_err().to_string(),
+            "invalid telemetry HTTP sink header `Invalid Header`"
+        );
+
+        let unknown_sink_error = serde_yaml::from_str::<TelemetryConfig>(
+            r#"
+                sinks:
+                  - type: kafka
+            "#,
+        )
+        .unwrap_err();
+        assert!(unknown_sink_error.to_string().contains("unknown variant"));
+    }
+
     #[test]
     fn test_grpc_config_serialization() {
         let grpc_config: GrpcConfig = serde_json::from_str(r#"{}"#).unwrap();
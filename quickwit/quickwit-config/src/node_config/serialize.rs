@@ -33,8 +33,8 @@ use crate::service::QuickwitService;
 use crate::storage_config::StorageConfigs;
 use crate::templating::render_config;
 use crate::{
-    validate_identifier, validate_node_id, ConfigFormat, IndexerConfig, IngestApiConfig,
-    JaegerConfig, MetastoreConfigs, NodeConfig, SearcherConfig,
+    validate_identifier, validate_node_id, ConfigFormat, EncryptionConfig, IndexerConfig,
+    IngestApiConfig, JaegerConfig, MetastoreConfigs, NodeConfig, SearcherConfig, TelemetryConfig,
 };
 
 pub const DEFAULT_CLUSTER_ID: &str = "quickwit-default-cluster";
@@ -208,6 +208,12 @@ struct NodeConfigBuilder {
     #[serde(rename = "jaeger")]
     #[serde(default)]
     jaeger_config: JaegerConfig,
+    #[serde(rename = "encryption")]
+    #[serde(default)]
+    encryption_config: EncryptionConfig,
+    #[serde(rename = "telemetry")]
+    #[serde(default)]
+    telemetry_config: TelemetryConfig,
 }
 
 impl NodeConfigBuilder {
@@ -293,6 +299,7 @@ impl NodeConfigBuilder {
         self.storage_configs.apply_flavors();
         self.ingest_api_config.validate()?;
         self.searcher_config.validate()?;
+        self.telemetry_config.validate()?;
 
         let gossip_interval = self
             .gossip_interval_ms
@@ -321,6 +328,8 @@ impl NodeConfigBuilder {
             searcher_config: self.searcher_config,
             ingest_api_config: self.ingest_api_config,
             jaeger_config: self.jaeger_config,
+            encryption_config: self.encryption_config,
+            telemetry_config: self.telemetry_config,
         };
 
         validate(&node_config)?;
@@ -366,6 +375,8 @@ impl Default for NodeConfigBuilder {
             searcher_config: SearcherConfig::default(),
             ingest_api_config: IngestApiConfig::default(),
             jaeger_config: JaegerConfig::default(),
+            encryption_config: EncryptionConfig::default(),
+            telemetry_config: TelemetryConfig::default(),
         }
     }
 }
@@ -459,21 +470,25 @@ pub fn node_config_for_tests_from_ports(
         searcher_config: SearcherConfig::default(),
         ingest_api_config: IngestApiConfig::default(),
         jaeger_config: JaegerConfig::default(),
+        encryption_config: EncryptionConfig::default(),
+        telemetry_config: TelemetryConfig::default(),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::env;
     use std::net::Ipv4Addr;
-    use std::num::{NonZeroU64, NonZeroUsize};
-    use std::path::Path;
+    use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
+    use std::path::{Path, PathBuf};
 
     use bytesize::ByteSize;
     use itertools::Itertools;
 
     use super::*;
     use crate::storage_config::StorageBackendFlavor;
+    use crate::{LambdaLeafSearchConfig, TelemetrySinkConfig};
 
     fn get_config_filepath(config_filename: &str) -> String {
         format!(
@@ -606,12 +621,20 @@ mod tests {
                 max_num_concurrent_split_searches: 150,
                 max_num_concurrent_split_streams: 120,
                 split_cache: None,
+                disk_slice_cache_capacity: Some(ByteSize::gb(5)),
+                published_split_warmup_bandwidth: Some(ByteSize::mb(50)),
+                lambda_leaf_search: Some(LambdaLeafSearchConfig {
+                    function_name: "quickwit-leaf-searcher".to_string(),
+                    max_splits_per_invocation: NonZeroU32::new(5).unwrap(),
+                }),
                 request_timeout_secs: NonZeroU64::new(30).unwrap(),
                 storage_timeout_policy: Some(crate::StorageTimeoutPolicy {
                     min_throughtput_bytes_per_secs: 100_000,
                     timeout_millis: 2_000,
                     max_num_retries: 2
                 }),
+                storage_read_coalescing_gap: Some(ByteSize::kb(64)),
+                storage_read_coalescing_max_size: ByteSize::mb(4),
                 warmup_memory_budget: ByteSize::gb(100),
                 warmup_single_split_initial_allocation: ByteSize::gb(1),
             }
@@ -625,6 +648,30 @@ mod tests {
                 max_fetch_spans: NonZeroU64::new(1_000).unwrap(),
             }
         );
+        assert_eq!(
+            config.encryption_config,
+            EncryptionConfig {
+                keyfile_path_opt: Some(PathBuf::from("/etc/quickwit/keys.json")),
+            }
+        );
+        assert_eq!(
+            config.telemetry_config,
+            TelemetryConfig {
+                sinks: vec![
+                    TelemetrySinkConfig::File {
+                        path: PathBuf::from("/var/log/quickwit/telemetry.jsonl"),
+                    },
+                    TelemetrySinkConfig::Http {
+                        endpoint: "https://telemetry.example.com/quickwit".to_string(),
+                        headers: BTreeMap::from_iter([(
+                            "Authorization".to_string(),
+                            "Bearer secret".to_string()
+                        )]),
+                    },
+                    TelemetrySinkConfig::Prometheus,
+                ],
+            }
+        );
         Ok(())
     }
 
@@ -838,6 +885,8 @@ mod tests {
         assert_eq!(config.searcher_config, SearcherConfig::default());
         assert_eq!(config.ingest_api_config, IngestApiConfig::default());
         assert_eq!(config.jaeger_config, JaegerConfig::default());
+        assert_eq!(config.encryption_config, EncryptionConfig::default());
+        assert_eq!(config.telemetry_config, TelemetryConfig::default());
     }
 
     #[tokio::test]
@@ -24,7 +24,7 @@ use bytes::Bytes;
 use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
 use quickwit_proto::metastore::SourceType;
-use quickwit_proto::types::SourceId;
+use quickwit_proto::types::{IndexUid, SourceId};
 use regex::Regex;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -46,8 +46,15 @@ pub const INGEST_API_SOURCE_ID: &str = "_ingest-api-source";
 /// (this is for ingest v2)
 pub const INGEST_V2_SOURCE_ID: &str = "_ingest-source";
 
-pub const RESERVED_SOURCE_IDS: &[&str] =
-    &[CLI_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_V2_SOURCE_ID];
+/// Reserved source ID used by the janitor to publish rolled-up splits.
+pub const ROLLUP_SOURCE_ID: &str = "_rollup-source";
+
+pub const RESERVED_SOURCE_IDS: &[&str] = &[
+    CLI_SOURCE_ID,
+    INGEST_API_SOURCE_ID,
+    INGEST_V2_SOURCE_ID,
+    ROLLUP_SOURCE_ID,
+];
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(into = "VersionedSourceConfig")]
@@ -86,6 +93,7 @@ impl SourceConfig {
             SourceParams::Kafka(params) => serde_json::to_value(params),
             SourceParams::Kinesis(params) => serde_json::to_value(params),
             SourceParams::Pulsar(params) => serde_json::to_value(params),
+            SourceParams::Reindex(params) => serde_json::to_value(params),
             SourceParams::Stdin => serde_json::to_value(()),
             SourceParams::Vec(params) => serde_json::to_value(params),
             SourceParams::Void(params) => serde_json::to_value(params),
@@ -129,6 +137,36 @@ impl SourceConfig {
         }
     }
 
+    /// Creates the source config of the target index of a rollup policy. The source is never
+    /// enabled: it only records the rollup checkpoint of the rolled-up indexes.
+    pub fn rollup() -> Self {
+        Self {
+            source_id: ROLLUP_SOURCE_ID.to_string(),
+            num_pipelines: NonZeroUsize::MIN,
+            enabled: false,
+            source_params: SourceParams::Void(VoidSourceParams),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        }
+    }
+
+    /// Creates the source config of a reindex operation, which reads the documents matching a
+    /// query from the splits of another index and indexes them into the index owning the source.
+    pub fn reindex(
+        source_id: SourceId,
+        params: ReindexSourceParams,
+        transform_config: Option<TransformConfig>,
+    ) -> Self {
+        Self {
+            source_id,
+            num_pipelines: NonZeroUsize::MIN,
+            enabled: true,
+            source_params: SourceParams::Reindex(params),
+            transform_config,
+            input_format: SourceInputFormat::Json,
+        }
+    }
+
     /// Returns a fingerprint of parameters relevant for indexers.
     ///
     /// This should remain private to this crate to avoid confusion with the
@@ -231,6 +269,7 @@ pub enum SourceParams {
     #[serde(rename = "pubsub")]
     PubSub(PubSubSourceParams),
     Pulsar(PulsarSourceParams),
+    Reindex(ReindexSourceParams),
     Stdin,
     Vec(VecSourceParams),
     Void(VoidSourceParams),
@@ -263,6 +302,7 @@ impl SourceParams {
             SourceParams::Kinesis(_) => SourceType::Kinesis,
             SourceParams::PubSub(_) => SourceType::PubSub,
             SourceParams::Pulsar(_) => SourceType::Pulsar,
+            SourceParams::Reindex(_) => SourceType::Reindex,
             SourceParams::Stdin => SourceType::Stdin,
             SourceParams::Vec(_) => SourceType::Vec,
             SourceParams::Void(_) => SourceType::Void,
@@ -415,6 +455,18 @@ impl FileSourceParams {
     pub fn from_filepath<P: AsRef<str>>(filepath: P) -> anyhow::Result<Self> {
         Uri::from_str(filepath.as_ref()).map(Self::Filepath)
     }
+
+    /// Creates the params of a file source consuming notifications from an SQS queue, with the
+    /// default deduplication settings.
+    pub fn from_sqs_queue(queue_url: String, message_type: FileSourceMessageType) -> Self {
+        Self::Notifications(FileSourceNotification::Sqs(FileSourceSqs {
+            queue_url,
+            message_type,
+            deduplication_window_duration_secs: default_deduplication_window_duration_secs(),
+            deduplication_window_max_messages: default_deduplication_window_max_messages(),
+            deduplication_cleanup_interval_secs: default_deduplication_cleanup_interval_secs(),
+        }))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
@@ -554,6 +606,19 @@ pub struct VecSourceParams {
 #[serde(deny_unknown_fields)]
 pub struct VoidSourceParams;
 
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReindexSourceParams {
+    /// UID of the index whose documents are reindexed.
+    #[schema(value_type = String)]
+    pub source_index_uid: IndexUid,
+    /// JSON-serialized query AST selecting the documents to reindex.
+    pub query_ast: String,
+    /// Unix timestamp (in seconds) at which the set of splits of the source index is captured.
+    /// Splits published after this timestamp are ignored.
+    pub snapshot_timestamp: i64,
+}
+
 #[derive(
     Clone, Debug, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize, utoipa::ToSchema,
 )]
@@ -1477,6 +1542,46 @@ mod tests {
         assert_eq!(source_config.input_format, SourceInputFormat::PlainText);
     }
 
+    #[test]
+    fn test_load_reindex_source_config() {
+        let file_content = r#"{
+            "version": "0.8",
+            "source_id": "reindex-source",
+            "source_type": "reindex",
+            "params": {
+              "source_index_uid": "source-index:00000000000000000000000000",
+              "query_ast": "{\"type\":\"match_all\"}",
+              "snapshot_timestamp": 1700000000
+            }
+        }"#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                .unwrap();
+        assert_eq!(source_config.source_type(), SourceType::Reindex);
+        let SourceParams::Reindex(params) = &source_config.source_params else {
+            panic!("expected reindex source params");
+        };
+        assert_eq!(
+            params.source_index_uid,
+            IndexUid::for_test("source-index", 0)
+        );
+        assert_eq!(params.snapshot_timestamp, 1_700_000_000);
+
+        let file_content = r#"{
+            "version": "0.8",
+            "source_id": "reindex-source",
+            "source_type": "reindex",
+            "params": {
+              "source_index_uid": "source-index:00000000000000000000000000",
+              "query_ast": "{\"type\":\"match_all\"}",
+              "snapshot_timestamp": 1700000000
+            },
+            "input_format": "plain_text"
+        }"#;
+        load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+            .unwrap_err();
+    }
+
     #[tokio::test]
     async fn test_update_kafka_source_config() {
         let source_config_filepath = get_source_config_filepath("kafka-source.json");
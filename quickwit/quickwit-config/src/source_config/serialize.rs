@@ -116,6 +116,11 @@ impl SourceConfigForSerialization {
             | SourceParams::Pulsar(_) => {
                 // TODO consider any validation opportunity
             }
+            SourceParams::Reindex(_) => {
+                if self.input_format != SourceInputFormat::Json {
+                    bail!("reindex sources only support the `json` input format");
+                }
+            }
             SourceParams::PubSub(_)
             | SourceParams::Ingest
             | SourceParams::IngestApi
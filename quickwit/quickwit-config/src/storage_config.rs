@@ -32,10 +32,16 @@ pub enum StorageBackend {
     File,
     /// Google Cloud Storage
     Google,
+    /// Hadoop Distributed File System
+    Hdfs,
+    /// Read-only HTTP(S) file server
+    Http,
     /// In-memory storage, for testing purposes
     Ram,
     /// Amazon S3 or S3-compatible storage
     S3,
+    /// WebDAV server
+    Webdav,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
@@ -122,6 +128,33 @@ impl StorageConfigs {
             })
     }
 
+    pub fn find_hdfs(&self) -> Option<&HdfsStorageConfig> {
+        self.0
+            .iter()
+            .find_map(|storage_config| match storage_config {
+                StorageConfig::Hdfs(hdfs_storage_config) => Some(hdfs_storage_config),
+                _ => None,
+            })
+    }
+
+    pub fn find_http(&self) -> Option<&HttpStorageConfig> {
+        self.0
+            .iter()
+            .find_map(|storage_config| match storage_config {
+                StorageConfig::Http(http_storage_config) => Some(http_storage_config),
+                _ => None,
+            })
+    }
+
+    pub fn find_webdav(&self) -> Option<&WebdavStorageConfig> {
+        self.0
+            .iter()
+            .find_map(|storage_config| match storage_config {
+                StorageConfig::Webdav(webdav_storage_config) => Some(webdav_storage_config),
+                _ => None,
+            })
+    }
+
     pub fn find_file(&self) -> Option<&FileStorageConfig> {
         self.0
             .iter()
@@ -166,14 +199,19 @@ pub enum StorageConfig {
     Ram(RamStorageConfig),
     S3(S3StorageConfig),
     Google(GoogleCloudStorageConfig),
+    Hdfs(HdfsStorageConfig),
+    Http(HttpStorageConfig),
+    Webdav(WebdavStorageConfig),
 }
 
 impl StorageConfig {
     pub fn redact(&mut self) {
         match self {
             Self::Azure(azure_storage_config) => azure_storage_config.redact(),
-            Self::File(_) | Self::Ram(_) | Self::Google(_) => {}
+            Self::File(_) | Self::Ram(_) | Self::Google(_) | Self::Hdfs(_) => {}
             Self::S3(s3_storage_config) => s3_storage_config.redact(),
+            Self::Http(http_storage_config) => http_storage_config.redact(),
+            Self::Webdav(webdav_storage_config) => webdav_storage_config.redact(),
         }
     }
 
@@ -211,6 +249,27 @@ impl StorageConfig {
             _ => None,
         }
     }
+
+    pub fn as_hdfs(&self) -> Option<&HdfsStorageConfig> {
+        match self {
+            Self::Hdfs(hdfs_storage_config) => Some(hdfs_storage_config),
+            _ => None,
+        }
+    }
+
+    pub fn as_http(&self) -> Option<&HttpStorageConfig> {
+        match self {
+            Self::Http(http_storage_config) => Some(http_storage_config),
+            _ => None,
+        }
+    }
+
+    pub fn as_webdav(&self) -> Option<&WebdavStorageConfig> {
+        match self {
+            Self::Webdav(webdav_storage_config) => Some(webdav_storage_config),
+            _ => None,
+        }
+    }
 }
 
 impl From<AzureStorageConfig> for StorageConfig {
@@ -243,6 +302,24 @@ impl From<GoogleCloudStorageConfig> for StorageConfig {
     }
 }
 
+impl From<HdfsStorageConfig> for StorageConfig {
+    fn from(hdfs_storage_config: HdfsStorageConfig) -> Self {
+        Self::Hdfs(hdfs_storage_config)
+    }
+}
+
+impl From<HttpStorageConfig> for StorageConfig {
+    fn from(http_storage_config: HttpStorageConfig) -> Self {
+        Self::Http(http_storage_config)
+    }
+}
+
+impl From<WebdavStorageConfig> for StorageConfig {
+    fn from(webdav_storage_config: WebdavStorageConfig) -> Self {
+        Self::Webdav(webdav_storage_config)
+    }
+}
+
 impl StorageConfig {
     pub fn backend(&self) -> StorageBackend {
         match self {
@@ -251,6 +328,9 @@ impl StorageConfig {
             Self::Ram(_) => StorageBackend::Ram,
             Self::S3(_) => StorageBackend::S3,
             Self::Google(_) => StorageBackend::Google,
+            Self::Hdfs(_) => StorageBackend::Hdfs,
+            Self::Http(_) => StorageBackend::Http,
+            Self::Webdav(_) => StorageBackend::Webdav,
         }
     }
 }
@@ -425,6 +505,146 @@ impl GoogleCloudStorageConfig {
     }
 }
 
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HdfsStorageConfig {
+    /// User used to access HDFS, defaults to the user running Quickwit.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Path to the Kerberos ticket cache, for clusters secured with Kerberos.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kerberos_ticket_cache_path: Option<String>,
+}
+
+impl HdfsStorageConfig {
+    pub const HDFS_USER_ENV_VAR: &'static str = "QW_HDFS_USER";
+
+    /// Attempts to find the user in the environment variable `QW_HDFS_USER` or the config.
+    pub fn resolve_user(&self) -> Option<String> {
+        env::var(Self::HDFS_USER_ENV_VAR)
+            .ok()
+            .or_else(|| self.user.clone())
+    }
+}
+
+/// Credentials of the HTTP(S) and WebDAV storages. Basic authentication is used if a username is
+/// set, bearer authentication if a token is set.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpStorageConfig {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl HttpStorageConfig {
+    pub const HTTP_STORAGE_PASSWORD_ENV_VAR: &'static str = "QW_HTTP_STORAGE_PASSWORD";
+
+    pub const HTTP_STORAGE_TOKEN_ENV_VAR: &'static str = "QW_HTTP_STORAGE_TOKEN";
+
+    /// Redacts the password and the token.
+    pub fn redact(&mut self) {
+        redact_credentials(&mut self.password, &mut self.token);
+    }
+
+    /// Attempts to find the password in the environment variable `QW_HTTP_STORAGE_PASSWORD` or
+    /// the config.
+    pub fn resolve_password(&self) -> Option<String> {
+        env::var(Self::HTTP_STORAGE_PASSWORD_ENV_VAR)
+            .ok()
+            .or_else(|| self.password.clone())
+    }
+
+    /// Attempts to find the token in the environment variable `QW_HTTP_STORAGE_TOKEN` or the
+    /// config.
+    pub fn resolve_token(&self) -> Option<String> {
+        env::var(Self::HTTP_STORAGE_TOKEN_ENV_VAR)
+            .ok()
+            .or_else(|| self.token.clone())
+    }
+}
+
+impl fmt::Debug for HttpStorageConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpStorageConfig")
+            .field("username", &self.username)
+            .field(
+                "password",
+                &self.password.as_ref().map(|_| "***redacted***"),
+            )
+            .field("token", &self.token.as_ref().map(|_| "***redacted***"))
+            .finish()
+    }
+}
+
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebdavStorageConfig {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl WebdavStorageConfig {
+    pub const WEBDAV_STORAGE_PASSWORD_ENV_VAR: &'static str = "QW_WEBDAV_STORAGE_PASSWORD";
+
+    pub const WEBDAV_STORAGE_TOKEN_ENV_VAR: &'static str = "QW_WEBDAV_STORAGE_TOKEN";
+
+    /// Redacts the password and the token.
+    pub fn redact(&mut self) {
+        redact_credentials(&mut self.password, &mut self.token);
+    }
+
+    /// Attempts to find the password in the environment variable `QW_WEBDAV_STORAGE_PASSWORD` or
+    /// the config.
+    pub fn resolve_password(&self) -> Option<String> {
+        env::var(Self::WEBDAV_STORAGE_PASSWORD_ENV_VAR)
+            .ok()
+            .or_else(|| self.password.clone())
+    }
+
+    /// Attempts to find the token in the environment variable `QW_WEBDAV_STORAGE_TOKEN` or the
+    /// config.
+    pub fn resolve_token(&self) -> Option<String> {
+        env::var(Self::WEBDAV_STORAGE_TOKEN_ENV_VAR)
+            .ok()
+            .or_else(|| self.token.clone())
+    }
+}
+
+impl fmt::Debug for WebdavStorageConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebdavStorageConfig")
+            .field("username", &self.username)
+            .field(
+                "password",
+                &self.password.as_ref().map(|_| "***redacted***"),
+            )
+            .field("token", &self.token.as_ref().map(|_| "***redacted***"))
+            .finish()
+    }
+}
+
+fn redact_credentials(password_opt: &mut Option<String>, token_opt: &mut Option<String>) {
+    for secret in [password_opt, token_opt].into_iter().flatten() {
+        *secret = "***redacted***".to_string();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -603,6 +823,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_storage_hdfs_http_webdav_configs_serde() {
+        let storage_configs_yaml = r#"
+                hdfs:
+                    user: quickwit
+                    kerberos_ticket_cache_path: /tmp/krb5cc_1000
+                http:
+                    token: test-token
+                webdav:
+                    username: quickwit
+                    password: test-password
+            "#;
+        let mut storage_configs: StorageConfigs =
+            serde_yaml::from_str(storage_configs_yaml).unwrap();
+        storage_configs.validate().unwrap();
+
+        let expected_storage_configs = StorageConfigs(vec![
+            HdfsStorageConfig {
+                user: Some("quickwit".to_string()),
+                kerberos_ticket_cache_path: Some("/tmp/krb5cc_1000".to_string()),
+            }
+            .into(),
+            HttpStorageConfig {
+                token: Some("test-token".to_string()),
+                ..Default::default()
+            }
+            .into(),
+            WebdavStorageConfig {
+                username: Some("quickwit".to_string()),
+                password: Some("test-password".to_string()),
+                ..Default::default()
+            }
+            .into(),
+        ]);
+        assert_eq!(storage_configs, expected_storage_configs);
+
+        storage_configs.redact();
+        assert_eq!(
+            storage_configs.find_http().unwrap().token.as_ref().unwrap(),
+            "***redacted***"
+        );
+        let webdav_storage_config = storage_configs.find_webdav().unwrap();
+        assert_eq!(webdav_storage_config.username.as_ref().unwrap(), "quickwit");
+        assert_eq!(
+            webdav_storage_config.password.as_ref().unwrap(),
+            "***redacted***"
+        );
+    }
+
     #[test]
     fn test_storage_s3_config_serde() {
         {
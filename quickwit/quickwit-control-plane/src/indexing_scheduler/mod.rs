@@ -215,6 +215,7 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
             | SourceParams::Kinesis(_)
             | SourceParams::PubSub(_)
             | SourceParams::Pulsar(_)
+            | SourceParams::Reindex(_)
             | SourceParams::File(FileSourceParams::Notifications(_)) => {
                 sources.push(SourceToSchedule {
                     source_uid,
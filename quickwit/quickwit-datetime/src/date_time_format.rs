@@ -21,7 +21,7 @@ use serde_json::Value as JsonValue;
 use time::format_description::well_known::{Iso8601, Rfc2822, Rfc3339};
 use time::Month;
 
-use crate::java_date_time_format::is_strftime_formatting;
+use crate::java_date_time_format::{is_java_datetime_format, is_strftime_formatting};
 use crate::{StrptimeParser, TantivyDateTime};
 
 /// Specifies the datetime and unix timestamp formats to use when parsing date strings.
@@ -31,8 +31,14 @@ pub enum DateTimeInputFormat {
     Rfc2822,
     #[default]
     Rfc3339,
+    /// Custom `strftime` or Java date time format.
     Strptime(StrptimeParser),
+    /// Unix timestamp whose precision is inferred from its value.
     Timestamp,
+    /// Unix timestamp in milliseconds, as in Elasticsearch's `epoch_millis` format.
+    EpochMillis,
+    /// Unix timestamp in seconds, as in Elasticsearch's `epoch_second` format.
+    EpochSecond,
 }
 
 impl DateTimeInputFormat {
@@ -43,6 +49,8 @@ impl DateTimeInputFormat {
             DateTimeInputFormat::Rfc3339 => "rfc3339",
             DateTimeInputFormat::Strptime(parser) => parser.strptime_format.as_str(),
             DateTimeInputFormat::Timestamp => "unix_timestamp",
+            DateTimeInputFormat::EpochMillis => "epoch_millis",
+            DateTimeInputFormat::EpochSecond => "epoch_second",
         }
     }
 }
@@ -62,14 +70,24 @@ impl FromStr for DateTimeInputFormat {
             "rfc2822" => DateTimeInputFormat::Rfc2822,
             "rfc3339" => DateTimeInputFormat::Rfc3339,
             "unix_timestamp" => DateTimeInputFormat::Timestamp,
+            "epoch_millis" => DateTimeInputFormat::EpochMillis,
+            "epoch_second" => DateTimeInputFormat::EpochSecond,
             _ => {
-                if !is_strftime_formatting(date_time_format_str) {
+                if is_strftime_formatting(date_time_format_str) {
+                    DateTimeInputFormat::Strptime(StrptimeParser::from_strptime(
+                        date_time_format_str,
+                    )?)
+                } else if is_java_datetime_format(date_time_format_str) {
+                    DateTimeInputFormat::Strptime(StrptimeParser::from_java_datetime_format(
+                        date_time_format_str,
+                    )?)
+                } else {
                     return Err(format!(
                         "unknown input format: `{date_time_format_str}`. a custom date time \
-                         format must contain at least one `strftime` special characters"
+                         format must contain at least one `strftime` special characters or be a \
+                         Java date time format"
                     ));
                 }
-                DateTimeInputFormat::Strptime(StrptimeParser::from_strptime(date_time_format_str)?)
             }
         };
         Ok(date_time_format)
@@ -252,6 +270,37 @@ mod tests {
         assert_eq!(date_time_formats, &expected_date_time_formats);
     }
 
+    #[test]
+    fn test_date_time_input_format_epoch_and_java_formats() {
+        let date_time_formats_json = r#"
+            [
+                "epoch_millis",
+                "epoch_second",
+                "yyyy-MM-dd HH:mm:ss.SSS",
+                "strict_date_optional_time"
+            ]
+            "#;
+        let date_time_formats: Vec<DateTimeInputFormat> =
+            serde_json::from_str(date_time_formats_json).unwrap();
+        assert_eq!(date_time_formats[0], DateTimeInputFormat::EpochMillis);
+        assert_eq!(date_time_formats[1], DateTimeInputFormat::EpochSecond);
+        assert_eq!(
+            date_time_formats[2],
+            DateTimeInputFormat::Strptime(
+                StrptimeParser::from_java_datetime_format("yyyy-MM-dd HH:mm:ss.SSS").unwrap()
+            )
+        );
+        assert_eq!(
+            serde_json::to_value(&date_time_formats).unwrap(),
+            serde_json::json!([
+                "epoch_millis",
+                "epoch_second",
+                "yyyy-MM-dd HH:mm:ss.SSS",
+                "strict_date_optional_time"
+            ])
+        );
+    }
+
     #[test]
     fn test_date_time_output_format_ser() {
         let date_time_formats_json = serde_json::to_value(&[
@@ -27,6 +27,9 @@ const MIN_TIMESTAMP_SECONDS: i64 = 72_057_595;
 // Maximum supported timestamp value in seconds (16 Mar 2242 12:56:31 GMT).
 const MAX_TIMESTAMP_SECONDS: i64 = 8_589_934_591;
 
+const NANOS_PER_MILLI: i64 = 1_000_000;
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
 pub fn parse_date_time_str(
     date_time_str: &str,
     date_time_formats: &[DateTimeInputFormat],
@@ -49,6 +52,12 @@ pub fn parse_date_time_str(
                 .map(TantivyDateTime::from_utc)
                 .ok(),
             DateTimeInputFormat::Timestamp => parse_timestamp_str(trimmed_date_time_str),
+            DateTimeInputFormat::EpochMillis => {
+                parse_epoch_str(trimmed_date_time_str, NANOS_PER_MILLI)
+            }
+            DateTimeInputFormat::EpochSecond => {
+                parse_epoch_str(trimmed_date_time_str, NANOS_PER_SECOND)
+            }
         };
         if let Some(date_time) = date_time_opt {
             return Ok(date_time);
@@ -63,39 +72,100 @@ pub fn parse_date_time_str(
     ))
 }
 
+/// Returns the first of the date time formats accepting numeric values.
+fn find_numeric_date_time_format(
+    date_time_formats: &[DateTimeInputFormat],
+) -> Option<&DateTimeInputFormat> {
+    date_time_formats.iter().find(|date_time_format| {
+        matches!(
+            date_time_format,
+            DateTimeInputFormat::Timestamp
+                | DateTimeInputFormat::EpochMillis
+                | DateTimeInputFormat::EpochSecond
+        )
+    })
+}
+
 pub fn parse_timestamp_float(
     timestamp: f64,
     date_time_formats: &[DateTimeInputFormat],
 ) -> Result<TantivyDateTime, String> {
-    if !date_time_formats.contains(&DateTimeInputFormat::Timestamp) {
-        return Err(format!(
+    match find_numeric_date_time_format(date_time_formats) {
+        Some(DateTimeInputFormat::EpochMillis) => parse_epoch_float(timestamp, NANOS_PER_MILLI),
+        Some(DateTimeInputFormat::EpochSecond) => parse_epoch_float(timestamp, NANOS_PER_SECOND),
+        Some(_) => {
+            let duration_since_epoch = Duration::try_from_secs_f64(timestamp)
+                .map_err(|error| format!("failed to parse datetime `{timestamp}`: {error}"))?;
+            let timestamp_nanos = duration_since_epoch.as_nanos() as i64;
+            Ok(TantivyDateTime::from_timestamp_nanos(timestamp_nanos))
+        }
+        None => Err(format!(
             "failed to parse datetime `{timestamp}` using the following formats: `{}`",
             date_time_formats
                 .iter()
                 .map(|date_time_format| date_time_format.as_str())
                 .join("`, `")
-        ));
+        )),
     }
-    let duration_since_epoch = Duration::try_from_secs_f64(timestamp)
-        .map_err(|error| format!("failed to parse datetime `{timestamp}`: {error}"))?;
-    let timestamp_nanos = duration_since_epoch.as_nanos() as i64;
-    Ok(TantivyDateTime::from_timestamp_nanos(timestamp_nanos))
 }
 
 pub fn parse_timestamp_int(
     timestamp: i64,
     date_time_formats: &[DateTimeInputFormat],
 ) -> Result<TantivyDateTime, String> {
-    if !date_time_formats.contains(&DateTimeInputFormat::Timestamp) {
-        return Err(format!(
+    match find_numeric_date_time_format(date_time_formats) {
+        Some(DateTimeInputFormat::EpochMillis) => parse_epoch_int(timestamp, NANOS_PER_MILLI),
+        Some(DateTimeInputFormat::EpochSecond) => parse_epoch_int(timestamp, NANOS_PER_SECOND),
+        Some(_) => parse_timestamp(timestamp),
+        None => Err(format!(
             "failed to parse datetime `{timestamp}` using the following formats: `{}`",
             date_time_formats
                 .iter()
                 .map(|date_time_format| date_time_format.as_str())
                 .join("`, `")
+        )),
+    }
+}
+
+/// Parses a Unix timestamp expressed in a fixed unit, `nanos_per_unit` being the number of
+/// nanoseconds in that unit. Unlike [`parse_timestamp`], the precision is not inferred and
+/// negative timestamps are accepted.
+fn parse_epoch_int(timestamp: i64, nanos_per_unit: i64) -> Result<TantivyDateTime, String> {
+    timestamp
+        .checked_mul(nanos_per_unit)
+        .map(TantivyDateTime::from_timestamp_nanos)
+        .ok_or_else(|| format!("failed to parse epoch timestamp `{timestamp}`: out of range"))
+}
+
+fn parse_epoch_float(timestamp: f64, nanos_per_unit: i64) -> Result<TantivyDateTime, String> {
+    let timestamp_nanos = (timestamp * nanos_per_unit as f64).round();
+
+    if !timestamp_nanos.is_finite() || timestamp_nanos.abs() >= i64::MAX as f64 {
+        return Err(format!(
+            "failed to parse epoch timestamp `{timestamp}`: out of range"
         ));
     }
-    parse_timestamp(timestamp)
+    Ok(TantivyDateTime::from_timestamp_nanos(
+        timestamp_nanos as i64,
+    ))
+}
+
+/// Parses an epoch timestamp string such as `1672531200000` or `1672531200.123`.
+fn parse_epoch_str(timestamp_str: &str, nanos_per_unit: i64) -> Option<TantivyDateTime> {
+    if let Ok(timestamp) = timestamp_str.parse::<i64>() {
+        return parse_epoch_int(timestamp, nanos_per_unit).ok();
+    }
+    // Reject the float representations accepted by Rust but not by Elasticsearch, such as `inf`
+    // or `1e3`.
+    let digits_str = timestamp_str.strip_prefix('-').unwrap_or(timestamp_str);
+    if !digits_str
+        .bytes()
+        .all(|byte| byte.is_ascii_digit() || byte == b'.')
+    {
+        return None;
+    }
+    let timestamp = timestamp_str.parse::<f64>().ok()?;
+    parse_epoch_float(timestamp, nanos_per_unit).ok()
 }
 
 pub fn parse_timestamp_str(timestamp_str: &str) -> Option<TantivyDateTime> {
@@ -176,6 +246,8 @@ pub fn parse_timestamp(timestamp: i64) -> Result<TantivyDateTime, String> {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use time::macros::datetime;
     use time::Month;
 
@@ -413,6 +485,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_epoch_formats() {
+        // Elasticsearch interprets the timestamps according to the unit of the format, even
+        // for values that `unix_timestamp` would interpret with another precision.
+        let date_time = parse_timestamp_int(1_000, &[DateTimeInputFormat::EpochMillis]).unwrap();
+        assert_eq!(date_time.into_timestamp_secs(), 1);
+
+        let date_time = parse_timestamp_int(-1_000, &[DateTimeInputFormat::EpochSecond]).unwrap();
+        assert_eq!(date_time.into_timestamp_secs(), -1_000);
+
+        let date_time =
+            parse_timestamp_float(1_500.5, &[DateTimeInputFormat::EpochMillis]).unwrap();
+        assert_eq!(date_time.into_timestamp_micros(), 1_500_500);
+
+        let date_time = parse_timestamp_float(
+            1_337_602_154.25,
+            &[
+                DateTimeInputFormat::Rfc3339,
+                DateTimeInputFormat::EpochSecond,
+            ],
+        )
+        .unwrap();
+        assert_eq!(date_time.into_timestamp_millis(), 1_337_602_154_250);
+
+        let error = parse_timestamp_int(i64::MAX, &[DateTimeInputFormat::EpochMillis]).unwrap_err();
+        assert!(error.contains("out of range"));
+
+        for (date_time_str, date_time_format, expected_timestamp_millis) in [
+            (
+                "1337602154000",
+                DateTimeInputFormat::EpochMillis,
+                1_337_602_154_000,
+            ),
+            (
+                " 1337602154000.5 ",
+                DateTimeInputFormat::EpochMillis,
+                1_337_602_154_000,
+            ),
+            (
+                "1337602154",
+                DateTimeInputFormat::EpochSecond,
+                1_337_602_154_000,
+            ),
+            (
+                "1337602154.123",
+                DateTimeInputFormat::EpochSecond,
+                1_337_602_154_123,
+            ),
+            ("-1", DateTimeInputFormat::EpochSecond, -1_000),
+        ] {
+            let date_time = parse_date_time_str(date_time_str, &[date_time_format]).unwrap();
+            assert_eq!(date_time.into_timestamp_millis(), expected_timestamp_millis);
+        }
+        for date_time_str in ["inf", "NaN", "1e3", "2012-05-21"] {
+            parse_date_time_str(date_time_str, &[DateTimeInputFormat::EpochMillis]).unwrap_err();
+        }
+    }
+
     #[test]
     fn test_parse_timestamp_str() {
         let date_time = parse_timestamp_str("123456789").unwrap();
@@ -434,6 +564,27 @@ mod tests {
         assert_eq!(date_time.into_timestamp_nanos(), 123456789100000001);
     }
 
+    #[test]
+    fn test_parse_date_time_str_java_formats() {
+        let date_time_formats = [
+            DateTimeInputFormat::from_str("yyyy-MM-dd HH:mm:ss.SSS").unwrap(),
+            DateTimeInputFormat::from_str("strict_date_optional_time").unwrap(),
+            DateTimeInputFormat::EpochMillis,
+        ];
+        for date_time_str in [
+            "2012-05-21 12:09:14.000",
+            "2012-05-21T12:09:14",
+            "2012-05-21T14:09:14.000+02:00",
+            "1337602154000",
+        ] {
+            let date_time = parse_date_time_str(date_time_str, &date_time_formats).unwrap();
+            assert_eq!(
+                date_time.into_timestamp_secs(),
+                datetime!(2012-05-21 12:09:14 UTC).unix_timestamp()
+            );
+        }
+    }
+
     #[test]
     fn test_parse_date_time_millis() {
         for date_time_str in [
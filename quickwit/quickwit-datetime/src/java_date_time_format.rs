@@ -286,6 +286,38 @@ fn resolve_java_datetime_format_alias(java_datetime_format: &str) -> &str {
 
         m.insert("strict_week_date", "xxxx-'W'ww-e");
         m.insert("week_date", "xxxx-'W'w[w]-e");
+
+        m.insert("strict_date", "yyyy-MM-dd");
+        m.insert("date", "yyyy-MM-dd");
+
+        m.insert("strict_date_time", "yyyy-MM-dd'T'HH:mm:ss.SSSZ");
+        m.insert("date_time", "yyyy-MM-dd'T'HH:mm:ss.SSSZ");
+
+        m.insert("strict_date_time_no_millis", "yyyy-MM-dd'T'HH:mm:ssZ");
+        m.insert("date_time_no_millis", "yyyy-MM-dd'T'HH:mm:ssZ");
+
+        m.insert("strict_date_hour_minute_second", "yyyy-MM-dd'T'HH:mm:ss");
+        m.insert("date_hour_minute_second", "yyyy-MM-dd'T'HH:mm:ss");
+
+        m.insert(
+            "strict_date_hour_minute_second_millis",
+            "yyyy-MM-dd'T'HH:mm:ss.SSS",
+        );
+        m.insert(
+            "date_hour_minute_second_millis",
+            "yyyy-MM-dd'T'HH:mm:ss.SSS",
+        );
+        m.insert(
+            "strict_date_hour_minute_second_fraction",
+            "yyyy-MM-dd'T'HH:mm:ss.SSS",
+        );
+        m.insert(
+            "date_hour_minute_second_fraction",
+            "yyyy-MM-dd'T'HH:mm:ss.SSS",
+        );
+
+        m.insert("basic_date_time", "yyyyMMdd'T'HHmmss.SSSZ");
+        m.insert("basic_date_time_no_millis", "yyyyMMdd'T'HHmmssZ");
         m
     });
     java_datetime_format_map
@@ -424,6 +456,36 @@ impl std::hash::Hash for StrptimeParser {
     }
 }
 
+// Checks if a format is a Java date time format, i.e. either an Elasticsearch built-in format or a
+// pattern in which all the letters that are not quoted are supported pattern letters.
+pub fn is_java_datetime_format(format_str: &str) -> bool {
+    if resolve_java_datetime_format_alias(format_str) != format_str {
+        return true;
+    }
+    let mut chars = format_str.chars().peekable();
+    let mut num_tokens = 0;
+
+    while let Some(&c) = chars.peek() {
+        if c == '\'' {
+            chars.next();
+            // Skip the quoted literal.
+            for next_c in chars.by_ref() {
+                if next_c == '\'' {
+                    break;
+                }
+            }
+        } else if c.is_ascii_alphabetic() {
+            match match_java_date_format_token(&mut chars) {
+                Ok(Some(_)) => num_tokens += 1,
+                _ => return false,
+            }
+        } else {
+            chars.next();
+        }
+    }
+    num_tokens > 0
+}
+
 // `Strftime` format special characters.
 // These characters are taken from the parsing crate we use for compatibility.
 const STRFTIME_FORMAT_MARKERS: [&str; 36] = [
@@ -557,6 +619,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_java_datetime_format_es_builtin_aliases() {
+        test_parse_java_datetime_aux("date", "2021-01-21", datetime!(2021-01-21 00:00:00 UTC));
+        test_parse_java_datetime_aux(
+            "strict_date_time",
+            "2021-01-21T03:01:22.312Z",
+            datetime!(2021-01-21 03:01:22.312 UTC),
+        );
+        test_parse_java_datetime_aux(
+            "date_time_no_millis",
+            "2021-01-21T03:01:22+01:00",
+            datetime!(2021-01-21 03:01:22 +1),
+        );
+        test_parse_java_datetime_aux(
+            "date_hour_minute_second_millis",
+            "2021-01-21T03:01:22.312",
+            datetime!(2021-01-21 03:01:22.312 UTC),
+        );
+        test_parse_java_datetime_aux(
+            "basic_date_time_no_millis",
+            "20210121T030122Z",
+            datetime!(2021-01-21 03:01:22 UTC),
+        );
+    }
+
+    #[test]
+    fn test_is_java_datetime_format() {
+        assert!(is_java_datetime_format("yyyy-MM-dd HH:mm:ss.SSS"));
+        assert!(is_java_datetime_format("yyyy-MM-dd'T'HH:mm:ssZ"));
+        assert!(is_java_datetime_format("yyyy[-MM[-dd]]"));
+        assert!(is_java_datetime_format("strict_date_optional_time"));
+        assert!(is_java_datetime_format("basic_date"));
+
+        assert!(!is_java_datetime_format(""));
+        assert!(!is_java_datetime_format("'literal only'"));
+        assert!(!is_java_datetime_format("%Y-%m-%d"));
+        assert!(!is_java_datetime_format("unix_timestamp_secs"));
+        assert!(!is_java_datetime_format("yyyy-MM-dd hh:mm a"));
+        assert!(!is_java_datetime_format("yyyy-MM-dd'T'HHuu"));
+    }
+
     #[test]
     fn test_parse_java_week_formats() {
         test_parse_java_datetime_aux(
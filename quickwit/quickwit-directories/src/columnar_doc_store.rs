@@ -0,0 +1,449 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tantivy::error::DataCorruption;
+use tantivy::index::SegmentComponent;
+use tantivy::schema::{NamedFieldDocument, Schema, TantivyDocument};
+use tantivy::store::{StoreReader, StoreWriter};
+use tantivy::{Directory, Index, IndexReader, ReloadPolicy, SegmentId, TantivyError};
+
+/// Name of the file listing the column stores of a split written with the columnar doc store
+/// layout.
+pub const COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME: &str = "columnar_docstore.json";
+
+/// Extension of the column store files. It ends with `store` so that the hotcache retains the
+/// whole skip index of the column stores, like it does for the row doc store.
+const COLUMN_STORE_FILE_EXTENSION: &str = "colstore";
+
+/// The columnar doc store replaces the row doc store of a split with one doc store per stored
+/// field and per segment. Each column store holds, for every document of the segment, a document
+/// made of the values of a single field. Fetching a subset of the fields of a document only
+/// requires decompressing the blocks of the corresponding column stores.
+///
+/// The row doc store of the segments is emptied: it only holds empty documents, and must be
+/// restored with [`restore_row_doc_store`] before merging the segments.
+///
+/// The manifest lists the column store files of each segment, indexed by field name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ColumnarDocStoreManifest {
+    segments: BTreeMap<String, BTreeMap<String, PathBuf>>,
+}
+
+impl ColumnarDocStoreManifest {
+    /// Loads the manifest of the columnar doc store from the directory. Returns `None` if the
+    /// split was written with the row layout.
+    pub fn open(directory: &dyn Directory) -> tantivy::Result<Option<Self>> {
+        let manifest_path = Path::new(COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME);
+        if !directory.exists(manifest_path)? {
+            return Ok(None);
+        }
+        let manifest_bytes = directory.open_read(manifest_path)?.read_bytes()?;
+        Self::from_bytes(manifest_bytes.as_slice()).map(Some)
+    }
+
+    /// Asynchronous version of [`ColumnarDocStoreManifest::open`].
+    pub async fn open_async(directory: &dyn Directory) -> tantivy::Result<Option<Self>> {
+        let manifest_path = Path::new(COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME);
+        if !directory.exists(manifest_path)? {
+            return Ok(None);
+        }
+        let manifest_bytes = directory
+            .open_read(manifest_path)?
+            .read_bytes_async()
+            .await?;
+        Self::from_bytes(manifest_bytes.as_slice()).map(Some)
+    }
+
+    fn from_bytes(manifest_bytes: &[u8]) -> tantivy::Result<Self> {
+        serde_json::from_slice(manifest_bytes).map_err(|error| {
+            TantivyError::from(DataCorruption::comment_only(format!(
+                "failed to deserialize columnar doc store manifest: {error}"
+            )))
+        })
+    }
+
+    /// Returns the paths of all the column store files.
+    pub fn column_files(&self) -> impl Iterator<Item = &Path> {
+        self.segments
+            .values()
+            .flat_map(|columns| columns.values())
+            .map(PathBuf::as_path)
+    }
+
+    /// Opens the column stores of the given fields for a segment. Returns `None` if any of the
+    /// columns is missing.
+    ///
+    /// The skip indexes of the column stores are read synchronously: the directory is expected
+    /// to serve them from the hotcache.
+    pub fn open_column_readers(
+        &self,
+        directory: &dyn Directory,
+        segment_id: SegmentId,
+        field_names: &[&str],
+        cache_num_blocks: usize,
+    ) -> tantivy::Result<Option<Vec<StoreReader>>> {
+        let Some(columns) = self.segments.get(&segment_id.uuid_string()) else {
+            return Ok(None);
+        };
+        let mut column_readers = Vec::with_capacity(field_names.len());
+
+        for field_name in field_names {
+            let Some(column_file) = columns.get(*field_name) else {
+                return Ok(None);
+            };
+            let column_file_slice = directory.open_read(column_file)?;
+            let column_reader = StoreReader::open(column_file_slice, cache_num_blocks)?;
+            column_readers.push(column_reader);
+        }
+        Ok(Some(column_readers))
+    }
+}
+
+/// Reassembles the stored fields of a document from the documents read from its column stores.
+pub fn doc_from_columns(
+    schema: &Schema,
+    column_docs: impl IntoIterator<Item = TantivyDocument>,
+) -> tantivy::Result<TantivyDocument> {
+    let mut named_field_values = BTreeMap::new();
+
+    for column_doc in column_docs {
+        let NamedFieldDocument(column_values) = column_doc.to_named_doc(schema);
+        named_field_values.extend(column_values);
+    }
+    TantivyDocument::convert_named_doc(schema, NamedFieldDocument(named_field_values))
+        .map_err(|error| TantivyError::InternalError(error.to_string()))
+}
+
+fn stored_field_names(schema: &Schema) -> Vec<&str> {
+    schema
+        .fields()
+        .filter(|(_, field_entry)| field_entry.is_stored())
+        .map(|(_, field_entry)| field_entry.name())
+        .collect()
+}
+
+/// Writes the columnar doc store of an index in `output_directory`, using the doc store
+/// compression and block size of the index settings. The row doc store of the index is left
+/// untouched: see [`clear_row_doc_store`].
+///
+/// Returns the paths of the written files, manifest included.
+pub fn write_columnar_doc_store(
+    index: &Index,
+    output_directory: &dyn Directory,
+) -> tantivy::Result<Vec<PathBuf>> {
+    let schema = index.schema();
+    let index_reader: IndexReader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = index_reader.searcher();
+
+    let stored_field_names = stored_field_names(&schema);
+    let mut manifest = ColumnarDocStoreManifest::default();
+    let mut written_files = Vec::new();
+
+    for segment_reader in searcher.segment_readers() {
+        let segment_uuid = segment_reader.segment_id().uuid_string();
+        let mut columns = BTreeMap::new();
+        let mut column_writers = Vec::with_capacity(stored_field_names.len());
+
+        for field_name in &stored_field_names {
+            let field = schema.get_field(field_name)?;
+            let column_file = PathBuf::from(format!(
+                "{segment_uuid}.{}.{COLUMN_STORE_FILE_EXTENSION}",
+                field.field_id()
+            ));
+            let column_writer = new_store_writer(index, output_directory, &column_file)?;
+            column_writers.push((*field_name, column_writer));
+            columns.insert(field_name.to_string(), column_file.clone());
+            written_files.push(column_file);
+        }
+        // Deleted documents are written as well so that the doc IDs of the column stores match
+        // the doc IDs of the segment.
+        let store_reader = segment_reader.get_store_reader(1)?;
+
+        for doc_id in 0..segment_reader.max_doc() {
+            let doc: TantivyDocument = store_reader.get(doc_id)?;
+            let NamedFieldDocument(mut named_field_values) = doc.to_named_doc(&schema);
+
+            for (field_name, column_writer) in &mut column_writers {
+                let field_values = named_field_values.remove(*field_name).unwrap_or_default();
+                let column_named_doc =
+                    NamedFieldDocument(BTreeMap::from([(field_name.to_string(), field_values)]));
+                let column_doc = TantivyDocument::convert_named_doc(&schema, column_named_doc)
+                    .map_err(|error| TantivyError::InternalError(error.to_string()))?;
+                column_writer.store(&column_doc, &schema)?;
+            }
+        }
+        for (_, column_writer) in column_writers {
+            column_writer.close()?;
+        }
+        manifest.segments.insert(segment_uuid, columns);
+    }
+    let manifest_path = PathBuf::from(COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME);
+    let manifest_json = serde_json::to_vec(&manifest)
+        .expect("serializing the columnar doc store manifest should never fail");
+    output_directory.atomic_write(&manifest_path, &manifest_json)?;
+    written_files.push(manifest_path);
+
+    Ok(written_files)
+}
+
+/// Replaces the row doc store of the segments of an index with a doc store of empty documents,
+/// once the columnar doc store has been written. The store files are overwritten in
+/// `output_directory`.
+pub fn clear_row_doc_store(index: &Index, output_directory: &dyn Directory) -> tantivy::Result<()> {
+    let schema = index.schema();
+    let empty_doc = TantivyDocument::default();
+
+    for segment_meta in index.searchable_segment_metas()? {
+        let store_path = segment_meta.relative_path(SegmentComponent::Store);
+        output_directory
+            .delete(&store_path)
+            .map_err(|error| TantivyError::InternalError(error.to_string()))?;
+        let mut store_writer = new_store_writer(index, output_directory, &store_path)?;
+
+        for _ in 0..segment_meta.max_doc() {
+            store_writer.store(&empty_doc, &schema)?;
+        }
+        store_writer.close()?;
+    }
+    Ok(())
+}
+
+/// Rebuilds the row doc store of the segments of an index written with the columnar doc store
+/// layout from its column stores, so that the segments can be merged. The store files are written
+/// in `output_directory`, which is meant to shadow the directory of the index.
+///
+/// Returns `false` without writing anything if the index was written with the row layout.
+pub fn restore_row_doc_store(
+    index: &Index,
+    output_directory: &dyn Directory,
+) -> tantivy::Result<bool> {
+    let Some(manifest) = ColumnarDocStoreManifest::open(index.directory())? else {
+        return Ok(false);
+    };
+    let schema = index.schema();
+    let stored_field_names = stored_field_names(&schema);
+
+    for segment_meta in index.searchable_segment_metas()? {
+        let column_readers = manifest
+            .open_column_readers(index.directory(), segment_meta.id(), &stored_field_names, 1)?
+            .ok_or_else(|| {
+                TantivyError::from(DataCorruption::comment_only(format!(
+                    "columnar doc store of segment `{}` is incomplete",
+                    segment_meta.id().uuid_string()
+                )))
+            })?;
+        let store_path = segment_meta.relative_path(SegmentComponent::Store);
+        let mut store_writer = new_store_writer(index, output_directory, &store_path)?;
+
+        for doc_id in 0..segment_meta.max_doc() {
+            let column_docs = column_readers
+                .iter()
+                .map(|column_reader| column_reader.get::<TantivyDocument>(doc_id))
+                .collect::<tantivy::Result<Vec<_>>>()?;
+            let doc = doc_from_columns(&schema, column_docs)?;
+            store_writer.store(&doc, &schema)?;
+        }
+        store_writer.close()?;
+    }
+    Ok(true)
+}
+
+fn new_store_writer(
+    index: &Index,
+    output_directory: &dyn Directory,
+    store_path: &Path,
+) -> tantivy::Result<StoreWriter> {
+    let index_settings = index.settings();
+    let store_write = output_directory.open_write(store_path)?;
+    StoreWriter::new(
+        store_write,
+        index_settings.docstore_compression,
+        index_settings.docstore_blocksize,
+        false,
+    )
+}
+
+/// Loads the manifest and opens all the column stores of the directory, so that the reads
+/// required to open them are recorded when building the hotcache.
+///
+/// Returns the paths of the manifest and of the column store files.
+pub(crate) fn open_columnar_doc_store_files(
+    directory: &dyn Directory,
+) -> tantivy::Result<Vec<PathBuf>> {
+    let Some(manifest) = ColumnarDocStoreManifest::open(directory)? else {
+        return Ok(Vec::new());
+    };
+    let mut files = vec![PathBuf::from(COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME)];
+
+    for column_file in manifest.column_files() {
+        let column_file_slice = directory.open_read(column_file)?;
+        StoreReader::open(column_file_slice, 0)?;
+        files.push(column_file.to_path_buf());
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::directory::{OwnedBytes, RamDirectory};
+    use tantivy::schema::{Schema, Value, FAST, STORED, TEXT};
+    use tantivy::{doc, IndexSettings, IndexWriter};
+
+    use super::*;
+    use crate::{write_hotcache, HotDirectory};
+
+    #[test]
+    fn test_columnar_doc_store() -> tantivy::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+        let body_field = schema_builder.add_text_field("body", TEXT | STORED);
+        let count_field = schema_builder.add_u64_field("count", FAST | STORED);
+        let _unstored_field = schema_builder.add_text_field("unstored", TEXT);
+        let schema = schema_builder.build();
+
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 15_000_000)?;
+        for doc_id in 0..100u64 {
+            index_writer.add_document(doc!(
+                title_field => format!("title-{doc_id}"),
+                body_field => format!("body-{doc_id}"),
+                count_field => doc_id,
+            ))?;
+        }
+        index_writer.add_document(doc!(title_field => "title-only"))?;
+        index_writer.commit()?;
+
+        let written_files = write_columnar_doc_store(&index, &directory)?;
+        // 3 stored fields in a single segment, plus the manifest.
+        assert_eq!(written_files.len(), 4);
+        assert_eq!(
+            open_columnar_doc_store_files(&directory)?.len(),
+            written_files.len()
+        );
+        let manifest = ColumnarDocStoreManifest::open(&directory)?.unwrap();
+        assert!(manifest.column_files().all(|path| path
+            .to_string_lossy()
+            .ends_with(COLUMN_STORE_FILE_EXTENSION)));
+
+        let segment_id = index.searchable_segment_ids()?[0];
+        assert!(manifest
+            .open_column_readers(&directory, segment_id, &["unstored"], 1)?
+            .is_none());
+        let column_readers = manifest
+            .open_column_readers(&directory, segment_id, &["body", "count"], 1)?
+            .unwrap();
+        assert_eq!(column_readers.len(), 2);
+
+        let body_doc: TantivyDocument = column_readers[0].get(42)?;
+        let body_values: Vec<&str> = body_doc
+            .get_all(body_field)
+            .flat_map(|value| value.as_str())
+            .collect();
+        assert_eq!(body_values, ["body-42"]);
+        assert!(body_doc.get_first(title_field).is_none());
+
+        let count_doc: TantivyDocument = column_readers[1].get(42)?;
+        assert_eq!(
+            count_doc
+                .get_first(count_field)
+                .and_then(|value| value.as_u64()),
+            Some(42)
+        );
+        let count_doc: TantivyDocument = column_readers[1].get(100)?;
+        assert!(count_doc.get_first(count_field).is_none());
+
+        let unknown_segment_id = SegmentId::generate_random();
+        assert!(manifest
+            .open_column_readers(&directory, unknown_segment_id, &["body"], 1)?
+            .is_none());
+
+        // The manifest and the skip indexes of the column stores must be served by the hotcache
+        // alone.
+        let mut hotcache_bytes = Vec::new();
+        write_hotcache(directory, &mut hotcache_bytes)?;
+        let hot_directory =
+            HotDirectory::open(RamDirectory::create(), OwnedBytes::new(hotcache_bytes)).unwrap();
+        let manifest = ColumnarDocStoreManifest::open(&hot_directory)?.unwrap();
+        let column_readers = manifest
+            .open_column_readers(&hot_directory, segment_id, &["title", "body", "count"], 1)?
+            .unwrap();
+        assert_eq!(column_readers.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_and_restore_row_doc_store() -> tantivy::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+        let count_field = schema_builder.add_u64_field("count", FAST | STORED);
+        let schema = schema_builder.build();
+
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 15_000_000)?;
+        for doc_id in 0..10u64 {
+            index_writer.add_document(doc!(
+                title_field => format!("title-{doc_id}"),
+                count_field => doc_id,
+            ))?;
+        }
+        index_writer.commit()?;
+
+        let restored_directory = RamDirectory::create();
+        assert!(!restore_row_doc_store(&index, &restored_directory)?);
+
+        write_columnar_doc_store(&index, &directory)?;
+        clear_row_doc_store(&index, &directory)?;
+
+        let segment_meta = index.searchable_segment_metas()?.pop().unwrap();
+        let store_path = segment_meta.relative_path(SegmentComponent::Store);
+        let cleared_store_reader = StoreReader::open(directory.open_read(&store_path)?, 1)?;
+        let cleared_doc: TantivyDocument = cleared_store_reader.get(3)?;
+        assert!(cleared_doc.get_first(title_field).is_none());
+
+        assert!(restore_row_doc_store(&index, &restored_directory)?);
+        let restored_store_reader =
+            StoreReader::open(restored_directory.open_read(&store_path)?, 1)?;
+        let restored_doc: TantivyDocument = restored_store_reader.get(3)?;
+        assert_eq!(
+            restored_doc
+                .get_first(title_field)
+                .and_then(|value| value.as_str()),
+            Some("title-3")
+        );
+        assert_eq!(
+            restored_doc
+                .get_first(count_field)
+                .and_then(|value| value.as_u64()),
+            Some(3)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_columnar_doc_store_manifest_missing() -> tantivy::Result<()> {
+        let directory = RamDirectory::create();
+        assert!(ColumnarDocStoreManifest::open(&directory)?.is_none());
+        assert!(open_columnar_doc_store_files(&directory)?.is_empty());
+        Ok(())
+    }
+}
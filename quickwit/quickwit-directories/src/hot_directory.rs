@@ -27,6 +27,7 @@ use tantivy::directory::{FileHandle, FileSlice, OwnedBytes};
 use tantivy::error::DataCorruption;
 use tantivy::{Directory, HasLen, Index, IndexReader, ReloadPolicy, TantivyError};
 
+use crate::columnar_doc_store::open_columnar_doc_store_files;
 use crate::{CachingDirectory, DebugProxyDirectory};
 
 #[derive(Clone, Copy, Default)]
@@ -497,6 +498,7 @@ pub fn write_hotcache<D: Directory>(
             let _inv_idx = reader.inverted_index(field)?;
         }
     }
+    let columnar_doc_store_files = open_columnar_doc_store_files(&debug_proxy_directory)?;
     let mut cache_builder = StaticDirectoryCacheBuilder::default();
     let read_operations = debug_proxy_directory.drain_read_operations();
     let mut per_file_slices: HashMap<PathBuf, HashSet<Range<usize>>> = HashMap::default();
@@ -506,7 +508,8 @@ pub fn write_hotcache<D: Directory>(
             .or_default()
             .insert(read_operation.offset..read_operation.offset + read_operation.num_bytes);
     }
-    let index_files = list_index_files(&index)?;
+    let mut index_files = list_index_files(&index)?;
+    index_files.extend(columnar_doc_store_files);
     for file_path in index_files {
         let file_slice_res = debug_proxy_directory.open_read(&file_path);
         if let Err(tantivy::directory::error::OpenReadError::FileDoesNotExist(_)) = file_slice_res {
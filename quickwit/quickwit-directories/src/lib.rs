@@ -17,6 +17,7 @@
 //! - The `StorageDirectory` just wraps a `Storage` trait to make it compatible with tantivy's
 //!   Directory API.
 //! - The `BundleDirectory` bundles multiple files into a single file.
+//! - The columnar doc store replaces the doc store of a split with one store per field.
 //! - The `HotDirectory` wraps another directory with a static cache.
 //! - The `CachingDirectory` wraps a Directory with a dynamic cache.
 //! - The `DebugDirectory` acts as a proxy to another directory to instrument it and record all of
@@ -26,6 +27,7 @@
 
 mod bundle_directory;
 mod caching_directory;
+mod columnar_doc_store;
 mod debug_proxy_directory;
 mod hot_directory;
 mod storage_directory;
@@ -33,6 +35,10 @@ mod union_directory;
 
 pub use self::bundle_directory::{get_hotcache_from_split, read_split_footer, BundleDirectory};
 pub use self::caching_directory::CachingDirectory;
+pub use self::columnar_doc_store::{
+    clear_row_doc_store, doc_from_columns, restore_row_doc_store, write_columnar_doc_store,
+    ColumnarDocStoreManifest, COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME,
+};
 pub use self::debug_proxy_directory::{DebugProxyDirectory, ReadOperation};
 pub use self::hot_directory::{write_hotcache, HotDirectory};
 pub use self::storage_directory::StorageDirectory;
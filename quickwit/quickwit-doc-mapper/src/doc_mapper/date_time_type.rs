@@ -12,8 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::str::FromStr;
+
 use indexmap::IndexSet;
 use quickwit_datetime::{DateTimeInputFormat, DateTimeOutputFormat, TantivyDateTime};
+use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value as JsonValue;
 use tantivy::schema::{DateTimePrecision, OwnedValue as TantivyValue};
@@ -165,8 +168,26 @@ impl Default for InputFormats {
 impl<'de> Deserialize<'de> for InputFormats {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where D: Deserializer<'de> {
-        let date_time_formats = IndexSet::<DateTimeInputFormat>::deserialize(deserializer)?;
-
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrVec {
+            String(String),
+            Vec(Vec<String>),
+        }
+        let format_strs = match StringOrVec::deserialize(deserializer)? {
+            StringOrVec::String(format_str) => vec![format_str],
+            StringOrVec::Vec(format_strs) => format_strs,
+        };
+        // Elasticsearch-style `||` separated alternatives are flattened into the list of formats.
+        let mut date_time_formats = IndexSet::new();
+        for format_str in format_strs
+            .iter()
+            .flat_map(|format_str| format_str.split("||"))
+        {
+            let date_time_format =
+                DateTimeInputFormat::from_str(format_str.trim()).map_err(D::Error::custom)?;
+            date_time_formats.insert(date_time_format);
+        }
         if date_time_formats.is_empty() {
             return Ok(InputFormats::default());
         }
@@ -387,6 +408,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_deserialize_input_formats_alternatives_deser() {
+        {
+            let input_formats_json = r#""epoch_millis""#;
+            let input_formats: InputFormats = serde_json::from_str(input_formats_json).unwrap();
+            assert_eq!(input_formats.0, &[DateTimeInputFormat::EpochMillis]);
+        }
+        {
+            let input_formats_json = r#"["rfc3339||epoch_millis", "epoch_second || rfc3339"]"#;
+            let input_formats: InputFormats = serde_json::from_str(input_formats_json).unwrap();
+            assert_eq!(
+                input_formats.0,
+                &[
+                    DateTimeInputFormat::Rfc3339,
+                    DateTimeInputFormat::EpochMillis,
+                    DateTimeInputFormat::EpochSecond
+                ]
+            );
+        }
+        {
+            let input_formats_json = r#""yyyy-MM-dd HH:mm:ss||epoch_millis""#;
+            let input_formats: InputFormats = serde_json::from_str(input_formats_json).unwrap();
+            assert_eq!(input_formats.0.len(), 2);
+            assert_eq!(input_formats.0[1], DateTimeInputFormat::EpochMillis);
+        }
+    }
+
     #[test]
     fn test_deserialize_invalid_input_formats_should_error() {
         {
@@ -17,7 +17,9 @@ use std::num::NonZeroU32;
 
 use anyhow::{bail, Context};
 use fnv::FnvHashSet;
+use quickwit_common::shared_consts::NESTED_PATH_FIELD_NAME;
 use quickwit_proto::types::DocMappingUid;
+use quickwit_query::block_join::WithNestedDocsQuery;
 use quickwit_query::create_default_quickwit_tokenizer_manager;
 use quickwit_query::query_ast::QueryAst;
 use quickwit_query::tokenizers::TokenizerManager;
@@ -25,7 +27,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::{self, Value as JsonValue};
 use serde_json_borrow::Map as BorrowedJsonMap;
 use tantivy::query::Query;
-use tantivy::schema::{Field, FieldType, OwnedValue as TantivyValue, Schema, INDEXED, STORED};
+use tantivy::schema::{
+    Field, FieldType, OwnedValue as TantivyValue, Schema, FAST, INDEXED, STORED,
+};
 use tantivy::TantivyDocument as Document;
 
 use super::field_mapping_entry::RAW_TOKENIZER_NAME;
@@ -34,7 +38,7 @@ use super::tantivy_val_to_json::tantivy_value_to_json;
 use super::DocMapperBuilder;
 use crate::doc_mapper::mapping_tree::{
     build_field_path_from_str, build_mapping_tree, map_primitive_json_to_tantivy,
-    JsonValueIterator, MappingNode, MappingNodeRoot,
+    JsonValueIterator, MappingNode, MappingNodeRoot, NestedDoc, NestedDocs,
 };
 use crate::doc_mapper::{FieldMappingType, JsonObject, Partition};
 use crate::query_builder::build_query;
@@ -42,7 +46,7 @@ use crate::routing_expression::RoutingExpr;
 use crate::{
     Cardinality, DocMapping, DocParsingError, Mode, ModeType, NamedField, QueryParserError,
     TokenizerEntry, WarmupInfo, DOCUMENT_SIZE_FIELD_NAME, DYNAMIC_FIELD_NAME,
-    FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
+    FIELD_PRESENCE_FIELD_NAME, NESTED_SOURCE_FIELD_NAME, SOURCE_FIELD_NAME,
 };
 
 const FIELD_PRESENCE_FIELD: Field = Field::from_field_id(0u32);
@@ -69,6 +73,11 @@ pub struct DocMapper {
     dynamic_field: Option<Field>,
     /// Field in which the len of the source document is stored as a fast field.
     document_size_field: Option<Field>,
+    /// Field in which the path of the nested documents is stored as a fast field. Only present
+    /// when the field mappings contain `nested` fields.
+    nested_path_field: Option<Field>,
+    /// Field in which the values of the `nested` fields are stored on the root document.
+    nested_source_field: Option<Field>,
     /// Default list of field names used for search.
     default_search_field_names: Vec<String>,
     /// Timestamp field name.
@@ -192,6 +201,14 @@ impl TryFrom<DocMapperBuilder> for DocMapper {
         if !concatenate_dynamic_fields.is_empty() && dynamic_field.is_none() {
             bail!("concatenate field has `include_dynamic_fields` set, but index isn't dynamic");
         }
+        let (nested_path_field, nested_source_field) = if field_mappings.has_nested_fields() {
+            (
+                Some(schema_builder.add_text_field(NESTED_PATH_FIELD_NAME, FAST)),
+                Some(schema_builder.add_json_field(NESTED_SOURCE_FIELD_NAME, STORED)),
+            )
+        } else {
+            (None, None)
+        };
         let timestamp_field_path = if let Some(timestamp_field_name) = &doc_mapping.timestamp_field
         {
             validate_timestamp_field(timestamp_field_name, &field_mappings)?;
@@ -285,6 +302,8 @@ impl TryFrom<DocMapperBuilder> for DocMapper {
             source_field,
             dynamic_field,
             document_size_field,
+            nested_path_field,
+            nested_source_field,
             default_search_field_names,
             timestamp_field_name: doc_mapping.timestamp_field,
             timestamp_field_path,
@@ -423,6 +442,23 @@ fn extract_single_obj(
     }
 }
 
+/// Merges `json_obj` into `doc_json`, recursing into the objects present in both.
+fn merge_json_obj(
+    doc_json: &mut serde_json::Map<String, JsonValue>,
+    json_obj: serde_json::Map<String, JsonValue>,
+) {
+    for (key, value) in json_obj {
+        match (doc_json.get_mut(&key), value) {
+            (Some(JsonValue::Object(doc_json_obj)), JsonValue::Object(value_obj)) => {
+                merge_json_obj(doc_json_obj, value_obj);
+            }
+            (_, value) => {
+                doc_json.insert(key, value);
+            }
+        }
+    }
+}
+
 impl DocMapper {
     /// Returns the unique identifier of the doc mapping.
     pub fn doc_mapping_uid(&self) -> DocMappingUid {
@@ -492,16 +528,34 @@ impl DocMapper {
 
     /// Transforms a JSON object into a tantivy [`Document`] according to the rules
     /// defined for the `DocMapper`.
+    ///
+    /// The hidden documents of the `nested` fields are left out, see
+    /// [`DocMapper::doc_with_nested_docs_from_json_obj`].
     pub fn doc_from_json_obj(
         &self,
         json_obj: JsonObject,
         document_len: u64,
     ) -> Result<(Partition, Document), DocParsingError> {
+        let (partition, document, _nested_docs) =
+            self.doc_with_nested_docs_from_json_obj(json_obj, document_len)?;
+        Ok((partition, document))
+    }
+
+    /// Transforms a JSON object into a tantivy [`Document`] and the hidden documents of its
+    /// `nested` fields.
+    ///
+    /// The nested documents must be added to the index, in order, right before the document.
+    pub fn doc_with_nested_docs_from_json_obj(
+        &self,
+        json_obj: JsonObject,
+        document_len: u64,
+    ) -> Result<(Partition, Document, Vec<Document>), DocParsingError> {
         let partition: Partition = self.partition_key.eval_hash(&json_obj);
 
         let mut dynamic_json_obj = serde_json::Map::default();
         let mut field_path = Vec::new();
         let mut document = Document::default();
+        let mut nested_docs = NestedDocs::default();
 
         if let Some(source_field) = self.source_field {
             document.add_object(
@@ -521,43 +575,83 @@ impl DocMapper {
             &mut document,
             &mut field_path,
             &mut dynamic_json_obj,
+            &mut nested_docs,
         )?;
+        self.add_dynamic_fields(&mut document, dynamic_json_obj);
 
-        if let Some(dynamic_field) = self.dynamic_field {
-            if !dynamic_json_obj.is_empty() {
-                if !self.concatenate_dynamic_fields.is_empty() {
-                    let json_obj_values =
-                        JsonValueIterator::new(serde_json::Value::Object(dynamic_json_obj.clone()))
-                            .flat_map(map_primitive_json_to_tantivy);
-
-                    for value in json_obj_values {
-                        for concatenate_dynamic_field in self.concatenate_dynamic_fields.iter() {
-                            document.add_field_value(*concatenate_dynamic_field, &value);
-                        }
-                    }
-                }
+        if let Some(document_size_field) = self.document_size_field {
+            document.add_u64(document_size_field, document_len);
+        }
+        if let Some(nested_source_field) = self.nested_source_field {
+            if !nested_docs.source.is_empty() {
                 document.add_object(
-                    dynamic_field,
-                    dynamic_json_obj
+                    nested_source_field,
+                    nested_docs
+                        .source
                         .into_iter()
                         .map(|(key, val)| (key, TantivyValue::from(val)))
                         .collect(),
                 );
             }
         }
+        self.add_field_presence(&mut document);
+
+        let mut nested_documents = Vec::with_capacity(nested_docs.docs.len());
+        if let Some(nested_path_field) = self.nested_path_field {
+            for nested_doc in nested_docs.docs {
+                let NestedDoc {
+                    path,
+                    mut document,
+                    dynamic_json_obj,
+                } = nested_doc;
+                self.add_dynamic_fields(&mut document, dynamic_json_obj);
+                self.add_field_presence(&mut document);
+                document.add_text(nested_path_field, path);
+                nested_documents.push(document);
+            }
+        }
+        Ok((partition, document, nested_documents))
+    }
 
-        if let Some(document_size_field) = self.document_size_field {
-            document.add_u64(document_size_field, document_len);
+    fn add_dynamic_fields(
+        &self,
+        document: &mut Document,
+        dynamic_json_obj: serde_json::Map<String, JsonValue>,
+    ) {
+        let Some(dynamic_field) = self.dynamic_field else {
+            return;
+        };
+        if dynamic_json_obj.is_empty() {
+            return;
+        }
+        if !self.concatenate_dynamic_fields.is_empty() {
+            let json_obj_values =
+                JsonValueIterator::new(serde_json::Value::Object(dynamic_json_obj.clone()))
+                    .flat_map(map_primitive_json_to_tantivy);
+
+            for value in json_obj_values {
+                for concatenate_dynamic_field in self.concatenate_dynamic_fields.iter() {
+                    document.add_field_value(*concatenate_dynamic_field, &value);
+                }
+            }
         }
+        document.add_object(
+            dynamic_field,
+            dynamic_json_obj
+                .into_iter()
+                .map(|(key, val)| (key, TantivyValue::from(val)))
+                .collect(),
+        );
+    }
 
+    fn add_field_presence(&self, document: &mut Document) {
         if self.index_field_presence {
             let field_presence_hashes: FnvHashSet<u64> =
-                populate_field_presence(&document, &self.schema, true);
+                populate_field_presence(document, &self.schema, true);
             for field_presence_hash in field_presence_hashes {
                 document.add_field_value(FIELD_PRESENCE_FIELD, &field_presence_hash);
             }
         }
-        Ok((partition, document))
     }
 
     /// Converts a tantivy named Document to the json format.
@@ -579,6 +673,11 @@ impl DocMapper {
         let mut field_path: Vec<&str> = Vec::new();
         self.field_mappings
             .populate_json(&mut named_doc, &mut field_path, &mut doc_json);
+        if let Some(nested_source_json) =
+            extract_single_obj(&mut named_doc, NESTED_SOURCE_FIELD_NAME)?
+        {
+            merge_json_obj(&mut doc_json, nested_source_json);
+        }
         if let Some(source_json) = extract_single_obj(&mut named_doc, SOURCE_FIELD_NAME)? {
             doc_json.insert(
                 SOURCE_FIELD_NAME.to_string(),
@@ -648,6 +747,19 @@ impl DocMapper {
         )
     }
 
+    /// Returns the query matching the documents to delete.
+    ///
+    /// Unlike [`DocMapper::query`], the query also matches the nested documents of the matching
+    /// documents so that they get deleted along with them.
+    pub fn delete_query(
+        &self,
+        split_schema: Schema,
+        query_ast: &QueryAst,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        let (query, _warmup_info) = self.query(split_schema, query_ast, false)?;
+        Ok(Box::new(WithNestedDocsQuery { query }))
+    }
+
     /// Returns the list of search fields to search into, when no field is specified.
     /// (See `UserInputQuery`).
     pub fn default_search_fields(&self) -> &[String] {
@@ -709,8 +821,9 @@ mod tests {
     use std::iter::zip;
 
     use itertools::Itertools;
+    use quickwit_common::shared_consts::NESTED_PATH_FIELD_NAME;
     use quickwit_common::PathHasher;
-    use quickwit_query::query_ast::query_ast_from_user_text;
+    use quickwit_query::query_ast::{query_ast_from_user_text, QueryAst};
     use serde_json::{self, json, Value as JsonValue};
     use tantivy::schema::{
         FieldType, IndexRecordOption, OwnedValue as TantivyValue, OwnedValue, Type, Value,
@@ -719,8 +832,8 @@ mod tests {
     use super::DocMapper;
     use crate::doc_mapper::field_mapping_entry::{DEFAULT_TOKENIZER_NAME, RAW_TOKENIZER_NAME};
     use crate::{
-        DocMapperBuilder, DocParsingError, DOCUMENT_SIZE_FIELD_NAME, DYNAMIC_FIELD_NAME,
-        FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
+        DocMapperBuilder, DocParsingError, JsonObject, DOCUMENT_SIZE_FIELD_NAME,
+        DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
     };
 
     fn example_json_doc_value() -> JsonValue {
@@ -2500,4 +2613,152 @@ mod tests {
 
         assert_eq!(new_mapper.doc_to_json(named_doc.0).unwrap(), doc);
     }
+
+    fn nested_doc_mapper_for_test() -> DocMapper {
+        let doc_mapping = r#"{
+            "field_mappings": [
+                {"name": "title", "type": "text", "tokenizer": "raw"},
+                {
+                    "name": "items",
+                    "type": "nested",
+                    "field_mappings": [
+                        {"name": "name", "type": "text", "tokenizer": "raw"},
+                        {"name": "qty", "type": "u64", "fast": true},
+                        {
+                            "name": "variants",
+                            "type": "nested",
+                            "field_mappings": [
+                                {"name": "color", "type": "text", "tokenizer": "raw"}
+                            ]
+                        }
+                    ]
+                }
+            ]
+        }"#;
+        serde_json::from_str::<DocMapper>(doc_mapping).unwrap()
+    }
+
+    #[test]
+    fn test_nested_field_doc_block() {
+        let doc_mapper = nested_doc_mapper_for_test();
+        let schema = doc_mapper.schema();
+        let JsonValue::Object(json_doc) = json!({
+            "title": "a",
+            "items": [
+                {"name": "foo", "qty": 3, "variants": [{"color": "red"}], "extra": 1},
+                {"name": "bar", "qty": 10}
+            ]
+        }) else {
+            panic!();
+        };
+        let (_partition, document, nested_docs) = doc_mapper
+            .doc_with_nested_docs_from_json_obj(json_doc.clone(), 0)
+            .unwrap();
+        let nested_path_field = schema.get_field(NESTED_PATH_FIELD_NAME).unwrap();
+        let nested_paths: Vec<&str> = nested_docs
+            .iter()
+            .map(|nested_doc| {
+                nested_doc
+                    .get_first(nested_path_field)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+            })
+            .collect();
+        // Nested documents come before their parent.
+        assert_eq!(nested_paths, ["items.variants", "items", "items"]);
+
+        let item_name_field = schema.get_field("items.name").unwrap();
+        assert!(document.get_first(item_name_field).is_none());
+        assert!(document.get_first(nested_path_field).is_none());
+        assert_eq!(
+            nested_docs[1]
+                .get_first(item_name_field)
+                .unwrap()
+                .as_str()
+                .unwrap(),
+            "foo"
+        );
+        let dynamic_field = schema.get_field(DYNAMIC_FIELD_NAME).unwrap();
+        assert!(nested_docs[1].get_first(dynamic_field).is_some());
+
+        let named_doc = document.to_named_doc(&schema);
+        assert_eq!(doc_mapper.doc_to_json(named_doc.0).unwrap(), json_doc);
+    }
+
+    #[test]
+    fn test_nested_field_invalid_value() {
+        let doc_mapper = nested_doc_mapper_for_test();
+        let error = doc_mapper
+            .doc_from_json_str(r#"{"title": "a", "items": ["foo"]}"#)
+            .unwrap_err();
+        assert!(matches!(error, DocParsingError::ValueError(path, _) if path == "items"));
+    }
+
+    #[test]
+    fn test_nested_field_query_and_delete() {
+        use quickwit_query::block_join::num_root_docs;
+        use quickwit_query::query_ast::{BoolQuery, NestedQuery, TermQuery};
+        use tantivy::collector::Count;
+        use tantivy::Index;
+
+        let doc_mapper = nested_doc_mapper_for_test();
+        let index = Index::create_in_ram(doc_mapper.schema());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for json_doc in [
+            r#"{"title": "a", "items": [{"name": "foo", "qty": 3}, {"name": "bar", "qty": 10}]}"#,
+            r#"{"title": "b", "items": [{"name": "foo", "qty": 10}]}"#,
+            r#"{"title": "c"}"#,
+        ] {
+            let json_obj: JsonObject = serde_json::from_str(json_doc).unwrap();
+            let (_partition, document, nested_docs) = doc_mapper
+                .doc_with_nested_docs_from_json_obj(json_obj, 0)
+                .unwrap();
+            for nested_doc in nested_docs {
+                index_writer.add_document(nested_doc).unwrap();
+            }
+            index_writer.add_document(document).unwrap();
+        }
+        index_writer.commit().unwrap();
+
+        let count = |query_ast: &QueryAst| {
+            let searcher = index.reader().unwrap().searcher();
+            let (query, _) = doc_mapper.query(index.schema(), query_ast, true).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        let term_query = |field: &str, value: &str| -> QueryAst {
+            TermQuery {
+                field: field.to_string(),
+                value: value.to_string(),
+            }
+            .into()
+        };
+        let items_query: QueryAst = BoolQuery {
+            must: vec![
+                term_query("items.name", "foo"),
+                term_query("items.qty", "10"),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(count(&QueryAst::MatchAll), 3);
+        assert_eq!(count(&items_query), 0);
+        let nested_query: QueryAst = NestedQuery {
+            path: "items".to_string(),
+            query: Box::new(items_query),
+            score_mode: Default::default(),
+        }
+        .into();
+        assert_eq!(count(&nested_query), 1);
+
+        let delete_query = doc_mapper
+            .delete_query(index.schema(), &term_query("title", "a"))
+            .unwrap();
+        index_writer.delete_query(delete_query).unwrap();
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        // The nested documents of the deleted document are deleted along with it.
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(num_root_docs(searcher.segment_reader(0)).unwrap(), 2);
+    }
 }
@@ -742,6 +742,13 @@ fn deserialize_mapping_type(
             }
             return Ok(FieldMappingType::Object(object_options));
         }
+        QuickwitFieldType::Nested => {
+            let object_options: QuickwitObjectOptions = serde_json::from_value(json)?;
+            if object_options.field_mappings.is_empty() {
+                anyhow::bail!("nested type must have at least one field mapping");
+            }
+            return Ok(FieldMappingType::Nested(object_options));
+        }
         QuickwitFieldType::Concatenate => {
             let concatenate_options: QuickwitConcatenateOptions = serde_json::from_value(json)?;
             if concatenate_options.concatenate_fields.is_empty()
@@ -843,7 +850,9 @@ fn typed_mapping_to_json_params(
         FieldMappingType::IpAddr(options, _) => serialize_to_map(&options),
         FieldMappingType::DateTime(date_time_options, _) => serialize_to_map(&date_time_options),
         FieldMappingType::Json(json_options, _) => serialize_to_map(&json_options),
-        FieldMappingType::Object(object_options) => serialize_to_map(&object_options),
+        FieldMappingType::Object(object_options) | FieldMappingType::Nested(object_options) => {
+            serialize_to_map(&object_options)
+        }
         FieldMappingType::Concatenate(concatenate_options) => {
             serialize_to_map(&concatenate_options)
         }
@@ -1276,6 +1285,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_deserialize_nested_mapping() {
+        let mapping_entry = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "items",
+                "type": "nested",
+                "field_mappings": [
+                    {
+                        "name": "name",
+                        "type": "text"
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let FieldMappingType::Nested(options) = &mapping_entry.mapping_type else {
+            panic!("wrong property type");
+        };
+        assert_eq!(options.field_mappings.len(), 1);
+        let mapping_entry_json = serde_json::to_value(&mapping_entry).unwrap();
+        assert_eq!(mapping_entry_json["type"], "nested");
+
+        let error = serde_json::from_str::<FieldMappingEntry>(
+            r#"{"name": "items", "type": "nested", "field_mappings": []}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "error while parsing field `items`: nested type must have at least one field mapping"
+        );
+    }
+
     #[test]
     fn test_deserialize_mapping_with_unknown_type() {
         let result = serde_json::from_str::<FieldMappingEntry>(
@@ -46,6 +46,8 @@ pub enum FieldMappingType {
     Json(QuickwitJsonOptions, Cardinality),
     /// Object mapping type configuration.
     Object(QuickwitObjectOptions),
+    /// Nested mapping type configuration. Each object is indexed as a hidden document.
+    Nested(QuickwitObjectOptions),
     /// Concatenate field mapping type configuration.
     Concatenate(QuickwitConcatenateOptions),
 }
@@ -66,6 +68,7 @@ impl FieldMappingType {
             FieldMappingType::Object(_) => {
                 return QuickwitFieldType::Object;
             }
+            FieldMappingType::Nested(_) => return QuickwitFieldType::Nested,
             FieldMappingType::Concatenate(_) => return QuickwitFieldType::Concatenate,
         };
         match cardinality {
@@ -79,6 +82,7 @@ impl FieldMappingType {
 pub enum QuickwitFieldType {
     Simple(Type),
     Object,
+    Nested,
     Concatenate,
     Array(Type),
 }
@@ -88,6 +92,7 @@ impl QuickwitFieldType {
         match self {
             QuickwitFieldType::Simple(typ) => primitive_type_to_str(typ).to_string(),
             QuickwitFieldType::Object => "object".to_string(),
+            QuickwitFieldType::Nested => "nested".to_string(),
             QuickwitFieldType::Array(typ) => format!("array<{}>", primitive_type_to_str(typ)),
             QuickwitFieldType::Concatenate => "concatenate".to_string(),
         }
@@ -97,6 +102,9 @@ impl QuickwitFieldType {
         if type_str == "object" {
             return Some(QuickwitFieldType::Object);
         }
+        if type_str == "nested" {
+            return Some(QuickwitFieldType::Nested);
+        }
         if type_str == "concatenate" {
             return Some(QuickwitFieldType::Concatenate);
         }
@@ -161,6 +169,7 @@ mod tests {
         test_parse_type_aux("text", Some(QuickwitFieldType::Simple(Type::Str)));
         test_parse_type_aux("object", Some(QuickwitFieldType::Object));
         test_parse_type_aux("object2", None);
+        test_parse_type_aux("nested", Some(QuickwitFieldType::Nested));
         test_parse_type_aux("bool", Some(QuickwitFieldType::Simple(Type::Bool)));
         test_parse_type_aux("ip", Some(QuickwitFieldType::Simple(Type::IpAddr)));
     }
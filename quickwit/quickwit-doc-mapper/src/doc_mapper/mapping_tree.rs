@@ -725,6 +725,27 @@ impl NumVal for f64 {
     }
 }
 
+/// Hidden document built from an object of a `nested` field.
+pub(crate) struct NestedDoc {
+    /// Path of the `nested` field.
+    pub path: String,
+    pub document: Document,
+    /// Unmapped fields of the object, in dynamic mode.
+    pub dynamic_json_obj: serde_json::Map<String, JsonValue>,
+}
+
+/// Collects the hidden documents built from the `nested` fields of a document.
+#[derive(Default)]
+pub(crate) struct NestedDocs {
+    /// Hidden documents, in the order in which they must be indexed: the nested documents of an
+    /// object come before the document of the object itself.
+    pub docs: Vec<NestedDoc>,
+    /// Values of the outermost `nested` fields, stored along the root document so that it can be
+    /// displayed as it was ingested.
+    pub source: serde_json::Map<String, JsonValue>,
+    depth: usize,
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct MappingNode {
     pub branches: fnv::FnvHashMap<String, MappingTree>,
@@ -763,7 +784,7 @@ impl MappingNode {
         match (child_tree, sub_field_path.is_empty()) {
             (_, true) => Some(child_tree.clone().into()),
             (MappingTree::Leaf(_), false) => None,
-            (MappingTree::Node(child_node), false) => {
+            (MappingTree::Node(child_node), false) | (MappingTree::Nested(child_node), false) => {
                 child_node.internal_find_field_mapping_type(sub_field_path)
             }
         }
@@ -793,9 +814,20 @@ impl MappingNode {
             }
             (MappingTree::Leaf(leaf), true) => Some([leaf].into_iter()),
             (MappingTree::Node(_), true) => None,
+            // The fields of a nested field belong to hidden documents.
+            (MappingTree::Nested(_), _) => None,
         }
     }
 
+    /// Returns true if the tree contains at least one `nested` field.
+    pub fn has_nested_fields(&self) -> bool {
+        self.branches.values().any(|child_tree| match child_tree {
+            MappingTree::Leaf(_) => false,
+            MappingTree::Node(child_node) => child_node.has_nested_fields(),
+            MappingTree::Nested(_) => true,
+        })
+    }
+
     #[cfg(test)]
     pub fn num_fields(&self) -> usize {
         self.branches.len()
@@ -847,11 +879,19 @@ impl MappingNode {
         document: &mut Document,
         path: &mut Vec<String>,
         dynamic_json_obj: &mut serde_json::Map<String, JsonValue>,
+        nested_docs: &mut NestedDocs,
     ) -> Result<(), DocParsingError> {
         for (field_name, val) in json_obj {
             if let Some(child_tree) = self.branches.get(&field_name) {
                 path.push(field_name);
-                child_tree.doc_from_json(val, mode, document, path, dynamic_json_obj)?;
+                child_tree.doc_from_json(
+                    val,
+                    mode,
+                    document,
+                    path,
+                    dynamic_json_obj,
+                    nested_docs,
+                )?;
                 path.pop();
             } else {
                 match mode {
@@ -895,6 +935,9 @@ impl From<MappingTree> for FieldMappingType {
             MappingTree::Node(node) => FieldMappingType::Object(QuickwitObjectOptions {
                 field_mappings: node.into(),
             }),
+            MappingTree::Nested(node) => FieldMappingType::Nested(QuickwitObjectOptions {
+                field_mappings: node.into(),
+            }),
         }
     }
 }
@@ -925,6 +968,7 @@ impl From<MappingNode> for Vec<FieldMappingEntry> {
 pub(crate) enum MappingTree {
     Leaf(MappingLeaf),
     Node(MappingNode),
+    Nested(MappingNode),
 }
 
 impl MappingTree {
@@ -948,6 +992,26 @@ impl MappingTree {
                     ))
                 }
             }
+            MappingTree::Nested(mapping_node) => {
+                let json_values = match json_value {
+                    BorrowedJsonValue::Array(json_values) => &json_values[..],
+                    BorrowedJsonValue::Null => &[],
+                    _ => std::slice::from_ref(json_value),
+                };
+                for json_value in json_values {
+                    if let Some(json_obj) = json_value.as_object() {
+                        mapping_node.validate_from_json(json_obj, strict_mode, field_path)?;
+                    } else if !json_value.is_null() {
+                        return Err(DocParsingError::ValueError(
+                            field_path.join("."),
+                            format!(
+                                "expected an JSON object or array of objects, got {json_value}"
+                            ),
+                        ));
+                    }
+                }
+                Ok(())
+            }
         }
     }
 
@@ -958,6 +1022,7 @@ impl MappingTree {
         document: &mut Document,
         path: &mut Vec<String>,
         dynamic_json_obj: &mut serde_json::Map<String, JsonValue>,
+        nested_docs: &mut NestedDocs,
     ) -> Result<(), DocParsingError> {
         match self {
             MappingTree::Leaf(mapping_leaf) => {
@@ -965,7 +1030,14 @@ impl MappingTree {
             }
             MappingTree::Node(mapping_node) => {
                 if let JsonValue::Object(json_obj) = json_value {
-                    mapping_node.doc_from_json(json_obj, mode, document, path, dynamic_json_obj)
+                    mapping_node.doc_from_json(
+                        json_obj,
+                        mode,
+                        document,
+                        path,
+                        dynamic_json_obj,
+                        nested_docs,
+                    )
                 } else {
                     Err(DocParsingError::ValueError(
                         path.join("."),
@@ -973,6 +1045,51 @@ impl MappingTree {
                     ))
                 }
             }
+            MappingTree::Nested(mapping_node) => {
+                if nested_docs.depth == 0 && !json_value.is_null() {
+                    let field_path: Vec<&str> = path.iter().map(String::as_str).collect();
+                    insert_json_val(&field_path, json_value.clone(), &mut nested_docs.source);
+                }
+                let json_values = match json_value {
+                    JsonValue::Array(json_values) => json_values,
+                    JsonValue::Null => Vec::new(),
+                    json_value => vec![json_value],
+                };
+                let nested_path =
+                    field_name_for_field_path(&path.iter().map(String::as_str).collect_vec());
+                for json_value in json_values {
+                    let json_obj = match json_value {
+                        JsonValue::Object(json_obj) => json_obj,
+                        JsonValue::Null => continue,
+                        _ => {
+                            return Err(DocParsingError::ValueError(
+                                path.join("."),
+                                format!(
+                                    "expected an JSON object or array of objects, got {json_value}"
+                                ),
+                            ));
+                        }
+                    };
+                    let mut nested_document = Document::default();
+                    let mut nested_dynamic_json_obj = serde_json::Map::default();
+                    nested_docs.depth += 1;
+                    mapping_node.doc_from_json(
+                        json_obj,
+                        mode,
+                        &mut nested_document,
+                        path,
+                        &mut nested_dynamic_json_obj,
+                        nested_docs,
+                    )?;
+                    nested_docs.depth -= 1;
+                    nested_docs.docs.push(NestedDoc {
+                        path: nested_path.clone(),
+                        document: nested_document,
+                        dynamic_json_obj: nested_dynamic_json_obj,
+                    });
+                }
+                Ok(())
+            }
         }
     }
 
@@ -989,6 +1106,8 @@ impl MappingTree {
             MappingTree::Node(mapping_node) => {
                 mapping_node.populate_json(named_doc, field_path, doc_json);
             }
+            // The values of nested fields are stored in a dedicated field of the root document.
+            MappingTree::Nested(_) => {}
         }
     }
 }
@@ -1317,6 +1436,20 @@ fn build_mapping_from_field_type<'a>(
                 concatenate_dynamic_fields,
             ))
         }
+        FieldMappingType::Nested(entries) => {
+            let MappingNodeRoot {
+                field_mappings,
+                concatenate_dynamic_fields,
+            } = build_mapping_tree_from_entries(
+                &entries.field_mappings,
+                field_path,
+                schema_builder,
+            )?;
+            Ok((
+                MappingTree::Nested(field_mappings),
+                concatenate_dynamic_fields,
+            ))
+        }
         FieldMappingType::Concatenate(_) => {
             bail!("Concatenate shouldn't reach build_mapping_from_field_type: this is a bug")
         }
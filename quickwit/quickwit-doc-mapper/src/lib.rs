@@ -41,7 +41,7 @@ use doc_mapper::{
 };
 pub use doc_mapping::{DocMapping, Mode, ModeType};
 pub use error::{DocParsingError, QueryParserError};
-use quickwit_common::shared_consts::FIELD_PRESENCE_FIELD_NAME;
+use quickwit_common::shared_consts::{FIELD_PRESENCE_FIELD_NAME, NESTED_PATH_FIELD_NAME};
 use quickwit_proto::types::DocMappingUid;
 pub use routing_expression::RoutingExpr;
 
@@ -54,11 +54,17 @@ pub const DYNAMIC_FIELD_NAME: &str = "_dynamic";
 /// Field name reserved for storing the length of source document.
 pub const DOCUMENT_SIZE_FIELD_NAME: &str = "_doc_length";
 
+/// Field name reserved for storing the values of the `nested` fields of a document, which are
+/// indexed in hidden documents.
+pub const NESTED_SOURCE_FIELD_NAME: &str = "_nested_source";
+
 /// Quickwit reserved field names.
 const QW_RESERVED_FIELD_NAMES: &[&str] = &[
     DOCUMENT_SIZE_FIELD_NAME,
     DYNAMIC_FIELD_NAME,
     FIELD_PRESENCE_FIELD_NAME,
+    NESTED_PATH_FIELD_NAME,
+    NESTED_SOURCE_FIELD_NAME,
     SOURCE_FIELD_NAME,
 ];
 
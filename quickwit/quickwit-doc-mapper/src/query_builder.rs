@@ -16,9 +16,12 @@ use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 use std::ops::Bound;
 
+use quickwit_common::shared_consts::NESTED_PATH_FIELD_NAME;
+use quickwit_query::block_join::RootDocsQuery;
 use quickwit_query::query_ast::{
-    FieldPresenceQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, QueryAstVisitor, RangeQuery,
-    RegexQuery, TermSetQuery, WildcardQuery,
+    FieldPresenceQuery, FullTextMode, FullTextQuery, FunctionScoreQuery, FuzzyQuery,
+    PhrasePrefixQuery, QueryAst, QueryAstVisitor, RangeQuery, RegexQuery, RuntimeFieldQuery,
+    ScoreFunctionKind, TermSetQuery, WildcardQuery,
 };
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_query::{find_field_or_hit_dynamic, InvalidQuery};
@@ -45,6 +48,56 @@ impl<'a> QueryAstVisitor<'a> for RangeQueryFields {
     }
 }
 
+#[derive(Default)]
+struct ScoreFunctionFields {
+    score_function_field_names: HashSet<String>,
+}
+
+impl<'a> QueryAstVisitor<'a> for ScoreFunctionFields {
+    type Err = Infallible;
+
+    fn visit_function_score(
+        &mut self,
+        function_score_query: &'a FunctionScoreQuery,
+    ) -> Result<(), Infallible> {
+        for function in &function_score_query.functions {
+            match &function.function {
+                ScoreFunctionKind::Weight => {}
+                ScoreFunctionKind::FieldValueFactor(field_value_factor) => {
+                    self.score_function_field_names
+                        .insert(field_value_factor.field.clone());
+                }
+                ScoreFunctionKind::Decay(decay) => {
+                    self.score_function_field_names.insert(decay.field.clone());
+                }
+            }
+            if let Some(filter) = &function.filter {
+                self.visit(filter)?;
+            }
+        }
+        self.visit(&function_score_query.query)
+    }
+}
+
+/// Fast fields runtime fields are computed from.
+#[derive(Default)]
+struct RuntimeFieldFields {
+    runtime_field_field_names: HashSet<String>,
+}
+
+impl<'a> QueryAstVisitor<'a> for RuntimeFieldFields {
+    type Err = Infallible;
+
+    fn visit_runtime_field(
+        &mut self,
+        runtime_field_query: &'a RuntimeFieldQuery,
+    ) -> Result<(), Infallible> {
+        self.runtime_field_field_names
+            .extend(runtime_field_query.runtime_field.fields().iter().cloned());
+        Ok(())
+    }
+}
+
 struct ExistsQueryFastFields {
     fields: HashSet<FastFieldWarmupInfo>,
     schema: Schema,
@@ -92,6 +145,14 @@ pub(crate) fn build_query(
     // This cannot fail. The error type is Infallible.
     let _: Result<(), Infallible> = range_query_fields.visit(query_ast);
 
+    let mut score_function_fields = ScoreFunctionFields::default();
+    // This cannot fail. The error type is Infallible.
+    let _: Result<(), Infallible> = score_function_fields.visit(query_ast);
+
+    let mut runtime_field_fields = RuntimeFieldFields::default();
+    // This cannot fail. The error type is Infallible.
+    let _: Result<(), Infallible> = runtime_field_fields.visit(query_ast);
+
     let mut exists_query_fields = ExistsQueryFastFields {
         fields: HashSet::new(),
         schema: schema.clone(),
@@ -108,15 +169,39 @@ pub(crate) fn build_query(
                 name,
                 with_subfields: false,
             });
+    let score_function_fast_fields = score_function_fields
+        .score_function_field_names
+        .into_iter()
+        .map(|name| FastFieldWarmupInfo {
+            name,
+            with_subfields: false,
+        });
+    let runtime_field_fast_fields = runtime_field_fields
+        .runtime_field_field_names
+        .into_iter()
+        .map(|name| FastFieldWarmupInfo {
+            name,
+            with_subfields: false,
+        });
     fast_fields.extend(range_query_fast_fields);
+    fast_fields.extend(score_function_fast_fields);
+    fast_fields.extend(runtime_field_fast_fields);
     fast_fields.extend(exists_query_fields.fields);
 
-    let query = query_ast.build_tantivy_query(
+    let mut query = query_ast.build_tantivy_query(
         &schema,
         tokenizer_manager,
         search_fields,
         with_validation,
     )?;
+    if schema.get_field(NESTED_PATH_FIELD_NAME).is_ok() {
+        // Nested documents are hidden: they can only be reached through nested queries.
+        query = Box::new(RootDocsQuery { query });
+        fast_fields.insert(FastFieldWarmupInfo {
+            name: NESTED_PATH_FIELD_NAME.to_string(),
+            with_subfields: false,
+        });
+    }
 
     let term_set_query_fields = extract_term_set_query_fields(query_ast, &schema)?;
     let (term_ranges_grouped_by_field, automatons_grouped_by_field) =
@@ -156,6 +241,16 @@ impl<'a> ExtractTermSetFields<'a> {
             schema,
         }
     }
+
+    /// Missing fields are ignored: the query will be nullified or rejected when building the
+    /// tantivy query.
+    fn add_field(&mut self, field_name: &str) {
+        if let Some((field, _field_entry, _path)) =
+            find_field_or_hit_dynamic(field_name, self.schema)
+        {
+            self.term_dict_fields_to_warm_up.insert(field);
+        }
+    }
 }
 
 impl<'a> QueryAstVisitor<'a> for ExtractTermSetFields<'_> {
@@ -173,6 +268,19 @@ impl<'a> QueryAstVisitor<'a> for ExtractTermSetFields<'_> {
         }
         Ok(())
     }
+
+    // Fuzzy term queries walk the whole term dictionary of the field.
+    fn visit_fuzzy(&mut self, fuzzy_query: &'a FuzzyQuery) -> anyhow::Result<()> {
+        self.add_field(&fuzzy_query.field);
+        Ok(())
+    }
+
+    fn visit_full_text(&mut self, full_text_query: &'a FullTextQuery) -> anyhow::Result<()> {
+        if matches!(full_text_query.params.mode, FullTextMode::Fuzzy { .. }) {
+            self.add_field(&full_text_query.field);
+        }
+        Ok(())
+    }
 }
 
 fn extract_term_set_query_fields(
@@ -328,8 +436,8 @@ mod test {
 
     use quickwit_common::shared_consts::FIELD_PRESENCE_FIELD_NAME;
     use quickwit_query::query_ast::{
-        query_ast_from_user_text, FullTextMode, FullTextParams, PhrasePrefixQuery, QueryAstVisitor,
-        UserInputQuery,
+        query_ast_from_user_text, FullTextMode, FullTextParams, PhrasePrefixQuery, QueryAst,
+        QueryAstVisitor, UserInputQuery,
     };
     use quickwit_query::{
         create_default_quickwit_tokenizer_manager, BooleanOperand, MatchAllOrNone,
@@ -338,6 +446,7 @@ mod test {
     use tantivy::Term;
 
     use super::{build_query, ExtractPrefixTermRanges};
+    use crate::doc_mapper::FastFieldWarmupInfo;
     use crate::{TermRange, DYNAMIC_FIELD_NAME, SOURCE_FIELD_NAME};
 
     enum TestExpectation<'a> {
@@ -817,6 +926,56 @@ mod test {
         )
         .unwrap();
         assert!(warmup_info.term_dict_fields.is_empty());
+
+        let fuzzy_query = query_ast_from_user_text("desc:helo~1", None)
+            .parse_user_query(&[])
+            .unwrap();
+        let (_, warmup_info) = build_query(
+            &fuzzy_query,
+            make_schema(true),
+            &create_default_quickwit_tokenizer_manager(),
+            &[],
+            true,
+        )
+        .unwrap();
+        assert_eq!(warmup_info.term_dict_fields.len(), 1);
+        assert!(warmup_info
+            .term_dict_fields
+            .contains(&tantivy::schema::Field::from_field_id(2)));
+    }
+
+    #[test]
+    fn test_build_query_function_score_warmup_info() {
+        let query_ast: QueryAst = serde_json::from_value(serde_json::json!({
+            "type": "function_score",
+            "query": {"type": "match_all"},
+            "functions": [
+                {"function": {"field_value_factor": {"field": "u64_fast"}}},
+                {
+                    "filter": {"type": "term", "field": "desc", "value": "hello"},
+                    "function": {"decay": {"field": "dt", "curve": "gauss", "scale": "1d"}}
+                }
+            ]
+        }))
+        .unwrap();
+        let (_, warmup_info) = build_query(
+            &query_ast,
+            make_schema(true),
+            &create_default_quickwit_tokenizer_manager(),
+            &[],
+            true,
+        )
+        .unwrap();
+        for fast_field_name in ["u64_fast", "dt"] {
+            assert!(warmup_info.fast_fields.contains(&FastFieldWarmupInfo {
+                name: fast_field_name.to_string(),
+                with_subfields: false,
+            }));
+        }
+        // The terms of the filters need to be warmed up too.
+        assert!(warmup_info
+            .terms_grouped_by_field
+            .contains_key(&tantivy::schema::Field::from_field_id(2)));
     }
 
     #[test]
@@ -15,7 +15,7 @@
 use std::collections::BTreeSet;
 use std::fmt::Display;
 
-use quickwit_query::query_ast::QueryAst;
+use quickwit_query::query_ast::{FullTextMode, QueryAst};
 use serde::{Deserialize, Serialize};
 use tantivy::query_grammar::Occur;
 
@@ -83,6 +83,11 @@ fn extract_unsimplified_tags_filter_ast(query_ast: QueryAst) -> UnsimplifiedTagF
                 .collect();
             UnsimplifiedTagFilterAst::Or(children)
         }
+        QueryAst::FullText(full_text_query)
+            if matches!(full_text_query.params.mode, FullTextMode::Fuzzy { .. }) =>
+        {
+            UnsimplifiedTagFilterAst::Uninformative
+        }
         QueryAst::FullText(full_text_query) => {
             // TODO This is a bug in a sense.
             // A phrase is supposed to go through the tokenizer.
@@ -109,11 +114,19 @@ fn extract_unsimplified_tags_filter_ast(query_ast: QueryAst) -> UnsimplifiedTagF
             }
         }
         QueryAst::Boost { underlying, .. } => extract_unsimplified_tags_filter_ast(*underlying),
+        // Documents matching a function score query always match its inner query.
+        QueryAst::FunctionScore(function_score_query) => {
+            extract_unsimplified_tags_filter_ast(*function_score_query.query)
+        }
         QueryAst::UserInput(_user_text_query) => {
             panic!("Extract unsimplified should only be called on AST without UserInputQuery.");
         }
         QueryAst::FieldPresence(_) => UnsimplifiedTagFilterAst::Uninformative,
         QueryAst::Regex(_) => UnsimplifiedTagFilterAst::Uninformative,
+        QueryAst::Fuzzy(_) => UnsimplifiedTagFilterAst::Uninformative,
+        // The inner query of a nested query applies to nested documents, that have no tags.
+        QueryAst::Nested(_) => UnsimplifiedTagFilterAst::Uninformative,
+        QueryAst::RuntimeField(_) => UnsimplifiedTagFilterAst::Uninformative,
     }
 }
 
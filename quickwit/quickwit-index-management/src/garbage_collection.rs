@@ -22,6 +22,7 @@ use futures::{Future, StreamExt};
 use itertools::Itertools;
 use quickwit_common::metrics::IntCounter;
 use quickwit_common::pretty::PrettySample;
+use quickwit_common::uri::Uri;
 use quickwit_common::{rate_limited_info, Progress};
 use quickwit_metastore::{
     ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
@@ -32,7 +33,7 @@ use quickwit_proto::metastore::{
     MetastoreService, MetastoreServiceClient,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
-use quickwit_storage::{BulkDeleteError, Storage};
+use quickwit_storage::{BulkDeleteError, Storage, StorageResolver};
 use thiserror::Error;
 use time::OffsetDateTime;
 use tracing::{error, instrument};
@@ -95,7 +96,7 @@ pub struct SplitRemovalInfo {
 /// Detect all dangling splits and associated files from the index and removes them.
 ///
 /// * `indexes` - The target index uids and storages.
-/// * `storage - The storage managing the target index.
+/// * `storage_resolver` - The storage resolver used to delete the splits moved to a storage tier.
 /// * `metastore` - The metastore managing the target index.
 /// * `staged_grace_period` -  Threshold period after which a staged split can be safely garbage
 ///   collected.
@@ -105,6 +106,7 @@ pub struct SplitRemovalInfo {
 /// * `progress` - For reporting progress (useful when called from within a quickwit actor).
 pub async fn run_garbage_collect(
     indexes: HashMap<IndexUid, Arc<dyn Storage>>,
+    storage_resolver: &StorageResolver,
     metastore: MetastoreServiceClient,
     staged_grace_period: Duration,
     deletion_grace_period: Duration,
@@ -185,6 +187,7 @@ pub async fn run_garbage_collect(
         updated_before_timestamp,
         metastore,
         indexes,
+        storage_resolver,
         progress_opt,
         metrics,
     )
@@ -194,6 +197,7 @@ pub async fn run_garbage_collect(
 async fn delete_splits(
     splits_metadata_to_delete_per_index: HashMap<IndexUid, Vec<SplitMetadata>>,
     storages: &HashMap<IndexUid, Arc<dyn Storage>>,
+    storage_resolver: &StorageResolver,
     metastore: MetastoreServiceClient,
     progress_opt: Option<&Progress>,
     metrics: &Option<GcMetrics>,
@@ -209,6 +213,7 @@ async fn delete_splits(
                         delete_splits_from_storage_and_metastore(
                             index_uid,
                             storage,
+                            storage_resolver,
                             metastore,
                             splits_metadata_to_delete,
                             progress_opt,
@@ -313,11 +318,12 @@ fn get_index_gc_concurrency() -> Option<usize> {
 ///
 /// The aim of this is to spread the load out across a longer period
 /// rather than short, heavy bursts on the metastore and storage system itself.
-#[instrument(skip(storages, metastore, progress_opt, metrics), fields(num_indexes=%storages.len()))]
+#[instrument(skip(storages, storage_resolver, metastore, progress_opt, metrics), fields(num_indexes=%storages.len()))]
 async fn delete_splits_marked_for_deletion_several_indexes(
     updated_before_timestamp: i64,
     metastore: MetastoreServiceClient,
     storages: HashMap<IndexUid, Arc<dyn Storage>>,
+    storage_resolver: &StorageResolver,
     progress_opt: Option<&Progress>,
     metrics: Option<GcMetrics>,
 ) -> SplitRemovalInfo {
@@ -392,6 +398,7 @@ async fn delete_splits_marked_for_deletion_several_indexes(
         let _: Result<(), ()> = delete_splits(
             splits_metadata_to_delete_per_index,
             &storages,
+            storage_resolver,
             metastore.clone(),
             progress_opt,
             &metrics,
@@ -416,60 +423,89 @@ async fn delete_splits_marked_for_deletion_several_indexes(
 ///
 /// * `index_id` - The target index id.
 /// * `storage - The storage managing the target index.
+/// * `storage_resolver` - The storage resolver used to delete the splits moved to a storage tier.
 /// * `metastore` - The metastore managing the target index.
 /// * `splits`  - The list of splits to delete.
 /// * `progress` - For reporting progress (useful when called from within a quickwit actor).
 pub async fn delete_splits_from_storage_and_metastore(
     index_uid: IndexUid,
     storage: Arc<dyn Storage>,
+    storage_resolver: &StorageResolver,
     metastore: MetastoreServiceClient,
     splits: Vec<SplitMetadata>,
     progress_opt: Option<&Progress>,
 ) -> Result<Vec<SplitInfo>, DeleteSplitsError> {
-    let mut split_infos: HashMap<PathBuf, SplitInfo> = HashMap::with_capacity(splits.len());
+    let num_splits = splits.len();
+    // Splits moved to a storage tier are deleted from the storage of the tier.
+    let mut split_infos_per_storage_uri: HashMap<Option<Uri>, HashMap<PathBuf, SplitInfo>> =
+        HashMap::new();
 
     for split in splits {
         let split_info = split.as_split_info();
-        split_infos.insert(split_info.file_name.clone(), split_info);
-    }
-    let split_paths = split_infos
-        .keys()
-        .map(|split_path_buf| split_path_buf.as_path())
-        .collect::<Vec<&Path>>();
-    let delete_result = protect_future(progress_opt, storage.bulk_delete(&split_paths)).await;
-
-    if let Some(progress) = progress_opt {
-        progress.record_progress();
+        split_infos_per_storage_uri
+            .entry(split.storage_uri)
+            .or_default()
+            .insert(split_info.file_name.clone(), split_info);
     }
-    let mut successes = Vec::with_capacity(split_infos.len());
+    let mut successes = Vec::with_capacity(num_splits);
     let mut storage_error: Option<BulkDeleteError> = None;
     let mut storage_failures = Vec::new();
 
-    match delete_result {
-        Ok(_) => successes.extend(split_infos.into_values()),
-        Err(bulk_delete_error) => {
-            let success_split_paths: HashSet<&PathBuf> =
-                bulk_delete_error.successes.iter().collect();
-            for (split_path, split_info) in split_infos {
-                if success_split_paths.contains(&split_path) {
-                    successes.push(split_info);
-                } else {
-                    storage_failures.push(split_info);
+    for (storage_uri_opt, split_infos) in split_infos_per_storage_uri {
+        let split_storage = if let Some(storage_uri) = storage_uri_opt {
+            match storage_resolver.resolve(&storage_uri).await {
+                Ok(split_storage) => split_storage,
+                Err(resolve_error) => {
+                    error!(
+                        error=?resolve_error,
+                        index_id=index_uid.index_id,
+                        "failed to resolve storage `{storage_uri}`",
+                    );
+                    storage_failures.extend(split_infos.into_values());
+                    continue;
                 }
             }
-            let failed_split_paths = storage_failures
-                .iter()
-                .map(|split_info| split_info.file_name.as_path())
-                .collect::<Vec<_>>();
-            error!(
-                error=?bulk_delete_error.error,
-                index_id=index_uid.index_id,
-                "failed to delete split file(s) {:?} from storage",
-                PrettySample::new(&failed_split_paths, 5),
-            );
-            storage_error = Some(bulk_delete_error);
+        } else {
+            storage.clone()
+        };
+        let split_paths = split_infos
+            .keys()
+            .map(|split_path_buf| split_path_buf.as_path())
+            .collect::<Vec<&Path>>();
+        let delete_result =
+            protect_future(progress_opt, split_storage.bulk_delete(&split_paths)).await;
+
+        if let Some(progress) = progress_opt {
+            progress.record_progress();
         }
-    };
+        match delete_result {
+            Ok(_) => successes.extend(split_infos.into_values()),
+            Err(bulk_delete_error) => {
+                let success_split_paths: HashSet<&PathBuf> =
+                    bulk_delete_error.successes.iter().collect();
+                let num_storage_failures_before = storage_failures.len();
+
+                for (split_path, split_info) in split_infos {
+                    if success_split_paths.contains(&split_path) {
+                        successes.push(split_info);
+                    } else {
+                        storage_failures.push(split_info);
+                    }
+                }
+                let failed_split_paths = storage_failures[num_storage_failures_before..]
+                    .iter()
+                    .map(|split_info| split_info.file_name.as_path())
+                    .collect::<Vec<_>>();
+                error!(
+                    error=?bulk_delete_error.error,
+                    index_id=index_uid.index_id,
+                    "failed to delete split file(s) {:?} from storage",
+                    PrettySample::new(&failed_split_paths, 5),
+                );
+                storage_error = Some(bulk_delete_error);
+            }
+        };
+    }
     if !successes.is_empty() {
         let split_ids: Vec<SplitId> = successes
             .iter()
@@ -586,6 +622,7 @@ mod tests {
         // The staging grace period hasn't passed yet so the split remains staged.
         run_garbage_collect(
             hashmap(index_uid.clone(), storage.clone()),
+            &StorageResolver::for_test(),
             metastore.clone(),
             Duration::from_secs(30),
             Duration::from_secs(30),
@@ -614,6 +651,7 @@ mod tests {
         // The staging grace period has passed so the split is marked for deletion.
         run_garbage_collect(
             hashmap(index_uid.clone(), storage.clone()),
+            &StorageResolver::for_test(),
             metastore.clone(),
             Duration::from_secs(0),
             Duration::from_secs(30),
@@ -692,6 +730,7 @@ mod tests {
         // The delete grace period hasn't passed yet so the split remains marked for deletion.
         run_garbage_collect(
             hashmap(index_uid.clone(), storage.clone()),
+            &StorageResolver::for_test(),
             metastore.clone(),
             Duration::from_secs(30),
             Duration::from_secs(30),
@@ -720,6 +759,7 @@ mod tests {
         // The delete grace period has passed so the split is deleted.
         run_garbage_collect(
             hashmap(index_uid.clone(), storage.clone()),
+            &StorageResolver::for_test(),
             metastore.clone(),
             Duration::from_secs(30),
             Duration::from_secs(0),
@@ -759,6 +799,7 @@ mod tests {
                 IndexUid::new_with_random_ulid("index-test-gc-deletes"),
                 storage.clone(),
             ),
+            &StorageResolver::for_test(),
             MetastoreServiceClient::from_mock(mock_metastore),
             Duration::from_secs(30),
             Duration::from_secs(30),
@@ -822,6 +863,7 @@ mod tests {
         let deleted_split_infos = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage.clone(),
+            &StorageResolver::for_test(),
             metastore.clone(),
             vec![split_metadata],
             None,
@@ -846,6 +888,68 @@ mod tests {
             .is_empty());
     }
 
+    #[tokio::test]
+    async fn test_delete_splits_from_storage_and_metastore_tiered_split() {
+        let storage = storage_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let metastore = metastore_for_test();
+
+        let index_id = "test-delete-splits-tiered--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let create_index_request =
+            CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid()
+            .clone();
+
+        let tier_uri = Uri::for_test("ram:///cold/test-delete-splits-tiered--index");
+        let tier_storage = storage_resolver.resolve(&tier_uri).await.unwrap();
+
+        let split_id = "test-delete-splits-tiered--split";
+        let split_metadata = SplitMetadata {
+            split_id: split_id.to_string(),
+            index_uid: index_uid.clone(),
+            storage_uri: Some(tier_uri),
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), &split_metadata.clone())
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        let split_path_str = format!("{}.split", split_id);
+        let split_path = Path::new(&split_path_str);
+        let payload: Box<dyn PutPayload> = Box::new(vec![0]);
+        tier_storage.put(split_path, payload).await.unwrap();
+
+        let deleted_split_infos = delete_splits_from_storage_and_metastore(
+            index_uid.clone(),
+            storage.clone(),
+            &storage_resolver,
+            metastore.clone(),
+            vec![split_metadata],
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(deleted_split_infos.len(), 1);
+        assert_eq!(deleted_split_infos[0].split_id, split_id);
+        assert!(!tier_storage.exists(split_path).await.unwrap());
+        assert!(metastore
+            .list_splits(ListSplitsRequest::try_from_index_uid(index_uid).unwrap())
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_delete_splits_from_storage_and_metastore_storage_error() {
         let mut mock_storage = MockStorage::new();
@@ -920,6 +1024,7 @@ mod tests {
         let error = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage.clone(),
+            &StorageResolver::for_test(),
             metastore.clone(),
             vec![split_metadata_0, split_metadata_1],
             None,
@@ -993,6 +1098,7 @@ mod tests {
         let error = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage.clone(),
+            &StorageResolver::for_test(),
             MetastoreServiceClient::from_mock(mock_metastore),
             vec![split_metadata_0, split_metadata_1],
             None,
@@ -20,8 +20,11 @@ use futures_util::StreamExt;
 use itertools::Itertools;
 use quickwit_common::fs::{empty_dir, get_cache_directory_path};
 use quickwit_common::pretty::PrettySample;
-use quickwit_common::rate_limited_error;
-use quickwit_config::{validate_identifier, IndexConfig, SourceConfig};
+use quickwit_common::uri::Uri;
+use quickwit_common::{new_coolid, rate_limited_error};
+use quickwit_config::{
+    validate_identifier, IndexConfig, ReindexSourceParams, SourceConfig, TransformConfig,
+};
 use quickwit_indexing::check_source_connectivity;
 use quickwit_metastore::{
     AddSourceRequestExt, CreateIndexResponseExt, IndexMetadata, IndexMetadataResponseExt,
@@ -34,16 +37,19 @@ use quickwit_proto::metastore::{
     MarkSplitsForDeletionRequest, MetastoreError, MetastoreService, MetastoreServiceClient,
     ResetSourceCheckpointRequest, UpdateSourceRequest,
 };
-use quickwit_proto::types::{IndexUid, SplitId};
+use quickwit_proto::types::{IndexId, IndexUid, SplitId};
 use quickwit_proto::{ServiceError, ServiceErrorCode};
-use quickwit_storage::{StorageResolver, StorageResolverError};
+use quickwit_query::query_ast::QueryAst;
+use quickwit_storage::{StorageError, StorageResolver, StorageResolverError};
 use thiserror::Error;
+use time::OffsetDateTime;
 use tracing::{error, info};
 
 use crate::garbage_collection::{
     delete_splits_from_storage_and_metastore, run_garbage_collect, DeleteSplitsError,
     SplitRemovalInfo,
 };
+use crate::snapshot::{self, IndexSnapshotSummary, SplitFilesMode};
 
 #[derive(Error, Debug)]
 pub enum IndexServiceError {
@@ -61,6 +67,10 @@ pub enum IndexServiceError {
     OperationNotAllowed(String),
     #[error("internal error: {0}")]
     Internal(String),
+    #[error("invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+    #[error("storage error `{0}`")]
+    StorageOperation(#[from] StorageError),
 }
 
 impl ServiceError for IndexServiceError {
@@ -72,6 +82,7 @@ impl ServiceError for IndexServiceError {
             }
             Self::InvalidConfig(_) => ServiceErrorCode::BadRequest,
             Self::InvalidIdentifier(_) => ServiceErrorCode::BadRequest,
+            Self::InvalidSnapshot(_) => ServiceErrorCode::BadRequest,
             Self::Metastore(error) => error.error_code(),
             Self::OperationNotAllowed(_) => ServiceErrorCode::Forbidden,
             Self::SplitDeletion(delete_splits_error) => {
@@ -88,6 +99,13 @@ impl ServiceError for IndexServiceError {
                 );
                 ServiceErrorCode::Internal
             }
+            Self::StorageOperation(storage_error) => {
+                rate_limited_error!(
+                    limit_per_min = 6,
+                    "index service internal error/storage operation {storage_error:?}"
+                );
+                ServiceErrorCode::Internal
+            }
         }
     }
 }
@@ -219,6 +237,7 @@ impl IndexService {
         let deleted_splits = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage,
+            &self.storage_resolver,
             self.metastore.clone(),
             splits_metadata_to_delete,
             None,
@@ -361,6 +380,7 @@ impl IndexService {
 
         let deleted_entries = run_garbage_collect(
             [(index_uid, storage)].into_iter().collect(),
+            &self.storage_resolver,
             self.metastore.clone(),
             grace_period,
             // deletion_grace_period of zero, so that a cli call directly deletes splits after
@@ -416,6 +436,7 @@ impl IndexService {
         if let Err(err) = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage,
+            &self.storage_resolver,
             self.metastore.clone(),
             splits_metadata,
             None,
@@ -436,6 +457,93 @@ impl IndexService {
         Ok(())
     }
 
+    /// Takes a snapshot of the index identified by `index_id` into `snapshot_uri`. The snapshot
+    /// captures the index config, its sources and their checkpoints, and the published splits of
+    /// the index, whose files are copied or referenced depending on `split_files_mode`.
+    pub async fn snapshot_index(
+        &self,
+        index_id: &str,
+        snapshot_uri: &Uri,
+        split_files_mode: SplitFilesMode,
+    ) -> Result<IndexSnapshotSummary, IndexServiceError> {
+        snapshot::snapshot_index(
+            &self.metastore,
+            &self.storage_resolver,
+            index_id,
+            snapshot_uri,
+            split_files_mode,
+        )
+        .await
+    }
+
+    /// Restores the index snapshotted into `snapshot_uri`, optionally under a new index ID and
+    /// index URI. If the index ID changes and no index URI is provided, the index is restored
+    /// under `default_index_root_uri`.
+    pub async fn restore_index(
+        &self,
+        snapshot_uri: &Uri,
+        index_id_opt: Option<IndexId>,
+        index_uri_opt: Option<Uri>,
+        default_index_root_uri: &Uri,
+    ) -> Result<IndexMetadata, IndexServiceError> {
+        snapshot::restore_index(
+            &self.metastore,
+            &self.storage_resolver,
+            snapshot_uri,
+            index_id_opt,
+            index_uri_opt,
+            default_index_root_uri,
+        )
+        .await
+    }
+
+    /// Reindexes the documents of the index `source_index_id` matching `query_ast` into the index
+    /// `target_index_id`, optionally transforming them with `transform_config_opt`. The documents
+    /// are read by the indexers through a reindex source added to the target index, which is
+    /// returned. The splits read are the ones published when the reindex is requested.
+    pub async fn reindex(
+        &mut self,
+        source_index_id: &str,
+        target_index_id: &str,
+        query_ast: &QueryAst,
+        transform_config_opt: Option<TransformConfig>,
+    ) -> Result<SourceConfig, IndexServiceError> {
+        if source_index_id == target_index_id {
+            return Err(IndexServiceError::OperationNotAllowed(format!(
+                "index `{source_index_id}` cannot be reindexed into itself"
+            )));
+        }
+        let source_index_uid = self.index_uid(source_index_id).await?;
+        let target_index_uid = self.index_uid(target_index_id).await?;
+        let query_ast_json = serde_json::to_string(query_ast).map_err(|error| {
+            IndexServiceError::Internal(format!("failed to serialize query AST: {error}"))
+        })?;
+        let reindex_params = ReindexSourceParams {
+            source_index_uid,
+            query_ast: query_ast_json,
+            snapshot_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        let source_config =
+            SourceConfig::reindex(new_coolid("reindex"), reindex_params, transform_config_opt);
+        let source_config = self.add_source(target_index_uid, source_config).await?;
+        info!(
+            source_id=%source_config.source_id,
+            "reindex of index `{source_index_id}` into index `{target_index_id}` started"
+        );
+        Ok(source_config)
+    }
+
+    async fn index_uid(&self, index_id: &str) -> Result<IndexUid, IndexServiceError> {
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let index_uid = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?
+            .index_uid;
+        Ok(index_uid)
+    }
+
     /// Adds a source to an index identified by its UID.
     pub async fn add_source(
         &mut self,
@@ -551,7 +659,12 @@ pub async fn validate_storage_uri(
     storage_resolver: &StorageResolver,
     index_config: &IndexConfig,
 ) -> anyhow::Result<()> {
-    storage_resolver.resolve(&index_config.index_uri).await?;
+    let storage = storage_resolver.resolve(&index_config.index_uri).await?;
+    storage_resolver.wrap_split_storage(
+        storage,
+        index_config.encryption_key_id(),
+        index_config.requires_encryption(),
+    )?;
     Ok(())
 }
 
@@ -559,7 +672,9 @@ pub async fn validate_storage_uri(
 mod tests {
 
     use quickwit_common::uri::Uri;
-    use quickwit_config::{IndexConfig, CLI_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_V2_SOURCE_ID};
+    use quickwit_config::{
+        IndexConfig, SourceParams, CLI_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_V2_SOURCE_ID,
+    };
     use quickwit_metastore::{
         metastore_for_test, MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
     };
@@ -673,4 +788,62 @@ mod tests {
         assert!(splits.is_empty());
         assert!(!storage.exists(split_path).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_reindex() {
+        let metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let mut index_service = IndexService::new(metastore.clone(), storage_resolver);
+        let source_index_config =
+            IndexConfig::for_test("source-index", "ram://indexes/source-index");
+        let source_index_uid = index_service
+            .create_index(source_index_config, false)
+            .await
+            .unwrap()
+            .index_uid;
+        let target_index_config =
+            IndexConfig::for_test("target-index", "ram://indexes/target-index");
+        index_service
+            .create_index(target_index_config, false)
+            .await
+            .unwrap();
+
+        let error = index_service
+            .reindex("source-index", "source-index", &QueryAst::MatchAll, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+
+        let transform_config = TransformConfig::new(".severity = \"ERROR\"".to_string(), None);
+        let source_config = index_service
+            .reindex(
+                "source-index",
+                "target-index",
+                &QueryAst::MatchAll,
+                Some(transform_config.clone()),
+            )
+            .await
+            .unwrap();
+        assert!(source_config.source_id.starts_with("reindex-"));
+        assert_eq!(source_config.transform_config, Some(transform_config));
+
+        let SourceParams::Reindex(reindex_params) = &source_config.source_params else {
+            panic!("expected reindex source params");
+        };
+        assert_eq!(reindex_params.source_index_uid, source_index_uid);
+        let query_ast: QueryAst = serde_json::from_str(&reindex_params.query_ast).unwrap();
+        assert_eq!(query_ast, QueryAst::MatchAll);
+
+        let target_index_metadata = metastore
+            .index_metadata(IndexMetadataRequest::for_index_id(
+                "target-index".to_string(),
+            ))
+            .await
+            .unwrap()
+            .deserialize_index_metadata()
+            .unwrap();
+        assert!(target_index_metadata
+            .sources
+            .contains_key(&source_config.source_id));
+    }
 }
@@ -14,6 +14,10 @@
 
 mod garbage_collection;
 mod index;
+mod snapshot;
 
 pub use garbage_collection::{run_garbage_collect, GcMetrics};
 pub use index::{clear_cache_directory, validate_storage_uri, IndexService, IndexServiceError};
+pub use snapshot::{
+    IndexSnapshot, IndexSnapshotSummary, SplitFilesMode, SNAPSHOT_MANIFEST_FILE_NAME,
+};
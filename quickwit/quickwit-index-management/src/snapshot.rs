@@ -0,0 +1,695 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::{stream, StreamExt, TryStreamExt};
+use itertools::Itertools;
+use quickwit_common::split_file;
+use quickwit_common::uri::Uri;
+use quickwit_config::{validate_identifier, SourceConfig};
+use quickwit_metastore::checkpoint::{IndexCheckpointDelta, SourceCheckpointDelta};
+use quickwit_metastore::{
+    CreateIndexRequestExt, CreateIndexResponseExt, IndexMetadata, IndexMetadataResponseExt,
+    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitMetadata,
+    SplitState, StageSplitsRequestExt,
+};
+use quickwit_proto::metastore::{
+    serde_utils, CreateIndexRequest, IndexMetadataRequest, ListSplitsRequest, MetastoreService,
+    MetastoreServiceClient, PublishSplitsRequest, SourceType, StageSplitsRequest,
+};
+use quickwit_proto::types::{IndexId, Position, SplitId};
+use quickwit_storage::{Storage, StorageErrorKind, StorageFilePayload, StorageResolver};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+
+use crate::index::{validate_storage_uri, IndexServiceError};
+
+/// Name of the file holding the manifest of a snapshot, relative to the snapshot URI.
+pub const SNAPSHOT_MANIFEST_FILE_NAME: &str = "snapshot.json";
+
+/// Name of the directory holding the split files copied by a snapshot, relative to the snapshot
+/// URI.
+const SNAPSHOT_SPLITS_DIR_NAME: &str = "splits";
+
+const MAX_CONCURRENT_SPLIT_COPIES: usize = 4;
+
+/// Maximum number of attempts made to read the splits and the checkpoint of an index in a
+/// consistent state.
+const MAX_NUM_CAPTURE_ATTEMPTS: usize = 5;
+
+/// Defines how the split files of an index are captured by a snapshot.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitFilesMode {
+    /// The split files are copied to the snapshot URI.
+    #[default]
+    Copy,
+    /// The snapshot only references the split files stored in the storage of the index. The
+    /// snapshot can no longer be restored once the referenced splits have been merged or deleted
+    /// and garbage collected.
+    Reference,
+}
+
+/// Manifest of an index snapshot, stored at the root of the snapshot URI.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "VersionedIndexSnapshot")]
+#[serde(from = "VersionedIndexSnapshot")]
+pub struct IndexSnapshot {
+    /// Metadata of the index, including its config, its sources, and their checkpoints.
+    pub index_metadata: IndexMetadata,
+    /// Published splits of the index when the snapshot was taken.
+    pub splits: Vec<SplitMetadata>,
+    pub split_files_mode: SplitFilesMode,
+    pub create_timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "version")]
+enum VersionedIndexSnapshot {
+    #[serde(rename = "0.8")]
+    V0_8(IndexSnapshotV0_8),
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexSnapshotV0_8 {
+    index_metadata: IndexMetadata,
+    splits: Vec<SplitMetadata>,
+    split_files_mode: SplitFilesMode,
+    create_timestamp: i64,
+}
+
+impl From<IndexSnapshot> for VersionedIndexSnapshot {
+    fn from(snapshot: IndexSnapshot) -> Self {
+        VersionedIndexSnapshot::V0_8(IndexSnapshotV0_8 {
+            index_metadata: snapshot.index_metadata,
+            splits: snapshot.splits,
+            split_files_mode: snapshot.split_files_mode,
+            create_timestamp: snapshot.create_timestamp,
+        })
+    }
+}
+
+impl From<VersionedIndexSnapshot> for IndexSnapshot {
+    fn from(versioned_snapshot: VersionedIndexSnapshot) -> Self {
+        match versioned_snapshot {
+            VersionedIndexSnapshot::V0_8(snapshot) => IndexSnapshot {
+                index_metadata: snapshot.index_metadata,
+                splits: snapshot.splits,
+                split_files_mode: snapshot.split_files_mode,
+                create_timestamp: snapshot.create_timestamp,
+            },
+        }
+    }
+}
+
+/// Summary of a snapshot returned once it has been taken.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IndexSnapshotSummary {
+    pub index_id: IndexId,
+    pub snapshot_uri: Uri,
+    pub split_files_mode: SplitFilesMode,
+    pub num_splits: usize,
+    pub num_bytes: u64,
+}
+
+/// A split file to copy from a source storage to a target storage.
+struct SplitFileCopy {
+    source_storage: Arc<dyn Storage>,
+    source_path: PathBuf,
+    target_path: PathBuf,
+}
+
+pub(crate) async fn snapshot_index(
+    metastore: &MetastoreServiceClient,
+    storage_resolver: &StorageResolver,
+    index_id: &str,
+    snapshot_uri: &Uri,
+    split_files_mode: SplitFilesMode,
+) -> Result<IndexSnapshotSummary, IndexServiceError> {
+    let snapshot_storage = storage_resolver.resolve(snapshot_uri).await?;
+    let manifest_path = Path::new(SNAPSHOT_MANIFEST_FILE_NAME);
+
+    if snapshot_storage.exists(manifest_path).await? {
+        return Err(IndexServiceError::OperationNotAllowed(format!(
+            "snapshot `{snapshot_uri}` already exists"
+        )));
+    }
+    let (index_metadata, splits) = capture_index_state(metastore, index_id).await?;
+    let index_uri = index_metadata.index_uri();
+
+    if snapshot_uri == index_uri {
+        return Err(IndexServiceError::OperationNotAllowed(
+            "snapshot URI must be different from the index URI".to_string(),
+        ));
+    }
+    if split_files_mode == SplitFilesMode::Copy {
+        let storages = resolve_storages(
+            storage_resolver,
+            splits
+                .iter()
+                .map(|split_metadata| split_metadata.storage_uri(index_uri)),
+        )
+        .await?;
+        let split_file_copies = splits
+            .iter()
+            .map(|split_metadata| {
+                let source_storage = storages[split_metadata.storage_uri(index_uri)].clone();
+                let split_file_name = split_file(&split_metadata.split_id);
+                SplitFileCopy {
+                    source_storage,
+                    source_path: PathBuf::from(&split_file_name),
+                    target_path: Path::new(SNAPSHOT_SPLITS_DIR_NAME).join(&split_file_name),
+                }
+            })
+            .collect();
+        copy_split_files(split_file_copies, snapshot_storage.clone()).await?;
+    }
+    let summary = IndexSnapshotSummary {
+        index_id: index_id.to_string(),
+        snapshot_uri: snapshot_uri.clone(),
+        split_files_mode,
+        num_splits: splits.len(),
+        num_bytes: splits
+            .iter()
+            .map(|split_metadata| split_metadata.footer_offsets.end)
+            .sum(),
+    };
+    let snapshot = IndexSnapshot {
+        index_metadata,
+        splits,
+        split_files_mode,
+        create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+    };
+    // The manifest is written last so that an incomplete snapshot cannot be restored.
+    let snapshot_json = serde_json::to_vec_pretty(&snapshot).map_err(|error| {
+        IndexServiceError::Internal(format!("failed to serialize snapshot: {error}"))
+    })?;
+    snapshot_storage
+        .put(manifest_path, Box::new(snapshot_json))
+        .await?;
+    info!(
+        index_id=%index_id,
+        snapshot_uri=%snapshot_uri,
+        num_splits=summary.num_splits,
+        "created index snapshot"
+    );
+    Ok(summary)
+}
+
+pub(crate) async fn restore_index(
+    metastore: &MetastoreServiceClient,
+    storage_resolver: &StorageResolver,
+    snapshot_uri: &Uri,
+    index_id_opt: Option<IndexId>,
+    index_uri_opt: Option<Uri>,
+    default_index_root_uri: &Uri,
+) -> Result<IndexMetadata, IndexServiceError> {
+    let snapshot_storage = storage_resolver.resolve(snapshot_uri).await?;
+    let snapshot = load_snapshot(&*snapshot_storage, snapshot_uri).await?;
+    let snapshot_index_uri = snapshot.index_metadata.index_uri();
+
+    let mut index_config = snapshot.index_metadata.index_config().clone();
+
+    if let Some(index_id) = index_id_opt {
+        validate_identifier("index", &index_id).map_err(|_| {
+            IndexServiceError::InvalidIdentifier(format!("invalid index ID: `{index_id}`"))
+        })?;
+        if index_id != index_config.index_id {
+            index_config.index_uri = default_index_root_uri
+                .join(&index_id)
+                .map_err(IndexServiceError::InvalidConfig)?;
+            index_config.index_id = index_id;
+        }
+    }
+    if let Some(index_uri) = index_uri_opt {
+        index_config.index_uri = index_uri;
+    }
+    // The restored splits are all stored under the index URI. The storage tiers of the snapshotted
+    // index are only reused when the index is restored at its original location.
+    if index_config.index_uri != *snapshot_index_uri
+        && index_config.tiering_policy_opt.take().is_some()
+    {
+        info!(index_id=%index_config.index_id, "dropping tiering policy of restored index");
+    }
+    validate_storage_uri(storage_resolver, &index_config)
+        .await
+        .map_err(IndexServiceError::InvalidConfig)?;
+
+    let source_configs: Vec<SourceConfig> = snapshot
+        .index_metadata
+        .sources
+        .values()
+        .sorted_by(|left, right| left.source_id.cmp(&right.source_id))
+        .cloned()
+        .collect();
+    let create_index_request =
+        CreateIndexRequest::try_from_index_and_source_configs(&index_config, &source_configs)?;
+    let index_uid = metastore
+        .create_index(create_index_request)
+        .await?
+        .index_uid()
+        .clone();
+
+    let restored_splits: Vec<SplitMetadata> = snapshot
+        .splits
+        .iter()
+        .map(|split_metadata| {
+            let mut restored_split_metadata = split_metadata.clone();
+            restored_split_metadata.index_uid = index_uid.clone();
+            restored_split_metadata.storage_uri = None;
+            // Opstamps refer to the delete tasks of the snapshotted index, which are not restored.
+            restored_split_metadata.delete_opstamp = 0;
+            restored_split_metadata
+        })
+        .collect();
+    let mut staged_split_ids: Vec<SplitId> = restored_splits
+        .iter()
+        .map(|split_metadata| split_metadata.split_id.clone())
+        .collect();
+
+    if !restored_splits.is_empty() {
+        // The splits are staged before their files are copied so that the garbage collector
+        // cleans up the files if the restore fails midway.
+        let stage_splits_request =
+            StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), restored_splits)?;
+        metastore.stage_splits(stage_splits_request).await?;
+
+        let index_storage = storage_resolver.resolve(&index_config.index_uri).await?;
+        let split_file_copies = match snapshot.split_files_mode {
+            SplitFilesMode::Copy => snapshot
+                .splits
+                .iter()
+                .map(|split_metadata| {
+                    let split_file_name = split_file(&split_metadata.split_id);
+                    SplitFileCopy {
+                        source_storage: snapshot_storage.clone(),
+                        source_path: Path::new(SNAPSHOT_SPLITS_DIR_NAME).join(&split_file_name),
+                        target_path: PathBuf::from(split_file_name),
+                    }
+                })
+                .collect(),
+            SplitFilesMode::Reference => {
+                let storages = resolve_storages(
+                    storage_resolver,
+                    snapshot
+                        .splits
+                        .iter()
+                        .map(|split_metadata| split_metadata.storage_uri(snapshot_index_uri)),
+                )
+                .await?;
+                snapshot
+                    .splits
+                    .iter()
+                    .map(|split_metadata| {
+                        let source_storage =
+                            storages[split_metadata.storage_uri(snapshot_index_uri)].clone();
+                        let split_file_name = split_file(&split_metadata.split_id);
+                        SplitFileCopy {
+                            source_storage,
+                            source_path: PathBuf::from(&split_file_name),
+                            target_path: PathBuf::from(split_file_name),
+                        }
+                    })
+                    .collect()
+            }
+        };
+        copy_split_files(split_file_copies, index_storage).await?;
+    }
+    // A publish request carries the checkpoint delta of a single source, so the checkpoints of
+    // the sources are restored by successive requests.
+    let mut checkpoint_deltas = restored_checkpoint_deltas(&snapshot.index_metadata)?.into_iter();
+    let mut checkpoint_delta_opt = checkpoint_deltas.next();
+
+    while !staged_split_ids.is_empty() || checkpoint_delta_opt.is_some() {
+        let index_checkpoint_delta_json_opt = checkpoint_delta_opt
+            .as_ref()
+            .map(serde_utils::to_json_str)
+            .transpose()?;
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: Some(index_uid.clone()),
+            staged_split_ids: std::mem::take(&mut staged_split_ids),
+            replaced_split_ids: Vec::new(),
+            index_checkpoint_delta_json_opt,
+            publish_token_opt: None,
+        };
+        metastore.publish_splits(publish_splits_request).await?;
+        checkpoint_delta_opt = checkpoint_deltas.next();
+    }
+    let index_metadata_request = IndexMetadataRequest::for_index_uid(index_uid);
+    let index_metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    info!(
+        index_id=%index_metadata.index_id(),
+        snapshot_uri=%snapshot_uri,
+        num_splits=snapshot.splits.len(),
+        "restored index from snapshot"
+    );
+    Ok(index_metadata)
+}
+
+/// Reads the metadata and the published splits of an index. The metadata is read again after
+/// listing the splits to make sure that the checkpoint matches the splits.
+async fn capture_index_state(
+    metastore: &MetastoreServiceClient,
+    index_id: &str,
+) -> Result<(IndexMetadata, Vec<SplitMetadata>), IndexServiceError> {
+    for _ in 0..MAX_NUM_CAPTURE_ATTEMPTS {
+        let index_metadata = fetch_index_metadata(metastore, index_id).await?;
+        let query = ListSplitsQuery::for_index(index_metadata.index_uid.clone())
+            .with_split_state(SplitState::Published);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+        let splits = metastore
+            .list_splits(list_splits_request)
+            .await?
+            .collect_splits_metadata()
+            .await?;
+        let index_metadata_after = fetch_index_metadata(metastore, index_id).await?;
+
+        if index_metadata_after.index_uid == index_metadata.index_uid
+            && index_metadata_after.checkpoint == index_metadata.checkpoint
+        {
+            return Ok((index_metadata, splits));
+        }
+    }
+    Err(IndexServiceError::OperationNotAllowed(format!(
+        "failed to capture a consistent state of index `{index_id}` because it is being written \
+         to: retry later or disable its sources"
+    )))
+}
+
+async fn fetch_index_metadata(
+    metastore: &MetastoreServiceClient,
+    index_id: &str,
+) -> Result<IndexMetadata, IndexServiceError> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let index_metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    Ok(index_metadata)
+}
+
+async fn load_snapshot(
+    snapshot_storage: &dyn Storage,
+    snapshot_uri: &Uri,
+) -> Result<IndexSnapshot, IndexServiceError> {
+    let manifest_path = Path::new(SNAPSHOT_MANIFEST_FILE_NAME);
+    let snapshot_bytes = match snapshot_storage.get_all(manifest_path).await {
+        Ok(snapshot_bytes) => snapshot_bytes,
+        Err(error) if error.kind() == StorageErrorKind::NotFound => {
+            return Err(IndexServiceError::InvalidSnapshot(format!(
+                "snapshot `{snapshot_uri}` does not exist or is incomplete"
+            )));
+        }
+        Err(error) => return Err(error.into()),
+    };
+    serde_json::from_slice(snapshot_bytes.as_slice()).map_err(|error| {
+        IndexServiceError::InvalidSnapshot(format!(
+            "failed to deserialize snapshot `{snapshot_uri}`: {error}"
+        ))
+    })
+}
+
+/// Builds the checkpoint deltas restoring the checkpoints of the sources of a snapshotted index.
+/// The checkpoints of the ingest API and CLI sources are not restored because they refer to queues
+/// and shards that are not captured by snapshots.
+fn restored_checkpoint_deltas(
+    index_metadata: &IndexMetadata,
+) -> Result<Vec<IndexCheckpointDelta>, IndexServiceError> {
+    let mut checkpoint_deltas = Vec::new();
+
+    for source_config in index_metadata
+        .sources
+        .values()
+        .sorted_by(|left, right| left.source_id.cmp(&right.source_id))
+    {
+        if matches!(
+            source_config.source_type(),
+            SourceType::Cli | SourceType::IngestV1 | SourceType::IngestV2
+        ) {
+            continue;
+        }
+        let Some(source_checkpoint) = index_metadata
+            .checkpoint
+            .source_checkpoint(&source_config.source_id)
+        else {
+            continue;
+        };
+        let mut source_delta = SourceCheckpointDelta::default();
+
+        for (partition_id, position) in source_checkpoint.iter() {
+            if position == Position::Beginning {
+                continue;
+            }
+            source_delta
+                .record_partition_delta(partition_id, Position::Beginning, position)
+                .map_err(|error| IndexServiceError::InvalidSnapshot(error.to_string()))?;
+        }
+        if !source_delta.is_empty() {
+            checkpoint_deltas.push(IndexCheckpointDelta {
+                source_id: source_config.source_id.clone(),
+                source_delta,
+            });
+        }
+    }
+    Ok(checkpoint_deltas)
+}
+
+async fn resolve_storages<'a>(
+    storage_resolver: &StorageResolver,
+    storage_uris: impl IntoIterator<Item = &'a Uri>,
+) -> Result<HashMap<Uri, Arc<dyn Storage>>, IndexServiceError> {
+    let mut storages = HashMap::new();
+
+    for storage_uri in storage_uris {
+        if !storages.contains_key(storage_uri) {
+            let storage = storage_resolver.resolve(storage_uri).await?;
+            storages.insert(storage_uri.clone(), storage);
+        }
+    }
+    Ok(storages)
+}
+
+async fn copy_split_files(
+    split_file_copies: Vec<SplitFileCopy>,
+    target_storage: Arc<dyn Storage>,
+) -> Result<(), IndexServiceError> {
+    stream::iter(split_file_copies)
+        .map(|split_file_copy| {
+            let target_storage = target_storage.clone();
+            async move {
+                let payload = StorageFilePayload::open(
+                    split_file_copy.source_storage,
+                    &split_file_copy.source_path,
+                )
+                .await?;
+                target_storage
+                    .put(&split_file_copy.target_path, Box::new(payload))
+                    .await
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_SPLIT_COPIES)
+        .try_collect::<Vec<()>>()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::{IndexConfig, SourceParams};
+    use quickwit_metastore::metastore_for_test;
+    use quickwit_proto::metastore::EntityKind;
+    use quickwit_proto::types::IndexUid;
+    use quickwit_storage::PutPayload;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore_index() {
+        let metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+
+        let index_id = "test-snapshot--index";
+        let index_uri = Uri::for_test("ram:///indexes/test-snapshot--index");
+        let index_config = IndexConfig::for_test(index_id, index_uri.as_str());
+        let source_config = SourceConfig::for_test("test-source", SourceParams::void());
+        let create_index_request =
+            CreateIndexRequest::try_from_index_and_source_configs(&index_config, &[source_config])
+                .unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid()
+            .clone();
+
+        let split_metadata = SplitMetadata {
+            split_id: "test-snapshot--split".to_string(),
+            index_uid: index_uid.clone(),
+            footer_offsets: 0..5,
+            delete_opstamp: 3,
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), &split_metadata)
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        let checkpoint_delta = IndexCheckpointDelta::for_test("test-source", 0..43);
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: Some(index_uid.clone()),
+            staged_split_ids: vec!["test-snapshot--split".to_string()],
+            index_checkpoint_delta_json_opt: Some(
+                serde_utils::to_json_str(&checkpoint_delta).unwrap(),
+            ),
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+
+        let index_storage = storage_resolver.resolve(&index_uri).await.unwrap();
+        let payload: Box<dyn PutPayload> = Box::new(b"split".to_vec());
+        index_storage
+            .put(Path::new("test-snapshot--split.split"), payload)
+            .await
+            .unwrap();
+
+        let snapshot_uri = Uri::for_test("ram:///snapshots/test-snapshot");
+        let summary = snapshot_index(
+            &metastore,
+            &storage_resolver,
+            index_id,
+            &snapshot_uri,
+            SplitFilesMode::Copy,
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.num_splits, 1);
+        assert_eq!(summary.num_bytes, 5);
+
+        let snapshot_storage = storage_resolver.resolve(&snapshot_uri).await.unwrap();
+        assert!(snapshot_storage
+            .exists(Path::new("splits/test-snapshot--split.split"))
+            .await
+            .unwrap());
+
+        let error = snapshot_index(
+            &metastore,
+            &storage_resolver,
+            index_id,
+            &snapshot_uri,
+            SplitFilesMode::Copy,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+
+        let default_index_root_uri = Uri::for_test("ram:///indexes");
+        let restored_index_metadata = restore_index(
+            &metastore,
+            &storage_resolver,
+            &snapshot_uri,
+            Some("test-snapshot--restored".to_string()),
+            None,
+            &default_index_root_uri,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            restored_index_metadata.index_id(),
+            "test-snapshot--restored"
+        );
+        assert_eq!(
+            restored_index_metadata.index_uri(),
+            "ram:///indexes/test-snapshot--restored"
+        );
+        assert!(restored_index_metadata.sources.contains_key("test-source"));
+
+        let original_index_metadata = fetch_index_metadata(&metastore, index_id).await.unwrap();
+        assert_eq!(
+            restored_index_metadata.checkpoint,
+            original_index_metadata.checkpoint
+        );
+
+        let query = ListSplitsQuery::for_index(restored_index_metadata.index_uid.clone())
+            .with_split_state(SplitState::Published);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query).unwrap();
+        let restored_splits = metastore
+            .list_splits(list_splits_request)
+            .await
+            .unwrap()
+            .collect_splits_metadata()
+            .await
+            .unwrap();
+        assert_eq!(restored_splits.len(), 1);
+        assert_eq!(restored_splits[0].split_id, "test-snapshot--split");
+        assert_eq!(restored_splits[0].delete_opstamp, 0);
+
+        let restored_index_storage = storage_resolver
+            .resolve(restored_index_metadata.index_uri())
+            .await
+            .unwrap();
+        let split_bytes = restored_index_storage
+            .get_all(Path::new("test-snapshot--split.split"))
+            .await
+            .unwrap();
+        assert_eq!(split_bytes.as_slice(), b"split");
+
+        // The index already exists.
+        let error = restore_index(
+            &metastore,
+            &storage_resolver,
+            &snapshot_uri,
+            None,
+            None,
+            &default_index_root_uri,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            IndexServiceError::Metastore(quickwit_proto::metastore::MetastoreError::AlreadyExists(
+                EntityKind::Index { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_restore_index_missing_snapshot() {
+        let metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let snapshot_uri = Uri::for_test("ram:///snapshots/missing-snapshot");
+        let default_index_root_uri = Uri::for_test("ram:///indexes");
+
+        let error = restore_index(
+            &metastore,
+            &storage_resolver,
+            &snapshot_uri,
+            None,
+            None,
+            &default_index_root_uri,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, IndexServiceError::InvalidSnapshot(_)));
+    }
+}
@@ -494,12 +494,13 @@ impl DocProcessor {
     fn process_json_doc(&self, json_doc: JsonDoc) -> Result<ProcessedDoc, DocProcessorError> {
         let num_bytes = json_doc.num_bytes;
 
-        let (partition, doc) = self
+        let (partition, doc, nested_docs) = self
             .doc_mapper
-            .doc_from_json_obj(json_doc.json_obj, json_doc.num_bytes as u64)?;
+            .doc_with_nested_docs_from_json_obj(json_doc.json_obj, json_doc.num_bytes as u64)?;
         let timestamp_opt = self.extract_timestamp(&doc)?;
         Ok(ProcessedDoc {
             doc,
+            nested_docs,
             timestamp_opt,
             partition,
             num_bytes,
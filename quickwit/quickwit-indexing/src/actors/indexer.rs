@@ -302,6 +302,7 @@ impl IndexerState {
         for doc in batch.docs {
             let ProcessedDoc {
                 doc,
+                nested_docs,
                 timestamp_opt,
                 partition,
                 num_bytes,
@@ -327,6 +328,13 @@ impl IndexerState {
                 record_timestamp(timestamp, &mut indexed_split.split_attrs.time_range);
             }
             let _protect_guard = ctx.protect_zone();
+            // Nested documents must directly precede their root document.
+            for nested_doc in nested_docs {
+                indexed_split
+                    .index_writer
+                    .add_document(nested_doc)
+                    .context("failed to add nested document")?;
+            }
             indexed_split
                 .index_writer
                 .add_document(doc)
@@ -794,6 +802,7 @@ mod tests {
                             body_field=>"this is a test document",
                             timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
                         ),
+                        nested_docs: Vec::new(),
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                         partition: 1,
                         num_bytes: 30,
@@ -803,6 +812,7 @@ mod tests {
                             body_field=>"this is a test document 2",
                             timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
                         ),
+                        nested_docs: Vec::new(),
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                         partition: 1,
                         num_bytes: 30,
@@ -820,6 +830,7 @@ mod tests {
                             body_field=>"this is a test document 3",
                             timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435i64)
                         ),
+                        nested_docs: Vec::new(),
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435i64)),
                         partition: 1,
                         num_bytes: 30,
@@ -829,6 +840,7 @@ mod tests {
                             body_field=>"this is a test document 4",
                             timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
                         ),
+                        nested_docs: Vec::new(),
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                         partition: 1,
                         num_bytes: 30,
@@ -845,6 +857,7 @@ mod tests {
                         body_field=>"this is a test document 5",
                         timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
                     ),
+                    nested_docs: Vec::new(),
                     timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                     partition: 1,
                     num_bytes: 30,
@@ -930,6 +943,7 @@ mod tests {
             let num_bytes = body.len() * 2;
             ProcessedDoc {
                 doc: doc!(body_field=>body),
+                nested_docs: Vec::new(),
                 timestamp_opt: None,
                 partition: 0,
                 num_bytes,
@@ -1008,6 +1022,7 @@ mod tests {
                                 body_field=>"this is a test document",
                                 timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
                             ),
+                            nested_docs: Vec::new(),
                             timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                             partition: 1,
                             num_bytes: 30,
@@ -1086,6 +1101,7 @@ mod tests {
                         body_field=>"this is a test document 5",
                         timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
                     ),
+                    nested_docs: Vec::new(),
                     timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                     partition: 1,
                     num_bytes: 30,
@@ -1173,6 +1189,7 @@ mod tests {
                         body_field=>"this is a test document 5",
                         timestamp_field=> DateTime::from_timestamp_secs(1_662_529_435)
                     ),
+                    nested_docs: Vec::new(),
                     timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                     partition: 1,
                     num_bytes: 30,
@@ -1257,6 +1274,7 @@ mod tests {
                             body_field=>"doc 2",
                             tenant_field=>"tenant_1",
                         ),
+                        nested_docs: Vec::new(),
                         timestamp_opt: None,
                         partition: 1,
                         num_bytes: 30,
@@ -1266,6 +1284,7 @@ mod tests {
                             body_field=>"doc 2",
                             tenant_field=>"tenant_2",
                         ),
+                        nested_docs: Vec::new(),
                         timestamp_opt: None,
                         partition: 3,
                         num_bytes: 30,
@@ -1351,6 +1370,7 @@ mod tests {
                 .send_message(ProcessedDocBatch::new(
                     vec![ProcessedDoc {
                         doc: doc!(body_field=>"doc {i}"),
+                        nested_docs: Vec::new(),
                         timestamp_opt: None,
                         partition,
                         num_bytes: 30,
@@ -1429,6 +1449,7 @@ mod tests {
                 .send_message(ProcessedDocBatch::new(
                     vec![ProcessedDoc {
                         doc: doc!(body_field=>"doc 1"),
+                        nested_docs: Vec::new(),
                         timestamp_opt: None,
                         partition: 0,
                         num_bytes: 30,
@@ -1500,6 +1521,7 @@ mod tests {
             .send_message(ProcessedDocBatch::new(
                 vec![ProcessedDoc {
                     doc: doc!(body_field=>"doc 1"),
+                    nested_docs: Vec::new(),
                     timestamp_opt: None,
                     partition: 0,
                     num_bytes: 30,
@@ -1556,6 +1578,7 @@ mod tests {
             .send_message(ProcessedDocBatch::new(
                 vec![ProcessedDoc {
                     doc: doc!(body_field=>"doc 1"),
+                    nested_docs: Vec::new(),
                     timestamp_opt: None,
                     partition: 0,
                     num_bytes: 30,
@@ -380,7 +380,12 @@ impl IndexingPipeline {
 
         // Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("Packager", tag_fields, uploader_mailbox);
+        let packager = Packager::new(
+            "Packager",
+            tag_fields,
+            self.params.indexing_settings.docstore_layout,
+            uploader_mailbox,
+        );
         let (packager_mailbox, packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -436,6 +441,7 @@ impl IndexingPipeline {
             metastore: self.params.metastore.clone(),
             ingester_pool: self.params.ingester_pool.clone(),
             queues_dir_path: self.params.queues_dir_path.clone(),
+            indexing_directory: self.params.indexing_directory.clone(),
             storage_resolver: self.params.source_storage_resolver.clone(),
             event_broker: self.params.event_broker.clone(),
             indexing_setting: self.params.indexing_settings.clone(),
@@ -603,7 +609,7 @@ mod tests {
 
     use quickwit_actors::{Command, Universe};
     use quickwit_common::ServiceStream;
-    use quickwit_config::{IndexingSettings, SourceInputFormat, SourceParams};
+    use quickwit_config::{DocStoreLayout, IndexingSettings, SourceInputFormat, SourceParams};
     use quickwit_doc_mapper::{default_doc_mapper_for_test, DocMapper};
     use quickwit_metastore::checkpoint::IndexCheckpointDelta;
     use quickwit_metastore::{IndexMetadata, IndexMetadataResponseExt, PublishSplitsRequestExt};
@@ -908,6 +914,7 @@ mod tests {
             split_store: split_store.clone(),
             merge_policy: default_merge_policy(),
             retention_policy: None,
+            docstore_layout: DocStoreLayout::Row,
             max_concurrent_split_uploads: 2,
             merge_io_throughput_limiter_opt: None,
             merge_scheduler_service: universe.get_or_spawn_one(),
@@ -285,6 +285,13 @@ impl IndexingService {
             .storage_resolver
             .resolve(&index_config.index_uri)
             .await
+            .and_then(|storage| {
+                self.storage_resolver.wrap_split_storage(
+                    storage,
+                    index_config.encryption_key_id(),
+                    index_config.requires_encryption(),
+                )
+            })
             .map_err(|error| {
                 let message = format!("failed to spawn indexing pipeline: {error}");
                 IndexingError::Internal(message)
@@ -307,6 +314,7 @@ impl IndexingService {
             merge_scheduler_service: self.merge_scheduler_service.clone(),
             merge_policy: merge_policy.clone(),
             retention_policy: retention_policy.clone(),
+            docstore_layout: index_config.indexing_settings.docstore_layout,
             merge_io_throughput_limiter_opt: self.merge_io_throughput_limiter_opt.clone(),
             max_concurrent_split_uploads: self.max_concurrent_split_uploads,
             event_broker: self.event_broker.clone(),
@@ -26,7 +26,7 @@ use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, Qu
 use quickwit_common::io::IoControls;
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
-use quickwit_directories::UnionDirectory;
+use quickwit_directories::{restore_row_doc_store, UnionDirectory};
 use quickwit_doc_mapper::DocMapper;
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::indexing::MergePipelineId;
@@ -35,6 +35,7 @@ use quickwit_proto::metastore::{
     MetastoreServiceClient,
 };
 use quickwit_proto::types::{NodeId, SplitId};
+use quickwit_query::block_join::num_root_docs;
 use quickwit_query::get_quickwit_fastfield_normalizer_manager;
 use quickwit_query::query_ast::QueryAst;
 use tantivy::directory::{Advice, DirectoryClone, MmapDirectory, RamDirectory};
@@ -155,16 +156,26 @@ fn combine_index_meta(mut index_metas: Vec<IndexMeta>) -> anyhow::Result<IndexMe
 fn open_split_directories(
     // Directories containing the splits to merge
     tantivy_dirs: &[Box<dyn Directory>],
+    // Directory receiving the row doc stores of the splits written with the columnar layout
+    restored_doc_stores_path: &Path,
     tokenizer_manager: &TokenizerManager,
 ) -> anyhow::Result<(IndexMeta, Vec<Box<dyn Directory>>)> {
+    let restored_doc_stores_directory = MmapDirectory::open(restored_doc_stores_path)?;
     let mut directories: Vec<Box<dyn Directory>> = Vec::new();
     let mut index_metas = Vec::new();
+    let mut has_restored_doc_stores = false;
     for tantivy_dir in tantivy_dirs {
         directories.push(tantivy_dir.clone());
 
-        let index_meta = open_index(tantivy_dir.clone(), tokenizer_manager)?.load_metas()?;
+        let index = open_index(tantivy_dir.clone(), tokenizer_manager)?;
+        has_restored_doc_stores |= restore_row_doc_store(&index, &restored_doc_stores_directory)?;
+        let index_meta = index.load_metas()?;
         index_metas.push(index_meta);
     }
+    if has_restored_doc_stores {
+        // The restored row doc stores shadow the empty ones of the splits.
+        directories.insert(0, Box::new(restored_doc_stores_directory));
+    }
     let union_index_meta = combine_index_meta(index_metas)?;
     Ok((union_index_meta, directories))
 }
@@ -304,8 +315,11 @@ impl MergeExecutor {
         merge_scratch_directory: TempDirectory,
         ctx: &ActorContext<Self>,
     ) -> anyhow::Result<IndexedSplit> {
+        let restored_doc_stores_directory =
+            merge_scratch_directory.named_temp_child("restored-doc-stores-")?;
         let (union_index_meta, split_directories) = open_split_directories(
             &tantivy_dirs,
+            restored_doc_stores_directory.path(),
             self.doc_mapper.tokenizer_manager().tantivy_manager(),
         )?;
         // TODO it would be nice if tantivy could let us run the merge in the current thread.
@@ -372,8 +386,11 @@ impl MergeExecutor {
             num_delete_tasks = delete_tasks.len()
         );
 
+        let restored_doc_stores_directory =
+            merge_scratch_directory.named_temp_child("restored-doc-stores-")?;
         let (union_index_meta, split_directories) = open_split_directories(
             &tantivy_dirs,
+            restored_doc_stores_directory.path(),
             self.doc_mapper.tokenizer_manager().tantivy_manager(),
         )?;
         let controlled_directory = self
@@ -424,7 +441,7 @@ impl MergeExecutor {
             };
 
         let merged_segment_reader = SegmentReader::open(&merged_segment)?;
-        let num_docs = merged_segment_reader.num_docs() as u64;
+        let num_docs = num_root_docs(&merged_segment_reader)? as u64;
         let uncompressed_docs_size_in_bytes = (num_docs as f32
             * split.uncompressed_docs_size_in_bytes as f32
             / split.num_docs as f32) as u64;
@@ -514,8 +531,7 @@ impl MergeExecutor {
                     "Delete all documents matched by query `{:?}`",
                     parsed_query_ast
                 );
-                let (query, _) =
-                    doc_mapper.query(union_index.schema(), &parsed_query_ast, false)?;
+                let query = doc_mapper.delete_query(union_index.schema(), &parsed_query_ast)?;
                 index_writer.delete_query(query)?;
             }
             debug!("commit-delete-operations");
@@ -546,7 +562,7 @@ impl MergeExecutor {
     }
 }
 
-fn open_index<T: Into<Box<dyn Directory>>>(
+pub(crate) fn open_index<T: Into<Box<dyn Directory>>>(
     directory: T,
     tokenizer_manager: &TokenizerManager,
 ) -> tantivy::Result<Index> {
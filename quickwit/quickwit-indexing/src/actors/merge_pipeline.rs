@@ -24,7 +24,7 @@ use quickwit_common::io::{IoControls, Limiter};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_common::KillSwitch;
-use quickwit_config::RetentionPolicy;
+use quickwit_config::{DocStoreLayout, RetentionPolicy};
 use quickwit_doc_mapper::DocMapper;
 use quickwit_metastore::{
     ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitMetadata,
@@ -295,7 +295,12 @@ impl MergePipeline {
 
         // Merge Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let merge_packager = Packager::new("MergePackager", tag_fields, merge_uploader_mailbox);
+        let merge_packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            self.params.docstore_layout,
+            merge_uploader_mailbox,
+        );
         let (merge_packager_mailbox, merge_packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -570,6 +575,7 @@ pub struct MergePipelineParams {
     pub split_store: IndexingSplitStore,
     pub merge_policy: Arc<dyn MergePolicy>,
     pub retention_policy: Option<RetentionPolicy>,
+    pub docstore_layout: DocStoreLayout,
     pub max_concurrent_split_uploads: usize, //< TODO share with the indexing pipeline.
     pub merge_io_throughput_limiter_opt: Option<Limiter>,
     pub event_broker: EventBroker,
@@ -583,6 +589,7 @@ mod tests {
     use quickwit_actors::{ActorExitStatus, Universe};
     use quickwit_common::temp_dir::TempDirectory;
     use quickwit_common::ServiceStream;
+    use quickwit_config::DocStoreLayout;
     use quickwit_doc_mapper::default_doc_mapper_for_test;
     use quickwit_metastore::ListSplitsRequestExt;
     use quickwit_proto::indexing::MergePipelineId;
@@ -634,6 +641,7 @@ mod tests {
             split_store,
             merge_policy: default_merge_policy(),
             retention_policy: None,
+            docstore_layout: DocStoreLayout::Row,
             max_concurrent_split_uploads: 2,
             merge_io_throughput_limiter_opt: None,
             event_broker: Default::default(),
@@ -35,6 +35,7 @@ pub use index_serializer::IndexSerializer;
 pub use indexer::{Indexer, IndexerCounters};
 pub use indexing_pipeline::{IndexingPipeline, IndexingPipelineParams};
 pub use indexing_service::{IndexingService, IndexingServiceCounters, INDEXING_DIR_NAME};
+pub(crate) use merge_executor::open_index;
 pub use merge_executor::{combine_partition_ids, merge_split_attrs, MergeExecutor};
 pub use merge_pipeline::{FinishPendingMergesAndShutdownPipeline, MergePipeline};
 pub(crate) use merge_planner::{MergePlanner, RunFinalizeMergePolicyAndQuit};
@@ -24,7 +24,8 @@ use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
-use quickwit_directories::write_hotcache;
+use quickwit_config::DocStoreLayout;
+use quickwit_directories::{clear_row_doc_store, write_columnar_doc_store, write_hotcache};
 use quickwit_doc_mapper::tag_pruning::append_to_tag_set;
 use quickwit_doc_mapper::NamedField;
 use quickwit_proto::search::{
@@ -32,7 +33,7 @@ use quickwit_proto::search::{
 };
 use tantivy::index::FieldMetadata;
 use tantivy::schema::{FieldType, Type};
-use tantivy::{InvertedIndexReader, ReloadPolicy, SegmentMeta};
+use tantivy::{Index, InvertedIndexReader, ReloadPolicy, SegmentMeta};
 use tokio::runtime::Handle;
 use tracing::{debug, info, instrument, warn};
 
@@ -55,6 +56,8 @@ use crate::models::{
 /// - commit: this step is CPU heavy
 /// - identifying the list of tags for the splits, and labelling it accordingly
 /// - creating a bundle file
+/// - writing the columnar doc store in place of the row doc store, if the index uses the columnar
+///   layout
 /// - computing the hotcache
 /// - appending it to the split file.
 ///
@@ -65,18 +68,22 @@ pub struct Packager {
     uploader_mailbox: Mailbox<Uploader>,
     /// List of tag fields ([`Vec<NamedField>`]) defined in the index config.
     tag_fields: Vec<NamedField>,
+    /// Layout of the stored fields defined in the index config.
+    docstore_layout: DocStoreLayout,
 }
 
 impl Packager {
     pub fn new(
         actor_name: &'static str,
         tag_fields: Vec<NamedField>,
+        docstore_layout: DocStoreLayout,
         uploader_mailbox: Mailbox<Uploader>,
     ) -> Packager {
         Packager {
             actor_name,
             uploader_mailbox,
             tag_fields,
+            docstore_layout,
         }
     }
 
@@ -87,8 +94,13 @@ impl Packager {
     ) -> anyhow::Result<PackagedSplit> {
         let segment_metas = split.index.searchable_segment_metas()?;
         assert_eq!(segment_metas.len(), 1);
-        let packaged_split =
-            create_packaged_split(&segment_metas[..], split, &self.tag_fields, ctx)?;
+        let packaged_split = create_packaged_split(
+            &segment_metas[..],
+            split,
+            &self.tag_fields,
+            self.docstore_layout,
+            ctx,
+        )?;
         Ok(packaged_split)
     }
 }
@@ -213,6 +225,17 @@ fn build_hotcache<W: io::Write>(split_path: &Path, out: &mut W) -> anyhow::Resul
     Ok(())
 }
 
+/// Writes the columnar doc store of the split and empties its row doc store.
+fn build_columnar_doc_store(index: &Index, split_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mmap_directory = tantivy::directory::MmapDirectory::open(split_path)?;
+    let columnar_doc_store_files = write_columnar_doc_store(index, &mmap_directory)?
+        .into_iter()
+        .map(|relative_path| split_path.join(relative_path))
+        .collect();
+    clear_row_doc_store(index, &mmap_directory)?;
+    Ok(columnar_doc_store_files)
+}
+
 /// Attempts to exhaustively extract the list of terms in a
 /// field term dictionary.
 ///
@@ -272,10 +295,11 @@ fn create_packaged_split(
     segment_metas: &[SegmentMeta],
     split: IndexedSplit,
     tag_fields: &[NamedField],
+    docstore_layout: DocStoreLayout,
     ctx: &ActorContext<Packager>,
 ) -> anyhow::Result<PackagedSplit> {
     debug!(split_id = split.split_id(), "create-packaged-split");
-    let split_files = list_split_files(segment_metas, &split.split_scratch_directory)?;
+    let mut split_files = list_split_files(segment_metas, &split.split_scratch_directory)?;
 
     // Extracts tag values from inverted indexes only when a field cardinality is less
     // than `MAX_VALUES_PER_TAG_FIELD`.
@@ -309,6 +333,15 @@ fn create_packaged_split(
 
     ctx.record_progress();
 
+    if docstore_layout == DocStoreLayout::Columnar {
+        debug!(split_id = split.split_id(), "write-columnar-doc-store");
+        let columnar_doc_store_files =
+            build_columnar_doc_store(&split.index, split.split_scratch_directory.path())?;
+        split_files.extend(columnar_doc_store_files);
+        split_files.sort();
+        ctx.record_progress();
+    }
+
     debug!(split_id = split.split_id(), "build-hotcache");
     let mut hotcache_bytes = Vec::new();
     build_hotcache(split.split_scratch_directory.path(), &mut hotcache_bytes)?;
@@ -323,6 +356,7 @@ fn create_packaged_split(
         tags,
         split_files,
         hotcache_bytes,
+        docstore_layout,
     };
     Ok(packaged_split)
 }
@@ -385,7 +419,7 @@ mod tests {
     use quickwit_proto::search::{deserialize_split_fields, ListFieldsEntryResponse};
     use quickwit_proto::types::{DocMappingUid, IndexUid, NodeId};
     use tantivy::directory::MmapDirectory;
-    use tantivy::schema::{NumericOptions, Schema, Type, FAST, STRING, TEXT};
+    use tantivy::schema::{NumericOptions, Schema, Type, FAST, STORED, STRING, TEXT};
     use tantivy::{doc, DateTime, IndexBuilder, IndexSettings};
     use tracing::Span;
 
@@ -445,7 +479,7 @@ mod tests {
     ) -> anyhow::Result<IndexedSplit> {
         let split_scratch_directory = TempDirectory::for_test();
         let mut schema_builder = Schema::builder();
-        let text_field = schema_builder.add_text_field("text", TEXT);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
         let timestamp_field = schema_builder.add_u64_field("timestamp", FAST);
         let tag_str = schema_builder.add_text_field("tag_str", STRING);
         let tag_many = schema_builder.add_text_field("tag_many", STRING);
@@ -561,7 +595,7 @@ mod tests {
                 "tag_str", "tag_many", "tag_u64", "tag_i64", "tag_f64", "tag_bool",
             ],
         );
-        let packager = Packager::new("TestPackager", tag_fields, mailbox);
+        let packager = Packager::new("TestPackager", tag_fields, DocStoreLayout::Row, mailbox);
         let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
         packager_mailbox
             .send_message(IndexedSplitBatch {
@@ -608,4 +642,67 @@ mod tests {
         universe.assert_quit().await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_packager_columnar_doc_store() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, inbox) = universe.create_test_mailbox();
+        let indexed_split =
+            make_indexed_split_for_test(&[DateTime::from_timestamp_secs(1628203589)])?;
+        let packager = Packager::new(
+            "TestPackager",
+            Vec::new(),
+            DocStoreLayout::Columnar,
+            mailbox,
+        );
+        let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
+        packager_mailbox
+            .send_message(IndexedSplitBatch {
+                splits: vec![indexed_split],
+                checkpoint_delta_opt: IndexCheckpointDelta::for_test("source_id", 10..20).into(),
+                publish_lock: PublishLock::default(),
+                publish_token_opt: None,
+                merge_task_opt: None,
+                batch_parent_span: Span::none(),
+            })
+            .await?;
+        packager_handle.process_pending_and_observe().await;
+
+        let packaged_splits = inbox.drain_for_test();
+        let packaged_split = packaged_splits[0]
+            .downcast_ref::<PackagedSplitBatch>()
+            .unwrap();
+        let split = &packaged_split.splits[0];
+        assert_eq!(split.docstore_layout, DocStoreLayout::Columnar);
+
+        let file_names: Vec<String> = split
+            .split_files
+            .iter()
+            .map(|split_file| {
+                split_file
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert!(file_names
+            .iter()
+            .any(|file_name| file_name
+                == quickwit_directories::COLUMNAR_DOC_STORE_MANIFEST_FILE_NAME));
+        // Only the `text` field is stored.
+        assert_eq!(
+            file_names
+                .iter()
+                .filter(|file_name| file_name.ends_with(".colstore"))
+                .count(),
+            1
+        );
+        assert!(split
+            .split_files
+            .iter()
+            .all(|split_file| split_file.exists()));
+        universe.assert_quit().await;
+        Ok(())
+    }
 }
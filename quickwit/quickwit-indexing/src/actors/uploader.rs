@@ -329,11 +329,14 @@ impl Handler<PackagedSplitBatch> for Uploader {
                         &packaged_split.split_attrs,
                         packaged_split.tags.clone(),
                         split_streamer.footer_range.start..split_streamer.footer_range.end,
+                        packaged_split.docstore_layout,
                     );
 
                     report_splits.push(ReportSplit {
                         storage_uri: split_store.remote_uri().to_string(),
                         split_id: packaged_split.split_id().to_string(),
+                        split_footer_start: None,
+                        split_footer_end: None,
                     });
 
                     split_metadata_list.push(split_metadata);
@@ -493,6 +496,7 @@ mod tests {
     use quickwit_actors::{ObservationType, Universe};
     use quickwit_common::pubsub::EventSubscriber;
     use quickwit_common::temp_dir::TempDirectory;
+    use quickwit_config::DocStoreLayout;
     use quickwit_metastore::checkpoint::{IndexCheckpointDelta, SourceCheckpointDelta};
     use quickwit_proto::metastore::{EmptyResponse, MockMetastoreService};
     use quickwit_proto::types::{DocMappingUid, NodeId};
@@ -573,6 +577,7 @@ mod tests {
                     split_scratch_directory,
                     tags: Default::default(),
                     hotcache_bytes: Vec::new(),
+                    docstore_layout: DocStoreLayout::Row,
                     split_files: Vec::new(),
                 }],
                 checkpoint_delta_opt,
@@ -688,6 +693,7 @@ mod tests {
             tags: Default::default(),
             split_files: Vec::new(),
             hotcache_bytes: Vec::new(),
+            docstore_layout: DocStoreLayout::Row,
         };
         let package_split_2 = PackagedSplit {
             split_attrs: SplitAttrs {
@@ -715,6 +721,7 @@ mod tests {
             tags: Default::default(),
             split_files: Vec::new(),
             hotcache_bytes: Vec::new(),
+            docstore_layout: DocStoreLayout::Row,
         };
         uploader_mailbox
             .send_message(PackagedSplitBatch::new(
@@ -834,6 +841,7 @@ mod tests {
                     split_scratch_directory,
                     tags: Default::default(),
                     hotcache_bytes: Vec::new(),
+                    docstore_layout: DocStoreLayout::Row,
                     split_files: Vec::new(),
                 }],
                 checkpoint_delta_opt,
@@ -1016,6 +1024,7 @@ mod tests {
                     split_scratch_directory,
                     tags: Default::default(),
                     hotcache_bytes: Vec::new(),
+                    docstore_layout: DocStoreLayout::Row,
                     split_files: Vec::new(),
                 }],
                 checkpoint_delta_opt,
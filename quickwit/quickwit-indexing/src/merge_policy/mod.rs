@@ -221,6 +221,7 @@ pub mod tests {
 
     use proptest::prelude::*;
     use quickwit_actors::Universe;
+    use quickwit_config::DocStoreLayout;
     use quickwit_proto::indexing::{IndexingPipelineId, MergePipelineId};
     use quickwit_proto::types::{IndexUid, NodeId, PipelineUid};
     use rand::seq::SliceRandom;
@@ -391,7 +392,14 @@ pub mod tests {
             source_id: "test_source".to_string(),
         };
         let split_attrs = merge_split_attrs(pipeline_id, merged_split_id, splits).unwrap();
-        create_split_metadata(merge_policy, None, &split_attrs, tags, 0..0)
+        create_split_metadata(
+            merge_policy,
+            None,
+            &split_attrs,
+            tags,
+            0..0,
+            DocStoreLayout::Row,
+        )
     }
 
     fn apply_merge(
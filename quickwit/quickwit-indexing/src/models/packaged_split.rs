@@ -18,6 +18,7 @@ use std::path::PathBuf;
 
 use itertools::Itertools;
 use quickwit_common::temp_dir::TempDirectory;
+use quickwit_config::DocStoreLayout;
 use quickwit_metastore::checkpoint::IndexCheckpointDelta;
 use quickwit_proto::types::{IndexUid, PublishToken, SplitId};
 use tracing::Span;
@@ -32,6 +33,7 @@ pub struct PackagedSplit {
     pub tags: BTreeSet<String>,
     pub split_files: Vec<PathBuf>,
     pub hotcache_bytes: Vec<u8>,
+    pub docstore_layout: DocStoreLayout,
 }
 
 impl PackagedSplit {
@@ -51,6 +53,7 @@ impl fmt::Debug for PackagedSplit {
             .field("split_scratch_directory", &self.split_scratch_directory)
             .field("tags", &self.tags)
             .field("split_files", &self.split_files)
+            .field("docstore_layout", &self.docstore_layout)
             .finish()
     }
 }
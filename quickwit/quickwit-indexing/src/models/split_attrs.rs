@@ -18,6 +18,7 @@ use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 use std::time::Duration;
 
+use quickwit_config::DocStoreLayout;
 use quickwit_metastore::{SplitMaturity, SplitMetadata};
 use quickwit_proto::types::{DocMappingUid, IndexUid, NodeId, SourceId, SplitId};
 use tantivy::DateTime;
@@ -92,6 +93,7 @@ pub fn create_split_metadata(
     split_attrs: &SplitAttrs,
     tags: BTreeSet<String>,
     footer_offsets: Range<u64>,
+    docstore_layout: DocStoreLayout,
 ) -> SplitMetadata {
     let create_timestamp = OffsetDateTime::now_utc().unix_timestamp();
 
@@ -125,6 +127,9 @@ pub fn create_split_metadata(
         footer_offsets,
         delete_opstamp: split_attrs.delete_opstamp,
         num_merge_ops: split_attrs.num_merge_ops,
+        storage_uri: None,
+        docstore_layout,
+        replaced_split_ids: split_attrs.replaced_split_ids.clone(),
     }
 }
 
@@ -670,6 +670,7 @@ mod tests {
     use quickwit_actors::{ActorContext, Universe};
     use quickwit_common::metrics::MEMORY_METRICS;
     use quickwit_common::stream_utils::InFlightValue;
+    use quickwit_common::temp_dir::TempDirectory;
     use quickwit_common::ServiceStream;
     use quickwit_config::{IndexingSettings, SourceConfig, SourceParams};
     use quickwit_proto::indexing::IndexingPipelineId;
@@ -940,6 +941,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool: ingester_pool.clone(),
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
@@ -1144,6 +1146,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool: ingester_pool.clone(),
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
@@ -1309,6 +1312,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool: ingester_pool.clone(),
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
@@ -1375,6 +1379,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool: ingester_pool.clone(),
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
@@ -1609,6 +1614,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool,
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
@@ -1764,6 +1770,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool: ingester_pool.clone(),
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
@@ -1896,6 +1903,7 @@ mod tests {
             metastore: MetastoreServiceClient::from_mock(mock_metastore),
             ingester_pool,
             queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
             storage_resolver: StorageResolver::for_test(),
             event_broker: event_broker.clone(),
             indexing_setting: IndexingSettings::default(),
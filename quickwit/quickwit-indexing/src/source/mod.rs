@@ -66,6 +66,7 @@ mod kinesis;
 mod pulsar_source;
 #[cfg(feature = "queue-sources")]
 mod queue_sources;
+mod reindex_source;
 mod source_factory;
 mod stdin_source;
 mod vec_source;
@@ -88,12 +89,15 @@ pub use kinesis::kinesis_source::{KinesisSource, KinesisSourceFactory};
 use once_cell::sync::{Lazy, OnceCell};
 #[cfg(feature = "pulsar")]
 pub use pulsar_source::{PulsarSource, PulsarSourceFactory};
+#[cfg(feature = "queue-sources")]
+pub use queue_sources::batch_queue;
 #[cfg(feature = "sqs")]
 pub use queue_sources::sqs_queue;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox};
 use quickwit_common::metrics::{GaugeGuard, MEMORY_METRICS};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::runtimes::RuntimeType;
+use quickwit_common::temp_dir::TempDirectory;
 use quickwit_config::{
     FileSourceNotification, FileSourceParams, IndexingSettings, SourceConfig, SourceParams,
 };
@@ -107,6 +111,7 @@ use quickwit_proto::metastore::{
 };
 use quickwit_proto::types::{IndexUid, NodeIdRef, PipelineUid, ShardId};
 use quickwit_storage::StorageResolver;
+pub use reindex_source::{ReindexSource, ReindexSourceFactory};
 use serde_json::Value as JsonValue;
 pub use source_factory::{SourceFactory, SourceLoader, TypedSourceFactory};
 use tokio::runtime::Handle;
@@ -156,6 +161,8 @@ pub struct SourceRuntime {
     pub ingester_pool: IngesterPool,
     // Ingest API queues directory path.
     pub queues_dir_path: PathBuf,
+    // Scratch directory of the indexing pipeline.
+    pub indexing_directory: TempDirectory,
     pub storage_resolver: StorageResolver,
     pub event_broker: EventBroker,
     pub indexing_setting: IndexingSettings,
@@ -415,6 +422,7 @@ pub fn quickwit_supported_sources() -> &'static SourceLoader {
         source_factory.add_source(SourceType::Kinesis, KinesisSourceFactory);
         #[cfg(feature = "pulsar")]
         source_factory.add_source(SourceType::Pulsar, PulsarSourceFactory);
+        source_factory.add_source(SourceType::Reindex, ReindexSourceFactory);
         source_factory.add_source(SourceType::Vec, VecSourceFactory);
         source_factory.add_source(SourceType::Void, VoidSourceFactory);
         source_factory
@@ -615,6 +623,7 @@ mod tests {
                 metastore,
                 ingester_pool: IngesterPool::default(),
                 queues_dir_path,
+                indexing_directory: TempDirectory::for_test(),
                 source_config: self.source_config,
                 storage_resolver: StorageResolver::for_test(),
                 event_broker: EventBroker::default(),
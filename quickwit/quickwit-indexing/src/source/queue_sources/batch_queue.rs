@@ -0,0 +1,338 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use quickwit_config::FileSourceMessageType;
+use quickwit_metastore::checkpoint::PartitionId;
+use quickwit_proto::metastore::{
+    ListShardsRequest, ListShardsSubrequest, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::types::SourceUid;
+use quickwit_storage::OwnedBytes;
+
+use super::message::{MessageMetadata, RawMessage};
+use super::Queue;
+
+/// Batch queues registered by queue URL, waiting to be picked up by the file
+/// source of an indexing pipeline.
+static REGISTERED_BATCH_QUEUES: Lazy<Mutex<HashMap<String, Arc<BatchQueue>>>> =
+    Lazy::new(Default::default);
+
+/// A message handed over to a [`BatchQueue`].
+#[derive(Debug, Clone)]
+pub struct BatchQueueMessage {
+    /// The unique message id assigned by the queue, also used as ack_id
+    pub message_id: String,
+    pub payload: String,
+    /// The approximate number of times the message was delivered
+    pub delivery_attempts: usize,
+}
+
+#[derive(Default)]
+struct InnerState {
+    pending: VecDeque<BatchQueueMessage>,
+    acknowledged: HashSet<String>,
+}
+
+/// A queue serving a fixed batch of messages that were received by an external
+/// consumer, typically the AWS Lambda SQS trigger. The external consumer owns
+/// the message visibility and deletion, so extending deadlines is a no-op and
+/// acknowledgements are only recorded.
+///
+/// Once all the messages are received, the queue is exhausted and the
+/// [`super::coordinator::QueueCoordinator`] terminates the source as soon as the
+/// messages are read.
+pub struct BatchQueue {
+    message_type: FileSourceMessageType,
+    messages: Vec<BatchQueueMessage>,
+    inner_state: Mutex<InnerState>,
+}
+
+impl fmt::Debug for BatchQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchQueue")
+            .field("num_messages", &self.messages.len())
+            .finish()
+    }
+}
+
+impl BatchQueue {
+    pub fn new(message_type: FileSourceMessageType, messages: Vec<BatchQueueMessage>) -> Self {
+        let inner_state = InnerState {
+            pending: messages.iter().cloned().collect(),
+            acknowledged: HashSet::new(),
+        };
+        Self {
+            message_type,
+            messages,
+            inner_state: Mutex::new(inner_state),
+        }
+    }
+
+    /// Registers the batch queue so that the next file source configured with
+    /// SQS notifications on `queue_url` consumes it instead of polling SQS.
+    pub fn register(self: &Arc<Self>, queue_url: &str) {
+        REGISTERED_BATCH_QUEUES
+            .lock()
+            .unwrap()
+            .insert(queue_url.to_string(), self.clone());
+    }
+
+    /// Removes the batch queue registered for `queue_url`, if any.
+    pub fn take_registered(queue_url: &str) -> Option<Arc<BatchQueue>> {
+        REGISTERED_BATCH_QUEUES.lock().unwrap().remove(queue_url)
+    }
+
+    pub fn message_type(&self) -> FileSourceMessageType {
+        self.message_type
+    }
+
+    /// Returns the ids of the messages that were neither acknowledged by the
+    /// coordinator nor fully indexed according to the shard table. These
+    /// messages should be delivered again.
+    pub async fn unprocessed_message_ids(
+        &self,
+        metastore: &MetastoreServiceClient,
+        source_uid: &SourceUid,
+    ) -> anyhow::Result<Vec<String>> {
+        let acknowledged = self.inner_state.lock().unwrap().acknowledged.clone();
+        let unacknowledged_messages: Vec<&BatchQueueMessage> = self
+            .messages
+            .iter()
+            .filter(|message| !acknowledged.contains(&message.message_id))
+            .collect();
+        if unacknowledged_messages.is_empty() {
+            return Ok(Vec::new());
+        }
+        let list_shards_request = ListShardsRequest {
+            subrequests: vec![ListShardsSubrequest {
+                index_uid: Some(source_uid.index_uid.clone()),
+                source_id: source_uid.source_id.clone(),
+                shard_state: None,
+            }],
+        };
+        let completed_partition_ids: HashSet<PartitionId> = metastore
+            .list_shards(list_shards_request)
+            .await?
+            .subresponses
+            .into_iter()
+            .flat_map(|subresponse| subresponse.shards)
+            .filter(|shard| {
+                shard
+                    .publish_position_inclusive
+                    .as_ref()
+                    .map_or(false, |position| position.is_eof())
+            })
+            .map(|shard| PartitionId::from(shard.shard_id().as_str()))
+            .collect();
+
+        let unprocessed_message_ids = unacknowledged_messages
+            .into_iter()
+            .filter(|message| {
+                // messages that cannot be pre-processed were never indexed
+                to_raw_message(message, Instant::now())
+                    .pre_process(self.message_type.into())
+                    .map_or(true, |preprocessed_message| {
+                        !completed_partition_ids.contains(&preprocessed_message.partition_id())
+                    })
+            })
+            .map(|message| message.message_id.clone())
+            .collect();
+        Ok(unprocessed_message_ids)
+    }
+}
+
+fn to_raw_message(message: &BatchQueueMessage, deadline: Instant) -> RawMessage {
+    RawMessage {
+        metadata: MessageMetadata {
+            ack_id: message.message_id.clone(),
+            message_id: message.message_id.clone(),
+            delivery_attempts: message.delivery_attempts,
+            initial_deadline: deadline,
+        },
+        payload: OwnedBytes::new(message.payload.clone().into_bytes()),
+    }
+}
+
+#[async_trait]
+impl Queue for BatchQueue {
+    async fn receive(
+        self: Arc<Self>,
+        max_messages: usize,
+        suggested_deadline: Duration,
+    ) -> anyhow::Result<Vec<RawMessage>> {
+        let deadline = Instant::now() + suggested_deadline;
+        let mut inner_state = self.inner_state.lock().unwrap();
+        let num_messages = max_messages.min(inner_state.pending.len());
+        let messages = inner_state
+            .pending
+            .drain(..num_messages)
+            .map(|message| to_raw_message(&message, deadline))
+            .collect();
+        Ok(messages)
+    }
+
+    async fn acknowledge(&self, ack_ids: &[String]) -> anyhow::Result<()> {
+        let mut inner_state = self.inner_state.lock().unwrap();
+        inner_state.acknowledged.extend(ack_ids.iter().cloned());
+        Ok(())
+    }
+
+    async fn modify_deadlines(
+        &self,
+        _ack_id: &str,
+        suggested_deadline: Duration,
+    ) -> anyhow::Result<Instant> {
+        Ok(Instant::now() + suggested_deadline)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.inner_state.lock().unwrap().pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::ingest::Shard;
+    use quickwit_proto::metastore::{
+        ListShardsResponse, ListShardsSubresponse, MockMetastoreService,
+    };
+    use quickwit_proto::types::{IndexUid, Position, ShardId};
+
+    use super::*;
+
+    fn batch_message(message_id: &str, payload: &str) -> BatchQueueMessage {
+        BatchQueueMessage {
+            message_id: message_id.to_string(),
+            payload: payload.to_string(),
+            delivery_attempts: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_queue_receive() {
+        let batch_queue = Arc::new(BatchQueue::new(
+            FileSourceMessageType::RawUri,
+            vec![
+                batch_message("message-1", "s3://bucket/file-1"),
+                batch_message("message-2", "s3://bucket/file-2"),
+            ],
+        ));
+        assert!(!batch_queue.is_exhausted());
+
+        let messages = batch_queue
+            .clone()
+            .receive(1, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].metadata.ack_id, "message-1");
+        assert!(!batch_queue.is_exhausted());
+
+        let messages = batch_queue
+            .clone()
+            .receive(10, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].payload.as_slice(), b"s3://bucket/file-2");
+        assert!(batch_queue.is_exhausted());
+
+        let messages = batch_queue
+            .clone()
+            .receive(10, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_queue_registry() {
+        let batch_queue = Arc::new(BatchQueue::new(FileSourceMessageType::RawUri, Vec::new()));
+        batch_queue.register("https://sqs.us-east-1.amazonaws.com/123456789012/test-registry");
+
+        assert!(BatchQueue::take_registered(
+            "https://sqs.us-east-1.amazonaws.com/123456789012/other-queue"
+        )
+        .is_none());
+        assert!(BatchQueue::take_registered(
+            "https://sqs.us-east-1.amazonaws.com/123456789012/test-registry"
+        )
+        .is_some());
+        assert!(BatchQueue::take_registered(
+            "https://sqs.us-east-1.amazonaws.com/123456789012/test-registry"
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_queue_unprocessed_message_ids() {
+        let batch_queue = BatchQueue::new(
+            FileSourceMessageType::RawUri,
+            vec![
+                batch_message("acknowledged", "s3://bucket/file-1"),
+                batch_message("indexed", "s3://bucket/file-2"),
+                batch_message("in-progress", "s3://bucket/file-3"),
+                batch_message("not-a-uri", ""),
+            ],
+        );
+        batch_queue
+            .acknowledge(&["acknowledged".to_string()])
+            .await
+            .unwrap();
+
+        let index_uid = IndexUid::for_test("test-index", 0);
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+        };
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_shards()
+            .return_once(move |request| {
+                assert_eq!(request.subrequests.len(), 1);
+                assert_eq!(request.subrequests[0].source_id, "test-source");
+
+                let shard = |file: &str, position: Position| Shard {
+                    index_uid: Some(index_uid.clone()),
+                    source_id: "test-source".to_string(),
+                    shard_id: Some(ShardId::from(file)),
+                    publish_position_inclusive: Some(position),
+                    ..Default::default()
+                };
+                Ok(ListShardsResponse {
+                    subresponses: vec![ListShardsSubresponse {
+                        index_uid: Some(index_uid.clone()),
+                        source_id: "test-source".to_string(),
+                        shards: vec![
+                            shard("s3://bucket/file-2", Position::eof(42u64)),
+                            shard("s3://bucket/file-3", Position::offset(21u64)),
+                        ],
+                    }],
+                })
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let unprocessed_message_ids = batch_queue
+            .unprocessed_message_ids(&metastore, &source_uid)
+            .await
+            .unwrap();
+        assert_eq!(unprocessed_message_ids, ["in-progress", "not-a-uri"]);
+    }
+}
@@ -19,13 +19,14 @@ use std::time::Duration;
 use itertools::Itertools;
 use quickwit_actors::{ActorExitStatus, Mailbox};
 use quickwit_common::rate_limited_error;
-use quickwit_config::{FileSourceMessageType, FileSourceSqs};
+use quickwit_config::FileSourceSqs;
 use quickwit_metastore::checkpoint::SourceCheckpoint;
 use quickwit_proto::indexing::IndexingPipelineId;
 use quickwit_proto::metastore::SourceType;
 use quickwit_proto::types::SourceUid;
 use quickwit_storage::StorageResolver;
 use serde::Serialize;
+use tracing::info;
 use ulid::Ulid;
 
 use super::helpers::QueueReceiver;
@@ -129,16 +130,24 @@ impl QueueCoordinator {
         config: FileSourceSqs,
         source_runtime: SourceRuntime,
     ) -> anyhow::Result<Self> {
+        use super::batch_queue::BatchQueue;
         use super::sqs_queue::SqsQueue;
-        let queue = SqsQueue::try_new(config.queue_url).await?;
-        let message_type = match config.message_type {
-            FileSourceMessageType::S3Notification => MessageType::S3Notification,
-            FileSourceMessageType::RawUri => MessageType::RawUri,
-        };
+        let (queue, message_type): (Arc<dyn Queue>, MessageType) =
+            if let Some(batch_queue) = BatchQueue::take_registered(&config.queue_url) {
+                info!(
+                    queue_url = config.queue_url,
+                    "consuming registered batch queue"
+                );
+                let message_type = batch_queue.message_type().into();
+                (batch_queue, message_type)
+            } else {
+                let queue = SqsQueue::try_new(config.queue_url).await?;
+                (Arc::new(queue), config.message_type.into())
+            };
         let shard_max_age = Duration::from_secs(config.deduplication_window_duration_secs as u64);
         Ok(QueueCoordinator::new(
             source_runtime,
-            Arc::new(queue),
+            queue,
             message_type,
             Some(shard_max_age),
             Some(config.deduplication_window_max_messages),
@@ -283,6 +292,13 @@ impl QueueCoordinator {
                     );
                 }
             }
+        } else if self.queue.is_exhausted() {
+            info!(
+                num_messages_processed = self.observable_state.num_messages_processed,
+                "queue exhausted, exiting source"
+            );
+            ctx.send_exit_with_success(doc_processor_mailbox).await?;
+            return Err(ActorExitStatus::Success);
         } else {
             self.poll_messages(ctx).await?;
         }
@@ -321,6 +337,7 @@ mod tests {
 
     use quickwit_actors::{ActorContext, Universe};
     use quickwit_common::uri::Uri;
+    use quickwit_config::FileSourceMessageType;
     use quickwit_proto::types::{NodeId, PipelineUid, Position};
     use tokio::sync::watch;
     use ulid::Ulid;
@@ -328,13 +345,14 @@ mod tests {
     use super::*;
     use crate::models::RawDocBatch;
     use crate::source::doc_file_reader::file_test_helpers::{generate_dummy_doc_file, DUMMY_DOC};
+    use crate::source::queue_sources::batch_queue::{BatchQueue, BatchQueueMessage};
     use crate::source::queue_sources::memory_queue::MemoryQueueForTests;
     use crate::source::queue_sources::message::PreProcessedPayload;
     use crate::source::queue_sources::shared_state::shared_state_for_tests::init_state;
     use crate::source::{SourceActor, BATCH_NUM_BYTES_LIMIT};
 
     fn setup_coordinator(
-        queue: Arc<MemoryQueueForTests>,
+        queue: Arc<dyn Queue>,
         shared_state: QueueSharedState,
     ) -> QueueCoordinator {
         let pipeline_id = IndexingPipelineId {
@@ -464,6 +482,57 @@ mod tests {
         assert_eq!(batches.iter().map(|b| b.docs.len()).sum::<usize>(), 20);
     }
 
+    #[tokio::test]
+    async fn test_process_batch_queue_until_exhausted() {
+        let (dummy_doc_file_1, _) = generate_dummy_doc_file(false, 10).await;
+        let test_uri_1 = Uri::from_str(dummy_doc_file_1.path().to_str().unwrap()).unwrap();
+        let (dummy_doc_file_2, _) = generate_dummy_doc_file(false, 10).await;
+        let test_uri_2 = Uri::from_str(dummy_doc_file_2.path().to_str().unwrap()).unwrap();
+        let batch_queue = Arc::new(BatchQueue::new(
+            FileSourceMessageType::RawUri,
+            [&test_uri_1, &test_uri_2]
+                .iter()
+                .enumerate()
+                .map(|(idx, uri)| BatchQueueMessage {
+                    message_id: format!("message-{idx}"),
+                    payload: uri.to_string(),
+                    delivery_attempts: 1,
+                })
+                .collect(),
+        ));
+        let shared_state = init_state("test-index", Default::default());
+        let mut coordinator = setup_coordinator(batch_queue.clone(), shared_state);
+
+        let universe = Universe::with_accelerated_time();
+        let (source_mailbox, _source_inbox) = universe.create_test_mailbox::<SourceActor>();
+        let (doc_processor_mailbox, doc_processor_inbox) =
+            universe.create_test_mailbox::<DocProcessor>();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(serde_json::Value::Null);
+        let ctx: SourceContext =
+            ActorContext::for_test(&universe, source_mailbox, observable_state_tx);
+
+        let mut num_iterations = 0;
+        let exit_status = loop {
+            num_iterations += 1;
+            assert!(num_iterations < 20, "the source should exit");
+            if let Err(exit_status) = coordinator.emit_batches(&doc_processor_mailbox, &ctx).await {
+                break exit_status;
+            }
+        };
+        assert!(exit_status.is_success());
+        assert!(batch_queue.is_exhausted());
+
+        let num_docs: usize = doc_processor_inbox
+            .drain_for_test()
+            .into_iter()
+            .flat_map(|box_any| box_any.downcast::<RawDocBatch>().ok())
+            .map(|raw_doc_batch| raw_doc_batch.docs.len())
+            .sum();
+        assert_eq!(num_docs, 20);
+        assert_eq!(coordinator.observable_state().num_messages_processed, 2);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_process_local_duplicate_message() {
         let queue = Arc::new(MemoryQueueForTests::new());
@@ -20,6 +20,7 @@ use std::time::Instant;
 use anyhow::Context;
 use quickwit_common::rate_limited_warn;
 use quickwit_common::uri::Uri;
+use quickwit_config::FileSourceMessageType;
 use quickwit_metastore::checkpoint::PartitionId;
 use quickwit_proto::types::Position;
 use quickwit_storage::{OwnedBytes, StorageResolver};
@@ -38,6 +39,15 @@ pub enum MessageType {
     // RawData,
 }
 
+impl From<FileSourceMessageType> for MessageType {
+    fn from(message_type: FileSourceMessageType) -> Self {
+        match message_type {
+            FileSourceMessageType::S3Notification => MessageType::S3Notification,
+            FileSourceMessageType::RawUri => MessageType::RawUri,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MessageMetadata {
     /// The handle that should be used to acknowledge the message or change its visibility deadline
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod batch_queue;
 pub mod coordinator;
 mod helpers;
 mod local_state;
@@ -81,4 +82,11 @@ pub trait Queue: fmt::Debug + Send + Sync + 'static {
         ack_id: &str,
         suggested_deadline: Duration,
     ) -> anyhow::Result<Instant>;
+
+    /// Returns true if the queue will never return new messages, e.g because it
+    /// serves a fixed batch of messages that were all received already. The
+    /// source then terminates once the received messages are read.
+    fn is_exhausted(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,566 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use bytes::Bytes;
+use quickwit_actors::{ActorExitStatus, Mailbox, HEARTBEAT};
+use quickwit_common::temp_dir::TempDirectory;
+use quickwit_config::{build_doc_mapper, ReindexSourceParams};
+use quickwit_directories::{restore_row_doc_store, UnionDirectory};
+use quickwit_doc_mapper::DocMapper;
+use quickwit_metastore::checkpoint::PartitionId;
+use quickwit_metastore::{
+    IndexMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, Split, SplitState,
+};
+use quickwit_proto::metastore::{
+    IndexMetadataRequest, ListSplitsRequest, MetastoreService, MetastoreServiceClient, SourceType,
+};
+use quickwit_proto::types::{IndexUid, Position, SourceId, SplitId};
+use quickwit_query::query_ast::QueryAst;
+use quickwit_storage::Storage;
+use serde_json::{json, Value as JsonValue};
+use tantivy::collector::DocSetCollector;
+use tantivy::directory::MmapDirectory;
+use tantivy::schema::NamedFieldDocument;
+use tantivy::{DocAddress, Document, ReloadPolicy, Searcher, TantivyDocument};
+use tracing::info;
+
+use super::{BatchBuilder, BATCH_NUM_BYTES_LIMIT};
+use crate::actors::{open_index, DocProcessor};
+use crate::get_tantivy_directory_from_split_bundle;
+use crate::source::{Source, SourceContext, SourceRuntime, TypedSourceFactory};
+
+/// Reads the documents matching a query from the splits of another index.
+///
+/// The set of splits is the one that was published at the snapshot timestamp of the source
+/// params, so documents indexed in the meantime are not reindexed and merges do not produce
+/// duplicates. Each split is a partition of the checkpoint and the position is the number of
+/// matching documents of the split emitted so far, which makes the reindex resumable.
+pub struct ReindexSource {
+    source_id: SourceId,
+    source_index_uid: IndexUid,
+    storage: Arc<dyn Storage>,
+    doc_mapper: Arc<DocMapper>,
+    query_ast: QueryAst,
+    scratch_directory: TempDirectory,
+    pending_splits: VecDeque<(SplitId, Position)>,
+    current_split_opt: Option<SplitCursor>,
+    num_splits: usize,
+    num_splits_completed: usize,
+    num_docs_emitted: u64,
+}
+
+impl fmt::Debug for ReindexSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ReindexSource {{ source_id: {} }}", self.source_id)
+    }
+}
+
+/// Iterates over the documents of a downloaded split matching the reindex query.
+struct SplitCursor {
+    partition_id: PartitionId,
+    searcher: Searcher,
+    doc_addresses: Arc<Vec<DocAddress>>,
+    next_doc_idx: usize,
+    // Holds the split file, which is deleted once the cursor is dropped.
+    _split_directory: TempDirectory,
+}
+
+impl SplitCursor {
+    fn position(&self) -> Position {
+        if self.next_doc_idx == 0 {
+            Position::Beginning
+        } else {
+            Position::offset(self.next_doc_idx)
+        }
+    }
+}
+
+impl ReindexSource {
+    async fn open_split(
+        &self,
+        split_id: SplitId,
+        position: Position,
+    ) -> anyhow::Result<SplitCursor> {
+        let split_directory = self.scratch_directory.named_temp_child("split-")?;
+        let split_file = quickwit_common::split_file(&split_id);
+        let split_path = split_directory.path().join(&split_file);
+        self.storage
+            .copy_to_file(Path::new(&split_file), &split_path)
+            .await
+            .with_context(|| format!("failed to download split `{split_id}`"))?;
+        let doc_mapper = self.doc_mapper.clone();
+        let query_ast = self.query_ast.clone();
+        let (searcher, doc_addresses) =
+            tokio::task::spawn_blocking(move || search_split(split_path, &doc_mapper, &query_ast))
+                .await
+                .context("failed to search split")??;
+        let next_doc_idx = position.as_usize().unwrap_or_default();
+
+        if next_doc_idx > doc_addresses.len() {
+            anyhow::bail!(
+                "checkpoint of split `{split_id}` is beyond its {} matching documents",
+                doc_addresses.len()
+            );
+        }
+        Ok(SplitCursor {
+            partition_id: PartitionId::from(split_id),
+            searcher,
+            doc_addresses: Arc::new(doc_addresses),
+            next_doc_idx,
+            _split_directory: split_directory,
+        })
+    }
+}
+
+#[async_trait]
+impl Source for ReindexSource {
+    async fn emit_batches(
+        &mut self,
+        doc_processor_mailbox: &Mailbox<DocProcessor>,
+        ctx: &SourceContext,
+    ) -> Result<Duration, ActorExitStatus> {
+        if self.current_split_opt.is_none() {
+            let Some((split_id, position)) = self.pending_splits.pop_front() else {
+                // The reindex is over. The source stays idle until it is deleted.
+                tokio::time::sleep(*HEARTBEAT / 2).await;
+                return Ok(Duration::default());
+            };
+            let split_cursor = ctx
+                .protect_future(self.open_split(split_id, position))
+                .await?;
+            self.current_split_opt = Some(split_cursor);
+        }
+        let split_cursor = self
+            .current_split_opt
+            .as_mut()
+            .expect("a split should be open");
+        let from_position = split_cursor.position();
+
+        let searcher = split_cursor.searcher.clone();
+        let doc_mapper = self.doc_mapper.clone();
+        let doc_addresses = split_cursor.doc_addresses.clone();
+        let next_doc_idx = split_cursor.next_doc_idx;
+        let docs = ctx
+            .protect_future(tokio::task::spawn_blocking(move || {
+                read_docs(&searcher, &doc_mapper, &doc_addresses[next_doc_idx..])
+            }))
+            .await
+            .context("failed to read documents")??;
+
+        let num_docs = docs.len();
+        let mut batch_builder = BatchBuilder::with_capacity(num_docs, SourceType::Reindex);
+
+        for doc in docs {
+            batch_builder.add_doc(doc);
+        }
+        split_cursor.next_doc_idx += num_docs;
+
+        let is_eof = split_cursor.next_doc_idx == split_cursor.doc_addresses.len();
+        let to_position = if is_eof {
+            Position::eof(split_cursor.next_doc_idx)
+        } else {
+            Position::offset(split_cursor.next_doc_idx)
+        };
+        batch_builder
+            .checkpoint_delta
+            .record_partition_delta(
+                split_cursor.partition_id.clone(),
+                from_position,
+                to_position,
+            )
+            .context("failed to record partition delta")?;
+        self.num_docs_emitted += num_docs as u64;
+
+        if is_eof {
+            self.current_split_opt = None;
+            self.num_splits_completed += 1;
+
+            if self.num_splits_completed == self.num_splits {
+                info!(
+                    source_index_uid=%self.source_index_uid,
+                    num_docs_emitted=self.num_docs_emitted,
+                    "read all the documents to reindex"
+                );
+            }
+        }
+        doc_processor_mailbox
+            .send_message(batch_builder.build())
+            .await?;
+        Ok(Duration::ZERO)
+    }
+
+    fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn observable_state(&self) -> JsonValue {
+        json!({
+            "source_index_uid": self.source_index_uid,
+            "num_splits": self.num_splits,
+            "num_splits_completed": self.num_splits_completed,
+            "num_docs_emitted": self.num_docs_emitted,
+        })
+    }
+}
+
+/// Returns the addresses, in increasing order, of the documents of the split matching the query.
+///
+/// The row doc store of a split written with the columnar layout is restored next to the split
+/// file.
+fn search_split(
+    split_path: PathBuf,
+    doc_mapper: &DocMapper,
+    query_ast: &QueryAst,
+) -> anyhow::Result<(Searcher, Vec<DocAddress>)> {
+    let tokenizer_manager = doc_mapper.tokenizer_manager().tantivy_manager();
+    let split_directory = get_tantivy_directory_from_split_bundle(&split_path)?;
+    let mut index = open_index(split_directory.clone(), tokenizer_manager)?;
+    let restored_doc_stores_path = split_path
+        .parent()
+        .context("split path should have a parent directory")?;
+    let restored_doc_stores_directory = MmapDirectory::open(restored_doc_stores_path)?;
+
+    if restore_row_doc_store(&index, &restored_doc_stores_directory)? {
+        let union_directory = UnionDirectory::union_of(vec![
+            Box::new(restored_doc_stores_directory),
+            split_directory,
+        ]);
+        index = open_index(union_directory, tokenizer_manager)?;
+    }
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let (query, _) = doc_mapper.query(index.schema(), query_ast, false)?;
+    let mut doc_addresses: Vec<DocAddress> = searcher
+        .search(&*query, &DocSetCollector)?
+        .into_iter()
+        .collect();
+    doc_addresses.sort_unstable();
+    Ok((searcher, doc_addresses))
+}
+
+/// Reads and converts to JSON the stored documents at the given addresses until the batch size
+/// limit is reached.
+fn read_docs(
+    searcher: &Searcher,
+    doc_mapper: &DocMapper,
+    doc_addresses: &[DocAddress],
+) -> anyhow::Result<Vec<Bytes>> {
+    let schema = searcher.schema();
+    let mut docs = Vec::new();
+    let mut num_bytes = 0;
+
+    for doc_address in doc_addresses {
+        let doc: TantivyDocument = searcher.doc(*doc_address)?;
+        let NamedFieldDocument(named_field_doc_map) = doc.to_named_doc(schema);
+        let doc_json = doc_mapper.doc_to_json(named_field_doc_map)?;
+        let doc_bytes = Bytes::from(serde_json::to_vec(&doc_json)?);
+        num_bytes += doc_bytes.len() as u64;
+        docs.push(doc_bytes);
+
+        if num_bytes >= BATCH_NUM_BYTES_LIMIT {
+            break;
+        }
+    }
+    Ok(docs)
+}
+
+/// Returns the IDs of the splits of the index that were published at the given timestamp, sorted
+/// by split ID.
+async fn list_snapshot_split_ids(
+    metastore: MetastoreServiceClient,
+    index_uid: IndexUid,
+    snapshot_timestamp: i64,
+) -> anyhow::Result<Vec<SplitId>> {
+    let query = ListSplitsQuery::for_index(index_uid)
+        .with_split_states([SplitState::Published, SplitState::MarkedForDeletion])
+        .with_create_timestamp_lte(snapshot_timestamp);
+    let request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+    let splits = metastore
+        .list_splits(request)
+        .await?
+        .collect_splits()
+        .await?;
+    let mut split_ids: Vec<SplitId> = splits
+        .into_iter()
+        .filter(|split| is_published_at(split, snapshot_timestamp))
+        .map(|split| split.split_metadata.split_id)
+        .collect();
+    split_ids.sort_unstable();
+    Ok(split_ids)
+}
+
+/// Returns whether the split was published at the given timestamp. Splits marked for deletion
+/// afterwards, typically because they were merged, are still part of the snapshot.
+fn is_published_at(split: &Split, timestamp: i64) -> bool {
+    let Some(publish_timestamp) = split.publish_timestamp else {
+        return false;
+    };
+    if publish_timestamp > timestamp {
+        return false;
+    }
+    split.split_state == SplitState::Published || split.update_timestamp > timestamp
+}
+
+pub struct ReindexSourceFactory;
+
+#[async_trait]
+impl TypedSourceFactory for ReindexSourceFactory {
+    type Source = ReindexSource;
+    type Params = ReindexSourceParams;
+
+    async fn typed_create_source(
+        source_runtime: SourceRuntime,
+        params: ReindexSourceParams,
+    ) -> anyhow::Result<ReindexSource> {
+        let source_id = source_runtime.source_id().to_string();
+        let index_metadata_request =
+            IndexMetadataRequest::for_index_uid(params.source_index_uid.clone());
+        let source_index_metadata = source_runtime
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let source_index_config = &source_index_metadata.index_config;
+        let doc_mapper = build_doc_mapper(
+            &source_index_config.doc_mapping,
+            &source_index_config.search_settings,
+        )?;
+        let query_ast: QueryAst =
+            serde_json::from_str(&params.query_ast).context("invalid query_ast json")?;
+        let query_ast = query_ast
+            .parse_user_query(doc_mapper.default_search_fields())
+            .context("invalid query")?;
+        let storage = source_runtime
+            .storage_resolver
+            .resolve(source_index_metadata.index_uri())
+            .await?;
+        let storage = source_runtime.storage_resolver.wrap_split_storage(
+            storage,
+            None,
+            source_index_metadata.index_config.requires_encryption(),
+        )?;
+        let scratch_directory = source_runtime
+            .indexing_directory
+            .named_temp_child("reindex-")?;
+
+        let split_ids = list_snapshot_split_ids(
+            source_runtime.metastore.clone(),
+            params.source_index_uid.clone(),
+            params.snapshot_timestamp,
+        )
+        .await?;
+        let checkpoint = source_runtime.fetch_checkpoint().await?;
+        let num_splits = split_ids.len();
+        let mut pending_splits = VecDeque::with_capacity(num_splits);
+
+        for split_id in split_ids {
+            let partition_id = PartitionId::from(split_id.as_str());
+            let position = checkpoint
+                .position_for_partition(&partition_id)
+                .cloned()
+                .unwrap_or_default();
+
+            if !position.is_eof() {
+                pending_splits.push_back((split_id, position));
+            }
+        }
+        let num_splits_completed = num_splits - pending_splits.len();
+
+        info!(
+            source_index_uid=%params.source_index_uid,
+            num_splits,
+            num_splits_completed,
+            "starting reindex source"
+        );
+        Ok(ReindexSource {
+            source_id,
+            source_index_uid: params.source_index_uid,
+            storage,
+            doc_mapper,
+            query_ast,
+            scratch_directory,
+            pending_splits,
+            current_split_opt: None,
+            num_splits,
+            num_splits_completed,
+            num_docs_emitted: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::path::PathBuf;
+
+    use quickwit_actors::{ActorContext, Universe};
+    use quickwit_common::pubsub::EventBroker;
+    use quickwit_config::{IndexingSettings, SourceConfig};
+    use quickwit_ingest::IngesterPool;
+    use quickwit_metastore::{AddSourceRequestExt, SplitMetadata};
+    use quickwit_proto::indexing::IndexingPipelineId;
+    use quickwit_proto::metastore::AddSourceRequest;
+    use quickwit_proto::types::PipelineUid;
+    use quickwit_query::query_ast::query_ast_from_user_text;
+    use tokio::sync::watch;
+
+    use super::*;
+    use crate::models::RawDocBatch;
+    use crate::source::SourceActor;
+    use crate::TestSandbox;
+
+    #[test]
+    fn test_is_published_at() {
+        let split = |split_state: SplitState, publish_timestamp_opt, update_timestamp| Split {
+            split_state,
+            update_timestamp,
+            publish_timestamp: publish_timestamp_opt,
+            split_metadata: SplitMetadata::default(),
+        };
+        assert!(is_published_at(
+            &split(SplitState::Published, Some(10), 10),
+            10
+        ));
+        assert!(!is_published_at(
+            &split(SplitState::Published, Some(11), 11),
+            10
+        ));
+        assert!(!is_published_at(&split(SplitState::Staged, None, 5), 10));
+        assert!(is_published_at(
+            &split(SplitState::MarkedForDeletion, Some(5), 11),
+            10
+        ));
+        assert!(!is_published_at(
+            &split(SplitState::MarkedForDeletion, Some(5), 10),
+            10
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reindex_source() {
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: severity
+                type: text
+                tokenizer: raw
+        "#;
+        let test_sandbox = TestSandbox::create("test-index", doc_mapping_yaml, "", &["body"])
+            .await
+            .unwrap();
+        test_sandbox
+            .add_documents(vec![
+                json!({"body": "first", "severity": "ERROR"}),
+                json!({"body": "second", "severity": "INFO"}),
+            ])
+            .await
+            .unwrap();
+        test_sandbox
+            .add_documents(vec![
+                json!({"body": "third", "severity": "ERROR"}),
+                json!({"body": "fourth", "severity": "ERROR"}),
+            ])
+            .await
+            .unwrap();
+        let index_uid = test_sandbox.index_uid();
+        let metastore = test_sandbox.metastore();
+
+        let query_ast = query_ast_from_user_text("severity:ERROR", None);
+        let params = ReindexSourceParams {
+            source_index_uid: index_uid.clone(),
+            query_ast: serde_json::to_string(&query_ast).unwrap(),
+            snapshot_timestamp: time::OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        let source_config =
+            SourceConfig::reindex("reindex-source".to_string(), params.clone(), None);
+        let add_source_request =
+            AddSourceRequest::try_from_source_config(index_uid.clone(), &source_config).unwrap();
+        metastore.add_source(add_source_request).await.unwrap();
+
+        let source_runtime = SourceRuntime {
+            pipeline_id: IndexingPipelineId {
+                node_id: test_sandbox.node_id(),
+                index_uid,
+                source_id: source_config.source_id.clone(),
+                pipeline_uid: PipelineUid::for_test(0u128),
+            },
+            source_config,
+            metastore,
+            ingester_pool: IngesterPool::default(),
+            queues_dir_path: PathBuf::from("./queues"),
+            indexing_directory: TempDirectory::for_test(),
+            storage_resolver: test_sandbox.storage_resolver(),
+            event_broker: EventBroker::default(),
+            indexing_setting: IndexingSettings::default(),
+        };
+        let mut source = ReindexSourceFactory::typed_create_source(source_runtime, params)
+            .await
+            .unwrap();
+        assert_eq!(source.observable_state()["num_splits"], 2);
+
+        let universe = test_sandbox.universe();
+        let (source_mailbox, _source_inbox) = universe.create_test_mailbox::<SourceActor>();
+        let (doc_processor_mailbox, doc_processor_inbox) =
+            universe.create_test_mailbox::<DocProcessor>();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(JsonValue::Null);
+        let ctx: SourceContext =
+            ActorContext::for_test(universe, source_mailbox, observable_state_tx);
+
+        for _ in 0..2 {
+            source
+                .emit_batches(&doc_processor_mailbox, &ctx)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            source.observable_state()["num_splits_completed"],
+            source.observable_state()["num_splits"]
+        );
+        assert_eq!(source.observable_state()["num_docs_emitted"], 3);
+
+        let batches = doc_processor_inbox.drain_for_test_typed::<RawDocBatch>();
+        assert_eq!(batches.len(), 2);
+
+        let mut bodies = BTreeSet::new();
+
+        for batch in &batches {
+            for doc in &batch.docs {
+                let doc_json: JsonValue = serde_json::from_slice(doc).unwrap();
+                assert_eq!(doc_json["severity"], "ERROR");
+                bodies.insert(doc_json["body"].as_str().unwrap().to_string());
+            }
+            for (_, partition_delta) in batch.checkpoint_delta.iter() {
+                assert!(partition_delta.to.is_eof());
+            }
+        }
+        assert_eq!(
+            bodies,
+            BTreeSet::from_iter(["first", "third", "fourth"].map(str::to_string))
+        );
+        test_sandbox.assert_quit().await;
+    }
+}
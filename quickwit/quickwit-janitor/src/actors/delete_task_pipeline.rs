@@ -187,7 +187,12 @@ impl DeleteTaskPipeline {
         let doc_mapper =
             build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
         let tag_fields = doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("MergePackager", tag_fields, uploader_mailbox);
+        let packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            index_config.indexing_settings.docstore_layout,
+            uploader_mailbox,
+        );
         let (packager_mailbox, packager_supervisor_handler) = ctx.spawn_actor().supervise(packager);
         let pipeline_id = MergePipelineId {
             node_id: NodeId::from("unknown"),
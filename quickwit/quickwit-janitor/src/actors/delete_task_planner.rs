@@ -320,6 +320,9 @@ impl DeleteTaskPlanner {
                 IndexMetasForLeafSearch {
                     doc_mapper_str: doc_mapper_str.to_string(),
                     index_uri,
+                    // The splits are rewritten through the index storage, which rejects the
+                    // splits written in clear if the index requires encryption.
+                    require_encryption: false,
                 },
             );
             let leaf_search_request = jobs_to_leaf_request(
@@ -336,8 +339,8 @@ impl DeleteTaskPlanner {
         Ok(false)
     }
 
-    /// Fetches stale splits from [`quickwit_metastore::Metastore`] and excludes immature splits and
-    /// split already among ongoing delete operations.
+    /// Fetches stale splits from [`quickwit_metastore::Metastore`] and excludes immature splits,
+    /// splits moved to a storage tier, and split already among ongoing delete operations.
     async fn get_relevant_stale_splits(
         &mut self,
         index_uid: IndexUid,
@@ -362,6 +365,9 @@ impl DeleteTaskPlanner {
         let ongoing_delete_operations = self.ongoing_delete_operations_inventory.list();
         let filtered_splits = stale_splits
             .into_iter()
+            // Splits moved to a storage tier are not rewritten by the delete pipeline, which only
+            // reads from and writes to the index storage.
+            .filter(|stale_split| stale_split.split_metadata.storage_uri.is_none())
             .filter(|stale_split| {
                 !ongoing_delete_operations.iter().any(|operation| {
                     operation
@@ -163,6 +163,11 @@ impl DeleteTaskService {
     ) -> anyhow::Result<()> {
         let index_uri = index_config.index_uri.clone();
         let index_storage = self.storage_resolver.resolve(&index_uri).await?;
+        let index_storage = self.storage_resolver.wrap_split_storage(
+            index_storage,
+            index_config.encryption_key_id(),
+            index_config.requires_encryption(),
+        )?;
         let index_metadata_request =
             IndexMetadataRequest::for_index_id(index_config.index_id.to_string());
         let index_metadata = self
@@ -131,6 +131,7 @@ impl GarbageCollector {
 
         let gc_res = run_garbage_collect(
             index_storages,
+            &self.storage_resolver,
             self.metastore.clone(),
             STAGED_GRACE_PERIOD,
             split_deletion_grace_period(),
@@ -362,6 +363,7 @@ mod tests {
 
         let result = run_garbage_collect(
             hashmap(index_uid, Arc::new(mock_storage)),
+            &StorageResolver::unconfigured(),
             MetastoreServiceClient::from_mock(mock_metastore),
             STAGED_GRACE_PERIOD,
             split_deletion_grace_period(),
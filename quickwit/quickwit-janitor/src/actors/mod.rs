@@ -16,8 +16,14 @@ mod delete_task_pipeline;
 mod delete_task_planner;
 mod delete_task_service;
 mod garbage_collector;
+mod monitor_executor;
 mod retention_policy_executor;
+mod rollup_executor;
+mod tiering_executor;
 
 pub use delete_task_service::{DeleteTaskService, DELETE_SERVICE_TASK_DIR_NAME};
 pub use garbage_collector::GarbageCollector;
+pub use monitor_executor::MonitorExecutor;
 pub use retention_policy_executor::RetentionPolicyExecutor;
+pub use rollup_executor::{RollupExecutor, ROLLUP_SCRATCH_DIR_NAME};
+pub use tiering_executor::{TieringExecutor, TIERING_SCRATCH_DIR_NAME};
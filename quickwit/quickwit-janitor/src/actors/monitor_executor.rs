@@ -0,0 +1,643 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler};
+use quickwit_config::{MonitorConfig, MonitorId};
+use quickwit_metastore::{
+    GetMonitorResponseExt, ListMonitorsResponseExt, MonitorStatus, UpdateMonitorStateRequestExt,
+};
+use quickwit_proto::metastore::{
+    GetMonitorRequest, ListMonitorsRequest, MetastoreError, MetastoreService,
+    MetastoreServiceClient, UpdateMonitorStateRequest,
+};
+use quickwit_search::{ClusterClient, SearcherContext};
+use serde::Serialize;
+use tracing::{debug, error, info};
+
+use crate::metrics::JANITOR_METRICS;
+use crate::monitor_evaluation::{run_monitor_evaluation, MonitorActionClients};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MonitorExecutorCounters {
+    /// The number of refresh passes of the monitors.
+    pub num_refresh_passes: usize,
+
+    /// The number of monitor evaluations.
+    pub num_evaluations: usize,
+
+    /// The number of evaluations that triggered the monitors' actions.
+    pub num_triggered_evaluations: usize,
+
+    /// The number of evaluations that failed.
+    pub num_failed_evaluations: usize,
+}
+
+#[derive(Debug)]
+struct Loop;
+
+#[derive(Debug)]
+struct Evaluate {
+    monitor_id: MonitorId,
+    /// Identifies the chain of scheduled evaluations of a monitor, so that a monitor disabled and
+    /// enabled again between two refresh passes does not end up being evaluated twice.
+    generation: u64,
+}
+
+struct ScheduledMonitor {
+    monitor_config: MonitorConfig,
+    generation: u64,
+}
+
+/// An actor that evaluates the monitors stored in the metastore according to their schedule. It
+/// keeps the enabled monitors in a cache periodically refreshed from the metastore.
+pub struct MonitorExecutor {
+    metastore: MetastoreServiceClient,
+    searcher_context: Arc<SearcherContext>,
+    cluster_client: ClusterClient,
+    action_clients: MonitorActionClients,
+    monitors: HashMap<MonitorId, ScheduledMonitor>,
+    next_generation: u64,
+    counters: MonitorExecutorCounters,
+}
+
+impl MonitorExecutor {
+    pub fn new(
+        metastore: MetastoreServiceClient,
+        searcher_context: Arc<SearcherContext>,
+        cluster_client: ClusterClient,
+    ) -> Self {
+        Self {
+            metastore,
+            searcher_context,
+            cluster_client,
+            action_clients: MonitorActionClients::default(),
+            monitors: HashMap::new(),
+            next_generation: 0,
+            counters: MonitorExecutorCounters::default(),
+        }
+    }
+
+    /// Monitors refresh loop handler logic.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn handle_refresh_loop(&mut self, ctx: &ActorContext<Self>) {
+        debug!("loading monitors from the metastore");
+        self.counters.num_refresh_passes += 1;
+
+        let list_monitors_result = ctx
+            .protect_future(self.metastore.list_monitors(ListMonitorsRequest {}))
+            .await;
+        let response = match list_monitors_result {
+            Ok(response) => response,
+            Err(error) => {
+                error!(%error, "failed to list monitors from the metastore");
+                return;
+            }
+        };
+        let monitors_metadata = match response.deserialize_monitors_metadata() {
+            Ok(monitors_metadata) => monitors_metadata,
+            Err(error) => {
+                error!(%error, "failed to deserialize monitors metadata");
+                return;
+            }
+        };
+        debug!(
+            "loaded {} monitors from the metastore",
+            monitors_metadata.len()
+        );
+
+        let enabled_monitor_configs: HashMap<MonitorId, MonitorConfig> = monitors_metadata
+            .into_iter()
+            .filter(|monitor_metadata| monitor_metadata.monitor_config.enabled)
+            .map(|monitor_metadata| {
+                (
+                    monitor_metadata.monitor_config.monitor_id.clone(),
+                    monitor_metadata.monitor_config,
+                )
+            })
+            .collect();
+
+        // Removing a monitor from the cache interrupts its chain of scheduled evaluations.
+        self.monitors
+            .retain(|monitor_id, _| enabled_monitor_configs.contains_key(monitor_id));
+
+        for (monitor_id, monitor_config) in enabled_monitor_configs {
+            if let Some(scheduled_monitor) = self.monitors.get_mut(&monitor_id) {
+                // Update the cached configuration in case the monitor was updated.
+                scheduled_monitor.monitor_config = monitor_config;
+                continue;
+            }
+            match monitor_config.duration_until_next_evaluation() {
+                Ok(next_interval) => {
+                    let generation = self.next_generation;
+                    self.next_generation += 1;
+
+                    info!(monitor_id=%monitor_id, scheduled_in=?next_interval, "monitor-schedule-evaluation");
+                    let message = Evaluate {
+                        monitor_id: monitor_id.clone(),
+                        generation,
+                    };
+                    self.monitors.insert(
+                        monitor_id,
+                        ScheduledMonitor {
+                            monitor_config,
+                            generation,
+                        },
+                    );
+                    ctx.schedule_self_msg(next_interval, message);
+                }
+                Err(error) => {
+                    error!(monitor_id=%monitor_id, error=?error, "failed to compute the next evaluation time of the monitor");
+                }
+            }
+        }
+    }
+
+    /// Evaluates a monitor and saves its new state.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn evaluate_monitor(&mut self, monitor_id: &str, ctx: &ActorContext<Self>) {
+        let get_monitor_request = GetMonitorRequest {
+            monitor_id: monitor_id.to_string(),
+        };
+        let get_monitor_result = ctx
+            .protect_future(self.metastore.get_monitor(get_monitor_request))
+            .await;
+        let mut monitor_metadata = match get_monitor_result
+            .and_then(|response| response.deserialize_monitor_metadata())
+        {
+            Ok(monitor_metadata) => monitor_metadata,
+            Err(MetastoreError::NotFound(_)) => {
+                debug!(monitor_id=%monitor_id, "the monitor might have been deleted");
+                self.monitors.remove(monitor_id);
+                return;
+            }
+            Err(error) => {
+                error!(monitor_id=%monitor_id, %error, "failed to fetch monitor from the metastore");
+                return;
+            }
+        };
+        let evaluation = run_monitor_evaluation(
+            &mut monitor_metadata,
+            self.metastore.clone(),
+            &self.searcher_context,
+            &self.cluster_client,
+            &self.action_clients,
+            ctx,
+        )
+        .await;
+
+        self.counters.num_evaluations += 1;
+        let evaluation_status = evaluation.status();
+
+        match evaluation_status {
+            MonitorStatus::Triggered => self.counters.num_triggered_evaluations += 1,
+            MonitorStatus::Error => {
+                self.counters.num_failed_evaluations += 1;
+                error!(monitor_id=%monitor_id, error=?evaluation.error, action_errors=?evaluation.action_errors, "failed to evaluate monitor");
+            }
+            MonitorStatus::Ok | MonitorStatus::Pending => {}
+        }
+        let status_label = match evaluation_status {
+            MonitorStatus::Ok | MonitorStatus::Pending => "ok",
+            MonitorStatus::Triggered => "triggered",
+            MonitorStatus::Error => "error",
+        };
+        JANITOR_METRICS
+            .monitor_evaluations
+            .with_label_values([status_label])
+            .inc();
+
+        let update_monitor_state_request = match UpdateMonitorStateRequest::try_from_monitor_state(
+            monitor_id,
+            &monitor_metadata.monitor_state,
+        ) {
+            Ok(request) => request,
+            Err(error) => {
+                error!(monitor_id=%monitor_id, %error, "failed to serialize monitor state");
+                return;
+            }
+        };
+        if let Err(error) = ctx
+            .protect_future(
+                self.metastore
+                    .update_monitor_state(update_monitor_state_request),
+            )
+            .await
+        {
+            error!(monitor_id=%monitor_id, %error, "failed to save monitor state");
+        }
+    }
+}
+
+#[async_trait]
+impl Actor for MonitorExecutor {
+    type ObservableState = MonitorExecutorCounters;
+
+    fn observable_state(&self) -> Self::ObservableState {
+        self.counters.clone()
+    }
+
+    fn name(&self) -> String {
+        "MonitorExecutor".to_string()
+    }
+
+    async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        self.handle(Loop, ctx).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Loop> for MonitorExecutor {
+    type Reply = ();
+
+    async fn handle(&mut self, _: Loop, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        self.handle_refresh_loop(ctx).await;
+        ctx.schedule_self_msg(REFRESH_INTERVAL, Loop);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Evaluate> for MonitorExecutor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: Evaluate,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        match self.monitors.get(&message.monitor_id) {
+            Some(scheduled_monitor) if scheduled_monitor.generation == message.generation => {}
+            _ => {
+                debug!(monitor_id=%message.monitor_id, "the monitor might have been deleted or disabled");
+                return Ok(());
+            }
+        }
+        info!(monitor_id=%message.monitor_id, "monitor-evaluate");
+        self.evaluate_monitor(&message.monitor_id, ctx).await;
+
+        let Some(scheduled_monitor) = self.monitors.get(&message.monitor_id) else {
+            return Ok(());
+        };
+        match scheduled_monitor
+            .monitor_config
+            .duration_until_next_evaluation()
+        {
+            Ok(next_interval) => {
+                info!(monitor_id=%message.monitor_id, scheduled_in=?next_interval, "monitor-schedule-evaluation");
+                ctx.schedule_self_msg(next_interval, message);
+            }
+            Err(error) => {
+                // Since we have failed to schedule the next evaluation of this monitor, we remove
+                // it from the cache for it to be retried next time it gets added back by the
+                // refresh loop.
+                self.monitors.remove(&message.monitor_id);
+                error!(monitor_id=%message.monitor_id, error=?error, "failed to compute the next evaluation time of the monitor");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use quickwit_actors::Universe;
+    use quickwit_common::ServiceStream;
+    use quickwit_metastore::{
+        IndexMetadata, IndexMetadataResponseExt, ListSplitsResponseExt, MonitorEvaluation,
+        MonitorMetadata, Split, SplitMaturity, SplitMetadata, SplitState,
+    };
+    use quickwit_proto::metastore::{
+        EmptyResponse, EntityKind, GetMonitorResponse, IndexMetadataResponse, ListMonitorsResponse,
+        ListSplitsResponse, MockMetastoreService,
+    };
+    use quickwit_proto::search::LeafSearchResponse;
+    use quickwit_search::{searcher_pool_for_test, MockSearchService, SearchJobPlacer};
+
+    use super::*;
+
+    fn monitor_executor_for_test(
+        mock_metastore: MockMetastoreService,
+        mock_search_service: MockSearchService,
+    ) -> MonitorExecutor {
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1000", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer);
+        MonitorExecutor::new(
+            MetastoreServiceClient::from_mock(mock_metastore),
+            Arc::new(SearcherContext::for_test()),
+            cluster_client,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_monitor_executor_refresh() {
+        let universe = Universe::with_accelerated_time();
+
+        let mut disabled_monitor_config = MonitorConfig::for_test("test-monitor-bar", "test-index");
+        disabled_monitor_config.enabled = false;
+
+        let monitors_metadata = vec![
+            MonitorMetadata::new(MonitorConfig::for_test("test-monitor-foo", "test-index")),
+            MonitorMetadata::new(disabled_monitor_config),
+        ];
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_monitors()
+            .returning(move |_request| {
+                Ok(ListMonitorsResponse::try_from_monitors_metadata(&monitors_metadata).unwrap())
+            });
+        mock_metastore.expect_get_monitor().returning(|request| {
+            Err(MetastoreError::NotFound(EntityKind::Monitor {
+                monitor_id: request.monitor_id,
+            }))
+        });
+        let monitor_executor = monitor_executor_for_test(mock_metastore, MockSearchService::new());
+        let (_mailbox, handle) = universe.spawn_builder().spawn(monitor_executor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_refresh_passes, 1);
+        assert_eq!(counters.num_evaluations, 0);
+
+        universe.sleep(REFRESH_INTERVAL).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_refresh_passes, 2);
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_monitor_executor_evaluate() {
+        let universe = Universe::with_accelerated_time();
+
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid = index_metadata.index_uid.clone();
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.query = "body:error".to_string();
+        let shift_time_by =
+            monitor_config.duration_until_next_evaluation().unwrap() + Duration::from_secs(1);
+
+        let mut monitor_metadata = MonitorMetadata::new(monitor_config);
+        // Move the checkpoint back in time so that the split published below is picked up.
+        monitor_metadata.monitor_state.checkpoint_timestamp -= 3_600;
+        let checkpoint_timestamp = monitor_metadata.monitor_state.checkpoint_timestamp;
+
+        let mut mock_metastore = MockMetastoreService::new();
+        let monitors_metadata = vec![monitor_metadata.clone()];
+        mock_metastore
+            .expect_list_monitors()
+            .returning(move |_request| {
+                Ok(ListMonitorsResponse::try_from_monitors_metadata(&monitors_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_get_monitor()
+            .returning(move |_request| {
+                Ok(GetMonitorResponse::try_from_monitor_metadata(&monitor_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_index_metadata()
+            .returning(move |_request| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(&index_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_list_splits()
+            .returning(move |_request| {
+                let split_metadata = SplitMetadata {
+                    split_id: "split-1".to_string(),
+                    index_uid: index_uid.clone(),
+                    num_docs: 10,
+                    ..Default::default()
+                };
+                let merged_split_metadata = SplitMetadata {
+                    split_id: "split-2".to_string(),
+                    index_uid: index_uid.clone(),
+                    num_docs: 10,
+                    num_merge_ops: 1,
+                    ..Default::default()
+                };
+                let splits = vec![
+                    Split {
+                        split_state: SplitState::Published,
+                        split_metadata,
+                        update_timestamp: checkpoint_timestamp + 60,
+                        publish_timestamp: Some(checkpoint_timestamp + 60),
+                    },
+                    Split {
+                        split_state: SplitState::Published,
+                        split_metadata: merged_split_metadata,
+                        update_timestamp: checkpoint_timestamp + 60,
+                        publish_timestamp: Some(checkpoint_timestamp + 60),
+                    },
+                ];
+                let response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(response)]))
+            });
+        mock_metastore
+            .expect_update_monitor_state()
+            .times(1)
+            .returning(move |request| {
+                let monitor_state = request.deserialize_monitor_state().unwrap();
+                assert_eq!(monitor_state.status, MonitorStatus::Triggered);
+                assert_eq!(monitor_state.history.len(), 1);
+
+                let evaluation: &MonitorEvaluation = monitor_state.last_evaluation().unwrap();
+                assert_eq!(evaluation.num_splits, 1);
+                assert_eq!(evaluation.value, Some(3.0));
+                assert!(evaluation.triggered);
+                assert!(evaluation.error.is_none());
+                assert!(monitor_state.checkpoint_timestamp > checkpoint_timestamp);
+                assert!(monitor_state.immature_splits.is_some());
+                Ok(EmptyResponse {})
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(1)
+            .returning(|leaf_search_request| {
+                let split_ids: Vec<&str> = leaf_search_request.leaf_requests[0]
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect();
+                assert_eq!(split_ids, ["split-1"]);
+
+                Ok(LeafSearchResponse {
+                    num_hits: 3,
+                    num_attempted_splits: 1,
+                    num_successful_splits: 1,
+                    ..Default::default()
+                })
+            });
+        let monitor_executor = monitor_executor_for_test(mock_metastore, mock_search_service);
+        let (_mailbox, handle) = universe.spawn_builder().spawn(monitor_executor);
+
+        universe.sleep(shift_time_by).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_evaluations, 1);
+        assert_eq!(counters.num_triggered_evaluations, 1);
+        assert_eq!(counters.num_failed_evaluations, 0);
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_monitor_executor_evaluate_split_merged_before_evaluation() {
+        let universe = Universe::with_accelerated_time();
+
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid = index_metadata.index_uid.clone();
+
+        let monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        let shift_time_by =
+            monitor_config.duration_until_next_evaluation().unwrap() + Duration::from_secs(1);
+
+        let mut monitor_metadata = MonitorMetadata::new(monitor_config);
+        monitor_metadata.monitor_state.checkpoint_timestamp -= 3_600;
+        let checkpoint_timestamp = monitor_metadata.monitor_state.checkpoint_timestamp;
+        // `split-1` and `split-2` were published up to the checkpoint, and evaluated.
+        let maturity_timestamp = checkpoint_timestamp + 7 * 24 * 3_600;
+        monitor_metadata.monitor_state.immature_splits = Some(BTreeMap::from([
+            ("split-1".to_string(), maturity_timestamp),
+            ("split-2".to_string(), maturity_timestamp),
+            ("split-3".to_string(), maturity_timestamp),
+        ]));
+
+        let mut mock_metastore = MockMetastoreService::new();
+        let monitors_metadata = vec![monitor_metadata.clone()];
+        mock_metastore
+            .expect_list_monitors()
+            .returning(move |_request| {
+                Ok(ListMonitorsResponse::try_from_monitors_metadata(&monitors_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_get_monitor()
+            .returning(move |_request| {
+                Ok(GetMonitorResponse::try_from_monitor_metadata(&monitor_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_index_metadata()
+            .returning(move |_request| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(&index_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_list_splits()
+            .times(1)
+            .returning(move |_request| {
+                let immature = SplitMaturity::Immature {
+                    maturation_period: Duration::from_secs(2 * 3_600),
+                };
+                // Merged from splits that were already evaluated.
+                let old_merged_split_metadata = SplitMetadata {
+                    split_id: "split-12".to_string(),
+                    index_uid: index_uid.clone(),
+                    num_docs: 20,
+                    num_merge_ops: 1,
+                    maturity: immature,
+                    create_timestamp: checkpoint_timestamp + 30,
+                    replaced_split_ids: vec!["split-1".to_string(), "split-2".to_string()],
+                    ..Default::default()
+                };
+                // Merged from `split-3` and from `split-4` and `split-5`, which were published
+                // after the checkpoint and merged before the evaluation.
+                let new_merged_split_metadata = SplitMetadata {
+                    split_id: "split-345".to_string(),
+                    index_uid: index_uid.clone(),
+                    num_docs: 30,
+                    num_merge_ops: 1,
+                    maturity: immature,
+                    create_timestamp: checkpoint_timestamp + 60,
+                    replaced_split_ids: vec![
+                        "split-3".to_string(),
+                        "split-4".to_string(),
+                        "split-5".to_string(),
+                    ],
+                    ..Default::default()
+                };
+                let splits = vec![
+                    Split {
+                        split_state: SplitState::Published,
+                        split_metadata: old_merged_split_metadata,
+                        update_timestamp: checkpoint_timestamp + 30,
+                        publish_timestamp: Some(checkpoint_timestamp + 30),
+                    },
+                    Split {
+                        split_state: SplitState::Published,
+                        split_metadata: new_merged_split_metadata,
+                        update_timestamp: checkpoint_timestamp + 60,
+                        publish_timestamp: Some(checkpoint_timestamp + 60),
+                    },
+                ];
+                let response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(response)]))
+            });
+        mock_metastore
+            .expect_update_monitor_state()
+            .times(1)
+            .returning(move |request| {
+                let monitor_state = request.deserialize_monitor_state().unwrap();
+                let evaluation: &MonitorEvaluation = monitor_state.last_evaluation().unwrap();
+                assert_eq!(evaluation.num_splits, 1);
+                assert_eq!(evaluation.value, Some(4.0));
+                assert!(evaluation.error.is_none());
+
+                let immature_split_ids: Vec<&str> = monitor_state
+                    .immature_splits
+                    .as_ref()
+                    .unwrap()
+                    .keys()
+                    .map(|split_id| split_id.as_str())
+                    .collect();
+                assert_eq!(immature_split_ids, ["split-12", "split-345"]);
+                Ok(EmptyResponse {})
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(1)
+            .returning(|leaf_search_request| {
+                let split_ids: Vec<&str> = leaf_search_request.leaf_requests[0]
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect();
+                assert_eq!(split_ids, ["split-345"]);
+
+                Ok(LeafSearchResponse {
+                    num_hits: 4,
+                    num_attempted_splits: 1,
+                    num_successful_splits: 1,
+                    ..Default::default()
+                })
+            });
+        let monitor_executor = monitor_executor_for_test(mock_metastore, mock_search_service);
+        let (_mailbox, handle) = universe.spawn_builder().spawn(monitor_executor);
+
+        universe.sleep(shift_time_by).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_evaluations, 1);
+        assert_eq!(counters.num_failed_evaluations, 0);
+
+        universe.assert_quit().await;
+    }
+}
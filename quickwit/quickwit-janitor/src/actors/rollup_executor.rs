@@ -0,0 +1,373 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler};
+use quickwit_common::pubsub::EventBroker;
+use quickwit_common::temp_dir;
+use quickwit_config::{IndexConfig, NodeConfig};
+use quickwit_metastore::ListIndexesMetadataResponseExt;
+use quickwit_proto::metastore::{
+    ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::types::IndexUid;
+use quickwit_search::{ClusterClient, SearcherContext};
+use quickwit_storage::StorageResolver;
+use serde::Serialize;
+use tracing::{debug, error, info};
+
+use crate::metrics::JANITOR_METRICS;
+use crate::rollup_execution::{run_execute_rollup_policy, RollupResources};
+
+const RUN_INTERVAL: Duration = Duration::from_secs(60 * 60); // 1 hour
+
+/// Name of the directory, relative to the data directory, in which rolled-up splits are built.
+pub const ROLLUP_SCRATCH_DIR_NAME: &str = "rollup";
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RollupExecutorCounters {
+    /// The number of refresh the config passes.
+    pub num_refresh_passes: usize,
+
+    /// The number of execution passes.
+    pub num_execution_passes: usize,
+
+    /// The number of failed execution passes.
+    pub num_failed_execution_passes: usize,
+
+    /// The number of intervals rolled up.
+    pub num_rolled_up_intervals: usize,
+
+    /// The number of documents written to the target indexes.
+    pub num_rolled_up_docs: usize,
+
+    /// The number of splits marked for deletion after being rolled up.
+    pub num_deleted_splits: usize,
+}
+
+#[derive(Debug)]
+struct Loop;
+
+#[derive(Debug)]
+struct Execute {
+    index_uid: IndexUid,
+}
+
+/// An actor for scheduling rollup policy execution on all indexes.
+/// Like the [`RetentionPolicyExecutor`](super::RetentionPolicyExecutor), it keeps a cache of the
+/// indexes that have a rollup policy configured and periodically updates it.
+pub struct RollupExecutor {
+    resources: RollupResources,
+    /// A map of index_id to index config of the indexes managed by this executor.
+    index_configs: HashMap<String, IndexConfig>,
+    counters: RollupExecutorCounters,
+}
+
+impl RollupExecutor {
+    pub fn new(
+        node_config: &NodeConfig,
+        metastore: MetastoreServiceClient,
+        storage_resolver: StorageResolver,
+        searcher_context: Arc<SearcherContext>,
+        cluster_client: ClusterClient,
+        event_broker: EventBroker,
+    ) -> Self {
+        let resources = RollupResources {
+            metastore,
+            storage_resolver,
+            searcher_context,
+            cluster_client,
+            event_broker,
+            node_id: node_config.node_id.clone(),
+            default_index_root_uri: node_config.default_index_root_uri.clone(),
+            scratch_directory_path: node_config.data_dir_path.join(ROLLUP_SCRATCH_DIR_NAME),
+            max_concurrent_split_uploads: node_config.indexer_config.max_concurrent_split_uploads,
+        };
+        Self {
+            resources,
+            index_configs: HashMap::new(),
+            counters: RollupExecutorCounters::default(),
+        }
+    }
+
+    /// Indexes refresh Loop handler logic.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn handle_refresh_loop(&mut self, ctx: &ActorContext<Self>) {
+        debug!("loading indexes from the metastore");
+        self.counters.num_refresh_passes += 1;
+
+        let response = match self
+            .resources
+            .metastore
+            .list_indexes_metadata(ListIndexesMetadataRequest::all())
+            .await
+        {
+            Ok(response) => response,
+            Err(error) => {
+                error!(%error, "failed to list indexes from the metastore");
+                return;
+            }
+        };
+        let indexes = match response.deserialize_indexes_metadata().await {
+            Ok(indexes) => indexes,
+            Err(error) => {
+                error!(%error, "failed to deserialize indexes metadata");
+                return;
+            }
+        };
+        let mut index_ids_with_rollup_policy = Vec::new();
+
+        for index_metadata in indexes {
+            let index_uid = index_metadata.index_uid.clone();
+            let index_config = index_metadata.into_index_config();
+
+            let Some(rollup_policy) = &index_config.rollup_policy_opt else {
+                continue;
+            };
+            index_ids_with_rollup_policy.push(index_config.index_id.clone());
+
+            // Update the cache entry in case the rollup policy was updated.
+            if let Some(value) = self.index_configs.get_mut(&index_config.index_id) {
+                *value = index_config;
+                continue;
+            }
+            if let Ok(next_interval) = rollup_policy.duration_until_next_evaluation() {
+                let message = Execute { index_uid };
+                info!(index_id=?index_config.index_id, scheduled_in=?next_interval, "rollup-policy-schedule-operation");
+                self.index_configs
+                    .insert(index_config.index_id.clone(), index_config);
+                ctx.schedule_self_msg(next_interval, message);
+            } else {
+                error!(index_id=%index_config.index_id, "couldn't extract the index next schedule time");
+            }
+        }
+        // Remove the indexes that were deleted or whose rollup policy was removed.
+        self.index_configs
+            .retain(|index_id, _| index_ids_with_rollup_policy.contains(index_id));
+    }
+}
+
+#[async_trait]
+impl Actor for RollupExecutor {
+    type ObservableState = RollupExecutorCounters;
+
+    fn observable_state(&self) -> Self::ObservableState {
+        self.counters.clone()
+    }
+
+    fn name(&self) -> String {
+        "RollupExecutor".to_string()
+    }
+
+    async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        temp_dir::create_or_purge_directory(&self.resources.scratch_directory_path)
+            .await
+            .context("failed to create rollup scratch directory")?;
+        self.handle(Loop, ctx).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Loop> for RollupExecutor {
+    type Reply = ();
+
+    async fn handle(&mut self, _: Loop, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        self.handle_refresh_loop(ctx).await;
+        ctx.schedule_self_msg(RUN_INTERVAL, Loop);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Execute> for RollupExecutor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: Execute,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        info!(index_id=%message.index_uid.index_id, "rollup-policy-execute-operation");
+        self.counters.num_execution_passes += 1;
+
+        let Some(index_config) = self.index_configs.get(&message.index_uid.index_id) else {
+            debug!(index_id=%message.index_uid.index_id, "the index might have been deleted");
+            return Ok(());
+        };
+        let rollup_policy = index_config
+            .rollup_policy_opt
+            .as_ref()
+            .expect("index should have a rollup policy");
+
+        let execution_result = run_execute_rollup_policy(
+            message.index_uid.clone(),
+            rollup_policy,
+            &self.resources,
+            ctx,
+        )
+        .await;
+        let is_lagging = match execution_result {
+            Ok(execution) => {
+                JANITOR_METRICS
+                    .rollup_executions
+                    .with_label_values(["success"])
+                    .inc();
+                self.counters.num_rolled_up_intervals += execution.num_intervals;
+                self.counters.num_rolled_up_docs += execution.num_rolled_up_docs;
+                self.counters.num_deleted_splits += execution.deleted_splits.len();
+                execution.is_lagging
+            }
+            Err(error) => {
+                JANITOR_METRICS
+                    .rollup_executions
+                    .with_label_values(["error"])
+                    .inc();
+                self.counters.num_failed_execution_passes += 1;
+                error!(index_id=%message.index_uid.index_id, error=?error, "failed to execute the rollup policy on the index");
+                false
+            }
+        };
+        if is_lagging {
+            // Catch up right away on the intervals left to roll up.
+            ctx.schedule_self_msg(Duration::ZERO, message);
+        } else if let Ok(next_interval) = rollup_policy.duration_until_next_evaluation() {
+            info!(index_id=?index_config.index_id, scheduled_in=?next_interval, "rollup-policy-schedule-operation");
+            ctx.schedule_self_msg(next_interval, message);
+        } else {
+            // The index is removed from the cache so that it gets scheduled again by the next
+            // refresh loop.
+            self.index_configs.remove(&message.index_uid.index_id);
+            error!(index_id=%message.index_uid.index_id, "couldn't extract the index next schedule interval");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_actors::Universe;
+    use quickwit_config::RollupPolicy;
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_proto::metastore::{
+        EntityKind, ListIndexesMetadataResponse, MetastoreError, MockMetastoreService,
+    };
+    use quickwit_search::{SearchJobPlacer, SearcherPool};
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct GetIndexIds;
+
+    #[async_trait]
+    impl Handler<GetIndexIds> for RollupExecutor {
+        type Reply = Vec<String>;
+
+        async fn handle(
+            &mut self,
+            _message: GetIndexIds,
+            _ctx: &ActorContext<Self>,
+        ) -> Result<Self::Reply, ActorExitStatus> {
+            let mut index_ids: Vec<String> = self.index_configs.keys().cloned().collect();
+            index_ids.sort();
+            Ok(index_ids)
+        }
+    }
+
+    fn make_index_metadata(index_id: &str, target_index_id_opt: Option<&str>) -> IndexMetadata {
+        let mut index_config =
+            IndexConfig::for_test(index_id, &format!("ram://indexes/{index_id}"));
+
+        if let Some(target_index_id) = target_index_id_opt {
+            let rollup_policy_json = json!({
+                "target_index_id": target_index_id,
+                "rollup_after": "1 day",
+                "interval": "1 hour",
+            });
+            let rollup_policy: RollupPolicy = serde_json::from_value(rollup_policy_json).unwrap();
+            index_config.rollup_policy_opt = Some(rollup_policy);
+        }
+        IndexMetadata::new(index_config)
+    }
+
+    #[tokio::test]
+    async fn test_rollup_executor_refresh() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .times(1)
+            .returning(|_list_indexes_request| {
+                let indexes_metadata = vec![
+                    make_index_metadata("index-1", Some("index-1-hourly")),
+                    make_index_metadata("index-2", None),
+                ];
+                Ok(ListIndexesMetadataResponse::for_test(indexes_metadata))
+            });
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .times(1)
+            .returning(|_list_indexes_request| {
+                let indexes_metadata = vec![
+                    make_index_metadata("index-1", None),
+                    make_index_metadata("index-2", Some("index-2-hourly")),
+                ];
+                Ok(ListIndexesMetadataResponse::for_test(indexes_metadata))
+            });
+        // The indexes are deleted before their rollup policy gets executed.
+        mock_metastore
+            .expect_index_metadata()
+            .times(..)
+            .returning(|index_metadata_request| {
+                let index_id = index_metadata_request.index_id.unwrap_or_default();
+                Err(MetastoreError::NotFound(EntityKind::Index { index_id }))
+            });
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut node_config = NodeConfig::for_test();
+        node_config.data_dir_path = temp_dir.path().to_path_buf();
+
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(SearcherPool::default()));
+        let rollup_executor = RollupExecutor::new(
+            &node_config,
+            MetastoreServiceClient::from_mock(mock_metastore),
+            StorageResolver::for_test(),
+            Arc::new(SearcherContext::for_test()),
+            cluster_client,
+            EventBroker::default(),
+        );
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, handle) = universe.spawn_builder().spawn(rollup_executor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_refresh_passes, 1);
+        assert!(temp_dir.path().join(ROLLUP_SCRATCH_DIR_NAME).exists());
+
+        let index_ids = mailbox.ask(GetIndexIds).await.unwrap();
+        assert_eq!(index_ids, ["index-1"]);
+
+        mailbox.ask(Loop).await.unwrap();
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_refresh_passes, 2);
+
+        let index_ids = mailbox.ask(GetIndexIds).await.unwrap();
+        assert_eq!(index_ids, ["index-2"]);
+
+        universe.assert_quit().await;
+    }
+}
@@ -18,12 +18,18 @@ use quickwit_actors::{
 };
 use serde_json::{json, Value as JsonValue};
 
-use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor};
+use crate::actors::{
+    DeleteTaskService, GarbageCollector, MonitorExecutor, RetentionPolicyExecutor, RollupExecutor,
+    TieringExecutor,
+};
 
 pub struct JanitorService {
     delete_task_service_handle: Option<ActorHandle<DeleteTaskService>>,
     garbage_collector_handle: ActorHandle<GarbageCollector>,
     retention_policy_executor_handle: ActorHandle<RetentionPolicyExecutor>,
+    monitor_executor_handle: ActorHandle<MonitorExecutor>,
+    rollup_executor_handle: ActorHandle<RollupExecutor>,
+    tiering_executor_handle: ActorHandle<TieringExecutor>,
 }
 
 impl JanitorService {
@@ -31,11 +37,17 @@ impl JanitorService {
         delete_task_service_handle: Option<ActorHandle<DeleteTaskService>>,
         garbage_collector_handle: ActorHandle<GarbageCollector>,
         retention_policy_executor_handle: ActorHandle<RetentionPolicyExecutor>,
+        monitor_executor_handle: ActorHandle<MonitorExecutor>,
+        rollup_executor_handle: ActorHandle<RollupExecutor>,
+        tiering_executor_handle: ActorHandle<TieringExecutor>,
     ) -> Self {
         Self {
             delete_task_service_handle,
             garbage_collector_handle,
             retention_policy_executor_handle,
+            monitor_executor_handle,
+            rollup_executor_handle,
+            tiering_executor_handle,
         }
     }
 
@@ -47,6 +59,9 @@ impl JanitorService {
             })
             && self.garbage_collector_handle.state() != ActorState::Failure
             && self.retention_policy_executor_handle.state() != ActorState::Failure
+            && self.monitor_executor_handle.state() != ActorState::Failure
+            && self.rollup_executor_handle.state() != ActorState::Failure
+            && self.tiering_executor_handle.state() != ActorState::Failure
     }
 }
 
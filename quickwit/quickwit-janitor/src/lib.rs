@@ -14,13 +14,15 @@
 
 #![deny(clippy::disallowed_methods)]
 
+use std::sync::Arc;
+
 use quickwit_actors::{Mailbox, Universe};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_config::NodeConfig;
 use quickwit_indexing::actors::MergeSchedulerService;
 use quickwit_metastore::SplitInfo;
 use quickwit_proto::metastore::MetastoreServiceClient;
-use quickwit_search::SearchJobPlacer;
+use quickwit_search::{ClusterClient, SearchJobPlacer, SearcherContext};
 use quickwit_storage::StorageResolver;
 use tracing::info;
 
@@ -28,11 +30,17 @@ pub mod actors;
 pub mod error;
 mod janitor_service;
 mod metrics;
+mod monitor_evaluation;
 mod retention_policy_execution;
+mod rollup_execution;
+mod tiering_execution;
 
 pub use janitor_service::JanitorService;
 
-use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor};
+use crate::actors::{
+    DeleteTaskService, GarbageCollector, MonitorExecutor, RetentionPolicyExecutor, RollupExecutor,
+    TieringExecutor,
+};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(SplitInfo)))]
@@ -55,6 +63,35 @@ pub async fn start_janitor_service(
     let retention_policy_executor = RetentionPolicyExecutor::new(metastore.clone());
     let (_, retention_policy_executor_handle) =
         universe.spawn_builder().spawn(retention_policy_executor);
+
+    let searcher_context = Arc::new(SearcherContext::new(
+        config.searcher_config.clone(),
+        None,
+        None,
+        storage_resolver.clone(),
+    ));
+    let cluster_client = ClusterClient::new(search_job_placer.clone());
+    let monitor_executor = MonitorExecutor::new(
+        metastore.clone(),
+        searcher_context.clone(),
+        cluster_client.clone(),
+    );
+    let (_, monitor_executor_handle) = universe.spawn_builder().spawn(monitor_executor);
+
+    let rollup_executor = RollupExecutor::new(
+        config,
+        metastore.clone(),
+        storage_resolver.clone(),
+        searcher_context,
+        cluster_client,
+        event_broker.clone(),
+    );
+    let (_, rollup_executor_handle) = universe.spawn_builder().spawn(rollup_executor);
+
+    let tiering_executor =
+        TieringExecutor::new(config, metastore.clone(), storage_resolver.clone());
+    let (_, tiering_executor_handle) = universe.spawn_builder().spawn(tiering_executor);
+
     let delete_task_service_handle = if run_delete_task_service {
         let delete_task_service = DeleteTaskService::new(
             metastore,
@@ -77,6 +114,9 @@ pub async fn start_janitor_service(
         delete_task_service_handle,
         garbage_collector_handle,
         retention_policy_executor_handle,
+        monitor_executor_handle,
+        rollup_executor_handle,
+        tiering_executor_handle,
     );
     let (janitor_service_mailbox, _janitor_service_handle) =
         universe.spawn_builder().spawn(janitor_service);
@@ -23,6 +23,10 @@ pub struct JanitorMetrics {
     pub gc_deleted_bytes: IntCounter,
     pub gc_runs: IntCounterVec<1>,
     pub gc_seconds_total: IntCounter,
+    pub monitor_evaluations: IntCounterVec<1>,
+    pub rollup_executions: IntCounterVec<1>,
+    pub tiering_moved_splits: IntCounterVec<1>,
+    pub tiering_moved_bytes: IntCounter,
     // TODO having a current run duration which is 0|undefined out of run, and returns `now -
     // start_time` during a run would be nice
 }
@@ -63,6 +67,33 @@ impl Default for JanitorMetrics {
                 "quickwit_janitor",
                 &[],
             ),
+            monitor_evaluations: new_counter_vec(
+                "monitor_evaluations_total",
+                "Total number of monitor evaluations.",
+                "quickwit_janitor",
+                &[],
+                ["status"],
+            ),
+            rollup_executions: new_counter_vec(
+                "rollup_executions_total",
+                "Total number of rollup policy executions.",
+                "quickwit_janitor",
+                &[],
+                ["status"],
+            ),
+            tiering_moved_splits: new_counter_vec(
+                "tiering_moved_splits_total",
+                "Total number of splits moved to a storage tier.",
+                "quickwit_janitor",
+                &[],
+                ["result"],
+            ),
+            tiering_moved_bytes: new_counter(
+                "tiering_moved_bytes_total",
+                "Total number of bytes moved to a storage tier.",
+                "quickwit_janitor",
+                &[],
+            ),
         }
     }
 }
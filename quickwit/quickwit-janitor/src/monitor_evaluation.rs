@@ -0,0 +1,849 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "kafka")]
+use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+#[cfg(feature = "kafka")]
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use itertools::Itertools;
+use quickwit_actors::ActorContext;
+use quickwit_config::{
+    KafkaActionParams, MonitorAction, MonitorConfig, ThresholdOperator, WebhookActionParams,
+};
+use quickwit_metastore::{
+    IndexMetadata, IndexMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, MonitorEvaluation, MonitorMetadata, MonitorState,
+    PendingMonitorAlert, Split, SplitMaturity, SplitMetadata, SplitState,
+};
+use quickwit_proto::metastore::{
+    IndexMetadataRequest, ListSplitsRequest, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::search::SearchRequest;
+use quickwit_proto::types::SplitId;
+use quickwit_query::query_ast::query_ast_from_user_text;
+use quickwit_search::{root_search_on_splits, ClusterClient, SearcherContext};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::actors::MonitorExecutor;
+
+/// Timeout applied to the delivery of an alert by a webhook or Kafka action.
+const ACTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Payload delivered by the actions of a triggered monitor.
+#[derive(Debug, Serialize)]
+struct MonitorAlert<'a> {
+    monitor_id: &'a str,
+    index_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    query: &'a str,
+    evaluation_timestamp: i64,
+    /// Publish timestamp (exclusive) of the first split considered by the evaluation.
+    start_timestamp: i64,
+    /// Publish timestamp (inclusive) of the last split considered by the evaluation.
+    end_timestamp: i64,
+    num_splits: usize,
+    num_hits: u64,
+    value: f64,
+    operator: ThresholdOperator,
+    threshold: f64,
+}
+
+/// Clients used by the actions of the monitors, reused across evaluations.
+#[derive(Default)]
+pub(crate) struct MonitorActionClients {
+    http_client: reqwest::Client,
+    /// Kafka producers, by client parameters.
+    #[cfg(feature = "kafka")]
+    kafka_producers: Mutex<HashMap<String, rdkafka::producer::FutureProducer>>,
+}
+
+/// Evaluates a monitor against the splits published to its index since its last checkpoint,
+/// executes its actions if its condition is met, and records the evaluation in the state of the
+/// monitor.
+///
+/// This function never fails: errors are reported in the returned [`MonitorEvaluation`]. If the
+/// evaluation itself fails, the checkpoint of the monitor does not move and the splits are
+/// evaluated again next time. If some actions fail to deliver the alert, the checkpoint moves
+/// forward and the alert is queued in the state of the monitor, to be delivered by these actions
+/// only during the next evaluations.
+pub(crate) async fn run_monitor_evaluation(
+    monitor_metadata: &mut MonitorMetadata,
+    metastore: MetastoreServiceClient,
+    searcher_context: &SearcherContext,
+    cluster_client: &ClusterClient,
+    action_clients: &MonitorActionClients,
+    ctx: &ActorContext<MonitorExecutor>,
+) -> MonitorEvaluation {
+    let (mut evaluation, immature_splits_opt, alert_opt) = evaluate_monitor(
+        monitor_metadata,
+        metastore,
+        searcher_context,
+        cluster_client,
+        ctx,
+    )
+    .await;
+    deliver_alerts(
+        monitor_metadata,
+        alert_opt,
+        action_clients,
+        &mut evaluation.action_errors,
+        ctx,
+    )
+    .await;
+
+    let monitor_state = &mut monitor_metadata.monitor_state;
+    monitor_state.record_evaluation(evaluation.clone());
+
+    if immature_splits_opt.is_some() {
+        monitor_state.immature_splits = immature_splits_opt;
+    }
+    evaluation
+}
+
+/// Evaluates the condition of a monitor. If the evaluation succeeds, the immature splits
+/// published up to its checkpoint are returned along with it, to be saved in the
+/// [`MonitorState`], and so is the alert to deliver if the condition is met.
+async fn evaluate_monitor(
+    monitor_metadata: &MonitorMetadata,
+    metastore: MetastoreServiceClient,
+    searcher_context: &SearcherContext,
+    cluster_client: &ClusterClient,
+    ctx: &ActorContext<MonitorExecutor>,
+) -> (
+    MonitorEvaluation,
+    Option<BTreeMap<SplitId, i64>>,
+    Option<JsonValue>,
+) {
+    let now_datetime = OffsetDateTime::now_utc();
+    let now = now_datetime.unix_timestamp();
+    // Splits published during the current second are left to the next evaluation so that none of
+    // them is skipped.
+    let start_timestamp = monitor_metadata.monitor_state.checkpoint_timestamp;
+    let end_timestamp = (now - 1).max(start_timestamp);
+
+    let mut evaluation = MonitorEvaluation {
+        evaluation_timestamp: now,
+        checkpoint_timestamp: end_timestamp,
+        num_splits: 0,
+        value: None,
+        triggered: false,
+        error: None,
+        action_errors: Vec::new(),
+    };
+    let monitor_config = &monitor_metadata.monitor_config;
+
+    let NewSplits {
+        index_metadata,
+        splits: new_splits,
+        immature_splits,
+    } = match list_new_splits(
+        monitor_config,
+        &monitor_metadata.monitor_state,
+        metastore.clone(),
+        start_timestamp,
+        end_timestamp,
+        now_datetime,
+        ctx,
+    )
+    .await
+    {
+        Ok(new_splits) => new_splits,
+        Err(error) => {
+            evaluation.error = Some(format!("failed to list new splits: {error:#}"));
+            return (evaluation, None, None);
+        }
+    };
+    evaluation.num_splits = new_splits.len();
+
+    let (num_hits, value_opt) = if !new_splits.is_empty() {
+        let search_request = match build_search_request(monitor_config) {
+            Ok(search_request) => search_request,
+            Err(error) => {
+                evaluation.error = Some(format!("failed to build search request: {error:#}"));
+                return (evaluation, None, None);
+            }
+        };
+        let search_result = ctx
+            .protect_future(root_search_on_splits(
+                searcher_context,
+                search_request,
+                &index_metadata,
+                new_splits,
+                cluster_client,
+            ))
+            .await;
+        let search_response = match search_result {
+            Ok(search_response) if search_response.failed_splits.is_empty() => search_response,
+            Ok(search_response) => {
+                let failed_split_ids = search_response
+                    .failed_splits
+                    .iter()
+                    .map(|failed_split| &failed_split.split_id)
+                    .join(", ");
+                evaluation.error = Some(format!("search failed on splits: {failed_split_ids}"));
+                return (evaluation, None, None);
+            }
+            Err(error) => {
+                evaluation.error = Some(format!("search failed: {error}"));
+                return (evaluation, None, None);
+            }
+        };
+        match extract_value(monitor_config, &search_response) {
+            Ok(value_opt) => (search_response.num_hits, value_opt),
+            Err(error) => {
+                evaluation.error = Some(format!("failed to extract value: {error:#}"));
+                return (evaluation, None, None);
+            }
+        }
+    } else if monitor_config.condition.value_path.is_none() {
+        // No new split: the number of matching documents is zero.
+        (0, Some(0.0))
+    } else {
+        // No new split: the aggregations are empty.
+        (0, None)
+    };
+    evaluation.value = value_opt;
+
+    let Some(value) = value_opt else {
+        return (evaluation, Some(immature_splits), None);
+    };
+    if !monitor_config.condition.is_met(value) {
+        return (evaluation, Some(immature_splits), None);
+    }
+    evaluation.triggered = true;
+
+    let alert = MonitorAlert {
+        monitor_id: &monitor_config.monitor_id,
+        index_id: &monitor_config.index_id,
+        description: monitor_config.description.as_deref(),
+        query: &monitor_config.query,
+        evaluation_timestamp: now,
+        start_timestamp,
+        end_timestamp,
+        num_splits: evaluation.num_splits,
+        num_hits,
+        value,
+        operator: monitor_config.condition.operator,
+        threshold: monitor_config.condition.threshold,
+    };
+    let alert_json = serde_json::to_value(alert).expect("alert should serialize to JSON");
+    (evaluation, Some(immature_splits), Some(alert_json))
+}
+
+/// Delivers the alerts pending from previous evaluations, in chronological order, followed by
+/// the new alert, if any. The alerts that some actions failed to deliver are queued again in the
+/// state of the monitor. An action that fails is not retried for the next alerts until the next
+/// evaluation.
+async fn deliver_alerts(
+    monitor_metadata: &mut MonitorMetadata,
+    alert_opt: Option<JsonValue>,
+    action_clients: &MonitorActionClients,
+    action_errors: &mut Vec<String>,
+    ctx: &ActorContext<MonitorExecutor>,
+) {
+    let monitor_config = &monitor_metadata.monitor_config;
+    let monitor_state = &mut monitor_metadata.monitor_state;
+    let mut alerts = std::mem::take(&mut monitor_state.pending_alerts);
+
+    if let Some(alert) = alert_opt {
+        alerts.push_back(PendingMonitorAlert {
+            alert,
+            action_indexes: (0..monitor_config.actions.len()).collect(),
+        });
+    }
+    let mut failed_action_indexes = HashSet::new();
+
+    for pending_alert in alerts {
+        let mut remaining_action_indexes = Vec::new();
+
+        for action_index in pending_alert.action_indexes {
+            // The actions of the monitor may have changed since the alert was queued.
+            let Some(action) = monitor_config.actions.get(action_index) else {
+                continue;
+            };
+            if failed_action_indexes.contains(&action_index) {
+                remaining_action_indexes.push(action_index);
+                continue;
+            }
+            if let Err(error) = ctx
+                .protect_future(execute_action(
+                    action,
+                    &monitor_config.monitor_id,
+                    &pending_alert.alert,
+                    action_clients,
+                ))
+                .await
+            {
+                warn!(monitor_id=%monitor_config.monitor_id, action=%action.action_type(), error=?error, "failed to execute monitor action");
+                action_errors.push(format!("{} action failed: {error:#}", action.action_type()));
+                failed_action_indexes.insert(action_index);
+                remaining_action_indexes.push(action_index);
+            }
+        }
+        if remaining_action_indexes.is_empty() {
+            continue;
+        }
+        let pending_alert = PendingMonitorAlert {
+            alert: pending_alert.alert,
+            action_indexes: remaining_action_indexes,
+        };
+        if let Some(dropped_alert) = monitor_state.push_pending_alert(pending_alert) {
+            warn!(monitor_id=%monitor_config.monitor_id, alert=%dropped_alert.alert, "dropping undelivered monitor alert");
+        }
+    }
+}
+
+/// Splits holding documents not evaluated yet by a monitor.
+struct NewSplits {
+    index_metadata: IndexMetadata,
+    splits: Vec<SplitMetadata>,
+    /// Immature splits published up to the end of the evaluation window, along with the
+    /// timestamp at which they become mature.
+    immature_splits: BTreeMap<SplitId, i64>,
+}
+
+/// Lists the splits published to the monitored index within `(start_timestamp, end_timestamp]`
+/// that hold documents not evaluated yet.
+async fn list_new_splits(
+    monitor_config: &MonitorConfig,
+    monitor_state: &MonitorState,
+    metastore: MetastoreServiceClient,
+    start_timestamp: i64,
+    end_timestamp: i64,
+    now: OffsetDateTime,
+    ctx: &ActorContext<MonitorExecutor>,
+) -> anyhow::Result<NewSplits> {
+    let index_metadata_request =
+        IndexMetadataRequest::for_index_id(monitor_config.index_id.clone());
+    let index_metadata = ctx
+        .protect_future(metastore.index_metadata(index_metadata_request))
+        .await?
+        .deserialize_index_metadata()?;
+
+    let mut immature_splits = if let Some(immature_splits) = &monitor_state.immature_splits {
+        immature_splits.clone()
+    } else {
+        // First evaluation: the immature splits published up to the checkpoint are listed.
+        let query = ListSplitsQuery::for_index(index_metadata.index_uid.clone())
+            .with_split_state(SplitState::Published)
+            .retain_immature(now);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+        let splits = ctx
+            .protect_future(metastore.list_splits(list_splits_request))
+            .await?
+            .collect_splits()
+            .await?;
+        let mut immature_splits = BTreeMap::new();
+        for split in splits {
+            if split
+                .publish_timestamp
+                .is_some_and(|publish_timestamp| publish_timestamp <= start_timestamp)
+            {
+                insert_immature_split(&mut immature_splits, &split.split_metadata);
+            }
+        }
+        immature_splits
+    };
+    if start_timestamp >= end_timestamp {
+        return Ok(NewSplits {
+            index_metadata,
+            splits: Vec::new(),
+            immature_splits,
+        });
+    }
+    // The update timestamp of a split is set when it gets published, so this filter returns a
+    // superset of the splits we are interested in.
+    let query = ListSplitsQuery::for_index(index_metadata.index_uid.clone())
+        .with_split_state(SplitState::Published)
+        .with_update_timestamp_gt(start_timestamp);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+    let published_splits: Vec<Split> = ctx
+        .protect_future(metastore.list_splits(list_splits_request))
+        .await?
+        .collect_splits()
+        .await?
+        .into_iter()
+        .filter(|split| {
+            split.publish_timestamp.is_some_and(|publish_timestamp| {
+                publish_timestamp > start_timestamp && publish_timestamp <= end_timestamp
+            })
+        })
+        .collect();
+
+    let new_splits: Vec<SplitMetadata> = published_splits
+        .iter()
+        .filter(|split| {
+            holds_unevaluated_documents(&split.split_metadata, monitor_state, &immature_splits)
+        })
+        .map(|split| split.split_metadata.clone())
+        .collect();
+
+    for split in &published_splits {
+        for replaced_split_id in &split.split_metadata.replaced_split_ids {
+            immature_splits.remove(replaced_split_id);
+        }
+    }
+    for split in &published_splits {
+        insert_immature_split(&mut immature_splits, &split.split_metadata);
+    }
+    // Mature splits are not merged anymore.
+    immature_splits.retain(|_, maturity_timestamp| *maturity_timestamp > now.unix_timestamp());
+
+    Ok(NewSplits {
+        index_metadata,
+        splits: new_splits,
+        immature_splits,
+    })
+}
+
+fn insert_immature_split(
+    immature_splits: &mut BTreeMap<SplitId, i64>,
+    split_metadata: &SplitMetadata,
+) {
+    if let SplitMaturity::Immature { maturation_period } = split_metadata.maturity {
+        let maturity_timestamp =
+            split_metadata.create_timestamp + maturation_period.as_secs() as i64;
+        immature_splits.insert(split_metadata.split_id.clone(), maturity_timestamp);
+    }
+}
+
+/// Returns true if some of the documents of a split published after the checkpoint of the monitor
+/// have not been evaluated yet.
+///
+/// Merged splits are only built from immature splits, so the documents of a merged split were
+/// evaluated if all the splits it was merged from were published up to the checkpoint, i.e. are
+/// in `immature_splits`. If only some of them were, the whole split is evaluated and the documents
+/// of those splits are evaluated twice.
+fn holds_unevaluated_documents(
+    split_metadata: &SplitMetadata,
+    monitor_state: &MonitorState,
+    immature_splits: &BTreeMap<SplitId, i64>,
+) -> bool {
+    if split_metadata.replaced_split_ids.is_empty() {
+        // The splits published before their replaced splits were recorded only carry their number
+        // of merge operations.
+        return split_metadata.num_merge_ops == 0;
+    }
+    // Splits rewritten by a delete task or moved to another storage tier hold documents of the
+    // split they replace, which was published earlier.
+    if split_metadata.replaced_split_ids.len() == 1 {
+        return false;
+    }
+    // Before the first evaluation, the immature splits published up to the checkpoint are unknown.
+    if monitor_state.immature_splits.is_none() {
+        return false;
+    }
+    split_metadata
+        .replaced_split_ids
+        .iter()
+        .any(|replaced_split_id| !immature_splits.contains_key(replaced_split_id))
+}
+
+fn build_search_request(monitor_config: &MonitorConfig) -> anyhow::Result<SearchRequest> {
+    let search_fields_opt = if monitor_config.search_fields.is_empty() {
+        None
+    } else {
+        Some(monitor_config.search_fields.clone())
+    };
+    let query_ast = query_ast_from_user_text(&monitor_config.query, search_fields_opt);
+    let aggregation_request = monitor_config
+        .aggregation_opt
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![monitor_config.index_id.clone()],
+        query_ast: serde_json::to_string(&query_ast)?,
+        max_hits: 0,
+        aggregation_request,
+        ..Default::default()
+    };
+    Ok(search_request)
+}
+
+/// Extracts the value compared against the threshold of the monitor's condition: the value
+/// located at `value_path` in the aggregation results if set, the number of hits otherwise.
+/// Returns `None` if the aggregation results do not hold a value at `value_path`, for instance
+/// the average of an empty set of documents.
+fn extract_value(
+    monitor_config: &MonitorConfig,
+    search_response: &quickwit_proto::search::SearchResponse,
+) -> anyhow::Result<Option<f64>> {
+    let Some(value_path) = &monitor_config.condition.value_path else {
+        return Ok(Some(search_response.num_hits as f64));
+    };
+    let Some(aggregation_json) = &search_response.aggregation else {
+        return Ok(None);
+    };
+    let aggregation: JsonValue = serde_json::from_str(aggregation_json)?;
+    extract_value_at_path(&aggregation, value_path)
+}
+
+fn extract_value_at_path(json_value: &JsonValue, value_path: &str) -> anyhow::Result<Option<f64>> {
+    let mut current_value = json_value;
+
+    for key in value_path.split('.') {
+        let next_value_opt = match current_value {
+            JsonValue::Object(map) => map.get(key),
+            JsonValue::Array(values) => key
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| values.get(index)),
+            _ => None,
+        };
+        let Some(next_value) = next_value_opt else {
+            return Ok(None);
+        };
+        current_value = next_value;
+    }
+    match current_value {
+        JsonValue::Null => Ok(None),
+        JsonValue::Number(number) => Ok(number.as_f64()),
+        _ => bail!("value at path `{value_path}` is not a number"),
+    }
+}
+
+async fn execute_action(
+    action: &MonitorAction,
+    monitor_id: &str,
+    alert: &JsonValue,
+    action_clients: &MonitorActionClients,
+) -> anyhow::Result<()> {
+    match action {
+        MonitorAction::Webhook(params) => {
+            execute_webhook_action(params, monitor_id, alert, &action_clients.http_client).await
+        }
+        MonitorAction::Kafka(params) => {
+            execute_kafka_action(params, monitor_id, alert, action_clients).await
+        }
+        MonitorAction::Log => {
+            warn!(monitor_id=%monitor_id, alert=%alert, "monitor triggered");
+            Ok(())
+        }
+    }
+}
+
+async fn execute_webhook_action(
+    params: &WebhookActionParams,
+    monitor_id: &str,
+    alert: &JsonValue,
+    http_client: &reqwest::Client,
+) -> anyhow::Result<()> {
+    let mut request_builder = http_client
+        .post(&params.url)
+        .timeout(ACTION_TIMEOUT)
+        .json(alert);
+
+    for (header_name, header_value) in &params.headers {
+        request_builder = request_builder.header(header_name, header_value);
+    }
+    request_builder
+        .send()
+        .await
+        .with_context(|| format!("failed to post alert to `{}`", params.url))?
+        .error_for_status()?;
+    info!(monitor_id=%monitor_id, url=%params.url, "posted monitor alert to webhook");
+    Ok(())
+}
+
+#[cfg(feature = "kafka")]
+impl MonitorActionClients {
+    /// Returns the Kafka producer configured with `client_params`, creating it on first use.
+    fn kafka_producer(
+        &self,
+        client_params: &JsonValue,
+    ) -> anyhow::Result<rdkafka::producer::FutureProducer> {
+        use rdkafka::config::ClientConfig;
+
+        let cache_key = client_params.to_string();
+        let mut kafka_producers = self
+            .kafka_producers
+            .lock()
+            .expect("the lock should not be poisoned");
+
+        if let Some(producer) = kafka_producers.get(&cache_key) {
+            return Ok(producer.clone());
+        }
+        let mut client_config = ClientConfig::new();
+
+        if let JsonValue::Object(client_params) = client_params {
+            for (key, value_json) in client_params {
+                let value = match value_json {
+                    JsonValue::Bool(value_bool) => value_bool.to_string(),
+                    JsonValue::Number(value_number) => value_number.to_string(),
+                    JsonValue::String(value_string) => value_string.clone(),
+                    JsonValue::Null => continue,
+                    JsonValue::Array(_) | JsonValue::Object(_) => bail!(
+                        "failed to parse Kafka client parameters. `client_params.{key}` must be a \
+                         boolean, number, or string"
+                    ),
+                };
+                client_config.set(key, value);
+            }
+        }
+        let producer: rdkafka::producer::FutureProducer = client_config
+            .create()
+            .context("failed to create Kafka producer")?;
+        kafka_producers.insert(cache_key, producer.clone());
+        Ok(producer)
+    }
+}
+
+#[cfg(feature = "kafka")]
+async fn execute_kafka_action(
+    params: &KafkaActionParams,
+    monitor_id: &str,
+    alert: &JsonValue,
+    action_clients: &MonitorActionClients,
+) -> anyhow::Result<()> {
+    use rdkafka::producer::FutureRecord;
+    use rdkafka::util::Timeout;
+
+    let producer = action_clients.kafka_producer(&params.client_params)?;
+    let payload = serde_json::to_vec(alert)?;
+    let record = FutureRecord::to(&params.topic)
+        .key(monitor_id)
+        .payload(&payload);
+    producer
+        .send(record, Timeout::After(ACTION_TIMEOUT))
+        .await
+        .map_err(|(error, _)| error)
+        .with_context(|| format!("failed to produce alert to topic `{}`", params.topic))?;
+    info!(monitor_id=%monitor_id, topic=%params.topic, "produced monitor alert to Kafka");
+    Ok(())
+}
+
+#[cfg(not(feature = "kafka"))]
+async fn execute_kafka_action(
+    _params: &KafkaActionParams,
+    _monitor_id: &str,
+    _alert: &JsonValue,
+    _action_clients: &MonitorActionClients,
+) -> anyhow::Result<()> {
+    bail!("Quickwit was compiled without the `kafka` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use quickwit_actors::Universe;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::watch;
+
+    use super::*;
+
+    /// Starts an HTTP server answering every request with `200 OK` and returns its URL along with
+    /// the number of requests it received.
+    async fn start_webhook_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/alerts", listener.local_addr().unwrap());
+        let num_requests = Arc::new(AtomicUsize::new(0));
+        let num_requests_clone = num_requests.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                // Reads the request up to the end of its body.
+                loop {
+                    let num_bytes = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..num_bytes]);
+                    let request_str = String::from_utf8_lossy(&request);
+                    let Some(headers_end) = request_str.find("\r\n\r\n") else {
+                        continue;
+                    };
+                    let content_length = request_str[..headers_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if num_bytes == 0 || request.len() >= headers_end + 4 + content_length {
+                        break;
+                    }
+                }
+                num_requests_clone.fetch_add(1, Ordering::SeqCst);
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                    .await
+                    .unwrap();
+            }
+        });
+        (url, num_requests)
+    }
+
+    fn webhook_action(url: &str) -> MonitorAction {
+        MonitorAction::Webhook(WebhookActionParams {
+            url: url.to_string(),
+            headers: BTreeMap::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_deliver_alerts_only_retries_failed_actions() {
+        let (url, num_requests) = start_webhook_server().await;
+        // Nothing listens on this port.
+        let unreachable_url = "http://127.0.0.1:1/alerts";
+
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.actions = vec![webhook_action(&url), webhook_action(unreachable_url)];
+        let mut monitor_metadata = MonitorMetadata::new(monitor_config);
+        let action_clients = MonitorActionClients::default();
+
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, _inbox) = universe.create_test_mailbox::<MonitorExecutor>();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(Default::default());
+        let ctx = ActorContext::for_test(&universe, mailbox, observable_state_tx);
+
+        let mut action_errors = Vec::new();
+        deliver_alerts(
+            &mut monitor_metadata,
+            Some(json!({"monitor_id": "test-monitor", "value": 1.0})),
+            &action_clients,
+            &mut action_errors,
+            &ctx,
+        )
+        .await;
+        assert_eq!(num_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(action_errors.len(), 1);
+        assert!(action_errors[0].starts_with("webhook action failed"));
+
+        let pending_alerts = &monitor_metadata.monitor_state.pending_alerts;
+        assert_eq!(pending_alerts.len(), 1);
+        assert_eq!(pending_alerts[0].alert["value"], 1.0);
+        assert_eq!(pending_alerts[0].action_indexes, [1]);
+
+        // The second action fails again: it is not retried for the next alert during the same
+        // evaluation, and the first action is not executed again for the pending alert.
+        let mut action_errors = Vec::new();
+        deliver_alerts(
+            &mut monitor_metadata,
+            Some(json!({"monitor_id": "test-monitor", "value": 2.0})),
+            &action_clients,
+            &mut action_errors,
+            &ctx,
+        )
+        .await;
+        assert_eq!(num_requests.load(Ordering::SeqCst), 2);
+        assert_eq!(action_errors.len(), 1);
+
+        let pending_alerts = &monitor_metadata.monitor_state.pending_alerts;
+        assert_eq!(pending_alerts.len(), 2);
+        assert_eq!(pending_alerts[0].alert["value"], 1.0);
+        assert_eq!(pending_alerts[0].action_indexes, [1]);
+        assert_eq!(pending_alerts[1].alert["value"], 2.0);
+        assert_eq!(pending_alerts[1].action_indexes, [1]);
+
+        // Once the second action is fixed, it delivers the pending alerts.
+        monitor_metadata.monitor_config.actions[1] = webhook_action(&url);
+        let mut action_errors = Vec::new();
+        deliver_alerts(
+            &mut monitor_metadata,
+            None,
+            &action_clients,
+            &mut action_errors,
+            &ctx,
+        )
+        .await;
+        assert_eq!(num_requests.load(Ordering::SeqCst), 4);
+        assert!(action_errors.is_empty());
+        assert!(monitor_metadata.monitor_state.pending_alerts.is_empty());
+
+        universe.assert_quit().await;
+    }
+
+    #[test]
+    fn test_extract_value_at_path() {
+        let aggregation = json!({
+            "max_latency": {
+                "value": 512.0
+            },
+            "avg_latency": {
+                "value": null
+            },
+            "services": {
+                "buckets": [
+                    {"key": "checkout", "doc_count": 42},
+                ]
+            },
+            "status": {
+                "value": "ok"
+            }
+        });
+        assert_eq!(
+            extract_value_at_path(&aggregation, "max_latency.value").unwrap(),
+            Some(512.0)
+        );
+        assert_eq!(
+            extract_value_at_path(&aggregation, "avg_latency.value").unwrap(),
+            None
+        );
+        assert_eq!(
+            extract_value_at_path(&aggregation, "services.buckets.0.doc_count").unwrap(),
+            Some(42.0)
+        );
+        assert_eq!(
+            extract_value_at_path(&aggregation, "services.buckets.1.doc_count").unwrap(),
+            None
+        );
+        assert_eq!(
+            extract_value_at_path(&aggregation, "min_latency.value").unwrap(),
+            None
+        );
+        extract_value_at_path(&aggregation, "status.value").unwrap_err();
+    }
+
+    #[test]
+    fn test_build_search_request() {
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.search_fields = vec!["body".to_string()];
+        monitor_config.aggregation_opt = Some(json!({
+            "max_latency": {
+                "max": {
+                    "field": "latency_ms"
+                }
+            }
+        }));
+        let search_request = build_search_request(&monitor_config).unwrap();
+        assert_eq!(search_request.index_id_patterns, ["test-index"]);
+        assert_eq!(search_request.max_hits, 0);
+
+        let query_ast: JsonValue = serde_json::from_str(&search_request.query_ast).unwrap();
+        assert_eq!(query_ast["type"], "user_input");
+        assert_eq!(query_ast["user_text"], "severity_text:ERROR");
+        assert_eq!(query_ast["default_fields"], json!(["body"]));
+
+        let aggregation_request: JsonValue =
+            serde_json::from_str(search_request.aggregation_request.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            aggregation_request["max_latency"]["max"]["field"],
+            "latency_ms"
+        );
+    }
+}
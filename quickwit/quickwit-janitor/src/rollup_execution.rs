@@ -0,0 +1,1117 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, ensure, Context};
+use itertools::Itertools;
+use quickwit_actors::{ActorContext, ActorExitStatus};
+use quickwit_common::io::IoControls;
+use quickwit_common::pretty::PrettySample;
+use quickwit_common::pubsub::EventBroker;
+use quickwit_common::temp_dir;
+use quickwit_common::uri::Uri;
+use quickwit_config::{
+    build_doc_mapper, load_index_config_from_user_config, ConfigFormat, IndexConfig,
+    RollupAggregation, RollupPolicy, SourceConfig, ROLLUP_DOC_COUNT_FIELD, ROLLUP_SOURCE_ID,
+};
+use quickwit_doc_mapper::DocMapper;
+use quickwit_index_management::IndexService;
+use quickwit_indexing::actors::{Packager, Publisher, Uploader, UploaderType};
+use quickwit_indexing::merge_policy::merge_policy_from_settings;
+use quickwit_indexing::models::{EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBuilder};
+use quickwit_indexing::{IndexingSplitStore, PublisherType, SplitsUpdateMailbox};
+use quickwit_metastore::checkpoint::{IndexCheckpointDelta, PartitionId, SourceCheckpointDelta};
+use quickwit_metastore::{
+    AddSourceRequestExt, IndexMetadata, IndexMetadataResponseExt, ListSplitsQuery,
+    ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitMetadata, SplitState,
+};
+use quickwit_proto::indexing::IndexingPipelineId;
+use quickwit_proto::metastore::{
+    AddSourceRequest, EntityKind, IndexMetadataRequest, ListSplitsRequest,
+    MarkSplitsForDeletionRequest, MetastoreError, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::search::SearchRequest;
+use quickwit_proto::types::{IndexUid, NodeId, PipelineUid, Position, SplitId};
+use quickwit_query::get_quickwit_fastfield_normalizer_manager;
+use quickwit_query::query_ast::QueryAst;
+use quickwit_search::{root_search_on_splits, ClusterClient, SearcherContext};
+use quickwit_storage::StorageResolver;
+use serde_json::{json, Map as JsonObject, Value as JsonValue};
+use tantivy::schema::Type;
+use tantivy::store::{Compressor, ZstdCompressor};
+use tantivy::{DateTime, IndexBuilder, IndexSettings};
+use time::OffsetDateTime;
+use tracing::{info, Span};
+
+use crate::actors::RollupExecutor;
+
+/// Maximum number of intervals rolled up by a single execution. An index lagging behind is caught
+/// up by successive executions.
+const MAX_NUM_INTERVALS_PER_EXECUTION: i64 = 24;
+
+/// Maximum number of distinct values of a `group_by` field within an interval.
+const MAX_NUM_GROUPS: u64 = 10_000;
+
+const ROLLUP_AGGREGATION_NAME: &str = "rollup";
+
+/// Services and settings shared by the rollup executions of all the indexes.
+pub(crate) struct RollupResources {
+    pub metastore: MetastoreServiceClient,
+    pub storage_resolver: StorageResolver,
+    pub searcher_context: Arc<SearcherContext>,
+    pub cluster_client: ClusterClient,
+    pub event_broker: EventBroker,
+    pub node_id: NodeId,
+    pub default_index_root_uri: Uri,
+    pub scratch_directory_path: PathBuf,
+    pub max_concurrent_split_uploads: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RollupExecution {
+    /// Number of intervals rolled up.
+    pub num_intervals: usize,
+    /// Number of documents written to the target index.
+    pub num_rolled_up_docs: usize,
+    /// Splits marked for deletion because all their documents have been rolled up.
+    pub deleted_splits: Vec<SplitMetadata>,
+    /// Whether intervals eligible for rollup remain to be rolled up.
+    pub is_lagging: bool,
+}
+
+/// Rolls up the documents of the index `index_uid` older than the rollup delay that have not been
+/// rolled up yet into the target index of the rollup policy.
+///
+/// Progress is tracked by the checkpoint of the `_rollup-source` source of the target index, which
+/// records, for each rolled-up index, the end of the last interval rolled up. The rolled-up
+/// documents and the checkpoint are published atomically, so an interval is rolled up exactly
+/// once. Documents ingested after their interval was rolled up are ignored.
+pub(crate) async fn run_execute_rollup_policy(
+    index_uid: IndexUid,
+    rollup_policy: &RollupPolicy,
+    resources: &RollupResources,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<RollupExecution> {
+    let mut execution = RollupExecution::default();
+
+    let interval_secs = rollup_policy.interval()?.as_secs() as i64;
+    let rollup_after_secs = rollup_policy.rollup_after()?.as_secs() as i64;
+
+    let metastore = resources.metastore.clone();
+    let index_metadata_request = IndexMetadataRequest::for_index_uid(index_uid.clone());
+    let index_metadata = ctx
+        .protect_future(metastore.index_metadata(index_metadata_request))
+        .await?
+        .deserialize_index_metadata()?;
+    let target_index_metadata =
+        get_or_create_target_index(&index_metadata, rollup_policy, resources, ctx).await?;
+
+    let partition_id = PartitionId::from(index_uid.to_string());
+    let checkpoint_position = target_index_metadata
+        .checkpoint
+        .source_checkpoint(ROLLUP_SOURCE_ID)
+        .and_then(|source_checkpoint| source_checkpoint.position_for_partition(&partition_id))
+        .cloned()
+        .unwrap_or_default();
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let max_end_timestamp = floor_timestamp(now - rollup_after_secs, interval_secs);
+
+    let start_timestamp = if let Some(checkpoint_timestamp) = checkpoint_position.as_i64() {
+        checkpoint_timestamp
+    } else {
+        let published_splits =
+            list_published_splits(&index_uid, None, None, &metastore, ctx).await?;
+        let Some(min_timestamp) = published_splits
+            .iter()
+            .filter_map(|split| split.time_range.as_ref())
+            .map(|time_range| *time_range.start())
+            .min()
+        else {
+            return Ok(execution);
+        };
+        floor_timestamp(min_timestamp, interval_secs)
+    };
+    if start_timestamp < max_end_timestamp {
+        let end_timestamp = max_end_timestamp
+            .min(start_timestamp + MAX_NUM_INTERVALS_PER_EXECUTION * interval_secs);
+        execution.is_lagging = end_timestamp < max_end_timestamp;
+        execution.num_intervals = ((end_timestamp - start_timestamp) / interval_secs) as usize;
+
+        let splits = list_published_splits(
+            &index_uid,
+            Some(start_timestamp),
+            Some(end_timestamp),
+            &metastore,
+            ctx,
+        )
+        .await?;
+        let target_docs = if splits.is_empty() {
+            Vec::new()
+        } else {
+            compute_rollup_docs(
+                &index_metadata,
+                &target_index_metadata,
+                rollup_policy,
+                splits,
+                start_timestamp,
+                end_timestamp,
+                resources,
+                ctx,
+            )
+            .await?
+        };
+        execution.num_rolled_up_docs = target_docs.len();
+
+        let checkpoint_delta = IndexCheckpointDelta {
+            source_id: ROLLUP_SOURCE_ID.to_string(),
+            source_delta: SourceCheckpointDelta::from_partition_delta(
+                partition_id,
+                checkpoint_position,
+                Position::offset(end_timestamp as u64),
+            )?,
+        };
+        publish_rollup_docs(
+            &target_index_metadata,
+            target_docs,
+            checkpoint_delta,
+            resources,
+            ctx,
+        )
+        .await?;
+        info!(
+            index_id=%index_uid.index_id,
+            target_index_id=%rollup_policy.target_index_id,
+            start_timestamp=%start_timestamp,
+            end_timestamp=%end_timestamp,
+            num_rolled_up_docs=%execution.num_rolled_up_docs,
+            "rolled up {} intervals",
+            execution.num_intervals
+        );
+        if rollup_policy.delete_raw_splits {
+            execution.deleted_splits =
+                delete_rolled_up_splits(&index_uid, end_timestamp, &metastore, ctx).await?;
+        }
+    } else if rollup_policy.delete_raw_splits {
+        execution.deleted_splits =
+            delete_rolled_up_splits(&index_uid, start_timestamp, &metastore, ctx).await?;
+    }
+    Ok(execution)
+}
+
+/// Rounds `timestamp` down to a multiple of `interval_secs`.
+fn floor_timestamp(timestamp: i64, interval_secs: i64) -> i64 {
+    timestamp.div_euclid(interval_secs) * interval_secs
+}
+
+/// Returns the metadata of the target index of the rollup policy, creating the index with a
+/// generated doc mapping if it does not exist yet.
+async fn get_or_create_target_index(
+    index_metadata: &IndexMetadata,
+    rollup_policy: &RollupPolicy,
+    resources: &RollupResources,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<IndexMetadata> {
+    let metastore = resources.metastore.clone();
+    let index_metadata_request =
+        IndexMetadataRequest::for_index_id(rollup_policy.target_index_id.clone());
+
+    let mut target_index_metadata = match ctx
+        .protect_future(metastore.index_metadata(index_metadata_request))
+        .await
+    {
+        Ok(response) => response.deserialize_index_metadata()?,
+        Err(MetastoreError::NotFound(EntityKind::Index { .. })) => {
+            let target_index_config = build_target_index_config(
+                &index_metadata.index_config,
+                rollup_policy,
+                &resources.default_index_root_uri,
+            )?;
+            info!(
+                index_id=%index_metadata.index_id(),
+                target_index_id=%rollup_policy.target_index_id,
+                "creating rollup target index"
+            );
+            let mut index_service =
+                IndexService::new(metastore.clone(), resources.storage_resolver.clone());
+            ctx.protect_future(index_service.create_index(target_index_config, false))
+                .await?
+        }
+        Err(error) => return Err(error.into()),
+    };
+    let target_doc_mapper = build_doc_mapper(
+        &target_index_metadata.index_config.doc_mapping,
+        &target_index_metadata.index_config.search_settings,
+    )?;
+    let target_schema = target_doc_mapper.schema();
+
+    for target_field_name in rollup_policy.target_field_names() {
+        ensure!(
+            target_schema.get_field(&target_field_name).is_ok(),
+            "rollup target index `{}` does not have a field named `{target_field_name}`",
+            rollup_policy.target_index_id
+        );
+    }
+    ensure!(
+        target_doc_mapper.timestamp_field_name().is_some(),
+        "rollup target index `{}` does not have a timestamp field",
+        rollup_policy.target_index_id
+    );
+    if !target_index_metadata.sources.contains_key(ROLLUP_SOURCE_ID) {
+        let source_config = SourceConfig::rollup();
+        let add_source_request = AddSourceRequest::try_from_source_config(
+            target_index_metadata.index_uid.clone(),
+            &source_config,
+        )?;
+        ctx.protect_future(metastore.add_source(add_source_request))
+            .await?;
+        target_index_metadata
+            .sources
+            .insert(source_config.source_id.clone(), source_config);
+    }
+    Ok(target_index_metadata)
+}
+
+/// Generates the config of the target index of a rollup policy. The types of the `group_by`
+/// fields are inherited from the rolled-up index.
+fn build_target_index_config(
+    index_config: &IndexConfig,
+    rollup_policy: &RollupPolicy,
+    default_index_root_uri: &Uri,
+) -> anyhow::Result<IndexConfig> {
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    let schema = doc_mapper.schema();
+    let timestamp_field_name = doc_mapper
+        .timestamp_field_name()
+        .context("rolled-up index does not have a timestamp field")?;
+    let target_timestamp_field_name = RollupPolicy::target_field_name(timestamp_field_name);
+
+    let mut field_mappings = vec![json!({
+        "name": target_timestamp_field_name,
+        "type": "datetime",
+        "input_formats": ["unix_timestamp"],
+        "fast": true,
+        "fast_precision": "seconds",
+    })];
+    for field_name in &rollup_policy.group_by {
+        let field = schema
+            .get_field(field_name)
+            .with_context(|| format!("unknown group-by field `{field_name}`"))?;
+        let target_field_name = RollupPolicy::target_field_name(field_name);
+        let field_mapping = match schema.get_field_entry(field).field_type().value_type() {
+            Type::Str => json!({
+                "name": target_field_name,
+                "type": "text",
+                "tokenizer": "raw",
+                "fast": true,
+            }),
+            Type::U64 => json!({"name": target_field_name, "type": "u64", "fast": true}),
+            Type::I64 => json!({"name": target_field_name, "type": "i64", "fast": true}),
+            Type::F64 => json!({"name": target_field_name, "type": "f64", "fast": true}),
+            Type::Bool => json!({"name": target_field_name, "type": "bool", "fast": true}),
+            Type::IpAddr => json!({"name": target_field_name, "type": "ip", "fast": true}),
+            value_type => {
+                bail!("group-by field `{field_name}` has unsupported type `{value_type:?}`")
+            }
+        };
+        field_mappings.push(field_mapping);
+    }
+    field_mappings.push(json!({"name": ROLLUP_DOC_COUNT_FIELD, "type": "u64", "fast": true}));
+
+    for metric in &rollup_policy.metrics {
+        for aggregation in &metric.aggregations {
+            let field_type = match aggregation {
+                RollupAggregation::ValueCount => "u64",
+                _ => "f64",
+            };
+            field_mappings.push(json!({
+                "name": metric.target_field_name(*aggregation),
+                "type": field_type,
+                "fast": true,
+            }));
+        }
+    }
+    let target_index_config_json = json!({
+        "version": "0.8",
+        "index_id": rollup_policy.target_index_id,
+        "doc_mapping": {
+            "mode": "strict",
+            "field_mappings": field_mappings,
+            "timestamp_field": target_timestamp_field_name,
+        },
+        "indexing_settings": {
+            "merge_policy": index_config.indexing_settings.merge_policy,
+        },
+    });
+    load_index_config_from_user_config(
+        ConfigFormat::Json,
+        target_index_config_json.to_string().as_bytes(),
+        default_index_root_uri,
+    )
+}
+
+/// Lists the published splits of the index, optionally restricted to the splits overlapping the
+/// time range `[start_timestamp, end_timestamp)`.
+async fn list_published_splits(
+    index_uid: &IndexUid,
+    start_timestamp_opt: Option<i64>,
+    end_timestamp_opt: Option<i64>,
+    metastore: &MetastoreServiceClient,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<Vec<SplitMetadata>> {
+    let mut query =
+        ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+
+    if let Some(start_timestamp) = start_timestamp_opt {
+        query = query.with_time_range_start_gte(start_timestamp);
+    }
+    if let Some(end_timestamp) = end_timestamp_opt {
+        query = query.with_time_range_end_lt(end_timestamp);
+    }
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+    let splits = ctx
+        .protect_future(metastore.list_splits(list_splits_request))
+        .await?
+        .collect_splits_metadata()
+        .await?;
+    Ok(splits)
+}
+
+/// Computes the rolled-up documents of the time range `[start_timestamp, end_timestamp)` by
+/// running a `date_histogram` aggregation, nesting a `terms` aggregation per `group_by` field and
+/// a `stats` aggregation per metric, on the given splits.
+#[allow(clippy::too_many_arguments)]
+async fn compute_rollup_docs(
+    index_metadata: &IndexMetadata,
+    target_index_metadata: &IndexMetadata,
+    rollup_policy: &RollupPolicy,
+    splits: Vec<SplitMetadata>,
+    start_timestamp: i64,
+    end_timestamp: i64,
+    resources: &RollupResources,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<Vec<JsonObject<String, JsonValue>>> {
+    let index_config = &index_metadata.index_config;
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    let timestamp_field_name = doc_mapper
+        .timestamp_field_name()
+        .context("rolled-up index does not have a timestamp field")?;
+    let interval_secs = rollup_policy.interval()?.as_secs();
+
+    let target_index_config = &target_index_metadata.index_config;
+    let target_doc_mapper = build_doc_mapper(
+        &target_index_config.doc_mapping,
+        &target_index_config.search_settings,
+    )?;
+    let target_timestamp_field_name = target_doc_mapper
+        .timestamp_field_name()
+        .context("rollup target index does not have a timestamp field")?;
+    let target_schema = target_doc_mapper.schema();
+
+    let mut group_by_types = Vec::with_capacity(rollup_policy.group_by.len());
+
+    for field_name in &rollup_policy.group_by {
+        let target_field_name = RollupPolicy::target_field_name(field_name);
+        let target_field = target_schema.get_field(&target_field_name)?;
+        let value_type = target_schema
+            .get_field_entry(target_field)
+            .field_type()
+            .value_type();
+        group_by_types.push((target_field_name, value_type));
+    }
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_config.index_id.clone()],
+        query_ast: serde_json::to_string(&QueryAst::MatchAll)?,
+        start_timestamp: Some(start_timestamp),
+        end_timestamp: Some(end_timestamp),
+        max_hits: 0,
+        aggregation_request: Some(
+            build_aggregation_request(rollup_policy, timestamp_field_name, interval_secs)
+                .to_string(),
+        ),
+        ..Default::default()
+    };
+    let search_response = ctx
+        .protect_future(root_search_on_splits(
+            &resources.searcher_context,
+            search_request,
+            index_metadata,
+            splits,
+            &resources.cluster_client,
+        ))
+        .await?;
+    if !search_response.failed_splits.is_empty() {
+        let failed_split_ids = search_response
+            .failed_splits
+            .iter()
+            .map(|failed_split| &failed_split.split_id)
+            .join(", ");
+        bail!("search failed on splits: {failed_split_ids}");
+    }
+    let Some(aggregation_json) = search_response.aggregation else {
+        return Ok(Vec::new());
+    };
+    let aggregation: JsonValue = serde_json::from_str(&aggregation_json)?;
+    let rollup_doc_builder = RollupDocBuilder {
+        rollup_policy,
+        target_timestamp_field_name,
+        group_by_types: &group_by_types,
+    };
+    rollup_doc_builder.build_docs(&aggregation)
+}
+
+fn build_aggregation_request(
+    rollup_policy: &RollupPolicy,
+    timestamp_field_name: &str,
+    interval_secs: u64,
+) -> JsonValue {
+    let mut sub_aggregations = JsonObject::new();
+
+    for (metric_ord, metric) in rollup_policy.metrics.iter().enumerate() {
+        sub_aggregations.insert(
+            metric_aggregation_name(metric_ord),
+            json!({"stats": {"field": metric.field}}),
+        );
+    }
+    for (group_ord, field_name) in rollup_policy.group_by.iter().enumerate().rev() {
+        let mut terms_aggregation = json!({
+            "terms": {
+                "field": field_name,
+                "size": MAX_NUM_GROUPS,
+                "shard_size": MAX_NUM_GROUPS,
+            }
+        });
+        if !sub_aggregations.is_empty() {
+            terms_aggregation["aggs"] = JsonValue::Object(sub_aggregations);
+        }
+        sub_aggregations = JsonObject::new();
+        sub_aggregations.insert(group_aggregation_name(group_ord), terms_aggregation);
+    }
+    let mut date_histogram_aggregation = json!({
+        "date_histogram": {
+            "field": timestamp_field_name,
+            "fixed_interval": format!("{interval_secs}s"),
+            "min_doc_count": 1,
+        }
+    });
+    if !sub_aggregations.is_empty() {
+        date_histogram_aggregation["aggs"] = JsonValue::Object(sub_aggregations);
+    }
+    json!({ ROLLUP_AGGREGATION_NAME: date_histogram_aggregation })
+}
+
+fn group_aggregation_name(group_ord: usize) -> String {
+    format!("group_{group_ord}")
+}
+
+fn metric_aggregation_name(metric_ord: usize) -> String {
+    format!("metric_{metric_ord}")
+}
+
+/// Converts the results of the rollup aggregation into documents of the target index: one
+/// document per leaf bucket.
+struct RollupDocBuilder<'a> {
+    rollup_policy: &'a RollupPolicy,
+    target_timestamp_field_name: &'a str,
+    /// Names and types of the `group_by` fields in the target index.
+    group_by_types: &'a [(String, Type)],
+}
+
+impl RollupDocBuilder<'_> {
+    fn build_docs(
+        &self,
+        aggregation: &JsonValue,
+    ) -> anyhow::Result<Vec<JsonObject<String, JsonValue>>> {
+        let mut docs = Vec::new();
+        let Some(date_buckets) = aggregation[ROLLUP_AGGREGATION_NAME]["buckets"].as_array() else {
+            return Ok(docs);
+        };
+        for date_bucket in date_buckets {
+            let key_millis = date_bucket["key"]
+                .as_f64()
+                .context("date histogram bucket does not have a numeric key")?;
+            let timestamp = (key_millis / 1_000.0) as i64;
+            let mut group_values = Vec::with_capacity(self.group_by_types.len());
+            self.collect_docs(date_bucket, timestamp, &mut group_values, &mut docs)?;
+        }
+        Ok(docs)
+    }
+
+    fn collect_docs(
+        &self,
+        bucket: &JsonValue,
+        timestamp: i64,
+        group_values: &mut Vec<JsonValue>,
+        docs: &mut Vec<JsonObject<String, JsonValue>>,
+    ) -> anyhow::Result<()> {
+        let group_ord = group_values.len();
+
+        let Some((target_field_name, value_type)) = self.group_by_types.get(group_ord) else {
+            docs.push(self.build_doc(bucket, timestamp, group_values)?);
+            return Ok(());
+        };
+        let terms_aggregation = &bucket[group_aggregation_name(group_ord)];
+
+        if terms_aggregation["sum_other_doc_count"]
+            .as_u64()
+            .unwrap_or(0)
+            > 0
+        {
+            bail!(
+                "group-by field `{}` has more than {MAX_NUM_GROUPS} distinct values within an \
+                 interval",
+                self.rollup_policy.group_by[group_ord]
+            );
+        }
+        let Some(term_buckets) = terms_aggregation["buckets"].as_array() else {
+            return Ok(());
+        };
+        for term_bucket in term_buckets {
+            let group_value = convert_term_key(term_bucket, *value_type)
+                .with_context(|| format!("invalid value for field `{target_field_name}`"))?;
+            group_values.push(group_value);
+            self.collect_docs(term_bucket, timestamp, group_values, docs)?;
+            group_values.pop();
+        }
+        Ok(())
+    }
+
+    fn build_doc(
+        &self,
+        bucket: &JsonValue,
+        timestamp: i64,
+        group_values: &[JsonValue],
+    ) -> anyhow::Result<JsonObject<String, JsonValue>> {
+        let mut doc = JsonObject::new();
+        doc.insert(
+            self.target_timestamp_field_name.to_string(),
+            json!(timestamp),
+        );
+
+        for ((target_field_name, _), group_value) in self.group_by_types.iter().zip(group_values) {
+            doc.insert(target_field_name.clone(), group_value.clone());
+        }
+        let doc_count = bucket["doc_count"]
+            .as_u64()
+            .context("bucket does not have a document count")?;
+        doc.insert(ROLLUP_DOC_COUNT_FIELD.to_string(), json!(doc_count));
+
+        for (metric_ord, metric) in self.rollup_policy.metrics.iter().enumerate() {
+            let stats = &bucket[metric_aggregation_name(metric_ord)];
+
+            for aggregation in &metric.aggregations {
+                let stat_value = match aggregation {
+                    RollupAggregation::ValueCount => &stats["count"],
+                    aggregation => &stats[aggregation.as_str()],
+                };
+                // The min, max, and avg of an empty set of values are null.
+                if stat_value.is_number() {
+                    doc.insert(metric.target_field_name(*aggregation), stat_value.clone());
+                }
+            }
+        }
+        Ok(doc)
+    }
+}
+
+/// Converts the key of a `terms` bucket into a value accepted by the target field.
+fn convert_term_key(term_bucket: &JsonValue, value_type: Type) -> anyhow::Result<JsonValue> {
+    let key = &term_bucket["key"];
+
+    let value = match (value_type, key) {
+        (Type::Str | Type::IpAddr, JsonValue::String(_)) => key.clone(),
+        (Type::F64, JsonValue::Number(_)) => key.clone(),
+        (Type::U64 | Type::I64, JsonValue::Number(number)) => {
+            if number.is_f64() {
+                let float = number.as_f64().unwrap_or_default();
+                ensure!(float.fract() == 0.0, "expected an integer, got `{float}`");
+
+                if value_type == Type::U64 {
+                    json!(float as u64)
+                } else {
+                    json!(float as i64)
+                }
+            } else {
+                key.clone()
+            }
+        }
+        (Type::Bool, JsonValue::Bool(_)) => key.clone(),
+        (Type::Bool, JsonValue::Number(number)) => json!(number.as_f64() != Some(0.0)),
+        (Type::Bool, JsonValue::String(string)) => json!(string == "true"),
+        _ => bail!("unexpected key `{key}`"),
+    };
+    Ok(value)
+}
+
+/// Writes the rolled-up documents into a split and publishes it to the target index along with
+/// the checkpoint delta. If there are no documents, only the checkpoint is published.
+async fn publish_rollup_docs(
+    target_index_metadata: &IndexMetadata,
+    target_docs: Vec<JsonObject<String, JsonValue>>,
+    checkpoint_delta: IndexCheckpointDelta,
+    resources: &RollupResources,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<()> {
+    let target_index_config = &target_index_metadata.index_config;
+    let target_index_uid = target_index_metadata.index_uid.clone();
+    let target_doc_mapper = build_doc_mapper(
+        &target_index_config.doc_mapping,
+        &target_index_config.search_settings,
+    )?;
+    let target_storage = resources
+        .storage_resolver
+        .resolve(&target_index_config.index_uri)
+        .await?;
+    let target_storage = resources.storage_resolver.wrap_split_storage(
+        target_storage,
+        target_index_config.encryption_key_id(),
+        target_index_config.requires_encryption(),
+    )?;
+    let split_store = IndexingSplitStore::create_without_local_store_for_test(target_storage);
+
+    let publisher = Publisher::new(
+        PublisherType::MainPublisher,
+        resources.metastore.clone(),
+        None,
+        None,
+    );
+    let (publisher_mailbox, publisher_handle) = ctx.spawn_actor().spawn(publisher);
+
+    let uploader = Uploader::new(
+        UploaderType::IndexUploader,
+        resources.metastore.clone(),
+        merge_policy_from_settings(&target_index_config.indexing_settings),
+        target_index_config.retention_policy_opt.clone(),
+        split_store,
+        SplitsUpdateMailbox::Publisher(publisher_mailbox),
+        resources.max_concurrent_split_uploads,
+        resources.event_broker.clone(),
+    );
+    let (uploader_mailbox, _uploader_handle) = ctx.spawn_actor().spawn(uploader);
+
+    let tag_fields = target_doc_mapper.tag_named_fields()?;
+    let packager = Packager::new(
+        "RollupPackager",
+        tag_fields,
+        target_index_config.indexing_settings.docstore_layout,
+        uploader_mailbox,
+    );
+    let (packager_mailbox, _packager_handle) = ctx.spawn_actor().spawn(packager);
+
+    if target_docs.is_empty() {
+        let empty_split = EmptySplit {
+            index_uid: target_index_uid,
+            checkpoint_delta,
+            publish_lock: Default::default(),
+            publish_token_opt: None,
+            batch_parent_span: Span::current(),
+        };
+        ctx.send_message(&packager_mailbox, empty_split).await?;
+    } else {
+        let split = {
+            let _protect_guard = ctx.protect_zone();
+            build_split(
+                target_index_metadata,
+                &target_doc_mapper,
+                target_docs,
+                resources,
+            )?
+        };
+        let split_batch = IndexedSplitBatch {
+            splits: vec![split],
+            checkpoint_delta_opt: Some(checkpoint_delta),
+            publish_lock: Default::default(),
+            publish_token_opt: None,
+            merge_task_opt: None,
+            batch_parent_span: Span::current(),
+        };
+        ctx.send_message(&packager_mailbox, split_batch).await?;
+    }
+    // Dropping the mailbox of the packager shuts down the pipeline once the batch is processed.
+    drop(packager_mailbox);
+
+    let (exit_status, publisher_counters) = ctx.protect_future(publisher_handle.join()).await;
+
+    if !matches!(exit_status, ActorExitStatus::Success)
+        || publisher_counters.num_published_splits + publisher_counters.num_empty_splits == 0
+    {
+        bail!(
+            "failed to publish rolled-up documents: publisher exited with status `{exit_status:?}`"
+        );
+    }
+    Ok(())
+}
+
+fn build_split(
+    target_index_metadata: &IndexMetadata,
+    target_doc_mapper: &DocMapper,
+    target_docs: Vec<JsonObject<String, JsonValue>>,
+    resources: &RollupResources,
+) -> anyhow::Result<IndexedSplit> {
+    let target_index_config = &target_index_metadata.index_config;
+    let target_index_uid = &target_index_metadata.index_uid;
+    let indexing_settings = &target_index_config.indexing_settings;
+
+    let index_settings = IndexSettings {
+        docstore_blocksize: indexing_settings.docstore_blocksize,
+        docstore_compression: Compressor::Zstd(ZstdCompressor {
+            compression_level: Some(indexing_settings.docstore_compression_level),
+        }),
+        docstore_compress_dedicated_thread: true,
+    };
+    let index_builder = IndexBuilder::new()
+        .settings(index_settings)
+        .schema(target_doc_mapper.schema())
+        .tokenizers(
+            target_doc_mapper
+                .tokenizer_manager()
+                .tantivy_manager()
+                .clone(),
+        )
+        .fast_field_tokenizers(
+            get_quickwit_fastfield_normalizer_manager()
+                .tantivy_manager()
+                .clone(),
+        );
+    let pipeline_id = IndexingPipelineId {
+        node_id: resources.node_id.clone(),
+        index_uid: target_index_uid.clone(),
+        source_id: ROLLUP_SOURCE_ID.to_string(),
+        pipeline_uid: PipelineUid::random(),
+    };
+    let scratch_directory = temp_dir::Builder::default()
+        .join(&target_index_uid.index_id)
+        .join(&target_index_uid.incarnation_id.to_string())
+        .tempdir_in(&resources.scratch_directory_path)?;
+    let mut split_builder = IndexedSplitBuilder::new_in_dir(
+        pipeline_id,
+        0,
+        0,
+        target_doc_mapper.doc_mapping_uid(),
+        scratch_directory,
+        index_builder,
+        IoControls::default().set_component("rollup"),
+    )?;
+    let target_timestamp_field_name = target_doc_mapper
+        .timestamp_field_name()
+        .context("rollup target index does not have a timestamp field")?;
+
+    for target_doc in target_docs {
+        let timestamp = target_doc
+            .get(target_timestamp_field_name)
+            .and_then(JsonValue::as_i64)
+            .map(DateTime::from_timestamp_secs)
+            .context("rolled-up document does not have a timestamp")?;
+        let doc_len = serde_json::to_vec(&target_doc)?.len() as u64;
+        let (_partition, doc, nested_docs) = target_doc_mapper
+            .doc_with_nested_docs_from_json_obj(target_doc, doc_len)
+            .context("failed to convert rolled-up document")?;
+
+        let split_attrs = &mut split_builder.split_attrs;
+        split_attrs.num_docs += 1;
+        split_attrs.uncompressed_docs_size_in_bytes += doc_len;
+        split_attrs.time_range = Some(match split_attrs.time_range.take() {
+            Some(time_range) => {
+                timestamp.min(*time_range.start())..=timestamp.max(*time_range.end())
+            }
+            None => timestamp..=timestamp,
+        });
+        for nested_doc in nested_docs {
+            split_builder.index_writer.add_document(nested_doc)?;
+        }
+        split_builder.index_writer.add_document(doc)?;
+    }
+    split_builder.finalize()
+}
+
+/// Marks for deletion the published splits of the index whose documents have all been rolled up,
+/// i.e. splits ending before `checkpoint_timestamp`.
+async fn delete_rolled_up_splits(
+    index_uid: &IndexUid,
+    checkpoint_timestamp: i64,
+    metastore: &MetastoreServiceClient,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<Vec<SplitMetadata>> {
+    let query = ListSplitsQuery::for_index(index_uid.clone())
+        .with_split_state(SplitState::Published)
+        .with_time_range_end_lt(checkpoint_timestamp);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+    let rolled_up_splits: Vec<SplitMetadata> = ctx
+        .protect_future(metastore.list_splits(list_splits_request))
+        .await?
+        .collect_splits_metadata()
+        .await?
+        .into_iter()
+        .filter(|split_metadata| {
+            split_metadata
+                .time_range
+                .as_ref()
+                .is_some_and(|time_range| *time_range.end() < checkpoint_timestamp)
+        })
+        .collect();
+
+    if rolled_up_splits.is_empty() {
+        return Ok(rolled_up_splits);
+    }
+    let split_ids: Vec<SplitId> = rolled_up_splits
+        .iter()
+        .map(|split_metadata| split_metadata.split_id.clone())
+        .collect();
+    info!(
+        index_id=%index_uid.index_id,
+        split_ids=?PrettySample::new(&split_ids, 5),
+        "marking {} rolled-up splits for deletion",
+        split_ids.len()
+    );
+    let mark_splits_for_deletion_request =
+        MarkSplitsForDeletionRequest::new(index_uid.clone(), split_ids);
+    ctx.protect_future(metastore.mark_splits_for_deletion(mark_splits_for_deletion_request))
+        .await?;
+    Ok(rolled_up_splits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollup_policy_for_test() -> RollupPolicy {
+        let rollup_policy_json = json!({
+            "target_index_id": "logs-hourly",
+            "rollup_after": "1 day",
+            "interval": "1 hour",
+            "group_by": ["service_name", "status_code"],
+            "metrics": [{
+                "field": "latency_ms",
+                "aggregations": ["min", "max", "avg", "value_count"],
+            }],
+            "delete_raw_splits": true,
+        });
+        serde_json::from_value(rollup_policy_json).unwrap()
+    }
+
+    #[test]
+    fn test_floor_timestamp() {
+        assert_eq!(floor_timestamp(0, 3600), 0);
+        assert_eq!(floor_timestamp(3599, 3600), 0);
+        assert_eq!(floor_timestamp(3600, 3600), 3600);
+        assert_eq!(floor_timestamp(-1, 3600), -3600);
+    }
+
+    #[test]
+    fn test_build_target_index_config() {
+        let index_config_json = json!({
+            "version": "0.8",
+            "index_id": "logs",
+            "doc_mapping": {
+                "field_mappings": [
+                    {"name": "timestamp", "type": "datetime", "fast": true},
+                    {"name": "service_name", "type": "text", "tokenizer": "raw", "fast": true},
+                    {"name": "status_code", "type": "u64", "fast": true},
+                    {"name": "latency_ms", "type": "f64", "fast": true},
+                ],
+                "timestamp_field": "timestamp",
+            },
+        });
+        let default_index_root_uri = Uri::for_test("s3://quickwit-indexes");
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Json,
+            index_config_json.to_string().as_bytes(),
+            &default_index_root_uri,
+        )
+        .unwrap();
+        let rollup_policy = rollup_policy_for_test();
+        let target_index_config =
+            build_target_index_config(&index_config, &rollup_policy, &default_index_root_uri)
+                .unwrap();
+        assert_eq!(target_index_config.index_id, "logs-hourly");
+        assert_eq!(
+            target_index_config.index_uri,
+            "s3://quickwit-indexes/logs-hourly"
+        );
+        let target_doc_mapper = build_doc_mapper(
+            &target_index_config.doc_mapping,
+            &target_index_config.search_settings,
+        )
+        .unwrap();
+        assert_eq!(target_doc_mapper.timestamp_field_name(), Some("timestamp"));
+
+        let target_schema = target_doc_mapper.schema();
+        let field_types: Vec<(String, Type)> = rollup_policy
+            .target_field_names()
+            .into_iter()
+            .map(|field_name| {
+                let field = target_schema.get_field(&field_name).unwrap();
+                let value_type = target_schema
+                    .get_field_entry(field)
+                    .field_type()
+                    .value_type();
+                (field_name, value_type)
+            })
+            .collect();
+        assert_eq!(
+            field_types,
+            [
+                ("service_name".to_string(), Type::Str),
+                ("status_code".to_string(), Type::U64),
+                ("doc_count".to_string(), Type::U64),
+                ("latency_ms_min".to_string(), Type::F64),
+                ("latency_ms_max".to_string(), Type::F64),
+                ("latency_ms_avg".to_string(), Type::F64),
+                ("latency_ms_value_count".to_string(), Type::U64),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_aggregation_request() {
+        let rollup_policy = rollup_policy_for_test();
+        let aggregation_request = build_aggregation_request(&rollup_policy, "timestamp", 3600);
+        let expected_aggregation_request = json!({
+            "rollup": {
+                "date_histogram": {
+                    "field": "timestamp",
+                    "fixed_interval": "3600s",
+                    "min_doc_count": 1,
+                },
+                "aggs": {
+                    "group_0": {
+                        "terms": {
+                            "field": "service_name",
+                            "size": MAX_NUM_GROUPS,
+                            "shard_size": MAX_NUM_GROUPS,
+                        },
+                        "aggs": {
+                            "group_1": {
+                                "terms": {
+                                    "field": "status_code",
+                                    "size": MAX_NUM_GROUPS,
+                                    "shard_size": MAX_NUM_GROUPS,
+                                },
+                                "aggs": {
+                                    "metric_0": {"stats": {"field": "latency_ms"}},
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        });
+        assert_eq!(aggregation_request, expected_aggregation_request);
+    }
+
+    #[test]
+    fn test_rollup_doc_builder() {
+        let rollup_policy = rollup_policy_for_test();
+        let group_by_types = [
+            ("service_name".to_string(), Type::Str),
+            ("status_code".to_string(), Type::U64),
+        ];
+        let rollup_doc_builder = RollupDocBuilder {
+            rollup_policy: &rollup_policy,
+            target_timestamp_field_name: "timestamp",
+            group_by_types: &group_by_types,
+        };
+        let aggregation = json!({
+            "rollup": {
+                "buckets": [{
+                    "key": 3600000.0,
+                    "doc_count": 3,
+                    "group_0": {
+                        "sum_other_doc_count": 0,
+                        "buckets": [{
+                            "key": "api",
+                            "doc_count": 3,
+                            "group_1": {
+                                "sum_other_doc_count": 0,
+                                "buckets": [
+                                    {
+                                        "key": 200.0,
+                                        "doc_count": 2,
+                                        "metric_0": {"count": 2, "min": 1.0, "max": 3.0, "avg": 2.0, "sum": 4.0},
+                                    },
+                                    {
+                                        "key": 500.0,
+                                        "doc_count": 1,
+                                        "metric_0": {"count": 0, "min": null, "max": null, "avg": null, "sum": 0.0},
+                                    },
+                                ],
+                            },
+                        }],
+                    },
+                }],
+            },
+        });
+        let docs = rollup_doc_builder.build_docs(&aggregation).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(
+            JsonValue::Object(docs[0].clone()),
+            json!({
+                "timestamp": 3600,
+                "service_name": "api",
+                "status_code": 200,
+                "doc_count": 2,
+                "latency_ms_min": 1.0,
+                "latency_ms_max": 3.0,
+                "latency_ms_avg": 2.0,
+                "latency_ms_value_count": 2,
+            })
+        );
+        assert_eq!(
+            JsonValue::Object(docs[1].clone()),
+            json!({
+                "timestamp": 3600,
+                "service_name": "api",
+                "status_code": 500,
+                "doc_count": 1,
+                "latency_ms_value_count": 0,
+            })
+        );
+
+        let aggregation = json!({
+            "rollup": {
+                "buckets": [{
+                    "key": 3600000.0,
+                    "doc_count": 3,
+                    "group_0": {"sum_other_doc_count": 1, "buckets": []},
+                }],
+            },
+        });
+        let error = rollup_doc_builder.build_docs(&aggregation).unwrap_err();
+        assert!(error.to_string().contains("distinct values"));
+    }
+
+    #[test]
+    fn test_convert_term_key() {
+        assert_eq!(
+            convert_term_key(&json!({"key": 42.0}), Type::U64).unwrap(),
+            json!(42)
+        );
+        assert_eq!(
+            convert_term_key(&json!({"key": -1}), Type::I64).unwrap(),
+            json!(-1)
+        );
+        assert_eq!(
+            convert_term_key(&json!({"key": 1, "key_as_string": "true"}), Type::Bool).unwrap(),
+            json!(true)
+        );
+        assert_eq!(
+            convert_term_key(&json!({"key": "foo"}), Type::Str).unwrap(),
+            json!("foo")
+        );
+        convert_term_key(&json!({"key": 1.5}), Type::U64).unwrap_err();
+        convert_term_key(&json!({"key": "foo"}), Type::F64).unwrap_err();
+    }
+}
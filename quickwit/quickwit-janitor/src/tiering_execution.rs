@@ -0,0 +1,639 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use quickwit_actors::ActorContext;
+use quickwit_common::split_file;
+use quickwit_common::uri::Uri;
+use quickwit_config::{StorageTier, TieringPolicy};
+use quickwit_indexing::new_split_id;
+use quickwit_metastore::{
+    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitMaturity,
+    SplitMetadata, SplitState, StageSplitsRequestExt,
+};
+use quickwit_proto::metastore::{
+    ListSplitsRequest, MetastoreService, MetastoreServiceClient, PublishSplitsRequest,
+    StageSplitsRequest,
+};
+use quickwit_proto::types::IndexUid;
+use quickwit_storage::{FilePayload, Storage, StorageResolver};
+use time::OffsetDateTime;
+use tracing::{error, info};
+
+use crate::actors::TieringExecutor;
+
+/// Maximum number of splits moved by a single execution. An index lagging behind is caught up by
+/// successive executions.
+const MAX_NUM_SPLITS_PER_EXECUTION: usize = 100;
+
+/// Services and settings shared by the tiering executions of all the indexes.
+pub(crate) struct TieringResources {
+    pub metastore: MetastoreServiceClient,
+    pub storage_resolver: StorageResolver,
+    pub scratch_directory_path: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TieringExecution {
+    /// The number of splits moved to a storage tier.
+    pub num_moved_splits: usize,
+    /// The number of bytes moved to a storage tier.
+    pub num_moved_bytes: u64,
+    /// The number of splits that could not be moved.
+    pub num_failed_splits: usize,
+    /// Whether some eligible splits were left for the next execution.
+    pub is_lagging: bool,
+}
+
+/// Moves the published splits of the index `index_uid` older than the delay of a storage tier to
+/// the coldest such tier.
+///
+/// A split is moved by copying its file to the storage of the tier under a new split ID, staging
+/// the new split with its storage URI, and publishing it in replacement of the original split.
+/// The original split file is deleted by the garbage collector after the deletion grace period,
+/// so searches running on the original split are not interrupted.
+pub(crate) async fn run_execute_tiering_policy(
+    index_uid: IndexUid,
+    index_uri: &Uri,
+    tiering_policy: &TieringPolicy,
+    resources: &TieringResources,
+    ctx: &ActorContext<TieringExecutor>,
+) -> anyhow::Result<TieringExecution> {
+    let mut execution = TieringExecution::default();
+
+    let now = OffsetDateTime::now_utc();
+    let now_timestamp = now.unix_timestamp();
+    let mut tier_max_timestamps = Vec::with_capacity(tiering_policy.tiers.len());
+
+    for tier in &tiering_policy.tiers {
+        let move_after_secs = tier.move_after()?.as_secs() as i64;
+        tier_max_timestamps.push(now_timestamp - move_after_secs);
+    }
+    let Some(&max_timestamp) = tier_max_timestamps.first() else {
+        return Ok(execution);
+    };
+    let splits =
+        list_published_splits(&index_uid, max_timestamp, &resources.metastore, ctx).await?;
+
+    let mut split_moves: Vec<(SplitMetadata, &StorageTier)> = Vec::new();
+
+    for split_metadata in splits {
+        if !split_metadata.is_mature(now) {
+            continue;
+        }
+        // Splits stored in a storage that is no longer part of the tiering policy are left
+        // untouched.
+        let Some(current_tier_rank) = tiering_policy.tier_rank(split_metadata.storage_uri.as_ref())
+        else {
+            continue;
+        };
+        let Some(target_tier_ord) = target_tier_ord(&split_metadata, &tier_max_timestamps) else {
+            continue;
+        };
+        if target_tier_ord + 1 > current_tier_rank {
+            split_moves.push((split_metadata, &tiering_policy.tiers[target_tier_ord]));
+        }
+    }
+    if split_moves.len() > MAX_NUM_SPLITS_PER_EXECUTION {
+        // The oldest splits are moved first.
+        split_moves.sort_by_key(|(split_metadata, _)| split_age_timestamp(split_metadata));
+        split_moves.truncate(MAX_NUM_SPLITS_PER_EXECUTION);
+        execution.is_lagging = true;
+    }
+    let mut storages: HashMap<Uri, Arc<dyn Storage>> = HashMap::new();
+
+    for (split_metadata, tier) in split_moves {
+        let source_uri = split_metadata.storage_uri(index_uri).clone();
+        let source_storage = resolve_storage(&mut storages, &source_uri, resources).await?;
+        let tier_storage = resolve_storage(&mut storages, &tier.storage_uri, resources).await?;
+
+        match move_split(
+            &index_uid,
+            &split_metadata,
+            tier,
+            source_storage,
+            tier_storage,
+            resources,
+            ctx,
+        )
+        .await
+        {
+            Ok(moved_split_metadata) => {
+                info!(
+                    index_id=%index_uid.index_id,
+                    split_id=%split_metadata.split_id,
+                    moved_split_id=%moved_split_metadata.split_id,
+                    tier=%tier.name,
+                    "moved split to storage tier"
+                );
+                execution.num_moved_splits += 1;
+                execution.num_moved_bytes += split_metadata.footer_offsets.end;
+            }
+            Err(move_error) => {
+                error!(
+                    index_id=%index_uid.index_id,
+                    split_id=%split_metadata.split_id,
+                    tier=%tier.name,
+                    error=?move_error,
+                    "failed to move split to storage tier"
+                );
+                execution.num_failed_splits += 1;
+            }
+        }
+    }
+    Ok(execution)
+}
+
+/// Lists the published splits that may be older than `max_timestamp`.
+async fn list_published_splits(
+    index_uid: &IndexUid,
+    max_timestamp: i64,
+    metastore: &MetastoreServiceClient,
+    ctx: &ActorContext<TieringExecutor>,
+) -> anyhow::Result<Vec<SplitMetadata>> {
+    let query = ListSplitsQuery::for_index(index_uid.clone())
+        .with_split_state(SplitState::Published)
+        .with_time_range_end_lt(max_timestamp);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query)?;
+    let splits = ctx
+        .protect_future(metastore.list_splits(list_splits_request))
+        .await?
+        .collect_splits_metadata()
+        .await?;
+    Ok(splits)
+}
+
+/// Returns the timestamp from which the age of a split is computed: the end of its time range if
+/// any, its creation date otherwise.
+fn split_age_timestamp(split_metadata: &SplitMetadata) -> i64 {
+    split_metadata
+        .time_range
+        .as_ref()
+        .map(|time_range| *time_range.end())
+        .unwrap_or(split_metadata.create_timestamp)
+}
+
+/// Returns the position of the coldest tier the split is old enough to be moved to.
+fn target_tier_ord(split_metadata: &SplitMetadata, tier_max_timestamps: &[i64]) -> Option<usize> {
+    let age_timestamp = split_age_timestamp(split_metadata);
+    tier_max_timestamps
+        .iter()
+        .rposition(|max_timestamp| age_timestamp < *max_timestamp)
+}
+
+async fn resolve_storage(
+    storages: &mut HashMap<Uri, Arc<dyn Storage>>,
+    storage_uri: &Uri,
+    resources: &TieringResources,
+) -> anyhow::Result<Arc<dyn Storage>> {
+    if let Some(storage) = storages.get(storage_uri) {
+        return Ok(storage.clone());
+    }
+    let storage = resources.storage_resolver.resolve(storage_uri).await?;
+    storages.insert(storage_uri.clone(), storage.clone());
+    Ok(storage)
+}
+
+async fn move_split(
+    index_uid: &IndexUid,
+    split_metadata: &SplitMetadata,
+    tier: &StorageTier,
+    source_storage: Arc<dyn Storage>,
+    tier_storage: Arc<dyn Storage>,
+    resources: &TieringResources,
+    ctx: &ActorContext<TieringExecutor>,
+) -> anyhow::Result<SplitMetadata> {
+    let mut moved_split_metadata = split_metadata.clone();
+    moved_split_metadata.split_id = new_split_id();
+    moved_split_metadata.storage_uri = Some(tier.storage_uri.clone());
+    // Lets monitors tell the moved split apart from a split holding new documents.
+    moved_split_metadata.replaced_split_ids = vec![split_metadata.split_id.clone()];
+    // Moved splits are never merged.
+    moved_split_metadata.maturity = SplitMaturity::Mature;
+
+    // The split is staged before being uploaded so that the garbage collector cleans up the split
+    // file if the move fails midway.
+    let stage_splits_request =
+        StageSplitsRequest::try_from_split_metadata(index_uid.clone(), &moved_split_metadata)?;
+    ctx.protect_future(resources.metastore.stage_splits(stage_splits_request))
+        .await?;
+
+    let split_path = PathBuf::from(split_file(&split_metadata.split_id));
+    let local_split_path = resources.scratch_directory_path.join(&split_path);
+    let copy_result = copy_split_file(
+        &split_path,
+        &local_split_path,
+        &moved_split_metadata.split_id,
+        source_storage,
+        tier_storage,
+        ctx,
+    )
+    .await;
+
+    if let Err(remove_error) = tokio::fs::remove_file(&local_split_path).await {
+        error!(path=%local_split_path.display(), error=?remove_error, "failed to remove local split file");
+    }
+    copy_result?;
+
+    let publish_splits_request = PublishSplitsRequest {
+        index_uid: Some(index_uid.clone()),
+        staged_split_ids: vec![moved_split_metadata.split_id.clone()],
+        replaced_split_ids: vec![split_metadata.split_id.clone()],
+        index_checkpoint_delta_json_opt: None,
+        publish_token_opt: None,
+    };
+    ctx.protect_future(resources.metastore.publish_splits(publish_splits_request))
+        .await?;
+    Ok(moved_split_metadata)
+}
+
+async fn copy_split_file(
+    split_path: &Path,
+    local_split_path: &Path,
+    moved_split_id: &str,
+    source_storage: Arc<dyn Storage>,
+    tier_storage: Arc<dyn Storage>,
+    ctx: &ActorContext<TieringExecutor>,
+) -> anyhow::Result<()> {
+    ctx.protect_future(source_storage.copy_to_file(split_path, local_split_path))
+        .await?;
+    let payload = FilePayload::open(local_split_path)?;
+    let moved_split_path = PathBuf::from(split_file(moved_split_id));
+    ctx.protect_future(tier_storage.put(&moved_split_path, Box::new(payload)))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quickwit_actors::Universe;
+    use quickwit_config::{IndexConfig, MonitorConfig};
+    use quickwit_metastore::{metastore_for_test, CreateIndexRequestExt, MonitorMetadata};
+    use quickwit_proto::metastore::CreateIndexRequest;
+    use quickwit_proto::search::LeafSearchResponse;
+    use quickwit_search::{
+        searcher_pool_for_test, ClusterClient, MockSearchService, SearchJobPlacer, SearcherContext,
+    };
+    use quickwit_storage::PutPayload;
+    use serde_json::json;
+    use tokio::sync::watch;
+
+    use super::*;
+    use crate::actors::MonitorExecutor;
+    use crate::monitor_evaluation::{run_monitor_evaluation, MonitorActionClients};
+
+    /// Waits until the splits published so far fall within the evaluation window of monitors,
+    /// which ends one second before the evaluation.
+    async fn wait_for_monitor_evaluation_window(publish_timestamp: i64) {
+        while OffsetDateTime::now_utc().unix_timestamp() - 1 < publish_timestamp {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    #[test]
+    fn test_target_tier_ord() {
+        let tier_max_timestamps = [1_000, 500];
+
+        let mut split_metadata = SplitMetadata {
+            time_range: Some(0..=1_200),
+            create_timestamp: 1_300,
+            ..Default::default()
+        };
+        assert_eq!(target_tier_ord(&split_metadata, &tier_max_timestamps), None);
+
+        split_metadata.time_range = Some(0..=800);
+        assert_eq!(
+            target_tier_ord(&split_metadata, &tier_max_timestamps),
+            Some(0)
+        );
+
+        split_metadata.time_range = Some(0..=100);
+        assert_eq!(
+            target_tier_ord(&split_metadata, &tier_max_timestamps),
+            Some(1)
+        );
+
+        split_metadata.time_range = None;
+        assert_eq!(target_tier_ord(&split_metadata, &tier_max_timestamps), None);
+
+        split_metadata.create_timestamp = 700;
+        assert_eq!(
+            target_tier_ord(&split_metadata, &tier_max_timestamps),
+            Some(0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_execute_tiering_policy() {
+        let metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let scratch_directory = tempfile::tempdir().unwrap();
+
+        let index_id = "test-tiering-execution--index";
+        let index_uri = Uri::for_test("ram:///indexes/test-tiering-execution--index");
+        let tier_uri = Uri::for_test("ram:///cold/test-tiering-execution--index");
+
+        let mut index_config = IndexConfig::for_test(index_id, index_uri.as_str());
+        let tiering_policy_json = json!({
+            "tiers": [{
+                "name": "cold",
+                "storage_uri": tier_uri,
+                "move_after": "30 days",
+            }],
+        });
+        let tiering_policy: TieringPolicy = serde_json::from_value(tiering_policy_json).unwrap();
+        index_config.tiering_policy_opt = Some(tiering_policy.clone());
+
+        let create_index_request =
+            CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid()
+            .clone();
+
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let old_split_metadata = SplitMetadata {
+            split_id: "old-split".to_string(),
+            index_uid: index_uid.clone(),
+            time_range: Some(0..=now_timestamp - 60 * 24 * 3600),
+            footer_offsets: 0..5,
+            maturity: SplitMaturity::Mature,
+            ..Default::default()
+        };
+        let recent_split_metadata = SplitMetadata {
+            split_id: "recent-split".to_string(),
+            index_uid: index_uid.clone(),
+            time_range: Some(0..=now_timestamp),
+            footer_offsets: 0..5,
+            maturity: SplitMaturity::Mature,
+            ..Default::default()
+        };
+        let stage_splits_request = StageSplitsRequest::try_from_splits_metadata(
+            index_uid.clone(),
+            [old_split_metadata, recent_split_metadata],
+        )
+        .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: Some(index_uid.clone()),
+            staged_split_ids: vec!["old-split".to_string(), "recent-split".to_string()],
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+
+        let index_storage = storage_resolver.resolve(&index_uri).await.unwrap();
+        let payload: Box<dyn PutPayload> = Box::new(b"split".to_vec());
+        index_storage
+            .put(Path::new("old-split.split"), payload)
+            .await
+            .unwrap();
+
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, _inbox) = universe.create_test_mailbox::<TieringExecutor>();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(Default::default());
+        let ctx = ActorContext::for_test(&universe, mailbox, observable_state_tx);
+
+        let resources = TieringResources {
+            metastore: metastore.clone(),
+            storage_resolver: storage_resolver.clone(),
+            scratch_directory_path: scratch_directory.path().to_path_buf(),
+        };
+        let execution = run_execute_tiering_policy(
+            index_uid.clone(),
+            &index_uri,
+            &tiering_policy,
+            &resources,
+            &ctx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(execution.num_moved_splits, 1);
+        assert_eq!(execution.num_moved_bytes, 5);
+        assert_eq!(execution.num_failed_splits, 0);
+        assert!(!execution.is_lagging);
+
+        let query =
+            ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query).unwrap();
+        let mut published_splits = metastore
+            .list_splits(list_splits_request)
+            .await
+            .unwrap()
+            .collect_splits_metadata()
+            .await
+            .unwrap();
+        published_splits.sort_by_key(|split_metadata| split_metadata.storage_uri.is_some());
+        assert_eq!(published_splits.len(), 2);
+        assert_eq!(published_splits[0].split_id, "recent-split");
+
+        let moved_split_metadata = &published_splits[1];
+        assert_eq!(moved_split_metadata.storage_uri.as_ref(), Some(&tier_uri));
+        assert_eq!(moved_split_metadata.maturity, SplitMaturity::Mature);
+
+        let tier_storage = storage_resolver.resolve(&tier_uri).await.unwrap();
+        let moved_split_path = PathBuf::from(split_file(&moved_split_metadata.split_id));
+        let moved_split_bytes = tier_storage.get_all(&moved_split_path).await.unwrap();
+        assert_eq!(moved_split_bytes.as_slice(), b"split");
+
+        // The moved split is not moved again.
+        let execution = run_execute_tiering_policy(
+            index_uid.clone(),
+            &index_uri,
+            &tiering_policy,
+            &resources,
+            &ctx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(execution.num_moved_splits, 0);
+
+        let query =
+            ListSplitsQuery::for_index(index_uid).with_split_state(SplitState::MarkedForDeletion);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query).unwrap();
+        let marked_splits = metastore
+            .list_splits(list_splits_request)
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        assert_eq!(marked_splits.len(), 1);
+        assert_eq!(marked_splits[0].split_id(), "old-split");
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_run_execute_tiering_policy_does_not_retrigger_monitors() {
+        let metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let scratch_directory = tempfile::tempdir().unwrap();
+
+        let index_id = "test-tiering-execution-monitor--index";
+        let index_uri = Uri::for_test("ram:///indexes/test-tiering-execution-monitor--index");
+        let tier_uri = Uri::for_test("ram:///cold/test-tiering-execution-monitor--index");
+
+        let mut index_config = IndexConfig::for_test(index_id, index_uri.as_str());
+        let tiering_policy_json = json!({
+            "tiers": [{
+                "name": "cold",
+                "storage_uri": tier_uri,
+                "move_after": "30 days",
+            }],
+        });
+        let tiering_policy: TieringPolicy = serde_json::from_value(tiering_policy_json).unwrap();
+        index_config.tiering_policy_opt = Some(tiering_policy.clone());
+
+        let create_index_request =
+            CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid()
+            .clone();
+
+        let mut monitor_metadata =
+            MonitorMetadata::new(MonitorConfig::for_test("test-monitor", index_id));
+        monitor_metadata.monitor_state.checkpoint_timestamp -= 1;
+
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let old_split_metadata = SplitMetadata {
+            split_id: "old-split".to_string(),
+            index_uid: index_uid.clone(),
+            time_range: Some(0..=now_timestamp - 60 * 24 * 3600),
+            footer_offsets: 0..5,
+            maturity: SplitMaturity::Mature,
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), &old_split_metadata)
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: Some(index_uid.clone()),
+            staged_split_ids: vec!["old-split".to_string()],
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+        let publish_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+        let index_storage = storage_resolver.resolve(&index_uri).await.unwrap();
+        let payload: Box<dyn PutPayload> = Box::new(b"split".to_vec());
+        index_storage
+            .put(Path::new("old-split.split"), payload)
+            .await
+            .unwrap();
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(1)
+            .returning(|leaf_search_request| {
+                let split_ids: Vec<&str> = leaf_search_request.leaf_requests[0]
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect();
+                assert_eq!(split_ids, ["old-split"]);
+
+                Ok(LeafSearchResponse {
+                    num_hits: 3,
+                    num_attempted_splits: 1,
+                    num_successful_splits: 1,
+                    ..Default::default()
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1000", mock_search_service)]);
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool));
+        let searcher_context = SearcherContext::for_test();
+        let action_clients = MonitorActionClients::default();
+
+        let universe = Universe::with_accelerated_time();
+        let (monitor_mailbox, _monitor_inbox) = universe.create_test_mailbox::<MonitorExecutor>();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(Default::default());
+        let monitor_ctx = ActorContext::for_test(&universe, monitor_mailbox, observable_state_tx);
+
+        wait_for_monitor_evaluation_window(publish_timestamp).await;
+
+        let evaluation = run_monitor_evaluation(
+            &mut monitor_metadata,
+            metastore.clone(),
+            &searcher_context,
+            &cluster_client,
+            &action_clients,
+            &monitor_ctx,
+        )
+        .await;
+        assert!(evaluation.error.is_none());
+        assert_eq!(evaluation.num_splits, 1);
+        assert!(evaluation.triggered);
+
+        let (mailbox, _inbox) = universe.create_test_mailbox::<TieringExecutor>();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(Default::default());
+        let ctx = ActorContext::for_test(&universe, mailbox, observable_state_tx);
+
+        let resources = TieringResources {
+            metastore: metastore.clone(),
+            storage_resolver: storage_resolver.clone(),
+            scratch_directory_path: scratch_directory.path().to_path_buf(),
+        };
+        let execution = run_execute_tiering_policy(
+            index_uid.clone(),
+            &index_uri,
+            &tiering_policy,
+            &resources,
+            &ctx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(execution.num_moved_splits, 1);
+        let move_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+        wait_for_monitor_evaluation_window(move_timestamp).await;
+
+        // The moved split holds the documents the monitor already alerted on, so it is not
+        // searched again.
+        let evaluation = run_monitor_evaluation(
+            &mut monitor_metadata,
+            metastore,
+            &searcher_context,
+            &cluster_client,
+            &action_clients,
+            &monitor_ctx,
+        )
+        .await;
+        assert!(evaluation.error.is_none());
+        assert!(evaluation.checkpoint_timestamp >= move_timestamp);
+        assert_eq!(evaluation.num_splits, 0);
+        assert!(!evaluation.triggered);
+
+        universe.assert_quit().await;
+    }
+}
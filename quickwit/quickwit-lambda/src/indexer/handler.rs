@@ -12,18 +12,78 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use aws_lambda_events::event::sqs::{BatchItemFailure, SqsBatchResponse, SqsEvent};
 use lambda_runtime::{Error, LambdaEvent};
 use serde_json::Value;
-use tracing::{debug_span, error, info, info_span, Instrument};
+use tracing::{debug_span, error, info, info_span, warn, Instrument};
 
 use super::environment::{DISABLE_JANITOR, DISABLE_MERGE, INDEX_CONFIG_URI};
-use super::ingest::{ingest, IngestArgs};
-use super::model::IndexerEvent;
+use super::ingest::{ingest, ingest_sqs_batch, IngestArgs, SqsBatchIngestArgs};
+use super::model::{is_sqs_event, IndexerEvent, SqsBatch};
 use crate::environment::INDEX_ID;
 use crate::logger;
 use crate::utils::LambdaContainerContext;
 
+/// Indexes a batch of S3 notifications delivered by an SQS event source
+/// mapping. The messages that could not be indexed are reported as batch item
+/// failures, which requires the `ReportBatchItemFailures` function response
+/// type to be enabled on the event source mapping.
+async fn sqs_batch_indexer_handler(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    let container_ctx = LambdaContainerContext::load();
+    let memory = event.context.env_config.memory;
+    let sqs_event = serde_json::from_value::<SqsEvent>(event.payload)?;
+    let sqs_batch = SqsBatch::try_from_event(sqs_event)?;
+    let num_messages = sqs_batch.messages.len();
+
+    let ingest_res = ingest_sqs_batch(SqsBatchIngestArgs {
+        queue_url: sqs_batch.queue_url,
+        messages: sqs_batch.messages,
+        input_format: quickwit_config::SourceInputFormat::Json,
+        vrl_script: None,
+        clear_cache: true,
+    })
+    .instrument(debug_span!(
+        "ingest_sqs_batch",
+        memory,
+        num_messages,
+        env.INDEX_CONFIG_URI = *INDEX_CONFIG_URI,
+        env.INDEX_ID = *INDEX_ID,
+        env.DISABLE_MERGE = *DISABLE_MERGE,
+        env.DISABLE_JANITOR = *DISABLE_JANITOR,
+        cold = container_ctx.cold,
+        container_id = container_ctx.container_id,
+    ))
+    .await;
+
+    match ingest_res {
+        Ok(result) => {
+            if result.failed_message_ids.is_empty() {
+                info!(result=?result, "Batch indexing succeeded");
+            } else {
+                warn!(result=?result, "Batch indexing partially failed");
+            }
+            let batch_item_failures = result
+                .failed_message_ids
+                .into_iter()
+                .map(|message_id| BatchItemFailure {
+                    item_identifier: message_id,
+                })
+                .collect();
+            Ok(serde_json::to_value(SqsBatchResponse {
+                batch_item_failures,
+            })?)
+        }
+        Err(e) => {
+            error!(err=?e, "Batch indexing failed");
+            Err(anyhow::anyhow!("Batch indexing failed").into())
+        }
+    }
+}
+
 async fn indexer_handler(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    if is_sqs_event(&event.payload) {
+        return sqs_batch_indexer_handler(event).await;
+    }
     let container_ctx = LambdaContainerContext::load();
     let memory = event.context.env_config.memory;
     let payload = serde_json::from_value::<IndexerEvent>(event.payload)?;
@@ -27,12 +27,12 @@ use quickwit_common::uri::Uri;
 use quickwit_config::merge_policy_config::MergePolicyConfig;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
-    load_index_config_from_user_config, ConfigFormat, IndexConfig, NodeConfig, SourceConfig,
-    SourceInputFormat, SourceParams, TransformConfig,
+    load_index_config_from_user_config, ConfigFormat, FileSourceMessageType, FileSourceParams,
+    IndexConfig, NodeConfig, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
 };
 use quickwit_indexing::actors::{IndexingService, MergePipeline, MergeSchedulerService};
 use quickwit_indexing::models::{DetachIndexingPipeline, DetachMergePipeline, SpawnPipeline};
-use quickwit_indexing::IndexingPipeline;
+use quickwit_indexing::{FinishPendingMergesAndShutdownPipeline, IndexingPipeline};
 use quickwit_ingest::IngesterPool;
 use quickwit_janitor::{start_janitor_service, JanitorService};
 use quickwit_metastore::{
@@ -56,6 +56,7 @@ use crate::indexer::environment::{
 };
 
 const LAMBDA_SOURCE_ID: &str = "ingest-lambda-source";
+const LAMBDA_SQS_SOURCE_ID: &str = "sqs-lambda-source";
 
 /// The indexing service needs to update its cluster chitchat state so that the control plane is
 /// aware of the running tasks. We thus create a fake cluster to instantiate the indexing service
@@ -126,7 +127,8 @@ pub(super) async fn send_telemetry() {
         HashSet::from_iter([QuickwitService::Indexer.as_str().to_string()]);
     let telemetry_info =
         QuickwitTelemetryInfo::new(services, HashSet::from_iter([QuickwitFeature::AwsLambda]));
-    let _telemetry_handle_opt = quickwit_telemetry::start_telemetry_loop(telemetry_info);
+    let _telemetry_handle_opt =
+        quickwit_telemetry::start_telemetry_loop(telemetry_info, Vec::new());
     quickwit_telemetry::send_telemetry_event(TelemetryEvent::RunCommand).await;
 }
 
@@ -148,10 +150,35 @@ pub(super) async fn configure_source(
     })
 }
 
+/// Convert the SQS queue that delivered a batch of S3 notifications to a source
+/// config
+///
+/// The notifications are deduplicated with the shard table, like for regular
+/// SQS file sources.
+pub(super) fn configure_sqs_source(
+    queue_url: String,
+    input_format: SourceInputFormat,
+    vrl_script: Option<String>,
+) -> SourceConfig {
+    let transform_config = vrl_script.map(|vrl_script| TransformConfig::new(vrl_script, None));
+    let source_params = SourceParams::File(FileSourceParams::from_sqs_queue(
+        queue_url,
+        FileSourceMessageType::S3Notification,
+    ));
+    SourceConfig {
+        source_id: LAMBDA_SQS_SOURCE_ID.to_owned(),
+        num_pipelines: NonZeroUsize::new(1).expect("1 is always non-zero."),
+        enabled: true,
+        source_params,
+        transform_config,
+        input_format,
+    }
+}
+
 /// Check if the index exists, creating it if necessary
 ///
-/// If the index exists but without the Lambda source ([`LAMBDA_SOURCE_ID`]),
-/// the source is added.
+/// If the index exists but without the provided Lambda source, the source is
+/// added.
 pub(super) async fn init_index_if_necessary(
     metastore: &mut MetastoreServiceClient,
     storage_resolver: &StorageResolver,
@@ -164,7 +191,10 @@ pub(super) async fn init_index_if_necessary(
     let metadata = match metadata_result {
         Ok(metadata_resp) => {
             let current_metadata = metadata_resp.deserialize_index_metadata()?;
-            if !current_metadata.sources.contains_key(LAMBDA_SOURCE_ID) {
+            if !current_metadata
+                .sources
+                .contains_key(&source_config.source_id)
+            {
                 let add_source_request = AddSourceRequest::try_from_source_config(
                     current_metadata.index_uid.clone(),
                     source_config,
@@ -321,26 +351,38 @@ pub(super) async fn prune_lambda_source(
     Ok(())
 }
 
-/// Observe the merge pipeline until there are no more ongoing merges
-pub(super) async fn wait_for_merges(
+/// Run the pending merges and wait for the merge pipeline to terminate
+///
+/// The merge pipeline is seeded with the immature splits published by previous
+/// invocations, so the small splits produced by successive invocations get
+/// merged together. Asking the pipeline to finish its pending merges, instead
+/// of observing its ongoing merges, guarantees that the merge planner has
+/// accounted for the splits published by this invocation and that no merge is
+/// left behind when the universe shuts down.
+pub(super) async fn finish_merges(
     merge_pipeline_handle: ActorHandle<MergePipeline>,
 ) -> anyhow::Result<()> {
-    // TODO: find a way to stop the MergePlanner actor in the MergePipeline,
-    // otherwise a new merge might be scheduled after this loop. That shouldn't
-    // have any concrete impact as the merge will be immediately cancelled, but
-    // it might generate errors during the universe shutdown (i.e "Failed to
-    // acquire permit")
+    // The shutdown request is ignored until the pipeline actors are spawned.
     loop {
-        let state = merge_pipeline_handle.state();
         let obs = merge_pipeline_handle.observe().await;
-        debug!(state=?state, ongoing=obs.num_ongoing_merges, "merge pipeline state");
-        if obs.num_ongoing_merges == 0 {
+        debug!(generation = obs.generation, "merge pipeline state");
+        if obs.generation > 0 {
             break;
         }
-        // We tolerate a relatively low refresh rate because the indexer
-        // typically runs for longer periods of times and merges happen only
-        // occasionally.
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+    merge_pipeline_handle
+        .mailbox()
+        .send_message(FinishPendingMergesAndShutdownPipeline)
+        .await?;
+    let (exit_status, statistics) = merge_pipeline_handle.join().await;
+    debug!(
+        exit_status=?exit_status,
+        num_published_splits = statistics.num_published_splits,
+        "merge pipeline terminated"
+    );
+    if !exit_status.is_success() {
+        bail!("merge pipeline failed: {exit_status:?}");
     }
     Ok(())
 }
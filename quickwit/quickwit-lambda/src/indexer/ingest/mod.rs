@@ -15,11 +15,12 @@
 mod helpers;
 
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use anyhow::bail;
 use helpers::{
-    configure_source, create_empty_cluster, init_index_if_necessary, send_telemetry,
-    spawn_pipelines, spawn_services,
+    configure_source, configure_sqs_source, create_empty_cluster, init_index_if_necessary,
+    send_telemetry, spawn_pipelines, spawn_services,
 };
 use quickwit_actors::Universe;
 use quickwit_cli::start_actor_runtimes;
@@ -27,13 +28,17 @@ use quickwit_cli::tool::start_statistics_reporting_loop;
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::Uri;
 use quickwit_config::service::QuickwitService;
-use quickwit_config::SourceInputFormat;
+use quickwit_config::{FileSourceMessageType, SourceConfig, SourceInputFormat};
 use quickwit_index_management::clear_cache_directory;
 use quickwit_indexing::models::IndexingStatistics;
-use tracing::{debug, info};
+use quickwit_indexing::source::batch_queue::{BatchQueue, BatchQueueMessage};
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::types::{IndexUid, SourceUid};
+use serde::Serialize;
+use tracing::{debug, info, warn};
 
 use crate::indexer::environment::{CONFIGURATION_TEMPLATE, DISABLE_JANITOR};
-use crate::indexer::ingest::helpers::{prune_lambda_source, wait_for_merges};
+use crate::indexer::ingest::helpers::{finish_merges, prune_lambda_source};
 use crate::utils::load_node_config;
 
 #[derive(Debug, Eq, PartialEq)]
@@ -44,17 +49,107 @@ pub struct IngestArgs {
     pub clear_cache: bool,
 }
 
+#[derive(Debug)]
+pub struct SqsBatchIngestArgs {
+    pub queue_url: String,
+    pub messages: Vec<BatchQueueMessage>,
+    pub input_format: SourceInputFormat,
+    pub vrl_script: Option<String>,
+    pub clear_cache: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SqsBatchIngestResult {
+    #[serde(flatten)]
+    pub statistics: IndexingStatistics,
+    /// Messages that were not indexed and should be delivered again
+    pub failed_message_ids: Vec<String>,
+}
+
+/// The outcome of an indexing pipeline run
+struct IndexingRun {
+    statistics: IndexingStatistics,
+    metastore: MetastoreServiceClient,
+    index_uid: IndexUid,
+}
+
 pub async fn ingest(args: IngestArgs) -> anyhow::Result<IndexingStatistics> {
     debug!(args=?args, "lambda-ingest");
 
+    let source_config =
+        configure_source(args.input_path, args.input_format, args.vrl_script).await?;
+    let indexing_run = run_indexing_pipeline(source_config, args.clear_cache).await?;
+    let statistics = indexing_run.statistics;
+
+    if statistics.num_invalid_docs > 0 {
+        bail!("Failed to ingest {} documents", statistics.num_invalid_docs)
+    }
+    Ok(statistics)
+}
+
+/// Indexes the files of the S3 notifications received in a batch of SQS
+/// messages, producing a single split for the whole batch.
+///
+/// The notifications go through the same queue coordinator as regular SQS file
+/// sources, so files that were already indexed, e.g by a previous delivery of
+/// the message, are skipped. The ids of the messages that could not be indexed
+/// are returned so that they can be reported as batch item failures.
+pub async fn ingest_sqs_batch(args: SqsBatchIngestArgs) -> anyhow::Result<SqsBatchIngestResult> {
+    debug!(
+        queue_url = args.queue_url,
+        num_messages = args.messages.len(),
+        "lambda-ingest-sqs-batch"
+    );
+    let batch_queue = Arc::new(BatchQueue::new(
+        FileSourceMessageType::S3Notification,
+        args.messages,
+    ));
+    // The file source of the pipeline picks up the batch queue instead of
+    // polling the SQS queue.
+    batch_queue.register(&args.queue_url);
+
+    let source_config =
+        configure_sqs_source(args.queue_url.clone(), args.input_format, args.vrl_script);
+    let source_id = source_config.source_id.clone();
+    let indexing_run_res = run_indexing_pipeline(source_config, args.clear_cache).await;
+    // The batch queue is still registered if the pipeline failed to start.
+    BatchQueue::take_registered(&args.queue_url);
+    let IndexingRun {
+        statistics,
+        metastore,
+        index_uid,
+    } = indexing_run_res?;
+
+    if statistics.num_invalid_docs > 0 {
+        warn!(
+            num_invalid_docs = statistics.num_invalid_docs,
+            "invalid documents were not indexed"
+        );
+    }
+    let failed_message_ids = batch_queue
+        .unprocessed_message_ids(
+            &metastore,
+            &SourceUid {
+                index_uid,
+                source_id,
+            },
+        )
+        .await?;
+    Ok(SqsBatchIngestResult {
+        statistics,
+        failed_message_ids,
+    })
+}
+
+async fn run_indexing_pipeline(
+    source_config: SourceConfig,
+    clear_cache: bool,
+) -> anyhow::Result<IndexingRun> {
     send_telemetry().await;
 
     let (config, storage_resolver, mut metastore) =
         load_node_config(CONFIGURATION_TEMPLATE).await?;
 
-    let source_config =
-        configure_source(args.input_path, args.input_format, args.vrl_script).await?;
-
     let index_metadata = init_index_if_necessary(
         &mut metastore,
         &storage_resolver,
@@ -62,6 +157,7 @@ pub async fn ingest(args: IngestArgs) -> anyhow::Result<IndexingStatistics> {
         &source_config,
     )
     .await?;
+    let index_uid = index_metadata.index_uid.clone();
 
     let mut services = vec![QuickwitService::Indexer];
     if !*DISABLE_JANITOR {
@@ -92,7 +188,7 @@ pub async fn ingest(args: IngestArgs) -> anyhow::Result<IndexingStatistics> {
     let statistics = start_statistics_reporting_loop(indexing_pipeline_handle, false).await?;
 
     debug!("wait for merges to complete");
-    wait_for_merges(merge_pipeline_handle).await?;
+    finish_merges(merge_pipeline_handle).await?;
 
     debug!("indexing completed, tearing down actors");
     // TODO: is it really necessary to terminate the indexing service?
@@ -105,16 +201,16 @@ pub async fn ingest(args: IngestArgs) -> anyhow::Result<IndexingStatistics> {
     universe.quit().await;
     debug!("universe.quit() awaited");
 
-    if args.clear_cache {
+    if clear_cache {
         info!("clearing local cache directory");
         clear_cache_directory(&config.data_dir_path).await?;
         info!("local cache directory cleared");
     }
-
-    if statistics.num_invalid_docs > 0 {
-        bail!("Failed to ingest {} documents", statistics.num_invalid_docs)
-    }
-    Ok(statistics)
+    Ok(IndexingRun {
+        statistics,
+        metastore,
+        index_uid,
+    })
 }
 
 #[cfg(all(test, feature = "s3-localstack-tests"))]
@@ -14,9 +14,13 @@
 
 use std::str::FromStr;
 
+use anyhow::{bail, Context};
 use aws_lambda_events::event::s3::S3Event;
+use aws_lambda_events::event::sqs::SqsEvent;
 use quickwit_common::uri::Uri;
+use quickwit_indexing::source::batch_queue::BatchQueueMessage;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -42,6 +46,69 @@ impl IndexerEvent {
     }
 }
 
+/// Returns true if the event is a batch of messages delivered by an SQS event
+/// source mapping.
+pub fn is_sqs_event(event: &Value) -> bool {
+    event["Records"][0]["eventSource"].as_str() == Some("aws:sqs")
+}
+
+/// A batch of SQS messages carrying S3 notifications.
+#[derive(Debug)]
+pub struct SqsBatch {
+    pub queue_url: String,
+    pub messages: Vec<BatchQueueMessage>,
+}
+
+impl SqsBatch {
+    pub fn try_from_event(event: SqsEvent) -> anyhow::Result<Self> {
+        let queue_arn = event
+            .records
+            .first()
+            .and_then(|record| record.event_source_arn.as_deref())
+            .context("SQS event without records")?;
+        let queue_url = queue_url_from_arn(queue_arn)?;
+        let mut messages = Vec::with_capacity(event.records.len());
+
+        for record in event.records {
+            if record.event_source_arn.as_deref() != Some(queue_arn) {
+                bail!("SQS event with records from multiple queues");
+            }
+            let message_id = record.message_id.context("SQS record without messageId")?;
+            let delivery_attempts = record
+                .attributes
+                .get("ApproximateReceiveCount")
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(1);
+            messages.push(BatchQueueMessage {
+                message_id,
+                payload: record.body.unwrap_or_default(),
+                delivery_attempts,
+            });
+        }
+        Ok(Self {
+            queue_url,
+            messages,
+        })
+    }
+}
+
+/// Converts an SQS queue ARN (`arn:aws:sqs:{region}:{account}:{name}`) to the
+/// queue URL.
+fn queue_url_from_arn(queue_arn: &str) -> anyhow::Result<String> {
+    let arn_parts: Vec<&str> = queue_arn.split(':').collect();
+    let ["arn", partition, "sqs", region, account_id, queue_name] = arn_parts[..] else {
+        bail!("invalid SQS queue ARN `{queue_arn}`");
+    };
+    let domain = if partition == "aws-cn" {
+        "amazonaws.com.cn"
+    } else {
+        "amazonaws.com"
+    };
+    Ok(format!(
+        "https://sqs.{region}.{domain}/{account_id}/{queue_name}"
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -60,6 +127,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_queue_url_from_arn() {
+        assert_eq!(
+            queue_url_from_arn("arn:aws:sqs:us-east-2:123456789012:my-queue").unwrap(),
+            "https://sqs.us-east-2.amazonaws.com/123456789012/my-queue"
+        );
+        assert_eq!(
+            queue_url_from_arn("arn:aws-cn:sqs:cn-north-1:123456789012:my-queue").unwrap(),
+            "https://sqs.cn-north-1.amazonaws.com.cn/123456789012/my-queue"
+        );
+        queue_url_from_arn("arn:aws:s3:::my-bucket").unwrap_err();
+    }
+
+    #[test]
+    fn test_sqs_batch_from_event() {
+        let sqs_event = json!({
+          "Records": [
+            {
+              "messageId": "059f36b4-87a3-44ab-83d2-661975830a7d",
+              "receiptHandle": "AQEBwJnKyrHigUMZj6rYigCgxlaS3SLy0a",
+              "body": "{\"Records\":[]}",
+              "attributes": {
+                "ApproximateReceiveCount": "2",
+                "SentTimestamp": "1545082649183",
+                "SenderId": "AIDAIENQZJOLO23YVJ4VO",
+                "ApproximateFirstReceiveTimestamp": "1545082649185"
+              },
+              "messageAttributes": {},
+              "md5OfBody": "e4e68fb7bd0e697a0ae8f1bb342846b3",
+              "eventSource": "aws:sqs",
+              "eventSourceARN": "arn:aws:sqs:us-east-2:123456789012:my-queue",
+              "awsRegion": "us-east-2"
+            },
+            {
+              "messageId": "2e1424d4-f796-459a-8184-9c92662be6da",
+              "receiptHandle": "AQEBzWwaftRI0KuVm4tP+/7q1rGgNqicHq",
+              "body": "{\"Records\":[]}",
+              "attributes": {
+                "ApproximateReceiveCount": "1",
+                "SentTimestamp": "1545082650636",
+                "SenderId": "AIDAIENQZJOLO23YVJ4VO",
+                "ApproximateFirstReceiveTimestamp": "1545082650649"
+              },
+              "messageAttributes": {},
+              "md5OfBody": "e4e68fb7bd0e697a0ae8f1bb342846b3",
+              "eventSource": "aws:sqs",
+              "eventSourceARN": "arn:aws:sqs:us-east-2:123456789012:my-queue",
+              "awsRegion": "us-east-2"
+            }
+          ]
+        });
+        assert!(is_sqs_event(&sqs_event));
+        let sqs_event: SqsEvent = serde_json::from_value(sqs_event).unwrap();
+        let sqs_batch = SqsBatch::try_from_event(sqs_event).unwrap();
+        assert_eq!(
+            sqs_batch.queue_url,
+            "https://sqs.us-east-2.amazonaws.com/123456789012/my-queue"
+        );
+        assert_eq!(sqs_batch.messages.len(), 2);
+        assert_eq!(
+            sqs_batch.messages[0].message_id,
+            "059f36b4-87a3-44ab-83d2-661975830a7d"
+        );
+        assert_eq!(sqs_batch.messages[0].delivery_attempts, 2);
+        assert_eq!(sqs_batch.messages[1].payload, r#"{"Records":[]}"#);
+    }
+
     #[test]
     fn test_s3_event_uri() {
         let s3_event = json!({
@@ -100,6 +234,7 @@ mod tests {
             }
           ]
         });
+        assert!(!is_sqs_event(&s3_event));
         let s3_event: IndexerEvent = serde_json::from_value(s3_event).unwrap();
         assert_eq!(
             s3_event.uri().unwrap(),
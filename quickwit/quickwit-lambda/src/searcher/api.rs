@@ -44,7 +44,12 @@ async fn create_local_search_service(
     let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
     let cluster_client = ClusterClient::new(search_job_placer);
     // TODO configure split cache
-    let searcher_context = Arc::new(SearcherContext::new(searcher_config, None));
+    let searcher_context = Arc::new(SearcherContext::new(
+        searcher_config,
+        None,
+        None,
+        storage_resolver.clone(),
+    ));
     let search_service = Arc::new(SearchServiceImpl::new(
         metastore,
         storage_resolver,
@@ -116,7 +121,8 @@ pub async fn setup_searcher_api(
         HashSet::from_iter([QuickwitService::Searcher.as_str().to_string()]),
         HashSet::from_iter([QuickwitFeature::AwsLambda]),
     );
-    let _telemetry_handle_opt = quickwit_telemetry::start_telemetry_loop(telemetry_info);
+    let _telemetry_handle_opt =
+        quickwit_telemetry::start_telemetry_loop(telemetry_info, Vec::new());
 
     let search_service = create_local_search_service(
         node_config.searcher_config,
@@ -0,0 +1,106 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use lambda_runtime::{Error, LambdaEvent};
+use quickwit_search::{
+    multi_leaf_search, LeafSearchInvocation, LeafSearchInvocationResponse, SearcherContext,
+};
+use quickwit_storage::StorageResolver;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+use tracing::{debug_span, error, info, info_span, warn, Instrument};
+
+use crate::logger;
+use crate::searcher::environment::CONFIGURATION_TEMPLATE;
+use crate::utils::{load_node_config_and_storage_resolver, LambdaContainerContext};
+
+/// The searcher context is kept across the invocations served by the same container so that
+/// warm containers benefit from the split footer and fast field caches.
+static LEAF_SEARCHER: OnceCell<(Arc<SearcherContext>, StorageResolver)> = OnceCell::const_new();
+
+async fn load_leaf_searcher() -> anyhow::Result<(Arc<SearcherContext>, StorageResolver)> {
+    let (node_config, storage_resolver) =
+        load_node_config_and_storage_resolver(CONFIGURATION_TEMPLATE).await?;
+    let searcher_context = Arc::new(SearcherContext::new(
+        node_config.searcher_config,
+        None,
+        None,
+        storage_resolver.clone(),
+    ));
+    Ok((searcher_context, storage_resolver))
+}
+
+async fn leaf_searcher_handler(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    let container_ctx = LambdaContainerContext::load();
+    let invocation = serde_json::from_value::<LeafSearchInvocation>(event.payload)?;
+    let leaf_search_request = invocation.into_leaf_search_request()?;
+    let num_splits: usize = leaf_search_request
+        .leaf_requests
+        .iter()
+        .map(|leaf_request| leaf_request.split_offsets.len())
+        .sum();
+    let (searcher_context, storage_resolver) =
+        LEAF_SEARCHER.get_or_try_init(load_leaf_searcher).await?;
+
+    let leaf_search_res = multi_leaf_search(
+        searcher_context.clone(),
+        leaf_search_request,
+        storage_resolver,
+    )
+    .instrument(debug_span!(
+        "leaf_search",
+        num_splits,
+        cold = container_ctx.cold,
+        container_id = container_ctx.container_id,
+    ))
+    .await;
+
+    match leaf_search_res {
+        Ok(leaf_search_response) => {
+            info!(
+                num_hits = leaf_search_response.num_hits,
+                num_failed_splits = leaf_search_response.failed_splits.len(),
+                "Leaf search succeeded"
+            );
+            let invocation_response =
+                LeafSearchInvocationResponse::from_leaf_search_response(&leaf_search_response);
+            if let Some(response_num_bytes) = invocation_response.oversized_response_num_bytes() {
+                warn!(
+                    response_num_bytes,
+                    num_splits, "Leaf search response exceeds the Lambda payload limit"
+                );
+            }
+            Ok(serde_json::to_value(invocation_response)?)
+        }
+        Err(e) => {
+            error!(err=?e, "Leaf search failed");
+            Err(anyhow::anyhow!("Leaf search failed: {e}").into())
+        }
+    }
+}
+
+/// Handles the leaf search requests that searchers fan out to Lambda invocations.
+pub async fn leaf_search_handler(event: LambdaEvent<Value>) -> Result<Value, Error> {
+    let request_id = event.context.request_id.clone();
+    let response = leaf_searcher_handler(event)
+        .instrument(info_span!("leaf_search_handler", request_id))
+        .await;
+    if let Err(e) = &response {
+        error!(err=?e, "Handler failed");
+    }
+    logger::flush_tracer();
+    response
+}
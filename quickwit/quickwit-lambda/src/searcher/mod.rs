@@ -14,6 +14,8 @@
 
 mod api;
 mod environment;
+mod leaf;
 pub mod warp_lambda;
 
 pub use api::setup_searcher_api;
+pub use leaf::leaf_search_handler;
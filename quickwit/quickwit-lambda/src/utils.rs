@@ -14,22 +14,19 @@
 
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering::SeqCst;
+use std::sync::Arc;
 
 use anyhow::Context;
 use quickwit_config::{ConfigFormat, NodeConfig};
 use quickwit_metastore::MetastoreResolver;
 use quickwit_proto::metastore::MetastoreServiceClient;
-use quickwit_storage::StorageResolver;
+use quickwit_storage::{LocalKeyProvider, StorageResolver};
 use tracing::info;
 
 pub(crate) async fn load_node_config(
     config_template: &str,
 ) -> anyhow::Result<(NodeConfig, StorageResolver, MetastoreServiceClient)> {
-    let config = NodeConfig::load(ConfigFormat::Yaml, config_template.as_bytes())
-        .await
-        .with_context(|| format!("Failed to parse node config `{config_template}`."))?;
-    info!(config=?config, "loaded node config");
-    let storage_resolver = StorageResolver::configured(&config.storage_configs);
+    let (config, storage_resolver) = load_node_config_and_storage_resolver(config_template).await?;
     let metastore_resolver =
         MetastoreResolver::configured(storage_resolver.clone(), &config.metastore_configs);
     let metastore: MetastoreServiceClient =
@@ -37,6 +34,24 @@ pub(crate) async fn load_node_config(
     Ok((config, storage_resolver, metastore))
 }
 
+/// Loads the node config without resolving the metastore, for the functions that only access
+/// the storage.
+pub(crate) async fn load_node_config_and_storage_resolver(
+    config_template: &str,
+) -> anyhow::Result<(NodeConfig, StorageResolver)> {
+    let config = NodeConfig::load(ConfigFormat::Yaml, config_template.as_bytes())
+        .await
+        .with_context(|| format!("Failed to parse node config `{config_template}`."))?;
+    info!(config=?config, "loaded node config");
+    let mut storage_resolver = StorageResolver::configured(&config.storage_configs);
+
+    if let Some(keyfile_path) = &config.encryption_config.keyfile_path_opt {
+        let key_provider = LocalKeyProvider::from_keyfile(keyfile_path)?;
+        storage_resolver = storage_resolver.with_key_provider(Arc::new(key_provider));
+    }
+    Ok((config, storage_resolver))
+}
+
 static CONTAINER_ID: AtomicU32 = AtomicU32::new(0);
 
 pub struct LambdaContainerContext {
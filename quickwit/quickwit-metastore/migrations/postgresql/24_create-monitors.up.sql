@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS monitors (
+    monitor_id VARCHAR(255) NOT NULL,
+    monitor_config_json TEXT NOT NULL,
+    monitor_state_json TEXT NOT NULL,
+    create_timestamp TIMESTAMP NOT NULL DEFAULT (CURRENT_TIMESTAMP AT TIME ZONE 'UTC'),
+    PRIMARY KEY (monitor_id)
+);
@@ -43,11 +43,14 @@ pub(crate) use metastore::index_metadata::serialize::{IndexMetadataV0_8, Version
 #[cfg(feature = "postgres")]
 pub use metastore::postgres::PostgresqlMetastore;
 pub use metastore::{
-    file_backed, AddSourceRequestExt, CreateIndexRequestExt, CreateIndexResponseExt, IndexMetadata,
-    IndexMetadataResponseExt, IndexesMetadataResponseExt, ListIndexesMetadataResponseExt,
+    file_backed, AddSourceRequestExt, CreateIndexRequestExt, CreateIndexResponseExt,
+    CreateMonitorRequestExt, GetMonitorResponseExt, IndexMetadata, IndexMetadataResponseExt,
+    IndexesMetadataResponseExt, ListIndexesMetadataResponseExt, ListMonitorsResponseExt,
     ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt, MetastoreServiceExt,
-    MetastoreServiceStreamSplitsExt, PublishSplitsRequestExt, StageSplitsRequestExt,
-    UpdateIndexRequestExt, UpdateSourceRequestExt,
+    MetastoreServiceStreamSplitsExt, MonitorEvaluation, MonitorMetadata, MonitorState,
+    MonitorStatus, PendingMonitorAlert, PublishSplitsRequestExt, StageSplitsRequestExt,
+    UpdateIndexRequestExt, UpdateMonitorStateRequestExt, UpdateSourceRequestExt,
+    MAX_MONITOR_HISTORY_LEN, MAX_PENDING_MONITOR_ALERTS,
 };
 pub use metastore_factory::{MetastoreFactory, UnsupportedMetastore};
 pub use metastore_resolver::MetastoreResolver;
@@ -59,6 +62,10 @@ pub(crate) use split_metadata_version::{SplitMetadataV0_8, VersionedSplitMetadat
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
     IndexMetadataV0_8,
+    MonitorEvaluation,
+    MonitorState,
+    MonitorStatus,
+    PendingMonitorAlert,
     Split,
     SplitMetadataV0_8,
     SplitState,
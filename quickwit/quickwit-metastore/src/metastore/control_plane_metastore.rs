@@ -19,20 +19,21 @@ use quickwit_common::uri::Uri;
 use quickwit_proto::control_plane::{ControlPlaneService, ControlPlaneServiceClient};
 use quickwit_proto::metastore::{
     AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexRequest,
-    DeleteIndexTemplatesRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
-    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
+    CreateIndexResponse, CreateIndexTemplateRequest, CreateMonitorRequest, DeleteIndexRequest,
+    DeleteIndexTemplatesRequest, DeleteMonitorsRequest, DeleteQuery, DeleteShardsRequest,
+    DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
     FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse, GetIndexTemplateRequest,
-    GetIndexTemplateResponse, IndexMetadataRequest, IndexMetadataResponse, IndexesMetadataRequest,
-    IndexesMetadataResponse, LastDeleteOpstampRequest, LastDeleteOpstampResponse,
-    ListDeleteTasksRequest, ListDeleteTasksResponse, ListIndexTemplatesRequest,
-    ListIndexTemplatesResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
-    ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
-    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreResult, MetastoreService,
-    MetastoreServiceClient, MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse,
-    PruneShardsRequest, PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest,
-    ToggleSourceRequest, UpdateIndexRequest, UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest,
-    UpdateSplitsDeleteOpstampResponse,
+    GetIndexTemplateResponse, GetMonitorRequest, GetMonitorResponse, IndexMetadataRequest,
+    IndexMetadataResponse, IndexesMetadataRequest, IndexesMetadataResponse,
+    LastDeleteOpstampRequest, LastDeleteOpstampResponse, ListDeleteTasksRequest,
+    ListDeleteTasksResponse, ListIndexTemplatesRequest, ListIndexTemplatesResponse,
+    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListMonitorsRequest,
+    ListMonitorsResponse, ListShardsRequest, ListShardsResponse, ListSplitsRequest,
+    ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreResult,
+    MetastoreService, MetastoreServiceClient, MetastoreServiceStream, OpenShardsRequest,
+    OpenShardsResponse, PruneShardsRequest, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest, UpdateMonitorStateRequest,
+    UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 
 /// A [`MetastoreService`] implementation that proxies some requests to the control plane so it can
@@ -274,4 +275,38 @@ impl MetastoreService for ControlPlaneMetastore {
     ) -> MetastoreResult<EmptyResponse> {
         self.metastore.delete_index_templates(request).await
     }
+
+    // Monitor API
+
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.create_monitor(request).await
+    }
+
+    async fn get_monitor(&self, request: GetMonitorRequest) -> MetastoreResult<GetMonitorResponse> {
+        self.metastore.get_monitor(request).await
+    }
+
+    async fn list_monitors(
+        &self,
+        request: ListMonitorsRequest,
+    ) -> MetastoreResult<ListMonitorsResponse> {
+        self.metastore.list_monitors(request).await
+    }
+
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.update_monitor_state(request).await
+    }
+
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.delete_monitors(request).await
+    }
 }
@@ -26,7 +26,8 @@ use std::ops::Bound;
 use itertools::Itertools;
 use quickwit_common::pretty::PrettySample;
 use quickwit_config::{
-    DocMapping, IndexingSettings, RetentionPolicy, SearchSettings, SourceConfig,
+    DocMapping, IndexingSettings, RetentionPolicy, RollupPolicy, SearchSettings, SourceConfig,
+    TieringPolicy,
 };
 use quickwit_proto::metastore::{
     AcquireShardsRequest, AcquireShardsResponse, DeleteQuery, DeleteShardsRequest,
@@ -219,6 +220,16 @@ impl FileBackedIndex {
         self.metadata.set_retention_policy(retention_policy_opt)
     }
 
+    /// Replaces the rollup policy in the index config, returning whether a mutation occurred.
+    pub fn set_rollup_policy(&mut self, rollup_policy_opt: Option<RollupPolicy>) -> bool {
+        self.metadata.set_rollup_policy(rollup_policy_opt)
+    }
+
+    /// Replaces the tiering policy in the index config, returning whether a mutation occurred.
+    pub fn set_tiering_policy(&mut self, tiering_policy_opt: Option<TieringPolicy>) -> bool {
+        self.metadata.set_tiering_policy(tiering_policy_opt)
+    }
+
     /// Replaces the search settings in the index config, returning whether a mutation occurred.
     pub fn set_search_settings(&mut self, search_settings: SearchSettings) -> bool {
         self.metadata.set_search_settings(search_settings)
@@ -746,6 +757,12 @@ fn split_query_predicate(split: &&Split, query: &ListSplitsQuery) -> bool {
         }
     }
 
+    if let Some(split_ids) = &query.split_ids {
+        if !split_ids.contains(&split.split_metadata.split_id) {
+            return false;
+        }
+    }
+
     if let Some((index_uid, split_id)) = &query.after_split {
         if *index_uid > split.split_metadata.index_uid {
             return false;
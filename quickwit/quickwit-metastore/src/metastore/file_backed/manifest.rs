@@ -17,13 +17,15 @@ use std::path::Path;
 
 use itertools::Itertools;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexTemplate, IndexTemplateId};
+use quickwit_config::{IndexTemplate, IndexTemplateId, MonitorId};
 use quickwit_proto::metastore::{serde_utils, MetastoreError, MetastoreResult};
 use quickwit_proto::types::{DocMappingUid, IndexId};
 use quickwit_storage::{OwnedBytes, Storage, StorageError, StorageErrorKind, StorageResult};
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
+use crate::MonitorMetadata;
+
 pub(super) const MANIFEST_FILE_NAME: &str = "manifest.json";
 
 // The legacy manifest file was deprecated in 0.8.0, we can drop support for it in 0.10.0 or 0.11.0.
@@ -40,6 +42,7 @@ impl LegacyManifest {
         Manifest {
             indexes: self.indexes,
             templates: HashMap::new(),
+            monitors: HashMap::new(),
         }
     }
 }
@@ -64,6 +67,8 @@ pub(crate) struct Manifest {
     // The templates are serialized as a sorted `Vec<IndexTemplate>` so the btree map is
     // unnecessary here and we can pass the hash map as is to the `MetastoreState`
     pub templates: HashMap<IndexTemplateId, IndexTemplate>,
+    // Same as above, the monitors are serialized as a sorted `Vec<MonitorMetadata>`.
+    pub monitors: HashMap<MonitorId, MonitorMetadata>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -103,6 +108,8 @@ impl From<VersionedManifest> for Manifest {
 struct ManifestV0_8 {
     indexes: BTreeMap<IndexId, IndexStatus>,
     templates: Vec<IndexTemplate>,
+    #[serde(default)]
+    monitors: Vec<MonitorMetadata>,
 }
 
 impl From<Manifest> for ManifestV0_8 {
@@ -112,9 +119,15 @@ impl From<Manifest> for ManifestV0_8 {
             .into_values()
             .sorted_unstable_by(|left, right| left.template_id.cmp(&right.template_id))
             .collect();
+        let monitors = manifest
+            .monitors
+            .into_values()
+            .sorted_unstable_by(|left, right| left.monitor_id().cmp(right.monitor_id()))
+            .collect();
         ManifestV0_8 {
             indexes: manifest.indexes,
             templates,
+            monitors,
         }
     }
 }
@@ -127,7 +140,16 @@ impl From<ManifestV0_8> for Manifest {
             .into_iter()
             .map(|template| (template.template_id.clone(), template))
             .collect();
-        Manifest { indexes, templates }
+        let monitors = manifest
+            .monitors
+            .into_iter()
+            .map(|monitor| (monitor.monitor_id().to_string(), monitor))
+            .collect();
+        Manifest {
+            indexes,
+            templates,
+            monitors,
+        }
     }
 }
 
@@ -144,12 +166,22 @@ impl quickwit_config::TestableForRegression for Manifest {
             "test-template-1".to_string(),
             IndexTemplate::sample_for_regression(),
         );
-        Manifest { indexes, templates }
+        let mut monitors = HashMap::new();
+        monitors.insert(
+            "test-monitor".to_string(),
+            MonitorMetadata::sample_for_regression(),
+        );
+        Manifest {
+            indexes,
+            templates,
+            monitors,
+        }
     }
 
     fn assert_equality(&self, other: &Self) {
         assert_eq!(self.indexes, other.indexes);
         assert_eq!(self.templates, other.templates);
+        assert_eq!(self.monitors, other.monitors);
     }
 }
 
@@ -244,6 +276,7 @@ fn into_metastore_error(
 
 #[cfg(test)]
 mod tests {
+    use quickwit_config::MonitorConfig;
     use serde_json::json;
 
     use super::*;
@@ -288,6 +321,7 @@ mod tests {
 
         assert_eq!(manifest.indexes.len(), 3);
         assert_eq!(manifest.templates.len(), 0);
+        assert_eq!(manifest.monitors.len(), 0);
 
         assert_eq!(
             manifest.indexes.get("test-index-1").unwrap(),
@@ -320,7 +354,15 @@ mod tests {
                 IndexTemplate::for_test("test-template-2", &["test-index-bar*"], 200),
             ),
         ]);
-        let manifest = Manifest { indexes, templates };
+        let monitors = HashMap::from_iter([(
+            "test-monitor".to_string(),
+            MonitorMetadata::new(MonitorConfig::for_test("test-monitor", "test-index-foo")),
+        )]);
+        let manifest = Manifest {
+            indexes,
+            templates,
+            monitors,
+        };
         let manifest_json = serde_json::to_string_pretty(&manifest).unwrap();
         let manifest_deserialized: Manifest = serde_json::from_str(&manifest_json).unwrap();
         assert_eq!(manifest, manifest_deserialized);
@@ -348,6 +390,10 @@ mod tests {
             "test-template".to_string(),
             IndexTemplate::for_test("test-template", &["test-index-*"], 100),
         );
+        manifest.monitors.insert(
+            "test-monitor".to_string(),
+            MonitorMetadata::new(MonitorConfig::for_test("test-monitor", "test-index")),
+        );
 
         save_manifest(&*storage, &manifest).await.unwrap();
 
@@ -371,6 +417,11 @@ mod tests {
         assert_eq!(template.template_id, "test-template");
         assert_eq!(template.index_id_patterns, ["test-index-*"]);
         assert_eq!(template.priority, 100);
+
+        assert_eq!(manifest.monitors.len(), 1);
+
+        let monitor = manifest.monitors.get("test-monitor").unwrap();
+        assert_eq!(monitor.monitor_config.index_id, "test-index");
     }
 
     #[tokio::test]
@@ -396,6 +447,7 @@ mod tests {
         let manifest = load_or_create_manifest(&*storage).await.unwrap();
         assert_eq!(manifest.indexes.len(), 3);
         assert_eq!(manifest.templates.len(), 0);
+        assert_eq!(manifest.monitors.len(), 0);
 
         assert_eq!(
             manifest.indexes.get("test-index-1").unwrap(),
@@ -41,20 +41,21 @@ use quickwit_common::ServiceStream;
 use quickwit_config::IndexTemplate;
 use quickwit_proto::metastore::{
     serde_utils, AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexRequest,
-    DeleteIndexTemplatesRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
-    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind,
-    FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse, GetIndexTemplateRequest,
-    GetIndexTemplateResponse, IndexMetadataFailure, IndexMetadataFailureReason,
-    IndexMetadataRequest, IndexMetadataResponse, IndexTemplateMatch, IndexesMetadataRequest,
-    IndexesMetadataResponse, LastDeleteOpstampRequest, LastDeleteOpstampResponse,
-    ListDeleteTasksRequest, ListDeleteTasksResponse, ListIndexTemplatesRequest,
-    ListIndexTemplatesResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
-    ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
-    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
-    MetastoreService, MetastoreServiceStream, OpenShardSubrequest, OpenShardsRequest,
-    OpenShardsResponse, PruneShardsRequest, PublishSplitsRequest, ResetSourceCheckpointRequest,
-    StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest, UpdateSourceRequest,
+    CreateIndexResponse, CreateIndexTemplateRequest, CreateMonitorRequest, DeleteIndexRequest,
+    DeleteIndexTemplatesRequest, DeleteMonitorsRequest, DeleteQuery, DeleteShardsRequest,
+    DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
+    EntityKind, FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse,
+    GetIndexTemplateRequest, GetIndexTemplateResponse, GetMonitorRequest, GetMonitorResponse,
+    IndexMetadataFailure, IndexMetadataFailureReason, IndexMetadataRequest, IndexMetadataResponse,
+    IndexTemplateMatch, IndexesMetadataRequest, IndexesMetadataResponse, LastDeleteOpstampRequest,
+    LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
+    ListIndexTemplatesRequest, ListIndexTemplatesResponse, ListIndexesMetadataRequest,
+    ListIndexesMetadataResponse, ListMonitorsRequest, ListMonitorsResponse, ListShardsRequest,
+    ListShardsResponse, ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest,
+    MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult, MetastoreService,
+    MetastoreServiceStream, OpenShardSubrequest, OpenShardsRequest, OpenShardsResponse,
+    PruneShardsRequest, PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest,
+    ToggleSourceRequest, UpdateIndexRequest, UpdateMonitorStateRequest, UpdateSourceRequest,
     UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::{IndexId, IndexUid};
@@ -71,13 +72,16 @@ use self::manifest::{load_or_create_manifest, save_manifest, MANIFEST_FILE_NAME}
 use self::state::MetastoreState;
 use self::store_operations::{delete_index, index_exists, load_index, put_index};
 use super::{
-    AddSourceRequestExt, CreateIndexRequestExt, IndexMetadataResponseExt,
-    IndexesMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsRequestExt,
-    ListSplitsResponseExt, PublishSplitsRequestExt, StageSplitsRequestExt, UpdateIndexRequestExt,
+    AddSourceRequestExt, CreateIndexRequestExt, CreateMonitorRequestExt, GetMonitorResponseExt,
+    IndexMetadataResponseExt, IndexesMetadataResponseExt, ListIndexesMetadataResponseExt,
+    ListMonitorsResponseExt, ListSplitsRequestExt, ListSplitsResponseExt, PublishSplitsRequestExt,
+    StageSplitsRequestExt, UpdateIndexRequestExt, UpdateMonitorStateRequestExt,
     UpdateSourceRequestExt, STREAM_SPLITS_CHUNK_SIZE,
 };
 use crate::checkpoint::IndexCheckpointDelta;
-use crate::{IndexMetadata, ListSplitsQuery, MetastoreServiceExt, Split, SplitState};
+use crate::{
+    IndexMetadata, ListSplitsQuery, MetastoreServiceExt, MonitorMetadata, Split, SplitState,
+};
 
 /// Status of an index tracked by the metastore.
 pub(crate) enum LazyIndexStatus {
@@ -554,6 +558,8 @@ impl MetastoreService for FileBackedMetastore {
         request: UpdateIndexRequest,
     ) -> MetastoreResult<IndexMetadataResponse> {
         let retention_policy_opt = request.deserialize_retention_policy()?;
+        let rollup_policy_opt = request.deserialize_rollup_policy()?;
+        let tiering_policy_opt = request.deserialize_tiering_policy()?;
         let search_settings = request.deserialize_search_settings()?;
         let indexing_settings = request.deserialize_indexing_settings()?;
         let doc_mapping = request.deserialize_doc_mapping()?;
@@ -562,6 +568,8 @@ impl MetastoreService for FileBackedMetastore {
         let index_metadata = self
             .mutate(index_uid, |index| {
                 let mut mutation_occurred = index.set_retention_policy(retention_policy_opt);
+                mutation_occurred |= index.set_rollup_policy(rollup_policy_opt);
+                mutation_occurred |= index.set_tiering_policy(tiering_policy_opt);
                 mutation_occurred |= index.set_search_settings(search_settings);
                 mutation_occurred |= index.set_indexing_settings(indexing_settings);
                 mutation_occurred |= index.set_doc_mapping(doc_mapping);
@@ -1194,6 +1202,130 @@ impl MetastoreService for FileBackedMetastore {
         }
         Ok(EmptyResponse {})
     }
+
+    // Monitor API
+
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let monitor_config = request.deserialize_monitor_config()?;
+        monitor_config
+            .validate()
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: error.to_string(),
+            })?;
+        let monitor_id = monitor_config.monitor_id.clone();
+
+        let mut state_wlock_guard = self.state.write().await;
+
+        let evicted_monitor_opt = match state_wlock_guard.monitors.entry(monitor_id.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(MonitorMetadata::new(monitor_config));
+                None
+            }
+            Entry::Occupied(mut entry) if request.overwrite => {
+                // Overwriting a monitor preserves its evaluation state.
+                let mut monitor_metadata = entry.get().clone();
+                monitor_metadata.monitor_config = monitor_config;
+                let evicted_monitor = entry.insert(monitor_metadata);
+                Some(evicted_monitor)
+            }
+            Entry::Occupied(_) => {
+                return Err(MetastoreError::AlreadyExists(EntityKind::Monitor {
+                    monitor_id,
+                }));
+            }
+        };
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            if let Some(evicted_monitor) = evicted_monitor_opt {
+                state_wlock_guard
+                    .monitors
+                    .insert(monitor_id, evicted_monitor);
+            } else {
+                state_wlock_guard.monitors.remove(&monitor_id);
+            }
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn get_monitor(&self, request: GetMonitorRequest) -> MetastoreResult<GetMonitorResponse> {
+        let inner_rlock_guard = self.state.read().await;
+        let monitor_metadata = inner_rlock_guard.monitors.get(&request.monitor_id).ok_or({
+            MetastoreError::NotFound(EntityKind::Monitor {
+                monitor_id: request.monitor_id,
+            })
+        })?;
+        GetMonitorResponse::try_from_monitor_metadata(monitor_metadata)
+    }
+
+    async fn list_monitors(
+        &self,
+        _request: ListMonitorsRequest,
+    ) -> MetastoreResult<ListMonitorsResponse> {
+        let inner_rlock_guard = self.state.read().await;
+        ListMonitorsResponse::try_from_monitors_metadata(inner_rlock_guard.monitors.values())
+    }
+
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let monitor_state = request.deserialize_monitor_state()?;
+        let monitor_id = request.monitor_id;
+
+        let mut state_wlock_guard = self.state.write().await;
+
+        let Some(monitor_metadata) = state_wlock_guard.monitors.get_mut(&monitor_id) else {
+            return Err(MetastoreError::NotFound(EntityKind::Monitor { monitor_id }));
+        };
+        let previous_monitor_state =
+            std::mem::replace(&mut monitor_metadata.monitor_state, monitor_state);
+
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            if let Some(monitor_metadata) = state_wlock_guard.monitors.get_mut(&monitor_id) {
+                monitor_metadata.monitor_state = previous_monitor_state;
+            }
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let mut evicted_monitors = Vec::with_capacity(request.monitor_ids.len());
+        let mut state_wlock_guard = self.state.write().await;
+
+        for monitor_id in &request.monitor_ids {
+            if let Some(evicted_monitor) = state_wlock_guard.monitors.remove(monitor_id) {
+                evicted_monitors.push(evicted_monitor);
+            }
+        }
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            for evicted_monitor in evicted_monitors {
+                state_wlock_guard
+                    .monitors
+                    .insert(evicted_monitor.monitor_id().to_string(), evicted_monitor);
+            }
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
 }
 
 impl MetastoreServiceExt for FileBackedMetastore {}
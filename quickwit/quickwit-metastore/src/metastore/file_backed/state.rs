@@ -16,7 +16,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
-use quickwit_config::{IndexTemplate, IndexTemplateId};
+use quickwit_config::{IndexTemplate, IndexTemplateId, MonitorId};
 use quickwit_proto::metastore::MetastoreResult;
 use quickwit_proto::types::IndexId;
 use quickwit_storage::Storage;
@@ -25,12 +25,14 @@ use super::index_template_matcher::IndexTemplateMatcher;
 use super::lazy_file_backed_index::LazyFileBackedIndex;
 use super::manifest::{IndexStatus, Manifest};
 use super::LazyIndexStatus;
+use crate::MonitorMetadata;
 
 #[derive(Default)]
 pub(super) struct MetastoreState {
     pub indexes: HashMap<IndexId, LazyIndexStatus>,
     pub templates: HashMap<IndexTemplateId, IndexTemplate>,
     pub template_matcher: IndexTemplateMatcher,
+    pub monitors: HashMap<MonitorId, MonitorMetadata>,
 }
 
 impl MetastoreState {
@@ -64,6 +66,7 @@ impl MetastoreState {
             indexes,
             templates: manifest.templates,
             template_matcher,
+            monitors: manifest.monitors,
         };
         Ok(state)
     }
@@ -82,6 +85,11 @@ impl MetastoreState {
             })
             .collect();
         let templates = self.templates.clone();
-        Manifest { indexes, templates }
+        let monitors = self.monitors.clone();
+        Manifest {
+            indexes,
+            templates,
+            monitors,
+        }
     }
 }
@@ -19,7 +19,8 @@ use std::collections::HashMap;
 
 use quickwit_common::uri::Uri;
 use quickwit_config::{
-    DocMapping, IndexConfig, IndexingSettings, RetentionPolicy, SearchSettings, SourceConfig,
+    DocMapping, IndexConfig, IndexingSettings, RetentionPolicy, RollupPolicy, SearchSettings,
+    SourceConfig, TieringPolicy,
 };
 use quickwit_proto::metastore::{EntityKind, MetastoreError, MetastoreResult};
 use quickwit_proto::types::{IndexUid, SourceId};
@@ -105,6 +106,26 @@ impl IndexMetadata {
         }
     }
 
+    /// Replaces or removes the current rollup policy, returning whether a mutation occurred.
+    pub fn set_rollup_policy(&mut self, rollup_policy_opt: Option<RollupPolicy>) -> bool {
+        if self.index_config.rollup_policy_opt != rollup_policy_opt {
+            self.index_config.rollup_policy_opt = rollup_policy_opt;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replaces or removes the current tiering policy, returning whether a mutation occurred.
+    pub fn set_tiering_policy(&mut self, tiering_policy_opt: Option<TieringPolicy>) -> bool {
+        if self.index_config.tiering_policy_opt != tiering_policy_opt {
+            self.index_config.tiering_policy_opt = tiering_policy_opt;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Replaces the current search settings, returning whether a mutation occurred.
     pub fn set_search_settings(&mut self, search_settings: SearchSettings) -> bool {
         if self.index_config.search_settings != search_settings {
@@ -14,6 +14,7 @@
 
 pub mod file_backed;
 pub(crate) mod index_metadata;
+pub(crate) mod monitor_metadata;
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
@@ -27,18 +28,23 @@ use bytes::Bytes;
 use futures::TryStreamExt;
 pub use index_metadata::IndexMetadata;
 use itertools::Itertools;
+pub use monitor_metadata::{
+    MonitorEvaluation, MonitorMetadata, MonitorState, MonitorStatus, PendingMonitorAlert,
+    MAX_MONITOR_HISTORY_LEN, MAX_PENDING_MONITOR_ALERTS,
+};
 use quickwit_common::thread_pool::run_cpu_intensive;
 use quickwit_config::{
-    DocMapping, FileSourceParams, IndexConfig, IndexingSettings, RetentionPolicy, SearchSettings,
-    SourceConfig, SourceParams,
+    DocMapping, FileSourceParams, IndexConfig, IndexingSettings, MonitorConfig, RetentionPolicy,
+    RollupPolicy, SearchSettings, SourceConfig, SourceParams, TieringPolicy,
 };
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore::{
-    serde_utils, AddSourceRequest, CreateIndexRequest, CreateIndexResponse, DeleteTask,
-    IndexMetadataFailure, IndexMetadataRequest, IndexMetadataResponse, IndexesMetadataResponse,
-    ListIndexesMetadataResponse, ListSplitsRequest, ListSplitsResponse, MetastoreError,
-    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
-    PublishSplitsRequest, StageSplitsRequest, UpdateIndexRequest, UpdateSourceRequest,
+    serde_utils, AddSourceRequest, CreateIndexRequest, CreateIndexResponse, CreateMonitorRequest,
+    DeleteTask, GetMonitorResponse, IndexMetadataFailure, IndexMetadataRequest,
+    IndexMetadataResponse, IndexesMetadataResponse, ListIndexesMetadataResponse,
+    ListMonitorsResponse, ListSplitsRequest, ListSplitsResponse, MetastoreError, MetastoreResult,
+    MetastoreService, MetastoreServiceClient, MetastoreServiceStream, PublishSplitsRequest,
+    StageSplitsRequest, UpdateIndexRequest, UpdateMonitorStateRequest, UpdateSourceRequest,
 };
 use quickwit_proto::types::{IndexUid, NodeId, SplitId};
 use time::OffsetDateTime;
@@ -187,6 +193,8 @@ pub trait UpdateIndexRequestExt {
         index_uid: impl Into<IndexUid>,
         search_settings: &SearchSettings,
         retention_policy_opt: &Option<RetentionPolicy>,
+        rollup_policy_opt: &Option<RollupPolicy>,
+        tiering_policy_opt: &Option<TieringPolicy>,
         indexing_settings: &IndexingSettings,
         doc_mapping: &DocMapping,
     ) -> MetastoreResult<UpdateIndexRequest>;
@@ -199,6 +207,14 @@ pub trait UpdateIndexRequestExt {
     /// [`RetentionPolicy`] object.
     fn deserialize_retention_policy(&self) -> MetastoreResult<Option<RetentionPolicy>>;
 
+    /// Deserializes the `rollup_policy_json` field of an [`UpdateIndexRequest`] into a
+    /// [`RollupPolicy`] object.
+    fn deserialize_rollup_policy(&self) -> MetastoreResult<Option<RollupPolicy>>;
+
+    /// Deserializes the `tiering_policy_json` field of an [`UpdateIndexRequest`] into a
+    /// [`TieringPolicy`] object.
+    fn deserialize_tiering_policy(&self) -> MetastoreResult<Option<TieringPolicy>>;
+
     /// Deserializes the `indexing_settings_json` field of an [`UpdateIndexRequest`] into a
     /// [`IndexingSettings`] object.
     fn deserialize_indexing_settings(&self) -> MetastoreResult<IndexingSettings>;
@@ -213,6 +229,8 @@ impl UpdateIndexRequestExt for UpdateIndexRequest {
         index_uid: impl Into<IndexUid>,
         search_settings: &SearchSettings,
         retention_policy_opt: &Option<RetentionPolicy>,
+        rollup_policy_opt: &Option<RollupPolicy>,
+        tiering_policy_opt: &Option<TieringPolicy>,
         indexing_settings: &IndexingSettings,
         doc_mapping: &DocMapping,
     ) -> MetastoreResult<UpdateIndexRequest> {
@@ -221,6 +239,14 @@ impl UpdateIndexRequestExt for UpdateIndexRequest {
             .as_ref()
             .map(serde_utils::to_json_str)
             .transpose()?;
+        let rollup_policy_json = rollup_policy_opt
+            .as_ref()
+            .map(serde_utils::to_json_str)
+            .transpose()?;
+        let tiering_policy_json = tiering_policy_opt
+            .as_ref()
+            .map(serde_utils::to_json_str)
+            .transpose()?;
         let indexing_settings_json = serde_utils::to_json_str(indexing_settings)?;
         let doc_mapping_json = serde_utils::to_json_str(doc_mapping)?;
 
@@ -230,6 +256,8 @@ impl UpdateIndexRequestExt for UpdateIndexRequest {
             retention_policy_json,
             indexing_settings_json,
             doc_mapping_json,
+            rollup_policy_json,
+            tiering_policy_json,
         };
         Ok(update_request)
     }
@@ -245,6 +273,20 @@ impl UpdateIndexRequestExt for UpdateIndexRequest {
             .transpose()
     }
 
+    fn deserialize_rollup_policy(&self) -> MetastoreResult<Option<RollupPolicy>> {
+        self.rollup_policy_json
+            .as_ref()
+            .map(|policy| serde_utils::from_json_str(policy))
+            .transpose()
+    }
+
+    fn deserialize_tiering_policy(&self) -> MetastoreResult<Option<TieringPolicy>> {
+        self.tiering_policy_json
+            .as_ref()
+            .map(|policy| serde_utils::from_json_str(policy))
+            .transpose()
+    }
+
     fn deserialize_indexing_settings(&self) -> MetastoreResult<IndexingSettings> {
         serde_utils::from_json_str(&self.indexing_settings_json)
     }
@@ -451,6 +493,130 @@ impl UpdateSourceRequestExt for UpdateSourceRequest {
         serde_utils::from_json_str(&self.source_config_json)
     }
 }
+/// Helper trait to build a [`CreateMonitorRequest`] and deserialize its payload.
+pub trait CreateMonitorRequestExt {
+    /// Creates a new [`CreateMonitorRequest`] from a [`MonitorConfig`].
+    fn try_from_monitor_config(
+        monitor_config: &MonitorConfig,
+        overwrite: bool,
+    ) -> MetastoreResult<CreateMonitorRequest>;
+
+    /// Deserializes the `monitor_config_json` field of a [`CreateMonitorRequest`] into a
+    /// [`MonitorConfig`].
+    fn deserialize_monitor_config(&self) -> MetastoreResult<MonitorConfig>;
+}
+
+impl CreateMonitorRequestExt for CreateMonitorRequest {
+    fn try_from_monitor_config(
+        monitor_config: &MonitorConfig,
+        overwrite: bool,
+    ) -> MetastoreResult<CreateMonitorRequest> {
+        let monitor_config_json = serde_utils::to_json_str(monitor_config)?;
+        let request = Self {
+            monitor_config_json,
+            overwrite,
+        };
+        Ok(request)
+    }
+
+    fn deserialize_monitor_config(&self) -> MetastoreResult<MonitorConfig> {
+        serde_utils::from_json_str(&self.monitor_config_json)
+    }
+}
+
+/// Helper trait to build a [`GetMonitorResponse`] and deserialize its payload.
+pub trait GetMonitorResponseExt {
+    /// Creates a new [`GetMonitorResponse`] from a [`MonitorMetadata`].
+    fn try_from_monitor_metadata(
+        monitor_metadata: &MonitorMetadata,
+    ) -> MetastoreResult<GetMonitorResponse>;
+
+    /// Deserializes the `monitor_metadata_json` field of a [`GetMonitorResponse`] into a
+    /// [`MonitorMetadata`].
+    fn deserialize_monitor_metadata(&self) -> MetastoreResult<MonitorMetadata>;
+}
+
+impl GetMonitorResponseExt for GetMonitorResponse {
+    fn try_from_monitor_metadata(
+        monitor_metadata: &MonitorMetadata,
+    ) -> MetastoreResult<GetMonitorResponse> {
+        let monitor_metadata_json = serde_utils::to_json_str(monitor_metadata)?;
+        let response = Self {
+            monitor_metadata_json,
+        };
+        Ok(response)
+    }
+
+    fn deserialize_monitor_metadata(&self) -> MetastoreResult<MonitorMetadata> {
+        serde_utils::from_json_str(&self.monitor_metadata_json)
+    }
+}
+
+/// Helper trait to build a [`ListMonitorsResponse`] and deserialize its payload.
+pub trait ListMonitorsResponseExt {
+    /// Creates a new [`ListMonitorsResponse`] from a list of [`MonitorMetadata`].
+    fn try_from_monitors_metadata<'a>(
+        monitors_metadata: impl IntoIterator<Item = &'a MonitorMetadata>,
+    ) -> MetastoreResult<ListMonitorsResponse>;
+
+    /// Deserializes the `monitors_metadata_json` field of a [`ListMonitorsResponse`] into a list
+    /// of [`MonitorMetadata`].
+    fn deserialize_monitors_metadata(&self) -> MetastoreResult<Vec<MonitorMetadata>>;
+}
+
+impl ListMonitorsResponseExt for ListMonitorsResponse {
+    fn try_from_monitors_metadata<'a>(
+        monitors_metadata: impl IntoIterator<Item = &'a MonitorMetadata>,
+    ) -> MetastoreResult<ListMonitorsResponse> {
+        let monitors_metadata_json = monitors_metadata
+            .into_iter()
+            .map(serde_utils::to_json_str)
+            .collect::<MetastoreResult<_>>()?;
+        let response = Self {
+            monitors_metadata_json,
+        };
+        Ok(response)
+    }
+
+    fn deserialize_monitors_metadata(&self) -> MetastoreResult<Vec<MonitorMetadata>> {
+        self.monitors_metadata_json
+            .iter()
+            .map(|monitor_metadata_json| serde_utils::from_json_str(monitor_metadata_json))
+            .collect()
+    }
+}
+
+/// Helper trait to build a [`UpdateMonitorStateRequest`] and deserialize its payload.
+pub trait UpdateMonitorStateRequestExt {
+    /// Creates a new [`UpdateMonitorStateRequest`] from a [`MonitorState`].
+    fn try_from_monitor_state(
+        monitor_id: impl Into<String>,
+        monitor_state: &MonitorState,
+    ) -> MetastoreResult<UpdateMonitorStateRequest>;
+
+    /// Deserializes the `monitor_state_json` field of a [`UpdateMonitorStateRequest`] into a
+    /// [`MonitorState`].
+    fn deserialize_monitor_state(&self) -> MetastoreResult<MonitorState>;
+}
+
+impl UpdateMonitorStateRequestExt for UpdateMonitorStateRequest {
+    fn try_from_monitor_state(
+        monitor_id: impl Into<String>,
+        monitor_state: &MonitorState,
+    ) -> MetastoreResult<UpdateMonitorStateRequest> {
+        let monitor_state_json = serde_utils::to_json_str(monitor_state)?;
+        let request = Self {
+            monitor_id: monitor_id.into(),
+            monitor_state_json,
+        };
+        Ok(request)
+    }
+
+    fn deserialize_monitor_state(&self) -> MetastoreResult<MonitorState> {
+        serde_utils::from_json_str(&self.monitor_state_json)
+    }
+}
+
 /// Helper trait to build a [`DeleteTask`] and deserialize its payload.
 pub trait StageSplitsRequestExt {
     /// Creates a new [`StageSplitsRequest`] from a [`SplitMetadata`].
@@ -629,6 +795,10 @@ pub struct ListSplitsQuery {
     /// A specific node ID to filter by.
     pub node_id: Option<NodeId>,
 
+    /// A specific set of split IDs to filter by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_ids: Option<Vec<SplitId>>,
+
     /// The maximum number of splits to retrieve.
     pub limit: Option<usize>,
 
@@ -705,6 +875,7 @@ impl ListSplitsQuery {
         Self {
             index_uids: Some(vec![index_uid]),
             node_id: None,
+            split_ids: None,
             limit: None,
             offset: None,
             split_states: Vec::new(),
@@ -728,6 +899,7 @@ impl ListSplitsQuery {
         Some(Self {
             index_uids: Some(index_uids),
             node_id: None,
+            split_ids: None,
             limit: None,
             offset: None,
             split_states: Vec::new(),
@@ -747,6 +919,7 @@ impl ListSplitsQuery {
         Self {
             index_uids: None,
             node_id: None,
+            split_ids: None,
             limit: None,
             offset: None,
             split_states: Vec::new(),
@@ -767,6 +940,12 @@ impl ListSplitsQuery {
         self
     }
 
+    /// Selects the splits with the given split IDs.
+    pub fn with_split_ids(mut self, split_ids: Vec<SplitId>) -> Self {
+        self.split_ids = Some(split_ids);
+        self
+    }
+
     /// Sets the maximum number of splits to retrieve.
     pub fn with_limit(mut self, n: usize) -> Self {
         self.limit = Some(n);
@@ -1025,6 +1204,7 @@ fn use_shard_api(params: &SourceParams) -> bool {
         SourceParams::Kinesis(_) => false,
         SourceParams::PubSub(_) => false,
         SourceParams::Pulsar(_) => false,
+        SourceParams::Reindex(_) => false,
         SourceParams::Stdin => panic!("stdin cannot be checkpointed"),
         SourceParams::Vec(_) => false,
         SourceParams::Void(_) => false,
@@ -0,0 +1,369 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use quickwit_config::MonitorConfig;
+use quickwit_proto::types::SplitId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use time::OffsetDateTime;
+
+/// Maximum number of evaluations retained in the history of a monitor.
+pub const MAX_MONITOR_HISTORY_LEN: usize = 100;
+
+/// Maximum number of alerts awaiting redelivery retained in the state of a monitor.
+pub const MAX_PENDING_MONITOR_ALERTS: usize = 100;
+
+/// A monitor metadata carries the configuration of a monitor along with its evaluation state.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(into = "VersionedMonitorMetadata")]
+#[serde(from = "VersionedMonitorMetadata")]
+pub struct MonitorMetadata {
+    /// Monitor configuration
+    pub monitor_config: MonitorConfig,
+    /// Evaluation state and history of the monitor.
+    pub monitor_state: MonitorState,
+    /// Time at which the monitor was created.
+    pub create_timestamp: i64,
+}
+
+impl MonitorMetadata {
+    /// Creates a new [`MonitorMetadata`]. The monitor only considers the splits published after
+    /// its creation.
+    pub fn new(monitor_config: MonitorConfig) -> Self {
+        let create_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        MonitorMetadata {
+            monitor_config,
+            monitor_state: MonitorState::new(create_timestamp),
+            create_timestamp,
+        }
+    }
+
+    /// Returns the monitor ID.
+    pub fn monitor_id(&self) -> &str {
+        &self.monitor_config.monitor_id
+    }
+}
+
+/// Evaluation state of a monitor.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct MonitorState {
+    /// Publish timestamp up to which (inclusive) the splits of the monitored index have been
+    /// evaluated.
+    pub checkpoint_timestamp: i64,
+    /// Outcome of the last evaluation.
+    pub status: MonitorStatus,
+    /// Most recent evaluations, in chronological order.
+    #[serde(default)]
+    pub history: VecDeque<MonitorEvaluation>,
+    /// Immature splits published up to the checkpoint, along with the timestamp at which they
+    /// become mature. A merged split published after the checkpoint holds documents that have not
+    /// been evaluated if some of the splits it was merged from are missing from this map. `None`
+    /// until the first successful evaluation.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub immature_splits: Option<BTreeMap<SplitId, i64>>,
+    /// Alerts that some of the actions failed to deliver, in chronological order. Their delivery
+    /// is retried by the next evaluations.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "VecDeque::is_empty")]
+    pub pending_alerts: VecDeque<PendingMonitorAlert>,
+}
+
+impl MonitorState {
+    /// Creates a new [`MonitorState`] starting at `checkpoint_timestamp`.
+    pub fn new(checkpoint_timestamp: i64) -> Self {
+        MonitorState {
+            checkpoint_timestamp,
+            status: MonitorStatus::Pending,
+            history: VecDeque::new(),
+            immature_splits: None,
+            pending_alerts: VecDeque::new(),
+        }
+    }
+
+    /// Records an evaluation, moving the checkpoint forward unless the evaluation failed. Failed
+    /// actions do not prevent the checkpoint from moving: their alerts are retried instead. The
+    /// history is truncated to the [`MAX_MONITOR_HISTORY_LEN`] most recent evaluations.
+    pub fn record_evaluation(&mut self, evaluation: MonitorEvaluation) {
+        self.status = evaluation.status();
+
+        if evaluation.error.is_none() {
+            self.checkpoint_timestamp = evaluation.checkpoint_timestamp;
+        }
+        if self.history.len() == MAX_MONITOR_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(evaluation);
+    }
+
+    /// Returns the most recent evaluation, if any.
+    pub fn last_evaluation(&self) -> Option<&MonitorEvaluation> {
+        self.history.back()
+    }
+
+    /// Queues an alert for redelivery. Only the [`MAX_PENDING_MONITOR_ALERTS`] most recent alerts
+    /// are retained: the oldest one is returned if it gets dropped.
+    pub fn push_pending_alert(
+        &mut self,
+        pending_alert: PendingMonitorAlert,
+    ) -> Option<PendingMonitorAlert> {
+        let dropped_alert_opt = if self.pending_alerts.len() == MAX_PENDING_MONITOR_ALERTS {
+            self.pending_alerts.pop_front()
+        } else {
+            None
+        };
+        self.pending_alerts.push_back(pending_alert);
+        dropped_alert_opt
+    }
+}
+
+/// Alert that some of the actions of a monitor failed to deliver.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct PendingMonitorAlert {
+    /// Payload of the alert.
+    #[schema(value_type = Object)]
+    pub alert: JsonValue,
+    /// Positions, in the actions of the monitor, of the actions that have not delivered the alert
+    /// yet.
+    pub action_indexes: Vec<usize>,
+}
+
+/// Outcome of the last evaluation of a monitor.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorStatus {
+    /// The monitor has not been evaluated yet.
+    #[default]
+    Pending,
+    /// The condition of the monitor was not met.
+    Ok,
+    /// The condition of the monitor was met and its actions were executed.
+    Triggered,
+    /// The evaluation of the monitor failed.
+    Error,
+}
+
+/// A single evaluation of a monitor.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct MonitorEvaluation {
+    /// Time at which the evaluation took place.
+    pub evaluation_timestamp: i64,
+    /// Publish timestamp up to which (inclusive) splits were considered by the evaluation.
+    pub checkpoint_timestamp: i64,
+    /// Number of newly published splits searched.
+    pub num_splits: usize,
+    /// Value compared against the threshold of the monitor's condition.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// Whether the monitor's condition was met.
+    pub triggered: bool,
+    /// Error message if the evaluation failed.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Error messages of the actions that failed to deliver an alert.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub action_errors: Vec<String>,
+}
+
+impl MonitorEvaluation {
+    /// Returns the status of the monitor resulting from this evaluation.
+    pub fn status(&self) -> MonitorStatus {
+        if self.error.is_some() || !self.action_errors.is_empty() {
+            MonitorStatus::Error
+        } else if self.triggered {
+            MonitorStatus::Triggered
+        } else {
+            MonitorStatus::Ok
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+enum VersionedMonitorMetadata {
+    #[serde(rename = "0.9")]
+    V0_9(MonitorMetadataV0_9),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MonitorMetadataV0_9 {
+    monitor_config: MonitorConfig,
+    #[serde(default)]
+    monitor_state: MonitorState,
+    create_timestamp: i64,
+}
+
+impl From<MonitorMetadata> for VersionedMonitorMetadata {
+    fn from(monitor_metadata: MonitorMetadata) -> Self {
+        VersionedMonitorMetadata::V0_9(MonitorMetadataV0_9 {
+            monitor_config: monitor_metadata.monitor_config,
+            monitor_state: monitor_metadata.monitor_state,
+            create_timestamp: monitor_metadata.create_timestamp,
+        })
+    }
+}
+
+impl From<VersionedMonitorMetadata> for MonitorMetadata {
+    fn from(versioned_monitor_metadata: VersionedMonitorMetadata) -> Self {
+        match versioned_monitor_metadata {
+            VersionedMonitorMetadata::V0_9(v0_9) => MonitorMetadata {
+                monitor_config: v0_9.monitor_config,
+                monitor_state: v0_9.monitor_state,
+                create_timestamp: v0_9.create_timestamp,
+            },
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testsuite"))]
+impl quickwit_config::TestableForRegression for MonitorMetadata {
+    fn sample_for_regression() -> Self {
+        let mut monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        monitor_config.evaluation_schedule = "hourly".to_string();
+
+        let mut monitor_state = MonitorState::new(1789);
+        monitor_state.record_evaluation(MonitorEvaluation {
+            evaluation_timestamp: 1800,
+            checkpoint_timestamp: 1799,
+            num_splits: 3,
+            value: Some(42.0),
+            triggered: true,
+            error: None,
+            action_errors: Vec::new(),
+        });
+        MonitorMetadata {
+            monitor_config,
+            monitor_state,
+            create_timestamp: 1789,
+        }
+    }
+
+    fn assert_equality(&self, other: &Self) {
+        assert_eq!(self, other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluation_for_test(
+        checkpoint_timestamp: i64,
+        triggered: bool,
+        error: Option<&str>,
+    ) -> MonitorEvaluation {
+        MonitorEvaluation {
+            evaluation_timestamp: checkpoint_timestamp + 1,
+            checkpoint_timestamp,
+            num_splits: 1,
+            value: Some(1.0),
+            triggered,
+            error: error.map(ToString::to_string),
+            action_errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_monitor_state_record_evaluation() {
+        let mut monitor_state = MonitorState::new(10);
+        assert_eq!(monitor_state.status, MonitorStatus::Pending);
+        assert!(monitor_state.last_evaluation().is_none());
+
+        monitor_state.record_evaluation(evaluation_for_test(20, false, None));
+        assert_eq!(monitor_state.status, MonitorStatus::Ok);
+        assert_eq!(monitor_state.checkpoint_timestamp, 20);
+
+        monitor_state.record_evaluation(evaluation_for_test(30, true, None));
+        assert_eq!(monitor_state.status, MonitorStatus::Triggered);
+        assert_eq!(monitor_state.checkpoint_timestamp, 30);
+
+        // Failed evaluations do not move the checkpoint so that the splits are evaluated again.
+        monitor_state.record_evaluation(evaluation_for_test(40, false, Some("timeout")));
+        assert_eq!(monitor_state.status, MonitorStatus::Error);
+        assert_eq!(monitor_state.checkpoint_timestamp, 30);
+        assert_eq!(monitor_state.history.len(), 3);
+
+        // Failed actions do not prevent the checkpoint from moving: their alerts are retried.
+        let mut evaluation = evaluation_for_test(50, true, None);
+        evaluation.action_errors = vec!["webhook action failed".to_string()];
+        monitor_state.record_evaluation(evaluation);
+        assert_eq!(monitor_state.status, MonitorStatus::Error);
+        assert_eq!(monitor_state.checkpoint_timestamp, 50);
+        assert_eq!(monitor_state.history.len(), 4);
+
+        for i in 0..MAX_MONITOR_HISTORY_LEN as i64 {
+            monitor_state.record_evaluation(evaluation_for_test(100 + i, false, None));
+        }
+        assert_eq!(monitor_state.history.len(), MAX_MONITOR_HISTORY_LEN);
+        assert_eq!(
+            monitor_state.history.front().unwrap().checkpoint_timestamp,
+            100
+        );
+        assert_eq!(
+            monitor_state
+                .last_evaluation()
+                .unwrap()
+                .checkpoint_timestamp,
+            100 + MAX_MONITOR_HISTORY_LEN as i64 - 1
+        );
+    }
+
+    #[test]
+    fn test_monitor_state_push_pending_alert() {
+        let mut monitor_state = MonitorState::new(10);
+
+        for i in 0..MAX_PENDING_MONITOR_ALERTS {
+            let pending_alert = PendingMonitorAlert {
+                alert: serde_json::json!({"evaluation_timestamp": i}),
+                action_indexes: vec![0],
+            };
+            assert!(monitor_state.push_pending_alert(pending_alert).is_none());
+        }
+        let pending_alert = PendingMonitorAlert {
+            alert: serde_json::json!({"evaluation_timestamp": MAX_PENDING_MONITOR_ALERTS}),
+            action_indexes: vec![0],
+        };
+        let dropped_alert = monitor_state.push_pending_alert(pending_alert).unwrap();
+        assert_eq!(dropped_alert.alert["evaluation_timestamp"], 0);
+        assert_eq!(
+            monitor_state.pending_alerts.len(),
+            MAX_PENDING_MONITOR_ALERTS
+        );
+        assert_eq!(
+            monitor_state.pending_alerts.back().unwrap().alert["evaluation_timestamp"],
+            MAX_PENDING_MONITOR_ALERTS
+        );
+    }
+
+    #[test]
+    fn test_monitor_metadata_serde() {
+        let monitor_config = MonitorConfig::for_test("test-monitor", "test-index");
+        let monitor_metadata = MonitorMetadata::new(monitor_config);
+        assert_eq!(
+            monitor_metadata.monitor_state.checkpoint_timestamp,
+            monitor_metadata.create_timestamp
+        );
+        let monitor_metadata_json = serde_json::to_value(&monitor_metadata).unwrap();
+        assert_eq!(monitor_metadata_json["version"], "0.9");
+
+        let monitor_metadata_deser: MonitorMetadata =
+            serde_json::from_value(monitor_metadata_json).unwrap();
+        assert_eq!(monitor_metadata_deser, monitor_metadata);
+    }
+}
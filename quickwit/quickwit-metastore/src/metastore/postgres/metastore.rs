@@ -28,21 +28,22 @@ use quickwit_config::{
 use quickwit_proto::ingest::{Shard, ShardState};
 use quickwit_proto::metastore::{
     serde_utils, AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexRequest,
-    DeleteIndexTemplatesRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
-    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind,
-    FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse, GetIndexTemplateRequest,
-    GetIndexTemplateResponse, IndexMetadataFailure, IndexMetadataFailureReason,
-    IndexMetadataRequest, IndexMetadataResponse, IndexTemplateMatch, IndexesMetadataRequest,
-    IndexesMetadataResponse, LastDeleteOpstampRequest, LastDeleteOpstampResponse,
-    ListDeleteTasksRequest, ListDeleteTasksResponse, ListIndexTemplatesRequest,
-    ListIndexTemplatesResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
-    ListShardsRequest, ListShardsResponse, ListShardsSubresponse, ListSplitsRequest,
-    ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError,
-    MetastoreResult, MetastoreService, MetastoreServiceStream, OpenShardSubrequest,
-    OpenShardSubresponse, OpenShardsRequest, OpenShardsResponse, PruneShardsRequest,
-    PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
-    UpdateIndexRequest, UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest,
+    CreateIndexResponse, CreateIndexTemplateRequest, CreateMonitorRequest, DeleteIndexRequest,
+    DeleteIndexTemplatesRequest, DeleteMonitorsRequest, DeleteQuery, DeleteShardsRequest,
+    DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
+    EntityKind, FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse,
+    GetIndexTemplateRequest, GetIndexTemplateResponse, GetMonitorRequest, GetMonitorResponse,
+    IndexMetadataFailure, IndexMetadataFailureReason, IndexMetadataRequest, IndexMetadataResponse,
+    IndexTemplateMatch, IndexesMetadataRequest, IndexesMetadataResponse, LastDeleteOpstampRequest,
+    LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
+    ListIndexTemplatesRequest, ListIndexTemplatesResponse, ListIndexesMetadataRequest,
+    ListIndexesMetadataResponse, ListMonitorsRequest, ListMonitorsResponse, ListShardsRequest,
+    ListShardsResponse, ListShardsSubresponse, ListSplitsRequest, ListSplitsResponse,
+    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
+    MetastoreService, MetastoreServiceStream, OpenShardSubrequest, OpenShardSubresponse,
+    OpenShardsRequest, OpenShardsResponse, PruneShardsRequest, PublishSplitsRequest,
+    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest,
+    UpdateMonitorStateRequest, UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest,
     UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::{IndexId, IndexUid, Position, PublishToken, ShardId, SourceId};
@@ -54,7 +55,7 @@ use tracing::{debug, info, instrument, warn};
 
 use super::error::convert_sqlx_err;
 use super::migrator::run_migrations;
-use super::model::{PgDeleteTask, PgIndex, PgIndexTemplate, PgShard, PgSplit, Splits};
+use super::model::{PgDeleteTask, PgIndex, PgIndexTemplate, PgMonitor, PgShard, PgSplit, Splits};
 use super::pool::TrackedPool;
 use super::split_stream::SplitStream;
 use super::utils::{append_query_filters_and_order_by, establish_connection};
@@ -73,9 +74,11 @@ use crate::metastore::{
     STREAM_SPLITS_CHUNK_SIZE,
 };
 use crate::{
-    AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
-    ListIndexesMetadataResponseExt, ListSplitsRequestExt, ListSplitsResponseExt,
-    MetastoreServiceExt, Split, SplitState, StageSplitsRequestExt, UpdateIndexRequestExt,
+    AddSourceRequestExt, CreateIndexRequestExt, CreateMonitorRequestExt, GetMonitorResponseExt,
+    IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt,
+    ListMonitorsResponseExt, ListSplitsRequestExt, ListSplitsResponseExt, MetastoreServiceExt,
+    MonitorMetadata, MonitorState, Split, SplitState, StageSplitsRequestExt, UpdateIndexRequestExt,
+    UpdateMonitorStateRequestExt,
 };
 
 /// PostgreSQL metastore implementation.
@@ -416,6 +419,8 @@ impl MetastoreService for PostgresqlMetastore {
         request: UpdateIndexRequest,
     ) -> MetastoreResult<IndexMetadataResponse> {
         let retention_policy_opt = request.deserialize_retention_policy()?;
+        let rollup_policy_opt = request.deserialize_rollup_policy()?;
+        let tiering_policy_opt = request.deserialize_tiering_policy()?;
         let search_settings = request.deserialize_search_settings()?;
         let indexing_settings = request.deserialize_indexing_settings()?;
         let doc_mapping = request.deserialize_doc_mapping()?;
@@ -425,6 +430,8 @@ impl MetastoreService for PostgresqlMetastore {
             mutate_index_metadata::<MetastoreError, _>(tx, index_uid, |index_metadata| {
                 let mut mutation_occurred =
                     index_metadata.set_retention_policy(retention_policy_opt);
+                mutation_occurred |= index_metadata.set_rollup_policy(rollup_policy_opt);
+                mutation_occurred |= index_metadata.set_tiering_policy(tiering_policy_opt);
                 mutation_occurred |= index_metadata.set_search_settings(search_settings);
                 mutation_occurred |= index_metadata.set_indexing_settings(indexing_settings);
                 mutation_occurred |= index_metadata.set_doc_mapping(doc_mapping);
@@ -1661,6 +1668,111 @@ impl MetastoreService for PostgresqlMetastore {
             .await?;
         Ok(EmptyResponse {})
     }
+
+    // Monitor API
+
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        const INSERT_MONITOR_QUERY: &str = include_str!("queries/monitors/insert.sql");
+        const UPSERT_MONITOR_QUERY: &str = include_str!("queries/monitors/upsert.sql");
+
+        let monitor_config = request.deserialize_monitor_config()?;
+
+        monitor_config
+            .validate()
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!("invalid monitor `{}`: `{error}`", monitor_config.monitor_id),
+            })?;
+        // Overwriting a monitor preserves its evaluation state, so the initial state is only
+        // written when the monitor is inserted.
+        let create_timestamp = OffsetDateTime::now_utc();
+        let monitor_state = MonitorState::new(create_timestamp.unix_timestamp());
+        let monitor_state_json = serde_utils::to_json_str(&monitor_state)?;
+
+        let query = if request.overwrite {
+            UPSERT_MONITOR_QUERY
+        } else {
+            INSERT_MONITOR_QUERY
+        };
+        let pg_query_result = sqlx::query(query)
+            .bind(&monitor_config.monitor_id)
+            .bind(&request.monitor_config_json)
+            .bind(monitor_state_json)
+            .bind(create_timestamp)
+            .execute(&self.connection_pool)
+            .await?;
+
+        if pg_query_result.rows_affected() == 0 {
+            return Err(MetastoreError::AlreadyExists(EntityKind::Monitor {
+                monitor_id: monitor_config.monitor_id,
+            }));
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn get_monitor(&self, request: GetMonitorRequest) -> MetastoreResult<GetMonitorResponse> {
+        let pg_monitor: PgMonitor = sqlx::query_as("SELECT * FROM monitors WHERE monitor_id = $1")
+            .bind(&request.monitor_id)
+            .fetch_optional(&self.connection_pool)
+            .await?
+            .ok_or({
+                MetastoreError::NotFound(EntityKind::Monitor {
+                    monitor_id: request.monitor_id,
+                })
+            })?;
+        let monitor_metadata = pg_monitor.monitor_metadata()?;
+        GetMonitorResponse::try_from_monitor_metadata(&monitor_metadata)
+    }
+
+    async fn list_monitors(
+        &self,
+        _request: ListMonitorsRequest,
+    ) -> MetastoreResult<ListMonitorsResponse> {
+        let pg_monitors: Vec<PgMonitor> =
+            sqlx::query_as("SELECT * FROM monitors ORDER BY monitor_id ASC")
+                .fetch_all(&self.connection_pool)
+                .await?;
+        let monitors_metadata: Vec<MonitorMetadata> = pg_monitors
+            .iter()
+            .map(|pg_monitor| pg_monitor.monitor_metadata())
+            .collect::<MetastoreResult<_>>()?;
+        ListMonitorsResponse::try_from_monitors_metadata(&monitors_metadata)
+    }
+
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        // Ensures the payload is a valid monitor state before persisting it.
+        request.deserialize_monitor_state()?;
+
+        let pg_query_result =
+            sqlx::query("UPDATE monitors SET monitor_state_json = $2 WHERE monitor_id = $1")
+                .bind(&request.monitor_id)
+                .bind(&request.monitor_state_json)
+                .execute(&self.connection_pool)
+                .await?;
+
+        if pg_query_result.rows_affected() == 0 {
+            return Err(MetastoreError::NotFound(EntityKind::Monitor {
+                monitor_id: request.monitor_id,
+            }));
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        sqlx::query("DELETE FROM monitors WHERE monitor_id = ANY($1)")
+            .bind(&request.monitor_ids)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(EmptyResponse {})
+    }
 }
 
 async fn open_or_fetch_shard<'e>(
@@ -17,13 +17,16 @@
 use std::convert::TryInto;
 use std::str::FromStr;
 
+use quickwit_config::MonitorId;
 use quickwit_proto::ingest::{Shard, ShardState};
-use quickwit_proto::metastore::{DeleteQuery, DeleteTask, MetastoreError, MetastoreResult};
+use quickwit_proto::metastore::{
+    serde_utils, DeleteQuery, DeleteTask, MetastoreError, MetastoreResult,
+};
 use quickwit_proto::types::{DocMappingUid, IndexId, IndexUid, ShardId, SourceId, SplitId};
 use sea_query::{Iden, Write};
 use tracing::error;
 
-use crate::{IndexMetadata, Split, SplitMetadata, SplitState};
+use crate::{IndexMetadata, MonitorMetadata, Split, SplitMetadata, SplitState};
 
 #[derive(Iden, Clone, Copy)]
 #[allow(dead_code)]
@@ -292,3 +295,24 @@ impl From<PgShard> for Shard {
 pub(super) struct PgIndexTemplate {
     pub index_template_json: String,
 }
+
+/// A model structure for handling monitors in a database.
+#[derive(sqlx::FromRow, Debug)]
+pub(super) struct PgMonitor {
+    pub monitor_id: MonitorId,
+    pub monitor_config_json: String,
+    pub monitor_state_json: String,
+    pub create_timestamp: sqlx::types::time::PrimitiveDateTime,
+}
+
+impl PgMonitor {
+    /// Deserializes the monitor configuration and state stored in dedicated columns.
+    pub fn monitor_metadata(&self) -> MetastoreResult<MonitorMetadata> {
+        let monitor_metadata = MonitorMetadata {
+            monitor_config: serde_utils::from_json_str(&self.monitor_config_json)?,
+            monitor_state: serde_utils::from_json_str(&self.monitor_state_json)?,
+            create_timestamp: self.create_timestamp.assume_utc().unix_timestamp(),
+        };
+        Ok(monitor_metadata)
+    }
+}
@@ -0,0 +1,4 @@
+INSERT INTO monitors(monitor_id, monitor_config_json, monitor_state_json, create_timestamp)
+    VALUES ($1, $2, $3, $4)
+ON CONFLICT (monitor_id)
+    DO NOTHING
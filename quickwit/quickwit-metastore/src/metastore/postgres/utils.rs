@@ -111,6 +111,10 @@ pub(super) fn append_query_filters_and_order_by(
         sql.cond_where(Expr::col(Splits::NodeId).eq(node_id));
     };
 
+    if let Some(split_ids) = &query.split_ids {
+        sql.cond_where(Expr::col(Splits::SplitId).is_in(split_ids));
+    };
+
     if !query.split_states.is_empty() {
         sql.cond_where(
             Expr::col(Splits::SplitState)
@@ -58,9 +58,11 @@ impl MetastoreResolver {
         let backend = match uri.protocol() {
             Protocol::Azure => MetastoreBackend::File,
             Protocol::Google => MetastoreBackend::File,
+            Protocol::Hdfs => MetastoreBackend::File,
             Protocol::File => MetastoreBackend::File,
             Protocol::Ram => MetastoreBackend::File,
             Protocol::S3 => MetastoreBackend::File,
+            Protocol::Webdav | Protocol::Webdavs => MetastoreBackend::File,
             Protocol::PostgreSQL => MetastoreBackend::PostgreSQL,
             _ => {
                 return Err(MetastoreResolverError::UnsupportedBackend(
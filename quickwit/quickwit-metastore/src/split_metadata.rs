@@ -20,6 +20,8 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use bytesize::ByteSize;
+use quickwit_common::uri::Uri;
+use quickwit_config::DocStoreLayout;
 use quickwit_proto::types::{DocMappingUid, IndexUid, SourceId, SplitId};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationMilliSeconds};
@@ -132,6 +134,20 @@ pub struct SplitMetadata {
     /// Doc mapping UID used when creating this split. This split may only be merged with other
     /// splits using the same doc mapping UID.
     pub doc_mapping_uid: DocMappingUid,
+
+    /// URI of the storage holding the split file when the split has been moved to a storage tier
+    /// different from the index URI. `None` if the split lives under the index URI.
+    #[schema(value_type = Option<String>)]
+    pub storage_uri: Option<Uri>,
+
+    /// Layout of the stored fields in the split file. Splits created before the columnar layout
+    /// was introduced use the row layout.
+    pub docstore_layout: DocStoreLayout,
+
+    /// IDs of the splits this split replaced when it was published, i.e. the splits it was
+    /// merged from, or the split it was rewritten or moved from. Empty for the splits created by
+    /// indexing.
+    pub replaced_split_ids: Vec<SplitId>,
 }
 
 impl fmt::Debug for SplitMetadata {
@@ -176,6 +192,15 @@ impl fmt::Debug for SplitMetadata {
         debug_struct.field("footer_offsets", &self.footer_offsets);
         debug_struct.field("delete_opstamp", &self.delete_opstamp);
         debug_struct.field("num_merge_ops", &self.num_merge_ops);
+        if let Some(storage_uri) = &self.storage_uri {
+            debug_struct.field("storage_uri", storage_uri);
+        }
+        if !self.docstore_layout.is_row() {
+            debug_struct.field("docstore_layout", &self.docstore_layout);
+        }
+        if !self.replaced_split_ids.is_empty() {
+            debug_struct.field("replaced_split_ids", &self.replaced_split_ids);
+        }
         debug_struct.finish()
     }
 }
@@ -227,6 +252,12 @@ impl SplitMetadata {
         }
     }
 
+    /// Returns the URI of the storage holding the split file: the URI of the storage tier the
+    /// split was moved to if any, the index URI otherwise.
+    pub fn storage_uri<'a>(&'a self, index_uri: &'a Uri) -> &'a Uri {
+        self.storage_uri.as_ref().unwrap_or(index_uri)
+    }
+
     /// Converts the split metadata into a [`SplitInfo`].
     pub fn as_split_info(&self) -> SplitInfo {
         let file_name = quickwit_common::split_file(self.split_id());
@@ -281,6 +312,9 @@ impl quickwit_config::TestableForRegression for SplitMetadata {
             footer_offsets: 1000..2000,
             num_merge_ops: 3,
             doc_mapping_uid: DocMappingUid::default(),
+            storage_uri: None,
+            docstore_layout: DocStoreLayout::Row,
+            replaced_split_ids: Vec::new(),
         }
     }
 
@@ -421,6 +455,9 @@ mod tests {
             delete_opstamp: 0,
             num_merge_ops: 0,
             doc_mapping_uid: DocMappingUid::default(),
+            storage_uri: None,
+            docstore_layout: DocStoreLayout::Row,
+            replaced_split_ids: Vec::new(),
         };
 
         let expected_output = "SplitMetadata { split_id: \"split-1\", index_uid: IndexUid { \
@@ -435,6 +472,23 @@ mod tests {
         assert_eq!(format!("{:?}", split_metadata), expected_output);
     }
 
+    #[test]
+    fn test_split_metadata_version_depends_on_docstore_layout() {
+        use quickwit_config::TestableForRegression;
+
+        let mut split_metadata = SplitMetadata::sample_for_regression();
+        let split_json = serde_json::to_value(&split_metadata).unwrap();
+        assert_eq!(split_json["version"], "0.9");
+
+        split_metadata.docstore_layout = DocStoreLayout::Columnar;
+        let split_json = serde_json::to_value(&split_metadata).unwrap();
+        assert_eq!(split_json["version"], "0.10");
+        assert_eq!(split_json["docstore_layout"], "columnar");
+
+        let deserialized: SplitMetadata = serde_json::from_value(split_json).unwrap();
+        assert_eq!(deserialized, split_metadata);
+    }
+
     #[test]
     fn test_spit_maturity_order() {
         assert!(
@@ -15,6 +15,8 @@
 use std::collections::BTreeSet;
 use std::ops::{Range, RangeInclusive};
 
+use quickwit_common::uri::Uri;
+use quickwit_config::DocStoreLayout;
 use quickwit_proto::types::{DocMappingUid, IndexUid, SplitId};
 use serde::{Deserialize, Serialize};
 
@@ -92,6 +94,20 @@ pub(crate) struct SplitMetadataV0_8 {
     // splits before when updates first appeared are compatible with each other.
     #[serde(default)]
     doc_mapping_uid: DocMappingUid,
+
+    /// URI of the storage tier holding the split file, if different from the index URI.
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storage_uri: Option<Uri>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "DocStoreLayout::is_row")]
+    docstore_layout: DocStoreLayout,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    replaced_split_ids: Vec<SplitId>,
 }
 
 impl From<SplitMetadataV0_8> for SplitMetadata {
@@ -128,6 +144,9 @@ impl From<SplitMetadataV0_8> for SplitMetadata {
             footer_offsets: v8.footer_offsets,
             num_merge_ops: v8.num_merge_ops,
             doc_mapping_uid: v8.doc_mapping_uid,
+            storage_uri: v8.storage_uri,
+            docstore_layout: v8.docstore_layout,
+            replaced_split_ids: v8.replaced_split_ids,
         }
     }
 }
@@ -150,6 +169,9 @@ impl From<SplitMetadata> for SplitMetadataV0_8 {
             footer_offsets: split.footer_offsets,
             num_merge_ops: split.num_merge_ops,
             doc_mapping_uid: split.doc_mapping_uid,
+            storage_uri: split.storage_uri,
+            docstore_layout: split.docstore_layout,
+            replaced_split_ids: split.replaced_split_ids,
         }
     }
 }
@@ -162,18 +184,28 @@ pub(crate) enum VersionedSplitMetadata {
     #[serde(alias = "0.8")]
     #[serde(alias = "0.7")]
     V0_8(SplitMetadataV0_8),
+    // The splits written with the columnar doc store layout have an empty row doc store: they
+    // are tagged with a version unknown to older nodes so that these fail to load them instead
+    // of returning empty documents.
+    #[serde(rename = "0.10")]
+    V0_10(SplitMetadataV0_8),
 }
 
 impl From<VersionedSplitMetadata> for SplitMetadata {
     fn from(versioned_helper: VersionedSplitMetadata) -> Self {
         match versioned_helper {
             VersionedSplitMetadata::V0_8(v0_8) => v0_8.into(),
+            VersionedSplitMetadata::V0_10(v0_10) => v0_10.into(),
         }
     }
 }
 
 impl From<SplitMetadata> for VersionedSplitMetadata {
     fn from(split_metadata: SplitMetadata) -> Self {
-        VersionedSplitMetadata::V0_8(split_metadata.into())
+        if split_metadata.docstore_layout.is_row() {
+            VersionedSplitMetadata::V0_8(split_metadata.into())
+        } else {
+            VersionedSplitMetadata::V0_10(split_metadata.into())
+        }
     }
 }
@@ -121,6 +121,8 @@ pub async fn test_metastore_update_retention_policy<
             index_uid.clone(),
             &index_config.search_settings,
             &loop_retention_policy_opt,
+            &index_config.rollup_policy_opt,
+            &index_config.tiering_policy_opt,
             &index_config.indexing_settings,
             &index_config.doc_mapping,
         )
@@ -168,6 +170,8 @@ pub async fn test_metastore_update_search_settings<
                 default_search_fields: loop_search_settings.clone(),
             },
             &index_config.retention_policy_opt,
+            &index_config.rollup_policy_opt,
+            &index_config.tiering_policy_opt,
             &index_config.indexing_settings,
             &index_config.doc_mapping,
         )
@@ -222,6 +226,8 @@ pub async fn test_metastore_update_indexing_settings<
             index_uid.clone(),
             &index_config.search_settings,
             &index_config.retention_policy_opt,
+            &index_config.rollup_policy_opt,
+            &index_config.tiering_policy_opt,
             &IndexingSettings {
                 merge_policy: loop_indexing_settings.clone(),
                 ..Default::default()
@@ -294,6 +300,8 @@ pub async fn test_metastore_update_doc_mapping<
             index_uid.clone(),
             &index_config.search_settings,
             &index_config.retention_policy_opt,
+            &index_config.rollup_policy_opt,
+            &index_config.tiering_policy_opt,
             &index_config.indexing_settings,
             &loop_doc_mapping,
         )
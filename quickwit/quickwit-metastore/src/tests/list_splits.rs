@@ -956,6 +956,62 @@ pub async fn test_metastore_list_splits_by_node_id<
     assert_eq!(splits[0].split_metadata.node_id, "test-node-1");
 }
 
+pub async fn test_metastore_list_splits_by_split_ids<
+    MetastoreToTest: MetastoreServiceExt + DefaultForTest,
+>() {
+    let metastore = MetastoreToTest::default_for_test().await;
+
+    let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let index_id = append_random_suffix("test-list-splits-by-split-ids");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request = CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .unwrap();
+
+    let splits_metadata: Vec<SplitMetadata> = (1..=3)
+        .map(|split_ord| SplitMetadata {
+            split_id: format!("{index_id}--split-{split_ord}"),
+            index_uid: index_uid.clone(),
+            create_timestamp: current_timestamp,
+            ..Default::default()
+        })
+        .collect();
+    let stage_splits_request =
+        StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), splits_metadata).unwrap();
+
+    metastore.stage_splits(stage_splits_request).await.unwrap();
+
+    let split_id_1 = format!("{index_id}--split-1");
+    let split_id_3 = format!("{index_id}--split-3");
+    let list_splits_query = ListSplitsQuery::for_index(index_uid.clone()).with_split_ids(vec![
+        split_id_1.clone(),
+        split_id_3.clone(),
+        format!("{index_id}--split-unknown"),
+    ]);
+    let list_splits_request =
+        ListSplitsRequest::try_from_list_splits_query(&list_splits_query).unwrap();
+
+    let splits = metastore
+        .list_splits(list_splits_request)
+        .await
+        .unwrap()
+        .collect_splits()
+        .await
+        .unwrap();
+    let split_ids: Vec<&str> = splits
+        .iter()
+        .map(|split| split.split_id())
+        .sorted()
+        .collect();
+    assert_eq!(split_ids, [split_id_1.as_str(), split_id_3.as_str()]);
+}
+
 pub async fn test_metastore_list_stale_splits<
     MetastoreToTest: MetastoreServiceExt + DefaultForTest,
 >() {
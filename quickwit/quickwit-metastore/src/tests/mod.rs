@@ -28,6 +28,7 @@ use quickwit_proto::types::IndexUid;
 pub(crate) mod delete_task;
 pub(crate) mod index;
 pub(crate) mod list_splits;
+pub(crate) mod monitor;
 pub(crate) mod shard;
 pub(crate) mod source;
 pub(crate) mod split;
@@ -355,6 +356,13 @@ macro_rules! metastore_test_suite {
                 $crate::tests::list_splits::test_metastore_list_splits_by_node_id::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_list_splits_by_split_ids() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::list_splits::test_metastore_list_splits_by_split_ids::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             #[serial_test::file_serial]
             async fn test_metastore_split_update_timestamp() {
@@ -544,6 +552,38 @@ macro_rules! metastore_test_suite {
             async fn test_metastore_delete_index_templates() {
                 $crate::tests::template::test_metastore_delete_index_templates::<$metastore_type>().await;
             }
+
+            /// Monitor API tests
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_create_monitor() {
+                $crate::tests::monitor::test_metastore_create_monitor::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_get_monitor() {
+                $crate::tests::monitor::test_metastore_get_monitor::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_list_monitors() {
+                $crate::tests::monitor::test_metastore_list_monitors::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_update_monitor_state() {
+                $crate::tests::monitor::test_metastore_update_monitor_state::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_delete_monitors() {
+                $crate::tests::monitor::test_metastore_delete_monitors::<$metastore_type>().await;
+            }
         }
     };
 }
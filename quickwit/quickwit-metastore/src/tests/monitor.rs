@@ -0,0 +1,300 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_common::rand::append_random_suffix;
+use quickwit_config::MonitorConfig;
+use quickwit_proto::metastore::{
+    CreateMonitorRequest, DeleteMonitorsRequest, EntityKind, GetMonitorRequest,
+    ListMonitorsRequest, MetastoreError, MetastoreResult, MetastoreService,
+    UpdateMonitorStateRequest,
+};
+
+use super::DefaultForTest;
+use crate::{
+    CreateMonitorRequestExt, GetMonitorResponseExt, ListMonitorsResponseExt, MetastoreServiceExt,
+    MonitorEvaluation, MonitorMetadata, MonitorStatus, UpdateMonitorStateRequestExt,
+};
+
+async fn list_all_monitors(
+    metastore: &mut dyn MetastoreService,
+) -> MetastoreResult<Vec<MonitorMetadata>> {
+    let list_monitors_response = metastore.list_monitors(ListMonitorsRequest {}).await?;
+    list_monitors_response.deserialize_monitors_metadata()
+}
+
+async fn cleanup_monitors(metastore: &mut dyn MetastoreService) {
+    let monitor_ids = list_all_monitors(metastore)
+        .await
+        .unwrap()
+        .iter()
+        .map(|monitor_metadata| monitor_metadata.monitor_id().to_string())
+        .collect::<Vec<_>>();
+
+    let delete_monitors_request = DeleteMonitorsRequest { monitor_ids };
+    metastore
+        .delete_monitors(delete_monitors_request)
+        .await
+        .unwrap();
+}
+
+pub async fn test_metastore_create_monitor<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_monitors(&mut metastore).await;
+
+    let monitor_id = append_random_suffix("test-create-monitor");
+    let monitor_config = MonitorConfig::for_test(&monitor_id, "test-index");
+
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&monitor_config, false).unwrap();
+    metastore
+        .create_monitor(create_monitor_request.clone())
+        .await
+        .unwrap();
+
+    let monitors = list_all_monitors(&mut metastore).await.unwrap();
+    assert_eq!(monitors.len(), 1);
+    assert_eq!(monitors[0].monitor_config, monitor_config);
+    assert_eq!(monitors[0].monitor_state.status, MonitorStatus::Pending);
+    assert_eq!(
+        monitors[0].monitor_state.checkpoint_timestamp,
+        monitors[0].create_timestamp
+    );
+
+    let error = metastore
+        .create_monitor(create_monitor_request)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, MetastoreError::AlreadyExists(EntityKind::Monitor { monitor_id }) if monitor_id.starts_with("test-create-monitor"))
+    );
+
+    // Overwriting a monitor updates its configuration but preserves its evaluation state.
+    let mut monitor_state = monitors[0].monitor_state.clone();
+    monitor_state.record_evaluation(MonitorEvaluation {
+        evaluation_timestamp: monitor_state.checkpoint_timestamp + 10,
+        checkpoint_timestamp: monitor_state.checkpoint_timestamp + 5,
+        num_splits: 1,
+        value: Some(1.0),
+        triggered: true,
+        error: None,
+        action_errors: Vec::new(),
+    });
+    let update_monitor_state_request =
+        UpdateMonitorStateRequest::try_from_monitor_state(&monitor_id, &monitor_state).unwrap();
+    metastore
+        .update_monitor_state(update_monitor_state_request)
+        .await
+        .unwrap();
+
+    let mut monitor_config = MonitorConfig::for_test(&monitor_id, "test-index");
+    monitor_config.query = "severity_text:FATAL".to_string();
+
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&monitor_config, true).unwrap();
+    metastore
+        .create_monitor(create_monitor_request)
+        .await
+        .unwrap();
+
+    let monitors = list_all_monitors(&mut metastore).await.unwrap();
+    assert_eq!(monitors.len(), 1);
+    assert_eq!(monitors[0].monitor_config.query, "severity_text:FATAL");
+    assert_eq!(monitors[0].monitor_state, monitor_state);
+
+    let mut invalid_monitor_config = MonitorConfig::for_test(&monitor_id, "test-index");
+    invalid_monitor_config.evaluation_schedule = "every full moon".to_string();
+
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&invalid_monitor_config, true).unwrap();
+    let error = metastore
+        .create_monitor(create_monitor_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    cleanup_monitors(&mut metastore).await;
+}
+
+pub async fn test_metastore_get_monitor<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_monitors(&mut metastore).await;
+
+    let monitor_id = append_random_suffix("test-get-monitor");
+    let monitor_config = MonitorConfig::for_test(&monitor_id, "test-index");
+
+    let get_monitor_request = GetMonitorRequest {
+        monitor_id: monitor_id.clone(),
+    };
+    let error = metastore
+        .get_monitor(get_monitor_request.clone())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, MetastoreError::NotFound(EntityKind::Monitor { monitor_id }) if monitor_id.starts_with("test-get-monitor"))
+    );
+
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&monitor_config, false).unwrap();
+    metastore
+        .create_monitor(create_monitor_request)
+        .await
+        .unwrap();
+
+    let monitor_metadata = metastore
+        .get_monitor(get_monitor_request)
+        .await
+        .unwrap()
+        .deserialize_monitor_metadata()
+        .unwrap();
+    assert_eq!(monitor_metadata.monitor_id(), monitor_id);
+    assert_eq!(monitor_metadata.monitor_config, monitor_config);
+
+    cleanup_monitors(&mut metastore).await;
+}
+
+pub async fn test_metastore_list_monitors<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_monitors(&mut metastore).await;
+
+    let monitors = list_all_monitors(&mut metastore).await.unwrap();
+    assert!(monitors.is_empty());
+
+    let monitor_id_foo = append_random_suffix("test-list-monitors-foo");
+    let monitor_id_bar = append_random_suffix("test-list-monitors-bar");
+
+    for monitor_id in [&monitor_id_foo, &monitor_id_bar] {
+        let monitor_config = MonitorConfig::for_test(monitor_id, "test-index");
+        let create_monitor_request =
+            CreateMonitorRequest::try_from_monitor_config(&monitor_config, false).unwrap();
+        metastore
+            .create_monitor(create_monitor_request)
+            .await
+            .unwrap();
+    }
+    let mut monitor_ids: Vec<String> = list_all_monitors(&mut metastore)
+        .await
+        .unwrap()
+        .iter()
+        .map(|monitor_metadata| monitor_metadata.monitor_id().to_string())
+        .collect();
+    monitor_ids.sort();
+    assert_eq!(monitor_ids, [monitor_id_bar, monitor_id_foo]);
+
+    cleanup_monitors(&mut metastore).await;
+}
+
+pub async fn test_metastore_update_monitor_state<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_monitors(&mut metastore).await;
+
+    let monitor_id = append_random_suffix("test-update-monitor-state");
+    let monitor_config = MonitorConfig::for_test(&monitor_id, "test-index");
+    let monitor_metadata = MonitorMetadata::new(monitor_config.clone());
+
+    let mut monitor_state = monitor_metadata.monitor_state;
+    monitor_state.record_evaluation(MonitorEvaluation {
+        evaluation_timestamp: monitor_state.checkpoint_timestamp + 10,
+        checkpoint_timestamp: monitor_state.checkpoint_timestamp + 5,
+        num_splits: 2,
+        value: Some(0.0),
+        triggered: false,
+        error: None,
+        action_errors: Vec::new(),
+    });
+    let update_monitor_state_request =
+        UpdateMonitorStateRequest::try_from_monitor_state(&monitor_id, &monitor_state).unwrap();
+    let error = metastore
+        .update_monitor_state(update_monitor_state_request.clone())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, MetastoreError::NotFound(EntityKind::Monitor { monitor_id }) if monitor_id.starts_with("test-update-monitor-state"))
+    );
+
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&monitor_config, false).unwrap();
+    metastore
+        .create_monitor(create_monitor_request)
+        .await
+        .unwrap();
+
+    metastore
+        .update_monitor_state(update_monitor_state_request)
+        .await
+        .unwrap();
+
+    let get_monitor_request = GetMonitorRequest {
+        monitor_id: monitor_id.clone(),
+    };
+    let monitor_metadata = metastore
+        .get_monitor(get_monitor_request)
+        .await
+        .unwrap()
+        .deserialize_monitor_metadata()
+        .unwrap();
+    assert_eq!(monitor_metadata.monitor_state, monitor_state);
+    assert_eq!(monitor_metadata.monitor_state.status, MonitorStatus::Ok);
+    assert_eq!(monitor_metadata.monitor_state.history.len(), 1);
+
+    cleanup_monitors(&mut metastore).await;
+}
+
+pub async fn test_metastore_delete_monitors<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_monitors(&mut metastore).await;
+
+    let delete_monitors_request = DeleteMonitorsRequest {
+        monitor_ids: vec!["monitor-not-found".to_string()],
+    };
+    metastore
+        .delete_monitors(delete_monitors_request)
+        .await
+        .unwrap();
+
+    let monitor_id_foo = append_random_suffix("test-delete-monitors-foo");
+    let monitor_id_bar = append_random_suffix("test-delete-monitors-bar");
+
+    for monitor_id in [&monitor_id_foo, &monitor_id_bar] {
+        let monitor_config = MonitorConfig::for_test(monitor_id, "test-index");
+        let create_monitor_request =
+            CreateMonitorRequest::try_from_monitor_config(&monitor_config, false).unwrap();
+        metastore
+            .create_monitor(create_monitor_request)
+            .await
+            .unwrap();
+    }
+    let delete_monitors_request = DeleteMonitorsRequest {
+        monitor_ids: vec![monitor_id_foo, "monitor-not-found".to_string()],
+    };
+    metastore
+        .delete_monitors(delete_monitors_request)
+        .await
+        .unwrap();
+
+    let monitors = list_all_monitors(&mut metastore).await.unwrap();
+    assert_eq!(monitors.len(), 1);
+    assert_eq!(monitors[0].monitor_id(), monitor_id_bar);
+
+    cleanup_monitors(&mut metastore).await;
+}
@@ -34,6 +34,10 @@ pub struct UpdateIndexRequest {
     pub indexing_settings_json: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub doc_mapping_json: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "6")]
+    pub rollup_policy_json: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "7")]
+    pub tiering_policy_json: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -538,6 +542,56 @@ pub struct DeleteIndexTemplatesRequest {
     pub template_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateMonitorRequest {
+    #[prost(string, tag = "1")]
+    pub monitor_config_json: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub overwrite: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMonitorRequest {
+    #[prost(string, tag = "1")]
+    pub monitor_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMonitorResponse {
+    #[prost(string, tag = "1")]
+    pub monitor_metadata_json: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListMonitorsRequest {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListMonitorsResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub monitors_metadata_json: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateMonitorStateRequest {
+    #[prost(string, tag = "1")]
+    pub monitor_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub monitor_state_json: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteMonitorsRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub monitor_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -559,6 +613,8 @@ pub enum SourceType {
     Vec = 10,
     Void = 11,
     Stdin = 13,
+    /// Documents read from the splits of another index
+    Reindex = 14,
 }
 impl SourceType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -580,6 +636,7 @@ impl SourceType {
             SourceType::Vec => "SOURCE_TYPE_VEC",
             SourceType::Void => "SOURCE_TYPE_VOID",
             SourceType::Stdin => "SOURCE_TYPE_STDIN",
+            SourceType::Reindex => "SOURCE_TYPE_REINDEX",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -598,6 +655,7 @@ impl SourceType {
             "SOURCE_TYPE_VEC" => Some(Self::Vec),
             "SOURCE_TYPE_VOID" => Some(Self::Void),
             "SOURCE_TYPE_STDIN" => Some(Self::Stdin),
+            "SOURCE_TYPE_REINDEX" => Some(Self::Reindex),
             _ => None,
         }
     }
@@ -799,6 +857,31 @@ impl RpcName for DeleteIndexTemplatesRequest {
         "delete_index_templates"
     }
 }
+impl RpcName for CreateMonitorRequest {
+    fn rpc_name() -> &'static str {
+        "create_monitor"
+    }
+}
+impl RpcName for GetMonitorRequest {
+    fn rpc_name() -> &'static str {
+        "get_monitor"
+    }
+}
+impl RpcName for ListMonitorsRequest {
+    fn rpc_name() -> &'static str {
+        "list_monitors"
+    }
+}
+impl RpcName for UpdateMonitorStateRequest {
+    fn rpc_name() -> &'static str {
+        "update_monitor_state"
+    }
+}
+impl RpcName for DeleteMonitorsRequest {
+    fn rpc_name() -> &'static str {
+        "delete_monitors"
+    }
+}
 pub type MetastoreServiceStream<T> = quickwit_common::ServiceStream<
     crate::metastore::MetastoreResult<T>,
 >;
@@ -976,6 +1059,31 @@ pub trait MetastoreService: std::fmt::Debug + Send + Sync + 'static {
         &self,
         request: DeleteIndexTemplatesRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Creates a monitor.
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Fetches a monitor.
+    async fn get_monitor(
+        &self,
+        request: GetMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<GetMonitorResponse>;
+    /// Returns all the monitors.
+    async fn list_monitors(
+        &self,
+        request: ListMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<ListMonitorsResponse>;
+    /// Updates the evaluation state and history of a monitor.
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Deletes monitors.
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
     async fn check_connectivity(&self) -> anyhow::Result<()>;
     fn endpoints(&self) -> Vec<quickwit_common::uri::Uri>;
 }
@@ -1256,6 +1364,36 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner.0.delete_index_templates(request).await
     }
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.create_monitor(request).await
+    }
+    async fn get_monitor(
+        &self,
+        request: GetMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<GetMonitorResponse> {
+        self.inner.0.get_monitor(request).await
+    }
+    async fn list_monitors(
+        &self,
+        request: ListMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<ListMonitorsResponse> {
+        self.inner.0.list_monitors(request).await
+    }
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.update_monitor_state(request).await
+    }
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.delete_monitors(request).await
+    }
     async fn check_connectivity(&self) -> anyhow::Result<()> {
         self.inner.0.check_connectivity().await
     }
@@ -1462,6 +1600,36 @@ pub mod mock_metastore_service {
         ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
             self.inner.lock().await.delete_index_templates(request).await
         }
+        async fn create_monitor(
+            &self,
+            request: super::CreateMonitorRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.create_monitor(request).await
+        }
+        async fn get_monitor(
+            &self,
+            request: super::GetMonitorRequest,
+        ) -> crate::metastore::MetastoreResult<super::GetMonitorResponse> {
+            self.inner.lock().await.get_monitor(request).await
+        }
+        async fn list_monitors(
+            &self,
+            request: super::ListMonitorsRequest,
+        ) -> crate::metastore::MetastoreResult<super::ListMonitorsResponse> {
+            self.inner.lock().await.list_monitors(request).await
+        }
+        async fn update_monitor_state(
+            &self,
+            request: super::UpdateMonitorStateRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.update_monitor_state(request).await
+        }
+        async fn delete_monitors(
+            &self,
+            request: super::DeleteMonitorsRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.delete_monitors(request).await
+        }
         async fn check_connectivity(&self) -> anyhow::Result<()> {
             self.inner.lock().await.check_connectivity().await
         }
@@ -1969,6 +2137,86 @@ impl tower::Service<DeleteIndexTemplatesRequest> for InnerMetastoreServiceClient
         Box::pin(fut)
     }
 }
+impl tower::Service<CreateMonitorRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: CreateMonitorRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.create_monitor(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<GetMonitorRequest> for InnerMetastoreServiceClient {
+    type Response = GetMonitorResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: GetMonitorRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.get_monitor(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<ListMonitorsRequest> for InnerMetastoreServiceClient {
+    type Response = ListMonitorsResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: ListMonitorsRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.list_monitors(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<UpdateMonitorStateRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: UpdateMonitorStateRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.update_monitor_state(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<DeleteMonitorsRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: DeleteMonitorsRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.delete_monitors(request).await };
+        Box::pin(fut)
+    }
+}
 /// A tower service stack is a set of tower services.
 #[derive(Debug)]
 struct MetastoreServiceTowerServiceStack {
@@ -2129,6 +2377,31 @@ struct MetastoreServiceTowerServiceStack {
         EmptyResponse,
         crate::metastore::MetastoreError,
     >,
+    create_monitor_svc: quickwit_common::tower::BoxService<
+        CreateMonitorRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    get_monitor_svc: quickwit_common::tower::BoxService<
+        GetMonitorRequest,
+        GetMonitorResponse,
+        crate::metastore::MetastoreError,
+    >,
+    list_monitors_svc: quickwit_common::tower::BoxService<
+        ListMonitorsRequest,
+        ListMonitorsResponse,
+        crate::metastore::MetastoreError,
+    >,
+    update_monitor_state_svc: quickwit_common::tower::BoxService<
+        UpdateMonitorStateRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    delete_monitors_svc: quickwit_common::tower::BoxService<
+        DeleteMonitorsRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
 }
 #[async_trait::async_trait]
 impl MetastoreService for MetastoreServiceTowerServiceStack {
@@ -2318,6 +2591,36 @@ impl MetastoreService for MetastoreServiceTowerServiceStack {
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.delete_index_templates_svc.clone().ready().await?.call(request).await
     }
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.create_monitor_svc.clone().ready().await?.call(request).await
+    }
+    async fn get_monitor(
+        &self,
+        request: GetMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<GetMonitorResponse> {
+        self.get_monitor_svc.clone().ready().await?.call(request).await
+    }
+    async fn list_monitors(
+        &self,
+        request: ListMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<ListMonitorsResponse> {
+        self.list_monitors_svc.clone().ready().await?.call(request).await
+    }
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.update_monitor_state_svc.clone().ready().await?.call(request).await
+    }
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.delete_monitors_svc.clone().ready().await?.call(request).await
+    }
     async fn check_connectivity(&self) -> anyhow::Result<()> {
         self.inner.0.check_connectivity().await
     }
@@ -2635,6 +2938,56 @@ type DeleteIndexTemplatesLayer = quickwit_common::tower::BoxLayer<
     EmptyResponse,
     crate::metastore::MetastoreError,
 >;
+type CreateMonitorLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        CreateMonitorRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    CreateMonitorRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
+type GetMonitorLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        GetMonitorRequest,
+        GetMonitorResponse,
+        crate::metastore::MetastoreError,
+    >,
+    GetMonitorRequest,
+    GetMonitorResponse,
+    crate::metastore::MetastoreError,
+>;
+type ListMonitorsLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        ListMonitorsRequest,
+        ListMonitorsResponse,
+        crate::metastore::MetastoreError,
+    >,
+    ListMonitorsRequest,
+    ListMonitorsResponse,
+    crate::metastore::MetastoreError,
+>;
+type UpdateMonitorStateLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        UpdateMonitorStateRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    UpdateMonitorStateRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
+type DeleteMonitorsLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        DeleteMonitorsRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    DeleteMonitorsRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
 #[derive(Debug, Default)]
 pub struct MetastoreServiceTowerLayerStack {
     create_index_layers: Vec<CreateIndexLayer>,
@@ -2668,6 +3021,11 @@ pub struct MetastoreServiceTowerLayerStack {
     find_index_template_matches_layers: Vec<FindIndexTemplateMatchesLayer>,
     list_index_templates_layers: Vec<ListIndexTemplatesLayer>,
     delete_index_templates_layers: Vec<DeleteIndexTemplatesLayer>,
+    create_monitor_layers: Vec<CreateMonitorLayer>,
+    get_monitor_layers: Vec<GetMonitorLayer>,
+    list_monitors_layers: Vec<ListMonitorsLayer>,
+    update_monitor_state_layers: Vec<UpdateMonitorStateLayer>,
+    delete_monitors_layers: Vec<DeleteMonitorsLayer>,
 }
 impl MetastoreServiceTowerLayerStack {
     pub fn stack_layer<L>(mut self, layer: L) -> Self
@@ -3463,56 +3821,183 @@ impl MetastoreServiceTowerLayerStack {
         >>::Service as tower::Service<
             DeleteIndexTemplatesRequest,
         >>::Future: Send + 'static,
-    {
-        self.create_index_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.update_index_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.index_metadata_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.indexes_metadata_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.list_indexes_metadata_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.delete_index_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.list_splits_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.stage_splits_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.publish_splits_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.mark_splits_for_deletion_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.delete_splits_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.add_source_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.update_source_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.toggle_source_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.delete_source_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.reset_source_checkpoint_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.last_delete_opstamp_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.create_delete_task_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.update_splits_delete_opstamp_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.list_delete_tasks_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.list_stale_splits_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.open_shards_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.acquire_shards_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.delete_shards_layers
-            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
-        self.prune_shards_layers
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    CreateMonitorRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                CreateMonitorRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                CreateMonitorRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                CreateMonitorRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<CreateMonitorRequest>>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    GetMonitorRequest,
+                    GetMonitorResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                GetMonitorRequest,
+                GetMonitorResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                GetMonitorRequest,
+                Response = GetMonitorResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                GetMonitorRequest,
+                GetMonitorResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<GetMonitorRequest>>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    ListMonitorsRequest,
+                    ListMonitorsResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                ListMonitorsRequest,
+                ListMonitorsResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                ListMonitorsRequest,
+                Response = ListMonitorsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                ListMonitorsRequest,
+                ListMonitorsResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<ListMonitorsRequest>>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    UpdateMonitorStateRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                UpdateMonitorStateRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                UpdateMonitorStateRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                UpdateMonitorStateRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<
+            UpdateMonitorStateRequest,
+        >>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    DeleteMonitorsRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                DeleteMonitorsRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                DeleteMonitorsRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                DeleteMonitorsRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<DeleteMonitorsRequest>>::Future: Send + 'static,
+    {
+        self.create_index_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.update_index_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.index_metadata_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.indexes_metadata_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_indexes_metadata_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_index_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_splits_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.stage_splits_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.publish_splits_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.mark_splits_for_deletion_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_splits_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.add_source_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.update_source_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.toggle_source_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_source_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.reset_source_checkpoint_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.last_delete_opstamp_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.create_delete_task_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.update_splits_delete_opstamp_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_delete_tasks_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_stale_splits_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.open_shards_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.acquire_shards_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_shards_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.prune_shards_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.list_shards_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
@@ -3526,6 +4011,16 @@ impl MetastoreServiceTowerLayerStack {
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.delete_index_templates_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.create_monitor_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.get_monitor_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_monitors_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.update_monitor_state_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_monitors_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self
     }
     pub fn stack_create_index_layer<L>(mut self, layer: L) -> Self
@@ -4144,6 +4639,104 @@ impl MetastoreServiceTowerLayerStack {
             .push(quickwit_common::tower::BoxLayer::new(layer));
         self
     }
+    pub fn stack_create_monitor_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    CreateMonitorRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                CreateMonitorRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<CreateMonitorRequest>>::Future: Send + 'static,
+    {
+        self.create_monitor_layers.push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_get_monitor_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    GetMonitorRequest,
+                    GetMonitorResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetMonitorRequest,
+                Response = GetMonitorResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetMonitorRequest>>::Future: Send + 'static,
+    {
+        self.get_monitor_layers.push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_list_monitors_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    ListMonitorsRequest,
+                    ListMonitorsResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                ListMonitorsRequest,
+                Response = ListMonitorsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListMonitorsRequest>>::Future: Send + 'static,
+    {
+        self.list_monitors_layers.push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_update_monitor_state_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    UpdateMonitorStateRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                UpdateMonitorStateRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            UpdateMonitorStateRequest,
+        >>::Future: Send + 'static,
+    {
+        self.update_monitor_state_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_delete_monitors_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    DeleteMonitorsRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                DeleteMonitorsRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<DeleteMonitorsRequest>>::Future: Send + 'static,
+    {
+        self.delete_monitors_layers.push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
     pub fn build<T>(self, instance: T) -> MetastoreServiceClient
     where
         T: MetastoreService,
@@ -4448,6 +5041,46 @@ impl MetastoreServiceTowerLayerStack {
                 quickwit_common::tower::BoxService::new(inner_client.clone()),
                 |svc, layer| layer.layer(svc),
             );
+        let create_monitor_svc = self
+            .create_monitor_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let get_monitor_svc = self
+            .get_monitor_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let list_monitors_svc = self
+            .list_monitors_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let update_monitor_state_svc = self
+            .update_monitor_state_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let delete_monitors_svc = self
+            .delete_monitors_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
         let tower_svc_stack = MetastoreServiceTowerServiceStack {
             inner: inner_client,
             create_index_svc,
@@ -4481,6 +5114,11 @@ impl MetastoreServiceTowerLayerStack {
             find_index_template_matches_svc,
             list_index_templates_svc,
             delete_index_templates_svc,
+            create_monitor_svc,
+            get_monitor_svc,
+            list_monitors_svc,
+            update_monitor_state_svc,
+            delete_monitors_svc,
         };
         MetastoreServiceClient::new(tower_svc_stack)
     }
@@ -4759,7 +5397,37 @@ where
             >,
         >
         + tower::Service<
-            DeleteIndexTemplatesRequest,
+            DeleteIndexTemplatesRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            CreateMonitorRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            GetMonitorRequest,
+            Response = GetMonitorResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<GetMonitorResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            ListMonitorsRequest,
+            Response = ListMonitorsResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<ListMonitorsResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            UpdateMonitorStateRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            DeleteMonitorsRequest,
             Response = EmptyResponse,
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
@@ -4951,6 +5619,36 @@ where
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.clone().call(request).await
     }
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
+    async fn get_monitor(
+        &self,
+        request: GetMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<GetMonitorResponse> {
+        self.clone().call(request).await
+    }
+    async fn list_monitors(
+        &self,
+        request: ListMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<ListMonitorsResponse> {
+        self.clone().call(request).await
+    }
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
     async fn check_connectivity(&self) -> anyhow::Result<()> {
         if self.inner.is_disconnected() {
             anyhow::bail!("actor `{}` is disconnected", self.inner.actor_instance_id())
@@ -5440,6 +6138,76 @@ where
                 DeleteIndexTemplatesRequest::rpc_name(),
             ))
     }
+    async fn create_monitor(
+        &self,
+        request: CreateMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .create_monitor(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                CreateMonitorRequest::rpc_name(),
+            ))
+    }
+    async fn get_monitor(
+        &self,
+        request: GetMonitorRequest,
+    ) -> crate::metastore::MetastoreResult<GetMonitorResponse> {
+        self.inner
+            .clone()
+            .get_monitor(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                GetMonitorRequest::rpc_name(),
+            ))
+    }
+    async fn list_monitors(
+        &self,
+        request: ListMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<ListMonitorsResponse> {
+        self.inner
+            .clone()
+            .list_monitors(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                ListMonitorsRequest::rpc_name(),
+            ))
+    }
+    async fn update_monitor_state(
+        &self,
+        request: UpdateMonitorStateRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .update_monitor_state(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                UpdateMonitorStateRequest::rpc_name(),
+            ))
+    }
+    async fn delete_monitors(
+        &self,
+        request: DeleteMonitorsRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .delete_monitors(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                DeleteMonitorsRequest::rpc_name(),
+            ))
+    }
     async fn check_connectivity(&self) -> anyhow::Result<()> {
         if self.connection_addrs_rx.borrow().len() == 0 {
             anyhow::bail!("no server currently available")
@@ -5819,6 +6587,61 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(crate::error::grpc_error_to_grpc_status)
     }
+    async fn create_monitor(
+        &self,
+        request: tonic::Request<CreateMonitorRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .create_monitor(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn get_monitor(
+        &self,
+        request: tonic::Request<GetMonitorRequest>,
+    ) -> Result<tonic::Response<GetMonitorResponse>, tonic::Status> {
+        self.inner
+            .0
+            .get_monitor(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn list_monitors(
+        &self,
+        request: tonic::Request<ListMonitorsRequest>,
+    ) -> Result<tonic::Response<ListMonitorsResponse>, tonic::Status> {
+        self.inner
+            .0
+            .list_monitors(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn update_monitor_state(
+        &self,
+        request: tonic::Request<UpdateMonitorStateRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .update_monitor_state(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn delete_monitors(
+        &self,
+        request: tonic::Request<DeleteMonitorsRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .delete_monitors(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
 }
 /// Generated client implementations.
 pub mod metastore_service_grpc_client {
@@ -6685,11 +7508,157 @@ pub mod metastore_service_grpc_client {
         }
         pub async fn list_shards(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListShardsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListShardsResponse>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::ListShardsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListShardsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/ListShards",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("quickwit.metastore.MetastoreService", "ListShards"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Creates an index template.
+        pub async fn create_index_template(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateIndexTemplateRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/CreateIndexTemplate",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "CreateIndexTemplate",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Fetches an index template.
+        pub async fn get_index_template(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetIndexTemplateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetIndexTemplateResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/GetIndexTemplate",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "GetIndexTemplate",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Finds matching index templates.
+        pub async fn find_index_template_matches(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FindIndexTemplateMatchesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FindIndexTemplateMatchesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/FindIndexTemplateMatches",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "FindIndexTemplateMatches",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Returns all the index templates.
+        pub async fn list_index_templates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListIndexTemplatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexTemplatesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/ListIndexTemplates",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "ListIndexTemplates",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Deletes index templates.
+        pub async fn delete_index_templates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteIndexTemplatesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -6701,19 +7670,22 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/ListShards",
+                "/quickwit.metastore.MetastoreService/DeleteIndexTemplates",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
-                    GrpcMethod::new("quickwit.metastore.MetastoreService", "ListShards"),
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "DeleteIndexTemplates",
+                    ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Creates an index template.
-        pub async fn create_index_template(
+        /// Creates a monitor.
+        pub async fn create_monitor(
             &mut self,
-            request: impl tonic::IntoRequest<super::CreateIndexTemplateRequest>,
+            request: impl tonic::IntoRequest<super::CreateMonitorRequest>,
         ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
             self.inner
                 .ready()
@@ -6726,24 +7698,24 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/CreateIndexTemplate",
+                "/quickwit.metastore.MetastoreService/CreateMonitor",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "quickwit.metastore.MetastoreService",
-                        "CreateIndexTemplate",
+                        "CreateMonitor",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Fetches an index template.
-        pub async fn get_index_template(
+        /// Fetches a monitor.
+        pub async fn get_monitor(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetIndexTemplateRequest>,
+            request: impl tonic::IntoRequest<super::GetMonitorRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetIndexTemplateResponse>,
+            tonic::Response<super::GetMonitorResponse>,
             tonic::Status,
         > {
             self.inner
@@ -6757,24 +7729,21 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/GetIndexTemplate",
+                "/quickwit.metastore.MetastoreService/GetMonitor",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
-                    GrpcMethod::new(
-                        "quickwit.metastore.MetastoreService",
-                        "GetIndexTemplate",
-                    ),
+                    GrpcMethod::new("quickwit.metastore.MetastoreService", "GetMonitor"),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Finds matching index templates.
-        pub async fn find_index_template_matches(
+        /// Returns all the monitors.
+        pub async fn list_monitors(
             &mut self,
-            request: impl tonic::IntoRequest<super::FindIndexTemplateMatchesRequest>,
+            request: impl tonic::IntoRequest<super::ListMonitorsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::FindIndexTemplateMatchesResponse>,
+            tonic::Response<super::ListMonitorsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -6788,26 +7757,23 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/FindIndexTemplateMatches",
+                "/quickwit.metastore.MetastoreService/ListMonitors",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "quickwit.metastore.MetastoreService",
-                        "FindIndexTemplateMatches",
+                        "ListMonitors",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Returns all the index templates.
-        pub async fn list_index_templates(
+        /// Updates the evaluation state and history of a monitor.
+        pub async fn update_monitor_state(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListIndexTemplatesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListIndexTemplatesResponse>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::UpdateMonitorStateRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -6819,22 +7785,22 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/ListIndexTemplates",
+                "/quickwit.metastore.MetastoreService/UpdateMonitorState",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "quickwit.metastore.MetastoreService",
-                        "ListIndexTemplates",
+                        "UpdateMonitorState",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Deletes index templates.
-        pub async fn delete_index_templates(
+        /// Deletes monitors.
+        pub async fn delete_monitors(
             &mut self,
-            request: impl tonic::IntoRequest<super::DeleteIndexTemplatesRequest>,
+            request: impl tonic::IntoRequest<super::DeleteMonitorsRequest>,
         ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
             self.inner
                 .ready()
@@ -6847,14 +7813,14 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/DeleteIndexTemplates",
+                "/quickwit.metastore.MetastoreService/DeleteMonitors",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "quickwit.metastore.MetastoreService",
-                        "DeleteIndexTemplates",
+                        "DeleteMonitors",
                     ),
                 );
             self.inner.unary(req, path, codec).await
@@ -7093,6 +8059,37 @@ pub mod metastore_service_grpc_server {
             &self,
             request: tonic::Request<super::DeleteIndexTemplatesRequest>,
         ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Creates a monitor.
+        async fn create_monitor(
+            &self,
+            request: tonic::Request<super::CreateMonitorRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Fetches a monitor.
+        async fn get_monitor(
+            &self,
+            request: tonic::Request<super::GetMonitorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetMonitorResponse>,
+            tonic::Status,
+        >;
+        /// Returns all the monitors.
+        async fn list_monitors(
+            &self,
+            request: tonic::Request<super::ListMonitorsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListMonitorsResponse>,
+            tonic::Status,
+        >;
+        /// Updates the evaluation state and history of a monitor.
+        async fn update_monitor_state(
+            &self,
+            request: tonic::Request<super::UpdateMonitorStateRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Deletes monitors.
+        async fn delete_monitors(
+            &self,
+            request: tonic::Request<super::DeleteMonitorsRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
     }
     /// Metastore meant to manage Quickwit's indexes, their splits and delete tasks.
     ///
@@ -8648,6 +9645,234 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/CreateMonitor" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateMonitorSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::CreateMonitorRequest>
+                    for CreateMonitorSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateMonitorRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).create_monitor(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateMonitorSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/GetMonitor" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetMonitorSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::GetMonitorRequest>
+                    for GetMonitorSvc<T> {
+                        type Response = super::GetMonitorResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetMonitorRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).get_monitor(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetMonitorSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/ListMonitors" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListMonitorsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::ListMonitorsRequest>
+                    for ListMonitorsSvc<T> {
+                        type Response = super::ListMonitorsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListMonitorsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).list_monitors(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListMonitorsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/UpdateMonitorState" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateMonitorStateSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::UpdateMonitorStateRequest>
+                    for UpdateMonitorStateSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateMonitorStateRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).update_monitor_state(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateMonitorStateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/DeleteMonitors" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteMonitorsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::DeleteMonitorsRequest>
+                    for DeleteMonitorsSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteMonitorsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).delete_monitors(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteMonitorsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -48,6 +48,12 @@ pub struct ReportSplit {
     /// The storage uri. This URI does NOT include the split id.
     #[prost(string, tag = "1")]
     pub storage_uri: ::prost::alloc::string::String,
+    /// Offsets of the hotcache and footer of the split. Only set for published splits, so that the
+    /// searcher owning the split can warm its footer.
+    #[prost(uint64, optional, tag = "3")]
+    pub split_footer_start: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub split_footer_end: ::core::option::Option<u64>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -191,6 +197,15 @@ pub struct SearchRequest {
     pub search_after: ::core::option::Option<PartialHit>,
     #[prost(enumeration = "CountHits", tag = "17")]
     pub count_hits: i32,
+    /// Paths of the fields of the hits to fetch. Only the top-level fields that are a prefix of one
+    /// of these paths are read from the splits written with the columnar doc store layout. Hits may
+    /// contain additional fields. If empty, the whole documents are fetched.
+    #[prost(string, repeated, tag = "18")]
+    pub fetch_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// json serialized runtime fields, computed at query time from the fast fields of
+    /// the documents.
+    #[prost(string, optional, tag = "19")]
+    pub runtime_mappings: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Eq, Hash)]
@@ -340,6 +355,14 @@ pub struct SplitIdAndFooterOffsets {
     /// The number of docs in the split
     #[prost(uint64, tag = "6")]
     pub num_docs: u64,
+    /// The URI of the storage holding the split, if it differs from the index URI.
+    /// This is the case for splits moved to a storage tier by the janitor.
+    #[prost(string, optional, tag = "7")]
+    pub storage_uri: ::core::option::Option<::prost::alloc::string::String>,
+    /// Whether the index requires its splits to be encrypted, in which case the split is rejected
+    /// if it was written in clear.
+    #[prost(bool, tag = "8")]
+    pub require_encryption: bool,
 }
 /// Hits returned by a FetchDocRequest.
 ///
@@ -505,6 +528,9 @@ pub struct FetchDocsRequest {
     /// `DocMapper` as json serialized trait.
     #[prost(string, tag = "6")]
     pub doc_mapper: ::prost::alloc::string::String,
+    /// Paths of the fields to fetch. See `SearchRequest.fetch_fields`.
+    #[prost(string, repeated, tag = "8")]
+    pub fetch_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -17,8 +17,8 @@
 use quickwit_common::pubsub::Event;
 
 use super::{
-    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteSourceRequest, SourceType,
-    ToggleSourceRequest,
+    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteSourceRequest,
+    PublishSplitsRequest, SourceType, ToggleSourceRequest,
 };
 use crate::types::{IndexUid, SourceId};
 
@@ -64,4 +64,5 @@ impl Event for AddSourceRequest {}
 impl Event for CreateIndexRequest {}
 impl Event for DeleteIndexRequest {}
 impl Event for DeleteSourceRequest {}
+impl Event for PublishSplitsRequest {}
 impl Event for ToggleSourceRequest {}
@@ -76,6 +76,11 @@ pub enum EntityKind {
         /// Index template ID.
         template_id: String,
     },
+    /// A monitor.
+    Monitor {
+        /// Monitor ID.
+        monitor_id: String,
+    },
 }
 
 impl fmt::Display for EntityKind {
@@ -97,6 +102,7 @@ impl fmt::Display for EntityKind {
             EntityKind::IndexTemplate { template_id } => {
                 write!(f, "index template `{}`", template_id)
             }
+            EntityKind::Monitor { monitor_id } => write!(f, "monitor `{monitor_id}`"),
         }
     }
 }
@@ -301,6 +307,7 @@ impl SourceType {
             SourceType::Nats => "nats",
             SourceType::PubSub => "pubsub",
             SourceType::Pulsar => "pulsar",
+            SourceType::Reindex => "reindex",
             SourceType::Stdin => "stdin",
             SourceType::Unspecified => "unspecified",
             SourceType::Vec => "vec",
@@ -321,6 +328,7 @@ impl fmt::Display for SourceType {
             SourceType::Nats => "NATS",
             SourceType::PubSub => "Google Cloud Pub/Sub",
             SourceType::Pulsar => "Apache Pulsar",
+            SourceType::Reindex => "reindex",
             SourceType::Stdin => "Stdin",
             SourceType::Unspecified => "unspecified",
             SourceType::Vec => "vec",
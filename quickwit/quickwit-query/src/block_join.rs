@@ -0,0 +1,527 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queries on the hidden documents indexed for `nested` fields.
+//!
+//! Each object of a `nested` field is indexed as a hidden document, right before the document it
+//! belongs to. The hidden documents store the path of their `nested` field in the
+//! [`NESTED_PATH_FIELD_NAME`] fast field, that root documents do not have. A root document and
+//! its nested documents form a block of consecutive documents that merges preserve.
+
+use quickwit_common::shared_consts::NESTED_PATH_FIELD_NAME;
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::StrColumn;
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TantivyError, Term, TERMINATED};
+
+/// Reads the nested path of the documents of a segment.
+#[derive(Clone)]
+pub struct NestedPathColumn {
+    column: StrColumn,
+}
+
+impl NestedPathColumn {
+    /// Opens the nested path column of a segment. Returns `None` if the segment does not contain
+    /// any nested document.
+    pub fn open(reader: &SegmentReader) -> tantivy::Result<Option<NestedPathColumn>> {
+        let column_opt = reader.fast_fields().str(NESTED_PATH_FIELD_NAME)?;
+        Ok(column_opt.map(|column| NestedPathColumn { column }))
+    }
+
+    /// Returns the ordinal of `path` in the column dictionary, or `None` if the segment does not
+    /// contain any nested document for this path.
+    pub fn path_ord(&self, path: &str) -> tantivy::Result<Option<u64>> {
+        Ok(self.column.dictionary().term_ord(path)?)
+    }
+
+    /// Returns the ordinal of the nested path of a document, or `None` for root documents.
+    pub fn doc_path_ord(&self, doc: DocId) -> Option<u64> {
+        self.column.term_ords(doc).next()
+    }
+
+    pub fn is_root(&self, doc: DocId) -> bool {
+        self.doc_path_ord(doc).is_none()
+    }
+
+    /// Returns the first document of the block ending with the root document `root_doc`.
+    pub fn block_start(&self, root_doc: DocId) -> DocId {
+        let mut block_start = root_doc;
+        while block_start > 0 && !self.is_root(block_start - 1) {
+            block_start -= 1;
+        }
+        block_start
+    }
+}
+
+/// Returns the number of alive root documents of a segment, which is the number of documents
+/// as seen by users.
+pub fn num_root_docs(reader: &SegmentReader) -> tantivy::Result<u32> {
+    let Some(column) = NestedPathColumn::open(reader)? else {
+        return Ok(reader.num_docs());
+    };
+    let num_root_docs = reader
+        .doc_ids_alive()
+        .filter(|&doc| column.is_root(doc))
+        .count();
+    Ok(num_root_docs as u32)
+}
+
+/// Returns the paths of the nested documents that can be the parent of a nested document of path
+/// `path`. For instance, the parent of a `order.items` nested document is either a root document
+/// or a `order` nested document.
+fn parent_paths(path: &str) -> Vec<&str> {
+    path.match_indices('.')
+        .map(|(dot_position, _)| &path[..dot_position])
+        .collect()
+}
+
+/// Identifies the nested documents of a given path and their parents in a segment.
+struct NestedLevel {
+    column: NestedPathColumn,
+    child_path_ord: Option<u64>,
+    parent_path_ords: Vec<u64>,
+}
+
+impl NestedLevel {
+    fn open(reader: &SegmentReader, path: &str) -> tantivy::Result<Option<NestedLevel>> {
+        let Some(column) = NestedPathColumn::open(reader)? else {
+            return Ok(None);
+        };
+        let child_path_ord = column.path_ord(path)?;
+        let mut parent_path_ords = Vec::new();
+        for parent_path in parent_paths(path) {
+            if let Some(parent_path_ord) = column.path_ord(parent_path)? {
+                parent_path_ords.push(parent_path_ord);
+            }
+        }
+        Ok(Some(NestedLevel {
+            column,
+            child_path_ord,
+            parent_path_ords,
+        }))
+    }
+
+    fn is_child(&self, doc: DocId) -> bool {
+        self.child_path_ord.is_some() && self.column.doc_path_ord(doc) == self.child_path_ord
+    }
+
+    fn is_parent(&self, doc: DocId) -> bool {
+        match self.column.doc_path_ord(doc) {
+            Some(path_ord) => self.parent_path_ords.contains(&path_ord),
+            None => true,
+        }
+    }
+}
+
+/// How the scores of the matching nested documents are combined into the score of their parent.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NestedScoreMode {
+    #[default]
+    Avg,
+    Max,
+    Min,
+    Sum,
+    /// Parents get a score of 0.
+    None,
+}
+
+/// Matches the parents of the nested documents of path `path` matching `child_query`.
+#[derive(Debug)]
+pub struct NestedBlockJoinQuery {
+    pub child_query: Box<dyn Query>,
+    pub path: String,
+    pub score_mode: NestedScoreMode,
+}
+
+impl Clone for NestedBlockJoinQuery {
+    fn clone(&self) -> Self {
+        NestedBlockJoinQuery {
+            child_query: self.child_query.box_clone(),
+            path: self.path.clone(),
+            score_mode: self.score_mode,
+        }
+    }
+}
+
+impl Query for NestedBlockJoinQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let child_weight = self.child_query.weight(enable_scoring)?;
+        Ok(Box::new(NestedBlockJoinWeight {
+            child_weight,
+            path: self.path.clone(),
+            score_mode: self.score_mode,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.child_query.query_terms(visitor);
+    }
+}
+
+struct NestedBlockJoinWeight {
+    child_weight: Box<dyn Weight>,
+    path: String,
+    score_mode: NestedScoreMode,
+}
+
+impl Weight for NestedBlockJoinWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let Some(level) = NestedLevel::open(reader, &self.path)? else {
+            return Ok(Box::new(tantivy::query::EmptyScorer));
+        };
+        if level.child_path_ord.is_none() {
+            return Ok(Box::new(tantivy::query::EmptyScorer));
+        }
+        let child_scorer = self.child_weight.scorer(reader, boost)?;
+        let mut scorer = NestedBlockJoinScorer {
+            child_scorer,
+            level,
+            max_doc: reader.max_doc(),
+            score_mode: self.score_mode,
+            doc: TERMINATED,
+            score: 0.0,
+        };
+        scorer.doc = scorer.next_parent();
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("NestedQuery", scorer.score()))
+    }
+}
+
+struct NestedBlockJoinScorer {
+    child_scorer: Box<dyn Scorer>,
+    level: NestedLevel,
+    max_doc: DocId,
+    score_mode: NestedScoreMode,
+    doc: DocId,
+    score: Score,
+}
+
+impl NestedBlockJoinScorer {
+    /// Returns the parent of the next matching child, and leaves the child scorer on the first
+    /// document following this parent.
+    fn next_parent(&mut self) -> DocId {
+        loop {
+            let child_doc = self.child_scorer.doc();
+            if child_doc == TERMINATED {
+                return TERMINATED;
+            }
+            if !self.level.is_child(child_doc) {
+                self.child_scorer.advance();
+                continue;
+            }
+            let Some(parent_doc) =
+                (child_doc + 1..self.max_doc).find(|&doc| self.level.is_parent(doc))
+            else {
+                // Orphan nested documents can only come from a corrupted block.
+                return TERMINATED;
+            };
+            let mut num_children = 0;
+            let mut score: Score = match self.score_mode {
+                NestedScoreMode::Max => Score::MIN,
+                NestedScoreMode::Min => Score::MAX,
+                NestedScoreMode::Avg | NestedScoreMode::Sum | NestedScoreMode::None => 0.0,
+            };
+            let mut doc = child_doc;
+            while doc < parent_doc {
+                if self.level.is_child(doc) {
+                    let child_score = self.child_scorer.score();
+                    num_children += 1;
+                    match self.score_mode {
+                        NestedScoreMode::Avg | NestedScoreMode::Sum => score += child_score,
+                        NestedScoreMode::Max => score = score.max(child_score),
+                        NestedScoreMode::Min => score = score.min(child_score),
+                        NestedScoreMode::None => {}
+                    }
+                }
+                doc = self.child_scorer.advance();
+            }
+            if self.score_mode == NestedScoreMode::Avg {
+                score /= num_children as Score;
+            }
+            self.score = score;
+            if doc == parent_doc {
+                self.child_scorer.advance();
+            }
+            return parent_doc;
+        }
+    }
+}
+
+impl DocSet for NestedBlockJoinScorer {
+    fn advance(&mut self) -> DocId {
+        self.doc = self.next_parent();
+        self.doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        // The children of the parents following `target` come after the parent preceding
+        // `target`.
+        let mut children_start = target;
+        while children_start > 0 && !self.level.is_parent(children_start - 1) {
+            children_start -= 1;
+        }
+        if self.child_scorer.doc() < children_start {
+            self.child_scorer.seek(children_start);
+        }
+        self.advance()
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.child_scorer.size_hint()
+    }
+}
+
+impl Scorer for NestedBlockJoinScorer {
+    fn score(&mut self) -> Score {
+        self.score
+    }
+}
+
+/// Matches the root documents matching `query`, leaving out nested documents.
+///
+/// All the queries run against splits containing nested documents are wrapped in this query so
+/// that nested documents never show up in search results.
+#[derive(Debug)]
+pub struct RootDocsQuery {
+    pub query: Box<dyn Query>,
+}
+
+impl Clone for RootDocsQuery {
+    fn clone(&self) -> Self {
+        RootDocsQuery {
+            query: self.query.box_clone(),
+        }
+    }
+}
+
+impl Query for RootDocsQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        Ok(Box::new(RootDocsWeight { weight }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+}
+
+struct RootDocsWeight {
+    weight: Box<dyn Weight>,
+}
+
+impl Weight for RootDocsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader, boost)?;
+        let Some(column) = NestedPathColumn::open(reader)? else {
+            return Ok(scorer);
+        };
+        let mut root_docs_scorer = RootDocsScorer { scorer, column };
+        let doc = root_docs_scorer.doc();
+        if doc != TERMINATED && !root_docs_scorer.column.is_root(doc) {
+            root_docs_scorer.advance();
+        }
+        Ok(Box::new(root_docs_scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        if let Some(column) = NestedPathColumn::open(reader)? {
+            if !column.is_root(doc) {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Document #({doc}) does not match"
+                )));
+            }
+        }
+        self.weight.explain(reader, doc)
+    }
+}
+
+struct RootDocsScorer {
+    scorer: Box<dyn Scorer>,
+    column: NestedPathColumn,
+}
+
+impl DocSet for RootDocsScorer {
+    fn advance(&mut self) -> DocId {
+        loop {
+            let doc = self.scorer.advance();
+            if doc == TERMINATED || self.column.is_root(doc) {
+                return doc;
+            }
+        }
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        let doc = self.scorer.seek(target);
+        if doc == TERMINATED || self.column.is_root(doc) {
+            return doc;
+        }
+        self.advance()
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for RootDocsScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
+
+/// Matches the root documents matching `query` and all their nested documents.
+///
+/// Deletions must remove whole blocks: nested documents left behind would otherwise be attached
+/// to the next root document when deleted documents get dropped by a merge.
+#[derive(Debug)]
+pub struct WithNestedDocsQuery {
+    pub query: Box<dyn Query>,
+}
+
+impl Clone for WithNestedDocsQuery {
+    fn clone(&self) -> Self {
+        WithNestedDocsQuery {
+            query: self.query.box_clone(),
+        }
+    }
+}
+
+impl Query for WithNestedDocsQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        Ok(Box::new(WithNestedDocsWeight { weight }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+}
+
+struct WithNestedDocsWeight {
+    weight: Box<dyn Weight>,
+}
+
+impl Weight for WithNestedDocsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let root_scorer = self.weight.scorer(reader, boost)?;
+        let Some(column) = NestedPathColumn::open(reader)? else {
+            return Ok(root_scorer);
+        };
+        let mut scorer = WithNestedDocsScorer {
+            root_scorer,
+            column,
+            doc: TERMINATED,
+        };
+        let root_doc = scorer.root_scorer.doc();
+        scorer.start_block(root_doc);
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("WithNestedDocsQuery", scorer.score()))
+    }
+}
+
+struct WithNestedDocsScorer {
+    root_scorer: Box<dyn Scorer>,
+    column: NestedPathColumn,
+    doc: DocId,
+}
+
+impl WithNestedDocsScorer {
+    /// Positions the scorer on the first document of the block of the first matching root
+    /// document starting from `root_doc`.
+    fn start_block(&mut self, mut root_doc: DocId) {
+        while root_doc != TERMINATED && !self.column.is_root(root_doc) {
+            root_doc = self.root_scorer.advance();
+        }
+        self.doc = if root_doc == TERMINATED {
+            TERMINATED
+        } else {
+            self.column.block_start(root_doc)
+        };
+    }
+}
+
+impl DocSet for WithNestedDocsScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        if self.doc < self.root_scorer.doc() {
+            self.doc += 1;
+        } else {
+            let root_doc = self.root_scorer.advance();
+            self.start_block(root_doc);
+        }
+        self.doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc >= target {
+            return self.doc;
+        }
+        if target <= self.root_scorer.doc() {
+            self.doc = target;
+            return self.doc;
+        }
+        let root_doc = self.root_scorer.seek(target);
+        self.start_block(root_doc);
+        if self.doc != TERMINATED && self.doc < target {
+            self.doc = target;
+        }
+        self.doc
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.root_scorer.size_hint()
+    }
+}
+
+impl Scorer for WithNestedDocsScorer {
+    fn score(&mut self) -> Score {
+        1.0
+    }
+}
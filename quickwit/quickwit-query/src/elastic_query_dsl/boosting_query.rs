@@ -0,0 +1,117 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+/// `BoostingQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-boosting-query.html>
+///
+/// Documents matching `positive` are returned. Among them, the score of the ones also matching
+/// `negative` is multiplied by `negative_boost`.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BoostingQuery {
+    positive: Box<ElasticQueryDslInner>,
+    negative: Box<ElasticQueryDslInner>,
+    negative_boost: NotNaNf32,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<BoostingQuery> for ElasticQueryDslInner {
+    fn from(boosting_query: BoostingQuery) -> Self {
+        Self::Boosting(boosting_query)
+    }
+}
+
+impl ConvertibleToQueryAst for BoostingQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let positive_ast = self.positive.convert_to_query_ast()?;
+        let negative_ast = self.negative.convert_to_query_ast()?;
+        // The two clauses are mutually exclusive, so the union gives each matching document
+        // either the positive score or the demoted positive score.
+        let positive_only_ast: QueryAst = query_ast::BoolQuery {
+            must: vec![positive_ast.clone()],
+            must_not: vec![negative_ast.clone()],
+            ..Default::default()
+        }
+        .into();
+        let demoted_ast: QueryAst = query_ast::BoolQuery {
+            must: vec![positive_ast],
+            filter: vec![negative_ast],
+            ..Default::default()
+        }
+        .into();
+        let bool_query_ast: QueryAst = query_ast::BoolQuery {
+            should: vec![
+                positive_only_ast,
+                demoted_ast.boost(Some(self.negative_boost)),
+            ],
+            ..Default::default()
+        }
+        .into();
+        Ok(bool_query_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boosting_query() {
+        let boosting_query: BoostingQuery = serde_json::from_str(
+            r#"{
+                "positive": { "term": { "text": "apple" } },
+                "negative": { "term": { "text": "pie" } },
+                "negative_boost": 0.5
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::Bool(bool_query) = boosting_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(bool_query.should.len(), 2);
+        let QueryAst::Bool(positive_only) = &bool_query.should[0] else {
+            panic!()
+        };
+        assert_eq!(positive_only.must.len(), 1);
+        assert_eq!(positive_only.must_not.len(), 1);
+        let QueryAst::Boost { underlying, boost } = &bool_query.should[1] else {
+            panic!()
+        };
+        assert_eq!(*boost, NotNaNf32::try_from(0.5f32).unwrap());
+        let QueryAst::Bool(demoted) = &**underlying else {
+            panic!()
+        };
+        assert_eq!(demoted.must.len(), 1);
+        assert_eq!(demoted.filter.len(), 1);
+    }
+
+    #[test]
+    fn test_boosting_query_requires_negative_boost() {
+        let error = serde_json::from_str::<BoostingQuery>(
+            r#"{
+                "positive": { "term": { "text": "apple" } },
+                "negative": { "term": { "text": "pie" } }
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("negative_boost"));
+    }
+}
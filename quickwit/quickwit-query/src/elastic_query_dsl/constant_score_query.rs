@@ -0,0 +1,73 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+/// `ConstantScoreQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-constant-score-query.html>
+///
+/// The filter is converted into a boolean filter clause, which does not contribute to the score.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConstantScoreQuery {
+    filter: Box<ElasticQueryDslInner>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<ConstantScoreQuery> for ElasticQueryDslInner {
+    fn from(constant_score_query: ConstantScoreQuery) -> Self {
+        Self::ConstantScore(constant_score_query)
+    }
+}
+
+impl ConvertibleToQueryAst for ConstantScoreQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let filter_ast = self.filter.convert_to_query_ast()?;
+        let bool_query_ast: QueryAst = query_ast::BoolQuery {
+            filter: vec![filter_ast],
+            ..Default::default()
+        }
+        .into();
+        Ok(bool_query_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_score_query() {
+        let constant_score_query: ConstantScoreQuery =
+            serde_json::from_str(r#"{"filter": {"term": {"user.id": "kimchy"}}, "boost": 1.2}"#)
+                .unwrap();
+        let QueryAst::Boost { underlying, boost } =
+            constant_score_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        assert_eq!(boost, NotNaNf32::try_from(1.2f32).unwrap());
+        let QueryAst::Bool(bool_query) = *underlying else {
+            panic!()
+        };
+        assert!(bool_query.must.is_empty());
+        assert_eq!(bool_query.filter.len(), 1);
+        assert!(matches!(bool_query.filter[0], QueryAst::Term(_)));
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+/// `DisMaxQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-dis-max-query.html>
+///
+/// A document matches if it matches any of the queries. Quickwit does not support disjunction
+/// max scoring: the scores of the matching queries are summed and `tie_breaker` is ignored.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DisMaxQuery {
+    queries: Vec<ElasticQueryDslInner>,
+    #[serde(default)]
+    tie_breaker: Option<NotNaNf32>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<DisMaxQuery> for ElasticQueryDslInner {
+    fn from(dis_max_query: DisMaxQuery) -> Self {
+        Self::DisMax(dis_max_query)
+    }
+}
+
+impl ConvertibleToQueryAst for DisMaxQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let should = self
+            .queries
+            .into_iter()
+            .map(|query| query.convert_to_query_ast())
+            .collect::<anyhow::Result<Vec<QueryAst>>>()?;
+        if should.is_empty() {
+            return Ok(QueryAst::MatchNone);
+        }
+        let bool_query_ast: QueryAst = query_ast::BoolQuery {
+            should,
+            ..Default::default()
+        }
+        .into();
+        Ok(bool_query_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dis_max_query() {
+        let dis_max_query: DisMaxQuery = serde_json::from_str(
+            r#"{
+                "queries": [
+                    { "term": { "title": "shoes" } },
+                    { "term": { "body": "shoes" } }
+                ],
+                "tie_breaker": 0.7
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::Bool(bool_query) = dis_max_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(bool_query.should.len(), 2);
+        assert!(bool_query.must.is_empty());
+    }
+
+    #[test]
+    fn test_dis_max_query_empty() {
+        let dis_max_query: DisMaxQuery = serde_json::from_str(r#"{"queries": []}"#).unwrap();
+        assert_eq!(
+            dis_max_query.convert_to_query_ast().unwrap(),
+            QueryAst::MatchNone
+        );
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::json_literal::JsonLiteral;
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{
+    self, DecayCurve, FieldValueFactorFunction, FunctionBoostMode, FunctionScoreMode, QueryAst,
+    ScoreFunctionKind,
+};
+
+/// `FunctionScoreQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-function-score-query.html>
+///
+/// A single function can be passed directly at the root of the query instead of in `functions`.
+///
+/// # Unsupported features
+/// - script_score
+/// - random_score
+/// - decay functions on geo points
+/// - multi_value_mode (fields are expected to be single-valued)
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FunctionScoreQuery {
+    #[serde(default)]
+    query: Option<Box<ElasticQueryDslInner>>,
+    #[serde(default)]
+    functions: Vec<ScoreFunction>,
+    #[serde(default)]
+    score_mode: FunctionScoreMode,
+    #[serde(default)]
+    boost_mode: FunctionBoostMode,
+    #[serde(default)]
+    max_boost: Option<NotNaNf32>,
+    #[serde(default)]
+    min_score: Option<NotNaNf32>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+    #[serde(default)]
+    weight: Option<NotNaNf32>,
+    #[serde(default)]
+    field_value_factor: Option<FieldValueFactorFunction>,
+    #[serde(default)]
+    gauss: Option<DecayFunction>,
+    #[serde(default)]
+    exp: Option<DecayFunction>,
+    #[serde(default)]
+    linear: Option<DecayFunction>,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+struct ScoreFunction {
+    #[serde(default)]
+    filter: Option<Box<ElasticQueryDslInner>>,
+    #[serde(default)]
+    weight: Option<NotNaNf32>,
+    #[serde(default)]
+    field_value_factor: Option<FieldValueFactorFunction>,
+    #[serde(default)]
+    gauss: Option<DecayFunction>,
+    #[serde(default)]
+    exp: Option<DecayFunction>,
+    #[serde(default)]
+    linear: Option<DecayFunction>,
+}
+
+/// Decay function parameters, in the form `{"my_field": {"origin": .., "scale": ..}}`.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(try_from = "serde_json::Map<String, serde_json::Value>")]
+struct DecayFunction {
+    field: String,
+    params: DecayFunctionParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+struct DecayFunctionParams {
+    #[serde(default)]
+    origin: Option<JsonLiteral>,
+    scale: JsonLiteral,
+    #[serde(default)]
+    offset: Option<JsonLiteral>,
+    #[serde(default)]
+    decay: Option<NotNaNf32>,
+}
+
+impl TryFrom<serde_json::Map<String, serde_json::Value>> for DecayFunction {
+    type Error = anyhow::Error;
+
+    fn try_from(mut map: serde_json::Map<String, serde_json::Value>) -> anyhow::Result<Self> {
+        if let Some(multi_value_mode) = map.remove("multi_value_mode") {
+            if !multi_value_mode.is_string() {
+                anyhow::bail!("`multi_value_mode` must be a string");
+            }
+        }
+        let mut fields = map.into_iter();
+        let Some((field, params_json)) = fields.next() else {
+            anyhow::bail!("decay functions require a field");
+        };
+        if let Some((second_field, _)) = fields.next() {
+            anyhow::bail!(
+                "decay functions apply to a single field. got several ({field}, {second_field}, \
+                 ...)"
+            );
+        }
+        let params: DecayFunctionParams = serde_json::from_value(params_json)?;
+        Ok(DecayFunction { field, params })
+    }
+}
+
+impl DecayFunction {
+    fn into_ast(self, curve: DecayCurve) -> query_ast::DecayFunction {
+        let DecayFunctionParams {
+            origin,
+            scale,
+            offset,
+            decay,
+        } = self.params;
+        query_ast::DecayFunction {
+            field: self.field,
+            curve,
+            origin,
+            scale,
+            offset,
+            decay: decay.unwrap_or_else(|| NotNaNf32::try_from(0.5f32).unwrap()),
+        }
+    }
+}
+
+impl ScoreFunction {
+    fn is_empty(&self) -> bool {
+        self.weight.is_none()
+            && self.field_value_factor.is_none()
+            && self.gauss.is_none()
+            && self.exp.is_none()
+            && self.linear.is_none()
+    }
+
+    fn convert_to_ast(self) -> anyhow::Result<query_ast::ScoreFunction> {
+        let filter = self
+            .filter
+            .map(|filter| filter.convert_to_query_ast())
+            .transpose()?;
+        let mut kinds: Vec<ScoreFunctionKind> = Vec::new();
+        if let Some(field_value_factor) = self.field_value_factor {
+            kinds.push(ScoreFunctionKind::FieldValueFactor(field_value_factor));
+        }
+        for (decay_function_opt, curve) in [
+            (self.gauss, DecayCurve::Gauss),
+            (self.exp, DecayCurve::Exp),
+            (self.linear, DecayCurve::Linear),
+        ] {
+            if let Some(decay_function) = decay_function_opt {
+                kinds.push(ScoreFunctionKind::Decay(decay_function.into_ast(curve)));
+            }
+        }
+        if kinds.len() > 1 {
+            anyhow::bail!("a score function can only define a single function");
+        }
+        let function = match kinds.pop() {
+            Some(kind) => kind,
+            None if self.weight.is_some() => ScoreFunctionKind::Weight,
+            None => anyhow::bail!("score functions require a function or a `weight`"),
+        };
+        Ok(query_ast::ScoreFunction {
+            filter,
+            weight: self.weight,
+            function,
+        })
+    }
+}
+
+impl From<FunctionScoreQuery> for ElasticQueryDslInner {
+    fn from(function_score_query: FunctionScoreQuery) -> Self {
+        Self::FunctionScore(function_score_query)
+    }
+}
+
+impl ConvertibleToQueryAst for FunctionScoreQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let query_ast = if let Some(query) = self.query {
+            query.convert_to_query_ast()?
+        } else {
+            QueryAst::MatchAll
+        };
+        let root_function = ScoreFunction {
+            filter: None,
+            weight: self.weight,
+            field_value_factor: self.field_value_factor,
+            gauss: self.gauss,
+            exp: self.exp,
+            linear: self.linear,
+        };
+        let mut es_functions = self.functions;
+        if !root_function.is_empty() {
+            if !es_functions.is_empty() {
+                anyhow::bail!(
+                    "a function cannot be defined at the root of a `function_score` query when \
+                     `functions` is set"
+                );
+            }
+            es_functions.push(root_function);
+        }
+        let functions = es_functions
+            .into_iter()
+            .map(ScoreFunction::convert_to_ast)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let function_score_ast: QueryAst = query_ast::FunctionScoreQuery {
+            query: Box::new(query_ast),
+            functions,
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+            max_boost: self.max_boost,
+            min_score: self.min_score,
+        }
+        .into();
+        Ok(function_score_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_ast::FieldValueFactorModifier;
+
+    #[test]
+    fn test_function_score_query_functions() {
+        let function_score_query: FunctionScoreQuery = serde_json::from_str(
+            r#"{
+                "query": {"term": {"severity_text": "ERROR"}},
+                "functions": [
+                    {
+                        "filter": {"term": {"service": "api"}},
+                        "weight": 2
+                    },
+                    {
+                        "field_value_factor": {
+                            "field": "likes",
+                            "factor": 1.2,
+                            "modifier": "sqrt",
+                            "missing": 1
+                        }
+                    },
+                    {
+                        "gauss": {
+                            "timestamp": {"origin": "2024-01-01T00:00:00Z", "scale": "10d"},
+                            "multi_value_mode": "avg"
+                        },
+                        "weight": 0.5
+                    }
+                ],
+                "score_mode": "sum",
+                "boost_mode": "replace",
+                "max_boost": 10,
+                "min_score": 0.1
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::FunctionScore(function_score_ast) =
+            function_score_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        assert!(matches!(*function_score_ast.query, QueryAst::Term(_)));
+        assert_eq!(function_score_ast.score_mode, FunctionScoreMode::Sum);
+        assert_eq!(function_score_ast.boost_mode, FunctionBoostMode::Replace);
+        assert_eq!(
+            function_score_ast.max_boost,
+            Some(NotNaNf32::try_from(10.0f32).unwrap())
+        );
+        assert_eq!(function_score_ast.functions.len(), 3);
+
+        let weight_function = &function_score_ast.functions[0];
+        assert!(matches!(weight_function.filter, Some(QueryAst::Term(_))));
+        assert_eq!(
+            weight_function.weight,
+            Some(NotNaNf32::try_from(2.0f32).unwrap())
+        );
+        assert_eq!(weight_function.function, ScoreFunctionKind::Weight);
+
+        let ScoreFunctionKind::FieldValueFactor(field_value_factor) =
+            &function_score_ast.functions[1].function
+        else {
+            panic!()
+        };
+        assert_eq!(field_value_factor.field, "likes");
+        assert_eq!(field_value_factor.modifier, FieldValueFactorModifier::Sqrt);
+        assert_eq!(
+            field_value_factor.missing,
+            Some(NotNaNf32::try_from(1.0f32).unwrap())
+        );
+
+        let ScoreFunctionKind::Decay(decay_function) = &function_score_ast.functions[2].function
+        else {
+            panic!()
+        };
+        assert_eq!(decay_function.field, "timestamp");
+        assert_eq!(decay_function.curve, DecayCurve::Gauss);
+        assert_eq!(
+            decay_function.origin,
+            Some(JsonLiteral::String("2024-01-01T00:00:00Z".to_string()))
+        );
+        assert_eq!(decay_function.scale, JsonLiteral::String("10d".to_string()));
+        assert_eq!(decay_function.decay, NotNaNf32::try_from(0.5f32).unwrap());
+    }
+
+    #[test]
+    fn test_function_score_query_root_function() {
+        let function_score_query: FunctionScoreQuery = serde_json::from_str(
+            r#"{
+                "exp": {"severity": {"origin": 0, "scale": 2, "offset": 1, "decay": 0.2}},
+                "boost": 2
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::Boost { underlying, boost } =
+            function_score_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        assert_eq!(boost, NotNaNf32::try_from(2.0f32).unwrap());
+        let QueryAst::FunctionScore(function_score_ast) = *underlying else {
+            panic!()
+        };
+        assert_eq!(*function_score_ast.query, QueryAst::MatchAll);
+        assert_eq!(function_score_ast.functions.len(), 1);
+        let ScoreFunctionKind::Decay(decay_function) = &function_score_ast.functions[0].function
+        else {
+            panic!()
+        };
+        assert_eq!(decay_function.curve, DecayCurve::Exp);
+        assert_eq!(decay_function.decay, NotNaNf32::try_from(0.2f32).unwrap());
+        assert_eq!(decay_function.offset, Some(JsonLiteral::Number(1.into())));
+    }
+
+    #[test]
+    fn test_function_score_query_invalid() {
+        let error =
+            serde_json::from_str::<FunctionScoreQuery>(r#"{"functions": [{"random_score": {}}]}"#)
+                .unwrap_err();
+        assert!(error.to_string().contains("unknown field `random_score`"));
+
+        let error = serde_json::from_str::<FunctionScoreQuery>(
+            r#"{"gauss": {"a": {"scale": 1}, "b": {"scale": 1}}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("single field"));
+
+        let function_score_query: FunctionScoreQuery = serde_json::from_str(
+            r#"{"functions": [{"field_value_factor": {"field": "likes"}, "exp": {"a": {"scale": 1}}}]}"#,
+        )
+        .unwrap();
+        let error = function_score_query.convert_to_query_ast().unwrap_err();
+        assert!(error.to_string().contains("single function"));
+
+        let function_score_query: FunctionScoreQuery =
+            serde_json::from_str(r#"{"functions": [{"filter": {"match_all": {}}}]}"#).unwrap();
+        let error = function_score_query.convert_to_query_ast().unwrap_err();
+        assert!(error.to_string().contains("require a function"));
+
+        let function_score_query: FunctionScoreQuery =
+            serde_json::from_str(r#"{"weight": 2, "functions": [{"weight": 3}]}"#).unwrap();
+        assert!(function_score_query.convert_to_query_ast().is_err());
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use super::{default_max_expansions, default_transpositions, StringOrStructForSerialization};
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{Fuzziness, FuzzyQuery as AstFuzzyQuery, QueryAst};
+
+/// `FuzzyQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-fuzzy-query.html>
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>")]
+pub(crate) struct FuzzyQuery {
+    pub(crate) field: String,
+    pub(crate) params: FuzzyQueryParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FuzzyQueryParams {
+    value: String,
+    #[serde(default)]
+    fuzziness: Fuzziness,
+    #[serde(default = "default_max_expansions")]
+    max_expansions: u32,
+    #[serde(default)]
+    prefix_length: u32,
+    #[serde(default = "default_transpositions")]
+    transpositions: bool,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<String> for FuzzyQueryParams {
+    fn from(value: String) -> FuzzyQueryParams {
+        FuzzyQueryParams {
+            value,
+            fuzziness: Fuzziness::default(),
+            max_expansions: default_max_expansions(),
+            prefix_length: 0,
+            transpositions: default_transpositions(),
+            boost: None,
+        }
+    }
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>> for FuzzyQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>) -> Self {
+        FuzzyQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<FuzzyQuery> for ElasticQueryDslInner {
+    fn from(fuzzy_query: FuzzyQuery) -> Self {
+        Self::Fuzzy(fuzzy_query)
+    }
+}
+
+impl ConvertibleToQueryAst for FuzzyQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let FuzzyQueryParams {
+            value,
+            fuzziness,
+            max_expansions,
+            prefix_length,
+            transpositions,
+            boost,
+        } = self.params;
+        // The value is not tokenized, so `AUTO` can be resolved right away.
+        let distance = fuzziness.distance_for_text(&value);
+        let fuzzy_ast: QueryAst = AstFuzzyQuery {
+            field: self.field,
+            value,
+            distance,
+            transpositions,
+            prefix_length,
+            max_expansions,
+            lenient: false,
+        }
+        .into();
+        Ok(fuzzy_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_query_deserialization_in_short_format() {
+        let fuzzy_query: FuzzyQuery = serde_json::from_str(r#"{"user.id": "kimchy"}"#).unwrap();
+        assert_eq!(fuzzy_query.field, "user.id");
+        assert_eq!(fuzzy_query.params.value, "kimchy");
+        assert_eq!(fuzzy_query.params.fuzziness, Fuzziness::default());
+        let QueryAst::Fuzzy(fuzzy_ast) = fuzzy_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(fuzzy_ast.distance, 2);
+        assert!(fuzzy_ast.transpositions);
+        assert_eq!(fuzzy_ast.max_expansions, 50);
+    }
+
+    #[test]
+    fn test_fuzzy_query_deserialization() {
+        let fuzzy_query: FuzzyQuery = serde_json::from_str(
+            r#"{
+                "user.id": {
+                    "value": "ki",
+                    "fuzziness": 1,
+                    "max_expansions": 10,
+                    "prefix_length": 1,
+                    "transpositions": false
+                }
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::Fuzzy(fuzzy_ast) = fuzzy_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(
+            fuzzy_ast,
+            AstFuzzyQuery {
+                field: "user.id".to_string(),
+                value: "ki".to_string(),
+                distance: 1,
+                transpositions: false,
+                prefix_length: 1,
+                max_expansions: 10,
+                lenient: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_query_invalid_fuzziness() {
+        let error =
+            serde_json::from_str::<FuzzyQuery>(r#"{"user.id": {"value": "ki", "fuzziness": 3}}"#)
+                .unwrap_err();
+        assert!(error.to_string().contains("fuzziness must be at most 2"));
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::QueryAst;
+
+/// `IdsQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-ids-query.html>
+///
+/// Quickwit documents do not have ids (hits are returned with an empty `_id`), so this query
+/// matches no document.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct IdsQuery {
+    values: Vec<String>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<IdsQuery> for ElasticQueryDslInner {
+    fn from(ids_query: IdsQuery) -> Self {
+        Self::Ids(ids_query)
+    }
+}
+
+impl ConvertibleToQueryAst for IdsQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let IdsQuery {
+            values: _values,
+            boost,
+        } = self;
+        Ok(QueryAst::MatchNone.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_query() {
+        let ids_query: IdsQuery = serde_json::from_str(r#"{"values": ["1", "4"]}"#).unwrap();
+        assert_eq!(ids_query.values, vec!["1".to_string(), "4".to_string()]);
+        assert_eq!(
+            ids_query.convert_to_query_ast().unwrap(),
+            QueryAst::MatchNone
+        );
+    }
+}
@@ -16,7 +16,7 @@ use serde::Deserialize;
 
 use super::{ElasticQueryDslInner, StringOrStructForSerialization};
 use crate::elastic_query_dsl::match_query::MatchQueryParams;
-use crate::elastic_query_dsl::{default_max_expansions, ConvertibleToQueryAst};
+use crate::elastic_query_dsl::ConvertibleToQueryAst;
 use crate::query_ast::{FullTextParams, FullTextQuery, QueryAst};
 use crate::OneFieldMap;
 
@@ -31,11 +31,14 @@ pub(crate) struct MatchBoolPrefixQuery {
 
 impl ConvertibleToQueryAst for MatchBoolPrefixQuery {
     fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        if self.params.fuzziness.is_some() {
+            anyhow::bail!("fuzziness is not supported in match_bool_prefix queries");
+        }
         let full_text_params = FullTextParams {
             tokenizer: None,
             mode: crate::query_ast::FullTextMode::BoolPrefix {
                 operator: self.params.operator,
-                max_expansions: default_max_expansions(),
+                max_expansions: self.params.max_expansions,
             },
             zero_terms_query: self.params.zero_terms_query,
         };
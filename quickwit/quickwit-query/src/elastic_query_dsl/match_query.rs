@@ -16,9 +16,10 @@ use serde::Deserialize;
 
 use super::LeniencyBool;
 use crate::elastic_query_dsl::{
-    ConvertibleToQueryAst, ElasticQueryDslInner, StringOrStructForSerialization,
+    default_max_expansions, default_transpositions, ConvertibleToQueryAst, ElasticQueryDslInner,
+    StringOrStructForSerialization,
 };
-use crate::query_ast::{FullTextParams, FullTextQuery, QueryAst};
+use crate::query_ast::{FullTextMode, FullTextParams, FullTextQuery, Fuzziness, QueryAst};
 use crate::{BooleanOperand, MatchAllOrNone, OneFieldMap};
 
 /// `MatchQuery` as defined in
@@ -40,13 +41,32 @@ pub(crate) struct MatchQueryParams {
     pub(crate) zero_terms_query: MatchAllOrNone,
     #[serde(default)]
     pub(crate) lenient: LeniencyBool,
+    #[serde(default)]
+    pub(crate) fuzziness: Option<Fuzziness>,
+    #[serde(default)]
+    pub(crate) prefix_length: u32,
+    #[serde(default = "default_max_expansions")]
+    pub(crate) max_expansions: u32,
+    #[serde(default = "default_transpositions")]
+    pub(crate) fuzzy_transpositions: bool,
 }
 
 impl ConvertibleToQueryAst for MatchQuery {
     fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let mode = if let Some(fuzziness) = self.params.fuzziness {
+            FullTextMode::Fuzzy {
+                operator: self.params.operator,
+                fuzziness,
+                transpositions: self.params.fuzzy_transpositions,
+                prefix_length: self.params.prefix_length,
+                max_expansions: self.params.max_expansions,
+            }
+        } else {
+            self.params.operator.into()
+        };
         let full_text_params = FullTextParams {
             tokenizer: None,
-            mode: self.params.operator.into(),
+            mode,
             zero_terms_query: self.params.zero_terms_query,
         };
         Ok(QueryAst::FullText(FullTextQuery {
@@ -83,6 +103,10 @@ impl From<String> for MatchQueryParams {
             zero_terms_query: Default::default(),
             operator: Default::default(),
             lenient: false,
+            fuzziness: None,
+            prefix_length: 0,
+            max_expansions: default_max_expansions(),
+            fuzzy_transpositions: default_transpositions(),
         }
     }
 }
@@ -90,7 +114,6 @@ impl From<String> for MatchQueryParams {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::query_ast::FullTextMode;
 
     #[test]
     fn test_deserialize_match_query_string() {
@@ -132,6 +155,10 @@ mod tests {
                 operator: BooleanOperand::And,
                 zero_terms_query: crate::MatchAllOrNone::MatchAll,
                 lenient: false,
+                fuzziness: None,
+                prefix_length: 0,
+                max_expansions: 50,
+                fuzzy_transpositions: true,
             },
         };
         let ast = match_query.convert_to_query_ast().unwrap();
@@ -154,4 +181,27 @@ mod tests {
         );
         assert_eq!(params.zero_terms_query, MatchAllOrNone::MatchAll);
     }
+
+    #[test]
+    fn test_match_query_with_fuzziness() {
+        let match_query: MatchQuery = serde_json::from_str(
+            r#"{"body": {"query": "helo wrld", "fuzziness": "AUTO", "prefix_length": 1}}"#,
+        )
+        .unwrap();
+        let QueryAst::FullText(FullTextQuery { params, .. }) =
+            match_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        assert_eq!(
+            params.mode,
+            FullTextMode::Fuzzy {
+                operator: BooleanOperand::Or,
+                fuzziness: Fuzziness::Auto { low: 3, high: 6 },
+                transpositions: true,
+                prefix_length: 1,
+                max_expansions: 50,
+            }
+        );
+    }
 }
@@ -15,19 +15,30 @@
 use serde::{Deserialize, Serialize};
 
 mod bool_query;
+mod boosting_query;
+mod constant_score_query;
+mod dis_max_query;
 mod exists_query;
+mod function_score_query;
+mod fuzzy_query;
+mod ids_query;
 mod match_bool_prefix;
 mod match_phrase_query;
 mod match_query;
 mod multi_match;
+mod nested_query;
 mod one_field_map;
 mod phrase_prefix_query;
+mod prefix_query;
 mod query_string_query;
 mod range_query;
 mod regex_query;
+mod simple_query_string_query;
 mod string_or_struct;
 mod term_query;
 mod terms_query;
+mod terms_set_query;
+mod wildcard_query;
 
 use bool_query::BoolQuery;
 pub use one_field_map::OneFieldMap;
@@ -37,13 +48,24 @@ use range_query::RangeQuery;
 pub(crate) use string_or_struct::StringOrStructForSerialization;
 use term_query::TermQuery;
 
+use crate::elastic_query_dsl::boosting_query::BoostingQuery;
+use crate::elastic_query_dsl::constant_score_query::ConstantScoreQuery;
+use crate::elastic_query_dsl::dis_max_query::DisMaxQuery;
 use crate::elastic_query_dsl::exists_query::ExistsQuery;
+use crate::elastic_query_dsl::function_score_query::FunctionScoreQuery;
+use crate::elastic_query_dsl::fuzzy_query::FuzzyQuery;
+use crate::elastic_query_dsl::ids_query::IdsQuery;
 use crate::elastic_query_dsl::match_bool_prefix::MatchBoolPrefixQuery;
 use crate::elastic_query_dsl::match_phrase_query::MatchPhraseQuery;
 use crate::elastic_query_dsl::match_query::MatchQuery;
 use crate::elastic_query_dsl::multi_match::MultiMatchQuery;
+use crate::elastic_query_dsl::nested_query::NestedQuery;
+use crate::elastic_query_dsl::prefix_query::PrefixQuery;
 use crate::elastic_query_dsl::regex_query::RegexQuery;
+use crate::elastic_query_dsl::simple_query_string_query::SimpleQueryStringQuery;
 use crate::elastic_query_dsl::terms_query::TermsQuery;
+use crate::elastic_query_dsl::terms_set_query::TermsSetQuery;
+use crate::elastic_query_dsl::wildcard_query::WildcardQuery;
 use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::QueryAst;
 
@@ -59,6 +81,10 @@ fn default_max_expansions() -> u32 {
     50
 }
 
+fn default_transpositions() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Copy, Default)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MatchAllQuery {
@@ -85,6 +111,17 @@ pub(crate) enum ElasticQueryDslInner {
     Range(RangeQuery),
     Exists(ExistsQuery),
     Regexp(RegexQuery),
+    Fuzzy(FuzzyQuery),
+    Prefix(PrefixQuery),
+    Wildcard(WildcardQuery),
+    Ids(IdsQuery),
+    ConstantScore(ConstantScoreQuery),
+    DisMax(DisMaxQuery),
+    SimpleQueryString(SimpleQueryStringQuery),
+    Boosting(BoostingQuery),
+    TermsSet(TermsSetQuery),
+    FunctionScore(FunctionScoreQuery),
+    Nested(NestedQuery),
 }
 
 #[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
@@ -133,6 +170,23 @@ impl ConvertibleToQueryAst for ElasticQueryDslInner {
             Self::Exists(exists_query) => exists_query.convert_to_query_ast(),
             Self::MultiMatch(multi_match_query) => multi_match_query.convert_to_query_ast(),
             Self::Regexp(regex_query) => regex_query.convert_to_query_ast(),
+            Self::Fuzzy(fuzzy_query) => fuzzy_query.convert_to_query_ast(),
+            Self::Prefix(prefix_query) => prefix_query.convert_to_query_ast(),
+            Self::Wildcard(wildcard_query) => wildcard_query.convert_to_query_ast(),
+            Self::Ids(ids_query) => ids_query.convert_to_query_ast(),
+            Self::ConstantScore(constant_score_query) => {
+                constant_score_query.convert_to_query_ast()
+            }
+            Self::DisMax(dis_max_query) => dis_max_query.convert_to_query_ast(),
+            Self::SimpleQueryString(simple_query_string_query) => {
+                simple_query_string_query.convert_to_query_ast()
+            }
+            Self::Boosting(boosting_query) => boosting_query.convert_to_query_ast(),
+            Self::TermsSet(terms_set_query) => terms_set_query.convert_to_query_ast(),
+            Self::FunctionScore(function_score_query) => {
+                function_score_query.convert_to_query_ast()
+            }
+            Self::Nested(nested_query) => nested_query.convert_to_query_ast(),
         }
     }
 }
@@ -192,6 +192,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -202,6 +206,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -222,6 +230,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -232,6 +244,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -252,6 +268,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -262,6 +282,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -344,6 +368,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -354,6 +382,10 @@ mod tests {
                         operator: crate::BooleanOperand::Or,
                         zero_terms_query: Default::default(),
                         lenient: false,
+                        fuzziness: None,
+                        prefix_length: 0,
+                        max_expansions: 50,
+                        fuzzy_transpositions: true,
                     },
                 }
                 .into(),
@@ -361,6 +393,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_multimatch_query_with_fuzziness() {
+        test_multimatch_query_ok_aux(
+            r#"{
+                "query": "quikc",
+                "fuzziness": 1,
+                "fields": ["title"]
+            }"#,
+            BoolQuery::union(vec![MatchQuery {
+                field: "title".to_string(),
+                params: MatchQueryParams {
+                    query: "quikc".to_string(),
+                    operator: crate::BooleanOperand::Or,
+                    zero_terms_query: Default::default(),
+                    lenient: false,
+                    fuzziness: Some(crate::query_ast::Fuzziness::Distance(1)),
+                    prefix_length: 0,
+                    max_expansions: 50,
+                    fuzzy_transpositions: true,
+                },
+            }
+            .into()]),
+        );
+    }
+
     #[test]
     fn test_multimatch_unsupported() {
         test_multimatch_query_err_aux(
@@ -0,0 +1,104 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::block_join::NestedScoreMode;
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+/// `NestedQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-nested-query.html>
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NestedQuery {
+    path: String,
+    query: Box<ElasticQueryDslInner>,
+    #[serde(default)]
+    score_mode: NestedScoreMode,
+    // Quickwit matches no documents on unmapped nested paths regardless of this parameter.
+    #[serde(default, rename = "ignore_unmapped")]
+    _ignore_unmapped: bool,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<NestedQuery> for ElasticQueryDslInner {
+    fn from(nested_query: NestedQuery) -> Self {
+        Self::Nested(nested_query)
+    }
+}
+
+impl ConvertibleToQueryAst for NestedQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let query_ast = self.query.convert_to_query_ast()?;
+        let nested_query_ast: QueryAst = query_ast::NestedQuery {
+            path: self.path,
+            query: Box::new(query_ast),
+            score_mode: self.score_mode,
+        }
+        .into();
+        Ok(nested_query_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_query() {
+        let nested_query: NestedQuery = serde_json::from_str(
+            r#"{
+                "path": "items",
+                "query": {"term": {"items.name": "foo"}},
+                "score_mode": "max",
+                "ignore_unmapped": true
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::Nested(nested_query_ast) = nested_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        assert_eq!(nested_query_ast.path, "items");
+        assert_eq!(nested_query_ast.score_mode, NestedScoreMode::Max);
+        assert!(matches!(*nested_query_ast.query, QueryAst::Term(_)));
+    }
+
+    #[test]
+    fn test_nested_query_default_score_mode() {
+        let nested_query: NestedQuery =
+            serde_json::from_str(r#"{"path": "items", "query": {"match_all": {}}, "boost": 2.0}"#)
+                .unwrap();
+        let QueryAst::Boost { underlying, .. } = nested_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        let QueryAst::Nested(nested_query_ast) = *underlying else {
+            panic!()
+        };
+        assert_eq!(nested_query_ast.score_mode, NestedScoreMode::Avg);
+    }
+
+    #[test]
+    fn test_nested_query_invalid_score_mode() {
+        let error = serde_json::from_str::<NestedQuery>(
+            r#"{"path": "items", "query": {"match_all": {}}, "score_mode": "median"}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown variant `median`"));
+    }
+}
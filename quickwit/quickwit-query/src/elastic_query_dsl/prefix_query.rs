@@ -0,0 +1,116 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{
+    ConvertibleToQueryAst, ElasticQueryDslInner, StringOrStructForSerialization,
+};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{QueryAst, WildcardQuery as AstWildcardQuery};
+
+/// `PrefixQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-prefix-query.html>
+///
+/// It is converted into a wildcard query with a trailing `*`.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>")]
+pub(crate) struct PrefixQuery {
+    pub(crate) field: String,
+    pub(crate) params: PrefixQueryParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PrefixQueryParams {
+    value: String,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<String> for PrefixQueryParams {
+    fn from(value: String) -> PrefixQueryParams {
+        PrefixQueryParams { value, boost: None }
+    }
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>> for PrefixQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>) -> Self {
+        PrefixQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<PrefixQuery> for ElasticQueryDslInner {
+    fn from(prefix_query: PrefixQuery) -> Self {
+        Self::Prefix(prefix_query)
+    }
+}
+
+/// Escapes the characters that have a special meaning in wildcard queries.
+fn escape_wildcard(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl ConvertibleToQueryAst for PrefixQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let PrefixQueryParams { value, boost } = self.params;
+        let wildcard_ast: QueryAst = AstWildcardQuery {
+            field: self.field,
+            value: format!("{}*", escape_wildcard(&value)),
+            lenient: false,
+        }
+        .into();
+        Ok(wildcard_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_query_deserialization() {
+        let prefix_query: PrefixQuery =
+            serde_json::from_str(r#"{"user.id": {"value": "ki", "boost": 2.0}}"#).unwrap();
+        assert_eq!(prefix_query.field, "user.id");
+        assert_eq!(prefix_query.params.value, "ki");
+
+        let prefix_query: PrefixQuery = serde_json::from_str(r#"{"user.id": "ki"}"#).unwrap();
+        let QueryAst::Wildcard(wildcard_ast) = prefix_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(wildcard_ast.field, "user.id");
+        assert_eq!(wildcard_ast.value, "ki*");
+    }
+
+    #[test]
+    fn test_prefix_query_escapes_wildcards() {
+        let prefix_query: PrefixQuery = serde_json::from_str(r#"{"user.id": "k?i*"}"#).unwrap();
+        let QueryAst::Wildcard(wildcard_ast) = prefix_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(wildcard_ast.value, r"k\?i\**");
+    }
+}
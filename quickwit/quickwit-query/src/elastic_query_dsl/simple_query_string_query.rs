@@ -0,0 +1,235 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use super::bool_query::MinimumShouldMatch;
+use super::LeniencyBool;
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{QueryAst, UserInputQuery};
+use crate::BooleanOperand;
+
+/// Characters that have a special meaning in the query string syntax, but not in the simple
+/// query string syntax.
+const CHARS_TO_ESCAPE: &[char] = &[':', '[', ']', '{', '}', '^', '!', '\'', '`'];
+
+/// Operators of the query string syntax that are regular words in the simple query string syntax.
+const OPERATOR_WORDS: &[&str] = &["AND", "OR", "NOT"];
+
+/// `SimpleQueryStringQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-simple-query-string-query.html>
+///
+/// The query is translated into the query string syntax.
+///
+/// # Unsupported features
+/// - flags, accepted but ignored: all the operators are enabled
+/// - minimum_should_match, accepted but ignored
+/// - per field boosts (`title^3`)
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SimpleQueryStringQuery {
+    query: String,
+    #[serde(default)]
+    fields: Option<Vec<String>>,
+    #[serde(default)]
+    default_operator: BooleanOperand,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+    #[serde(default)]
+    lenient: LeniencyBool,
+    // Accepted for compatibility. Wildcard terms are never analyzed.
+    #[serde(default)]
+    analyze_wildcard: bool,
+    // Accepted for compatibility. All the operators are always enabled.
+    #[serde(default)]
+    flags: Option<String>,
+    // Accepted for compatibility.
+    #[serde(default)]
+    minimum_should_match: Option<MinimumShouldMatch>,
+}
+
+impl From<SimpleQueryStringQuery> for ElasticQueryDslInner {
+    fn from(simple_query_string_query: SimpleQueryStringQuery) -> Self {
+        Self::SimpleQueryString(simple_query_string_query)
+    }
+}
+
+/// Translates a simple query string into the query string syntax.
+///
+/// Outside of phrases, `|` becomes `OR`, a standalone `+` becomes `AND`, the words `AND`, `OR`,
+/// and `NOT` are quoted, and the characters that the simple query string syntax treats as regular
+/// characters are escaped.
+fn simple_query_to_query_string(simple_query: &str) -> String {
+    let mut query_string = String::with_capacity(simple_query.len());
+    let mut in_phrase = false;
+    let mut chars = simple_query.char_indices().peekable();
+    let mut previous_char_opt: Option<char> = None;
+    while let Some((offset, current_char)) = chars.next() {
+        if !in_phrase && previous_char_opt.map_or(true, is_word_boundary) {
+            if let Some(operator_word) = operator_word_prefix(&simple_query[offset..]) {
+                query_string.push('"');
+                query_string.push_str(operator_word);
+                query_string.push('"');
+                // Operator words are made of ASCII characters.
+                for _ in 1..operator_word.len() {
+                    chars.next();
+                }
+                previous_char_opt = operator_word.chars().last();
+                continue;
+            }
+        }
+        match current_char {
+            '\\' => {
+                query_string.push('\\');
+                if let Some((_, escaped_char)) = chars.next() {
+                    query_string.push(escaped_char);
+                }
+            }
+            '"' => {
+                in_phrase = !in_phrase;
+                query_string.push('"');
+            }
+            _ if in_phrase => query_string.push(current_char),
+            '|' => {
+                while chars.next_if(|(_, next_char)| *next_char == '|').is_some() {}
+                query_string.push_str(" OR ");
+            }
+            '+' if previous_char_opt.map_or(true, char::is_whitespace)
+                && chars
+                    .peek()
+                    .map_or(true, |(_, next_char)| next_char.is_whitespace()) =>
+            {
+                query_string.push_str(" AND ");
+            }
+            _ if CHARS_TO_ESCAPE.contains(&current_char) => {
+                query_string.push('\\');
+                query_string.push(current_char);
+            }
+            _ => query_string.push(current_char),
+        }
+        previous_char_opt = Some(current_char);
+    }
+    query_string
+}
+
+fn is_word_boundary(character: char) -> bool {
+    character.is_whitespace() || "()|+-".contains(character)
+}
+
+/// Returns the operator word starting the given text, if it is followed by a word boundary.
+fn operator_word_prefix(text: &str) -> Option<&'static str> {
+    OPERATOR_WORDS.iter().copied().find(|operator_word| {
+        text.strip_prefix(operator_word)
+            .is_some_and(|rest| rest.chars().next().map_or(true, is_word_boundary))
+    })
+}
+
+impl ConvertibleToQueryAst for SimpleQueryStringQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let SimpleQueryStringQuery {
+            query,
+            fields,
+            default_operator,
+            boost,
+            lenient,
+            analyze_wildcard: _analyze_wildcard,
+            flags: _flags,
+            minimum_should_match: _minimum_should_match,
+        } = self;
+        let user_input_query: QueryAst = UserInputQuery {
+            user_text: simple_query_to_query_string(&query),
+            default_fields: fields,
+            default_operator,
+            lenient,
+        }
+        .into();
+        Ok(user_input_query.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_query_to_query_string() {
+        assert_eq!(simple_query_to_query_string("hello world"), "hello world");
+        assert_eq!(
+            simple_query_to_query_string("fried | potatoes"),
+            "fried  OR  potatoes"
+        );
+        assert_eq!(
+            simple_query_to_query_string("fried||potatoes"),
+            "fried OR potatoes"
+        );
+        assert_eq!(
+            simple_query_to_query_string("fried + potatoes"),
+            "fried  AND  potatoes"
+        );
+        assert_eq!(
+            simple_query_to_query_string("fried -potatoes +frittata"),
+            "fried -potatoes +frittata"
+        );
+        assert_eq!(
+            simple_query_to_query_string("\"fried | eggs\"~2 pota*"),
+            "\"fried | eggs\"~2 pota*"
+        );
+        assert_eq!(
+            simple_query_to_query_string("url:http [x] {y} a^2 !b it's"),
+            "url\\:http \\[x\\] \\{y\\} a\\^2 \\!b it\\'s"
+        );
+        assert_eq!(simple_query_to_query_string("a\\|b"), "a\\|b");
+        assert_eq!(
+            simple_query_to_query_string("cats and OR dogs"),
+            "cats and \"OR\" dogs"
+        );
+        assert_eq!(
+            simple_query_to_query_string("AND NOT -OR (NOT|cats)"),
+            "\"AND\" \"NOT\" -\"OR\" (\"NOT\" OR cats)"
+        );
+        assert_eq!(
+            simple_query_to_query_string("ORACLE ANDROID NOTE band \"cats AND dogs\""),
+            "ORACLE ANDROID NOTE band \"cats AND dogs\""
+        );
+    }
+
+    #[test]
+    fn test_simple_query_string_query() {
+        let simple_query_string_query: SimpleQueryStringQuery = serde_json::from_str(
+            r#"{
+                "query": "fried + (eggs | potato)",
+                "fields": ["title", "body"],
+                "default_operator": "AND",
+                "analyze_wildcard": true,
+                "flags": "OR|AND|PREFIX",
+                "minimum_should_match": "75%",
+                "lenient": true
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::UserInput(user_input_query) =
+            simple_query_string_query.convert_to_query_ast().unwrap()
+        else {
+            panic!()
+        };
+        assert_eq!(user_input_query.user_text, "fried  AND  (eggs  OR  potato)");
+        assert_eq!(
+            user_input_query.default_fields.unwrap(),
+            vec!["title".to_string(), "body".to_string()]
+        );
+        assert_eq!(user_input_query.default_operator, BooleanOperand::And);
+        assert!(user_input_query.lenient);
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::bool_query::{BoolQuery, MinimumShouldMatch};
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::term_query::term_query_from_field_value;
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::QueryAst;
+
+/// `TermsSetQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-terms-set-query.html>
+///
+/// # Unsupported features
+/// - minimum_should_match_field
+/// - minimum_should_match_script
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(try_from = "OneFieldMap<TermsSetQueryParams>")]
+pub(crate) struct TermsSetQuery {
+    field: String,
+    terms: Vec<String>,
+    minimum_should_match: MinimumShouldMatch,
+    boost: Option<NotNaNf32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TermsSetQueryParams {
+    terms: Vec<String>,
+    // Same syntax as in `bool` queries.
+    #[serde(default)]
+    minimum_should_match: Option<MinimumShouldMatch>,
+    #[serde(default)]
+    minimum_should_match_field: Option<String>,
+    #[serde(default)]
+    minimum_should_match_script: Option<serde_json::Value>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl TryFrom<OneFieldMap<TermsSetQueryParams>> for TermsSetQuery {
+    type Error = anyhow::Error;
+
+    fn try_from(one_field_map: OneFieldMap<TermsSetQueryParams>) -> anyhow::Result<Self> {
+        let TermsSetQueryParams {
+            terms,
+            minimum_should_match,
+            minimum_should_match_field,
+            minimum_should_match_script,
+            boost,
+        } = one_field_map.value;
+        if minimum_should_match_field.is_some() {
+            anyhow::bail!("`minimum_should_match_field` is not supported in `terms_set` queries");
+        }
+        if minimum_should_match_script.is_some() {
+            anyhow::bail!("`minimum_should_match_script` is not supported in `terms_set` queries");
+        }
+        let Some(minimum_should_match) = minimum_should_match else {
+            anyhow::bail!("`terms_set` queries require `minimum_should_match`");
+        };
+        Ok(TermsSetQuery {
+            field: one_field_map.field,
+            terms,
+            minimum_should_match,
+            boost,
+        })
+    }
+}
+
+impl From<TermsSetQuery> for ElasticQueryDslInner {
+    fn from(terms_set_query: TermsSetQuery) -> Self {
+        Self::TermsSet(terms_set_query)
+    }
+}
+
+impl ConvertibleToQueryAst for TermsSetQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let term_queries: Vec<ElasticQueryDslInner> = self
+            .terms
+            .into_iter()
+            .map(|term| term_query_from_field_value(&self.field, term).into())
+            .collect();
+        let mut union = BoolQuery::union(term_queries);
+        union.minimum_should_match = Some(self.minimum_should_match);
+        let terms_set_ast = union.convert_to_query_ast()?;
+        Ok(terms_set_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms_set_query() {
+        let terms_set_query: TermsSetQuery = serde_json::from_str(
+            r#"{
+                "programming_languages": {
+                    "terms": ["c++", "java", "php"],
+                    "minimum_should_match": 2
+                }
+            }"#,
+        )
+        .unwrap();
+        let QueryAst::Bool(bool_query) = terms_set_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(bool_query.should.len(), 3);
+        assert_eq!(bool_query.minimum_should_match, Some(2));
+    }
+
+    #[test]
+    fn test_terms_set_query_unsupported_minimum_should_match_field() {
+        let error = serde_json::from_str::<TermsSetQuery>(
+            r#"{
+                "programming_languages": {
+                    "terms": ["c++", "java", "php"],
+                    "minimum_should_match_field": "required_matches"
+                }
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("minimum_should_match_field"));
+    }
+
+    #[test]
+    fn test_terms_set_query_missing_minimum_should_match() {
+        let error = serde_json::from_str::<TermsSetQuery>(
+            r#"{"programming_languages": {"terms": ["c++"]}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("require `minimum_should_match`"));
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{
+    ConvertibleToQueryAst, ElasticQueryDslInner, StringOrStructForSerialization,
+};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{QueryAst, WildcardQuery as AstWildcardQuery};
+
+/// `WildcardQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-wildcard-query.html>
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>")]
+pub(crate) struct WildcardQuery {
+    pub(crate) field: String,
+    pub(crate) params: WildcardQueryParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct WildcardQueryParams {
+    #[serde(alias = "wildcard")]
+    value: String,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<String> for WildcardQueryParams {
+    fn from(value: String) -> WildcardQueryParams {
+        WildcardQueryParams { value, boost: None }
+    }
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>> for WildcardQuery {
+    fn from(
+        one_field_map: OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>,
+    ) -> Self {
+        WildcardQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<WildcardQuery> for ElasticQueryDslInner {
+    fn from(wildcard_query: WildcardQuery) -> Self {
+        Self::Wildcard(wildcard_query)
+    }
+}
+
+impl ConvertibleToQueryAst for WildcardQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let WildcardQueryParams { value, boost } = self.params;
+        let wildcard_ast: QueryAst = AstWildcardQuery {
+            field: self.field,
+            value,
+            lenient: false,
+        }
+        .into();
+        Ok(wildcard_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_query_deserialization() {
+        for wildcard_query_json in [
+            r#"{"user.id": {"value": "ki*y"}}"#,
+            r#"{"user.id": {"wildcard": "ki*y"}}"#,
+            r#"{"user.id": "ki*y"}"#,
+        ] {
+            let wildcard_query: WildcardQuery = serde_json::from_str(wildcard_query_json).unwrap();
+            let QueryAst::Wildcard(wildcard_ast) = wildcard_query.convert_to_query_ast().unwrap()
+            else {
+                panic!()
+            };
+            assert_eq!(wildcard_ast.field, "user.id");
+            assert_eq!(wildcard_ast.value, "ki*y");
+        }
+    }
+}
@@ -23,11 +23,13 @@
 // For the individual detailed API documentation however, you should refer to elastic
 // documentation.
 
+pub mod block_join;
 mod elastic_query_dsl;
 mod error;
 mod json_literal;
 mod not_nan_f32;
 pub mod query_ast;
+pub mod runtime_field;
 pub mod tokenizers;
 
 pub use elastic_query_dsl::{ElasticQueryDsl, OneFieldMap};
@@ -25,9 +25,10 @@ use tantivy::schema::{
 use tantivy::tokenizer::{TextAnalyzer, TokenStream};
 use tantivy::Term;
 
+use crate::query_ast::fuzzy_query::make_fuzzy_term_query;
 use crate::query_ast::tantivy_query_ast::{TantivyBoolQuery, TantivyQueryAst};
 use crate::query_ast::utils::full_text_query;
-use crate::query_ast::{BuildTantivyAst, QueryAst};
+use crate::query_ast::{BuildTantivyAst, Fuzziness, QueryAst, FUZZY_MAX_EXPANSIONS};
 use crate::tokenizers::TokenizerManager;
 use crate::{find_field_or_hit_dynamic, BooleanOperand, InvalidQuery, MatchAllOrNone};
 
@@ -105,10 +106,19 @@ impl FullTextParams {
         mut terms: Vec<(usize, Term)>,
         index_record_option: IndexRecordOption,
     ) -> Result<TantivyQueryAst, InvalidQuery> {
+        if let FullTextMode::Fuzzy { max_expansions, .. } = self.mode {
+            if max_expansions != FUZZY_MAX_EXPANSIONS {
+                return Err(InvalidQuery::Other(anyhow::anyhow!(
+                    "fuzzy queries only support the default max_expansions \
+                     ({FUZZY_MAX_EXPANSIONS}), got `{max_expansions}`"
+                )));
+            }
+        }
         if terms.is_empty() {
             return Ok(self.zero_terms_query.into());
         }
-        if terms.len() == 1 {
+        // A fuzzy query on a single term is still a fuzzy query.
+        if terms.len() == 1 && !matches!(self.mode, FullTextMode::Fuzzy { .. }) {
             let term = terms.pop().unwrap().1;
             return Ok(TantivyTermQuery::new(term, IndexRecordOption::WithFreqs).into());
         }
@@ -137,6 +147,21 @@ impl FullTextParams {
                 }
                 Ok(TantivyBoolQuery::build_clause(operator, leaf_queries).into())
             }
+            FullTextMode::Fuzzy {
+                operator,
+                fuzziness,
+                transpositions,
+                prefix_length,
+                ..
+            } => {
+                let leaf_queries: Vec<TantivyQueryAst> = terms
+                    .into_iter()
+                    .map(|(_, term)| {
+                        make_fuzzy_term_query(term, fuzziness, transpositions, prefix_length)
+                    })
+                    .collect();
+                Ok(TantivyBoolQuery::build_clause(operator, leaf_queries).into())
+            }
             FullTextMode::Phrase { slop } => {
                 if !index_record_option.has_positions() {
                     return Err(InvalidQuery::SchemaError(
@@ -167,6 +192,10 @@ fn is_zero(val: &u32) -> bool {
     *val == 0u32
 }
 
+fn default_transpositions() -> bool {
+    true
+}
+
 /// `FullTextMode` describe how we should derive a query from a user sequence of tokens.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -183,6 +212,21 @@ pub enum FullTextMode {
         // expansion.
         max_expansions: u32,
     },
+    // After tokenization, each token matches the terms within the edit distance given by
+    // `fuzziness`, and the resulting queries are combined in a boolean clause.
+    //
+    // Tantivy does not cap the number of terms a fuzzy term expands to, so `max_expansions` must
+    // be `FUZZY_MAX_EXPANSIONS`.
+    Fuzzy {
+        operator: BooleanOperand,
+        #[serde(default)]
+        fuzziness: Fuzziness,
+        #[serde(default = "default_transpositions")]
+        transpositions: bool,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        prefix_length: u32,
+        max_expansions: u32,
+    },
     // Act as Phrase with slop 0 if the field has positions,
     // otherwise act as an intersection.
     PhraseFallbackToIntersection,
@@ -307,7 +351,7 @@ mod tests {
     use tantivy::schema::{Schema, TEXT};
 
     use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
-    use crate::query_ast::{BuildTantivyAst, FullTextMode, FullTextQuery};
+    use crate::query_ast::{BuildTantivyAst, FullTextMode, FullTextQuery, Fuzziness};
     use crate::{create_default_quickwit_tokenizer_manager, BooleanOperand};
 
     #[test]
@@ -423,4 +467,73 @@ mod tests {
         let bool_query = ast.as_bool_query().unwrap();
         assert_eq!(bool_query.must.len(), 2);
     }
+
+    #[test]
+    fn test_full_text_fuzzy_mode() {
+        let full_text_query = FullTextQuery {
+            field: "body".to_string(),
+            text: "Helo wordl".to_string(),
+            params: super::FullTextParams {
+                tokenizer: None,
+                mode: FullTextMode::Fuzzy {
+                    operator: BooleanOperand::And,
+                    fuzziness: Fuzziness::default(),
+                    transpositions: true,
+                    prefix_length: 0,
+                    max_expansions: 50,
+                },
+                zero_terms_query: crate::MatchAllOrNone::MatchNone,
+            },
+            lenient: false,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let ast: TantivyQueryAst = full_text_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+        let bool_query = ast.as_bool_query().unwrap();
+        assert_eq!(bool_query.must.len(), 2);
+        for leaf in &bool_query.must {
+            assert!(format!("{:?}", leaf.as_leaf().unwrap()).starts_with("FuzzyTermQuery"));
+        }
+    }
+
+    #[test]
+    fn test_full_text_fuzzy_mode_single_term() {
+        let full_text_query = FullTextQuery {
+            field: "body".to_string(),
+            text: "Helo".to_string(),
+            params: super::FullTextParams {
+                tokenizer: None,
+                mode: FullTextMode::Fuzzy {
+                    operator: BooleanOperand::Or,
+                    fuzziness: Fuzziness::Distance(1),
+                    transpositions: true,
+                    prefix_length: 0,
+                    max_expansions: 50,
+                },
+                zero_terms_query: crate::MatchAllOrNone::MatchNone,
+            },
+            lenient: false,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let ast: TantivyQueryAst = full_text_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+        let leaf = ast.as_leaf().unwrap();
+        assert!(format!("{leaf:?}").starts_with("FuzzyTermQuery"));
+    }
 }
@@ -0,0 +1,1110 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use tantivy::schema::{FieldType, Schema as TantivySchema};
+use tantivy::DateTime;
+use time::OffsetDateTime;
+
+use self::function_score::{TantivyFunctionScoreQuery, TantivyScoreFunction, ValueFunction};
+use super::{BuildTantivyAst, QueryAst};
+use crate::json_literal::InterpretUserInput;
+use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
+use crate::tokenizers::TokenizerManager;
+use crate::{InvalidQuery, JsonLiteral, MatchAllOrNone, NotNaNf32};
+
+/// Scores the documents matching `query` with a list of functions computed from their fast
+/// field values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FunctionScoreQuery {
+    pub query: Box<QueryAst>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<ScoreFunction>,
+    #[serde(default)]
+    pub score_mode: FunctionScoreMode,
+    #[serde(default)]
+    pub boost_mode: FunctionBoostMode,
+    /// Upper bound of the combined function score.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_boost: Option<NotNaNf32>,
+    /// Documents with a score lower than `min_score` are excluded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<NotNaNf32>,
+}
+
+impl From<FunctionScoreQuery> for QueryAst {
+    fn from(function_score_query: FunctionScoreQuery) -> Self {
+        QueryAst::FunctionScore(function_score_query)
+    }
+}
+
+/// How the scores of the different functions are combined.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionScoreMode {
+    #[default]
+    Multiply,
+    Sum,
+    /// Average weighted by the function weights.
+    Avg,
+    First,
+    Max,
+    Min,
+}
+
+/// How the combined function score is combined with the score of the query.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionBoostMode {
+    #[default]
+    Multiply,
+    Replace,
+    Sum,
+    Avg,
+    Max,
+    Min,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScoreFunction {
+    /// If set, the function only applies to the documents matching the filter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryAst>,
+    /// Multiplier applied to the value of the function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<NotNaNf32>,
+    pub function: ScoreFunctionKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreFunctionKind {
+    /// Constant function, equal to its weight.
+    Weight,
+    FieldValueFactor(FieldValueFactorFunction),
+    Decay(DecayFunction),
+}
+
+/// Scores documents with `modifier(factor * field_value)`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct FieldValueFactorFunction {
+    pub field: String,
+    #[serde(default = "default_factor")]
+    pub factor: NotNaNf32,
+    #[serde(default)]
+    pub modifier: FieldValueFactorModifier,
+    /// Value used for documents without a value. If it is not set, the function does not apply
+    /// to these documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing: Option<NotNaNf32>,
+}
+
+fn default_factor() -> NotNaNf32 {
+    NotNaNf32::ONE
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldValueFactorModifier {
+    #[default]
+    None,
+    Log,
+    Log1p,
+    Log2p,
+    Ln,
+    Ln1p,
+    Ln2p,
+    Square,
+    Sqrt,
+    Reciprocal,
+}
+
+impl FieldValueFactorModifier {
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            FieldValueFactorModifier::None => value,
+            FieldValueFactorModifier::Log => value.log10(),
+            FieldValueFactorModifier::Log1p => (value + 1.0).log10(),
+            FieldValueFactorModifier::Log2p => (value + 2.0).log10(),
+            FieldValueFactorModifier::Ln => value.ln(),
+            FieldValueFactorModifier::Ln1p => value.ln_1p(),
+            FieldValueFactorModifier::Ln2p => (value + 2.0).ln(),
+            FieldValueFactorModifier::Square => value * value,
+            FieldValueFactorModifier::Sqrt => value.sqrt(),
+            FieldValueFactorModifier::Reciprocal => 1.0 / value,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayCurve {
+    Gauss,
+    Exp,
+    Linear,
+}
+
+/// Scores documents depending on the distance between their field value and `origin`.
+///
+/// Documents within `offset` of the origin get a score of 1, and documents at `offset + scale`
+/// get a score of `decay`. Documents without a value get a score of 1.
+///
+/// On date fields, `origin` is a date and defaults to now, and `scale` and `offset` are
+/// durations such as `7d` or `12h`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct DecayFunction {
+    pub field: String,
+    pub curve: DecayCurve,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<JsonLiteral>,
+    pub scale: JsonLiteral,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<JsonLiteral>,
+    #[serde(default = "default_decay")]
+    pub decay: NotNaNf32,
+}
+
+fn default_decay() -> NotNaNf32 {
+    NotNaNf32::try_from(0.5f32).unwrap()
+}
+
+impl DecayCurve {
+    /// Returns the score of a document at `distance` of the origin, once the offset has been
+    /// subtracted.
+    fn score(self, distance: f64, scale: f64, decay: f64) -> f64 {
+        match self {
+            DecayCurve::Gauss => {
+                let sigma_squared = -scale * scale / (2.0 * decay.ln());
+                (-distance * distance / (2.0 * sigma_squared)).exp()
+            }
+            DecayCurve::Exp => {
+                let lambda = decay.ln() / scale;
+                (lambda * distance).exp()
+            }
+            DecayCurve::Linear => {
+                let zero_distance = scale / (1.0 - decay);
+                ((zero_distance - distance) / zero_distance).max(0.0)
+            }
+        }
+    }
+}
+
+/// Parses a duration such as `7d` or `500ms` into microseconds.
+fn parse_duration_micros(duration: &str) -> Option<f64> {
+    let duration = duration.trim();
+    let unit_start = duration.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (amount_str, unit) = duration.split_at(unit_start);
+    let amount: f64 = amount_str.parse().ok()?;
+    let unit_micros: f64 = match unit {
+        "nanos" => 0.001,
+        "micros" => 1.0,
+        "ms" => 1_000.0,
+        "s" => 1_000_000.0,
+        "m" => 60.0 * 1_000_000.0,
+        "h" => 3_600.0 * 1_000_000.0,
+        "d" => 86_400.0 * 1_000_000.0,
+        "w" => 7.0 * 86_400.0 * 1_000_000.0,
+        _ => return None,
+    };
+    Some(amount * unit_micros)
+}
+
+/// Interprets a duration on a date field. Numbers are milliseconds.
+fn interpret_duration_micros(duration: &JsonLiteral) -> Option<f64> {
+    match duration {
+        JsonLiteral::String(duration_str) => parse_duration_micros(duration_str),
+        JsonLiteral::Number(_) => f64::interpret_json(duration).map(|millis| millis * 1_000.0),
+        JsonLiteral::Bool(_) => None,
+    }
+}
+
+fn interpret_date_micros(date: &JsonLiteral) -> Option<f64> {
+    if let JsonLiteral::String(date_str) = date {
+        if date_str == "now" {
+            let now = DateTime::from_utc(OffsetDateTime::now_utc());
+            return Some(now.into_timestamp_micros() as f64);
+        }
+    }
+    DateTime::interpret_json(date).map(|date_time| date_time.into_timestamp_micros() as f64)
+}
+
+fn invalid_function(message: String) -> InvalidQuery {
+    InvalidQuery::Other(anyhow::anyhow!(message))
+}
+
+/// Checks that the field exists and is a fast field. Returns whether it is a date field.
+fn check_fast_field(field_name: &str, schema: &TantivySchema) -> Result<bool, InvalidQuery> {
+    let (_, field_entry, _) = super::utils::find_field_or_hit_dynamic(field_name, schema)
+        .ok_or_else(|| InvalidQuery::FieldDoesNotExist {
+            full_path: field_name.to_string(),
+        })?;
+    if !field_entry.is_fast() {
+        return Err(InvalidQuery::SchemaError(format!(
+            "score functions are only supported for fast fields. (`{}` is not a fast field)",
+            field_entry.name()
+        )));
+    }
+    Ok(matches!(field_entry.field_type(), FieldType::Date(_)))
+}
+
+impl FieldValueFactorFunction {
+    fn build_value_function(&self, schema: &TantivySchema) -> Result<ValueFunction, InvalidQuery> {
+        check_fast_field(&self.field, schema)?;
+        Ok(ValueFunction::FieldValueFactor {
+            field: self.field.clone(),
+            factor: f32::from(self.factor) as f64,
+            modifier: self.modifier,
+            missing: self.missing.map(|missing| f32::from(missing) as f64),
+        })
+    }
+}
+
+impl DecayFunction {
+    fn build_value_function(&self, schema: &TantivySchema) -> Result<ValueFunction, InvalidQuery> {
+        let is_date_field = check_fast_field(&self.field, schema)?;
+        let invalid_param = |param_name: &str| {
+            invalid_function(format!(
+                "invalid `{param_name}` in decay function on field `{}`",
+                self.field
+            ))
+        };
+        let (origin, scale, offset) = if is_date_field {
+            let origin = match &self.origin {
+                Some(origin) => {
+                    interpret_date_micros(origin).ok_or_else(|| invalid_param("origin"))?
+                }
+                None => interpret_date_micros(&JsonLiteral::String("now".to_string()))
+                    .expect("now should be a valid date"),
+            };
+            let scale =
+                interpret_duration_micros(&self.scale).ok_or_else(|| invalid_param("scale"))?;
+            let offset = match &self.offset {
+                Some(offset) => {
+                    interpret_duration_micros(offset).ok_or_else(|| invalid_param("offset"))?
+                }
+                None => 0.0,
+            };
+            (origin, scale, offset)
+        } else {
+            let origin = self
+                .origin
+                .as_ref()
+                .and_then(f64::interpret_json)
+                .ok_or_else(|| invalid_param("origin"))?;
+            let scale = f64::interpret_json(&self.scale).ok_or_else(|| invalid_param("scale"))?;
+            let offset = match &self.offset {
+                Some(offset) => {
+                    f64::interpret_json(offset).ok_or_else(|| invalid_param("offset"))?
+                }
+                None => 0.0,
+            };
+            (origin, scale, offset)
+        };
+        if scale <= 0.0 {
+            return Err(invalid_param("scale"));
+        }
+        if offset < 0.0 {
+            return Err(invalid_param("offset"));
+        }
+        let decay = f32::from(self.decay) as f64;
+        if decay <= 0.0 || decay >= 1.0 {
+            return Err(invalid_function(format!(
+                "`decay` must be strictly between 0 and 1 in decay function on field `{}`",
+                self.field
+            )));
+        }
+        Ok(ValueFunction::Decay {
+            field: self.field.clone(),
+            curve: self.curve,
+            origin,
+            scale,
+            offset,
+            decay,
+        })
+    }
+}
+
+impl BuildTantivyAst for FunctionScoreQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+        search_fields: &[String],
+        with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        let query = self.query.build_tantivy_ast_call(
+            schema,
+            tokenizer_manager,
+            search_fields,
+            with_validation,
+        )?;
+        if query.const_predicate() == Some(MatchAllOrNone::MatchNone) {
+            return Ok(TantivyQueryAst::match_none());
+        }
+        let mut functions = Vec::with_capacity(self.functions.len());
+        for function in &self.functions {
+            let filter = if let Some(filter) = &function.filter {
+                let filter = filter.build_tantivy_ast_call(
+                    schema,
+                    tokenizer_manager,
+                    search_fields,
+                    with_validation,
+                )?;
+                match filter.const_predicate() {
+                    // The function never applies.
+                    Some(MatchAllOrNone::MatchNone) => continue,
+                    Some(MatchAllOrNone::MatchAll) => None,
+                    None => Some(filter.simplify().into()),
+                }
+            } else {
+                None
+            };
+            let value_function = match &function.function {
+                ScoreFunctionKind::Weight => ValueFunction::Weight,
+                ScoreFunctionKind::FieldValueFactor(field_value_factor) => {
+                    field_value_factor.build_value_function(schema)?
+                }
+                ScoreFunctionKind::Decay(decay) => decay.build_value_function(schema)?,
+            };
+            functions.push(TantivyScoreFunction {
+                filter,
+                weight: function.weight.map(f32::from).unwrap_or(1.0),
+                value_function,
+            });
+        }
+        let function_score_query = TantivyFunctionScoreQuery {
+            query: query.simplify().into(),
+            functions,
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+            max_boost: self.max_boost.map(f32::from).unwrap_or(f32::MAX),
+            min_score: self.min_score.map(f32::from),
+        };
+        Ok(function_score_query.into())
+    }
+}
+
+mod function_score {
+    use tantivy::columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+    use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+    use tantivy::{DateTime, DocId, DocSet, Score, SegmentReader, TantivyError, Term, TERMINATED};
+
+    use super::{DecayCurve, FieldValueFactorModifier, FunctionBoostMode, FunctionScoreMode};
+
+    /// A score function, with its parameters resolved against the schema.
+    #[derive(Clone, Debug)]
+    pub enum ValueFunction {
+        Weight,
+        FieldValueFactor {
+            field: String,
+            factor: f64,
+            modifier: FieldValueFactorModifier,
+            missing: Option<f64>,
+        },
+        Decay {
+            field: String,
+            curve: DecayCurve,
+            origin: f64,
+            scale: f64,
+            offset: f64,
+            decay: f64,
+        },
+    }
+
+    #[derive(Debug)]
+    pub struct TantivyScoreFunction {
+        pub filter: Option<Box<dyn Query>>,
+        pub weight: f32,
+        pub value_function: ValueFunction,
+    }
+
+    impl Clone for TantivyScoreFunction {
+        fn clone(&self) -> Self {
+            TantivyScoreFunction {
+                filter: self.filter.as_ref().map(|filter| filter.box_clone()),
+                weight: self.weight,
+                value_function: self.value_function.clone(),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct TantivyFunctionScoreQuery {
+        pub query: Box<dyn Query>,
+        pub functions: Vec<TantivyScoreFunction>,
+        pub score_mode: FunctionScoreMode,
+        pub boost_mode: FunctionBoostMode,
+        pub max_boost: f32,
+        pub min_score: Option<f32>,
+    }
+
+    impl Clone for TantivyFunctionScoreQuery {
+        fn clone(&self) -> Self {
+            TantivyFunctionScoreQuery {
+                query: self.query.box_clone(),
+                functions: self.functions.clone(),
+                score_mode: self.score_mode,
+                boost_mode: self.boost_mode,
+                max_boost: self.max_boost,
+                min_score: self.min_score,
+            }
+        }
+    }
+
+    impl Query for TantivyFunctionScoreQuery {
+        fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+            let query_weight = self.query.weight(enable_scoring)?;
+            let functions = self
+                .functions
+                .iter()
+                .map(|function| {
+                    let filter_weight = function
+                        .filter
+                        .as_ref()
+                        .map(|filter| filter.weight(enable_scoring))
+                        .transpose()?;
+                    Ok(FunctionWeight {
+                        filter_weight,
+                        weight: function.weight,
+                        value_function: function.value_function.clone(),
+                    })
+                })
+                .collect::<tantivy::Result<Vec<FunctionWeight>>>()?;
+            Ok(Box::new(FunctionScoreWeight {
+                query_weight,
+                functions,
+                score_mode: self.score_mode,
+                boost_mode: self.boost_mode,
+                max_boost: self.max_boost,
+                min_score: self.min_score,
+            }))
+        }
+
+        fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+            self.query.query_terms(visitor);
+            for filter in self
+                .functions
+                .iter()
+                .filter_map(|function| function.filter.as_ref())
+            {
+                filter.query_terms(visitor);
+            }
+        }
+    }
+
+    struct FunctionWeight {
+        filter_weight: Option<Box<dyn Weight>>,
+        weight: f32,
+        value_function: ValueFunction,
+    }
+
+    impl FunctionWeight {
+        fn segment_function(&self, reader: &SegmentReader) -> tantivy::Result<SegmentFunction> {
+            let filter_scorer = self
+                .filter_weight
+                .as_ref()
+                .map(|filter_weight| filter_weight.scorer(reader, 1.0))
+                .transpose()?;
+            let value_function = match &self.value_function {
+                ValueFunction::Weight => SegmentValueFunction::Weight,
+                ValueFunction::FieldValueFactor {
+                    field,
+                    factor,
+                    modifier,
+                    missing,
+                } => SegmentValueFunction::FieldValueFactor {
+                    column_opt: NumericColumn::open(reader, field)?,
+                    factor: *factor,
+                    modifier: *modifier,
+                    missing: *missing,
+                },
+                ValueFunction::Decay {
+                    field,
+                    curve,
+                    origin,
+                    scale,
+                    offset,
+                    decay,
+                } => SegmentValueFunction::Decay {
+                    column_opt: NumericColumn::open(reader, field)?,
+                    curve: *curve,
+                    origin: *origin,
+                    scale: *scale,
+                    offset: *offset,
+                    decay: *decay,
+                },
+            };
+            Ok(SegmentFunction {
+                filter_scorer,
+                weight: self.weight,
+                value_function,
+            })
+        }
+    }
+
+    /// A fast field column, with its values converted to `f64`.
+    ///
+    /// Dates are expressed in microseconds since the epoch.
+    struct NumericColumn {
+        column: Column<u64>,
+        column_type: ColumnType,
+    }
+
+    impl NumericColumn {
+        fn open(reader: &SegmentReader, field: &str) -> tantivy::Result<Option<NumericColumn>> {
+            let column_opt = reader.fast_fields().u64_lenient(field)?;
+            Ok(column_opt.map(|(column, column_type)| NumericColumn {
+                column,
+                column_type,
+            }))
+        }
+
+        fn first(&self, doc: DocId) -> Option<f64> {
+            let value = self.column.first(doc)?;
+            match self.column_type {
+                ColumnType::U64 | ColumnType::Bool => Some(value as f64),
+                ColumnType::I64 => Some(i64::from_u64(value) as f64),
+                ColumnType::F64 => Some(f64::from_u64(value)),
+                ColumnType::DateTime => {
+                    Some(DateTime::from_u64(value).into_timestamp_micros() as f64)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    enum SegmentValueFunction {
+        Weight,
+        FieldValueFactor {
+            column_opt: Option<NumericColumn>,
+            factor: f64,
+            modifier: FieldValueFactorModifier,
+            missing: Option<f64>,
+        },
+        Decay {
+            column_opt: Option<NumericColumn>,
+            curve: DecayCurve,
+            origin: f64,
+            scale: f64,
+            offset: f64,
+            decay: f64,
+        },
+    }
+
+    impl SegmentValueFunction {
+        /// Returns `None` if the function does not apply to the document.
+        fn value(&self, doc: DocId) -> Option<f64> {
+            match self {
+                SegmentValueFunction::Weight => Some(1.0),
+                SegmentValueFunction::FieldValueFactor {
+                    column_opt,
+                    factor,
+                    modifier,
+                    missing,
+                } => {
+                    let field_value = column_opt
+                        .as_ref()
+                        .and_then(|column| column.first(doc))
+                        .or(*missing)?;
+                    let value = modifier.apply(factor * field_value);
+                    // Negative and invalid values (for instance the log of 0) would break the
+                    // ranking.
+                    if value.is_finite() && value > 0.0 {
+                        Some(value)
+                    } else {
+                        Some(0.0)
+                    }
+                }
+                SegmentValueFunction::Decay {
+                    column_opt,
+                    curve,
+                    origin,
+                    scale,
+                    offset,
+                    decay,
+                } => {
+                    let Some(field_value) =
+                        column_opt.as_ref().and_then(|column| column.first(doc))
+                    else {
+                        return Some(1.0);
+                    };
+                    let distance = ((field_value - origin).abs() - offset).max(0.0);
+                    Some(curve.score(distance, *scale, *decay))
+                }
+            }
+        }
+    }
+
+    struct SegmentFunction {
+        filter_scorer: Option<Box<dyn Scorer>>,
+        weight: f32,
+        value_function: SegmentValueFunction,
+    }
+
+    impl SegmentFunction {
+        /// Returns the value of the function and its weight, or `None` if the function does not
+        /// apply to the document. Documents must be visited in increasing order.
+        fn value_and_weight(&mut self, doc: DocId) -> Option<(f32, f32)> {
+            if let Some(filter_scorer) = &mut self.filter_scorer {
+                if filter_scorer.doc() < doc {
+                    filter_scorer.seek(doc);
+                }
+                if filter_scorer.doc() != doc {
+                    return None;
+                }
+            }
+            let value = self.value_function.value(doc)? as f32;
+            Some((value, self.weight))
+        }
+    }
+
+    struct FunctionScoreWeight {
+        query_weight: Box<dyn Weight>,
+        functions: Vec<FunctionWeight>,
+        score_mode: FunctionScoreMode,
+        boost_mode: FunctionBoostMode,
+        max_boost: f32,
+        min_score: Option<f32>,
+    }
+
+    impl Weight for FunctionScoreWeight {
+        fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+            let query_scorer = self.query_weight.scorer(reader, 1.0)?;
+            let functions = self
+                .functions
+                .iter()
+                .map(|function| function.segment_function(reader))
+                .collect::<tantivy::Result<Vec<SegmentFunction>>>()?;
+            let mut scorer = FunctionScoreScorer {
+                query_scorer,
+                functions,
+                score_mode: self.score_mode,
+                boost_mode: self.boost_mode,
+                max_boost: self.max_boost,
+                min_score: self.min_score,
+                boost,
+                score: 0.0,
+            };
+            if scorer.doc() != TERMINATED && !scorer.score_current_doc() {
+                scorer.advance();
+            }
+            Ok(Box::new(scorer))
+        }
+
+        fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+            let mut scorer = self.scorer(reader, 1.0)?;
+            if scorer.seek(doc) != doc {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Document #({doc}) does not match"
+                )));
+            }
+            let mut explanation = Explanation::new("FunctionScoreQuery", scorer.score());
+            explanation.add_detail(self.query_weight.explain(reader, doc)?);
+            Ok(explanation)
+        }
+    }
+
+    struct FunctionScoreScorer {
+        query_scorer: Box<dyn Scorer>,
+        functions: Vec<SegmentFunction>,
+        score_mode: FunctionScoreMode,
+        boost_mode: FunctionBoostMode,
+        max_boost: f32,
+        min_score: Option<f32>,
+        boost: Score,
+        // Score of the current document, before applying `boost`.
+        score: Score,
+    }
+
+    impl FunctionScoreScorer {
+        fn function_score(&mut self, doc: DocId) -> Score {
+            let mut num_matching_functions = 0;
+            let mut combined_score: Score = match self.score_mode {
+                FunctionScoreMode::Multiply | FunctionScoreMode::First => 1.0,
+                FunctionScoreMode::Sum | FunctionScoreMode::Avg => 0.0,
+                FunctionScoreMode::Max => Score::MIN,
+                FunctionScoreMode::Min => Score::MAX,
+            };
+            let mut total_weight: Score = 0.0;
+            for function in &mut self.functions {
+                let Some((value, weight)) = function.value_and_weight(doc) else {
+                    continue;
+                };
+                num_matching_functions += 1;
+                let weighted_value = value * weight;
+                match self.score_mode {
+                    FunctionScoreMode::Multiply => combined_score *= weighted_value,
+                    FunctionScoreMode::Sum | FunctionScoreMode::Avg => {
+                        combined_score += weighted_value;
+                        total_weight += weight;
+                    }
+                    FunctionScoreMode::First => {
+                        combined_score = weighted_value;
+                        break;
+                    }
+                    FunctionScoreMode::Max => combined_score = combined_score.max(weighted_value),
+                    FunctionScoreMode::Min => combined_score = combined_score.min(weighted_value),
+                }
+            }
+            if num_matching_functions == 0 {
+                return 1.0f32.min(self.max_boost);
+            }
+            if self.score_mode == FunctionScoreMode::Avg && total_weight > 0.0 {
+                combined_score /= total_weight;
+            }
+            combined_score.min(self.max_boost)
+        }
+
+        /// Computes the score of the current document, and returns whether it reaches
+        /// `min_score`.
+        fn score_current_doc(&mut self) -> bool {
+            let doc = self.query_scorer.doc();
+            let query_score = self.query_scorer.score();
+            let function_score = self.function_score(doc);
+            self.score = match self.boost_mode {
+                FunctionBoostMode::Multiply => query_score * function_score,
+                FunctionBoostMode::Replace => function_score,
+                FunctionBoostMode::Sum => query_score + function_score,
+                FunctionBoostMode::Avg => (query_score + function_score) / 2.0,
+                FunctionBoostMode::Max => query_score.max(function_score),
+                FunctionBoostMode::Min => query_score.min(function_score),
+            };
+            self.min_score
+                .map_or(true, |min_score| self.score >= min_score)
+        }
+    }
+
+    impl DocSet for FunctionScoreScorer {
+        fn advance(&mut self) -> DocId {
+            loop {
+                let doc = self.query_scorer.advance();
+                if doc == TERMINATED || self.score_current_doc() {
+                    return doc;
+                }
+            }
+        }
+
+        fn seek(&mut self, target: DocId) -> DocId {
+            let doc = self.query_scorer.seek(target);
+            if doc == TERMINATED || self.score_current_doc() {
+                return doc;
+            }
+            self.advance()
+        }
+
+        fn doc(&self) -> DocId {
+            self.query_scorer.doc()
+        }
+
+        fn size_hint(&self) -> u32 {
+            self.query_scorer.size_hint()
+        }
+    }
+
+    impl Scorer for FunctionScoreScorer {
+        fn score(&mut self) -> Score {
+            self.score * self.boost
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::collector::TopDocs;
+    use tantivy::query::EnableScoring;
+    use tantivy::schema::{Schema as TantivySchema, FAST, INDEXED, STRING};
+    use tantivy::{doc, DateTime, Index};
+
+    use super::*;
+    use crate::create_default_quickwit_tokenizer_manager;
+    use crate::query_ast::TermQuery;
+
+    fn build_index() -> Index {
+        let mut schema_builder = TantivySchema::builder();
+        let kind = schema_builder.add_text_field("kind", STRING);
+        let severity = schema_builder.add_u64_field("severity", FAST | INDEXED);
+        let timestamp = schema_builder.add_date_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for (doc_kind, doc_severity, doc_timestamp_secs) in [
+            ("error", 1u64, 1_000i64),
+            ("error", 5u64, 2_000i64),
+            ("warn", 3u64, 3_000i64),
+        ] {
+            index_writer
+                .add_document(doc!(
+                    kind => doc_kind,
+                    severity => doc_severity,
+                    timestamp => DateTime::from_timestamp_secs(doc_timestamp_secs),
+                ))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        index
+    }
+
+    /// Returns the scores of the matching documents, by doc id.
+    fn search(index: &Index, function_score_query: FunctionScoreQuery) -> Vec<(u32, f32)> {
+        let schema = index.schema();
+        let tantivy_query: Box<dyn tantivy::query::Query> = QueryAst::from(function_score_query)
+            .build_tantivy_query(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let top_docs = searcher
+            .search(&tantivy_query, &TopDocs::with_limit(10))
+            .unwrap();
+        let mut scores: Vec<(u32, f32)> = top_docs
+            .into_iter()
+            .map(|(score, doc_address)| (doc_address.doc_id, score))
+            .collect();
+        scores.sort_by_key(|(doc_id, _)| *doc_id);
+        // Checks that the scorer can also be used to only count documents.
+        let num_docs = tantivy_query
+            .weight(EnableScoring::disabled_from_searcher(&searcher))
+            .unwrap()
+            .count(searcher.segment_reader(0))
+            .unwrap();
+        assert_eq!(num_docs as usize, scores.len());
+        scores
+    }
+
+    fn function_score_query(functions: Vec<ScoreFunction>) -> FunctionScoreQuery {
+        FunctionScoreQuery {
+            query: Box::new(QueryAst::MatchAll),
+            functions,
+            score_mode: FunctionScoreMode::default(),
+            boost_mode: FunctionBoostMode::default(),
+            max_boost: None,
+            min_score: None,
+        }
+    }
+
+    fn field_value_factor(modifier: FieldValueFactorModifier) -> ScoreFunction {
+        ScoreFunction {
+            filter: None,
+            weight: None,
+            function: ScoreFunctionKind::FieldValueFactor(FieldValueFactorFunction {
+                field: "severity".to_string(),
+                factor: NotNaNf32::try_from(2.0f32).unwrap(),
+                modifier,
+                missing: None,
+            }),
+        }
+    }
+
+    fn weight(weight: f32, filter: Option<QueryAst>) -> ScoreFunction {
+        ScoreFunction {
+            filter,
+            weight: Some(NotNaNf32::try_from(weight).unwrap()),
+            function: ScoreFunctionKind::Weight,
+        }
+    }
+
+    fn kind_filter(kind: &str) -> QueryAst {
+        TermQuery {
+            field: "kind".to_string(),
+            value: kind.to_string(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_function_score_field_value_factor() {
+        let index = build_index();
+        let query = function_score_query(vec![field_value_factor(FieldValueFactorModifier::None)]);
+        assert_eq!(search(&index, query), vec![(0, 2.0), (1, 10.0), (2, 6.0)]);
+        let query =
+            function_score_query(vec![field_value_factor(FieldValueFactorModifier::Square)]);
+        assert_eq!(search(&index, query), vec![(0, 4.0), (1, 100.0), (2, 36.0)]);
+    }
+
+    #[test]
+    fn test_function_score_weight_with_filter_and_score_modes() {
+        let index = build_index();
+        let mut query = function_score_query(vec![
+            weight(3.0, Some(kind_filter("error"))),
+            weight(2.0, None),
+        ]);
+        assert_eq!(
+            search(&index, query.clone()),
+            vec![(0, 6.0), (1, 6.0), (2, 2.0)]
+        );
+        query.score_mode = FunctionScoreMode::Sum;
+        assert_eq!(
+            search(&index, query.clone()),
+            vec![(0, 5.0), (1, 5.0), (2, 2.0)]
+        );
+        query.score_mode = FunctionScoreMode::First;
+        assert_eq!(
+            search(&index, query.clone()),
+            vec![(0, 3.0), (1, 3.0), (2, 2.0)]
+        );
+        query.score_mode = FunctionScoreMode::Min;
+        assert_eq!(
+            search(&index, query.clone()),
+            vec![(0, 2.0), (1, 2.0), (2, 2.0)]
+        );
+        query.max_boost = Some(NotNaNf32::try_from(1.5f32).unwrap());
+        assert_eq!(search(&index, query), vec![(0, 1.5), (1, 1.5), (2, 1.5)]);
+    }
+
+    #[test]
+    fn test_function_score_avg_is_weighted() {
+        let index = build_index();
+        let mut query = function_score_query(vec![
+            ScoreFunction {
+                weight: Some(NotNaNf32::try_from(3.0f32).unwrap()),
+                ..field_value_factor(FieldValueFactorModifier::None)
+            },
+            weight(1.0, None),
+        ]);
+        query.score_mode = FunctionScoreMode::Avg;
+        // (3 * 2 * severity + 1) / 4
+        assert_eq!(search(&index, query), vec![(0, 1.75), (1, 7.75), (2, 4.75)]);
+    }
+
+    #[test]
+    fn test_function_score_boost_mode_and_min_score() {
+        let index = build_index();
+        let mut query =
+            function_score_query(vec![field_value_factor(FieldValueFactorModifier::None)]);
+        query.query = Box::new(QueryAst::MatchAll.boost(NotNaNf32::try_from(2.0f32).ok()));
+        query.boost_mode = FunctionBoostMode::Sum;
+        assert_eq!(
+            search(&index, query.clone()),
+            vec![(0, 4.0), (1, 12.0), (2, 8.0)]
+        );
+        query.min_score = Some(NotNaNf32::try_from(8.0f32).unwrap());
+        assert_eq!(search(&index, query.clone()), vec![(1, 12.0), (2, 8.0)]);
+        query.boost_mode = FunctionBoostMode::Replace;
+        assert_eq!(search(&index, query), vec![(1, 10.0)]);
+    }
+
+    #[test]
+    fn test_function_score_decay() {
+        let index = build_index();
+        let decay_function =
+            |curve, field: &str, origin: JsonLiteral, scale: JsonLiteral| ScoreFunction {
+                filter: None,
+                weight: None,
+                function: ScoreFunctionKind::Decay(DecayFunction {
+                    field: field.to_string(),
+                    curve,
+                    origin: Some(origin),
+                    scale,
+                    offset: None,
+                    decay: default_decay(),
+                }),
+            };
+        let query = function_score_query(vec![decay_function(
+            DecayCurve::Linear,
+            "severity",
+            JsonLiteral::from(1u64),
+            JsonLiteral::from(2u64),
+        )]);
+        assert_eq!(search(&index, query), vec![(0, 1.0), (1, 0.0), (2, 0.5)]);
+        let query = function_score_query(vec![decay_function(
+            DecayCurve::Exp,
+            "timestamp",
+            JsonLiteral::String("1970-01-01T00:50:00Z".to_string()),
+            JsonLiteral::String("1000s".to_string()),
+        )]);
+        assert_eq!(search(&index, query), vec![(0, 0.25), (1, 0.5), (2, 1.0)]);
+        let query = function_score_query(vec![decay_function(
+            DecayCurve::Gauss,
+            "severity",
+            JsonLiteral::from(5u64),
+            JsonLiteral::from(2u64),
+        )]);
+        let scores = search(&index, query);
+        assert_eq!(scores[1], (1, 1.0));
+        assert!((scores[2].1 - 0.5).abs() < 1e-6);
+        assert!((scores[0].1 - 0.0625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_function_score_invalid_functions() {
+        let index = build_index();
+        let schema = index.schema();
+        let build = |function: ScoreFunction| {
+            QueryAst::from(function_score_query(vec![function])).build_tantivy_query(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+        };
+        let not_fast_field = ScoreFunction {
+            filter: None,
+            weight: None,
+            function: ScoreFunctionKind::FieldValueFactor(FieldValueFactorFunction {
+                field: "kind".to_string(),
+                factor: NotNaNf32::ONE,
+                modifier: FieldValueFactorModifier::None,
+                missing: None,
+            }),
+        };
+        let error = build(not_fast_field).unwrap_err();
+        assert!(error.to_string().contains("not a fast field"));
+        let invalid_decay = ScoreFunction {
+            filter: None,
+            weight: None,
+            function: ScoreFunctionKind::Decay(DecayFunction {
+                field: "timestamp".to_string(),
+                curve: DecayCurve::Gauss,
+                origin: None,
+                scale: JsonLiteral::String("10 parsecs".to_string()),
+                offset: None,
+                decay: default_decay(),
+            }),
+        };
+        let error = build(invalid_decay).unwrap_err();
+        assert!(error.to_string().contains("invalid `scale`"));
+    }
+
+    #[test]
+    fn test_parse_duration_micros() {
+        assert_eq!(parse_duration_micros("500ms"), Some(500_000.0));
+        assert_eq!(parse_duration_micros("1.5h"), Some(5_400_000_000.0));
+        assert_eq!(parse_duration_micros("7d"), Some(604_800_000_000.0));
+        assert_eq!(parse_duration_micros("7"), None);
+        assert_eq!(parse_duration_micros("7y"), None);
+    }
+
+    #[test]
+    fn test_function_score_query_serialization() {
+        let query_ast: QueryAst = function_score_query(vec![weight(2.0, None)]).into();
+        let query_ast_json = serde_json::to_value(&query_ast).unwrap();
+        assert_eq!(
+            query_ast_json,
+            serde_json::json!({
+                "type": "function_score",
+                "query": {"type": "match_all"},
+                "functions": [{"weight": 2.0, "function": "weight"}],
+                "score_mode": "multiply",
+                "boost_mode": "multiply",
+            })
+        );
+        let deserialized_query_ast: QueryAst = serde_json::from_value(query_ast_json).unwrap();
+        assert_eq!(deserialized_query_ast, query_ast);
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tantivy::query::{FuzzyTermQuery as TantivyFuzzyTermQuery, TermQuery as TantivyTermQuery};
+use tantivy::schema::{IndexRecordOption, Schema as TantivySchema, Type};
+use tantivy::Term;
+use tantivy_fst::automaton::AlwaysMatch;
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::query_ast::tantivy_query_ast::{TantivyBoolQuery, TantivyQueryAst};
+use crate::query_ast::{AutomatonQuery, FullTextMode, FullTextParams, JsonPathPrefix};
+use crate::tokenizers::TokenizerManager;
+use crate::{BooleanOperand, InvalidQuery};
+
+/// Maximum edit distance supported by tantivy's levenshtein automata.
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+/// The only `max_expansions` accepted by fuzzy queries, the Elasticsearch default. Tantivy does
+/// not cap the number of terms a fuzzy term query expands to.
+pub const FUZZY_MAX_EXPANSIONS: u32 = 50;
+
+/// Byte marking the end of the path in the serialized value of a json term.
+const JSON_END_OF_PATH: u8 = 0u8;
+
+/// Maximum edit distance allowed to match a term, as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/common-options.html#fuzziness>
+///
+/// It is serialized the Elasticsearch way: either as a number or as `AUTO[:low,high]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fuzziness {
+    /// Fixed edit distance.
+    Distance(u8),
+    /// Edit distance depending on the length of the term: terms shorter than `low` characters
+    /// must match exactly, terms shorter than `high` characters can be one edit away, longer
+    /// terms can be two edits away.
+    Auto { low: u32, high: u32 },
+}
+
+impl Default for Fuzziness {
+    fn default() -> Self {
+        Fuzziness::Auto { low: 3, high: 6 }
+    }
+}
+
+impl Fuzziness {
+    /// Returns the edit distance to apply to the given term text.
+    pub fn distance_for_text(&self, text: &str) -> u8 {
+        match *self {
+            Fuzziness::Distance(distance) => distance,
+            Fuzziness::Auto { low, high } => {
+                let num_chars = text.chars().count();
+                if num_chars < low as usize {
+                    0
+                } else if num_chars < high as usize {
+                    1
+                } else {
+                    2
+                }
+            }
+        }
+    }
+}
+
+fn parse_fuzzy_distance(distance_str: &str) -> Result<u8, String> {
+    let distance: u8 = distance_str
+        .parse()
+        .map_err(|_| format!("invalid fuzziness `{distance_str}`"))?;
+    if distance > MAX_FUZZY_DISTANCE {
+        return Err(format!(
+            "fuzziness must be at most {MAX_FUZZY_DISTANCE}, got `{distance}`"
+        ));
+    }
+    Ok(distance)
+}
+
+impl FromStr for Fuzziness {
+    type Err = String;
+
+    fn from_str(fuzziness_str: &str) -> Result<Self, Self::Err> {
+        let Some(auto_params) = fuzziness_str.strip_prefix("AUTO") else {
+            return parse_fuzzy_distance(fuzziness_str.trim()).map(Fuzziness::Distance);
+        };
+        if auto_params.is_empty() {
+            return Ok(Fuzziness::default());
+        }
+        let invalid_auto_fuzziness = || format!("invalid fuzziness `{fuzziness_str}`");
+        let (low_str, high_str) = auto_params
+            .strip_prefix(':')
+            .and_then(|low_high| low_high.split_once(','))
+            .ok_or_else(invalid_auto_fuzziness)?;
+        let low: u32 = low_str
+            .trim()
+            .parse()
+            .map_err(|_| invalid_auto_fuzziness())?;
+        let high: u32 = high_str
+            .trim()
+            .parse()
+            .map_err(|_| invalid_auto_fuzziness())?;
+        if low > high {
+            return Err(invalid_auto_fuzziness());
+        }
+        Ok(Fuzziness::Auto { low, high })
+    }
+}
+
+impl fmt::Display for Fuzziness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fuzziness::Distance(distance) => write!(f, "{distance}"),
+            Fuzziness::Auto { low, high } => write!(f, "AUTO:{low},{high}"),
+        }
+    }
+}
+
+impl Serialize for Fuzziness {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        match self {
+            Fuzziness::Distance(distance) => serializer.serialize_u8(*distance),
+            Fuzziness::Auto { .. } => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Fuzziness {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum NumberOrString {
+            Number(u64),
+            String(String),
+        }
+        let fuzziness_str = match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(distance) => distance.to_string(),
+            NumberOrString::String(fuzziness_str) => fuzziness_str,
+        };
+        fuzziness_str.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Splits the serialized value of a text or json text term into the json path prefix (empty for
+/// text fields) and the text itself.
+fn split_json_path_and_text(term: &Term) -> Option<(&[u8], &str)> {
+    let value_bytes = term.serialized_value_bytes();
+    match term.typ() {
+        Type::Str => Some((&[][..], std::str::from_utf8(value_bytes).ok()?)),
+        Type::Json => {
+            // Json terms are serialized as `<path>\0<type code><value>`.
+            let end_of_path_pos = value_bytes
+                .iter()
+                .position(|byte| *byte == JSON_END_OF_PATH)?;
+            let text_start_pos = end_of_path_pos + 2;
+            if text_start_pos > value_bytes.len() {
+                return None;
+            }
+            let (path_prefix, text_bytes) = value_bytes.split_at(text_start_pos);
+            Some((path_prefix, std::str::from_utf8(text_bytes).ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Builds a tantivy fuzzy term query.
+///
+/// Tantivy has no notion of a prefix that must match exactly, so when `prefix_length` is
+/// non-zero, the fuzzy query is intersected with an automaton query matching that prefix.
+pub(crate) fn make_fuzzy_term_query(
+    term: Term,
+    fuzziness: Fuzziness,
+    transpositions: bool,
+    prefix_length: u32,
+) -> TantivyQueryAst {
+    let Some((path_prefix, text)) = split_json_path_and_text(&term) else {
+        return TantivyTermQuery::new(term, IndexRecordOption::WithFreqs).into();
+    };
+    let distance = fuzziness.distance_for_text(text);
+    if prefix_length == 0 || distance == 0 {
+        return TantivyFuzzyTermQuery::new(term, distance, transpositions).into();
+    }
+    let text_prefix_len: usize = text
+        .chars()
+        .take(prefix_length as usize)
+        .map(char::len_utf8)
+        .sum();
+    let mut prefix = path_prefix.to_vec();
+    prefix.extend_from_slice(&text.as_bytes()[..text_prefix_len]);
+    let prefix_query = AutomatonQuery {
+        field: term.field(),
+        automaton: Arc::new(JsonPathPrefix {
+            prefix,
+            automaton: Arc::new(AlwaysMatch),
+        }),
+    };
+    let fuzzy_query = TantivyFuzzyTermQuery::new(term, distance, transpositions);
+    TantivyBoolQuery::build_clause(
+        BooleanOperand::And,
+        vec![fuzzy_query.into(), prefix_query.into()],
+    )
+    .into()
+}
+
+/// A Fuzzy query matches the terms within a given edit distance of `value`.
+///
+/// Like the `TermQuery`, the value is not tokenized.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct FuzzyQuery {
+    pub field: String,
+    pub value: String,
+    /// Maximum levenshtein distance, at most 2.
+    pub distance: u8,
+    /// Whether a transposition of two adjacent characters counts as a single edit.
+    pub transpositions: bool,
+    /// Number of leading characters that must match exactly.
+    #[serde(default)]
+    pub prefix_length: u32,
+    /// Maximum number of terms the query expands to. Tantivy does not cap the expansion of
+    /// fuzzy term queries, so only [`FUZZY_MAX_EXPANSIONS`] is accepted.
+    pub max_expansions: u32,
+    /// Support missing fields
+    pub lenient: bool,
+}
+
+impl From<FuzzyQuery> for QueryAst {
+    fn from(fuzzy_query: FuzzyQuery) -> Self {
+        Self::Fuzzy(fuzzy_query)
+    }
+}
+
+impl BuildTantivyAst for FuzzyQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        if self.distance > MAX_FUZZY_DISTANCE {
+            return Err(InvalidQuery::Other(anyhow::anyhow!(
+                "fuzzy query distance must be at most {MAX_FUZZY_DISTANCE}, got `{}`",
+                self.distance
+            )));
+        }
+        let full_text_params = FullTextParams {
+            tokenizer: Some("raw".to_string()),
+            mode: FullTextMode::Fuzzy {
+                operator: BooleanOperand::Or,
+                fuzziness: Fuzziness::Distance(self.distance),
+                transpositions: self.transpositions,
+                prefix_length: self.prefix_length,
+                max_expansions: self.max_expansions,
+            },
+            zero_terms_query: Default::default(),
+        };
+        crate::query_ast::utils::full_text_query(
+            &self.field,
+            &self.value,
+            &full_text_params,
+            schema,
+            tokenizer_manager,
+            self.lenient,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{Schema, TEXT};
+
+    use super::*;
+    use crate::create_default_quickwit_tokenizer_manager;
+
+    #[test]
+    fn test_fuzziness_parse_and_serialize() {
+        assert_eq!(
+            "AUTO".parse::<Fuzziness>().unwrap(),
+            Fuzziness::Auto { low: 3, high: 6 }
+        );
+        assert_eq!(
+            "AUTO:2,5".parse::<Fuzziness>().unwrap(),
+            Fuzziness::Auto { low: 2, high: 5 }
+        );
+        assert_eq!("1".parse::<Fuzziness>().unwrap(), Fuzziness::Distance(1));
+        assert!("3".parse::<Fuzziness>().is_err());
+        assert!("AUTO:5,2".parse::<Fuzziness>().is_err());
+        assert!("AUTO:5".parse::<Fuzziness>().is_err());
+
+        let fuzziness: Fuzziness = serde_json::from_str("2").unwrap();
+        assert_eq!(fuzziness, Fuzziness::Distance(2));
+        assert_eq!(serde_json::to_string(&fuzziness).unwrap(), "2");
+
+        let fuzziness: Fuzziness = serde_json::from_str(r#""AUTO""#).unwrap();
+        assert_eq!(serde_json::to_string(&fuzziness).unwrap(), r#""AUTO:3,6""#);
+    }
+
+    #[test]
+    fn test_fuzziness_auto_distance() {
+        let fuzziness = Fuzziness::default();
+        assert_eq!(fuzziness.distance_for_text("ab"), 0);
+        assert_eq!(fuzziness.distance_for_text("abc"), 1);
+        assert_eq!(fuzziness.distance_for_text("abcde"), 1);
+        assert_eq!(fuzziness.distance_for_text("abcdef"), 2);
+        assert_eq!(Fuzziness::Distance(1).distance_for_text("abcdef"), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_query_text_field() {
+        let fuzzy_query = FuzzyQuery {
+            field: "body".to_string(),
+            value: "quikc".to_string(),
+            distance: 2,
+            transpositions: true,
+            prefix_length: 0,
+            max_expansions: 50,
+            lenient: false,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let tantivy_query_ast = fuzzy_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+        let leaf = tantivy_query_ast.as_leaf().unwrap();
+        assert!(format!("{leaf:?}").starts_with("FuzzyTermQuery"));
+    }
+
+    #[test]
+    fn test_fuzzy_query_with_prefix_length() {
+        let fuzzy_query = FuzzyQuery {
+            field: "body".to_string(),
+            value: "quikc".to_string(),
+            distance: 1,
+            transpositions: true,
+            prefix_length: 2,
+            max_expansions: 50,
+            lenient: false,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let tantivy_query_ast = fuzzy_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+        let bool_query = tantivy_query_ast.as_bool_query().unwrap();
+        assert_eq!(bool_query.must.len(), 2);
+    }
+
+    #[test]
+    fn test_fuzzy_query_invalid_distance() {
+        let fuzzy_query = FuzzyQuery {
+            field: "body".to_string(),
+            value: "quikc".to_string(),
+            distance: 3,
+            transpositions: true,
+            prefix_length: 0,
+            max_expansions: 50,
+            lenient: false,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        fuzzy_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_fuzzy_query_invalid_max_expansions() {
+        let fuzzy_query = FuzzyQuery {
+            field: "body".to_string(),
+            value: "quikc".to_string(),
+            distance: 1,
+            transpositions: true,
+            prefix_length: 0,
+            max_expansions: 10,
+            lenient: false,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let error = fuzzy_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("max_expansions"));
+    }
+
+    #[test]
+    fn test_split_json_path_and_text() {
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", TEXT);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let _schema = schema_builder.build();
+
+        let text_term = Term::from_field_text(text_field, "hello");
+        let (path, text) = split_json_path_and_text(&text_term).unwrap();
+        assert!(path.is_empty());
+        assert_eq!(text, "hello");
+
+        let mut json_term = Term::from_field_json_path(json_field, "sub.field", false);
+        json_term.append_type_and_str("hello");
+        let (path, text) = split_json_path_and_text(&json_term).unwrap();
+        assert_eq!(path, b"sub\x01field\0s");
+        assert_eq!(text, "hello");
+    }
+}
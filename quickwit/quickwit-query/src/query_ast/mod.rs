@@ -21,9 +21,13 @@ use crate::tokenizers::TokenizerManager;
 mod bool_query;
 mod field_presence;
 mod full_text_query;
+mod function_score_query;
+mod fuzzy_query;
+mod nested_query;
 mod phrase_prefix_query;
 mod range_query;
 mod regex_query;
+mod runtime_field_query;
 mod tantivy_query_ast;
 mod term_query;
 mod term_set_query;
@@ -35,9 +39,16 @@ mod wildcard_query;
 pub use bool_query::BoolQuery;
 pub use field_presence::FieldPresenceQuery;
 pub use full_text_query::{FullTextMode, FullTextParams, FullTextQuery};
+pub use function_score_query::{
+    DecayCurve, DecayFunction, FieldValueFactorFunction, FieldValueFactorModifier,
+    FunctionBoostMode, FunctionScoreMode, FunctionScoreQuery, ScoreFunction, ScoreFunctionKind,
+};
+pub use fuzzy_query::{Fuzziness, FuzzyQuery, FUZZY_MAX_EXPANSIONS, MAX_FUZZY_DISTANCE};
+pub use nested_query::NestedQuery;
 pub use phrase_prefix_query::PhrasePrefixQuery;
 pub use range_query::RangeQuery;
 pub use regex_query::{AutomatonQuery, JsonPathPrefix, RegexQuery};
+pub use runtime_field_query::RuntimeFieldQuery;
 use tantivy_query_ast::TantivyQueryAst;
 pub use term_query::TermQuery;
 pub use term_set_query::TermSetQuery;
@@ -45,6 +56,7 @@ pub use user_input_query::UserInputQuery;
 pub use visitor::{QueryAstTransformer, QueryAstVisitor};
 pub use wildcard_query::WildcardQuery;
 
+pub use crate::block_join::NestedScoreMode;
 use crate::{BooleanOperand, InvalidQuery, NotNaNf32};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -61,6 +73,10 @@ pub enum QueryAst {
     UserInput(UserInputQuery),
     Wildcard(WildcardQuery),
     Regex(RegexQuery),
+    Fuzzy(FuzzyQuery),
+    FunctionScore(FunctionScoreQuery),
+    Nested(NestedQuery),
+    RuntimeField(RuntimeFieldQuery),
     MatchAll,
     MatchNone,
     Boost {
@@ -104,7 +120,9 @@ impl QueryAst {
             | ast @ QueryAst::FieldPresence(_)
             | ast @ QueryAst::Range(_)
             | ast @ QueryAst::Wildcard(_)
-            | ast @ QueryAst::Regex(_) => Ok(ast),
+            | ast @ QueryAst::Regex(_)
+            | ast @ QueryAst::Fuzzy(_)
+            | ast @ QueryAst::RuntimeField(_) => Ok(ast),
             QueryAst::UserInput(user_text_query) => {
                 user_text_query.parse_user_query(default_search_fields)
             }
@@ -115,6 +133,23 @@ impl QueryAst {
                     boost,
                 })
             }
+            QueryAst::FunctionScore(mut function_score_query) => {
+                let query = function_score_query
+                    .query
+                    .parse_user_query(default_search_fields)?;
+                function_score_query.query = Box::new(query);
+                for function in &mut function_score_query.functions {
+                    if let Some(filter) = function.filter.take() {
+                        function.filter = Some(filter.parse_user_query(default_search_fields)?);
+                    }
+                }
+                Ok(function_score_query.into())
+            }
+            QueryAst::Nested(mut nested_query) => {
+                let query = nested_query.query.parse_user_query(default_search_fields)?;
+                nested_query.query = Box::new(query);
+                Ok(nested_query.into())
+            }
         }
     }
 
@@ -254,6 +289,30 @@ impl BuildTantivyAst for QueryAst {
                 search_fields,
                 with_validation,
             ),
+            QueryAst::Fuzzy(fuzzy) => fuzzy.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
+            QueryAst::FunctionScore(function_score) => function_score.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
+            QueryAst::Nested(nested) => nested.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
+            QueryAst::RuntimeField(runtime_field) => runtime_field.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
         }
     }
 }
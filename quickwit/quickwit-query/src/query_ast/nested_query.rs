@@ -0,0 +1,257 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_common::shared_consts::NESTED_PATH_FIELD_NAME;
+use serde::{Deserialize, Serialize};
+use tantivy::schema::Schema as TantivySchema;
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::block_join::{NestedBlockJoinQuery, NestedScoreMode};
+use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
+use crate::tokenizers::TokenizerManager;
+use crate::{InvalidQuery, MatchAllOrNone};
+
+/// Matches the documents with at least one object of the `nested` field `path` matching `query`.
+///
+/// The fields of `query` are referred to by their full path, for instance `items.name` for the
+/// `name` field of the `items` nested field.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NestedQuery {
+    pub path: String,
+    pub query: Box<QueryAst>,
+    #[serde(default)]
+    pub score_mode: NestedScoreMode,
+}
+
+impl From<NestedQuery> for QueryAst {
+    fn from(nested_query: NestedQuery) -> Self {
+        QueryAst::Nested(nested_query)
+    }
+}
+
+impl BuildTantivyAst for NestedQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+        search_fields: &[String],
+        with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        if schema.get_field(NESTED_PATH_FIELD_NAME).is_err() {
+            // The split does not contain any nested document.
+            return Ok(TantivyQueryAst::match_none());
+        }
+        let child_query = self.query.build_tantivy_ast_call(
+            schema,
+            tokenizer_manager,
+            search_fields,
+            with_validation,
+        )?;
+        if child_query.const_predicate() == Some(MatchAllOrNone::MatchNone) {
+            return Ok(TantivyQueryAst::match_none());
+        }
+        let nested_block_join_query = NestedBlockJoinQuery {
+            child_query: child_query.simplify().into(),
+            path: self.path.clone(),
+            score_mode: self.score_mode,
+        };
+        Ok(nested_block_join_query.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::collector::{Count, DocSetCollector};
+    use tantivy::query::{
+        ConstScoreQuery, EnableScoring, Query, Scorer, TermQuery as TantivyTermQuery, Weight,
+    };
+    use tantivy::schema::{IndexRecordOption, Schema as TantivySchema, FAST, INDEXED, STRING};
+    use tantivy::{doc, DocAddress, DocSet, Index, Term, TERMINATED};
+
+    use super::*;
+    use crate::block_join::{RootDocsQuery, WithNestedDocsQuery};
+    use crate::create_default_quickwit_tokenizer_manager;
+    use crate::query_ast::{BoolQuery, TermQuery};
+
+    fn build_index() -> Index {
+        let mut schema_builder = TantivySchema::builder();
+        let nested_path = schema_builder.add_text_field(NESTED_PATH_FIELD_NAME, FAST);
+        let title = schema_builder.add_text_field("title", STRING);
+        let name = schema_builder.add_text_field("items.name", STRING);
+        let qty = schema_builder.add_u64_field("items.qty", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        // Nested documents are indexed right before their root document.
+        index_writer
+            .add_document(doc!(nested_path=>"items", name=>"foo", qty=>3u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(nested_path=>"items", name=>"bar", qty=>10u64))
+            .unwrap();
+        index_writer.add_document(doc!(title=>"a")).unwrap();
+        index_writer
+            .add_document(doc!(nested_path=>"items", name=>"foo", qty=>10u64))
+            .unwrap();
+        index_writer.add_document(doc!(title=>"b")).unwrap();
+        index_writer.add_document(doc!(title=>"c")).unwrap();
+        index_writer.commit().unwrap();
+        index
+    }
+
+    fn search_doc_ids(index: &Index, query_ast: &QueryAst) -> Vec<u32> {
+        let schema = index.schema();
+        let query = query_ast
+            .build_tantivy_query(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+        let root_docs_query = RootDocsQuery { query };
+        let searcher = index.reader().unwrap().searcher();
+        let mut doc_ids: Vec<u32> = searcher
+            .search(&root_docs_query, &DocSetCollector)
+            .unwrap()
+            .into_iter()
+            .map(|doc_address: DocAddress| doc_address.doc_id)
+            .collect();
+        doc_ids.sort();
+        doc_ids
+    }
+
+    fn items_query(name: &str, qty: &str) -> QueryAst {
+        BoolQuery {
+            must: vec![
+                TermQuery {
+                    field: "items.name".to_string(),
+                    value: name.to_string(),
+                }
+                .into(),
+                TermQuery {
+                    field: "items.qty".to_string(),
+                    value: qty.to_string(),
+                }
+                .into(),
+            ],
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_nested_query() {
+        let index = build_index();
+        let nested_query: QueryAst = NestedQuery {
+            path: "items".to_string(),
+            query: Box::new(items_query("foo", "10")),
+            score_mode: NestedScoreMode::Avg,
+        }
+        .into();
+        assert_eq!(search_doc_ids(&index, &nested_query), vec![4]);
+
+        let nested_query: QueryAst = NestedQuery {
+            path: "items".to_string(),
+            query: Box::new(QueryAst::MatchAll),
+            score_mode: NestedScoreMode::Avg,
+        }
+        .into();
+        assert_eq!(search_doc_ids(&index, &nested_query), vec![2, 4]);
+
+        let nested_query: QueryAst = NestedQuery {
+            path: "unknown".to_string(),
+            query: Box::new(QueryAst::MatchAll),
+            score_mode: NestedScoreMode::Avg,
+        }
+        .into();
+        assert!(search_doc_ids(&index, &nested_query).is_empty());
+    }
+
+    #[test]
+    fn test_nested_docs_are_hidden() {
+        let index = build_index();
+        // Without a nested query, the conditions apply to the root documents, that do not have
+        // any `items` field.
+        assert!(search_doc_ids(&index, &items_query("foo", "10")).is_empty());
+        assert_eq!(search_doc_ids(&index, &QueryAst::MatchAll), vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_nested_query_score_mode() {
+        let index = build_index();
+        let searcher = index.reader().unwrap().searcher();
+        let schema = index.schema();
+        let qty_field = schema.get_field("items.qty").unwrap();
+        let child_query = ConstScoreQuery::new(
+            Box::new(TantivyTermQuery::new(
+                Term::from_field_u64(qty_field, 10),
+                IndexRecordOption::Basic,
+            )),
+            2.0,
+        );
+        for (score_mode, expected_score) in [
+            (NestedScoreMode::Avg, 2.0),
+            (NestedScoreMode::Sum, 2.0),
+            (NestedScoreMode::None, 0.0),
+        ] {
+            let nested_query = NestedBlockJoinQuery {
+                child_query: Box::new(child_query.clone()),
+                path: "items".to_string(),
+                score_mode,
+            };
+            let weight = nested_query
+                .weight(EnableScoring::enabled_from_searcher(&searcher))
+                .unwrap();
+            let mut scorer = weight.scorer(searcher.segment_reader(0), 1.0).unwrap();
+            assert_eq!(scorer.doc(), 2);
+            assert_eq!(scorer.score(), expected_score);
+            assert_eq!(scorer.advance(), 4);
+            assert_eq!(scorer.seek(5), TERMINATED);
+        }
+    }
+
+    #[test]
+    fn test_with_nested_docs_query() {
+        let index = build_index();
+        let searcher = index.reader().unwrap().searcher();
+        let title_field = index.schema().get_field("title").unwrap();
+        for (title, expected_count) in [("a", 3), ("b", 2), ("c", 1)] {
+            let query = WithNestedDocsQuery {
+                query: Box::new(TantivyTermQuery::new(
+                    Term::from_field_text(title_field, title),
+                    IndexRecordOption::Basic,
+                )),
+            };
+            assert_eq!(searcher.search(&query, &Count).unwrap(), expected_count);
+        }
+    }
+
+    #[test]
+    fn test_nested_query_serialization() {
+        let nested_query: QueryAst = NestedQuery {
+            path: "items".to_string(),
+            query: Box::new(QueryAst::MatchAll),
+            score_mode: NestedScoreMode::Max,
+        }
+        .into();
+        let nested_query_json = serde_json::to_string(&nested_query).unwrap();
+        assert_eq!(
+            nested_query_json,
+            r#"{"type":"nested","path":"items","query":{"type":"match_all"},"score_mode":"max"}"#
+        );
+        let deserialized_query: QueryAst = serde_json::from_str(&nested_query_json).unwrap();
+        assert_eq!(deserialized_query, nested_query);
+    }
+}
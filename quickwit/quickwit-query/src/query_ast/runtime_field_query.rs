@@ -0,0 +1,76 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+use tantivy::schema::Schema as TantivySchema;
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::json_literal::InterpretUserInput;
+use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
+use crate::runtime_field::{RuntimeField, RuntimeFieldRangeQuery};
+use crate::tokenizers::TokenizerManager;
+use crate::{InvalidQuery, JsonLiteral};
+
+/// Matches the documents for which the value of the runtime field `field` is within the bounds.
+///
+/// This query is not meant to be written by users: range, term and exists queries targeting a
+/// runtime field are rewritten into it, see
+/// [`resolve_runtime_fields`](crate::runtime_field::resolve_runtime_fields).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeFieldQuery {
+    pub field: String,
+    pub runtime_field: RuntimeField,
+    pub lower_bound: Bound<JsonLiteral>,
+    pub upper_bound: Bound<JsonLiteral>,
+}
+
+impl From<RuntimeFieldQuery> for QueryAst {
+    fn from(runtime_field_query: RuntimeFieldQuery) -> Self {
+        QueryAst::RuntimeField(runtime_field_query)
+    }
+}
+
+impl RuntimeFieldQuery {
+    fn convert_bound(&self, bound: &Bound<JsonLiteral>) -> Result<Bound<f64>, InvalidQuery> {
+        let convert = |value: &JsonLiteral| {
+            f64::interpret_json(value).ok_or_else(|| InvalidQuery::InvalidBoundary {
+                expected_value_type: "number",
+                field_name: self.field.clone(),
+            })
+        };
+        match bound {
+            Bound::Included(value) => Ok(Bound::Included(convert(value)?)),
+            Bound::Excluded(value) => Ok(Bound::Excluded(convert(value)?)),
+            Bound::Unbounded => Ok(Bound::Unbounded),
+        }
+    }
+}
+
+impl BuildTantivyAst for RuntimeFieldQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        _schema: &TantivySchema,
+        _tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        let lower_bound = self.convert_bound(&self.lower_bound)?;
+        let upper_bound = self.convert_bound(&self.upper_bound)?;
+        let runtime_field_query =
+            RuntimeFieldRangeQuery::new(self.runtime_field.clone(), lower_bound, upper_bound);
+        Ok(runtime_field_query.into())
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::borrow::Cow;
 use std::collections::{BTreeSet, HashMap};
 use std::ops::Bound;
 
@@ -25,13 +26,17 @@ use tantivy::schema::Schema as TantivySchema;
 use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
 use crate::query_ast::{
-    self, BuildTantivyAst, FieldPresenceQuery, FullTextMode, FullTextParams, QueryAst,
+    self, BuildTantivyAst, FieldPresenceQuery, FullTextMode, FullTextParams, Fuzziness, QueryAst,
+    MAX_FUZZY_DISTANCE,
 };
 use crate::tokenizers::TokenizerManager;
 use crate::{BooleanOperand, InvalidQuery, JsonLiteral};
 
 const DEFAULT_PHRASE_QUERY_MAX_EXPANSION: u32 = 50;
 
+/// Edit distance of the fuzzy terms written without a distance, like `term~`.
+const DEFAULT_FUZZY_DISTANCE: u8 = 2;
+
 /// A query expressed in the tantivy query grammar DSL.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UserInputQuery {
@@ -64,7 +69,8 @@ impl UserInputQuery {
             .as_ref()
             .map(|search_fields| &search_fields[..])
             .unwrap_or(default_search_fields);
-        let user_input_ast = tantivy::query_grammar::parse_query(&self.user_text)
+        let user_text = add_default_fuzzy_distance(&self.user_text);
+        let user_input_ast = tantivy::query_grammar::parse_query(&user_text)
             .map_err(|_| anyhow::anyhow!("failed to parse query: `{}`", &self.user_text))?;
         let default_occur = match self.default_operator {
             BooleanOperand::And => Occur::Must,
@@ -222,6 +228,58 @@ fn is_wildcard(phrase: &str) -> bool {
         .is_break()
 }
 
+/// Appends the default distance to the unquoted terms followed by a bare `~`, like `term~`,
+/// which the grammar does not accept.
+fn add_default_fuzzy_distance(user_text: &str) -> Cow<'_, str> {
+    if !user_text.contains('~') {
+        return Cow::Borrowed(user_text);
+    }
+    let mut output = String::with_capacity(user_text.len() + 1);
+    let mut delimiter_opt: Option<char> = None;
+    let mut escaped = false;
+    let mut prev_char_opt: Option<char> = None;
+    let mut chars = user_text.chars().peekable();
+
+    while let Some(current_char) = chars.next() {
+        output.push(current_char);
+
+        if escaped {
+            escaped = false;
+        } else {
+            match (current_char, delimiter_opt) {
+                ('\\', _) => escaped = true,
+                ('"' | '\'', None) => delimiter_opt = Some(current_char),
+                (_, Some(delimiter)) if current_char == delimiter => delimiter_opt = None,
+                ('~', None) => {
+                    let follows_term = prev_char_opt.is_some_and(|prev_char| {
+                        !prev_char.is_whitespace() && !"\"')]}".contains(prev_char)
+                    });
+                    let has_distance = chars.peek().is_some_and(char::is_ascii_digit);
+
+                    if follows_term && !has_distance {
+                        output.push_str(&DEFAULT_FUZZY_DISTANCE.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        prev_char_opt = Some(current_char);
+    }
+    Cow::Owned(output)
+}
+
+/// Unquoted terms followed by `~<distance>`, like `term~1`, are fuzzy terms: the grammar
+/// parses the distance as a slop.
+fn parse_fuzzy_distance(slop: u32) -> anyhow::Result<Option<u8>> {
+    if slop == 0 {
+        return Ok(None);
+    }
+    if slop > MAX_FUZZY_DISTANCE as u32 {
+        bail!("fuzzy distance must be at most {MAX_FUZZY_DISTANCE}, got `{slop}`");
+    }
+    Ok(Some(slop as u8))
+}
+
 /// Convert a leaf of a text query AST to a QueryAst.
 /// This may generate more than a single leaf if there are multiple default fields.
 fn convert_user_input_literal(
@@ -247,19 +305,32 @@ fn convert_user_input_literal(
     if field_names.is_empty() {
         anyhow::bail!("query requires a default search field and none was supplied");
     }
-    let mode = match delimiter {
-        Delimiter::None => FullTextMode::PhraseFallbackToIntersection,
-        Delimiter::SingleQuotes => FullTextMode::Bool {
+    let fuzzy_distance_opt = if delimiter == Delimiter::None && !prefix {
+        parse_fuzzy_distance(slop)?
+    } else {
+        None
+    };
+    let mode = match (delimiter, fuzzy_distance_opt) {
+        (_, Some(distance)) => FullTextMode::Fuzzy {
+            operator: BooleanOperand::And,
+            fuzziness: Fuzziness::Distance(distance),
+            transpositions: true,
+            prefix_length: 0,
+            max_expansions: DEFAULT_PHRASE_QUERY_MAX_EXPANSION,
+        },
+        (Delimiter::None, None) => FullTextMode::PhraseFallbackToIntersection,
+        (Delimiter::SingleQuotes, None) => FullTextMode::Bool {
             operator: BooleanOperand::And,
         },
-        Delimiter::DoubleQuotes => FullTextMode::Phrase { slop },
+        (Delimiter::DoubleQuotes, None) => FullTextMode::Phrase { slop },
     };
     let full_text_params = FullTextParams {
         tokenizer: None,
         mode,
         zero_terms_query: crate::MatchAllOrNone::MatchNone,
     };
-    let wildcard = delimiter == Delimiter::None && is_wildcard(&phrase);
+    let wildcard =
+        delimiter == Delimiter::None && fuzzy_distance_opt.is_none() && is_wildcard(&phrase);
     let mut phrase_queries: Vec<QueryAst> = field_names
         .into_iter()
         .map(|field_name| {
@@ -306,7 +377,8 @@ fn convert_user_input_literal(
 #[cfg(test)]
 mod tests {
     use crate::query_ast::{
-        BoolQuery, BuildTantivyAst, FullTextMode, FullTextQuery, QueryAst, UserInputQuery,
+        BoolQuery, BuildTantivyAst, FullTextMode, FullTextQuery, Fuzziness, QueryAst,
+        UserInputQuery,
     };
     use crate::{create_default_quickwit_tokenizer_manager, BooleanOperand, InvalidQuery};
 
@@ -476,6 +548,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_user_input_query_fuzzy() {
+        let parse_fuzzy_query_util = |query: &str| {
+            let ast = UserInputQuery {
+                user_text: query.to_string(),
+                default_fields: None,
+                default_operator: BooleanOperand::Or,
+                lenient: false,
+            }
+            .parse_user_query(&[])
+            .unwrap();
+            let QueryAst::FullText(full_text_query) = ast else {
+                panic!()
+            };
+            full_text_query
+        };
+        {
+            let fuzzy_query = parse_fuzzy_query_util("title:helo~1");
+            assert_eq!(&fuzzy_query.field, "title");
+            assert_eq!(&fuzzy_query.text, "helo");
+            let FullTextMode::Fuzzy { fuzziness, .. } = fuzzy_query.params.mode else {
+                panic!()
+            };
+            assert_eq!(fuzziness, Fuzziness::Distance(1));
+        }
+        {
+            let fuzzy_query = parse_fuzzy_query_util("title:helo~");
+            assert_eq!(&fuzzy_query.text, "helo");
+            let FullTextMode::Fuzzy { fuzziness, .. } = fuzzy_query.params.mode else {
+                panic!()
+            };
+            assert_eq!(fuzziness, Fuzziness::Distance(2));
+        }
+        {
+            let phrase_query = parse_fuzzy_query_util("title:\"hello world\"~2");
+            assert_eq!(phrase_query.params.mode, FullTextMode::Phrase { slop: 2 });
+        }
+        UserInputQuery {
+            user_text: "title:helo~3".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::Or,
+            lenient: false,
+        }
+        .parse_user_query(&[])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn test_add_default_fuzzy_distance() {
+        assert_eq!(add_default_fuzzy_distance("title:helo"), "title:helo");
+        assert_eq!(add_default_fuzzy_distance("title:helo~"), "title:helo~2");
+        assert_eq!(add_default_fuzzy_distance("title:helo~1"), "title:helo~1");
+        assert_eq!(
+            add_default_fuzzy_distance("helo~ AND world~"),
+            "helo~2 AND world~2"
+        );
+        assert_eq!(add_default_fuzzy_distance(r"helo\~"), r"helo\~");
+        assert_eq!(
+            add_default_fuzzy_distance(r#""hello world"~"#),
+            r#""hello world"~"#
+        );
+        assert_eq!(
+            add_default_fuzzy_distance(r#""helo~ world""#),
+            r#""helo~ world""#
+        );
+    }
+
     #[test]
     fn test_user_input_query_different_delimiter() {
         let parse_user_query_delimiter_util = |query: &str| {
@@ -16,8 +16,8 @@ use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::field_presence::FieldPresenceQuery;
 use crate::query_ast::user_input_query::UserInputQuery;
 use crate::query_ast::{
-    BoolQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, RangeQuery, RegexQuery, TermQuery,
-    TermSetQuery, WildcardQuery,
+    BoolQuery, FullTextQuery, FunctionScoreQuery, FuzzyQuery, NestedQuery, PhrasePrefixQuery,
+    QueryAst, RangeQuery, RegexQuery, RuntimeFieldQuery, TermQuery, TermSetQuery, WildcardQuery,
 };
 
 /// Simple trait to implement a Visitor over the QueryAst.
@@ -41,6 +41,10 @@ pub trait QueryAstVisitor<'a> {
             QueryAst::FieldPresence(exists) => self.visit_exists(exists),
             QueryAst::Wildcard(wildcard) => self.visit_wildcard(wildcard),
             QueryAst::Regex(regex) => self.visit_regex(regex),
+            QueryAst::Fuzzy(fuzzy) => self.visit_fuzzy(fuzzy),
+            QueryAst::FunctionScore(function_score) => self.visit_function_score(function_score),
+            QueryAst::Nested(nested) => self.visit_nested(nested),
+            QueryAst::RuntimeField(runtime_field) => self.visit_runtime_field(runtime_field),
         }
     }
 
@@ -111,6 +115,36 @@ pub trait QueryAstVisitor<'a> {
     fn visit_regex(&mut self, _regex_query: &'a RegexQuery) -> Result<(), Self::Err> {
         Ok(())
     }
+
+    fn visit_fuzzy(&mut self, _fuzzy_query: &'a FuzzyQuery) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    fn visit_function_score(
+        &mut self,
+        function_score_query: &'a FunctionScoreQuery,
+    ) -> Result<(), Self::Err> {
+        self.visit(&function_score_query.query)?;
+        for filter in function_score_query
+            .functions
+            .iter()
+            .filter_map(|function| function.filter.as_ref())
+        {
+            self.visit(filter)?;
+        }
+        Ok(())
+    }
+
+    fn visit_nested(&mut self, nested_query: &'a NestedQuery) -> Result<(), Self::Err> {
+        self.visit(&nested_query.query)
+    }
+
+    fn visit_runtime_field(
+        &mut self,
+        _runtime_field_query: &'a RuntimeFieldQuery,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
 }
 
 /// Simple trait to implement a Visitor over the QueryAst.
@@ -134,6 +168,12 @@ pub trait QueryAstTransformer {
             QueryAst::FieldPresence(exists) => self.transform_exists(exists),
             QueryAst::Wildcard(wildcard) => self.transform_wildcard(wildcard),
             QueryAst::Regex(regex) => self.transform_regex(regex),
+            QueryAst::Fuzzy(fuzzy) => self.transform_fuzzy(fuzzy),
+            QueryAst::FunctionScore(function_score) => {
+                self.transform_function_score(function_score)
+            }
+            QueryAst::Nested(nested) => self.transform_nested(nested),
+            QueryAst::RuntimeField(runtime_field) => self.transform_runtime_field(runtime_field),
         }
     }
 
@@ -236,4 +276,43 @@ pub trait QueryAstTransformer {
     fn transform_regex(&mut self, regex_query: RegexQuery) -> Result<Option<QueryAst>, Self::Err> {
         Ok(Some(QueryAst::Regex(regex_query)))
     }
+
+    fn transform_fuzzy(&mut self, fuzzy_query: FuzzyQuery) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(QueryAst::Fuzzy(fuzzy_query)))
+    }
+
+    fn transform_function_score(
+        &mut self,
+        mut function_score_query: FunctionScoreQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        let Some(query) = self.transform(*function_score_query.query)? else {
+            return Ok(None);
+        };
+        function_score_query.query = Box::new(query);
+        for function in &mut function_score_query.functions {
+            if let Some(filter) = function.filter.take() {
+                // Like in boolean queries, a removed filter is considered as matching.
+                function.filter = self.transform(filter)?;
+            }
+        }
+        Ok(Some(QueryAst::FunctionScore(function_score_query)))
+    }
+
+    fn transform_nested(
+        &mut self,
+        mut nested_query: NestedQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        let Some(query) = self.transform(*nested_query.query)? else {
+            return Ok(None);
+        };
+        nested_query.query = Box::new(query);
+        Ok(Some(QueryAst::Nested(nested_query)))
+    }
+
+    fn transform_runtime_field(
+        &mut self,
+        runtime_field_query: RuntimeFieldQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(QueryAst::RuntimeField(runtime_field_query)))
+    }
 }
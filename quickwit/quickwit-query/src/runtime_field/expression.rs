@@ -0,0 +1,495 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+use anyhow::{bail, Context};
+
+/// Arithmetic expression over the numeric fast fields of a document.
+///
+/// Supported syntax:
+/// - numeric literals: `3`, `2.5`, `1e3`;
+/// - field references: `response_time`, `attributes.size`, or `doc['field']` and
+///   `doc['field'].value` for field names that are not plain identifiers;
+/// - operators `+`, `-`, `*`, `/`, `%` and parentheses;
+/// - functions `abs`, `ceil`, `floor`, `round`, `sqrt`, `min` and `max`;
+/// - an optional `emit(...)` wrapper and trailing `;`, so that simple Painless scripts are accepted
+///   as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    root: Expr,
+    fields: Vec<String>,
+    num_nodes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Field(usize),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Function(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Abs,
+    Ceil,
+    Floor,
+    Round,
+    Sqrt,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        let function = match name {
+            "abs" => Function::Abs,
+            "ceil" => Function::Ceil,
+            "floor" => Function::Floor,
+            "round" => Function::Round,
+            "sqrt" => Function::Sqrt,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    fn is_unary(&self) -> bool {
+        !matches!(self, Function::Min | Function::Max)
+    }
+}
+
+impl Expression {
+    /// Names of the fields referenced by the expression.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Number of nodes of the expression, used as the cost of evaluating it for one document.
+    pub fn num_nodes(&self) -> u64 {
+        self.num_nodes
+    }
+
+    /// Evaluates the expression. `field_value` returns the value of the i-th field of
+    /// [`Self::fields`]. Returns `None` if one of the fields has no value or if the result is not
+    /// a finite number.
+    pub fn eval(&self, field_value: &impl Fn(usize) -> Option<f64>) -> Option<f64> {
+        let value = eval_expr(&self.root, field_value)?;
+        value.is_finite().then_some(value)
+    }
+}
+
+fn eval_expr(expr: &Expr, field_value: &impl Fn(usize) -> Option<f64>) -> Option<f64> {
+    let value = match expr {
+        Expr::Number(value) => *value,
+        Expr::Field(field_ord) => field_value(*field_ord)?,
+        Expr::Neg(expr) => -eval_expr(expr, field_value)?,
+        Expr::Binary(op, left, right) => {
+            let left = eval_expr(left, field_value)?;
+            let right = eval_expr(right, field_value)?;
+            match op {
+                BinaryOp::Add => left + right,
+                BinaryOp::Sub => left - right,
+                BinaryOp::Mul => left * right,
+                BinaryOp::Div => left / right,
+                BinaryOp::Rem => left % right,
+            }
+        }
+        Expr::Function(function, args) => {
+            let mut values = Vec::with_capacity(args.len());
+            for arg in args {
+                values.push(eval_expr(arg, field_value)?);
+            }
+            match function {
+                Function::Abs => values[0].abs(),
+                Function::Ceil => values[0].ceil(),
+                Function::Floor => values[0].floor(),
+                Function::Round => values[0].round(),
+                Function::Sqrt => values[0].sqrt(),
+                Function::Min => values.into_iter().fold(f64::INFINITY, f64::min),
+                Function::Max => values.into_iter().fold(f64::NEG_INFINITY, f64::max),
+            }
+        }
+    };
+    Some(value)
+}
+
+impl FromStr for Expression {
+    type Err = anyhow::Error;
+
+    fn from_str(script: &str) -> anyhow::Result<Expression> {
+        let tokens = tokenize(script)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            fields: Vec::new(),
+            num_nodes: 0,
+        };
+        let root = parser.parse_script()?;
+        Ok(Expression {
+            root,
+            fields: parser.fields,
+            num_nodes: parser.num_nodes,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+    Semicolon,
+    Op(char),
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '@'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '@' || c == '.'
+}
+
+fn take_while(
+    script: &str,
+    chars: &mut Peekable<CharIndices>,
+    start: usize,
+    predicate: impl Fn(char) -> bool,
+) -> String {
+    let mut end = start;
+    while let Some(&(pos, c)) = chars.peek() {
+        if !predicate(c) {
+            break;
+        }
+        end = pos + c.len_utf8();
+        chars.next();
+    }
+    script[start..end].to_string()
+}
+
+fn tokenize(script: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = script.char_indices().peekable();
+    while let Some(&(pos, c)) = chars.peek() {
+        let starts_number = c.is_ascii_digit()
+            || (c == '.'
+                && matches!(script[pos + 1..].chars().next(), Some(c) if c.is_ascii_digit()));
+        if c.is_whitespace() {
+            chars.next();
+        } else if starts_number {
+            let mut prev = ' ';
+            let number_str = take_while(script, &mut chars, pos, |c| {
+                let is_number_char = c.is_ascii_digit()
+                    || c == '.'
+                    || c == 'e'
+                    || c == 'E'
+                    || ((c == '-' || c == '+') && (prev == 'e' || prev == 'E'));
+                prev = c;
+                is_number_char
+            });
+            let number = number_str
+                .parse::<f64>()
+                .with_context(|| format!("invalid number `{number_str}`"))?;
+            tokens.push(Token::Number(number));
+        } else if is_ident_start(c) {
+            let ident = take_while(script, &mut chars, pos, is_ident_char);
+            tokens.push(Token::Ident(ident));
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut value = String::new();
+            let mut closed = false;
+            for (_, next_c) in chars.by_ref() {
+                if next_c == c {
+                    closed = true;
+                    break;
+                }
+                value.push(next_c);
+            }
+            if !closed {
+                bail!("unterminated string in script");
+            }
+            tokens.push(Token::Str(value));
+        } else {
+            chars.next();
+            let token = match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                '[' => Token::LBracket,
+                ']' => Token::RBracket,
+                ',' => Token::Comma,
+                '.' => Token::Dot,
+                ';' => Token::Semicolon,
+                '+' | '-' | '*' | '/' | '%' => Token::Op(c),
+                _ => bail!("unexpected character `{c}` in script"),
+            };
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    fields: Vec<String>,
+    num_nodes: u64,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!("expected {expected:?}, got {token:?}"),
+            None => bail!("expected {expected:?}, got end of script"),
+        }
+    }
+
+    fn node(&mut self, expr: Expr) -> Expr {
+        self.num_nodes += 1;
+        expr
+    }
+
+    fn parse_script(&mut self) -> anyhow::Result<Expr> {
+        let expr = self.parse_sum()?;
+        while self.peek() == Some(&Token::Semicolon) {
+            self.pos += 1;
+        }
+        if let Some(token) = self.peek() {
+            bail!("unexpected {token:?} in script");
+        }
+        Ok(expr)
+    }
+
+    fn parse_sum(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_product()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            let right = self.parse_product()?;
+            let op = if op == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            expr = self.node(Expr::Binary(op, Box::new(expr), Box::new(right)));
+        }
+        Ok(expr)
+    }
+
+    fn parse_product(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            let op = match op {
+                '*' => BinaryOp::Mul,
+                '/' => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            expr = self.node(Expr::Binary(op, Box::new(expr), Box::new(right)));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<Expr> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                let expr = self.parse_unary()?;
+                Ok(self.node(Expr::Neg(Box::new(expr))))
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.parse_unary()
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Expr> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(self.node(Expr::Number(value))),
+            Some(Token::LParen) => {
+                let expr = self.parse_sum()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) => match self.peek() {
+                Some(Token::LParen) => {
+                    self.pos += 1;
+                    self.parse_call(&ident)
+                }
+                Some(Token::LBracket) if ident == "doc" => {
+                    self.pos += 1;
+                    let Some(Token::Str(field_name)) = self.next() else {
+                        bail!("expected a quoted field name after `doc[`");
+                    };
+                    self.expect(Token::RBracket)?;
+                    if self.peek() == Some(&Token::Dot) {
+                        self.pos += 1;
+                        if self.next() != Some(Token::Ident("value".to_string())) {
+                            bail!("expected `.value` after `doc['{field_name}']`");
+                        }
+                    }
+                    Ok(self.field(field_name))
+                }
+                _ => Ok(self.field(ident)),
+            },
+            Some(token) => bail!("unexpected {token:?} in script"),
+            None => bail!("unexpected end of script"),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str) -> anyhow::Result<Expr> {
+        let mut args = Vec::new();
+        if self.peek() != Some(&Token::RParen) {
+            loop {
+                args.push(self.parse_sum()?);
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+        self.expect(Token::RParen)?;
+        if name == "emit" {
+            if args.len() != 1 {
+                bail!("`emit` expects exactly one argument");
+            }
+            return Ok(args.pop().unwrap());
+        }
+        let function =
+            Function::from_name(name).with_context(|| format!("unknown function `{name}`"))?;
+        if function.is_unary() && args.len() != 1 {
+            bail!("`{name}` expects exactly one argument");
+        }
+        if args.is_empty() {
+            bail!("`{name}` expects at least one argument");
+        }
+        Ok(self.node(Expr::Function(function, args)))
+    }
+
+    fn field(&mut self, field_name: String) -> Expr {
+        let field_ord = if let Some(field_ord) = self.fields.iter().position(|f| f == &field_name) {
+            field_ord
+        } else {
+            self.fields.push(field_name);
+            self.fields.len() - 1
+        };
+        self.node(Expr::Field(field_ord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(script: &str, fields: &[(&str, f64)]) -> Option<f64> {
+        let expression: Expression = script.parse().unwrap();
+        expression.eval(&|field_ord| {
+            let field_name = &expression.fields()[field_ord];
+            fields
+                .iter()
+                .find(|(name, _)| name == field_name)
+                .map(|(_, value)| *value)
+        })
+    }
+
+    #[test]
+    fn test_expression_arithmetic() {
+        assert_eq!(eval("1 + 2 * 3", &[]), Some(7.0));
+        assert_eq!(eval("(1 + 2) * 3", &[]), Some(9.0));
+        assert_eq!(eval("-2 - -3", &[]), Some(1.0));
+        assert_eq!(eval("7 % 4 / 2", &[]), Some(1.5));
+        assert_eq!(eval("1.5e3 + .5", &[]), Some(1500.5));
+        assert_eq!(eval("1 / 0", &[]), None);
+    }
+
+    #[test]
+    fn test_expression_fields_and_functions() {
+        let fields = [
+            ("end", 1_500.0),
+            ("start", 1_000.0),
+            ("attributes.size", -4.0),
+        ];
+        assert_eq!(eval("end - start", &fields), Some(500.0));
+        assert_eq!(eval("abs(attributes.size)", &fields), Some(4.0));
+        assert_eq!(
+            eval("max(end, start, 2000) + min(1)", &fields),
+            Some(2_001.0)
+        );
+        assert_eq!(
+            eval("sqrt(16) + floor(1.7) + ceil(1.2) + round(2.5)", &fields),
+            Some(9.0)
+        );
+        assert_eq!(
+            eval("emit(doc['end'].value - doc[\"start\"].value);", &fields),
+            Some(500.0)
+        );
+        assert_eq!(eval("end - missing", &fields), None);
+
+        let expression: Expression = "end - start + end".parse().unwrap();
+        assert_eq!(
+            expression.fields(),
+            &["end".to_string(), "start".to_string()]
+        );
+        assert_eq!(expression.num_nodes(), 5);
+    }
+
+    #[test]
+    fn test_expression_parse_errors() {
+        for (script, expected_error) in [
+            ("1 +", "unexpected end of script"),
+            ("(1 + 2", "expected RParen, got end of script"),
+            ("foo(1)", "unknown function `foo`"),
+            ("abs(1, 2)", "`abs` expects exactly one argument"),
+            ("max()", "`max` expects at least one argument"),
+            ("1 $ 2", "unexpected character `$` in script"),
+            ("doc[field]", "expected a quoted field name after `doc[`"),
+            ("1 2", "unexpected Number(2.0) in script"),
+        ] {
+            let error = script.parse::<Expression>().unwrap_err();
+            assert_eq!(error.to_string(), expected_error, "script: {script}");
+        }
+    }
+}
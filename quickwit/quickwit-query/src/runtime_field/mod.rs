@@ -0,0 +1,444 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime fields are fields that are not indexed, but computed at query time from the fast fields
+//! of each document, by evaluating a small [`Expression`].
+
+mod expression;
+mod query;
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+pub use expression::Expression;
+pub use query::RuntimeFieldRangeQuery;
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+use tantivy::{DateTime, DocId, SegmentReader};
+
+use crate::query_ast::{
+    FieldPresenceQuery, FullTextQuery, QueryAst, QueryAstTransformer, QueryAstVisitor, RangeQuery,
+    RuntimeFieldQuery, TermQuery,
+};
+use crate::JsonLiteral;
+
+/// Maximum cost of evaluating the runtime fields of a request on a split, expressed as the number
+/// of documents of the split multiplied by the number of nodes of the expressions. The costs of
+/// all the runtime fields used by the request and of all the segments of the split add up.
+pub const MAX_RUNTIME_FIELD_COST_PER_SPLIT: u64 = 1_000_000_000;
+
+/// Runtime fields defined in a search request, by field name.
+pub type RuntimeMappings = BTreeMap<String, RuntimeField>;
+
+/// Parses the JSON runtime mappings of a search request.
+pub fn parse_runtime_mappings(runtime_mappings_json: &str) -> anyhow::Result<RuntimeMappings> {
+    serde_json::from_str(runtime_mappings_json).context("failed to parse runtime mappings")
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeFieldType {
+    #[default]
+    #[serde(alias = "double")]
+    F64,
+    #[serde(alias = "long")]
+    I64,
+}
+
+/// A field computed at query time by evaluating `script` on each document.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "RuntimeFieldForSerialization")]
+#[serde(into = "RuntimeFieldForSerialization")]
+pub struct RuntimeField {
+    field_type: RuntimeFieldType,
+    script: String,
+    expression: Arc<Expression>,
+}
+
+impl RuntimeField {
+    pub fn new(field_type: RuntimeFieldType, script: &str) -> anyhow::Result<RuntimeField> {
+        let expression: Expression = script
+            .parse()
+            .with_context(|| format!("failed to parse runtime field script `{script}`"))?;
+        Ok(RuntimeField {
+            field_type,
+            script: script.to_string(),
+            expression: Arc::new(expression),
+        })
+    }
+
+    pub fn field_type(&self) -> RuntimeFieldType {
+        self.field_type
+    }
+
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+
+    /// Names of the fast fields the runtime field is computed from.
+    pub fn fields(&self) -> &[String] {
+        self.expression.fields()
+    }
+
+    /// Cost of evaluating the runtime field on a document, namely the number of nodes of its
+    /// expression. See [`MAX_RUNTIME_FIELD_COST_PER_SPLIT`].
+    pub fn cost_per_doc(&self) -> u64 {
+        self.expression.num_nodes()
+    }
+
+    /// Opens the runtime field on a segment.
+    pub fn for_segment(&self, reader: &SegmentReader) -> tantivy::Result<SegmentRuntimeField> {
+        let columns = self
+            .fields()
+            .iter()
+            .map(|field| NumericColumn::open(reader, field))
+            .collect::<tantivy::Result<Vec<_>>>()?;
+        Ok(SegmentRuntimeField {
+            field_type: self.field_type,
+            expression: self.expression.clone(),
+            columns,
+        })
+    }
+}
+
+impl fmt::Debug for RuntimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeField")
+            .field("field_type", &self.field_type)
+            .field("script", &self.script)
+            .finish()
+    }
+}
+
+impl PartialEq for RuntimeField {
+    fn eq(&self, other: &Self) -> bool {
+        self.field_type == other.field_type && self.script == other.script
+    }
+}
+
+impl Eq for RuntimeField {}
+
+/// Elasticsearch accepts both `"script": "..."` and `"script": {"source": "..."}`.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RuntimeFieldScript {
+    Source(String),
+    Object {
+        source: String,
+        #[serde(default)]
+        lang: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuntimeFieldForSerialization {
+    #[serde(rename = "type", default)]
+    field_type: RuntimeFieldType,
+    script: RuntimeFieldScript,
+}
+
+impl TryFrom<RuntimeFieldForSerialization> for RuntimeField {
+    type Error = anyhow::Error;
+
+    fn try_from(runtime_field: RuntimeFieldForSerialization) -> anyhow::Result<RuntimeField> {
+        let source = match runtime_field.script {
+            RuntimeFieldScript::Source(source) => source,
+            RuntimeFieldScript::Object { source, lang } => {
+                if let Some(lang) = lang {
+                    if lang != "expression" && lang != "painless" {
+                        bail!("unsupported runtime field script language `{lang}`");
+                    }
+                }
+                source
+            }
+        };
+        RuntimeField::new(runtime_field.field_type, &source)
+    }
+}
+
+impl From<RuntimeField> for RuntimeFieldForSerialization {
+    fn from(runtime_field: RuntimeField) -> Self {
+        RuntimeFieldForSerialization {
+            field_type: runtime_field.field_type,
+            script: RuntimeFieldScript::Source(runtime_field.script),
+        }
+    }
+}
+
+/// A fast field column, with its values converted to `f64`.
+///
+/// Dates are expressed in milliseconds since the epoch. Columns that are not numeric are ignored.
+struct NumericColumn {
+    column: Column<u64>,
+    column_type: ColumnType,
+}
+
+impl NumericColumn {
+    fn open(reader: &SegmentReader, field: &str) -> tantivy::Result<Option<NumericColumn>> {
+        let column_opt = reader.fast_fields().u64_lenient(field)?;
+        Ok(column_opt.map(|(column, column_type)| NumericColumn {
+            column,
+            column_type,
+        }))
+    }
+
+    fn first(&self, doc: DocId) -> Option<f64> {
+        let value = self.column.first(doc)?;
+        match self.column_type {
+            ColumnType::U64 | ColumnType::Bool => Some(value as f64),
+            ColumnType::I64 => Some(i64::from_u64(value) as f64),
+            ColumnType::F64 => Some(f64::from_u64(value)),
+            ColumnType::DateTime => Some(DateTime::from_u64(value).into_timestamp_millis() as f64),
+            _ => None,
+        }
+    }
+}
+
+/// A runtime field opened on a segment.
+pub struct SegmentRuntimeField {
+    field_type: RuntimeFieldType,
+    expression: Arc<Expression>,
+    columns: Vec<Option<NumericColumn>>,
+}
+
+impl SegmentRuntimeField {
+    pub fn field_type(&self) -> RuntimeFieldType {
+        self.field_type
+    }
+
+    /// Returns the value of the runtime field for `doc`, or `None` if one of the fields it is
+    /// computed from has no value. Values of `i64` runtime fields are truncated.
+    pub fn value(&self, doc: DocId) -> Option<f64> {
+        let value = self.expression.eval(&|field_ord| {
+            self.columns[field_ord]
+                .as_ref()
+                .and_then(|column| column.first(doc))
+        })?;
+        match self.field_type {
+            RuntimeFieldType::F64 => Some(value),
+            RuntimeFieldType::I64 => Some(value.trunc()),
+        }
+    }
+}
+
+/// Rewrites the range, term, full-text and exists queries targeting a runtime field into
+/// [`RuntimeFieldQuery`]. Must be called on an AST without user input queries.
+pub fn resolve_runtime_fields(
+    query_ast: QueryAst,
+    runtime_mappings: &RuntimeMappings,
+) -> anyhow::Result<QueryAst> {
+    if runtime_mappings.is_empty() {
+        return Ok(query_ast);
+    }
+    let mut resolver = RuntimeFieldResolver { runtime_mappings };
+    let query_ast = resolver
+        .transform(query_ast)?
+        .unwrap_or(QueryAst::MatchNone);
+    Ok(query_ast)
+}
+
+/// Returns the cost of evaluating the runtime field queries of `query_ast` on a document. Each
+/// runtime field query evaluates its runtime field independently.
+pub fn runtime_field_cost_per_doc(query_ast: &QueryAst) -> u64 {
+    let mut runtime_field_cost = RuntimeFieldCost::default();
+    let _: Result<(), Infallible> = runtime_field_cost.visit(query_ast);
+    runtime_field_cost.cost_per_doc
+}
+
+#[derive(Default)]
+struct RuntimeFieldCost {
+    cost_per_doc: u64,
+}
+
+impl<'a> QueryAstVisitor<'a> for RuntimeFieldCost {
+    type Err = Infallible;
+
+    fn visit_runtime_field(
+        &mut self,
+        runtime_field_query: &'a RuntimeFieldQuery,
+    ) -> Result<(), Infallible> {
+        self.cost_per_doc += runtime_field_query.runtime_field.cost_per_doc();
+        Ok(())
+    }
+}
+
+struct RuntimeFieldResolver<'a> {
+    runtime_mappings: &'a RuntimeMappings,
+}
+
+impl RuntimeFieldResolver<'_> {
+    fn runtime_field_query(
+        &self,
+        field: &str,
+        lower_bound: Bound<JsonLiteral>,
+        upper_bound: Bound<JsonLiteral>,
+    ) -> Option<QueryAst> {
+        let runtime_field = self.runtime_mappings.get(field)?;
+        let runtime_field_query = RuntimeFieldQuery {
+            field: field.to_string(),
+            runtime_field: runtime_field.clone(),
+            lower_bound,
+            upper_bound,
+        };
+        Some(runtime_field_query.into())
+    }
+}
+
+impl QueryAstTransformer for RuntimeFieldResolver<'_> {
+    type Err = anyhow::Error;
+
+    fn transform_range(&mut self, range_query: RangeQuery) -> anyhow::Result<Option<QueryAst>> {
+        if !self.runtime_mappings.contains_key(&range_query.field) {
+            return Ok(Some(range_query.into()));
+        }
+        Ok(self.runtime_field_query(
+            &range_query.field,
+            range_query.lower_bound,
+            range_query.upper_bound,
+        ))
+    }
+
+    fn transform_term(&mut self, term_query: TermQuery) -> anyhow::Result<Option<QueryAst>> {
+        if !self.runtime_mappings.contains_key(&term_query.field) {
+            return Ok(Some(term_query.into()));
+        }
+        let value = JsonLiteral::String(term_query.value);
+        Ok(self.runtime_field_query(
+            &term_query.field,
+            Bound::Included(value.clone()),
+            Bound::Included(value),
+        ))
+    }
+
+    fn transform_full_text(
+        &mut self,
+        full_text_query: FullTextQuery,
+    ) -> anyhow::Result<Option<QueryAst>> {
+        if !self.runtime_mappings.contains_key(&full_text_query.field) {
+            return Ok(Some(full_text_query.into()));
+        }
+        let value = JsonLiteral::String(full_text_query.text);
+        Ok(self.runtime_field_query(
+            &full_text_query.field,
+            Bound::Included(value.clone()),
+            Bound::Included(value),
+        ))
+    }
+
+    fn transform_exists(
+        &mut self,
+        exists_query: FieldPresenceQuery,
+    ) -> anyhow::Result<Option<QueryAst>> {
+        if let Some(query_ast) =
+            self.runtime_field_query(&exists_query.field, Bound::Unbounded, Bound::Unbounded)
+        {
+            return Ok(Some(query_ast));
+        }
+        Ok(Some(exists_query.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_mappings_deserialization() {
+        let runtime_mappings = parse_runtime_mappings(
+            r#"{
+                "duration_ms": {"type": "long", "script": {"source": "emit(doc['end'].value - doc['start'].value)", "lang": "painless"}},
+                "ratio": {"script": "hits / total"}
+            }"#,
+        )
+        .unwrap();
+        let duration = &runtime_mappings["duration_ms"];
+        assert_eq!(duration.field_type(), RuntimeFieldType::I64);
+        assert_eq!(duration.fields(), &["end".to_string(), "start".to_string()]);
+        let ratio = &runtime_mappings["ratio"];
+        assert_eq!(ratio.field_type(), RuntimeFieldType::F64);
+
+        let runtime_mappings_json = serde_json::to_string(&runtime_mappings).unwrap();
+        assert_eq!(
+            parse_runtime_mappings(&runtime_mappings_json).unwrap(),
+            runtime_mappings
+        );
+
+        let error = parse_runtime_mappings(r#"{"a": {"script": "b +"}}"#).unwrap_err();
+        assert!(format!("{error:?}").contains("failed to parse runtime field script"));
+        let error = parse_runtime_mappings(r#"{"a": {"script": {"source": "b", "lang": "vrl"}}}"#)
+            .unwrap_err();
+        assert!(format!("{error:?}").contains("unsupported runtime field script language"));
+    }
+
+    #[test]
+    fn test_resolve_runtime_fields() {
+        let runtime_mappings =
+            parse_runtime_mappings(r#"{"duration_ms": {"script": "end - start"}}"#).unwrap();
+        let query_ast: QueryAst = crate::query_ast::BoolQuery {
+            must: vec![
+                TermQuery {
+                    field: "duration_ms".to_string(),
+                    value: "10".to_string(),
+                }
+                .into(),
+                TermQuery {
+                    field: "service".to_string(),
+                    value: "api".to_string(),
+                }
+                .into(),
+            ],
+            filter: vec![FieldPresenceQuery {
+                field: "duration_ms".to_string(),
+            }
+            .into()],
+            ..Default::default()
+        }
+        .into();
+        let QueryAst::Bool(bool_query) =
+            resolve_runtime_fields(query_ast, &runtime_mappings).unwrap()
+        else {
+            panic!("expected a bool query");
+        };
+        let runtime_field = runtime_mappings["duration_ms"].clone();
+        assert_eq!(
+            bool_query.must[0],
+            QueryAst::RuntimeField(RuntimeFieldQuery {
+                field: "duration_ms".to_string(),
+                runtime_field: runtime_field.clone(),
+                lower_bound: Bound::Included(JsonLiteral::String("10".to_string())),
+                upper_bound: Bound::Included(JsonLiteral::String("10".to_string())),
+            })
+        );
+        assert!(matches!(bool_query.must[1], QueryAst::Term(_)));
+        assert_eq!(
+            bool_query.filter[0],
+            QueryAst::RuntimeField(RuntimeFieldQuery {
+                field: "duration_ms".to_string(),
+                runtime_field: runtime_field.clone(),
+                lower_bound: Bound::Unbounded,
+                upper_bound: Bound::Unbounded,
+            })
+        );
+        assert_eq!(runtime_field.cost_per_doc(), 3);
+        assert_eq!(
+            runtime_field_cost_per_doc(&QueryAst::Bool(bool_query)),
+            2 * runtime_field.cost_per_doc()
+        );
+    }
+}
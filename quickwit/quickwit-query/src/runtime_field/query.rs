@@ -0,0 +1,195 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::{Bound, RangeBounds};
+
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TantivyError, TERMINATED};
+
+use super::{RuntimeField, SegmentRuntimeField};
+
+/// Matches the documents for which the value of a runtime field is within a range.
+///
+/// With unbounded bounds, matches the documents for which the runtime field has a value.
+#[derive(Clone, Debug)]
+pub struct RuntimeFieldRangeQuery {
+    runtime_field: RuntimeField,
+    lower_bound: Bound<f64>,
+    upper_bound: Bound<f64>,
+}
+
+impl RuntimeFieldRangeQuery {
+    pub fn new(
+        runtime_field: RuntimeField,
+        lower_bound: Bound<f64>,
+        upper_bound: Bound<f64>,
+    ) -> Self {
+        RuntimeFieldRangeQuery {
+            runtime_field,
+            lower_bound,
+            upper_bound,
+        }
+    }
+}
+
+impl Query for RuntimeFieldRangeQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+impl Weight for RuntimeFieldRangeQuery {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let runtime_field = self.runtime_field.for_segment(reader)?;
+        let mut scorer = RuntimeFieldRangeScorer {
+            runtime_field,
+            bounds: (self.lower_bound, self.upper_bound),
+            max_doc: reader.max_doc(),
+            doc: 0,
+            boost,
+        };
+        if scorer.max_doc == 0 {
+            scorer.doc = TERMINATED;
+        } else if !scorer.matches(0) {
+            scorer.advance();
+        }
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("RuntimeFieldRangeQuery", scorer.score()))
+    }
+}
+
+struct RuntimeFieldRangeScorer {
+    runtime_field: SegmentRuntimeField,
+    bounds: (Bound<f64>, Bound<f64>),
+    max_doc: DocId,
+    doc: DocId,
+    boost: Score,
+}
+
+impl RuntimeFieldRangeScorer {
+    fn matches(&self, doc: DocId) -> bool {
+        self.runtime_field
+            .value(doc)
+            .map_or(false, |value| self.bounds.contains(&value))
+    }
+}
+
+impl DocSet for RuntimeFieldRangeScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        loop {
+            self.doc += 1;
+            if self.doc >= self.max_doc {
+                self.doc = TERMINATED;
+                return TERMINATED;
+            }
+            if self.matches(self.doc) {
+                return self.doc;
+            }
+        }
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc
+    }
+}
+
+impl Scorer for RuntimeFieldRangeScorer {
+    fn score(&mut self) -> Score {
+        self.boost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use tantivy::collector::DocSetCollector;
+    use tantivy::schema::{Schema, FAST, INDEXED};
+    use tantivy::{doc, DateTime, Index};
+
+    use super::RuntimeFieldRangeQuery;
+    use crate::runtime_field::{RuntimeField, RuntimeFieldType};
+
+    fn test_index() -> Index {
+        let mut schema_builder = Schema::builder();
+        let start = schema_builder.add_date_field("start", FAST);
+        let end = schema_builder.add_date_field("end", FAST);
+        let size = schema_builder.add_i64_field("size", FAST | INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for (start_ms, end_ms, size_val) in
+            [(1_000, 1_250, -3), (2_000, 2_010, 7), (3_000, 3_500, 2)]
+        {
+            index_writer
+                .add_document(doc!(
+                    start => DateTime::from_timestamp_millis(start_ms),
+                    end => DateTime::from_timestamp_millis(end_ms),
+                    size => size_val,
+                ))
+                .unwrap();
+        }
+        // No end date: the runtime field has no value.
+        index_writer
+            .add_document(doc!(start => DateTime::from_timestamp_millis(4_000), size => 1i64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index
+    }
+
+    fn matching_docs(index: &Index, query: &RuntimeFieldRangeQuery) -> Vec<u32> {
+        let searcher = index.reader().unwrap().searcher();
+        let mut docs: Vec<u32> = searcher
+            .search(query, &DocSetCollector)
+            .unwrap()
+            .into_iter()
+            .map(|doc_address| doc_address.doc_id)
+            .collect();
+        docs.sort();
+        docs
+    }
+
+    #[test]
+    fn test_runtime_field_range_query() {
+        let index = test_index();
+        let duration = RuntimeField::new(RuntimeFieldType::F64, "end - start").unwrap();
+        let query =
+            RuntimeFieldRangeQuery::new(duration.clone(), Bound::Included(100.0), Bound::Unbounded);
+        assert_eq!(matching_docs(&index, &query), vec![0, 2]);
+
+        let query = RuntimeFieldRangeQuery::new(duration, Bound::Unbounded, Bound::Unbounded);
+        assert_eq!(matching_docs(&index, &query), vec![0, 1, 2]);
+
+        let doubled_size = RuntimeField::new(RuntimeFieldType::I64, "abs(size) * 2.5").unwrap();
+        let query =
+            RuntimeFieldRangeQuery::new(doubled_size, Bound::Included(7.0), Bound::Included(7.0));
+        assert_eq!(matching_docs(&index, &query), vec![0]);
+    }
+}
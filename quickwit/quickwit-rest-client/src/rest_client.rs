@@ -17,13 +17,15 @@ use std::time::Duration;
 
 use bytes::Bytes;
 use quickwit_cluster::ClusterSnapshot;
-use quickwit_config::{ConfigFormat, SourceConfig};
+use quickwit_config::{ConfigFormat, SourceConfig, TransformConfig};
 use quickwit_indexing::actors::IndexingServiceCounters;
 pub use quickwit_ingest::CommitType;
 use quickwit_metastore::{IndexMetadata, Split, SplitInfo};
 use quickwit_proto::ingest::Shard;
+use quickwit_search::{SqlRequest, SqlResponse};
 use quickwit_serve::{
-    ListSplitsQueryParams, ListSplitsResponse, RestIngestResponse, SearchRequestQueryString,
+    IndexSnapshotSummary, ListSplitsQueryParams, ListSplitsResponse, RestIngestResponse,
+    SearchRequestQueryString, SplitFilesMode,
 };
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use reqwest::{Client, ClientBuilder, Method, StatusCode, Url};
@@ -231,6 +233,23 @@ impl QuickwitClient {
         Ok(search_response)
     }
 
+    pub async fn sql(&self, sql_request: SqlRequest) -> Result<SqlResponse, Error> {
+        let body = Bytes::from(serde_json::to_vec(&sql_request)?);
+        let response = self
+            .transport
+            .send::<()>(
+                Method::POST,
+                "_sql",
+                None,
+                None,
+                Some(body),
+                self.search_timeout,
+            )
+            .await?;
+        let sql_response = response.deserialize().await?;
+        Ok(sql_response)
+    }
+
     pub fn indexes(&self) -> IndexClient {
         IndexClient::new(&self.transport, self.timeout)
     }
@@ -422,6 +441,72 @@ impl<'a> IndexClient<'a> {
         Ok(())
     }
 
+    pub async fn snapshot(
+        &self,
+        index_id: &str,
+        snapshot_uri: &str,
+        split_files_mode: SplitFilesMode,
+    ) -> Result<IndexSnapshotSummary, Error> {
+        let path = format!("indexes/{index_id}/snapshot");
+        let body = Bytes::from(serde_json::to_vec(&json!({
+            "snapshot_uri": snapshot_uri,
+            "split_files_mode": split_files_mode,
+        }))?);
+        let response = self
+            .transport
+            .send::<()>(Method::POST, &path, None, None, Some(body), self.timeout)
+            .await?;
+        let snapshot_summary = response.deserialize().await?;
+        Ok(snapshot_summary)
+    }
+
+    pub async fn restore(
+        &self,
+        snapshot_uri: &str,
+        index_id_opt: Option<&str>,
+        index_uri_opt: Option<&str>,
+    ) -> Result<IndexMetadata, Error> {
+        let body = Bytes::from(serde_json::to_vec(&json!({
+            "snapshot_uri": snapshot_uri,
+            "index_id": index_id_opt,
+            "index_uri": index_uri_opt,
+        }))?);
+        let response = self
+            .transport
+            .send::<()>(
+                Method::POST,
+                "indexes/restore",
+                None,
+                None,
+                Some(body),
+                self.timeout,
+            )
+            .await?;
+        let index_metadata = response.deserialize().await?;
+        Ok(index_metadata)
+    }
+
+    pub async fn reindex(
+        &self,
+        index_id: &str,
+        source_index_id: &str,
+        query: &str,
+        transform_config_opt: Option<&TransformConfig>,
+    ) -> Result<SourceConfig, Error> {
+        let path = format!("indexes/{index_id}/reindex");
+        let body = Bytes::from(serde_json::to_vec(&json!({
+            "source_index_id": source_index_id,
+            "query": query,
+            "transform": transform_config_opt,
+        }))?);
+        let response = self
+            .transport
+            .send::<()>(Method::POST, &path, None, None, Some(body), self.timeout)
+            .await?;
+        let source_config = response.deserialize().await?;
+        Ok(source_config)
+    }
+
     pub async fn delete(&self, index_id: &str, dry_run: bool) -> Result<Vec<SplitInfo>, Error> {
         let path = format!("indexes/{index_id}");
         let response = self
@@ -736,6 +821,7 @@ mod test {
     use quickwit_metastore::IndexMetadata;
     use quickwit_serve::{
         ListSplitsQueryParams, ListSplitsResponse, RestIngestResponse, SearchRequestQueryString,
+        SplitFilesMode,
     };
     use reqwest::header::CONTENT_TYPE;
     use reqwest::{StatusCode, Url};
@@ -796,6 +882,35 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_sql_endpoint() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let sql_request = SqlRequest {
+            query: "SELECT COUNT(*) FROM my-index".to_string(),
+            ..Default::default()
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/v1/_sql"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(json!({"columns": ["COUNT(*)"], "rows": [[3]], "num_hits": 3})),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let expected_sql_response = SqlResponse {
+            columns: vec!["COUNT(*)".to_string()],
+            rows: vec![vec![json!(3)]],
+            num_hits: 3,
+        };
+        assert_eq!(
+            qw_client.sql(sql_request).await.unwrap(),
+            expected_sql_response
+        );
+    }
+
     fn get_ndjson_filepath(ndjson_dataset_filename: &str) -> String {
         format!(
             "{}/resources/tests/{}",
@@ -1033,6 +1148,92 @@ mod test {
             .await;
         qw_client.indexes().clear("my-index").await.unwrap_err();
 
+        // POST snapshot index
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/my-index/snapshot"))
+            .and(body_json(json!({
+                "snapshot_uri": "s3://my-bucket/snapshots/my-index",
+                "split_files_mode": "reference",
+            })))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({
+                "index_id": "my-index",
+                "snapshot_uri": "s3://my-bucket/snapshots/my-index",
+                "split_files_mode": "reference",
+                "num_splits": 2,
+                "num_bytes": 1024,
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let snapshot_summary = qw_client
+            .indexes()
+            .snapshot(
+                "my-index",
+                "s3://my-bucket/snapshots/my-index",
+                SplitFilesMode::Reference,
+            )
+            .await
+            .unwrap();
+        assert_eq!(snapshot_summary.num_splits, 2);
+        assert_eq!(snapshot_summary.num_bytes, 1024);
+
+        // POST restore index
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/restore"))
+            .and(body_json(json!({
+                "snapshot_uri": "s3://my-bucket/snapshots/my-index",
+                "index_id": "test-index",
+                "index_uri": null,
+            })))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(index_metadata.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .indexes()
+                .restore(
+                    "s3://my-bucket/snapshots/my-index",
+                    Some("test-index"),
+                    None
+                )
+                .await
+                .unwrap(),
+            index_metadata
+        );
+
+        // POST reindex
+        let transform_config = TransformConfig::new(".severity = \"INFO\"".to_string(), None);
+        let reindex_source_config = SourceConfig::ingest_api_default();
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/my-index-v2/reindex"))
+            .and(body_json(json!({
+                "source_index_id": "my-index",
+                "query": "severity:ERROR",
+                "transform": {"script": ".severity = \"INFO\"", "timezone": "UTC"},
+            })))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(reindex_source_config.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .indexes()
+                .reindex(
+                    "my-index-v2",
+                    "my-index",
+                    "severity:ERROR",
+                    Some(&transform_config)
+                )
+                .await
+                .unwrap(),
+            reindex_source_config
+        );
+
         // DELETE index
         Mock::given(method("DELETE"))
             .and(path("/api/v1/indexes/my-index"))
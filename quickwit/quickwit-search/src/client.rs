@@ -32,7 +32,8 @@ use tower::timeout::Timeout;
 use tracing::{info_span, warn, Instrument};
 
 use crate::error::parse_grpc_error;
-use crate::SearchService;
+use crate::lambda_leaf_search::LambdaLeafSearchClient;
+use crate::{SearchError, SearchService};
 
 /// Impl is an enumeration that meant to manage Quickwit's search service client types.
 #[derive(Clone)]
@@ -43,6 +44,7 @@ enum SearchServiceClientImpl {
             InterceptedService<Timeout<Channel>, SpanContextInterceptor>,
         >,
     ),
+    Lambda(LambdaLeafSearchClient),
 }
 
 /// A search service client.
@@ -63,6 +65,9 @@ impl fmt::Debug for SearchServiceClient {
             SearchServiceClientImpl::Grpc(_grpc_client) => {
                 write!(formatter, "Grpc({:?})", self.grpc_addr)
             }
+            SearchServiceClientImpl::Lambda(lambda_client) => {
+                write!(formatter, "Lambda({:?})", lambda_client.function_name())
+            }
         }
     }
 }
@@ -89,6 +94,17 @@ impl SearchServiceClient {
         }
     }
 
+    /// Create a search service client instance sending leaf search requests to a Lambda function.
+    pub(crate) fn from_lambda_client(
+        lambda_client: LambdaLeafSearchClient,
+        grpc_addr: SocketAddr,
+    ) -> Self {
+        SearchServiceClient {
+            client_impl: SearchServiceClientImpl::Lambda(lambda_client),
+            grpc_addr,
+        }
+    }
+
     /// Return the grpc_addr the underlying client connects to.
     pub fn grpc_addr(&self) -> SocketAddr {
         self.grpc_addr
@@ -112,6 +128,7 @@ impl SearchServiceClient {
                 .map(|tonic_response| tonic_response.into_inner())
                 .map_err(|tonic_error| parse_grpc_error(&tonic_error)),
             SearchServiceClientImpl::Local(service) => service.root_search(request).await,
+            SearchServiceClientImpl::Lambda(_) => Err(unsupported_by_lambda("root_search")),
         }
     }
 
@@ -127,6 +144,9 @@ impl SearchServiceClient {
                 .map(|tonic_response| tonic_response.into_inner())
                 .map_err(|tonic_error| parse_grpc_error(&tonic_error)),
             SearchServiceClientImpl::Local(service) => service.leaf_search(request).await,
+            SearchServiceClientImpl::Lambda(lambda_client) => {
+                lambda_client.leaf_search(request).await
+            }
         }
     }
 
@@ -145,6 +165,7 @@ impl SearchServiceClient {
                 Ok(tonic_response.into_inner())
             }
             SearchServiceClientImpl::Local(service) => service.leaf_list_fields(request).await,
+            SearchServiceClientImpl::Lambda(_) => Err(unsupported_by_lambda("leaf_list_fields")),
         }
     }
 
@@ -199,6 +220,11 @@ impl SearchServiceClient {
                     UnboundedReceiverStream::new(result_receiver)
                 })
             }
+            SearchServiceClientImpl::Lambda(_) => {
+                let (result_sender, result_receiver) = tokio::sync::mpsc::unbounded_channel();
+                let _ = result_sender.send(Err(unsupported_by_lambda("leaf_search_stream")));
+                UnboundedReceiverStream::new(result_receiver)
+            }
         }
     }
 
@@ -217,6 +243,7 @@ impl SearchServiceClient {
                 Ok(tonic_response.into_inner())
             }
             SearchServiceClientImpl::Local(service) => service.fetch_docs(request).await,
+            SearchServiceClientImpl::Lambda(_) => Err(unsupported_by_lambda("fetch_docs")),
         }
     }
 
@@ -235,6 +262,7 @@ impl SearchServiceClient {
                 Ok(tonic_response.into_inner())
             }
             SearchServiceClientImpl::Local(service) => service.leaf_list_terms(request).await,
+            SearchServiceClientImpl::Lambda(_) => Err(unsupported_by_lambda("leaf_list_terms")),
         }
     }
 
@@ -255,6 +283,7 @@ impl SearchServiceClient {
                 let get_search_after_context_resp = grpc_resp.into_inner();
                 Ok(get_search_after_context_resp.payload)
             }
+            SearchServiceClientImpl::Lambda(_) => Err(unsupported_by_lambda("get_kv")),
         }
     }
 
@@ -272,6 +301,7 @@ impl SearchServiceClient {
                     .await
                     .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
             }
+            SearchServiceClientImpl::Lambda(_) => return Err(unsupported_by_lambda("put_kv")),
         }
         Ok(())
     }
@@ -296,10 +326,18 @@ impl SearchServiceClient {
                     );
                 }
             }
+            // Lambda functions do not cache splits.
+            SearchServiceClientImpl::Lambda(_) => {}
         }
     }
 }
 
+fn unsupported_by_lambda(rpc_name: &str) -> SearchError {
+    SearchError::Internal(format!(
+        "`{rpc_name}` is not supported by the leaf search Lambda transport"
+    ))
+}
+
 /// Creates a [`SearchServiceClient`] from a socket address.
 /// The underlying channel connects lazily and is set up to time out after 5 seconds. It reconnects
 /// automatically should the connection be dropped.
@@ -48,12 +48,23 @@ const TARGET_NUM_REPLICATION: usize = 2;
 #[derive(Clone)]
 pub struct ClusterClient {
     pub(crate) search_job_placer: SearchJobPlacer,
+    lambda_client_opt: Option<SearchServiceClient>,
 }
 
 impl ClusterClient {
     /// Instantiates [`ClusterClient`].
     pub fn new(search_job_placer: SearchJobPlacer) -> Self {
-        Self { search_job_placer }
+        Self {
+            search_job_placer,
+            lambda_client_opt: None,
+        }
+    }
+
+    /// Sends the leaf search requests to `lambda_client` instead of the searchers they were
+    /// placed on. The splits failing on Lambda are retried on the searchers of the cluster.
+    pub fn with_lambda_client(mut self, lambda_client: SearchServiceClient) -> Self {
+        self.lambda_client_opt = Some(lambda_client);
+        self
     }
 
     /// Fetches docs with retry on another node client.
@@ -87,6 +98,9 @@ impl ClusterClient {
         request: LeafSearchRequest,
         mut client: SearchServiceClient,
     ) -> crate::Result<LeafSearchResponse> {
+        if let Some(lambda_client) = &self.lambda_client_opt {
+            client = lambda_client.clone();
+        }
         let mut response_res = client.leaf_search(request.clone()).await;
         let retry_policy = LeafSearchRetryPolicy {};
         // We retry only once.
@@ -274,7 +288,7 @@ fn replicate_kv_to_one_server(
 
 /// Takes two intermediate aggregation results serialized using postcard,
 /// merge them and returns the merged serialized result.
-fn merge_intermediate_aggregation(left: &[u8], right: &[u8]) -> crate::Result<Vec<u8>> {
+pub(crate) fn merge_intermediate_aggregation(left: &[u8], right: &[u8]) -> crate::Result<Vec<u8>> {
     let mut intermediate_aggregation_results_left: IntermediateAggregationResults =
         postcard::from_bytes(left)?;
     let intermediate_aggregation_results_right: IntermediateAggregationResults =
@@ -406,6 +420,8 @@ mod tests {
                 timestamp_start: None,
                 timestamp_end: None,
                 num_docs: 0,
+                storage_uri: None,
+                require_encryption: false,
             }],
             ..Default::default()
         }
@@ -433,6 +449,8 @@ mod tests {
                         timestamp_start: None,
                         timestamp_end: None,
                         num_docs: 0,
+                        storage_uri: None,
+                        require_encryption: false,
                     },
                     SplitIdAndFooterOffsets {
                         split_id: "split_2".to_string(),
@@ -441,6 +459,8 @@ mod tests {
                         timestamp_start: None,
                         timestamp_end: None,
                         num_docs: 0,
+                        storage_uri: None,
+                        require_encryption: false,
                     },
                 ],
             }],
@@ -470,6 +490,8 @@ mod tests {
                     timestamp_start: None,
                     timestamp_end: None,
                     num_docs: 0,
+                    storage_uri: None,
+                    require_encryption: false,
                 },
                 SplitIdAndFooterOffsets {
                     split_id: "split_2".to_string(),
@@ -478,6 +500,8 @@ mod tests {
                     timestamp_start: None,
                     timestamp_end: None,
                     num_docs: 0,
+                    storage_uri: None,
+                    require_encryption: false,
                 },
             ],
         }
@@ -18,12 +18,15 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 use quickwit_common::binary_heap::{SortKeyMapper, TopK};
-use quickwit_doc_mapper::{FastFieldWarmupInfo, WarmupInfo};
+use quickwit_doc_mapper::{DocMapper, FastFieldWarmupInfo, WarmupInfo};
 use quickwit_proto::search::{
     LeafSearchResponse, PartialHit, ResourceStats, SearchRequest, SortByValue, SortOrder,
     SortValue, SplitSearchError,
 };
 use quickwit_proto::types::SplitId;
+use quickwit_query::runtime_field::{
+    RuntimeField, RuntimeFieldType, RuntimeMappings, SegmentRuntimeField,
+};
 use serde::Deserialize;
 use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
@@ -31,11 +34,26 @@ use tantivy::aggregation::{AggregationLimitsGuard, AggregationSegmentCollector};
 use tantivy::collector::{Collector, SegmentCollector};
 use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64};
 use tantivy::fastfield::Column;
+use tantivy::schema::Schema;
 use tantivy::{DateTime, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
+use crate::composite_aggregation::{
+    CompositeAggregations, CompositeAggregationsSegmentCollector,
+    CompositeIntermediateAggregationResults,
+};
 use crate::find_trace_ids_collector::{FindTraceIdsCollector, FindTraceIdsSegmentCollector, Span};
+use crate::nested_aggregation::{
+    NestedAggregations, NestedAggregationsSegmentCollector, NestedIntermediateAggregationResults,
+};
+use crate::runtime_field_aggregation::{
+    aggregations_use_runtime_fields, RuntimeFieldAggregationSegmentCollector,
+};
+use crate::term_frequency_aggregation::{
+    BackgroundQueries, TermFrequencyAggregations, TermFrequencyAggregationsSegmentCollector,
+    TermFrequencyIntermediateAggregationResults,
+};
 use crate::top_k_collector::{specialized_top_k_segment_collector, QuickwitSegmentTopKCollector};
-use crate::{merge_resource_stats, merge_resource_stats_it, GlobalDocAddress};
+use crate::{merge_resource_stats, merge_resource_stats_it, GlobalDocAddress, SearchError};
 
 #[derive(Clone, Debug)]
 pub(crate) enum SortByComponent {
@@ -46,6 +64,10 @@ pub(crate) enum SortByComponent {
         field_name: String,
         order: SortOrder,
     },
+    RuntimeField {
+        runtime_field: RuntimeField,
+        order: SortOrder,
+    },
     Score {
         order: SortOrder,
     },
@@ -96,6 +118,17 @@ impl SortByComponent {
                     sort_field_type,
                 })
             }
+            SortByComponent::RuntimeField { runtime_field, .. } => {
+                let runtime_field = runtime_field.for_segment(segment_reader)?;
+                let sort_field_type = match runtime_field.field_type() {
+                    RuntimeFieldType::F64 => SortFieldType::F64,
+                    RuntimeFieldType::I64 => SortFieldType::I64,
+                };
+                Ok(SortingFieldExtractorComponent::RuntimeField {
+                    runtime_field,
+                    sort_field_type,
+                })
+            }
             SortByComponent::Score { .. } => Ok(SortingFieldExtractorComponent::Score),
         }
     }
@@ -103,22 +136,32 @@ impl SortByComponent {
         match self {
             SortByComponent::DocId { .. } => false,
             SortByComponent::FastField { .. } => false,
+            SortByComponent::RuntimeField { .. } => false,
             SortByComponent::Score { .. } => true,
         }
     }
     pub fn add_fast_field(&self, set: &mut HashSet<String>) {
-        if let SortByComponent::FastField {
-            field_name,
-            order: _,
-        } = self
-        {
-            set.insert(field_name.clone());
+        match self {
+            SortByComponent::FastField { field_name, .. } => {
+                set.insert(field_name.clone());
+            }
+            SortByComponent::RuntimeField { runtime_field, .. } => {
+                set.extend(runtime_field.fields().iter().cloned());
+            }
+            SortByComponent::DocId { .. } | SortByComponent::Score { .. } => {}
+        }
+    }
+    pub fn runtime_field_cost_per_doc(&self) -> u64 {
+        match self {
+            SortByComponent::RuntimeField { runtime_field, .. } => runtime_field.cost_per_doc(),
+            _ => 0,
         }
     }
     pub fn sort_order(&self) -> SortOrder {
         match self {
             SortByComponent::DocId { order } => *order,
             SortByComponent::FastField { order, .. } => *order,
+            SortByComponent::RuntimeField { order, .. } => *order,
             SortByComponent::Score { order } => *order,
         }
     }
@@ -142,6 +185,11 @@ pub(crate) enum SortingFieldExtractorComponent {
         sort_column: Column<u64>,
         sort_field_type: SortFieldType,
     },
+    /// Runtime fields are evaluated lazily, for the matching documents only.
+    RuntimeField {
+        runtime_field: SegmentRuntimeField,
+        sort_field_type: SortFieldType,
+    },
     Score,
 }
 
@@ -150,16 +198,31 @@ impl SortingFieldExtractorComponent {
         matches!(self, SortingFieldExtractorComponent::Score)
     }
     pub fn is_fast_field(&self) -> bool {
-        matches!(self, SortingFieldExtractorComponent::FastField { .. })
+        matches!(
+            self,
+            SortingFieldExtractorComponent::FastField { .. }
+                | SortingFieldExtractorComponent::RuntimeField { .. }
+        )
     }
     /// Loads the fast field values for the given doc_ids in its u64 representation. The returned
     /// u64 representation maintains the ordering of the original value.
     #[inline]
     pub fn extract_typed_sort_values_block(&self, doc_ids: &[DocId], values: &mut [Option<u64>]) {
         // In the collect block case we don't have scores to extract
-        if let SortingFieldExtractorComponent::FastField { sort_column, .. } = self {
-            let values = &mut values[..doc_ids.len()];
-            sort_column.first_vals(doc_ids, values);
+        match self {
+            SortingFieldExtractorComponent::FastField { sort_column, .. } => {
+                let values = &mut values[..doc_ids.len()];
+                sort_column.first_vals(doc_ids, values);
+            }
+            SortingFieldExtractorComponent::RuntimeField {
+                runtime_field,
+                sort_field_type,
+            } => {
+                for (doc_id, value) in doc_ids.iter().zip(values.iter_mut()) {
+                    *value = runtime_field_sort_value(runtime_field, *sort_field_type, *doc_id);
+                }
+            }
+            SortingFieldExtractorComponent::DocId | SortingFieldExtractorComponent::Score => {}
         }
     }
 
@@ -176,6 +239,10 @@ impl SortingFieldExtractorComponent {
             SortingFieldExtractorComponent::FastField { sort_column, .. } => {
                 sort_column.first(doc_id)
             }
+            SortingFieldExtractorComponent::RuntimeField {
+                runtime_field,
+                sort_field_type,
+            } => runtime_field_sort_value(runtime_field, *sort_field_type, doc_id),
             SortingFieldExtractorComponent::Score { .. } => Some((score as f64).to_u64()),
         }
     }
@@ -198,6 +265,9 @@ impl SortingFieldExtractorComponent {
             SortingFieldExtractorComponent::DocId => SortValue::U64(sort_value),
             SortingFieldExtractorComponent::FastField {
                 sort_field_type, ..
+            }
+            | SortingFieldExtractorComponent::RuntimeField {
+                sort_field_type, ..
             } => map_fast_field_to_value(sort_value, *sort_field_type),
             SortingFieldExtractorComponent::Score => SortValue::F64(f64::from_u64(sort_value)),
         }
@@ -222,6 +292,9 @@ impl SortingFieldExtractorComponent {
             },
             SortingFieldExtractorComponent::FastField {
                 sort_field_type, ..
+            }
+            | SortingFieldExtractorComponent::RuntimeField {
+                sort_field_type, ..
             } => {
                 // We need to convert a (potential user provided) value in the correct u64
                 // representation of the fast field.
@@ -371,6 +444,20 @@ impl SortingFieldExtractorComponent {
     }
 }
 
+/// Returns the value of a runtime field in the u64 representation of its sort field type.
+fn runtime_field_sort_value(
+    runtime_field: &SegmentRuntimeField,
+    sort_field_type: SortFieldType,
+    doc_id: DocId,
+) -> Option<u64> {
+    let value = runtime_field.value(doc_id)?;
+    if sort_field_type == SortFieldType::I64 {
+        Some((value as i64).to_u64())
+    } else {
+        Some(value.to_u64())
+    }
+}
+
 impl From<SortingFieldExtractorComponent> for SortingFieldExtractorPair {
     fn from(value: SortingFieldExtractorComponent) -> Self {
         Self {
@@ -470,6 +557,10 @@ fn get_score_extractor(
 enum AggregationSegmentCollectors {
     FindTraceIdsSegmentCollector(Box<FindTraceIdsSegmentCollector>),
     TantivyAggregationSegmentCollector(AggregationSegmentCollector),
+    NestedAggregationsSegmentCollector(Box<NestedAggregationsSegmentCollector>),
+    CompositeAggregationsSegmentCollector(Box<CompositeAggregationsSegmentCollector>),
+    TermFrequencyAggregationsSegmentCollector(Box<TermFrequencyAggregationsSegmentCollector>),
+    RuntimeFieldAggregationSegmentCollector(Box<RuntimeFieldAggregationSegmentCollector>),
 }
 
 /// Quickwit collector working at the scale of the segment.
@@ -540,6 +631,18 @@ impl SegmentCollector for QuickwitSegmentCollector {
             Some(AggregationSegmentCollectors::TantivyAggregationSegmentCollector(collector)) => {
                 collector.collect_block(filtered_docs)
             }
+            Some(AggregationSegmentCollectors::NestedAggregationsSegmentCollector(collector)) => {
+                collector.collect_block(filtered_docs)
+            }
+            Some(AggregationSegmentCollectors::CompositeAggregationsSegmentCollector(
+                collector,
+            )) => collector.collect_block(filtered_docs),
+            Some(AggregationSegmentCollectors::TermFrequencyAggregationsSegmentCollector(
+                collector,
+            )) => collector.collect_block(filtered_docs),
+            Some(AggregationSegmentCollectors::RuntimeFieldAggregationSegmentCollector(
+                collector,
+            )) => collector.collect_block(filtered_docs),
             None => (),
         }
     }
@@ -558,6 +661,18 @@ impl SegmentCollector for QuickwitSegmentCollector {
             Some(AggregationSegmentCollectors::TantivyAggregationSegmentCollector(collector)) => {
                 collector.collect(doc_id, score)
             }
+            Some(AggregationSegmentCollectors::NestedAggregationsSegmentCollector(collector)) => {
+                collector.collect(doc_id, score)
+            }
+            Some(AggregationSegmentCollectors::CompositeAggregationsSegmentCollector(
+                collector,
+            )) => collector.collect(doc_id, score),
+            Some(AggregationSegmentCollectors::TermFrequencyAggregationsSegmentCollector(
+                collector,
+            )) => collector.collect(doc_id, score),
+            Some(AggregationSegmentCollectors::RuntimeFieldAggregationSegmentCollector(
+                collector,
+            )) => collector.collect(doc_id),
             None => (),
         }
     }
@@ -580,6 +695,33 @@ impl SegmentCollector for QuickwitSegmentCollector {
                     .expect("Collector fruit should be serializable.");
                 Some(serialized)
             }
+            Some(AggregationSegmentCollectors::NestedAggregationsSegmentCollector(collector)) => {
+                let serialized = postcard::to_allocvec(&collector.harvest()?)
+                    .expect("Collector fruit should be serializable.");
+                Some(serialized)
+            }
+            Some(AggregationSegmentCollectors::CompositeAggregationsSegmentCollector(
+                collector,
+            )) => {
+                let serialized = postcard::to_allocvec(&collector.harvest()?)
+                    .expect("Collector fruit should be serializable.");
+                Some(serialized)
+            }
+            Some(AggregationSegmentCollectors::TermFrequencyAggregationsSegmentCollector(
+                collector,
+            )) => {
+                let serialized = postcard::to_allocvec(&collector.harvest()?)
+                    .expect("Collector fruit should be serializable.");
+                Some(serialized)
+            }
+            // Same fruit as `TantivyAggregationSegmentCollector`.
+            Some(AggregationSegmentCollectors::RuntimeFieldAggregationSegmentCollector(
+                collector,
+            )) => {
+                let serialized = postcard::to_allocvec(&collector.harvest()?)
+                    .expect("Collector fruit should be serializable.");
+                Some(serialized)
+            }
             None => None,
         };
 
@@ -604,6 +746,15 @@ pub enum QuickwitAggregations {
     FindTraceIdsAggregation(FindTraceIdsCollector),
     /// Your classic Tantivy aggregation.
     TantivyAggregations(Aggregations),
+    /// Tantivy aggregations along with `nested` aggregations, which aggregate the nested
+    /// documents of the matching documents.
+    NestedAggregations(NestedAggregations),
+    /// Tantivy aggregations along with `composite` aggregations, which paginate through the
+    /// combinations of the values of several fields.
+    CompositeAggregations(CompositeAggregations),
+    /// Tantivy aggregations along with `significant_terms` and `rare_terms` aggregations, which
+    /// select terms by their document frequency.
+    TermFrequencyAggregations(TermFrequencyAggregations),
 }
 
 impl QuickwitAggregations {
@@ -616,6 +767,15 @@ impl QuickwitAggregations {
             QuickwitAggregations::TantivyAggregations(aggregations) => {
                 get_fast_field_names(aggregations)
             }
+            QuickwitAggregations::NestedAggregations(aggregations) => {
+                aggregations.fast_field_names()
+            }
+            QuickwitAggregations::CompositeAggregations(aggregations) => {
+                aggregations.fast_field_names()
+            }
+            QuickwitAggregations::TermFrequencyAggregations(aggregations) => {
+                aggregations.fast_field_names()
+            }
         }
     }
 
@@ -627,6 +787,18 @@ impl QuickwitAggregations {
             QuickwitAggregations::TantivyAggregations(aggreg) => {
                 QuickwitIncrementalAggregations::TantivyAggregations(aggreg.clone(), Vec::new())
             }
+            QuickwitAggregations::NestedAggregations(aggreg) => {
+                QuickwitIncrementalAggregations::NestedAggregations(aggreg.clone(), Vec::new())
+            }
+            QuickwitAggregations::CompositeAggregations(aggreg) => {
+                QuickwitIncrementalAggregations::CompositeAggregations(aggreg.clone(), Vec::new())
+            }
+            QuickwitAggregations::TermFrequencyAggregations(aggreg) => {
+                QuickwitIncrementalAggregations::TermFrequencyAggregations(
+                    aggreg.clone(),
+                    Vec::new(),
+                )
+            }
         }
     }
 }
@@ -635,6 +807,9 @@ impl QuickwitAggregations {
 enum QuickwitIncrementalAggregations {
     FindTraceIdsAggregation(FindTraceIdsCollector, Vec<Vec<Span>>),
     TantivyAggregations(Aggregations, Vec<Vec<u8>>),
+    NestedAggregations(NestedAggregations, Vec<Vec<u8>>),
+    CompositeAggregations(CompositeAggregations, Vec<Vec<u8>>),
+    TermFrequencyAggregations(TermFrequencyAggregations, Vec<Vec<u8>>),
     NoAggregation,
 }
 
@@ -650,7 +825,10 @@ impl QuickwitIncrementalAggregations {
                     state.push(new_state);
                 }
             }
-            QuickwitIncrementalAggregations::TantivyAggregations(_, state) => {
+            QuickwitIncrementalAggregations::TantivyAggregations(_, state)
+            | QuickwitIncrementalAggregations::NestedAggregations(_, state)
+            | QuickwitIncrementalAggregations::CompositeAggregations(_, state)
+            | QuickwitIncrementalAggregations::TermFrequencyAggregations(_, state) => {
                 state.push(intermediate_result);
             }
             QuickwitIncrementalAggregations::NoAggregation => (),
@@ -680,6 +858,9 @@ impl QuickwitIncrementalAggregations {
                 None
             }
             QuickwitIncrementalAggregations::TantivyAggregations(_, _) => None,
+            QuickwitIncrementalAggregations::NestedAggregations(_, _) => None,
+            QuickwitIncrementalAggregations::CompositeAggregations(_, _) => None,
+            QuickwitIncrementalAggregations::TermFrequencyAggregations(_, _) => None,
             QuickwitIncrementalAggregations::NoAggregation => None,
         }
     }
@@ -701,6 +882,24 @@ impl QuickwitIncrementalAggregations {
                     state.iter().map(|vec| vec.as_slice()),
                 )
             }
+            QuickwitIncrementalAggregations::NestedAggregations(aggregation, state) => {
+                merge_intermediate_aggregation_result(
+                    &Some(QuickwitAggregations::NestedAggregations(aggregation)),
+                    state.iter().map(|vec| vec.as_slice()),
+                )
+            }
+            QuickwitIncrementalAggregations::CompositeAggregations(aggregation, state) => {
+                merge_intermediate_aggregation_result(
+                    &Some(QuickwitAggregations::CompositeAggregations(aggregation)),
+                    state.iter().map(|vec| vec.as_slice()),
+                )
+            }
+            QuickwitIncrementalAggregations::TermFrequencyAggregations(aggregation, state) => {
+                merge_intermediate_aggregation_result(
+                    &Some(QuickwitAggregations::TermFrequencyAggregations(aggregation)),
+                    state.iter().map(|vec| vec.as_slice()),
+                )
+            }
             QuickwitIncrementalAggregations::NoAggregation => Ok(None),
         }
     }
@@ -718,7 +917,9 @@ pub(crate) struct QuickwitCollector {
     pub sort_by: SortByPair,
     pub aggregation: Option<QuickwitAggregations>,
     pub aggregation_limits: AggregationLimitsGuard,
+    pub runtime_mappings: RuntimeMappings,
     search_after: Option<PartialHit>,
+    background_queries: BackgroundQueries,
 }
 
 impl QuickwitCollector {
@@ -741,11 +942,53 @@ impl QuickwitCollector {
             sort_by_second.add_fast_field(&mut fast_field_names);
         }
         if let Some(aggregations) = &self.aggregation {
-            fast_field_names.extend(aggregations.fast_field_names());
+            for field_name in aggregations.fast_field_names() {
+                // Runtime fields have no column: they are computed from other fast fields.
+                if let Some(runtime_field) = self.runtime_mappings.get(&field_name) {
+                    fast_field_names.extend(runtime_field.fields().iter().cloned());
+                } else {
+                    fast_field_names.insert(field_name);
+                }
+            }
         }
         fast_field_names
     }
 
+    /// Returns the cost of evaluating the runtime fields used by the sort and the aggregations on
+    /// a document.
+    pub fn runtime_field_cost_per_doc(&self) -> u64 {
+        let mut cost_per_doc = self.sort_by.first.runtime_field_cost_per_doc();
+        if let Some(sort_by_second) = &self.sort_by.second {
+            cost_per_doc += sort_by_second.runtime_field_cost_per_doc();
+        }
+        if let Some(aggregations) = &self.aggregation {
+            cost_per_doc += aggregations
+                .fast_field_names()
+                .iter()
+                .filter_map(|field_name| self.runtime_mappings.get(field_name))
+                .map(|runtime_field| runtime_field.cost_per_doc())
+                .sum::<u64>();
+        }
+        cost_per_doc
+    }
+
+    /// Builds the queries run by the aggregations besides the search query, namely the background
+    /// filters of the `significant_terms` aggregations, and returns their warmup info.
+    pub fn build_aggregation_queries(
+        &mut self,
+        doc_mapper: &DocMapper,
+        split_schema: &Schema,
+    ) -> crate::Result<WarmupInfo> {
+        let Some(QuickwitAggregations::TermFrequencyAggregations(aggregations)) = &self.aggregation
+        else {
+            return Ok(WarmupInfo::default());
+        };
+        let (background_queries, warmup_info) =
+            aggregations.build_background_queries(doc_mapper, split_schema)?;
+        self.background_queries = background_queries;
+        Ok(warmup_info)
+    }
+
     pub fn warmup_info(&self) -> WarmupInfo {
         WarmupInfo {
             fast_fields: self
@@ -781,6 +1024,22 @@ impl Collector for QuickwitCollector {
                     Box::new(collector.for_segment(0, segment_reader)?),
                 ))
             }
+            Some(QuickwitAggregations::TantivyAggregations(aggs))
+                if aggregations_use_runtime_fields(aggs, &self.runtime_mappings) =>
+            {
+                Some(
+                    AggregationSegmentCollectors::RuntimeFieldAggregationSegmentCollector(
+                        Box::new(
+                            RuntimeFieldAggregationSegmentCollector::from_agg_req_and_reader(
+                                aggs,
+                                &self.runtime_mappings,
+                                segment_reader,
+                                &self.aggregation_limits,
+                            )?,
+                        ),
+                    ),
+                )
+            }
             Some(QuickwitAggregations::TantivyAggregations(aggs)) => Some(
                 AggregationSegmentCollectors::TantivyAggregationSegmentCollector(
                     AggregationSegmentCollector::from_agg_req_and_reader(
@@ -791,6 +1050,37 @@ impl Collector for QuickwitCollector {
                     )?,
                 ),
             ),
+            Some(QuickwitAggregations::NestedAggregations(aggs)) => Some(
+                AggregationSegmentCollectors::NestedAggregationsSegmentCollector(Box::new(
+                    NestedAggregationsSegmentCollector::from_agg_req_and_reader(
+                        aggs,
+                        segment_reader,
+                        segment_ord,
+                        &self.aggregation_limits,
+                    )?,
+                )),
+            ),
+            Some(QuickwitAggregations::CompositeAggregations(aggs)) => Some(
+                AggregationSegmentCollectors::CompositeAggregationsSegmentCollector(Box::new(
+                    CompositeAggregationsSegmentCollector::from_agg_req_and_reader(
+                        aggs,
+                        segment_reader,
+                        segment_ord,
+                        &self.aggregation_limits,
+                    )?,
+                )),
+            ),
+            Some(QuickwitAggregations::TermFrequencyAggregations(aggs)) => Some(
+                AggregationSegmentCollectors::TermFrequencyAggregationsSegmentCollector(Box::new(
+                    TermFrequencyAggregationsSegmentCollector::from_agg_req_and_reader(
+                        aggs,
+                        &self.background_queries,
+                        segment_reader,
+                        segment_ord,
+                        &self.aggregation_limits,
+                    )?,
+                )),
+            ),
             None => None,
         };
         let score_extractor = get_score_extractor(&self.sort_by, segment_reader)?;
@@ -904,6 +1194,69 @@ fn merge_intermediate_aggregation_result<'a>(
                 None
             }
         }
+        Some(QuickwitAggregations::NestedAggregations(_)) => {
+            let fruits: Vec<NestedIntermediateAggregationResults> =
+                intermediate_aggregation_results
+                    .map(|intermediate_aggregation_result| {
+                        postcard::from_bytes(intermediate_aggregation_result).map_err(map_error)
+                    })
+                    .collect::<Result<_, _>>()?;
+
+            let mut fruit_iter = fruits.into_iter();
+            if let Some(first_fruit) = fruit_iter.next() {
+                let mut merged_fruit = first_fruit;
+                for fruit in fruit_iter {
+                    merged_fruit.merge_fruits(fruit)?;
+                }
+                let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
+
+                Some(serialized)
+            } else {
+                None
+            }
+        }
+        Some(QuickwitAggregations::CompositeAggregations(aggregations)) => {
+            let fruits: Vec<CompositeIntermediateAggregationResults> =
+                intermediate_aggregation_results
+                    .map(|intermediate_aggregation_result| {
+                        postcard::from_bytes(intermediate_aggregation_result).map_err(map_error)
+                    })
+                    .collect::<Result<_, _>>()?;
+
+            let mut fruit_iter = fruits.into_iter();
+            if let Some(first_fruit) = fruit_iter.next() {
+                let mut merged_fruit = first_fruit;
+                for fruit in fruit_iter {
+                    merged_fruit.merge_fruits(fruit, aggregations)?;
+                }
+                let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
+
+                Some(serialized)
+            } else {
+                None
+            }
+        }
+        Some(QuickwitAggregations::TermFrequencyAggregations(aggregations)) => {
+            let fruits: Vec<TermFrequencyIntermediateAggregationResults> =
+                intermediate_aggregation_results
+                    .map(|intermediate_aggregation_result| {
+                        postcard::from_bytes(intermediate_aggregation_result).map_err(map_error)
+                    })
+                    .collect::<Result<_, _>>()?;
+
+            let mut fruit_iter = fruits.into_iter();
+            if let Some(first_fruit) = fruit_iter.next() {
+                let mut merged_fruit = first_fruit;
+                for fruit in fruit_iter {
+                    merged_fruit.merge_fruits(fruit, aggregations)?;
+                }
+                let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
+
+                Some(serialized)
+            } else {
+                None
+            }
+        }
         None => None,
     };
 
@@ -991,12 +1344,30 @@ fn top_k_partial_hits(
     top_k_hits.finalize()
 }
 
+/// Parses the runtime mappings of a search request.
+pub(crate) fn runtime_mappings_from_request(
+    search_request: &SearchRequest,
+) -> crate::Result<RuntimeMappings> {
+    let Some(runtime_mappings_json) = &search_request.runtime_mappings else {
+        return Ok(RuntimeMappings::new());
+    };
+    quickwit_query::runtime_field::parse_runtime_mappings(runtime_mappings_json)
+        .map_err(|err| SearchError::InvalidArgument(format!("{err:#}")))
+}
+
 pub(crate) fn sort_by_from_request(search_request: &SearchRequest) -> SortByPair {
+    // Runtime mappings are validated by the root.
+    let runtime_mappings = runtime_mappings_from_request(search_request).unwrap_or_default();
     let to_sort_by_component = |field_name: &str, order| {
         if field_name == "_score" {
             SortByComponent::Score { order }
         } else if field_name == "_shard_doc" || field_name == "_doc" {
             SortByComponent::DocId { order }
+        } else if let Some(runtime_field) = runtime_mappings.get(field_name) {
+            SortByComponent::RuntimeField {
+                runtime_field: runtime_field.clone(),
+                order,
+            }
         } else {
             SortByComponent::FastField {
                 field_name: field_name.to_string(),
@@ -1040,6 +1411,7 @@ pub(crate) fn make_collector_for_split(
         None => None,
     };
     let sort_by = sort_by_from_request(search_request);
+    let runtime_mappings = runtime_mappings_from_request(search_request)?;
     Ok(QuickwitCollector {
         split_id,
         start_offset: search_request.start_offset as usize,
@@ -1047,7 +1419,9 @@ pub(crate) fn make_collector_for_split(
         sort_by,
         aggregation,
         aggregation_limits,
+        runtime_mappings,
         search_after: search_request.search_after.clone(),
+        background_queries: BackgroundQueries::default(),
     })
 }
 
@@ -1061,6 +1435,7 @@ pub(crate) fn make_merge_collector(
         None => None,
     };
     let sort_by = sort_by_from_request(search_request);
+    let runtime_mappings = runtime_mappings_from_request(search_request)?;
     Ok(QuickwitCollector {
         split_id: SplitId::default(),
         start_offset: search_request.start_offset as usize,
@@ -1068,7 +1443,9 @@ pub(crate) fn make_merge_collector(
         sort_by,
         aggregation,
         aggregation_limits: aggregation_limits.clone(),
+        runtime_mappings,
         search_after: search_request.search_after.clone(),
+        background_queries: BackgroundQueries::default(),
     })
 }
 
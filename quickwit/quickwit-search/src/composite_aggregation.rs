@@ -0,0 +1,1372 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `composite` aggregations.
+//!
+//! A `composite` aggregation buckets the documents by the combination of the values of its
+//! sources, and returns the first `size` buckets in the order of their keys, starting after the
+//! `after` key. Paginating with the returned `after_key` goes through all the buckets, however
+//! many there are.
+//!
+//! Each segment only keeps the `size` smallest keys greater than the `after` key. A key evicted
+//! from a segment is greater than all the kept keys, and is never admitted again, so the document
+//! counts of the kept keys are exact. The merge of the leaf results keeps the `size` smallest
+//! keys as well, which are the `size` smallest keys of the whole index, with exact counts.
+//!
+//! `composite` aggregations are only supported at the top level of the aggregation request.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::net::Ipv6Addr;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
+use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use tantivy::aggregation::{AggregationLimitsGuard, AggregationSegmentCollector};
+use tantivy::collector::SegmentCollector;
+use tantivy::columnar::{ColumnType, DynamicColumn};
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+use crate::nested_aggregation::{SingleBucketIntermediateResult, SingleBucketSegmentCollector};
+
+const DEFAULT_COMPOSITE_SIZE: usize = 10;
+
+// Each segment keeps up to `size` buckets, each with its own sub-aggregation collectors.
+const MAX_COMPOSITE_SIZE: usize = 10_000;
+
+// Estimated memory used by the collector of a sub-aggregation of a bucket, charged to the
+// aggregation memory limit along with the key of the bucket. The sub-aggregation collectors charge
+// the memory of their own buckets as they grow.
+const SUB_AGGREGATION_COLLECTOR_NUM_BYTES: u64 = 256;
+
+const MINUTE_MILLIS: i64 = 60_000;
+const HOUR_MILLIS: i64 = 60 * MINUTE_MILLIS;
+const DAY_MILLIS: i64 = 24 * HOUR_MILLIS;
+
+/// Aggregation request containing at least one top-level `composite` aggregation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeAggregations {
+    /// Aggregations other than the `composite` aggregations.
+    pub root: Aggregations,
+    /// `composite` aggregations, by name.
+    pub composite: BTreeMap<String, CompositeAggregation>,
+}
+
+/// Aggregation bucketing the documents by the combination of the values of its sources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeAggregation {
+    /// Sources of the values of the bucket keys, in the order of the keys.
+    pub sources: Vec<CompositeSource>,
+    /// Maximum number of buckets returned.
+    pub size: usize,
+    /// Only the buckets whose key is greater than this key are returned.
+    pub after: Option<Vec<CompositeKeyValue>>,
+    /// Sub-aggregations, run on the documents of each bucket.
+    pub aggs: Aggregations,
+}
+
+/// Source of the values of one component of the bucket keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeSource {
+    /// Name of the source in the bucket keys.
+    pub name: String,
+    /// Field the values are read from.
+    pub field: String,
+    pub kind: CompositeSourceKind,
+    pub order: CompositeSourceOrder,
+    /// If true, the documents without value for the field are put in a bucket with a `null` key
+    /// component. Otherwise, they are ignored.
+    pub missing_bucket: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompositeSourceKind {
+    /// The values of the field.
+    Terms,
+    /// The start of the date interval containing the value of the field, in milliseconds.
+    DateHistogram(DateInterval),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateInterval {
+    /// Fixed interval, in milliseconds.
+    Fixed(i64),
+    Calendar(CalendarUnit),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarUnit {
+    Minute,
+    Hour,
+    Day,
+    /// Weeks start on Monday.
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeSourceOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Value of one component of a bucket key.
+///
+/// Values of different types are ordered as follows: `null`, booleans, numbers, strings. Numbers
+/// are compared by value, whatever their type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CompositeKeyValue {
+    Null,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+impl CompositeKeyValue {
+    fn type_rank(&self) -> u8 {
+        match self {
+            CompositeKeyValue::Null => 0,
+            CompositeKeyValue::Bool(_) => 1,
+            CompositeKeyValue::I64(_) | CompositeKeyValue::U64(_) | CompositeKeyValue::F64(_) => 2,
+            CompositeKeyValue::Str(_) => 3,
+        }
+    }
+
+    fn from_json(value_json: &JsonValue) -> anyhow::Result<Self> {
+        let value = match value_json {
+            JsonValue::Null => CompositeKeyValue::Null,
+            JsonValue::Bool(value) => CompositeKeyValue::Bool(*value),
+            JsonValue::Number(number) => {
+                if let Some(value) = number.as_u64() {
+                    CompositeKeyValue::U64(value)
+                } else if let Some(value) = number.as_i64() {
+                    CompositeKeyValue::I64(value)
+                } else if let Some(value) = number.as_f64() {
+                    CompositeKeyValue::F64(value)
+                } else {
+                    bail!("unsupported number `{number}`");
+                }
+            }
+            JsonValue::String(value) => CompositeKeyValue::Str(value.clone()),
+            JsonValue::Array(_) | JsonValue::Object(_) => {
+                bail!("key values must be strings, numbers, booleans or null")
+            }
+        };
+        Ok(value)
+    }
+
+    fn to_json(&self) -> JsonValue {
+        match self {
+            CompositeKeyValue::Null => JsonValue::Null,
+            CompositeKeyValue::Bool(value) => JsonValue::Bool(*value),
+            CompositeKeyValue::I64(value) => JsonValue::from(*value),
+            CompositeKeyValue::U64(value) => JsonValue::from(*value),
+            CompositeKeyValue::F64(value) => JsonValue::from(*value),
+            CompositeKeyValue::Str(value) => JsonValue::String(value.clone()),
+        }
+    }
+}
+
+impl Ord for CompositeKeyValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (CompositeKeyValue::Bool(left), CompositeKeyValue::Bool(right)) => left.cmp(right),
+            (CompositeKeyValue::I64(left), CompositeKeyValue::I64(right)) => left.cmp(right),
+            (CompositeKeyValue::U64(left), CompositeKeyValue::U64(right)) => left.cmp(right),
+            (CompositeKeyValue::F64(left), CompositeKeyValue::F64(right)) => left.total_cmp(right),
+            (CompositeKeyValue::I64(left), CompositeKeyValue::U64(right)) => {
+                if *left < 0 {
+                    Ordering::Less
+                } else {
+                    (*left as u64).cmp(right)
+                }
+            }
+            (CompositeKeyValue::U64(_), CompositeKeyValue::I64(_)) => other.cmp(self).reverse(),
+            (CompositeKeyValue::I64(left), CompositeKeyValue::F64(right)) => {
+                (*left as f64).total_cmp(right)
+            }
+            (CompositeKeyValue::U64(left), CompositeKeyValue::F64(right)) => {
+                (*left as f64).total_cmp(right)
+            }
+            (CompositeKeyValue::F64(_), CompositeKeyValue::I64(_) | CompositeKeyValue::U64(_)) => {
+                other.cmp(self).reverse()
+            }
+            (CompositeKeyValue::Str(left), CompositeKeyValue::Str(right)) => left.cmp(right),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+impl PartialOrd for CompositeKeyValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for CompositeKeyValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CompositeKeyValue {}
+
+/// Component of a bucket key, ordered according to the order of its source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct KeyComponent {
+    value: CompositeKeyValue,
+    descending: bool,
+}
+
+impl Ord for KeyComponent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = self.value.cmp(&other.value);
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl PartialOrd for KeyComponent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+type CompositeKey = Vec<KeyComponent>;
+
+fn default_composite_size() -> usize {
+    DEFAULT_COMPOSITE_SIZE
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompositeParams {
+    sources: Vec<BTreeMap<String, CompositeSourceJson>>,
+    #[serde(default = "default_composite_size")]
+    size: usize,
+    #[serde(default)]
+    after: Option<JsonMap<String, JsonValue>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CompositeSourceJson {
+    Terms(TermsSourceJson),
+    DateHistogram(DateHistogramSourceJson),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TermsSourceJson {
+    field: String,
+    #[serde(default)]
+    order: CompositeSourceOrder,
+    #[serde(default)]
+    missing_bucket: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DateHistogramSourceJson {
+    field: String,
+    #[serde(default)]
+    fixed_interval: Option<String>,
+    #[serde(default)]
+    calendar_interval: Option<String>,
+    #[serde(default)]
+    order: CompositeSourceOrder,
+    #[serde(default)]
+    missing_bucket: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompositeAggregationJson {
+    composite: CompositeParams,
+    #[serde(default, alias = "aggregations")]
+    aggs: Aggregations,
+}
+
+impl TryFrom<JsonMap<String, JsonValue>> for CompositeAggregations {
+    type Error = anyhow::Error;
+
+    fn try_from(aggregations_json: JsonMap<String, JsonValue>) -> anyhow::Result<Self> {
+        let mut root_json = JsonMap::new();
+        let mut composite = BTreeMap::new();
+        for (name, aggregation_json) in aggregations_json {
+            if aggregation_json.get("composite").is_none() {
+                root_json.insert(name, aggregation_json);
+                continue;
+            }
+            let composite_aggregation = parse_composite_aggregation(aggregation_json)
+                .with_context(|| format!("invalid composite aggregation `{name}`"))?;
+            composite.insert(name, composite_aggregation);
+        }
+        if composite.is_empty() {
+            bail!("no composite aggregation");
+        }
+        let root = serde_json::from_value(JsonValue::Object(root_json))?;
+        Ok(CompositeAggregations { root, composite })
+    }
+}
+
+fn parse_composite_aggregation(
+    aggregation_json: JsonValue,
+) -> anyhow::Result<CompositeAggregation> {
+    let CompositeAggregationJson { composite, aggs } = serde_json::from_value(aggregation_json)?;
+    if composite.size == 0 || composite.size > MAX_COMPOSITE_SIZE {
+        bail!("`size` must be between 1 and {MAX_COMPOSITE_SIZE}");
+    }
+    if composite.sources.is_empty() {
+        bail!("`sources` must not be empty");
+    }
+    let mut sources: Vec<CompositeSource> = Vec::with_capacity(composite.sources.len());
+    for source_json in composite.sources {
+        if source_json.len() != 1 {
+            bail!("each source must be an object with a single named source");
+        }
+        let (name, source_json) = source_json.into_iter().next().expect("source should exist");
+        if sources.iter().any(|source| source.name == name) {
+            bail!("duplicate source `{name}`");
+        }
+        let source = parse_composite_source(name.clone(), source_json)
+            .with_context(|| format!("invalid source `{name}`"))?;
+        sources.push(source);
+    }
+    let after = if let Some(after_json) = composite.after {
+        let mut after = Vec::with_capacity(sources.len());
+        for source in &sources {
+            let value_json = after_json
+                .get(&source.name)
+                .with_context(|| format!("`after` is missing source `{}`", source.name))?;
+            let value = CompositeKeyValue::from_json(value_json)
+                .with_context(|| format!("invalid `after` value for source `{}`", source.name))?;
+            after.push(value);
+        }
+        if after_json.len() != sources.len() {
+            bail!("`after` must only contain the sources of the aggregation");
+        }
+        Some(after)
+    } else {
+        None
+    };
+    Ok(CompositeAggregation {
+        sources,
+        size: composite.size,
+        after,
+        aggs,
+    })
+}
+
+fn parse_composite_source(
+    name: String,
+    source_json: CompositeSourceJson,
+) -> anyhow::Result<CompositeSource> {
+    let source = match source_json {
+        CompositeSourceJson::Terms(terms) => CompositeSource {
+            name,
+            field: terms.field,
+            kind: CompositeSourceKind::Terms,
+            order: terms.order,
+            missing_bucket: terms.missing_bucket,
+        },
+        CompositeSourceJson::DateHistogram(date_histogram) => {
+            let interval = match (
+                date_histogram.fixed_interval,
+                date_histogram.calendar_interval,
+            ) {
+                (Some(fixed_interval), None) => {
+                    DateInterval::Fixed(parse_fixed_interval(&fixed_interval)?)
+                }
+                (None, Some(calendar_interval)) => {
+                    DateInterval::Calendar(parse_calendar_interval(&calendar_interval)?)
+                }
+                _ => bail!("exactly one of `fixed_interval` and `calendar_interval` is required"),
+            };
+            CompositeSource {
+                name,
+                field: date_histogram.field,
+                kind: CompositeSourceKind::DateHistogram(interval),
+                order: date_histogram.order,
+                missing_bucket: date_histogram.missing_bucket,
+            }
+        }
+    };
+    Ok(source)
+}
+
+/// Parses an interval such as `30s` or `1d` into milliseconds.
+fn parse_fixed_interval(interval: &str) -> anyhow::Result<i64> {
+    let unit_start = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .with_context(|| format!("missing unit in fixed interval `{interval}`"))?;
+    let (value_str, unit) = interval.split_at(unit_start);
+    let value: i64 = value_str
+        .parse()
+        .with_context(|| format!("invalid fixed interval `{interval}`"))?;
+    let unit_millis = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => MINUTE_MILLIS,
+        "h" => HOUR_MILLIS,
+        "d" => DAY_MILLIS,
+        _ => bail!("unsupported unit in fixed interval `{interval}`"),
+    };
+    let interval_millis = value.saturating_mul(unit_millis);
+    if interval_millis <= 0 {
+        bail!("fixed interval `{interval}` must be positive");
+    }
+    Ok(interval_millis)
+}
+
+fn parse_calendar_interval(interval: &str) -> anyhow::Result<CalendarUnit> {
+    let calendar_unit = match interval {
+        "minute" | "1m" => CalendarUnit::Minute,
+        "hour" | "1h" => CalendarUnit::Hour,
+        "day" | "1d" => CalendarUnit::Day,
+        "week" | "1w" => CalendarUnit::Week,
+        "month" | "1M" => CalendarUnit::Month,
+        "quarter" | "1q" => CalendarUnit::Quarter,
+        "year" | "1y" => CalendarUnit::Year,
+        _ => bail!("unsupported calendar interval `{interval}`"),
+    };
+    Ok(calendar_unit)
+}
+
+impl<'de> Deserialize<'de> for CompositeAggregations {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let aggregations_json = JsonMap::<String, JsonValue>::deserialize(deserializer)?;
+        CompositeAggregations::try_from(aggregations_json).map_err(serde::de::Error::custom)
+    }
+}
+
+impl CompositeAggregations {
+    /// Returns the list of fast fields that should be loaded for the aggregation.
+    pub fn fast_field_names(&self) -> HashSet<String> {
+        let mut fast_field_names = get_fast_field_names(&self.root);
+        for composite_aggregation in self.composite.values() {
+            fast_field_names.extend(get_fast_field_names(&composite_aggregation.aggs));
+            for source in &composite_aggregation.sources {
+                fast_field_names.insert(source.field.clone());
+            }
+        }
+        fast_field_names
+    }
+}
+
+impl DateInterval {
+    /// Returns the start of the interval containing `timestamp_millis`.
+    fn bucket_start(&self, timestamp_millis: i64) -> i64 {
+        match self {
+            DateInterval::Fixed(interval_millis) => {
+                timestamp_millis - timestamp_millis.rem_euclid(*interval_millis)
+            }
+            DateInterval::Calendar(CalendarUnit::Minute) => {
+                timestamp_millis - timestamp_millis.rem_euclid(MINUTE_MILLIS)
+            }
+            DateInterval::Calendar(CalendarUnit::Hour) => {
+                timestamp_millis - timestamp_millis.rem_euclid(HOUR_MILLIS)
+            }
+            DateInterval::Calendar(CalendarUnit::Day) => {
+                timestamp_millis - timestamp_millis.rem_euclid(DAY_MILLIS)
+            }
+            DateInterval::Calendar(CalendarUnit::Week) => {
+                let days = timestamp_millis.div_euclid(DAY_MILLIS);
+                // 1970-01-01 was a Thursday.
+                let days_since_monday = (days + 3).rem_euclid(7);
+                (days - days_since_monday) * DAY_MILLIS
+            }
+            DateInterval::Calendar(calendar_unit) => {
+                let (year, month) = civil_from_days(timestamp_millis.div_euclid(DAY_MILLIS));
+                let start_month = match calendar_unit {
+                    CalendarUnit::Month => month,
+                    CalendarUnit::Quarter => (month - 1) / 3 * 3 + 1,
+                    _ => 1,
+                };
+                days_from_civil(year, start_month, 1) * DAY_MILLIS
+            }
+        }
+    }
+}
+
+/// Returns the number of days since 1970-01-01 of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the year and month of the date `days` days after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month)
+}
+
+/// Intermediate results of a [`CompositeAggregation`]: the smallest keys and their buckets.
+#[derive(Default, Serialize, Deserialize)]
+struct CompositeIntermediateResult {
+    buckets: BTreeMap<CompositeKey, SingleBucketIntermediateResult>,
+}
+
+impl CompositeIntermediateResult {
+    fn merge_fruits(
+        &mut self,
+        other: CompositeIntermediateResult,
+        size: usize,
+    ) -> tantivy::Result<()> {
+        for (key, other_bucket) in other.buckets {
+            if let Some(bucket) = self.buckets.get_mut(&key) {
+                bucket.merge_fruits(other_bucket)?;
+            } else {
+                self.buckets.insert(key, other_bucket);
+            }
+        }
+        while self.buckets.len() > size {
+            self.buckets.pop_last();
+        }
+        Ok(())
+    }
+
+    fn into_final_result(
+        self,
+        composite_aggregation: CompositeAggregation,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<JsonValue> {
+        let key_json = |key: &CompositeKey| -> JsonValue {
+            let key_json: JsonMap<String, JsonValue> = composite_aggregation
+                .sources
+                .iter()
+                .zip(key)
+                .map(|(source, component)| (source.name.clone(), component.value.to_json()))
+                .collect();
+            JsonValue::Object(key_json)
+        };
+        let mut buckets = self.buckets;
+        while buckets.len() > composite_aggregation.size {
+            buckets.pop_last();
+        }
+        let after_key_json_opt = buckets.last_key_value().map(|(key, _)| key_json(key));
+        let mut buckets_json = Vec::with_capacity(buckets.len());
+        for (key, bucket) in buckets {
+            let mut bucket_json =
+                bucket.into_final_result(composite_aggregation.aggs.clone(), limits)?;
+            bucket_json.insert("key".to_string(), key_json(&key));
+            buckets_json.push(JsonValue::Object(bucket_json));
+        }
+        let mut result_json = JsonMap::new();
+        if let Some(after_key_json) = after_key_json_opt {
+            result_json.insert("after_key".to_string(), after_key_json);
+        }
+        result_json.insert("buckets".to_string(), JsonValue::Array(buckets_json));
+        Ok(JsonValue::Object(result_json))
+    }
+}
+
+/// Intermediate results of [`CompositeAggregations`].
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct CompositeIntermediateAggregationResults {
+    root: IntermediateAggregationResults,
+    composite: BTreeMap<String, CompositeIntermediateResult>,
+}
+
+impl CompositeIntermediateAggregationResults {
+    pub fn merge_fruits(
+        &mut self,
+        other: CompositeIntermediateAggregationResults,
+        aggregations: &CompositeAggregations,
+    ) -> tantivy::Result<()> {
+        self.root.merge_fruits(other.root)?;
+        for (name, other_result) in other.composite {
+            let size = aggregations
+                .composite
+                .get(&name)
+                .map(|composite_aggregation| composite_aggregation.size)
+                .unwrap_or(MAX_COMPOSITE_SIZE);
+            if let Some(result) = self.composite.get_mut(&name) {
+                result.merge_fruits(other_result, size)?;
+            } else {
+                self.composite.insert(name, other_result);
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the intermediate results into the final JSON results.
+    pub fn into_final_result(
+        mut self,
+        aggregations: CompositeAggregations,
+        limits: AggregationLimitsGuard,
+    ) -> tantivy::Result<JsonValue> {
+        let final_root_result = self
+            .root
+            .into_final_result(aggregations.root, limits.clone())?;
+        let JsonValue::Object(mut final_result_json) =
+            serde_json::to_value(final_root_result).map_err(map_error)?
+        else {
+            return Err(TantivyError::InternalError(
+                "aggregation results should serialize to a JSON object".to_string(),
+            ));
+        };
+        for (name, composite_aggregation) in aggregations.composite {
+            let composite_result = self.composite.remove(&name).unwrap_or_default();
+            let composite_result_json =
+                composite_result.into_final_result(composite_aggregation, &limits)?;
+            final_result_json.insert(name, composite_result_json);
+        }
+        Ok(JsonValue::Object(final_result_json))
+    }
+}
+
+fn map_error(err: serde_json::Error) -> TantivyError {
+    TantivyError::InternalError(format!("failed to serialize aggregation results: {err}"))
+}
+
+struct SegmentCompositeSource {
+    kind: CompositeSourceKind,
+    descending: bool,
+    missing_bucket: bool,
+    columns: Vec<DynamicColumn>,
+}
+
+impl SegmentCompositeSource {
+    fn open(source: &CompositeSource, segment_reader: &SegmentReader) -> tantivy::Result<Self> {
+        let mut columns = Vec::new();
+        for column_handle in segment_reader
+            .fast_fields()
+            .dynamic_column_handles(&source.field)?
+        {
+            let column_type = column_handle.column_type();
+
+            if matches!(source.kind, CompositeSourceKind::DateHistogram(_))
+                && column_type != ColumnType::DateTime
+            {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "`date_histogram` source `{}` requires a datetime field, but field `{}` has \
+                     {column_type:?} values",
+                    source.name, source.field
+                )));
+            }
+            columns.push(column_handle.open()?);
+        }
+        Ok(SegmentCompositeSource {
+            kind: source.kind,
+            descending: source.order == CompositeSourceOrder::Desc,
+            missing_bucket: source.missing_bucket,
+            columns,
+        })
+    }
+
+    /// Writes the sorted and deduplicated key components of the document to `components`.
+    fn collect_components(
+        &self,
+        doc: DocId,
+        components: &mut Vec<KeyComponent>,
+        text_buffer: &mut String,
+    ) -> tantivy::Result<()> {
+        components.clear();
+        let mut push_value = |value: CompositeKeyValue| {
+            components.push(KeyComponent {
+                value,
+                descending: self.descending,
+            });
+        };
+        for column in &self.columns {
+            match (self.kind, column) {
+                (CompositeSourceKind::Terms, DynamicColumn::Bool(column)) => {
+                    column
+                        .values_for_doc(doc)
+                        .for_each(|value| push_value(CompositeKeyValue::Bool(value)));
+                }
+                (CompositeSourceKind::Terms, DynamicColumn::I64(column)) => {
+                    column
+                        .values_for_doc(doc)
+                        .for_each(|value| push_value(CompositeKeyValue::I64(value)));
+                }
+                (CompositeSourceKind::Terms, DynamicColumn::U64(column)) => {
+                    column
+                        .values_for_doc(doc)
+                        .for_each(|value| push_value(CompositeKeyValue::U64(value)));
+                }
+                (CompositeSourceKind::Terms, DynamicColumn::F64(column)) => {
+                    column
+                        .values_for_doc(doc)
+                        .for_each(|value| push_value(CompositeKeyValue::F64(value)));
+                }
+                (CompositeSourceKind::Terms, DynamicColumn::DateTime(column)) => {
+                    column.values_for_doc(doc).for_each(|value| {
+                        let timestamp_millis = value.into_timestamp_nanos().div_euclid(1_000_000);
+                        push_value(CompositeKeyValue::I64(timestamp_millis))
+                    });
+                }
+                (CompositeSourceKind::Terms, DynamicColumn::IpAddr(column)) => {
+                    column.values_for_doc(doc).for_each(|value| {
+                        push_value(CompositeKeyValue::Str(ip_addr_to_string(value)))
+                    });
+                }
+                (CompositeSourceKind::Terms, DynamicColumn::Str(column)) => {
+                    for term_ord in column.term_ords(doc) {
+                        text_buffer.clear();
+                        column.ord_to_str(term_ord, text_buffer)?;
+                        push_value(CompositeKeyValue::Str(text_buffer.clone()));
+                    }
+                }
+                (CompositeSourceKind::DateHistogram(interval), DynamicColumn::DateTime(column)) => {
+                    column.values_for_doc(doc).for_each(|value| {
+                        let timestamp_millis = value.into_timestamp_nanos().div_euclid(1_000_000);
+                        push_value(CompositeKeyValue::I64(
+                            interval.bucket_start(timestamp_millis),
+                        ))
+                    });
+                }
+                // Bytes columns are ignored by terms sources. Date histogram sources only open
+                // datetime columns.
+                _ => {}
+            }
+        }
+        if components.is_empty() && self.missing_bucket {
+            components.push(KeyComponent {
+                value: CompositeKeyValue::Null,
+                descending: self.descending,
+            });
+        }
+        components.sort();
+        components.dedup();
+        Ok(())
+    }
+}
+
+/// Returns the number of aggregations of the request, including the nested sub-aggregations.
+fn num_aggregations(aggregations: &Aggregations) -> u64 {
+    aggregations
+        .values()
+        .map(|aggregation| 1 + num_aggregations(&aggregation.sub_aggregation))
+        .sum()
+}
+
+fn ip_addr_to_string(ip_addr: Ipv6Addr) -> String {
+    if let Some(ipv4_addr) = ip_addr.to_ipv4_mapped() {
+        ipv4_addr.to_string()
+    } else {
+        ip_addr.to_string()
+    }
+}
+
+struct CompositeSegmentCollector {
+    name: String,
+    aggregation: CompositeAggregation,
+    after_key_opt: Option<CompositeKey>,
+    sources: Vec<SegmentCompositeSource>,
+    buckets: BTreeMap<CompositeKey, SingleBucketSegmentCollector>,
+    segment_reader: SegmentReader,
+    segment_ord: SegmentOrdinal,
+    limits: AggregationLimitsGuard,
+    /// Estimated memory used by a bucket, besides the values of its key.
+    bucket_num_bytes: u64,
+    components_per_source: Vec<Vec<KeyComponent>>,
+    key_buffer: CompositeKey,
+}
+
+impl CompositeSegmentCollector {
+    fn new(
+        name: String,
+        aggregation: &CompositeAggregation,
+        segment_reader: &SegmentReader,
+        segment_ord: SegmentOrdinal,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<Self> {
+        let sources = aggregation
+            .sources
+            .iter()
+            .map(|source| SegmentCompositeSource::open(source, segment_reader))
+            .collect::<tantivy::Result<Vec<_>>>()?;
+        let after_key_opt = aggregation.after.as_ref().map(|after| {
+            after
+                .iter()
+                .zip(&sources)
+                .map(|(value, source)| KeyComponent {
+                    value: value.clone(),
+                    descending: source.descending,
+                })
+                .collect()
+        });
+        let bucket_num_bytes = (std::mem::size_of::<SingleBucketSegmentCollector>()
+            + sources.len() * std::mem::size_of::<KeyComponent>())
+            as u64
+            + num_aggregations(&aggregation.aggs) * SUB_AGGREGATION_COLLECTOR_NUM_BYTES;
+        Ok(CompositeSegmentCollector {
+            name,
+            aggregation: aggregation.clone(),
+            after_key_opt,
+            bucket_num_bytes,
+            components_per_source: vec![Vec::new(); sources.len()],
+            sources,
+            buckets: BTreeMap::new(),
+            segment_reader: segment_reader.clone(),
+            segment_ord,
+            limits: limits.clone(),
+            key_buffer: Vec::new(),
+        })
+    }
+
+    fn collect(
+        &mut self,
+        doc: DocId,
+        score: Score,
+        text_buffer: &mut String,
+    ) -> tantivy::Result<()> {
+        for (source, components) in self.sources.iter().zip(&mut self.components_per_source) {
+            source.collect_components(doc, components, text_buffer)?;
+            if components.is_empty() {
+                return Ok(());
+            }
+        }
+        // The document belongs to the buckets of all the combinations of its source values.
+        let mut component_indexes = vec![0; self.sources.len()];
+        loop {
+            self.key_buffer.clear();
+            for (components, &component_index) in
+                self.components_per_source.iter().zip(&component_indexes)
+            {
+                self.key_buffer.push(components[component_index].clone());
+            }
+            self.collect_key(doc, score)?;
+
+            let mut source_index = component_indexes.len();
+            loop {
+                if source_index == 0 {
+                    return Ok(());
+                }
+                source_index -= 1;
+                component_indexes[source_index] += 1;
+                if component_indexes[source_index] < self.components_per_source[source_index].len()
+                {
+                    break;
+                }
+                component_indexes[source_index] = 0;
+            }
+        }
+    }
+
+    fn collect_key(&mut self, doc: DocId, score: Score) -> tantivy::Result<()> {
+        let key = self.key_buffer.as_slice();
+        if let Some(after_key) = &self.after_key_opt {
+            if key <= after_key.as_slice() {
+                return Ok(());
+            }
+        }
+        if let Some(bucket) = self.buckets.get_mut(key) {
+            bucket.collect(doc, score);
+            return Ok(());
+        }
+        if self.buckets.len() >= self.aggregation.size {
+            let Some((largest_key, _)) = self.buckets.last_key_value() else {
+                return Ok(());
+            };
+            if key > largest_key.as_slice() {
+                return Ok(());
+            }
+            self.buckets.pop_last();
+        } else {
+            // The bucket replacing an evicted one is not charged again.
+            let key_values_num_bytes: usize = key
+                .iter()
+                .map(|component| match &component.value {
+                    CompositeKeyValue::Str(value) => value.len(),
+                    _ => 0,
+                })
+                .sum();
+            self.limits
+                .add_memory_consumed(self.bucket_num_bytes + key_values_num_bytes as u64)?;
+        }
+        let mut bucket = SingleBucketSegmentCollector::new(
+            &self.aggregation.aggs,
+            &self.segment_reader,
+            self.segment_ord,
+            &self.limits,
+        )?;
+        bucket.collect(doc, score);
+        self.buckets.insert(key.to_vec(), bucket);
+        Ok(())
+    }
+
+    fn harvest(self) -> tantivy::Result<CompositeIntermediateResult> {
+        let mut buckets = BTreeMap::new();
+        for (key, bucket) in self.buckets {
+            buckets.insert(key, bucket.harvest()?);
+        }
+        Ok(CompositeIntermediateResult { buckets })
+    }
+}
+
+/// Segment collector of [`CompositeAggregations`].
+pub(crate) struct CompositeAggregationsSegmentCollector {
+    root: AggregationSegmentCollector,
+    composite: Vec<CompositeSegmentCollector>,
+    // Reading the values of a document may fail: the first error is returned on harvest.
+    error_opt: Option<TantivyError>,
+    text_buffer: String,
+}
+
+impl CompositeAggregationsSegmentCollector {
+    pub fn from_agg_req_and_reader(
+        aggregations: &CompositeAggregations,
+        segment_reader: &SegmentReader,
+        segment_ord: SegmentOrdinal,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<Self> {
+        let root = AggregationSegmentCollector::from_agg_req_and_reader(
+            &aggregations.root,
+            segment_reader,
+            segment_ord,
+            limits,
+        )?;
+        let mut composite = Vec::with_capacity(aggregations.composite.len());
+        for (name, composite_aggregation) in &aggregations.composite {
+            let composite_collector = CompositeSegmentCollector::new(
+                name.clone(),
+                composite_aggregation,
+                segment_reader,
+                segment_ord,
+                limits,
+            )?;
+            composite.push(composite_collector);
+        }
+        Ok(CompositeAggregationsSegmentCollector {
+            root,
+            composite,
+            error_opt: None,
+            text_buffer: String::new(),
+        })
+    }
+
+    pub fn collect_block(&mut self, docs: &[DocId]) {
+        for &doc in docs {
+            self.collect(doc, 0.0);
+        }
+    }
+
+    pub fn collect(&mut self, doc: DocId, score: Score) {
+        self.root.collect(doc, score);
+        if self.error_opt.is_some() {
+            return;
+        }
+        for composite_collector in &mut self.composite {
+            if let Err(error) = composite_collector.collect(doc, score, &mut self.text_buffer) {
+                self.error_opt = Some(error);
+                return;
+            }
+        }
+    }
+
+    pub fn harvest(self) -> tantivy::Result<CompositeIntermediateAggregationResults> {
+        if let Some(error) = self.error_opt {
+            return Err(error);
+        }
+        let root = self.root.harvest()?;
+        let mut composite = BTreeMap::new();
+        for composite_collector in self.composite {
+            let name = composite_collector.name.clone();
+            composite.insert(name, composite_collector.harvest()?);
+        }
+        Ok(CompositeIntermediateAggregationResults { root, composite })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tantivy::schema::{DateOptions, DateTimePrecision, Schema, FAST, STRING};
+    use tantivy::{doc, DateTime, Index};
+
+    use super::*;
+    use crate::collector::QuickwitAggregations;
+
+    #[test]
+    fn test_composite_aggregations_serde() {
+        let aggregations: QuickwitAggregations = serde_json::from_value(json!({
+            "num_services": {"value_count": {"field": "service"}},
+            "pairs": {
+                "composite": {
+                    "size": 2,
+                    "sources": [
+                        {"service": {"terms": {"field": "service"}}},
+                        {"day": {"date_histogram": {"field": "timestamp", "calendar_interval": "1d", "order": "desc"}}}
+                    ],
+                    "after": {"service": "api", "day": 1700000000000u64}
+                },
+                "aggs": {"total_bytes": {"sum": {"field": "bytes"}}}
+            }
+        }))
+        .unwrap();
+        let QuickwitAggregations::CompositeAggregations(composite_aggregations) = aggregations
+        else {
+            panic!("expected composite aggregations");
+        };
+        assert_eq!(composite_aggregations.root.len(), 1);
+        let composite_aggregation = &composite_aggregations.composite["pairs"];
+        assert_eq!(composite_aggregation.size, 2);
+        assert_eq!(composite_aggregation.sources.len(), 2);
+        assert_eq!(composite_aggregation.sources[0].name, "service");
+        assert_eq!(
+            composite_aggregation.sources[1].kind,
+            CompositeSourceKind::DateHistogram(DateInterval::Calendar(CalendarUnit::Day))
+        );
+        assert_eq!(
+            composite_aggregation.sources[1].order,
+            CompositeSourceOrder::Desc
+        );
+        assert_eq!(
+            composite_aggregation.after,
+            Some(vec![
+                CompositeKeyValue::Str("api".to_string()),
+                CompositeKeyValue::U64(1_700_000_000_000),
+            ])
+        );
+        assert_eq!(
+            composite_aggregations.fast_field_names(),
+            HashSet::from_iter([
+                "service".to_string(),
+                "timestamp".to_string(),
+                "bytes".to_string()
+            ])
+        );
+
+        let error = CompositeAggregations::try_from(
+            json!({
+                "pairs": {
+                    "composite": {
+                        "sources": [{"service": {"terms": {"field": "service"}}}],
+                        "after": {"host": "h1"}
+                    }
+                }
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid composite aggregation `pairs`: `after` is missing source `service`"
+        );
+
+        let error = CompositeAggregations::try_from(
+            json!({
+                "pairs": {
+                    "composite": {
+                        "sources": [{"day": {"date_histogram": {"field": "timestamp", "fixed_interval": "1x"}}}]
+                    }
+                }
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid composite aggregation `pairs`: invalid source `day`: unsupported unit in \
+             fixed interval `1x`"
+        );
+
+        let error = CompositeAggregations::try_from(
+            json!({
+                "pairs": {
+                    "composite": {
+                        "sources": [{"service": {"terms": {"field": "service"}}}],
+                        "size": 10_001
+                    }
+                }
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid composite aggregation `pairs`: `size` must be between 1 and 10000"
+        );
+    }
+
+    #[test]
+    fn test_composite_key_value_ordering() {
+        assert!(CompositeKeyValue::Null < CompositeKeyValue::Bool(false));
+        assert!(CompositeKeyValue::I64(-1) < CompositeKeyValue::U64(0));
+        assert_eq!(CompositeKeyValue::I64(3), CompositeKeyValue::U64(3));
+        assert!(CompositeKeyValue::U64(3) < CompositeKeyValue::F64(3.5));
+        assert!(CompositeKeyValue::F64(1e30) < CompositeKeyValue::Str(String::new()));
+    }
+
+    #[test]
+    fn test_date_interval_bucket_start() {
+        // 2024-05-17T13:45:30.123Z, a Friday.
+        let timestamp_millis = 1_715_953_530_123;
+        let bucket_start = |interval| DateInterval::bucket_start(&interval, timestamp_millis);
+        assert_eq!(bucket_start(DateInterval::Fixed(1_000)), 1_715_953_530_000);
+        assert_eq!(
+            bucket_start(DateInterval::Calendar(CalendarUnit::Hour)),
+            1_715_950_800_000
+        );
+        // 2024-05-13, a Monday.
+        assert_eq!(
+            bucket_start(DateInterval::Calendar(CalendarUnit::Week)),
+            1_715_558_400_000
+        );
+        // 2024-05-01.
+        assert_eq!(
+            bucket_start(DateInterval::Calendar(CalendarUnit::Month)),
+            1_714_521_600_000
+        );
+        // 2024-04-01.
+        assert_eq!(
+            bucket_start(DateInterval::Calendar(CalendarUnit::Quarter)),
+            1_711_929_600_000
+        );
+        // 2024-01-01.
+        assert_eq!(
+            bucket_start(DateInterval::Calendar(CalendarUnit::Year)),
+            1_704_067_200_000
+        );
+        // 1969-12-01.
+        assert_eq!(
+            DateInterval::Calendar(CalendarUnit::Month).bucket_start(-1),
+            -2_678_400_000
+        );
+    }
+
+    fn collect_composite_aggregations(
+        index: &Index,
+        aggregations: &CompositeAggregations,
+        docs: &[DocId],
+    ) -> CompositeIntermediateAggregationResults {
+        let searcher = index.reader().unwrap().searcher();
+        let limits = AggregationLimitsGuard::new(None, None);
+        let mut collector = CompositeAggregationsSegmentCollector::from_agg_req_and_reader(
+            aggregations,
+            searcher.segment_reader(0),
+            0,
+            &limits,
+        )
+        .unwrap();
+        collector.collect_block(docs);
+        collector.harvest().unwrap()
+    }
+
+    #[test]
+    fn test_composite_aggregations_collector() {
+        let mut schema_builder = Schema::builder();
+        let service = schema_builder.add_text_field("service", STRING | FAST);
+        let host = schema_builder.add_text_field("host", STRING | FAST);
+        let date_options = DateOptions::default()
+            .set_fast()
+            .set_precision(DateTimePrecision::Milliseconds);
+        let timestamp = schema_builder.add_date_field("timestamp", date_options);
+        let bytes = schema_builder.add_u64_field("bytes", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        let day = |num_days: i64| DateTime::from_timestamp_millis(num_days * DAY_MILLIS + 1_000);
+        index_writer
+            .add_document(doc!(service=>"db", host=>"h1", timestamp=>day(1), bytes=>1u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(service=>"api", host=>"h2", timestamp=>day(0), bytes=>2u64))
+            .unwrap();
+        index_writer
+            .add_document(
+                doc!(service=>"api", host=>"h1", host=>"h2", timestamp=>day(0), bytes=>4u64),
+            )
+            .unwrap();
+        index_writer
+            .add_document(doc!(service=>"web", timestamp=>day(2), bytes=>8u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(service=>"api", host=>"h1", timestamp=>day(1), bytes=>16u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let composite_aggregations = |after: JsonValue| -> CompositeAggregations {
+            let mut composite_json = json!({
+                "size": 2,
+                "sources": [
+                    {"service": {"terms": {"field": "service"}}},
+                    {"host": {"terms": {"field": "host", "order": "desc", "missing_bucket": true}}}
+                ]
+            });
+            if !after.is_null() {
+                composite_json["after"] = after;
+            }
+            serde_json::from_value(json!({
+                "num_docs": {"value_count": {"field": "bytes"}},
+                "pairs": {
+                    "composite": composite_json,
+                    "aggs": {"total_bytes": {"sum": {"field": "bytes"}}}
+                },
+                "days": {
+                    "composite": {
+                        "sources": [
+                            {"day": {"date_histogram": {"field": "timestamp", "fixed_interval": "1d"}}}
+                        ]
+                    }
+                }
+            }))
+            .unwrap()
+        };
+        let limits = AggregationLimitsGuard::new(None, None);
+
+        // Collects the documents in two batches, as if they were in two splits.
+        let aggregations = composite_aggregations(JsonValue::Null);
+        let mut intermediate_results =
+            collect_composite_aggregations(&index, &aggregations, &[0, 2, 3]);
+        intermediate_results
+            .merge_fruits(
+                collect_composite_aggregations(&index, &aggregations, &[1, 4]),
+                &aggregations,
+            )
+            .unwrap();
+        let final_results = intermediate_results
+            .into_final_result(aggregations, limits.clone())
+            .unwrap();
+        assert_eq!(
+            final_results,
+            json!({
+                "num_docs": {"value": 5.0},
+                "pairs": {
+                    "after_key": {"service": "api", "host": "h1"},
+                    "buckets": [
+                        {"key": {"service": "api", "host": "h2"}, "doc_count": 2, "total_bytes": {"value": 6.0}},
+                        {"key": {"service": "api", "host": "h1"}, "doc_count": 2, "total_bytes": {"value": 20.0}},
+                    ]
+                },
+                "days": {
+                    "after_key": {"day": 2 * DAY_MILLIS},
+                    "buckets": [
+                        {"key": {"day": 0}, "doc_count": 2},
+                        {"key": {"day": DAY_MILLIS}, "doc_count": 2},
+                        {"key": {"day": 2 * DAY_MILLIS}, "doc_count": 1},
+                    ]
+                }
+            })
+        );
+
+        let aggregations = composite_aggregations(json!({"service": "api", "host": "h1"}));
+        let final_results = collect_composite_aggregations(&index, &aggregations, &[0, 1, 2, 3, 4])
+            .into_final_result(aggregations, limits.clone())
+            .unwrap();
+        assert_eq!(
+            final_results["pairs"],
+            json!({
+                "after_key": {"service": "web", "host": null},
+                "buckets": [
+                    {"key": {"service": "db", "host": "h1"}, "doc_count": 1, "total_bytes": {"value": 1.0}},
+                    {"key": {"service": "web", "host": null}, "doc_count": 1, "total_bytes": {"value": 8.0}},
+                ]
+            })
+        );
+
+        let aggregations = composite_aggregations(json!({"service": "web", "host": null}));
+        let final_results = collect_composite_aggregations(&index, &aggregations, &[0, 1, 2, 3, 4])
+            .into_final_result(aggregations, limits)
+            .unwrap();
+        assert_eq!(final_results["pairs"], json!({"buckets": []}));
+    }
+
+    #[test]
+    fn test_composite_aggregations_collector_errors() {
+        let mut schema_builder = Schema::builder();
+        let service = schema_builder.add_text_field("service", STRING | FAST);
+        let bytes = schema_builder.add_u64_field("bytes", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for service_id in 0..20u64 {
+            index_writer
+                .add_document(doc!(service=>format!("service-{service_id}"), bytes=>service_id))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let aggregations: CompositeAggregations = serde_json::from_value(json!({
+            "days": {
+                "composite": {
+                    "sources": [
+                        {"day": {"date_histogram": {"field": "bytes", "fixed_interval": "1d"}}}
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let error = CompositeAggregationsSegmentCollector::from_agg_req_and_reader(
+            &aggregations,
+            searcher.segment_reader(0),
+            0,
+            &AggregationLimitsGuard::new(None, None),
+        )
+        .err()
+        .unwrap();
+        assert!(error
+            .to_string()
+            .contains("`date_histogram` source `day` requires a datetime field"));
+
+        // The buckets and their sub-aggregation collectors are charged to the memory limit.
+        let aggregations: CompositeAggregations = serde_json::from_value(json!({
+            "services": {
+                "composite": {
+                    "size": 100,
+                    "sources": [{"service": {"terms": {"field": "service"}}}]
+                },
+                "aggs": {"total_bytes": {"sum": {"field": "bytes"}}}
+            }
+        }))
+        .unwrap();
+        let mut collector = CompositeAggregationsSegmentCollector::from_agg_req_and_reader(
+            &aggregations,
+            searcher.segment_reader(0),
+            0,
+            &AggregationLimitsGuard::new(Some(1_000), None),
+        )
+        .unwrap();
+        collector.collect_block(&(0..20).collect::<Vec<DocId>>());
+        let error = collector.harvest().err().unwrap();
+        assert!(error.to_string().contains("memory limit"));
+    }
+}
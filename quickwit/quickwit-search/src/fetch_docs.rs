@@ -18,16 +18,18 @@ use std::sync::Arc;
 use anyhow::{Context, Ok};
 use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
-use quickwit_doc_mapper::DocMapper;
+use quickwit_directories::{doc_from_columns, ColumnarDocStoreManifest};
+use quickwit_doc_mapper::{DocMapper, DYNAMIC_FIELD_NAME};
 use quickwit_proto::search::{
     FetchDocsResponse, PartialHit, SnippetRequest, SplitIdAndFooterOffsets,
 };
 use quickwit_storage::Storage;
 use tantivy::query::Query;
 use tantivy::schema::document::CompactDocValue;
-use tantivy::schema::{Document as DocumentTrait, Field, TantivyDocument, Value};
+use tantivy::schema::{Document as DocumentTrait, Field, Schema, TantivyDocument, Value};
 use tantivy::snippet::SnippetGenerator;
-use tantivy::{ReloadPolicy, Score, Searcher, Term};
+use tantivy::store::StoreReader;
+use tantivy::{Directory, DocId, ReloadPolicy, Score, Searcher, Term};
 use tracing::{error, Instrument};
 
 use crate::leaf::open_index_with_caches;
@@ -45,6 +47,7 @@ async fn fetch_docs_to_map(
     splits: &[SplitIdAndFooterOffsets],
     doc_mapper: Arc<DocMapper>,
     snippet_request_opt: Option<&SnippetRequest>,
+    fetch_fields: &[String],
 ) -> anyhow::Result<HashMap<GlobalDocAddress, Document>> {
     let mut split_fetch_docs_futures = Vec::new();
 
@@ -72,6 +75,7 @@ async fn fetch_docs_to_map(
             split_and_offset,
             doc_mapper.clone(),
             snippet_request_opt,
+            fetch_fields,
         ));
     }
 
@@ -105,6 +109,9 @@ async fn fetch_docs_to_map(
 /// This function takes a list of partial hits (possibly from different splits)
 /// and the storage associated to an index, fetches the document from
 /// the split document stores, and returns the full hits.
+///
+/// If `fetch_fields` is not empty, only the matching top-level fields are read from the splits
+/// written with the columnar doc store layout.
 pub async fn fetch_docs(
     searcher_context: Arc<SearcherContext>,
     partial_hits: Vec<PartialHit>,
@@ -112,6 +119,7 @@ pub async fn fetch_docs(
     splits: &[SplitIdAndFooterOffsets],
     doc_mapper: Arc<DocMapper>,
     snippet_request_opt: Option<&SnippetRequest>,
+    fetch_fields: &[String],
 ) -> anyhow::Result<FetchDocsResponse> {
     let global_doc_addrs: Vec<GlobalDocAddress> = partial_hits
         .iter()
@@ -125,6 +133,7 @@ pub async fn fetch_docs(
         splits,
         doc_mapper,
         snippet_request_opt,
+        fetch_fields,
     )
     .await?;
 
@@ -165,11 +174,12 @@ async fn fetch_docs_in_split(
     split: &SplitIdAndFooterOffsets,
     doc_mapper: Arc<DocMapper>,
     snippet_request_opt: Option<&SnippetRequest>,
+    fetch_fields: &[String],
 ) -> anyhow::Result<Vec<(GlobalDocAddress, Document)>> {
     global_doc_addrs.sort_by_key(|doc| doc.doc_addr);
     // Opens the index without the ephemeral unbounded cache, this cache is indeed not useful
     // when fetching docs as we will fetch them only once.
-    let (mut index, _) = open_index_with_caches(
+    let (mut index, hot_directory) = open_index_with_caches(
         &searcher_context,
         index_storage,
         split,
@@ -196,16 +206,39 @@ async fn fetch_docs_in_split(
     } else {
         None
     };
+    // The splits written with the columnar layout have an empty row doc store. Snippets are
+    // extracted from the whole documents.
+    let column_fetch_fields: &[String] = if snippet_request_opt.is_some() {
+        &[]
+    } else {
+        fetch_fields
+    };
+    let column_readers_opt = open_column_readers(&hot_directory, &searcher, column_fetch_fields)
+        .await
+        .context("open-column-readers")?
+        .map(Arc::new);
 
     let doc_futures = global_doc_addrs.into_iter().map(|global_doc_addr| {
         let moved_searcher = searcher.clone();
         let moved_doc_mapper = doc_mapper.clone();
         let fields_snippet_generator_opt_clone = fields_snippet_generator_opt.clone();
+        let column_readers_opt_clone = column_readers_opt.clone();
         async move {
-            let doc: TantivyDocument = moved_searcher
-                .doc_async(global_doc_addr.doc_addr)
+            let doc: TantivyDocument = if let Some(column_readers) = column_readers_opt_clone {
+                let segment_ord = global_doc_addr.doc_addr.segment_ord as usize;
+                fetch_doc_from_columns(
+                    &column_readers[segment_ord],
+                    global_doc_addr.doc_addr.doc_id,
+                    moved_searcher.schema(),
+                )
                 .await
-                .context("searcher-doc-async")?;
+                .context("fetch-doc-from-columns")?
+            } else {
+                moved_searcher
+                    .doc_async(global_doc_addr.doc_addr)
+                    .await
+                    .context("searcher-doc-async")?
+            };
 
             let named_field_doc = doc.to_named_doc(moved_searcher.schema());
             let content_json = convert_document_to_json_string(named_field_doc, &moved_doc_mapper)?;
@@ -257,6 +290,90 @@ async fn fetch_docs_in_split(
         .await
 }
 
+/// Opens, for each segment of the split, the column stores of the stored fields matching
+/// `fetch_fields`, or of all the stored fields if `fetch_fields` is empty. Returns `None` if the
+/// split was not written with the columnar doc store layout.
+async fn open_column_readers(
+    directory: &dyn Directory,
+    searcher: &Searcher,
+    fetch_fields: &[String],
+) -> anyhow::Result<Option<Vec<Vec<StoreReader>>>> {
+    let Some(manifest) = ColumnarDocStoreManifest::open_async(directory).await? else {
+        return Ok(None);
+    };
+    let column_field_names: Vec<&str> = searcher
+        .schema()
+        .fields()
+        .filter(|(_, field_entry)| field_entry.is_stored())
+        .map(|(_, field_entry)| field_entry.name())
+        .filter(|field_name| {
+            fetch_fields.is_empty() || is_column_required(field_name, fetch_fields)
+        })
+        .collect();
+    let mut column_readers_per_segment = Vec::with_capacity(searcher.segment_readers().len());
+
+    for segment_reader in searcher.segment_readers() {
+        let segment_id = segment_reader.segment_id();
+        let Some(column_readers) = manifest.open_column_readers(
+            directory,
+            segment_id,
+            &column_field_names,
+            NUM_CONCURRENT_REQUESTS,
+        )?
+        else {
+            anyhow::bail!(
+                "columnar doc store of segment `{}` is incomplete",
+                segment_id.uuid_string()
+            );
+        };
+        column_readers_per_segment.push(column_readers);
+    }
+    Ok(Some(column_readers_per_segment))
+}
+
+/// Returns true if the column of a stored field is needed to fetch the fields matching
+/// `fetch_fields`. Dynamic fields may hold any top-level field and are always read.
+fn is_column_required(field_name: &str, fetch_fields: &[String]) -> bool {
+    if field_name == DYNAMIC_FIELD_NAME {
+        return true;
+    }
+    let top_level_field_name = top_level_field_name(field_name);
+    fetch_fields
+        .iter()
+        .any(|fetch_field| fetch_field.starts_with(&top_level_field_name))
+}
+
+/// Returns the name of the top-level field of a field path, with the escaped dots unescaped.
+fn top_level_field_name(field_name: &str) -> String {
+    let mut top_level_field_name = String::with_capacity(field_name.len());
+    let mut chars = field_name.chars();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => top_level_field_name.extend(chars.next()),
+            '.' => break,
+            _ => top_level_field_name.push(character),
+        }
+    }
+    top_level_field_name
+}
+
+/// Reassembles the stored fields of a document from its column stores.
+async fn fetch_doc_from_columns(
+    column_readers: &[StoreReader],
+    doc_id: DocId,
+    schema: &Schema,
+) -> anyhow::Result<TantivyDocument> {
+    let mut column_docs = Vec::with_capacity(column_readers.len());
+
+    for column_reader in column_readers {
+        let column_doc: TantivyDocument = column_reader.get_async(doc_id).await?;
+        column_docs.push(column_doc);
+    }
+    let doc = doc_from_columns(schema, column_docs)?;
+    Ok(doc)
+}
+
 // A struct to hold the snippet generators associated to
 // the snippet fields from a search request.
 #[derive(Clone)]
@@ -350,3 +467,26 @@ async fn create_snippet_generator(
         SNIPPET_MAX_NUM_CHARS,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_level_field_name() {
+        assert_eq!(top_level_field_name("body"), "body");
+        assert_eq!(top_level_field_name("attributes.host"), "attributes");
+        assert_eq!(top_level_field_name(r"k8s\.pod.name"), "k8s.pod");
+        assert_eq!(top_level_field_name(""), "");
+    }
+
+    #[test]
+    fn test_is_column_required() {
+        let fetch_fields = vec!["attributes.host".to_string(), "body".to_string()];
+        assert!(is_column_required("body", &fetch_fields));
+        assert!(is_column_required("attributes", &fetch_fields));
+        assert!(is_column_required("attributes.host", &fetch_fields));
+        assert!(is_column_required(DYNAMIC_FIELD_NAME, &fetch_fields));
+        assert!(!is_column_required("severity_text", &fetch_fields));
+    }
+}
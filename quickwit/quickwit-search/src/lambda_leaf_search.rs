@@ -0,0 +1,760 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use prost::Message;
+use quickwit_config::LambdaLeafSearchConfig;
+use quickwit_proto::search::{
+    LeafRequestRef, LeafSearchRequest, LeafSearchResponse, SplitSearchError,
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::cluster_client::merge_intermediate_aggregation;
+use crate::{merge_resource_stats_it, SearchError, SearchServiceClient};
+
+/// Invokes a function running leaf searches outside of the cluster, typically an AWS Lambda
+/// function. The payloads are the JSON serialized [`LeafSearchInvocation`] and
+/// [`LeafSearchInvocationResponse`].
+#[async_trait]
+pub trait LeafSearchInvoker: Send + Sync + 'static {
+    /// Name of the invoked function.
+    fn function_name(&self) -> &str;
+
+    /// Invokes the function synchronously and returns its response payload.
+    async fn invoke(&self, payload: Vec<u8>) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Payload of a leaf search invocation: the leaf search request, encoded with protobuf then
+/// base64, as the payloads of Lambda functions must be JSON documents.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeafSearchInvocation {
+    leaf_search_request: String,
+}
+
+impl LeafSearchInvocation {
+    /// Creates the invocation payload of a leaf search request.
+    pub fn from_leaf_search_request(leaf_search_request: &LeafSearchRequest) -> Self {
+        Self {
+            leaf_search_request: BASE64_STANDARD.encode(leaf_search_request.encode_to_vec()),
+        }
+    }
+
+    /// Decodes the leaf search request of the invocation.
+    pub fn into_leaf_search_request(self) -> crate::Result<LeafSearchRequest> {
+        let leaf_search_request_bytes =
+            BASE64_STANDARD
+                .decode(self.leaf_search_request)
+                .map_err(|error| {
+                    SearchError::InvalidArgument(format!("invalid leaf search invocation: {error}"))
+                })?;
+        LeafSearchRequest::decode(&leaf_search_request_bytes[..]).map_err(|error| {
+            SearchError::InvalidArgument(format!("invalid leaf search invocation: {error}"))
+        })
+    }
+}
+
+/// Maximum size of the response payload of a synchronous AWS Lambda invocation.
+const MAX_INVOCATION_RESPONSE_NUM_BYTES: usize = 6 * 1024 * 1024;
+
+/// Number of bytes reserved for the JSON envelope of the encoded leaf search response.
+const INVOCATION_RESPONSE_ENVELOPE_NUM_BYTES: usize = 1024;
+
+/// Response payload of a leaf search invocation, encoded like [`LeafSearchInvocation`].
+///
+/// Responses exceeding the payload size limit of AWS Lambda are replaced by their size, so that
+/// the caller can split the work across several invocations.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeafSearchInvocationResponse {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    leaf_search_response: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oversized_response_num_bytes: Option<u64>,
+}
+
+impl LeafSearchInvocationResponse {
+    /// Creates the response payload of a leaf search response.
+    pub fn from_leaf_search_response(leaf_search_response: &LeafSearchResponse) -> Self {
+        let encoded_response = BASE64_STANDARD.encode(leaf_search_response.encode_to_vec());
+
+        if encoded_response.len() + INVOCATION_RESPONSE_ENVELOPE_NUM_BYTES
+            > MAX_INVOCATION_RESPONSE_NUM_BYTES
+        {
+            return Self {
+                leaf_search_response: None,
+                oversized_response_num_bytes: Some(encoded_response.len() as u64),
+            };
+        }
+        Self {
+            leaf_search_response: Some(encoded_response),
+            oversized_response_num_bytes: None,
+        }
+    }
+
+    /// Returns the size of the encoded leaf search response if it exceeded the payload size
+    /// limit and was left out.
+    pub fn oversized_response_num_bytes(&self) -> Option<u64> {
+        self.oversized_response_num_bytes
+    }
+
+    /// Decodes the leaf search response of the invocation. Returns `None` if the response
+    /// exceeded the payload size limit.
+    pub fn into_leaf_search_response(self) -> crate::Result<Option<LeafSearchResponse>> {
+        let Some(leaf_search_response) = self.leaf_search_response else {
+            return Ok(None);
+        };
+        let leaf_search_response_bytes =
+            BASE64_STANDARD
+                .decode(leaf_search_response)
+                .map_err(|error| {
+                    SearchError::Internal(format!(
+                        "invalid leaf search invocation response: {error}"
+                    ))
+                })?;
+        LeafSearchResponse::decode(&leaf_search_response_bytes[..])
+            .map(Some)
+            .map_err(|error| {
+                SearchError::Internal(format!("invalid leaf search invocation response: {error}"))
+            })
+    }
+}
+
+/// Transport of the [`SearchServiceClient`] sending leaf search requests to a
+/// [`LeafSearchInvoker`]. Leaf search requests are split into concurrent invocations of at most
+/// `max_splits_per_invocation` splits.
+#[derive(Clone)]
+pub(crate) struct LambdaLeafSearchClient {
+    invoker: Arc<dyn LeafSearchInvoker>,
+    max_splits_per_invocation: usize,
+}
+
+impl LambdaLeafSearchClient {
+    pub fn function_name(&self) -> &str {
+        self.invoker.function_name()
+    }
+
+    pub async fn leaf_search(
+        &self,
+        leaf_search_request: LeafSearchRequest,
+    ) -> crate::Result<LeafSearchResponse> {
+        let invocation_futures =
+            split_leaf_search_request(leaf_search_request, self.max_splits_per_invocation)
+                .into_iter()
+                .map(|leaf_search_request| self.invoke_leaf_search(leaf_search_request));
+        let leaf_search_responses = join_all(invocation_futures).await;
+        merge_leaf_search_responses(leaf_search_responses)
+    }
+
+    /// Invokes a leaf search. Failed invocations are reported as retryable split failures, so
+    /// that the cluster client retries them on the searchers of the cluster.
+    ///
+    /// If the response exceeds the payload size limit of the function, the splits are searched
+    /// again in two halves. A single split whose response is too large fails with a
+    /// non-retryable error.
+    fn invoke_leaf_search(
+        &self,
+        leaf_search_request: LeafSearchRequest,
+    ) -> BoxFuture<'_, LeafSearchResponse> {
+        async move {
+            let error = match self.try_invoke_leaf_search(&leaf_search_request).await {
+                Ok(Some(leaf_search_response)) => return leaf_search_response,
+                Ok(None) => {
+                    let num_splits = num_splits(&leaf_search_request);
+
+                    if num_splits > 1 {
+                        return self
+                            .invoke_leaf_search_in_halves(leaf_search_request, num_splits)
+                            .await;
+                    }
+                    let error = format!(
+                        "leaf search response exceeds the {MAX_INVOCATION_RESPONSE_NUM_BYTES} \
+                         bytes payload limit of function `{}`",
+                        self.function_name()
+                    );
+                    warn!(function_name=%self.function_name(), "{error}");
+                    return failed_leaf_search_response(&leaf_search_request, error, false);
+                }
+                Err(error) => error,
+            };
+            warn!(
+                function_name=%self.function_name(),
+                error=%error,
+                "leaf search invocation failed"
+            );
+            failed_leaf_search_response(&leaf_search_request, error.to_string(), true)
+        }
+        .boxed()
+    }
+
+    async fn invoke_leaf_search_in_halves(
+        &self,
+        leaf_search_request: LeafSearchRequest,
+        num_splits: usize,
+    ) -> LeafSearchResponse {
+        let invocation_futures =
+            split_leaf_search_request(leaf_search_request.clone(), num_splits.div_ceil(2))
+                .into_iter()
+                .map(|leaf_search_request| self.invoke_leaf_search(leaf_search_request));
+        let leaf_search_responses = join_all(invocation_futures).await;
+
+        match merge_leaf_search_responses(leaf_search_responses) {
+            Ok(leaf_search_response) => leaf_search_response,
+            Err(error) => {
+                failed_leaf_search_response(&leaf_search_request, error.to_string(), false)
+            }
+        }
+    }
+
+    async fn try_invoke_leaf_search(
+        &self,
+        leaf_search_request: &LeafSearchRequest,
+    ) -> crate::Result<Option<LeafSearchResponse>> {
+        let invocation = LeafSearchInvocation::from_leaf_search_request(leaf_search_request);
+        let payload = serde_json::to_vec(&invocation)
+            .expect("serializing a leaf search invocation should never fail");
+        let response_payload = self
+            .invoker
+            .invoke(payload)
+            .await
+            .map_err(|error| SearchError::Internal(format!("{error:#}")))?;
+        let invocation_response: LeafSearchInvocationResponse =
+            serde_json::from_slice(&response_payload).map_err(|error| {
+                SearchError::Internal(format!("invalid leaf search invocation response: {error}"))
+            })?;
+        invocation_response.into_leaf_search_response()
+    }
+}
+
+fn num_splits(leaf_search_request: &LeafSearchRequest) -> usize {
+    leaf_search_request
+        .leaf_requests
+        .iter()
+        .map(|leaf_request| leaf_request.split_offsets.len())
+        .sum()
+}
+
+/// Returns a response reporting all of the splits of the request as failed.
+fn failed_leaf_search_response(
+    leaf_search_request: &LeafSearchRequest,
+    error: String,
+    retryable_error: bool,
+) -> LeafSearchResponse {
+    let failed_splits: Vec<SplitSearchError> = leaf_search_request
+        .leaf_requests
+        .iter()
+        .flat_map(|leaf_request| leaf_request.split_offsets.iter())
+        .map(|split_offsets| SplitSearchError {
+            error: error.clone(),
+            split_id: split_offsets.split_id.clone(),
+            retryable_error,
+        })
+        .collect();
+    LeafSearchResponse {
+        num_attempted_splits: failed_splits.len() as u64,
+        failed_splits,
+        ..Default::default()
+    }
+}
+
+/// Splits a leaf search request into requests targeting at most `max_splits_per_request` splits.
+fn split_leaf_search_request(
+    leaf_search_request: LeafSearchRequest,
+    max_splits_per_request: usize,
+) -> Vec<LeafSearchRequest> {
+    let mut leaf_search_requests = Vec::new();
+    let mut leaf_requests: Vec<LeafRequestRef> = Vec::new();
+    let mut num_splits = 0;
+
+    let new_leaf_search_request = |leaf_requests: Vec<LeafRequestRef>| LeafSearchRequest {
+        search_request: leaf_search_request.search_request.clone(),
+        leaf_requests,
+        doc_mappers: leaf_search_request.doc_mappers.clone(),
+        index_uris: leaf_search_request.index_uris.clone(),
+    };
+    for leaf_request in &leaf_search_request.leaf_requests {
+        let mut remaining_split_offsets = &leaf_request.split_offsets[..];
+
+        while !remaining_split_offsets.is_empty() {
+            let num_splits_to_take = remaining_split_offsets
+                .len()
+                .min(max_splits_per_request - num_splits);
+            let (split_offsets, tail) = remaining_split_offsets.split_at(num_splits_to_take);
+            leaf_requests.push(LeafRequestRef {
+                doc_mapper_ord: leaf_request.doc_mapper_ord,
+                index_uri_ord: leaf_request.index_uri_ord,
+                split_offsets: split_offsets.to_vec(),
+            });
+            num_splits += num_splits_to_take;
+            remaining_split_offsets = tail;
+
+            if num_splits == max_splits_per_request {
+                let leaf_requests = std::mem::take(&mut leaf_requests);
+                leaf_search_requests.push(new_leaf_search_request(leaf_requests));
+                num_splits = 0;
+            }
+        }
+    }
+    if !leaf_requests.is_empty() {
+        leaf_search_requests.push(new_leaf_search_request(leaf_requests));
+    }
+    leaf_search_requests
+}
+
+/// Merges the responses of leaf searches targeting disjoint sets of splits. Partial hits are
+/// concatenated: like for the responses of different searchers, the root search keeps the top
+/// hits only.
+fn merge_leaf_search_responses(
+    leaf_search_responses: Vec<LeafSearchResponse>,
+) -> crate::Result<LeafSearchResponse> {
+    let resource_stats = merge_resource_stats_it(
+        leaf_search_responses
+            .iter()
+            .map(|leaf_search_response| &leaf_search_response.resource_stats),
+    );
+    let mut merged_response = LeafSearchResponse {
+        resource_stats,
+        ..Default::default()
+    };
+    for leaf_search_response in leaf_search_responses {
+        merged_response.num_hits += leaf_search_response.num_hits;
+        merged_response
+            .partial_hits
+            .extend(leaf_search_response.partial_hits);
+        merged_response
+            .failed_splits
+            .extend(leaf_search_response.failed_splits);
+        merged_response.num_attempted_splits += leaf_search_response.num_attempted_splits;
+        merged_response.num_successful_splits += leaf_search_response.num_successful_splits;
+
+        merged_response.intermediate_aggregation_result = match (
+            merged_response.intermediate_aggregation_result,
+            leaf_search_response.intermediate_aggregation_result,
+        ) {
+            (Some(left_agg_bytes), Some(right_agg_bytes)) => Some(merge_intermediate_aggregation(
+                &left_agg_bytes,
+                &right_agg_bytes,
+            )?),
+            (left_agg_bytes_opt, right_agg_bytes_opt) => left_agg_bytes_opt.or(right_agg_bytes_opt),
+        };
+    }
+    Ok(merged_response)
+}
+
+/// Creates a [`SearchServiceClient`] sending leaf search requests to a [`LeafSearchInvoker`].
+/// The client does not support any other RPC.
+pub fn create_search_client_from_leaf_search_invoker(
+    invoker: Arc<dyn LeafSearchInvoker>,
+    max_splits_per_invocation: usize,
+) -> SearchServiceClient {
+    let lambda_client = LambdaLeafSearchClient {
+        invoker,
+        max_splits_per_invocation: max_splits_per_invocation.max(1),
+    };
+    // The client does not target any node of the cluster, so retries of failed splits can be
+    // placed on any searcher.
+    let unspecified_addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
+    SearchServiceClient::from_lambda_client(lambda_client, unspecified_addr)
+}
+
+/// Creates the [`SearchServiceClient`] invoking the AWS Lambda function configured in
+/// `lambda_leaf_search_config`.
+#[cfg(feature = "lambda")]
+pub async fn create_lambda_leaf_search_client(
+    lambda_leaf_search_config: &LambdaLeafSearchConfig,
+) -> anyhow::Result<SearchServiceClient> {
+    let invoker = aws_lambda::AwsLambdaLeafSearchInvoker::new(
+        lambda_leaf_search_config.function_name.clone(),
+    )
+    .await;
+    Ok(create_search_client_from_leaf_search_invoker(
+        Arc::new(invoker),
+        lambda_leaf_search_config.max_splits_per_invocation.get() as usize,
+    ))
+}
+
+/// Creates the [`SearchServiceClient`] invoking the AWS Lambda function configured in
+/// `lambda_leaf_search_config`.
+#[cfg(not(feature = "lambda"))]
+pub async fn create_lambda_leaf_search_client(
+    _lambda_leaf_search_config: &LambdaLeafSearchConfig,
+) -> anyhow::Result<SearchServiceClient> {
+    anyhow::bail!(
+        "Quickwit was compiled without the `lambda` feature, leaf searches cannot be offloaded to \
+         AWS Lambda"
+    )
+}
+
+#[cfg(feature = "lambda")]
+mod aws_lambda {
+    use anyhow::bail;
+    use async_trait::async_trait;
+    use aws_sdk_lambda::primitives::Blob;
+    use aws_sdk_lambda::types::InvocationType;
+    use aws_sdk_lambda::Client as LambdaClient;
+
+    use super::LeafSearchInvoker;
+
+    /// Invokes the leaf search AWS Lambda function.
+    pub(super) struct AwsLambdaLeafSearchInvoker {
+        lambda_client: LambdaClient,
+        function_name: String,
+    }
+
+    impl AwsLambdaLeafSearchInvoker {
+        pub async fn new(function_name: String) -> Self {
+            let aws_config = quickwit_aws::get_aws_config().await;
+            let lambda_client = LambdaClient::new(aws_config);
+            Self {
+                lambda_client,
+                function_name,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LeafSearchInvoker for AwsLambdaLeafSearchInvoker {
+        fn function_name(&self) -> &str {
+            &self.function_name
+        }
+
+        async fn invoke(&self, payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+            let invoke_output = self
+                .lambda_client
+                .invoke()
+                .function_name(&self.function_name)
+                .invocation_type(InvocationType::RequestResponse)
+                .payload(Blob::new(payload))
+                .send()
+                .await?;
+            let response_payload = invoke_output
+                .payload
+                .map(Blob::into_inner)
+                .unwrap_or_default();
+
+            if let Some(function_error) = invoke_output.function_error {
+                bail!(
+                    "function `{}` failed with {function_error} error: {}",
+                    self.function_name,
+                    String::from_utf8_lossy(&response_payload)
+                );
+            }
+            Ok(response_payload)
+        }
+    }
+}
+
+/// Leaf search invoker running the leaf searches with a local search service, like the leaf
+/// search Lambda function does.
+#[cfg(any(test, feature = "testsuite"))]
+pub struct LocalLeafSearchInvoker {
+    search_service: Arc<dyn crate::SearchService>,
+}
+
+#[cfg(any(test, feature = "testsuite"))]
+impl LocalLeafSearchInvoker {
+    /// Creates a new invoker running the leaf searches with `search_service`.
+    pub fn new(search_service: Arc<dyn crate::SearchService>) -> Self {
+        Self { search_service }
+    }
+}
+
+#[cfg(any(test, feature = "testsuite"))]
+#[async_trait]
+impl LeafSearchInvoker for LocalLeafSearchInvoker {
+    fn function_name(&self) -> &str {
+        "local"
+    }
+
+    async fn invoke(&self, payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let invocation: LeafSearchInvocation = serde_json::from_slice(&payload)?;
+        let leaf_search_request = invocation.into_leaf_search_request()?;
+        let leaf_search_response = self.search_service.leaf_search(leaf_search_request).await?;
+        let invocation_response =
+            LeafSearchInvocationResponse::from_leaf_search_response(&leaf_search_response);
+        Ok(serde_json::to_vec(&invocation_response)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use quickwit_proto::search::{PartialHit, SearchRequest, SplitIdAndFooterOffsets};
+
+    use super::*;
+    use crate::MockSearchService;
+
+    fn split_offsets(split_id: &str) -> SplitIdAndFooterOffsets {
+        SplitIdAndFooterOffsets {
+            split_id: split_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn leaf_search_request(split_ids_per_index: &[&[&str]]) -> LeafSearchRequest {
+        let leaf_requests = split_ids_per_index
+            .iter()
+            .enumerate()
+            .map(|(ord, split_ids)| LeafRequestRef {
+                doc_mapper_ord: ord as u32,
+                index_uri_ord: ord as u32,
+                split_offsets: split_ids
+                    .iter()
+                    .map(|split_id| split_offsets(split_id))
+                    .collect(),
+            })
+            .collect();
+        LeafSearchRequest {
+            search_request: Some(SearchRequest {
+                index_id_patterns: vec!["test-index-*".to_string()],
+                max_hits: 10,
+                ..Default::default()
+            }),
+            leaf_requests,
+            doc_mappers: vec!["doc-mapper-1".to_string(), "doc-mapper-2".to_string()],
+            index_uris: vec![
+                "ram:///test-index-1".to_string(),
+                "ram:///test-index-2".to_string(),
+            ],
+        }
+    }
+
+    fn split_ids(leaf_search_request: &LeafSearchRequest) -> Vec<(u32, &str)> {
+        leaf_search_request
+            .leaf_requests
+            .iter()
+            .flat_map(|leaf_request| {
+                leaf_request.split_offsets.iter().map(|split_offsets| {
+                    (leaf_request.index_uri_ord, split_offsets.split_id.as_str())
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_leaf_search_request() {
+        let request = leaf_search_request(&[&["split-1", "split-2", "split-3"], &["split-4"]]);
+
+        let requests = split_leaf_search_request(request.clone(), 10);
+        assert_eq!(requests, [request.clone()]);
+
+        let requests = split_leaf_search_request(request.clone(), 2);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(split_ids(&requests[0]), [(0, "split-1"), (0, "split-2")]);
+        assert_eq!(split_ids(&requests[1]), [(0, "split-3"), (1, "split-4")]);
+        assert_eq!(requests[1].leaf_requests.len(), 2);
+        assert_eq!(requests[1].doc_mappers, request.doc_mappers);
+        assert_eq!(requests[1].index_uris, request.index_uris);
+
+        let requests = split_leaf_search_request(request, 1);
+        assert_eq!(requests.len(), 4);
+        assert_eq!(split_ids(&requests[3]), [(1, "split-4")]);
+    }
+
+    #[test]
+    fn test_leaf_search_invocation_serialization() {
+        let request = leaf_search_request(&[&["split-1"]]);
+        let invocation = LeafSearchInvocation::from_leaf_search_request(&request);
+        let invocation_json = serde_json::to_string(&invocation).unwrap();
+        let invocation: LeafSearchInvocation = serde_json::from_str(&invocation_json).unwrap();
+        assert_eq!(invocation.into_leaf_search_request().unwrap(), request);
+
+        let invocation: LeafSearchInvocation =
+            serde_json::from_str(r#"{"leaf_search_request": "not base64!"}"#).unwrap();
+        let error = invocation.into_leaf_search_request().unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn test_leaf_search_invocation_response_serialization() {
+        let leaf_search_response = LeafSearchResponse {
+            num_hits: 1,
+            ..Default::default()
+        };
+        let invocation_response =
+            LeafSearchInvocationResponse::from_leaf_search_response(&leaf_search_response);
+        assert!(invocation_response.oversized_response_num_bytes().is_none());
+        let invocation_response_json = serde_json::to_string(&invocation_response).unwrap();
+        let invocation_response: LeafSearchInvocationResponse =
+            serde_json::from_str(&invocation_response_json).unwrap();
+        assert_eq!(
+            invocation_response.into_leaf_search_response().unwrap(),
+            Some(leaf_search_response)
+        );
+
+        let leaf_search_response = LeafSearchResponse {
+            partial_hits: vec![PartialHit {
+                split_id: "x".repeat(MAX_INVOCATION_RESPONSE_NUM_BYTES),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let invocation_response =
+            LeafSearchInvocationResponse::from_leaf_search_response(&leaf_search_response);
+        assert!(
+            invocation_response.oversized_response_num_bytes().unwrap()
+                > MAX_INVOCATION_RESPONSE_NUM_BYTES as u64
+        );
+        let invocation_response_json = serde_json::to_string(&invocation_response).unwrap();
+        assert!(invocation_response_json.len() < 100);
+        let invocation_response: LeafSearchInvocationResponse =
+            serde_json::from_str(&invocation_response_json).unwrap();
+        assert!(invocation_response
+            .into_leaf_search_response()
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lambda_leaf_search_client() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(3)
+            .returning(|leaf_search_request| {
+                let split_ids: Vec<String> = leaf_search_request
+                    .leaf_requests
+                    .iter()
+                    .flat_map(|leaf_request| leaf_request.split_offsets.iter())
+                    .map(|split_offsets| split_offsets.split_id.clone())
+                    .collect();
+                let partial_hits = split_ids
+                    .iter()
+                    .map(|split_id| PartialHit {
+                        split_id: split_id.clone(),
+                        ..Default::default()
+                    })
+                    .collect();
+                Ok(LeafSearchResponse {
+                    num_hits: split_ids.len() as u64,
+                    partial_hits,
+                    num_attempted_splits: split_ids.len() as u64,
+                    num_successful_splits: split_ids.len() as u64,
+                    ..Default::default()
+                })
+            });
+        let invoker = LocalLeafSearchInvoker::new(Arc::new(mock_search_service));
+        let mut search_client = create_search_client_from_leaf_search_invoker(Arc::new(invoker), 2);
+
+        let request =
+            leaf_search_request(&[&["split-1", "split-2", "split-3"], &["split-4", "split-5"]]);
+        let leaf_search_response = search_client.leaf_search(request).await.unwrap();
+        assert_eq!(leaf_search_response.num_hits, 5);
+        assert_eq!(leaf_search_response.num_attempted_splits, 5);
+        assert_eq!(leaf_search_response.num_successful_splits, 5);
+        assert_eq!(leaf_search_response.partial_hits.len(), 5);
+        assert!(leaf_search_response.failed_splits.is_empty());
+
+        let error = search_client
+            .fetch_docs(Default::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SearchError::Internal(_)));
+    }
+
+    struct FailingInvoker {
+        num_invocations: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LeafSearchInvoker for FailingInvoker {
+        fn function_name(&self) -> &str {
+            "failing"
+        }
+
+        async fn invoke(&self, _payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+            self.num_invocations.fetch_add(1, Ordering::Relaxed);
+            anyhow::bail!("function timed out")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lambda_leaf_search_client_failed_invocations() {
+        let invoker = Arc::new(FailingInvoker {
+            num_invocations: AtomicUsize::new(0),
+        });
+        let mut search_client = create_search_client_from_leaf_search_invoker(invoker.clone(), 2);
+
+        let request = leaf_search_request(&[&["split-1", "split-2", "split-3"]]);
+        let leaf_search_response = search_client.leaf_search(request).await.unwrap();
+        assert_eq!(invoker.num_invocations.load(Ordering::Relaxed), 2);
+        assert_eq!(leaf_search_response.num_attempted_splits, 3);
+        assert_eq!(leaf_search_response.num_successful_splits, 0);
+        assert_eq!(leaf_search_response.failed_splits.len(), 3);
+        assert!(leaf_search_response
+            .failed_splits
+            .iter()
+            .all(|failed_split| failed_split.retryable_error));
+    }
+
+    #[tokio::test]
+    async fn test_lambda_leaf_search_client_oversized_responses() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(3)
+            .returning(|leaf_search_request| {
+                // The hits of `split-large` alone exceed the payload limit of the function.
+                let partial_hits: Vec<PartialHit> = leaf_search_request
+                    .leaf_requests
+                    .iter()
+                    .flat_map(|leaf_request| leaf_request.split_offsets.iter())
+                    .map(|split_offsets| {
+                        let num_bytes = if split_offsets.split_id == "split-large" {
+                            5 * 1024 * 1024
+                        } else {
+                            2 * 1024 * 1024
+                        };
+                        PartialHit {
+                            split_id: "x".repeat(num_bytes),
+                            ..Default::default()
+                        }
+                    })
+                    .collect();
+                Ok(LeafSearchResponse {
+                    num_hits: partial_hits.len() as u64,
+                    num_attempted_splits: partial_hits.len() as u64,
+                    num_successful_splits: partial_hits.len() as u64,
+                    partial_hits,
+                    ..Default::default()
+                })
+            });
+        let invoker = LocalLeafSearchInvoker::new(Arc::new(mock_search_service));
+        let mut search_client =
+            create_search_client_from_leaf_search_invoker(Arc::new(invoker), 10);
+
+        let request = leaf_search_request(&[&["split-1", "split-2", "split-large"]]);
+        let leaf_search_response = search_client.leaf_search(request).await.unwrap();
+        assert_eq!(leaf_search_response.num_attempted_splits, 3);
+        assert_eq!(leaf_search_response.num_successful_splits, 2);
+        assert_eq!(leaf_search_response.partial_hits.len(), 2);
+        assert_eq!(leaf_search_response.failed_splits.len(), 1);
+
+        let failed_split = &leaf_search_response.failed_splits[0];
+        assert_eq!(failed_split.split_id, "split-large");
+        assert!(!failed_split.retryable_error);
+        assert!(failed_split.error.contains("payload limit"));
+    }
+}
@@ -14,7 +14,7 @@
 
 use std::collections::{HashMap, HashSet};
 use std::ops::Bound;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
@@ -23,24 +23,31 @@ use anyhow::Context;
 use bytesize::ByteSize;
 use futures::future::try_join_all;
 use quickwit_common::pretty::PrettySample;
+use quickwit_common::uri::Uri;
 use quickwit_directories::{CachingDirectory, HotDirectory, StorageDirectory};
 use quickwit_doc_mapper::{Automaton, DocMapper, FastFieldWarmupInfo, TermRange, WarmupInfo};
 use quickwit_proto::search::{
     CountHits, LeafSearchRequest, LeafSearchResponse, PartialHit, ResourceStats, SearchRequest,
     SortOrder, SortValue, SplitIdAndFooterOffsets, SplitSearchError,
 };
-use quickwit_query::query_ast::{BoolQuery, QueryAst, QueryAstTransformer, RangeQuery, TermQuery};
+use quickwit_query::block_join::num_root_docs;
+use quickwit_query::query_ast::{
+    BoolQuery, FunctionScoreQuery, NestedQuery, QueryAst, QueryAstTransformer, RangeQuery,
+    TermQuery,
+};
+use quickwit_query::runtime_field::{runtime_field_cost_per_doc, MAX_RUNTIME_FIELD_COST_PER_SPLIT};
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_storage::{
-    wrap_storage_with_cache, BundleStorage, ByteRangeCache, MemorySizedCache, OwnedBytes,
-    SplitCache, Storage, StorageResolver, TimeoutAndRetryStorage,
+    wrap_storage_with_cache, BundleStorage, ByteRangeCache, CoalescingStorage, DiskSliceCache,
+    MemorySizedCache, OwnedBytes, ReadPlanner, SplitCache, Storage, StorageResolver,
+    TimeoutAndRetryStorage, SPLIT_FOOTER_CACHE_PATH,
 };
 use tantivy::aggregation::agg_req::{AggregationVariants, Aggregations};
 use tantivy::aggregation::AggregationLimitsGuard;
 use tantivy::directory::FileSlice;
 use tantivy::fastfield::FastFieldReaders;
 use tantivy::schema::Field;
-use tantivy::{DateTime, Index, ReloadPolicy, Searcher, TantivyError, Term};
+use tantivy::{DateTime, Index, ReloadPolicy, Searcher, SegmentReader, TantivyError, Term};
 use tokio::task::JoinError;
 use tracing::*;
 
@@ -52,10 +59,11 @@ use crate::service::{deserialize_doc_mapper, SearcherContext};
 use crate::{QuickwitAggregations, SearchError};
 
 #[instrument(skip_all)]
-async fn get_split_footer_from_cache_or_fetch(
+pub(crate) async fn get_split_footer_from_cache_or_fetch(
     index_storage: Arc<dyn Storage>,
     split_and_footer_offsets: &SplitIdAndFooterOffsets,
     footer_cache: &MemorySizedCache<String>,
+    disk_slice_cache_opt: Option<&DiskSliceCache>,
 ) -> anyhow::Result<OwnedBytes> {
     {
         let possible_val = footer_cache.get(&split_and_footer_offsets.split_id);
@@ -63,13 +71,29 @@ async fn get_split_footer_from_cache_or_fetch(
             return Ok(footer_data);
         }
     }
+    let footer_range = split_and_footer_offsets.split_footer_start as usize
+        ..split_and_footer_offsets.split_footer_end as usize;
+    let footer_cache_path = Path::new(SPLIT_FOOTER_CACHE_PATH);
+
+    if let Some(disk_slice_cache) = disk_slice_cache_opt {
+        if let Some(footer_data) = disk_slice_cache
+            .get(
+                &split_and_footer_offsets.split_id,
+                footer_cache_path,
+                footer_range.clone(),
+            )
+            .await
+        {
+            footer_cache.put(
+                split_and_footer_offsets.split_id.to_owned(),
+                footer_data.clone(),
+            );
+            return Ok(footer_data);
+        }
+    }
     let split_file = PathBuf::from(format!("{}.split", split_and_footer_offsets.split_id));
     let footer_data_opt = index_storage
-        .get_slice(
-            &split_file,
-            split_and_footer_offsets.split_footer_start as usize
-                ..split_and_footer_offsets.split_footer_end as usize,
-        )
+        .get_slice(&split_file, footer_range.clone())
         .await
         .with_context(|| {
             format!(
@@ -84,9 +108,56 @@ async fn get_split_footer_from_cache_or_fetch(
         footer_data_opt.clone(),
     );
 
+    if let Some(disk_slice_cache) = disk_slice_cache_opt {
+        disk_slice_cache
+            .put(
+                &split_and_footer_offsets.split_id,
+                footer_cache_path,
+                footer_range,
+                footer_data_opt.clone(),
+            )
+            .await;
+    }
     Ok(footer_data_opt)
 }
 
+/// Returns the storage holding the split file: the storage of the tier the split was moved to if
+/// any, the index storage otherwise. Encrypted splits are transparently decrypted, while splits
+/// written in clear are rejected if the index requires encryption.
+pub(crate) async fn resolve_split_storage(
+    searcher_context: &SearcherContext,
+    index_storage: Arc<dyn Storage>,
+    split_and_footer_offsets: &SplitIdAndFooterOffsets,
+) -> anyhow::Result<Arc<dyn Storage>> {
+    let split_storage = match &split_and_footer_offsets.storage_uri {
+        Some(storage_uri_str) => {
+            let storage_uri = Uri::from_str(storage_uri_str)?;
+
+            if storage_uri == *index_storage.uri() {
+                index_storage
+            } else {
+                searcher_context
+                    .storage_resolver
+                    .resolve(&storage_uri)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to resolve storage `{storage_uri}` of split `{}`",
+                            split_and_footer_offsets.split_id
+                        )
+                    })?
+            }
+        }
+        None => index_storage,
+    };
+    let split_storage = searcher_context.storage_resolver.wrap_split_storage(
+        split_storage,
+        None,
+        split_and_footer_offsets.require_encryption,
+    )?;
+    Ok(split_storage)
+}
+
 /// Returns hotcache_bytes and the split directory (`BundleStorage`) with cache layer:
 /// - A split footer cache given by `SearcherContext.split_footer_cache`.
 #[instrument(skip_all, fields(split_footer_start=split_and_footer_offsets.split_footer_start, split_footer_end=split_and_footer_offsets.split_footer_end))]
@@ -100,6 +171,7 @@ pub(crate) async fn open_split_bundle(
         index_storage.clone(),
         split_and_footer_offsets,
         &searcher_context.split_footer_cache,
+        searcher_context.disk_slice_cache_opt.as_deref(),
     )
     .await?;
 
@@ -152,20 +224,44 @@ pub(crate) async fn open_index_with_caches(
     tokenizer_manager: Option<&TokenizerManager>,
     ephemeral_unbounded_cache: Option<ByteRangeCache>,
 ) -> anyhow::Result<(Index, HotDirectory)> {
-    let index_storage_with_retry_on_timeout =
-        configure_storage_retries(searcher_context, index_storage);
+    let split_storage =
+        resolve_split_storage(searcher_context, index_storage, split_and_footer_offsets).await?;
+    let split_storage_with_retry_on_timeout =
+        configure_storage_retries(searcher_context, split_storage);
 
     let (hotcache_bytes, bundle_storage) = open_split_bundle(
         searcher_context,
-        index_storage_with_retry_on_timeout,
+        split_storage_with_retry_on_timeout,
         split_and_footer_offsets,
     )
     .await?;
 
-    let bundle_storage_with_cache = wrap_storage_with_cache(
-        searcher_context.fast_fields_cache.clone(),
-        Arc::new(bundle_storage),
-    );
+    let bundle_storage: Arc<dyn Storage> = Arc::new(bundle_storage);
+    // The reads are coalesced right above the split, so that the ranges served by the caches
+    // are not fetched again.
+    let bundle_storage = if let Some(read_coalescing_gap) =
+        searcher_context.searcher_config.storage_read_coalescing_gap
+    {
+        let read_planner = ReadPlanner::new(
+            read_coalescing_gap.as_u64() as usize,
+            searcher_context
+                .searcher_config
+                .storage_read_coalescing_max_size
+                .as_u64() as usize,
+        );
+        Arc::new(CoalescingStorage::new(bundle_storage, read_planner))
+    } else {
+        bundle_storage
+    };
+    // The on disk slice cache sits behind the in memory fast fields cache.
+    let bundle_storage = if let Some(disk_slice_cache) = &searcher_context.disk_slice_cache_opt {
+        disk_slice_cache
+            .wrap_split_storage(split_and_footer_offsets.split_id.clone(), bundle_storage)
+    } else {
+        bundle_storage
+    };
+    let bundle_storage_with_cache =
+        wrap_storage_with_cache(searcher_context.fast_fields_cache.clone(), bundle_storage);
 
     let directory = StorageDirectory::new(bundle_storage_with_cache);
 
@@ -492,9 +588,15 @@ async fn leaf_search_single_split(
 
     let mut collector =
         make_collector_for_split(split_id.clone(), &search_request, aggregations_limits)?;
+    check_runtime_field_cost(
+        searcher.segment_readers(),
+        runtime_field_cost_per_doc(&query_ast) + collector.runtime_field_cost_per_doc(),
+        MAX_RUNTIME_FIELD_COST_PER_SPLIT,
+    )?;
 
     let split_schema = index.schema();
     let (query, mut warmup_info) = doc_mapper.query(split_schema.clone(), &query_ast, false)?;
+    warmup_info.merge(collector.build_aggregation_queries(&doc_mapper, &split_schema)?);
 
     let collector_warmup_info = collector.warmup_info();
     warmup_info.merge(collector_warmup_info);
@@ -536,7 +638,12 @@ async fn leaf_search_single_split(
                 collector.update_search_param(&search_request);
                 let mut leaf_search_response: LeafSearchResponse =
                     if is_metadata_count_request_with_ast(&query_ast, &search_request) {
-                        get_leaf_resp_from_count(searcher.num_docs())
+                        let num_docs = searcher
+                            .segment_readers()
+                            .iter()
+                            .map(|segment_reader| num_root_docs(segment_reader).map(u64::from))
+                            .sum::<tantivy::Result<u64>>()?;
+                        get_leaf_resp_from_count(num_docs)
                     } else if collector.is_count_only() {
                         let count = query.count(&searcher)? as u64;
                         get_leaf_resp_from_count(count)
@@ -565,6 +672,30 @@ async fn leaf_search_single_split(
     Ok(leaf_search_response)
 }
 
+/// Returns an error if evaluating runtime fields costing `cost_per_doc` on the documents of all the
+/// segments of a split exceeds `max_cost`.
+fn check_runtime_field_cost(
+    segment_readers: &[SegmentReader],
+    cost_per_doc: u64,
+    max_cost: u64,
+) -> crate::Result<()> {
+    if cost_per_doc == 0 {
+        return Ok(());
+    }
+    let num_docs: u64 = segment_readers
+        .iter()
+        .map(|segment_reader| segment_reader.max_doc() as u64)
+        .sum();
+    let cost = num_docs.saturating_mul(cost_per_doc);
+    if cost > max_cost {
+        return Err(SearchError::InvalidQuery(format!(
+            "runtime fields are too expensive to evaluate: cost {cost} exceeds the limit of \
+             {max_cost} per split"
+        )));
+    }
+    Ok(())
+}
+
 /// Rewrite a request removing parts which incur additional download or computation with no
 /// effect.
 ///
@@ -875,6 +1006,26 @@ impl QueryAstTransformer for RemoveTimestampRange<'_> {
         Ok(Some(QueryAst::Bool(bool_query)))
     }
 
+    fn transform_function_score(
+        &mut self,
+        mut function_score_query: FunctionScoreQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        // the filters of the score functions do not restrict the matching documents
+        let Some(query) = self.transform(*function_score_query.query)? else {
+            return Ok(None);
+        };
+        function_score_query.query = Box::new(query);
+        Ok(Some(QueryAst::FunctionScore(function_score_query)))
+    }
+
+    fn transform_nested(
+        &mut self,
+        nested_query: NestedQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        // the inner query applies to nested documents, not to the timestamp of the documents
+        Ok(Some(QueryAst::Nested(nested_query)))
+    }
+
     fn transform_range(&mut self, range_query: RangeQuery) -> Result<Option<QueryAst>, Self::Err> {
         if range_query.field == self.timestamp_field {
             match range_query.lower_bound {
@@ -1277,7 +1428,7 @@ pub async fn multi_leaf_search(
 async fn resolve_storage_and_leaf_search(
     searcher_context: Arc<SearcherContext>,
     search_request: Arc<SearchRequest>,
-    index_uri: quickwit_common::uri::Uri,
+    index_uri: Uri,
     storage_resolver: StorageResolver,
     splits: Vec<SplitIdAndFooterOffsets>,
     doc_mapper: Arc<DocMapper>,
@@ -1529,15 +1680,79 @@ mod tests {
 
     use bytes::BufMut;
     use quickwit_directories::write_hotcache;
+    use quickwit_proto::search::SortField;
+    use quickwit_query::runtime_field::{parse_runtime_mappings, resolve_runtime_fields};
     use rand::{thread_rng, Rng};
     use tantivy::directory::RamDirectory;
+    use tantivy::indexer::NoMergePolicy;
     use tantivy::schema::{
-        BytesOptions, FieldEntry, Schema, TextFieldIndexing, TextOptions, Value,
+        BytesOptions, FieldEntry, Schema, TextFieldIndexing, TextOptions, Value, FAST,
     };
-    use tantivy::TantivyDocument;
+    use tantivy::{doc, TantivyDocument};
 
     use super::*;
 
+    #[test]
+    fn test_check_runtime_field_cost_adds_up_segments_and_fields() {
+        let mut schema_builder = Schema::builder();
+        let start = schema_builder.add_u64_field("start", FAST);
+        let end = schema_builder.add_u64_field("end", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0..3u64 {
+            for doc in 0..4u64 {
+                index_writer
+                    .add_document(doc!(start => doc, end => doc + segment_ord))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let runtime_mappings_json = r#"{"duration_ms": {"script": "end - start"}}"#;
+        let runtime_mappings = parse_runtime_mappings(runtime_mappings_json).unwrap();
+        let query_ast = resolve_runtime_fields(
+            TermQuery {
+                field: "duration_ms".to_string(),
+                value: "1".to_string(),
+            }
+            .into(),
+            &runtime_mappings,
+        )
+        .unwrap();
+        let search_request = SearchRequest {
+            max_hits: 10,
+            sort_fields: vec![SortField {
+                field_name: "duration_ms".to_string(),
+                sort_order: SortOrder::Desc as i32,
+                ..Default::default()
+            }],
+            runtime_mappings: Some(runtime_mappings_json.to_string()),
+            ..Default::default()
+        };
+        let collector = make_collector_for_split(
+            "split".to_string(),
+            &search_request,
+            AggregationLimitsGuard::default(),
+        )
+        .unwrap();
+        // The runtime field is evaluated once by the query and once by the sort.
+        let cost_per_doc =
+            runtime_field_cost_per_doc(&query_ast) + collector.runtime_field_cost_per_doc();
+        assert_eq!(cost_per_doc, 6);
+
+        // Each segment costs 24, the split 72.
+        check_runtime_field_cost(searcher.segment_readers(), cost_per_doc, 72).unwrap();
+        let error =
+            check_runtime_field_cost(searcher.segment_readers(), cost_per_doc, 50).unwrap_err();
+        assert!(matches!(error, SearchError::InvalidQuery(_)));
+        assert!(error
+            .to_string()
+            .contains("cost 72 exceeds the limit of 50"));
+    }
+
     fn bool_filter(ast: impl Into<QueryAst>) -> QueryAst {
         BoolQuery {
             must: vec![QueryAst::MatchAll],
@@ -204,6 +204,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
 
         let split_2 = SplitIdAndFooterOffsets {
@@ -213,6 +215,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
 
         let query_1 = SearchRequest {
@@ -270,6 +274,8 @@ mod tests {
             timestamp_start: Some(100),
             timestamp_end: Some(199),
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
         let split_2 = SplitIdAndFooterOffsets {
             split_id: "split_2".to_string(),
@@ -278,6 +284,8 @@ mod tests {
             timestamp_start: Some(150),
             timestamp_end: Some(249),
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
         let split_3 = SplitIdAndFooterOffsets {
             split_id: "split_3".to_string(),
@@ -286,6 +294,8 @@ mod tests {
             timestamp_start: Some(150),
             timestamp_end: Some(249),
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
 
         let query_1 = SearchRequest {
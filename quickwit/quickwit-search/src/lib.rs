@@ -20,22 +20,30 @@
 mod client;
 mod cluster_client;
 mod collector;
+mod composite_aggregation;
 mod error;
 mod fetch_docs;
 mod filters;
 mod find_trace_ids_collector;
+mod lambda_leaf_search;
 mod leaf;
 mod leaf_cache;
 mod list_fields;
 mod list_fields_cache;
 mod list_terms;
+mod nested_aggregation;
+mod percolator;
 mod retry;
 mod root;
+mod runtime_field_aggregation;
 mod scroll_context;
 mod search_job_placer;
 mod search_response_rest;
 mod search_stream;
 mod service;
+mod split_warmup;
+mod sql;
+mod term_frequency_aggregation;
 pub(crate) mod top_k_collector;
 
 mod metrics;
@@ -60,7 +68,16 @@ pub type Result<T> = std::result::Result<T, SearchError>;
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::{Arc, OnceLock};
 
+pub use composite_aggregation::{
+    CalendarUnit, CompositeAggregation, CompositeAggregations, CompositeKeyValue, CompositeSource,
+    CompositeSourceKind, CompositeSourceOrder, DateInterval,
+};
 pub use find_trace_ids_collector::FindTraceIdsCollector;
+pub use nested_aggregation::{NestedAggregation, NestedAggregations};
+pub use percolator::{
+    percolate, percolate_documents, PercolateRequest, PercolateResponse, PercolatorQuery,
+    MAX_PERCOLATOR_QUERIES,
+};
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_metastore::{
@@ -74,6 +91,10 @@ use quickwit_proto::types::IndexUid;
 use quickwit_storage::StorageResolver;
 pub use service::SearcherContext;
 use tantivy::DocAddress;
+pub use term_frequency_aggregation::{
+    RareTermsAggregation, SignificanceHeuristic, SignificantTermsAggregation,
+    TermFrequencyAggregations,
+};
 
 pub use crate::client::{
     create_search_client_from_channel, create_search_client_from_grpc_addr, SearchServiceClient,
@@ -81,9 +102,16 @@ pub use crate::client::{
 pub use crate::cluster_client::ClusterClient;
 pub use crate::error::{parse_grpc_error, SearchError};
 use crate::fetch_docs::fetch_docs;
+#[cfg(any(test, feature = "testsuite"))]
+pub use crate::lambda_leaf_search::LocalLeafSearchInvoker;
+pub use crate::lambda_leaf_search::{
+    create_lambda_leaf_search_client, create_search_client_from_leaf_search_invoker,
+    LeafSearchInvocation, LeafSearchInvocationResponse, LeafSearchInvoker,
+};
+pub use crate::leaf::multi_leaf_search;
 pub use crate::root::{
-    check_all_index_metadata_found, jobs_to_leaf_request, root_search, search_plan,
-    IndexMetasForLeafSearch, SearchJob,
+    check_all_index_metadata_found, jobs_to_leaf_request, root_search, root_search_on_splits,
+    search_plan, IndexMetasForLeafSearch, SearchJob,
 };
 pub use crate::search_job_placer::{Job, SearchJobPlacer};
 pub use crate::search_response_rest::{
@@ -91,6 +119,8 @@ pub use crate::search_response_rest::{
 };
 pub use crate::search_stream::root_search_stream;
 pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl};
+pub use crate::split_warmup::{PublishedSplitsReporter, SplitFooterWarmer};
+pub use crate::sql::{sql, SqlFormat, SqlRequest, SqlResponse};
 
 /// A pool of searcher clients identified by their gRPC socket address.
 pub type SearcherPool = Pool<SocketAddr, SearchServiceClient>;
@@ -174,6 +204,12 @@ fn extract_split_and_footer_offsets(split_metadata: &SplitMetadata) -> SplitIdAn
             .as_ref()
             .map(|time_range| *time_range.end()),
         num_docs: split_metadata.num_docs as u64,
+        storage_uri: split_metadata
+            .storage_uri
+            .as_ref()
+            .map(|storage_uri| storage_uri.to_string()),
+        // Set from the index config when the leaf requests are built.
+        require_encryption: false,
     }
 }
 
@@ -263,7 +299,12 @@ pub async fn start_searcher_service(
     search_job_placer: SearchJobPlacer,
     searcher_context: Arc<SearcherContext>,
 ) -> anyhow::Result<Arc<dyn SearchService>> {
-    let cluster_client = ClusterClient::new(search_job_placer);
+    let mut cluster_client = ClusterClient::new(search_job_placer);
+
+    if let Some(lambda_leaf_search_config) = &searcher_context.searcher_config.lambda_leaf_search {
+        let lambda_client = create_lambda_leaf_search_client(lambda_leaf_search_config).await?;
+        cluster_client = cluster_client.with_lambda_client(lambda_client);
+    }
     let search_service = Arc::new(SearchServiceImpl::new(
         metastore,
         storage_resolver,
@@ -285,7 +326,12 @@ pub async fn single_node_search(
     let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
     let cluster_client = ClusterClient::new(search_job_placer);
     let searcher_config = SearcherConfig::default();
-    let searcher_context = Arc::new(SearcherContext::new(searcher_config, None));
+    let searcher_context = Arc::new(SearcherContext::new(
+        searcher_config,
+        None,
+        None,
+        storage_resolver.clone(),
+    ));
     let search_service = Arc::new(SearchServiceImpl::new(
         metastore.clone(),
         storage_resolver,
@@ -32,7 +32,7 @@ use quickwit_proto::search::{
 use quickwit_proto::types::{IndexId, IndexUid};
 use quickwit_storage::Storage;
 
-use crate::leaf::open_split_bundle;
+use crate::leaf::{open_split_bundle, resolve_split_storage};
 use crate::search_job_placer::group_jobs_by_index_id;
 use crate::service::SearcherContext;
 use crate::{list_relevant_splits, resolve_index_patterns, ClusterClient, SearchError, SearchJob};
@@ -50,8 +50,10 @@ pub async fn get_fields_from_split<'a>(
     {
         return Ok(Box::new(list_fields.fields.into_iter()));
     }
+    let split_storage =
+        resolve_split_storage(searcher_context, index_storage, split_and_footer_offsets).await?;
     let (_, split_bundle) =
-        open_split_bundle(searcher_context, index_storage, split_and_footer_offsets).await?;
+        open_split_bundle(searcher_context, split_storage, split_and_footer_offsets).await?;
 
     let serialized_split_fields = split_bundle
         .get_all(Path::new(SPLIT_FIELDS_FILE_NAME))
@@ -274,6 +276,8 @@ pub struct IndexMetasForLeafSearch {
     pub index_id: IndexId,
     /// Index URI.
     pub index_uri: Uri,
+    /// Whether the splits of the index must be encrypted.
+    pub require_encryption: bool,
 }
 
 /// Performs a distributed list fields request.
@@ -297,6 +301,7 @@ pub async fn root_list_fields(
             let index_metadata_for_leaf_search = IndexMetasForLeafSearch {
                 index_uri: index_metadata.index_uri().clone(),
                 index_id: index_metadata.index_config.index_id.to_string(),
+                require_encryption: index_metadata.index_config.requires_encryption(),
             };
 
             (
@@ -364,7 +369,13 @@ pub fn jobs_to_leaf_requests(
             index_id: index_meta.index_id.to_string(),
             index_uri: index_meta.index_uri.to_string(),
             fields: search_request_for_leaf.fields.clone(),
-            split_offsets: job_group.into_iter().map(|job| job.offsets).collect(),
+            split_offsets: job_group
+                .into_iter()
+                .map(|job| SplitIdAndFooterOffsets {
+                    require_encryption: index_meta.require_encryption,
+                    ..job.offsets
+                })
+                .collect(),
         };
         leaf_search_requests.push(leaf_search_request);
         Ok(())
@@ -83,6 +83,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
 
         let split_2 = SplitIdAndFooterOffsets {
@@ -92,6 +94,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
 
         let result = ListFieldsEntryResponse {
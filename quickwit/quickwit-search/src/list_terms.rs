@@ -117,7 +117,20 @@ pub async fn root_list_terms(
         .collect_splits_metadata()
         .await?;
 
-    let jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
+    let index_uids_requiring_encryption: HashSet<&IndexUid> = indexes_metadata
+        .iter()
+        .filter(|index_metadata| index_metadata.index_config.requires_encryption())
+        .map(|index_metadata| &index_metadata.index_uid)
+        .collect();
+    let jobs: Vec<SearchJob> = split_metadatas
+        .iter()
+        .map(|split_metadata| {
+            let mut job = SearchJob::from(split_metadata);
+            job.offsets.require_encryption =
+                index_uids_requiring_encryption.contains(&split_metadata.index_uid);
+            job
+        })
+        .collect();
     let assigned_leaf_search_jobs = cluster_client
         .search_job_placer
         .assign_jobs(jobs, &HashSet::default())
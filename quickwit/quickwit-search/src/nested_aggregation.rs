@@ -0,0 +1,563 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `nested` and `reverse_nested` aggregations.
+//!
+//! Tantivy aggregations only see the documents matching the query, which are root documents.
+//! The `nested` aggregations run their sub-aggregations on the hidden nested documents of these
+//! root documents instead. They are only supported at the top level of the aggregation request,
+//! and `reverse_nested` aggregations are only supported as direct sub-aggregations of a `nested`
+//! aggregation.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{bail, Context};
+use quickwit_query::block_join::NestedPathColumn;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
+use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use tantivy::aggregation::{AggregationLimitsGuard, AggregationSegmentCollector};
+use tantivy::collector::SegmentCollector;
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Aggregation request containing at least one top-level `nested` aggregation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedAggregations {
+    /// Aggregations on the root documents.
+    pub root: Aggregations,
+    /// `nested` aggregations, by name.
+    pub nested: BTreeMap<String, NestedAggregation>,
+}
+
+/// Aggregation on the nested documents of path `path` of the root documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedAggregation {
+    /// Path of the nested field.
+    pub path: String,
+    /// Sub-aggregations, run on the nested documents.
+    pub aggs: Aggregations,
+    /// `reverse_nested` aggregations, by name. They aggregate the root documents having at least
+    /// one nested document of path `path`.
+    pub reverse_nested: BTreeMap<String, Aggregations>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NestedParams {
+    path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NestedAggregationJson {
+    nested: NestedParams,
+    #[serde(default, alias = "aggregations")]
+    aggs: JsonMap<String, JsonValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReverseNestedParams {
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReverseNestedAggregationJson {
+    reverse_nested: ReverseNestedParams,
+    #[serde(default, alias = "aggregations")]
+    aggs: Aggregations,
+}
+
+impl TryFrom<JsonMap<String, JsonValue>> for NestedAggregations {
+    type Error = anyhow::Error;
+
+    fn try_from(aggregations_json: JsonMap<String, JsonValue>) -> anyhow::Result<Self> {
+        let mut root_json = JsonMap::new();
+        let mut nested = BTreeMap::new();
+        for (name, aggregation_json) in aggregations_json {
+            if aggregation_json.get("nested").is_none() {
+                root_json.insert(name, aggregation_json);
+                continue;
+            }
+            let nested_aggregation = parse_nested_aggregation(aggregation_json)
+                .with_context(|| format!("invalid nested aggregation `{name}`"))?;
+            nested.insert(name, nested_aggregation);
+        }
+        if nested.is_empty() {
+            bail!("no nested aggregation");
+        }
+        let root = serde_json::from_value(JsonValue::Object(root_json))?;
+        Ok(NestedAggregations { root, nested })
+    }
+}
+
+fn parse_nested_aggregation(aggregation_json: JsonValue) -> anyhow::Result<NestedAggregation> {
+    let NestedAggregationJson { nested, aggs } = serde_json::from_value(aggregation_json)?;
+    let mut aggs_json = JsonMap::new();
+    let mut reverse_nested = BTreeMap::new();
+    for (name, aggregation_json) in aggs {
+        if aggregation_json.get("reverse_nested").is_none() {
+            aggs_json.insert(name, aggregation_json);
+            continue;
+        }
+        let reverse_nested_aggregation: ReverseNestedAggregationJson =
+            serde_json::from_value(aggregation_json)?;
+        if reverse_nested_aggregation.reverse_nested.path.is_some() {
+            bail!("`reverse_nested` aggregations only support joining back to the root documents");
+        }
+        reverse_nested.insert(name, reverse_nested_aggregation.aggs);
+    }
+    let aggs = serde_json::from_value(JsonValue::Object(aggs_json))?;
+    Ok(NestedAggregation {
+        path: nested.path,
+        aggs,
+        reverse_nested,
+    })
+}
+
+impl<'de> Deserialize<'de> for NestedAggregations {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let aggregations_json = JsonMap::<String, JsonValue>::deserialize(deserializer)?;
+        NestedAggregations::try_from(aggregations_json).map_err(serde::de::Error::custom)
+    }
+}
+
+impl NestedAggregations {
+    /// Returns the list of fast fields that should be loaded for the aggregation.
+    pub fn fast_field_names(&self) -> HashSet<String> {
+        let mut fast_field_names = get_fast_field_names(&self.root);
+        for nested_aggregation in self.nested.values() {
+            fast_field_names.extend(get_fast_field_names(&nested_aggregation.aggs));
+            for reverse_nested_aggs in nested_aggregation.reverse_nested.values() {
+                fast_field_names.extend(get_fast_field_names(reverse_nested_aggs));
+            }
+        }
+        fast_field_names
+    }
+}
+
+/// Documents count and sub-aggregation results of a single bucket.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct SingleBucketIntermediateResult {
+    doc_count: u64,
+    sub_aggregations: IntermediateAggregationResults,
+}
+
+impl SingleBucketIntermediateResult {
+    pub fn merge_fruits(&mut self, other: SingleBucketIntermediateResult) -> tantivy::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregations.merge_fruits(other.sub_aggregations)
+    }
+
+    pub fn into_final_result(
+        self,
+        aggregations: Aggregations,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<JsonMap<String, JsonValue>> {
+        let final_result = self
+            .sub_aggregations
+            .into_final_result(aggregations, limits.clone())?;
+        let JsonValue::Object(mut final_result_json) =
+            serde_json::to_value(final_result).map_err(map_error)?
+        else {
+            return Err(TantivyError::InternalError(
+                "aggregation results should serialize to a JSON object".to_string(),
+            ));
+        };
+        final_result_json.insert("doc_count".to_string(), self.doc_count.into());
+        Ok(final_result_json)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct NestedIntermediateResult {
+    bucket: SingleBucketIntermediateResult,
+    reverse_nested: BTreeMap<String, SingleBucketIntermediateResult>,
+}
+
+impl NestedIntermediateResult {
+    fn merge_fruits(&mut self, other: NestedIntermediateResult) -> tantivy::Result<()> {
+        self.bucket.merge_fruits(other.bucket)?;
+        merge_maps(
+            &mut self.reverse_nested,
+            other.reverse_nested,
+            |left, right| left.merge_fruits(right),
+        )
+    }
+}
+
+/// Intermediate results of [`NestedAggregations`].
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct NestedIntermediateAggregationResults {
+    root: IntermediateAggregationResults,
+    nested: BTreeMap<String, NestedIntermediateResult>,
+}
+
+impl NestedIntermediateAggregationResults {
+    pub fn merge_fruits(
+        &mut self,
+        other: NestedIntermediateAggregationResults,
+    ) -> tantivy::Result<()> {
+        self.root.merge_fruits(other.root)?;
+        merge_maps(&mut self.nested, other.nested, |left, right| {
+            left.merge_fruits(right)
+        })
+    }
+
+    /// Converts the intermediate results into the final JSON results.
+    pub fn into_final_result(
+        mut self,
+        aggregations: NestedAggregations,
+        limits: AggregationLimitsGuard,
+    ) -> tantivy::Result<JsonValue> {
+        let final_root_result = self
+            .root
+            .into_final_result(aggregations.root, limits.clone())?;
+        let JsonValue::Object(mut final_result_json) =
+            serde_json::to_value(final_root_result).map_err(map_error)?
+        else {
+            return Err(TantivyError::InternalError(
+                "aggregation results should serialize to a JSON object".to_string(),
+            ));
+        };
+        for (name, nested_aggregation) in aggregations.nested {
+            let mut nested_result = self.nested.remove(&name).unwrap_or_default();
+            let mut nested_result_json = nested_result
+                .bucket
+                .into_final_result(nested_aggregation.aggs, &limits)?;
+            for (reverse_nested_name, reverse_nested_aggs) in nested_aggregation.reverse_nested {
+                let reverse_nested_result = nested_result
+                    .reverse_nested
+                    .remove(&reverse_nested_name)
+                    .unwrap_or_default();
+                let reverse_nested_result_json =
+                    reverse_nested_result.into_final_result(reverse_nested_aggs, &limits)?;
+                nested_result_json.insert(
+                    reverse_nested_name,
+                    JsonValue::Object(reverse_nested_result_json),
+                );
+            }
+            final_result_json.insert(name, JsonValue::Object(nested_result_json));
+        }
+        Ok(JsonValue::Object(final_result_json))
+    }
+}
+
+fn merge_maps<T>(
+    map: &mut BTreeMap<String, T>,
+    other: BTreeMap<String, T>,
+    merge_fn: impl Fn(&mut T, T) -> tantivy::Result<()>,
+) -> tantivy::Result<()> {
+    for (key, other_value) in other {
+        if let Some(value) = map.get_mut(&key) {
+            merge_fn(value, other_value)?;
+        } else {
+            map.insert(key, other_value);
+        }
+    }
+    Ok(())
+}
+
+fn map_error(err: serde_json::Error) -> TantivyError {
+    TantivyError::InternalError(format!("failed to serialize aggregation results: {err}"))
+}
+
+pub(crate) struct SingleBucketSegmentCollector {
+    doc_count: u64,
+    collector: AggregationSegmentCollector,
+}
+
+impl SingleBucketSegmentCollector {
+    pub fn new(
+        aggregations: &Aggregations,
+        segment_reader: &SegmentReader,
+        segment_ord: SegmentOrdinal,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<Self> {
+        let collector = AggregationSegmentCollector::from_agg_req_and_reader(
+            aggregations,
+            segment_reader,
+            segment_ord,
+            limits,
+        )?;
+        Ok(SingleBucketSegmentCollector {
+            doc_count: 0,
+            collector,
+        })
+    }
+
+    pub fn collect(&mut self, doc: DocId, score: Score) {
+        self.doc_count += 1;
+        self.collector.collect(doc, score);
+    }
+
+    pub fn harvest(self) -> tantivy::Result<SingleBucketIntermediateResult> {
+        Ok(SingleBucketIntermediateResult {
+            doc_count: self.doc_count,
+            sub_aggregations: self.collector.harvest()?,
+        })
+    }
+}
+
+struct NestedSegmentCollector {
+    name: String,
+    /// `None` if the segment does not contain any nested document for the path.
+    child_path_ord: Option<u64>,
+    bucket: SingleBucketSegmentCollector,
+    reverse_nested: Vec<(String, SingleBucketSegmentCollector)>,
+}
+
+/// Segment collector of [`NestedAggregations`].
+pub(crate) struct NestedAggregationsSegmentCollector {
+    root: AggregationSegmentCollector,
+    nested: Vec<NestedSegmentCollector>,
+    /// `None` if the segment does not contain any nested document.
+    nested_path_column_opt: Option<NestedPathColumn>,
+}
+
+impl NestedAggregationsSegmentCollector {
+    pub fn from_agg_req_and_reader(
+        aggregations: &NestedAggregations,
+        segment_reader: &SegmentReader,
+        segment_ord: SegmentOrdinal,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<Self> {
+        let root = AggregationSegmentCollector::from_agg_req_and_reader(
+            &aggregations.root,
+            segment_reader,
+            segment_ord,
+            limits,
+        )?;
+        let nested_path_column_opt = NestedPathColumn::open(segment_reader)?;
+        let mut nested = Vec::with_capacity(aggregations.nested.len());
+        for (name, nested_aggregation) in &aggregations.nested {
+            let child_path_ord = if let Some(nested_path_column) = &nested_path_column_opt {
+                nested_path_column.path_ord(&nested_aggregation.path)?
+            } else {
+                None
+            };
+            let bucket = SingleBucketSegmentCollector::new(
+                &nested_aggregation.aggs,
+                segment_reader,
+                segment_ord,
+                limits,
+            )?;
+            let mut reverse_nested = Vec::with_capacity(nested_aggregation.reverse_nested.len());
+            for (reverse_nested_name, reverse_nested_aggs) in &nested_aggregation.reverse_nested {
+                let reverse_nested_collector = SingleBucketSegmentCollector::new(
+                    reverse_nested_aggs,
+                    segment_reader,
+                    segment_ord,
+                    limits,
+                )?;
+                reverse_nested.push((reverse_nested_name.clone(), reverse_nested_collector));
+            }
+            nested.push(NestedSegmentCollector {
+                name: name.clone(),
+                child_path_ord,
+                bucket,
+                reverse_nested,
+            });
+        }
+        Ok(NestedAggregationsSegmentCollector {
+            root,
+            nested,
+            nested_path_column_opt,
+        })
+    }
+
+    pub fn collect_block(&mut self, docs: &[DocId]) {
+        for &doc in docs {
+            self.collect(doc, 0.0);
+        }
+    }
+
+    pub fn collect(&mut self, doc: DocId, score: Score) {
+        self.root.collect(doc, score);
+        let Some(nested_path_column) = &self.nested_path_column_opt else {
+            return;
+        };
+        // The nested documents of a root document are right before it.
+        let block_start = nested_path_column.block_start(doc);
+        for nested_collector in &mut self.nested {
+            let Some(child_path_ord) = nested_collector.child_path_ord else {
+                continue;
+            };
+            let mut has_nested_docs = false;
+            for nested_doc in block_start..doc {
+                if nested_path_column.doc_path_ord(nested_doc) == Some(child_path_ord) {
+                    nested_collector.bucket.collect(nested_doc, 0.0);
+                    has_nested_docs = true;
+                }
+            }
+            if has_nested_docs {
+                for (_, reverse_nested_collector) in &mut nested_collector.reverse_nested {
+                    reverse_nested_collector.collect(doc, score);
+                }
+            }
+        }
+    }
+
+    pub fn harvest(self) -> tantivy::Result<NestedIntermediateAggregationResults> {
+        let root = self.root.harvest()?;
+        let mut nested = BTreeMap::new();
+        for nested_collector in self.nested {
+            let mut reverse_nested = BTreeMap::new();
+            for (name, reverse_nested_collector) in nested_collector.reverse_nested {
+                reverse_nested.insert(name, reverse_nested_collector.harvest()?);
+            }
+            let nested_result = NestedIntermediateResult {
+                bucket: nested_collector.bucket.harvest()?,
+                reverse_nested,
+            };
+            nested.insert(nested_collector.name, nested_result);
+        }
+        Ok(NestedIntermediateAggregationResults { root, nested })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::shared_consts::NESTED_PATH_FIELD_NAME;
+    use serde_json::json;
+    use tantivy::schema::{Schema, FAST, STRING};
+    use tantivy::{doc, Index};
+
+    use super::*;
+    use crate::collector::QuickwitAggregations;
+
+    #[test]
+    fn test_nested_aggregations_serde() {
+        let aggregations: QuickwitAggregations = serde_json::from_value(json!({
+            "titles": {"terms": {"field": "title"}},
+            "items": {
+                "nested": {"path": "items"},
+                "aggs": {
+                    "total_qty": {"sum": {"field": "items.qty"}},
+                    "roots": {"reverse_nested": {}, "aggs": {"titles": {"terms": {"field": "title"}}}}
+                }
+            }
+        }))
+        .unwrap();
+        let QuickwitAggregations::NestedAggregations(nested_aggregations) = aggregations else {
+            panic!("expected nested aggregations");
+        };
+        assert_eq!(nested_aggregations.root.len(), 1);
+        let nested_aggregation = &nested_aggregations.nested["items"];
+        assert_eq!(nested_aggregation.path, "items");
+        assert_eq!(nested_aggregation.aggs.len(), 1);
+        assert_eq!(nested_aggregation.reverse_nested["roots"].len(), 1);
+        assert_eq!(
+            nested_aggregations.fast_field_names(),
+            HashSet::from_iter(["title".to_string(), "items.qty".to_string()])
+        );
+
+        let aggregations: QuickwitAggregations = serde_json::from_value(json!({
+            "titles": {"terms": {"field": "title"}},
+        }))
+        .unwrap();
+        assert!(matches!(
+            aggregations,
+            QuickwitAggregations::TantivyAggregations(_)
+        ));
+
+        let error = NestedAggregations::try_from(
+            json!({
+                "items": {
+                    "nested": {"path": "items"},
+                    "aggs": {"roots": {"reverse_nested": {"path": "other"}}}
+                }
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "invalid nested aggregation `items`: `reverse_nested` aggregations only support \
+             joining back to the root documents"
+        );
+    }
+
+    #[test]
+    fn test_nested_aggregations_collector() {
+        let mut schema_builder = Schema::builder();
+        let nested_path = schema_builder.add_text_field(NESTED_PATH_FIELD_NAME, FAST);
+        let title = schema_builder.add_text_field("title", STRING | FAST);
+        let qty = schema_builder.add_u64_field("items.qty", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        index_writer
+            .add_document(doc!(nested_path=>"items", qty=>3u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(nested_path=>"items", qty=>10u64))
+            .unwrap();
+        index_writer.add_document(doc!(title=>"a")).unwrap();
+        index_writer
+            .add_document(doc!(nested_path=>"items", qty=>10u64))
+            .unwrap();
+        index_writer.add_document(doc!(title=>"b")).unwrap();
+        index_writer.add_document(doc!(title=>"c")).unwrap();
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let aggregations: NestedAggregations = serde_json::from_value(json!({
+            "num_titles": {"value_count": {"field": "title"}},
+            "items": {
+                "nested": {"path": "items"},
+                "aggs": {
+                    "total_qty": {"sum": {"field": "items.qty"}},
+                    "roots": {"reverse_nested": {}}
+                }
+            },
+            "unknown": {"nested": {"path": "unknown"}}
+        }))
+        .unwrap();
+        let limits = AggregationLimitsGuard::new(None, None);
+        let mut collector = NestedAggregationsSegmentCollector::from_agg_req_and_reader(
+            &aggregations,
+            searcher.segment_reader(0),
+            0,
+            &limits,
+        )
+        .unwrap();
+        collector.collect_block(&[2, 4]);
+        collector.collect(5, 1.0);
+        let mut intermediate_results = collector.harvest().unwrap();
+        intermediate_results
+            .merge_fruits(NestedIntermediateAggregationResults::default())
+            .unwrap();
+        let final_results = intermediate_results
+            .into_final_result(aggregations, limits)
+            .unwrap();
+        assert_eq!(
+            final_results,
+            json!({
+                "num_titles": {"value": 3.0},
+                "items": {
+                    "doc_count": 3,
+                    "total_qty": {"value": 23.0},
+                    "roots": {"doc_count": 2}
+                },
+                "unknown": {"doc_count": 0}
+            })
+        );
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use quickwit_config::build_doc_mapper;
+use quickwit_doc_mapper::{DocMapper, JsonObject};
+use quickwit_metastore::IndexMetadataResponseExt;
+use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService, MetastoreServiceClient};
+use quickwit_proto::search::SearchRequest;
+use quickwit_query::query_ast::QueryAst;
+use quickwit_query::ElasticQueryDsl;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tantivy::collector::DocSetCollector;
+use tantivy::{DocId, Index};
+
+use crate::{SearchError, SearchService};
+
+/// Maximum number of queries a percolator index can hold.
+pub const MAX_PERCOLATOR_QUERIES: u64 = 10_000;
+
+// Minimum memory budget accepted by tantivy's index writer.
+const PERCOLATOR_INDEXING_MEMORY_BUDGET: usize = 15_000_000;
+
+/// Request to percolate documents against the queries stored in a percolator index.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PercolateRequest {
+    /// Document to percolate.
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub document: Option<JsonObject>,
+    /// Documents to percolate.
+    #[serde(default)]
+    #[schema(value_type = Vec<Object>)]
+    pub documents: Vec<JsonObject>,
+    /// Field of the stored documents holding the query.
+    #[serde(default = "default_query_field")]
+    pub field: String,
+    /// Field of the stored documents holding the query ID.
+    #[serde(default = "default_id_field")]
+    pub id_field: String,
+}
+
+fn default_query_field() -> String {
+    "query".to_string()
+}
+
+fn default_id_field() -> String {
+    "id".to_string()
+}
+
+/// Response to a [`PercolateRequest`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PercolateResponse {
+    /// IDs of the queries matched by each document, in the order of the request documents.
+    pub matches: Vec<Vec<String>>,
+    /// Number of queries the documents were percolated against.
+    pub num_queries: usize,
+}
+
+/// A query stored in a percolator index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PercolatorQuery {
+    /// ID of the query.
+    pub id: String,
+    /// The query, which may still contain user queries.
+    pub query_ast: QueryAst,
+}
+
+impl PercolatorQuery {
+    /// Extracts a percolator query from a document of a percolator index. The query can either
+    /// be a query AST or an Elasticsearch query DSL, stored as a JSON object or as a string.
+    pub fn from_json_doc(
+        json_doc: &JsonObject,
+        query_field: &str,
+        id_field: &str,
+    ) -> anyhow::Result<Self> {
+        let id = match json_doc.get(id_field) {
+            Some(JsonValue::String(id)) => id.clone(),
+            Some(JsonValue::Number(id)) => id.to_string(),
+            _ => anyhow::bail!("document has no `{id_field}` field"),
+        };
+        let query_json = match json_doc.get(query_field) {
+            Some(JsonValue::String(query_str)) => serde_json::from_str(query_str)?,
+            Some(query_json @ JsonValue::Object(_)) => query_json.clone(),
+            _ => anyhow::bail!("query `{id}` has no `{query_field}` field"),
+        };
+        let query_ast = if let Ok(query_ast) = serde_json::from_value(query_json.clone()) {
+            query_ast
+        } else {
+            let elastic_query_dsl: ElasticQueryDsl = serde_json::from_value(query_json)
+                .map_err(|error| anyhow::anyhow!("invalid query `{id}`: {error}"))?;
+            elastic_query_dsl.try_into()?
+        };
+        Ok(PercolatorQuery { id, query_ast })
+    }
+}
+
+/// Percolates documents against the queries stored in the percolator index `index_id`.
+///
+/// The documents are parsed with the doc mapper of the percolator index, indexed in an in-memory
+/// index, and each stored query is then run against it.
+pub async fn percolate(
+    index_id: String,
+    percolate_request: PercolateRequest,
+    search_service: &dyn SearchService,
+    metastore: MetastoreServiceClient,
+) -> crate::Result<PercolateResponse> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.clone());
+    let index_config = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .into_index_config();
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
+        .map_err(|error| SearchError::Internal(format!("failed to build doc mapper: {error}")))?;
+
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.clone()],
+        query_ast: serde_json::to_string(&QueryAst::MatchAll)?,
+        max_hits: MAX_PERCOLATOR_QUERIES,
+        ..Default::default()
+    };
+    let search_response = search_service.root_search(search_request).await?;
+    if search_response.num_hits > MAX_PERCOLATOR_QUERIES {
+        return Err(SearchError::InvalidArgument(format!(
+            "percolator index `{index_id}` holds more than {MAX_PERCOLATOR_QUERIES} queries"
+        )));
+    }
+    let mut queries = Vec::with_capacity(search_response.hits.len());
+    for hit in &search_response.hits {
+        let json_doc: JsonObject = serde_json::from_str(&hit.json)?;
+        let query = PercolatorQuery::from_json_doc(
+            &json_doc,
+            &percolate_request.field,
+            &percolate_request.id_field,
+        )
+        .map_err(|error| SearchError::InvalidQuery(error.to_string()))?;
+        queries.push(query);
+    }
+    let documents: Vec<JsonObject> = percolate_request
+        .document
+        .into_iter()
+        .chain(percolate_request.documents)
+        .collect();
+    if documents.is_empty() {
+        return Err(SearchError::InvalidArgument(
+            "percolate request must contain at least one document".to_string(),
+        ));
+    }
+    let num_queries = queries.len();
+    let matches = crate::search_thread_pool()
+        .run_cpu_intensive(move || percolate_documents(&doc_mapper, &queries, documents))
+        .await
+        .map_err(|_| SearchError::Internal("percolation panicked".to_string()))??;
+    Ok(PercolateResponse {
+        matches,
+        num_queries,
+    })
+}
+
+/// Returns the IDs of the queries matched by each document.
+pub fn percolate_documents(
+    doc_mapper: &DocMapper,
+    queries: &[PercolatorQuery],
+    documents: Vec<JsonObject>,
+) -> crate::Result<Vec<Vec<String>>> {
+    let mut index = Index::create_in_ram(doc_mapper.schema());
+    index.set_tokenizers(doc_mapper.tokenizer_manager().tantivy_manager().clone());
+    index.set_fast_field_tokenizers(
+        quickwit_query::get_quickwit_fastfield_normalizer_manager()
+            .tantivy_manager()
+            .clone(),
+    );
+    // A single indexing thread and a single commit guarantee that the documents end up in one
+    // segment, in insertion order.
+    let mut index_writer = index.writer_with_num_threads(1, PERCOLATOR_INDEXING_MEMORY_BUDGET)?;
+    let num_documents = documents.len();
+    let mut doc_ords: HashMap<DocId, usize> = HashMap::with_capacity(num_documents);
+    let mut next_doc_id: DocId = 0;
+    for (doc_ord, json_doc) in documents.into_iter().enumerate() {
+        let (_partition, document, nested_docs) = doc_mapper
+            .doc_with_nested_docs_from_json_obj(json_doc, 0)
+            .map_err(|error| {
+                SearchError::InvalidArgument(format!("invalid document #{doc_ord}: {error}"))
+            })?;
+        next_doc_id += nested_docs.len() as DocId;
+        for nested_doc in nested_docs {
+            index_writer.add_document(nested_doc)?;
+        }
+        index_writer.add_document(document)?;
+        doc_ords.insert(next_doc_id, doc_ord);
+        next_doc_id += 1;
+    }
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+
+    let mut matches = vec![Vec::new(); num_documents];
+    for query in queries {
+        let query_ast = query
+            .query_ast
+            .clone()
+            .parse_user_query(doc_mapper.default_search_fields())
+            .map_err(|error| {
+                SearchError::InvalidQuery(format!("invalid query `{}`: {error}", query.id))
+            })?;
+        let (tantivy_query, _warmup_info) =
+            doc_mapper.query(searcher.schema().clone(), &query_ast, false)?;
+        for doc_address in searcher.search(&tantivy_query, &DocSetCollector)? {
+            if let Some(&doc_ord) = doc_ords.get(&doc_address.doc_id) {
+                matches[doc_ord].push(query.id.clone());
+            }
+        }
+    }
+    for document_matches in &mut matches {
+        document_matches.sort_unstable();
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn doc_mapper_for_test() -> DocMapper {
+        serde_json::from_value(json!({
+            "default_search_fields": ["body"],
+            "timestamp_field": "timestamp",
+            "field_mappings": [
+                {"name": "timestamp", "type": "datetime", "fast": true},
+                {"name": "body", "type": "text"},
+                {"name": "response_time", "type": "f64", "fast": true},
+                {"name": "server", "type": "text", "tokenizer": "raw"},
+                {"name": "query", "type": "json", "indexed": false}
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn percolator_query(json_doc: JsonValue) -> PercolatorQuery {
+        let JsonValue::Object(json_doc) = json_doc else {
+            panic!("expected a JSON object");
+        };
+        PercolatorQuery::from_json_doc(&json_doc, "query", "id").unwrap()
+    }
+
+    fn json_obj(json_value: JsonValue) -> JsonObject {
+        let JsonValue::Object(json_obj) = json_value else {
+            panic!("expected a JSON object");
+        };
+        json_obj
+    }
+
+    #[test]
+    fn test_percolator_query_from_json_doc() {
+        let query = percolator_query(json!({
+            "id": "errors",
+            "query": {"type": "term", "field": "body", "value": "error"}
+        }));
+        assert_eq!(query.id, "errors");
+        assert!(matches!(query.query_ast, QueryAst::Term(_)));
+
+        let query = percolator_query(json!({
+            "id": 3,
+            "query": r#"{"match_all": {}}"#
+        }));
+        assert_eq!(query.id, "3");
+        assert_eq!(query.query_ast, QueryAst::MatchAll);
+
+        let error =
+            PercolatorQuery::from_json_doc(&json_obj(json!({"id": "no-query"})), "query", "id")
+                .unwrap_err();
+        assert_eq!(error.to_string(), "query `no-query` has no `query` field");
+
+        let error = PercolatorQuery::from_json_doc(
+            &json_obj(json!({"id": "bad", "query": {"unknown": {}}})),
+            "query",
+            "id",
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("invalid query `bad`"));
+    }
+
+    #[test]
+    fn test_percolate_documents() {
+        let doc_mapper = doc_mapper_for_test();
+        let queries = vec![
+            percolator_query(json!({"id": "errors", "query": {"term": {"body": "error"}}})),
+            percolator_query(json!({
+                "id": "slow-requests",
+                "query": {"range": {"response_time": {"gte": 1.0}}}
+            })),
+            percolator_query(json!({
+                "id": "user-query",
+                "query": {"query_string": {"query": "disk AND server:alpha"}}
+            })),
+        ];
+        let documents = vec![
+            json_obj(json!({
+                "timestamp": 1_000,
+                "body": "error: disk full",
+                "response_time": 0.3,
+                "server": "alpha"
+            })),
+            json_obj(json!({
+                "timestamp": 2_000,
+                "body": "request served",
+                "response_time": 2.5
+            })),
+            json_obj(json!({"timestamp": 3_000, "body": "all good"})),
+        ];
+        let matches = percolate_documents(&doc_mapper, &queries, documents).unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                vec!["errors".to_string(), "user-query".to_string()],
+                vec!["slow-requests".to_string()],
+                Vec::new(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_percolate_invalid_document() {
+        let doc_mapper = doc_mapper_for_test();
+        let error = percolate_documents(
+            &doc_mapper,
+            &[],
+            vec![json_obj(
+                json!({"timestamp": 1_000, "response_time": "slow"}),
+            )],
+        )
+        .unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+    }
+}
@@ -129,6 +129,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
         let client_for_retry = retry_client(
             &search_job_placer,
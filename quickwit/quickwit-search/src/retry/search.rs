@@ -93,6 +93,8 @@ mod tests {
                         timestamp_start: None,
                         timestamp_end: None,
                         num_docs: 0,
+                        storage_uri: None,
+                        require_encryption: false,
                     },
                     SplitIdAndFooterOffsets {
                         split_id: "split_2".to_string(),
@@ -101,6 +103,8 @@ mod tests {
                         timestamp_start: None,
                         timestamp_end: None,
                         num_docs: 0,
+                        storage_uri: None,
+                        require_encryption: false,
                     },
                 ],
             }],
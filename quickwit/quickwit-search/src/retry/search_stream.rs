@@ -106,6 +106,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
         let split_2 = SplitIdAndFooterOffsets {
             split_id: "split_2".to_string(),
@@ -114,6 +116,8 @@ mod tests {
             timestamp_start: None,
             timestamp_end: None,
             num_docs: 0,
+            storage_uri: None,
+            require_encryption: false,
         };
         let retry_policy = LeafSearchStreamRetryPolicy {};
         let request = LeafSearchStreamRequest {
@@ -37,9 +37,12 @@ use quickwit_proto::search::{
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use quickwit_query::query_ast::{
-    BoolQuery, QueryAst, QueryAstVisitor, RangeQuery, TermQuery, TermSetQuery,
+    BoolQuery, FunctionScoreQuery, NestedQuery, QueryAst, QueryAstVisitor, RangeQuery, TermQuery,
+    TermSetQuery,
 };
+use quickwit_query::runtime_field::{resolve_runtime_fields, RuntimeMappings};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
 use tantivy::aggregation::agg_result::AggregationResults;
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tantivy::collector::Collector;
@@ -48,13 +51,20 @@ use tantivy::TantivyError;
 use tracing::{debug, info_span, instrument};
 
 use crate::cluster_client::ClusterClient;
-use crate::collector::{make_merge_collector, QuickwitAggregations};
+use crate::collector::{make_merge_collector, runtime_mappings_from_request, QuickwitAggregations};
+use crate::composite_aggregation::{
+    CompositeAggregations, CompositeIntermediateAggregationResults,
+};
 use crate::find_trace_ids_collector::Span;
 use crate::metrics::SEARCH_METRICS;
+use crate::nested_aggregation::{NestedAggregations, NestedIntermediateAggregationResults};
 use crate::scroll_context::{ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_job_placer::{group_by, group_jobs_by_index_id, Job};
 use crate::search_response_rest::StorageRequestCount;
 use crate::service::SearcherContext;
+use crate::term_frequency_aggregation::{
+    TermFrequencyAggregations, TermFrequencyIntermediateAggregationResults,
+};
 use crate::{
     extract_split_and_footer_offsets, list_relevant_splits, SearchError, SearchJobPlacer,
     SearchPlanResponseRest, SearchServiceClient,
@@ -157,6 +167,9 @@ pub struct IndexMetasForLeafSearch {
     pub index_uri: Uri,
     /// Doc mapper json string.
     pub doc_mapper_str: String,
+    /// Whether the splits of the index must be encrypted.
+    #[serde(default)]
+    pub require_encryption: bool,
 }
 
 pub(crate) type IndexesMetasForLeafSearch = HashMap<IndexUid, IndexMetasForLeafSearch>;
@@ -189,6 +202,7 @@ fn validate_request_and_build_metadata(
     )?;
     let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+    let runtime_mappings = runtime_mappings_from_request(search_request)?;
     let mut indexes_meta_for_leaf_search: HashMap<IndexUid, IndexMetasForLeafSearch> =
         HashMap::new();
     let mut query_ast_resolved_opt: Option<QueryAst> = None;
@@ -206,6 +220,7 @@ fn validate_request_and_build_metadata(
         let query_ast_resolved_for_index = query_ast
             .clone()
             .parse_user_query(doc_mapper.default_search_fields())
+            .and_then(|query_ast| resolve_runtime_fields(query_ast, &runtime_mappings))
             // We convert the error to return a 400 to the user (and not a 500).
             .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
 
@@ -240,11 +255,17 @@ fn validate_request_and_build_metadata(
 
         // Validate request against the current index schema.
         let schema = doc_mapper.schema();
-        validate_request(&schema, &doc_mapper.timestamp_field_name(), search_request)?;
+        validate_request(
+            &schema,
+            &doc_mapper.timestamp_field_name(),
+            search_request,
+            &runtime_mappings,
+        )?;
 
         validate_sort_field_types(
             &schema,
             &search_request.sort_fields,
+            &runtime_mappings,
             &mut sort_fields_is_datetime,
         )?;
 
@@ -256,6 +277,7 @@ fn validate_request_and_build_metadata(
             doc_mapper_str: serde_json::to_string(&doc_mapper).map_err(|err| {
                 SearchError::Internal(format!("failed to serialize doc mapper. cause: {err}"))
             })?,
+            require_encryption: index_metadata.index_config.requires_encryption(),
         };
         indexes_meta_for_leaf_search.insert(
             index_metadata.index_uid.clone(),
@@ -281,10 +303,21 @@ fn validate_request_and_build_metadata(
 fn validate_sort_field_types(
     schema: &Schema,
     sort_fields: &[SortField],
+    runtime_mappings: &RuntimeMappings,
     sort_field_is_datetime: &mut HashMap<String, bool>,
 ) -> crate::Result<()> {
     for sort_field in sort_fields.iter() {
-        if let Some(sort_field_entry) = get_sort_by_field_entry(&sort_field.field_name, schema)? {
+        if runtime_mappings.contains_key(&sort_field.field_name) {
+            if sort_field.sort_datetime_format.is_some() {
+                return Err(SearchError::InvalidArgument(format!(
+                    "sort by runtime field `{}` does not support datetime formats",
+                    sort_field.field_name
+                )));
+            }
+            sort_field_is_datetime.insert(sort_field.field_name.to_string(), false);
+        } else if let Some(sort_field_entry) =
+            get_sort_by_field_entry(&sort_field.field_name, schema)?
+        {
             validate_sort_by_field_type(
                 sort_field_entry,
                 sort_field.sort_datetime_format.is_some(),
@@ -366,6 +399,8 @@ fn simplify_search_request_for_scroll_api(req: &SearchRequest) -> crate::Result<
         // request is simplified after initial query, and we cache the hit count, so we don't need
         // to recompute it afterward.
         count_hits: quickwit_proto::search::CountHits::Underestimate as i32,
+        fetch_fields: req.fetch_fields.clone(),
+        runtime_mappings: req.runtime_mappings.clone(),
     })
 }
 
@@ -495,6 +530,7 @@ fn validate_request(
     schema: &Schema,
     timestamp_field_name: &Option<&str>,
     search_request: &SearchRequest,
+    runtime_mappings: &RuntimeMappings,
 ) -> crate::Result<()> {
     if timestamp_field_name.is_none()
         && (search_request.start_timestamp.is_some() || search_request.end_timestamp.is_some())
@@ -509,20 +545,39 @@ fn validate_request(
     validate_requested_snippet_fields(schema, &search_request.snippet_fields)?;
 
     if let Some(agg) = search_request.aggregation_request.as_ref() {
-        let aggs: QuickwitAggregations = serde_json::from_str(agg).map_err(|_err| {
-            let err = serde_json::from_str::<tantivy::aggregation::agg_req::Aggregations>(agg)
-                .unwrap_err();
-            SearchError::InvalidAggregationRequest(err.to_string())
-        })?;
+        let aggs: QuickwitAggregations =
+            serde_json::from_str(agg).map_err(|_err| aggregation_request_error(agg))?;
 
         // ensure that the required fast fields are indeed configured as fast fields.
         let fast_field_names = aggs.fast_field_names();
         let dynamic_field = schema.get_field(DYNAMIC_FIELD_NAME).ok();
         for fast_field_name in &fast_field_names {
+            if runtime_mappings.contains_key(fast_field_name) {
+                // Only the tantivy aggregations are run on runtime fields.
+                if !matches!(aggs, QuickwitAggregations::TantivyAggregations(_)) {
+                    return Err(SearchError::InvalidAggregationRequest(format!(
+                        "runtime field `{fast_field_name}` cannot be used in requests with \
+                         `composite`, `nested`, `significant_terms` or `rare_terms` aggregations"
+                    )));
+                }
+                continue;
+            }
             check_is_fast_field(schema, fast_field_name, dynamic_field)?;
         }
     };
 
+    // Runtime fields are computed from fast fields.
+    let dynamic_field = schema.get_field(DYNAMIC_FIELD_NAME).ok();
+    for (runtime_field_name, runtime_field) in runtime_mappings {
+        for fast_field_name in runtime_field.fields() {
+            check_is_fast_field(schema, fast_field_name, dynamic_field).map_err(|err| {
+                SearchError::InvalidArgument(format!(
+                    "invalid runtime field `{runtime_field_name}`: {err}"
+                ))
+            })?;
+        }
+    }
+
     if search_request.start_offset > 10_000 {
         return Err(SearchError::InvalidArgument(format!(
             "max value for start_offset is 10_000, but got {}",
@@ -540,6 +595,50 @@ fn validate_request(
     Ok(())
 }
 
+/// Returns the error of an aggregation request that cannot be parsed into any of the
+/// [`QuickwitAggregations`] variants.
+///
+/// The untagged deserialization only keeps the error of the last variant tried, so the request is
+/// parsed again into the variant its top-level aggregations call for.
+fn aggregation_request_error(aggregation_request: &str) -> SearchError {
+    let tantivy_error_msg = || {
+        serde_json::from_str::<tantivy::aggregation::agg_req::Aggregations>(aggregation_request)
+            .err()
+            .map(|error| error.to_string())
+            .unwrap_or_else(|| "invalid aggregation request".to_string())
+    };
+    let Ok(aggregations_json) =
+        serde_json::from_str::<JsonMap<String, JsonValue>>(aggregation_request)
+    else {
+        return SearchError::InvalidAggregationRequest(tantivy_error_msg());
+    };
+    let aggregation_types: Vec<&str> = ["composite", "nested", "significant_terms", "rare_terms"]
+        .into_iter()
+        .filter(|aggregation_type| {
+            aggregations_json
+                .values()
+                .any(|aggregation_json| aggregation_json.get(aggregation_type).is_some())
+        })
+        .collect();
+    let error_opt = match aggregation_types.as_slice() {
+        [] => None,
+        ["composite"] => CompositeAggregations::try_from(aggregations_json).err(),
+        ["nested"] => NestedAggregations::try_from(aggregations_json).err(),
+        ["significant_terms"] | ["rare_terms"] | ["significant_terms", "rare_terms"] => {
+            TermFrequencyAggregations::try_from(aggregations_json).err()
+        }
+        [aggregation_type_0, aggregation_type_1, ..] => Some(anyhow::anyhow!(
+            "`{aggregation_type_0}` and `{aggregation_type_1}` aggregations cannot be used in the \
+             same request"
+        )),
+    };
+    let error_msg = match error_opt {
+        Some(error) => format!("{error:#}"),
+        None => tantivy_error_msg(),
+    };
+    SearchError::InvalidAggregationRequest(error_msg)
+}
+
 fn get_scroll_ttl_duration(search_request: &SearchRequest) -> crate::Result<Option<Duration>> {
     let Some(scroll_ttl_secs) = search_request.scroll_ttl_secs else {
         return Ok(None);
@@ -837,6 +936,7 @@ pub(crate) async fn fetch_docs_phase(
     for (client, client_jobs) in assigned_fetch_docs_jobs {
         let fetch_jobs_requests = jobs_to_fetch_docs_requests(
             snippet_request.clone(),
+            &search_request.fetch_fields,
             indexes_metas_for_leaf_search,
             client_jobs,
         )?;
@@ -1044,6 +1144,55 @@ fn finalize_aggregation(
                 .into_final_result(aggregations, searcher_context.get_aggregation_limits())?;
             serde_json::to_string(&final_aggregation_results)?
         }
+        QuickwitAggregations::NestedAggregations(aggregations) => {
+            let intermediate_aggregation_results =
+                if let Some(intermediate_aggregation_result_bytes) =
+                    intermediate_aggregation_result_bytes_opt
+                {
+                    let intermediate_aggregation_results: NestedIntermediateAggregationResults =
+                        postcard::from_bytes(&intermediate_aggregation_result_bytes)?;
+                    intermediate_aggregation_results
+                } else {
+                    Default::default()
+                };
+            let final_aggregation_results = intermediate_aggregation_results
+                .into_final_result(aggregations, searcher_context.get_aggregation_limits())?;
+            serde_json::to_string(&final_aggregation_results)?
+        }
+        QuickwitAggregations::CompositeAggregations(aggregations) => {
+            // The merge collector has already merged the intermediate results of the leaves and
+            // kept the smallest keys.
+            let intermediate_aggregation_results =
+                if let Some(intermediate_aggregation_result_bytes) =
+                    intermediate_aggregation_result_bytes_opt
+                {
+                    let intermediate_aggregation_results: CompositeIntermediateAggregationResults =
+                        postcard::from_bytes(&intermediate_aggregation_result_bytes)?;
+                    intermediate_aggregation_results
+                } else {
+                    Default::default()
+                };
+            let final_aggregation_results = intermediate_aggregation_results
+                .into_final_result(aggregations, searcher_context.get_aggregation_limits())?;
+            serde_json::to_string(&final_aggregation_results)?
+        }
+        QuickwitAggregations::TermFrequencyAggregations(aggregations) => {
+            // The merge collector has already summed the frequencies of the leaves.
+            let intermediate_aggregation_results =
+                if let Some(intermediate_aggregation_result_bytes) =
+                    intermediate_aggregation_result_bytes_opt
+                {
+                    let intermediate_aggregation_results:
+                        TermFrequencyIntermediateAggregationResults =
+                        postcard::from_bytes(&intermediate_aggregation_result_bytes)?;
+                    intermediate_aggregation_results
+                } else {
+                    Default::default()
+                };
+            let final_aggregation_results = intermediate_aggregation_results
+                .into_final_result(aggregations, searcher_context.get_aggregation_limits())?;
+            serde_json::to_string(&final_aggregation_results)?
+        }
     };
     Ok(Some(merge_aggregation_result))
 }
@@ -1239,6 +1388,40 @@ pub async fn root_search(
     search_response_result
 }
 
+/// Performs a distributed search restricted to the given splits of a single index.
+///
+/// Unlike [`root_search`], the splits are not listed from the metastore, which lets callers such
+/// as the janitor's monitors search only the splits published since their last evaluation.
+#[instrument(skip_all, fields(index_id=%index_metadata.index_id(), num_splits=%split_metadatas.len()))]
+pub async fn root_search_on_splits(
+    searcher_context: &SearcherContext,
+    mut search_request: SearchRequest,
+    index_metadata: &IndexMetadata,
+    split_metadatas: Vec<SplitMetadata>,
+    cluster_client: &ClusterClient,
+) -> crate::Result<SearchResponse> {
+    let start_instant = tokio::time::Instant::now();
+    let request_metadata =
+        validate_request_and_build_metadata(std::slice::from_ref(index_metadata), &search_request)?;
+    search_request.query_ast = serde_json::to_string(&request_metadata.query_ast_resolved)?;
+
+    // convert search_after datetime values from input datetime format to nanos.
+    convert_search_after_datetime_values(
+        &mut search_request,
+        &request_metadata.sort_fields_is_datetime,
+    )?;
+    let mut search_response = root_search_aux(
+        searcher_context,
+        &request_metadata.indexes_meta_for_leaf_search,
+        search_request,
+        split_metadatas,
+        cluster_client,
+    )
+    .await?;
+    search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
+    Ok(search_response)
+}
+
 /// Returns details on how a query would be executed
 pub async fn search_plan(
     mut search_request: SearchRequest,
@@ -1554,6 +1737,19 @@ impl<'b> QueryAstVisitor<'b> for ExtractTimestampRange<'_> {
         Ok(())
     }
 
+    fn visit_function_score(
+        &mut self,
+        function_score_query: &'b FunctionScoreQuery,
+    ) -> Result<(), Self::Err> {
+        // the filters of the score functions do not restrict the matching documents
+        self.visit(&function_score_query.query)
+    }
+
+    fn visit_nested(&mut self, _nested_query: &'b NestedQuery) -> Result<(), Self::Err> {
+        // the inner query applies to nested documents, not to the timestamp of the documents
+        Ok(())
+    }
+
     fn visit_range(&mut self, range_query: &'b RangeQuery) -> Result<(), Self::Err> {
         use std::ops::Bound;
 
@@ -1708,8 +1904,15 @@ pub fn jobs_to_leaf_request(
             .index_uris
             .push(search_index_meta.index_uri.to_string());
 
+        let split_offsets = job_group
+            .into_iter()
+            .map(|job| SplitIdAndFooterOffsets {
+                require_encryption: search_index_meta.require_encryption,
+                ..job.offsets
+            })
+            .collect();
         let leaf_search_request_ref = LeafRequestRef {
-            split_offsets: job_group.into_iter().map(|job| job.offsets).collect(),
+            split_offsets,
             doc_mapper_ord,
             index_uri_ord,
         };
@@ -1724,6 +1927,7 @@ pub fn jobs_to_leaf_request(
 /// Builds a list of [`FetchDocsRequest`], one per index, from a list of [`FetchDocsJob`].
 pub fn jobs_to_fetch_docs_requests(
     snippet_request_opt: Option<SnippetRequest>,
+    fetch_fields: &[String],
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
     jobs: Vec<FetchDocsJob>,
 ) -> crate::Result<Vec<FetchDocsRequest>> {
@@ -1748,7 +1952,10 @@ pub fn jobs_to_fetch_docs_requests(
                 .collect();
             let split_offsets: Vec<SplitIdAndFooterOffsets> = fetch_docs_jobs
                 .into_iter()
-                .map(|fetch_doc_job| fetch_doc_job.into())
+                .map(|fetch_doc_job| SplitIdAndFooterOffsets {
+                    require_encryption: index_meta.require_encryption,
+                    ..SplitIdAndFooterOffsets::from(fetch_doc_job)
+                })
                 .collect();
             let fetch_docs_req = FetchDocsRequest {
                 partial_hits,
@@ -1756,6 +1963,7 @@ pub fn jobs_to_fetch_docs_requests(
                 index_uri: index_meta.index_uri.to_string(),
                 snippet_request: snippet_request_opt.clone(),
                 doc_mapper: index_meta.doc_mapper_str.clone(),
+                fetch_fields: fetch_fields.to_vec(),
             };
             fetch_docs_requests.push(fetch_docs_req);
 
@@ -1783,6 +1991,7 @@ mod tests {
         ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError,
     };
     use quickwit_query::query_ast::{qast_helper, qast_json_helper, query_ast_from_user_text};
+    use quickwit_query::runtime_field::parse_runtime_mappings;
     use tantivy::schema::{FAST, STORED, TEXT};
 
     use super::*;
@@ -1874,6 +2083,9 @@ mod tests {
             indexing_settings,
             search_settings,
             retention_policy_opt: Default::default(),
+            rollup_policy_opt: Default::default(),
+            tiering_policy_opt: Default::default(),
+            encryption_settings_opt: Default::default(),
         })
     }
 
@@ -2046,6 +2258,9 @@ mod tests {
             indexing_settings,
             search_settings,
             retention_policy_opt: Default::default(),
+            rollup_policy_opt: Default::default(),
+            tiering_policy_opt: Default::default(),
+            encryption_settings_opt: Default::default(),
         })
     }
 
@@ -2166,11 +2381,136 @@ mod tests {
         schema_builder.add_u64_field("id", FAST);
         let schema = schema_builder.build();
         let mut sort_field_are_datetime = HashMap::new();
-        validate_sort_field_types(&schema, &sort_fields, &mut sort_field_are_datetime).unwrap();
+        validate_sort_field_types(
+            &schema,
+            &sort_fields,
+            &RuntimeMappings::new(),
+            &mut sort_field_are_datetime,
+        )
+        .unwrap();
         assert_eq!(sort_field_are_datetime.get("_doc"), Some(&false));
         assert_eq!(sort_field_are_datetime.get("_shard_doc"), Some(&false));
     }
 
+    #[test]
+    fn test_validate_request_with_runtime_mappings() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("start", FAST);
+        schema_builder.add_u64_field("end", FAST);
+        schema_builder.add_u64_field("size", STORED);
+        let schema = schema_builder.build();
+        let runtime_mappings =
+            parse_runtime_mappings(r#"{"duration_ms": {"script": "end - start"}}"#).unwrap();
+        let search_request = SearchRequest {
+            aggregation_request: Some(
+                r#"{"avg_duration": {"avg": {"field": "duration_ms"}}}"#.to_string(),
+            ),
+            max_hits: 10,
+            ..Default::default()
+        };
+        validate_request(&schema, &None, &search_request, &runtime_mappings).unwrap();
+
+        let sort_fields = vec![SortField {
+            field_name: "duration_ms".to_string(),
+            sort_order: 0,
+            sort_datetime_format: None,
+        }];
+        let mut sort_field_are_datetime = HashMap::new();
+        validate_sort_field_types(
+            &schema,
+            &sort_fields,
+            &runtime_mappings,
+            &mut sort_field_are_datetime,
+        )
+        .unwrap();
+        assert_eq!(sort_field_are_datetime.get("duration_ms"), Some(&false));
+
+        let runtime_mappings =
+            parse_runtime_mappings(r#"{"double_size": {"script": "size * 2"}}"#).unwrap();
+        let error = validate_request(&schema, &None, &SearchRequest::default(), &runtime_mappings)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SearchError::InvalidArgument(message)
+                if message.contains("invalid runtime field `double_size`")
+        ));
+
+        let search_request = SearchRequest {
+            aggregation_request: Some(
+                r#"{"pairs": {"composite": {"sources": [{"duration": {"terms": {"field": "duration_ms"}}}]}}}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let runtime_mappings =
+            parse_runtime_mappings(r#"{"duration_ms": {"script": "end - start"}}"#).unwrap();
+        let error =
+            validate_request(&schema, &None, &search_request, &runtime_mappings).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid aggregation request: runtime field `duration_ms` cannot be used in requests \
+             with `composite`, `nested`, `significant_terms` or `rare_terms` aggregations"
+        );
+    }
+
+    #[test]
+    fn test_validate_request_reports_extension_aggregation_errors() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("service", FAST);
+        let schema = schema_builder.build();
+        let validate_aggregation_request = |aggregation_request: &str| {
+            let search_request = SearchRequest {
+                aggregation_request: Some(aggregation_request.to_string()),
+                ..Default::default()
+            };
+            validate_request(&schema, &None, &search_request, &RuntimeMappings::new())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            validate_aggregation_request(
+                r#"{"pairs": {"composite": {"sources": [{"service": {"terms": {"field": "service"}}}], "size": 0}}}"#
+            ),
+            "invalid aggregation request: invalid composite aggregation `pairs`: `size` must be \
+             between 1 and 10000"
+        );
+        assert_eq!(
+            validate_aggregation_request(
+                r#"{"pairs": {"composite": {"sources": []}}, "by_item": {"nested": {"path": "items"}}}"#
+            ),
+            "invalid aggregation request: `composite` and `nested` aggregations cannot be used in \
+             the same request"
+        );
+        assert_eq!(
+            validate_aggregation_request(
+                r#"{"rare": {"rare_terms": {"field": "service"}}, "unusual": {"significant_terms": {"field": "service", "size": 0}}}"#
+            ),
+            "invalid aggregation request: invalid significant_terms aggregation `unusual`: `size` \
+             must be greater than 0"
+        );
+        assert_eq!(
+            validate_aggregation_request(
+                r#"{"unusual": {"significant_terms": {"field": "service"}}, "pairs": {"composite": {"sources": [{"service": {"terms": {"field": "service"}}}]}}}"#
+            ),
+            "invalid aggregation request: `composite` and `significant_terms` aggregations cannot \
+             be used in the same request"
+        );
+        // Tantivy aggregations can be used along with `significant_terms` aggregations.
+        let search_request = SearchRequest {
+            aggregation_request: Some(
+                r#"{"unusual": {"significant_terms": {"field": "service"}}, "services": {"terms": {"field": "service"}}}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        validate_request(&schema, &None, &search_request, &RuntimeMappings::new()).unwrap();
+        // Without extension aggregations, the error is the one of the tantivy aggregations.
+        assert!(
+            validate_aggregation_request(r#"{"services": {"unknown_type": {}}}"#)
+                .contains("unknown_type")
+        );
+    }
+
     #[test]
     fn test_validate_sort_field_types_valid() {
         let sort_fields = vec![
@@ -2190,7 +2530,13 @@ mod tests {
         schema_builder.add_u64_field("id", FAST);
         let schema = schema_builder.build();
         let mut sort_field_are_datetime = HashMap::new();
-        validate_sort_field_types(&schema, &sort_fields, &mut sort_field_are_datetime).unwrap();
+        validate_sort_field_types(
+            &schema,
+            &sort_fields,
+            &RuntimeMappings::new(),
+            &mut sort_field_are_datetime,
+        )
+        .unwrap();
         assert_eq!(sort_field_are_datetime.get("timestamp"), Some(&true));
         assert_eq!(sort_field_are_datetime.get("id"), Some(&false));
     }
@@ -2217,9 +2563,13 @@ mod tests {
             let mut sort_field_are_datetime = HashMap::new();
             sort_field_are_datetime.insert("timestamp".to_string(), false);
             sort_field_are_datetime.insert("id".to_string(), false);
-            let error =
-                validate_sort_field_types(&schema, &sort_fields, &mut sort_field_are_datetime)
-                    .unwrap_err();
+            let error = validate_sort_field_types(
+                &schema,
+                &sort_fields,
+                &RuntimeMappings::new(),
+                &mut sort_field_are_datetime,
+            )
+            .unwrap_err();
             assert_eq!(
                 error.to_string(),
                 "sort datetime field `timestamp` must be of type datetime on all indexes"
@@ -2228,9 +2578,13 @@ mod tests {
         {
             let mut sort_field_are_datetime = HashMap::new();
             sort_field_are_datetime.insert("id".to_string(), true);
-            let error =
-                validate_sort_field_types(&schema, &sort_fields, &mut sort_field_are_datetime)
-                    .unwrap_err();
+            let error = validate_sort_field_types(
+                &schema,
+                &sort_fields,
+                &RuntimeMappings::new(),
+                &mut sort_field_are_datetime,
+            )
+            .unwrap_err();
             assert_eq!(
                 error.to_string(),
                 "sort datetime field `id` must be of type datetime on all indexes"
@@ -2705,6 +3059,129 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_root_search_require_encryption() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut mock_metastore = MockMetastoreService::new();
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        index_metadata.index_config.encryption_settings_opt =
+            Some(quickwit_config::EncryptionSettings {
+                key_id: "test-key".to_string(),
+                require_encryption: true,
+            });
+        let index_uid = index_metadata.index_uid.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::for_test(vec![
+                    index_metadata.clone()
+                ]))
+            });
+        mock_metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build()];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let split_offsets = &leaf_search_req.leaf_requests[0].split_offsets;
+                assert!(split_offsets[0].require_encryption);
+
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 1,
+                    partial_hits: vec![mock_partial_hit("split1", 1, 1)],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                assert!(fetch_docs_req.split_offsets[0].require_encryption);
+
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_context = SearcherContext::for_test();
+        let search_response = root_search(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from_mock(mock_metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 1);
+        assert_eq!(search_response.hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_root_search_on_splits() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 0,
+            ..Default::default()
+        };
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let splits = vec![
+            MockSplitBuilder::new("split1")
+                .with_index_uid(&index_metadata.index_uid)
+                .build()
+                .split_metadata,
+        ];
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                assert_eq!(leaf_search_req.leaf_requests.len(), 1);
+                assert_eq!(
+                    leaf_search_req.leaf_requests[0].split_offsets[0].split_id,
+                    "split1"
+                );
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 5,
+                    num_attempted_splits: 1,
+                    num_successful_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer);
+
+        let searcher_context = SearcherContext::for_test();
+        let search_response = root_search_on_splits(
+            &searcher_context,
+            search_request,
+            &index_metadata,
+            splits,
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 5);
+        assert!(search_response.hits.is_empty());
+    }
+
     #[tokio::test]
     async fn test_root_search_multiple_splits() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
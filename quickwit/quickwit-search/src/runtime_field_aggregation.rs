@@ -0,0 +1,306 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Aggregations on runtime fields.
+//!
+//! Tantivy aggregations read the columns of the segment, and runtime fields have none. The
+//! matching documents are therefore buffered, and at harvest time the values of their runtime
+//! fields, along with the other columns used by the aggregations, are written to a temporary
+//! in-RAM index on which the aggregations are run.
+
+use quickwit_query::runtime_field::{RuntimeFieldType, RuntimeMappings, SegmentRuntimeField};
+use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
+use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use tantivy::aggregation::{AggregationLimitsGuard, DistributedAggregationCollector};
+use tantivy::columnar::{ColumnType, DynamicColumn};
+use tantivy::query::AllQuery;
+use tantivy::schema::{
+    DateOptions, DateTimePrecision, Field, Schema, SchemaBuilder, TantivyDocument, FAST, STRING,
+};
+use tantivy::{DocId, Index, SegmentReader};
+
+// Minimum memory budget accepted by tantivy's index writer.
+const RUNTIME_FIELD_AGGREGATION_MEMORY_BUDGET: usize = 15_000_000;
+
+/// Returns true if some of the fields used by the aggregations are runtime fields.
+pub(crate) fn aggregations_use_runtime_fields(
+    aggregations: &Aggregations,
+    runtime_mappings: &RuntimeMappings,
+) -> bool {
+    !runtime_mappings.is_empty()
+        && get_fast_field_names(aggregations)
+            .iter()
+            .any(|field_name| runtime_mappings.contains_key(field_name))
+}
+
+/// Segment collector of aggregations using runtime fields.
+pub(crate) struct RuntimeFieldAggregationSegmentCollector {
+    aggregations: Aggregations,
+    runtime_fields: Vec<(String, SegmentRuntimeField)>,
+    /// Fields used by the aggregations that are not runtime fields.
+    column_field_names: Vec<String>,
+    segment_reader: SegmentReader,
+    limits: AggregationLimitsGuard,
+    docs: Vec<DocId>,
+}
+
+impl RuntimeFieldAggregationSegmentCollector {
+    pub fn from_agg_req_and_reader(
+        aggregations: &Aggregations,
+        runtime_mappings: &RuntimeMappings,
+        segment_reader: &SegmentReader,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<Self> {
+        let mut runtime_fields = Vec::new();
+        let mut column_field_names = Vec::new();
+        for field_name in get_fast_field_names(aggregations) {
+            if let Some(runtime_field) = runtime_mappings.get(&field_name) {
+                let segment_runtime_field = runtime_field.for_segment(segment_reader)?;
+                runtime_fields.push((field_name, segment_runtime_field));
+            } else {
+                column_field_names.push(field_name);
+            }
+        }
+        Ok(RuntimeFieldAggregationSegmentCollector {
+            aggregations: aggregations.clone(),
+            runtime_fields,
+            column_field_names,
+            segment_reader: segment_reader.clone(),
+            limits: limits.clone(),
+            docs: Vec::new(),
+        })
+    }
+
+    pub fn collect_block(&mut self, docs: &[DocId]) {
+        self.docs.extend_from_slice(docs);
+    }
+
+    pub fn collect(&mut self, doc: DocId) {
+        self.docs.push(doc);
+    }
+
+    pub fn harvest(self) -> tantivy::Result<IntermediateAggregationResults> {
+        let mut schema_builder = Schema::builder();
+        let runtime_fields: Vec<(Field, SegmentRuntimeField)> = self
+            .runtime_fields
+            .into_iter()
+            .map(|(field_name, runtime_field)| {
+                let field = match runtime_field.field_type() {
+                    RuntimeFieldType::F64 => schema_builder.add_f64_field(&field_name, FAST),
+                    RuntimeFieldType::I64 => schema_builder.add_i64_field(&field_name, FAST),
+                };
+                (field, runtime_field)
+            })
+            .collect();
+        let mut columns: Vec<(Field, DynamicColumn)> = Vec::new();
+        for field_name in &self.column_field_names {
+            let column_handles = self
+                .segment_reader
+                .fast_fields()
+                .dynamic_column_handles(field_name)?;
+            // In dynamic mode, a field can have columns of different types: only the columns with
+            // the type of the first one are kept.
+            let Some(column_type) = column_handles.first().map(|handle| handle.column_type())
+            else {
+                continue;
+            };
+            let field = add_field_for_column_type(&mut schema_builder, field_name, column_type);
+            for column_handle in column_handles {
+                if column_handle.column_type() == column_type {
+                    columns.push((field, column_handle.open()?));
+                }
+            }
+        }
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer =
+            index.writer_with_num_threads(1, RUNTIME_FIELD_AGGREGATION_MEMORY_BUDGET)?;
+        let mut text_buffer = String::new();
+        let mut bytes_buffer = Vec::new();
+        for doc in self.docs {
+            let mut document = TantivyDocument::default();
+            for (field, runtime_field) in &runtime_fields {
+                let Some(value) = runtime_field.value(doc) else {
+                    continue;
+                };
+                match runtime_field.field_type() {
+                    RuntimeFieldType::F64 => document.add_f64(*field, value),
+                    RuntimeFieldType::I64 => document.add_i64(*field, value as i64),
+                }
+            }
+            for (field, column) in &columns {
+                copy_column_values(
+                    column,
+                    doc,
+                    *field,
+                    &mut document,
+                    &mut text_buffer,
+                    &mut bytes_buffer,
+                )?;
+            }
+            index_writer.add_document(document)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let collector = DistributedAggregationCollector::from_aggs(self.aggregations, self.limits);
+        searcher.search(&AllQuery, &collector)
+    }
+}
+
+fn add_field_for_column_type(
+    schema_builder: &mut SchemaBuilder,
+    field_name: &str,
+    column_type: ColumnType,
+) -> Field {
+    match column_type {
+        ColumnType::I64 => schema_builder.add_i64_field(field_name, FAST),
+        ColumnType::U64 => schema_builder.add_u64_field(field_name, FAST),
+        ColumnType::F64 => schema_builder.add_f64_field(field_name, FAST),
+        ColumnType::Bool => schema_builder.add_bool_field(field_name, FAST),
+        ColumnType::IpAddr => schema_builder.add_ip_addr_field(field_name, FAST),
+        ColumnType::DateTime => {
+            let date_options = DateOptions::default()
+                .set_fast()
+                .set_precision(DateTimePrecision::Nanoseconds);
+            schema_builder.add_date_field(field_name, date_options)
+        }
+        ColumnType::Bytes => schema_builder.add_bytes_field(field_name, FAST),
+        ColumnType::Str => schema_builder.add_text_field(field_name, STRING | FAST),
+    }
+}
+
+fn copy_column_values(
+    column: &DynamicColumn,
+    doc: DocId,
+    field: Field,
+    document: &mut TantivyDocument,
+    text_buffer: &mut String,
+    bytes_buffer: &mut Vec<u8>,
+) -> tantivy::Result<()> {
+    match column {
+        DynamicColumn::Bool(column) => {
+            for value in column.values_for_doc(doc) {
+                document.add_bool(field, value);
+            }
+        }
+        DynamicColumn::I64(column) => {
+            for value in column.values_for_doc(doc) {
+                document.add_i64(field, value);
+            }
+        }
+        DynamicColumn::U64(column) => {
+            for value in column.values_for_doc(doc) {
+                document.add_u64(field, value);
+            }
+        }
+        DynamicColumn::F64(column) => {
+            for value in column.values_for_doc(doc) {
+                document.add_f64(field, value);
+            }
+        }
+        DynamicColumn::IpAddr(column) => {
+            for value in column.values_for_doc(doc) {
+                document.add_ip_addr(field, value);
+            }
+        }
+        DynamicColumn::DateTime(column) => {
+            for value in column.values_for_doc(doc) {
+                document.add_date(field, value);
+            }
+        }
+        DynamicColumn::Bytes(column) => {
+            for term_ord in column.term_ords(doc) {
+                bytes_buffer.clear();
+                column.ord_to_bytes(term_ord, bytes_buffer)?;
+                document.add_bytes(field, bytes_buffer.as_slice());
+            }
+        }
+        DynamicColumn::Str(column) => {
+            for term_ord in column.term_ords(doc) {
+                text_buffer.clear();
+                column.ord_to_str(term_ord, text_buffer)?;
+                document.add_text(field, &text_buffer);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_query::runtime_field::parse_runtime_mappings;
+    use serde_json::json;
+    use tantivy::aggregation::agg_result::AggregationResults;
+    use tantivy::doc;
+
+    use super::*;
+
+    #[test]
+    fn test_runtime_field_aggregation_collector() {
+        let mut schema_builder = Schema::builder();
+        let service = schema_builder.add_text_field("service", STRING | FAST);
+        let start = schema_builder.add_u64_field("start", FAST);
+        let end = schema_builder.add_u64_field("end", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for (service_val, start_val, end_val) in [
+            ("api", 10u64, 30u64),
+            ("api", 20, 60),
+            ("db", 0, 5),
+            ("db", 50, 150),
+        ] {
+            index_writer
+                .add_document(doc!(service => service_val, start => start_val, end => end_val))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0);
+
+        let runtime_mappings =
+            parse_runtime_mappings(r#"{"duration_ms": {"script": "end - start"}}"#).unwrap();
+        let aggregations: Aggregations = serde_json::from_value(json!({
+            "by_service": {
+                "terms": {"field": "service", "order": {"_key": "asc"}},
+                "aggs": {"avg_duration": {"avg": {"field": "duration_ms"}}}
+            }
+        }))
+        .unwrap();
+        assert!(aggregations_use_runtime_fields(
+            &aggregations,
+            &runtime_mappings
+        ));
+        let limits = AggregationLimitsGuard::default();
+        let mut collector = RuntimeFieldAggregationSegmentCollector::from_agg_req_and_reader(
+            &aggregations,
+            &runtime_mappings,
+            segment_reader,
+            &limits,
+        )
+        .unwrap();
+        // The last document does not match the query.
+        collector.collect_block(&[0, 1]);
+        collector.collect(2);
+        let intermediate_results = collector.harvest().unwrap();
+        let results: AggregationResults = intermediate_results
+            .into_final_result(aggregations, limits)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(results).unwrap()["by_service"]["buckets"],
+            json!([
+                {"key": "api", "doc_count": 2, "avg_duration": {"value": 30.0}},
+                {"key": "db", "doc_count": 1, "avg_duration": {"value": 5.0}},
+            ])
+        );
+    }
+}
@@ -86,7 +86,14 @@ pub async fn root_search_stream(
     })?;
 
     let index_uri: &Uri = &index_config.index_uri;
-    let leaf_search_jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
+    let leaf_search_jobs: Vec<SearchJob> = split_metadatas
+        .iter()
+        .map(|split_metadata| {
+            let mut job = SearchJob::from(split_metadata);
+            job.offsets.require_encryption = index_config.requires_encryption();
+            job
+        })
+        .collect();
     let assigned_leaf_search_jobs = cluster_client
         .search_job_placer
         .assign_jobs(leaf_search_jobs, &HashSet::default())
@@ -32,7 +32,7 @@ use quickwit_proto::search::{
     SnippetRequest,
 };
 use quickwit_storage::{
-    MemorySizedCache, QuickwitCache, SplitCache, StorageCache, StorageResolver,
+    DiskSliceCache, MemorySizedCache, QuickwitCache, SplitCache, StorageCache, StorageResolver,
 };
 use tantivy::aggregation::AggregationLimitsGuard;
 use tokio::sync::Semaphore;
@@ -48,6 +48,7 @@ use crate::root::fetch_docs_phase;
 use crate::scroll_context::{MiniKV, ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_permit_provider::SearchPermitProvider;
 use crate::search_stream::{leaf_search_stream, root_search_stream};
+use crate::split_warmup::SplitFooterWarmer;
 use crate::{fetch_docs, root_search, search_plan, ClusterClient, SearchError};
 
 #[derive(Clone)]
@@ -58,6 +59,7 @@ pub struct SearchServiceImpl {
     cluster_client: ClusterClient,
     searcher_context: Arc<SearcherContext>,
     search_after_cache: MiniKV,
+    split_footer_warmer_opt: Option<SplitFooterWarmer>,
 }
 
 /// Trait representing a search service.
@@ -133,7 +135,9 @@ pub trait SearchService: 'static + Send + Sync {
     async fn get_kv(&self, get_kv: GetKvRequest) -> Option<Vec<u8>>;
 
     /// Indexers call report_splits to inform searchers node about the presence of a split, which
-    /// would then be considered as a candidate for the searcher split cache.
+    /// would then be considered as a candidate for the searcher split cache. The metastore node
+    /// also reports the published splits with their footer offsets, so that their footer can be
+    /// warmed.
     async fn report_splits(&self, report_splits: ReportSplitsRequest) -> ReportSplitsResponse;
 
     /// Return the list of fields for a given or multiple indices.
@@ -160,12 +164,14 @@ impl SearchServiceImpl {
         cluster_client: ClusterClient,
         searcher_context: Arc<SearcherContext>,
     ) -> Self {
+        let split_footer_warmer_opt = SplitFooterWarmer::spawn_if_enabled(searcher_context.clone());
         SearchServiceImpl {
             metastore,
             storage_resolver,
             cluster_client,
             searcher_context,
             search_after_cache: MiniKV::default(),
+            split_footer_warmer_opt,
         }
     }
 }
@@ -240,6 +246,7 @@ impl SearchService for SearchServiceImpl {
             &fetch_docs_request.split_offsets,
             doc_mapper,
             snippet_request_opt,
+            &fetch_docs_request.fetch_fields,
         )
         .await?;
 
@@ -333,6 +340,9 @@ impl SearchService for SearchServiceImpl {
     }
 
     async fn report_splits(&self, report_splits: ReportSplitsRequest) -> ReportSplitsResponse {
+        if let Some(split_footer_warmer) = &self.split_footer_warmer_opt {
+            split_footer_warmer.warm_splits(&report_splits.report_splits);
+        }
         if let Some(split_cache) = self.searcher_context.split_cache_opt.as_ref() {
             split_cache.report_splits(report_splits.report_splits);
         }
@@ -470,10 +480,14 @@ pub struct SearcherContext {
     pub leaf_search_cache: LeafSearchCache,
     /// Search split cache. `None` if no split cache is configured.
     pub split_cache_opt: Option<Arc<SplitCache>>,
+    /// On disk cache of split footers and fast field slices. `None` if not configured.
+    pub disk_slice_cache_opt: Option<Arc<DiskSliceCache>>,
     /// List fields cache. Caches the list fields response for a given split.
     pub list_fields_cache: ListFieldsCache,
     /// The aggregation limits are passed to limit the memory usage.
     pub aggregation_limit: AggregationLimitsGuard,
+    /// Storage resolver used to open the splits stored outside of their index URI.
+    pub storage_resolver: StorageResolver,
 }
 
 impl std::fmt::Debug for SearcherContext {
@@ -486,14 +500,21 @@ impl std::fmt::Debug for SearcherContext {
 }
 
 impl SearcherContext {
-    #[cfg(test)]
+    #[cfg(any(test, feature = "testsuite"))]
     pub fn for_test() -> SearcherContext {
         let searcher_config = SearcherConfig::default();
-        SearcherContext::new(searcher_config, None)
+        SearcherContext::new(searcher_config, None, None, StorageResolver::unconfigured())
     }
 
-    /// Creates a new searcher context, given a searcher config, and an optional `SplitCache`.
-    pub fn new(searcher_config: SearcherConfig, split_cache_opt: Option<Arc<SplitCache>>) -> Self {
+    /// Creates a new searcher context, given a searcher config, an optional `SplitCache`, an
+    /// optional `DiskSliceCache`, and the storage resolver used to open splits moved to a storage
+    /// tier.
+    pub fn new(
+        searcher_config: SearcherConfig,
+        split_cache_opt: Option<Arc<SplitCache>>,
+        disk_slice_cache_opt: Option<Arc<DiskSliceCache>>,
+        storage_resolver: StorageResolver,
+    ) -> Self {
         let capacity_in_bytes = searcher_config.split_footer_cache_capacity.as_u64() as usize;
         let global_split_footer_cache = MemorySizedCache::with_capacity_in_bytes(
             capacity_in_bytes,
@@ -525,7 +546,9 @@ impl SearcherContext {
             leaf_search_cache,
             list_fields_cache,
             split_cache_opt,
+            disk_slice_cache_opt,
             aggregation_limit,
+            storage_resolver,
         }
     }
 
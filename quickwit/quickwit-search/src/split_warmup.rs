@@ -0,0 +1,373 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytesize::ByteSize;
+use quickwit_common::pubsub::EventSubscriber;
+use quickwit_common::rate_limiter::{RateLimiter, RateLimiterSettings};
+use quickwit_common::tower::ConstantRate;
+use quickwit_common::uri::Uri;
+use quickwit_metastore::{
+    IndexMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, SplitMetadata, SplitState,
+};
+use quickwit_proto::metastore::{
+    IndexMetadataRequest, ListSplitsRequest, MetastoreService, MetastoreServiceClient,
+    PublishSplitsRequest,
+};
+use quickwit_proto::search::{ReportSplit, ReportSplitsRequest, SplitIdAndFooterOffsets};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::leaf::{get_split_footer_from_cache_or_fetch, resolve_split_storage};
+use crate::{SearchJobPlacer, SearcherContext};
+
+/// Maximum number of splits waiting to be warmed. Splits reported while the queue is full are
+/// not warmed.
+const SPLIT_WARMUP_QUEUE_CAPACITY: usize = 1_000;
+
+/// Reports the splits published through the metastore to the searchers owning them, along with
+/// their footer offsets, so that they can warm the hotcache of the splits before they get
+/// searched.
+///
+/// The owner of a split is picked by the [`SearchJobPlacer`], with the same rendezvous hashing as
+/// the one used to place leaf search requests.
+#[derive(Clone)]
+pub struct PublishedSplitsReporter {
+    metastore: MetastoreServiceClient,
+    search_job_placer: SearchJobPlacer,
+}
+
+impl PublishedSplitsReporter {
+    /// Creates a new reporter.
+    pub fn new(metastore: MetastoreServiceClient, search_job_placer: SearchJobPlacer) -> Self {
+        Self {
+            metastore,
+            search_job_placer,
+        }
+    }
+
+    async fn report_splits_request(
+        &self,
+        publish_splits_request: PublishSplitsRequest,
+    ) -> anyhow::Result<ReportSplitsRequest> {
+        let index_uid = publish_splits_request.index_uid().clone();
+        let index_metadata_request = IndexMetadataRequest::for_index_uid(index_uid.clone());
+        let index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+
+        let list_splits_query = ListSplitsQuery::for_index(index_uid)
+            .with_split_state(SplitState::Published)
+            .with_split_ids(publish_splits_request.staged_split_ids);
+        let list_splits_request =
+            ListSplitsRequest::try_from_list_splits_query(&list_splits_query)?;
+        let splits_metadata: Vec<SplitMetadata> = self
+            .metastore
+            .list_splits(list_splits_request)
+            .await?
+            .collect_splits_metadata()
+            .await?;
+
+        let report_splits = splits_metadata
+            .into_iter()
+            .map(|split_metadata| ReportSplit {
+                storage_uri: split_metadata
+                    .storage_uri(index_metadata.index_uri())
+                    .to_string(),
+                split_footer_start: Some(split_metadata.footer_offsets.start),
+                split_footer_end: Some(split_metadata.footer_offsets.end),
+                split_id: split_metadata.split_id,
+            })
+            .collect();
+        Ok(ReportSplitsRequest { report_splits })
+    }
+}
+
+#[async_trait]
+impl EventSubscriber<PublishSplitsRequest> for PublishedSplitsReporter {
+    async fn handle_event(&mut self, publish_splits_request: PublishSplitsRequest) {
+        if publish_splits_request.staged_split_ids.is_empty() {
+            return;
+        }
+        match self.report_splits_request(publish_splits_request).await {
+            Ok(report_splits_request) => {
+                self.search_job_placer
+                    .handle_event(report_splits_request)
+                    .await;
+            }
+            Err(error) => {
+                warn!(error=%error, "failed to report published splits to searchers");
+            }
+        }
+    }
+}
+
+/// Warms the hotcache and footer of the published splits reported to this searcher, in the
+/// background and within a bandwidth limit.
+#[derive(Clone)]
+pub struct SplitFooterWarmer {
+    split_tx: mpsc::Sender<SplitIdAndFooterOffsets>,
+}
+
+impl SplitFooterWarmer {
+    /// Spawns the task warming the split footers if the searcher enables published split warmup
+    /// in its configuration. Returns `None` otherwise.
+    pub fn spawn_if_enabled(searcher_context: Arc<SearcherContext>) -> Option<Self> {
+        let bandwidth = searcher_context
+            .searcher_config
+            .published_split_warmup_bandwidth?;
+        Some(Self::spawn(searcher_context, bandwidth))
+    }
+
+    /// Spawns the task warming the split footers, which fetches at most `bandwidth` bytes per
+    /// second.
+    pub fn spawn(searcher_context: Arc<SearcherContext>, bandwidth: ByteSize) -> Self {
+        let (split_tx, split_rx) = mpsc::channel(SPLIT_WARMUP_QUEUE_CAPACITY);
+        let rate_limiter_settings = RateLimiterSettings {
+            burst_limit: bandwidth.as_u64(),
+            rate_limit: ConstantRate::bytes_per_sec(bandwidth),
+            refill_period: Duration::from_millis(100),
+        };
+        tokio::spawn(warm_split_footers_loop(
+            searcher_context,
+            RateLimiter::from_settings(rate_limiter_settings),
+            split_rx,
+        ));
+        Self { split_tx }
+    }
+
+    /// Enqueues the reported splits that carry footer offsets. Splits are dropped if the queue
+    /// is full: warming is only an optimization.
+    pub fn warm_splits(&self, report_splits: &[ReportSplit]) {
+        for report_split in report_splits {
+            let (Some(split_footer_start), Some(split_footer_end)) = (
+                report_split.split_footer_start,
+                report_split.split_footer_end,
+            ) else {
+                continue;
+            };
+            let split_and_footer_offsets = SplitIdAndFooterOffsets {
+                split_id: report_split.split_id.clone(),
+                split_footer_start,
+                split_footer_end,
+                timestamp_start: None,
+                timestamp_end: None,
+                num_docs: 0,
+                storage_uri: Some(report_split.storage_uri.clone()),
+            };
+            if self.split_tx.try_send(split_and_footer_offsets).is_err() {
+                debug!(split_id=%report_split.split_id, "split warmup queue is full");
+            }
+        }
+    }
+}
+
+async fn warm_split_footers_loop(
+    searcher_context: Arc<SearcherContext>,
+    mut rate_limiter: RateLimiter,
+    mut split_rx: mpsc::Receiver<SplitIdAndFooterOffsets>,
+) {
+    let burst_limit = rate_limiter.available_permits().max(1);
+
+    while let Some(split_and_footer_offsets) = split_rx.recv().await {
+        if searcher_context
+            .split_footer_cache
+            .get(&split_and_footer_offsets.split_id)
+            .is_some()
+        {
+            continue;
+        }
+        let mut num_bytes_to_acquire = split_and_footer_offsets
+            .split_footer_end
+            .saturating_sub(split_and_footer_offsets.split_footer_start);
+
+        // Footers larger than the burst limit are acquired in several chunks.
+        while num_bytes_to_acquire > 0 {
+            let num_permits = num_bytes_to_acquire.min(burst_limit);
+
+            if let Err(wait) = rate_limiter.acquire_with_duration(num_permits) {
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+            num_bytes_to_acquire -= num_permits;
+        }
+        if let Err(error) = warm_split_footer(&searcher_context, &split_and_footer_offsets).await {
+            warn!(
+                split_id=%split_and_footer_offsets.split_id,
+                error=?error,
+                "failed to warm split footer"
+            );
+        }
+    }
+}
+
+async fn warm_split_footer(
+    searcher_context: &SearcherContext,
+    split_and_footer_offsets: &SplitIdAndFooterOffsets,
+) -> anyhow::Result<()> {
+    let storage_uri_str = split_and_footer_offsets
+        .storage_uri
+        .as_deref()
+        .expect("reported splits should have a storage URI");
+    let storage_uri = Uri::from_str(storage_uri_str)?;
+    let storage = searcher_context
+        .storage_resolver
+        .resolve(&storage_uri)
+        .await?;
+    let split_storage =
+        resolve_split_storage(searcher_context, storage, split_and_footer_offsets).await?;
+    get_split_footer_from_cache_or_fetch(
+        split_storage,
+        split_and_footer_offsets,
+        &searcher_context.split_footer_cache,
+        searcher_context.disk_slice_cache_opt.as_deref(),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use quickwit_common::ServiceStream;
+    use quickwit_config::SearcherConfig;
+    use quickwit_indexing::MockSplitBuilder;
+    use quickwit_metastore::{IndexMetadata, ListSplitsResponseExt};
+    use quickwit_proto::metastore::{
+        IndexMetadataResponse, ListSplitsResponse, MockMetastoreService,
+    };
+    use quickwit_proto::search::ReportSplitsResponse;
+    use quickwit_storage::StorageResolver;
+
+    use super::*;
+    use crate::{searcher_pool_for_test, MockSearchService};
+
+    #[tokio::test]
+    async fn test_published_splits_reporter() {
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid = index_metadata.index_uid.clone();
+
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_index_metadata()
+            .return_once(move |_| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(&index_metadata).unwrap())
+            });
+        let index_uid_clone = index_uid.clone();
+        mock_metastore
+            .expect_list_splits()
+            .return_once(move |list_splits_request| {
+                let list_splits_query =
+                    list_splits_request.deserialize_list_splits_query().unwrap();
+                assert_eq!(list_splits_query.split_ids.unwrap(), ["split-1"]);
+
+                let mut split = MockSplitBuilder::new("split-1")
+                    .with_index_uid(&index_uid_clone)
+                    .build();
+                split.split_metadata.footer_offsets = 100..200;
+                let splits_response = ListSplitsResponse::try_from_splits(vec![split]).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_report_splits()
+            .return_once(|report_splits_request| {
+                assert_eq!(
+                    report_splits_request.report_splits,
+                    [ReportSplit {
+                        split_id: "split-1".to_string(),
+                        storage_uri: "ram:///indexes/test-index".to_string(),
+                        split_footer_start: Some(100),
+                        split_footer_end: Some(200),
+                    }]
+                );
+                ReportSplitsResponse {}
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let mut reporter = PublishedSplitsReporter::new(
+            MetastoreServiceClient::from_mock(mock_metastore),
+            SearchJobPlacer::new(searcher_pool),
+        );
+        // Publishing no new split is a no-op.
+        reporter
+            .handle_event(PublishSplitsRequest {
+                index_uid: Some(index_uid.clone()),
+                ..Default::default()
+            })
+            .await;
+        reporter
+            .handle_event(PublishSplitsRequest {
+                index_uid: Some(index_uid),
+                staged_split_ids: vec!["split-1".to_string()],
+                ..Default::default()
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_split_footer_warmer() {
+        let storage_uri = Uri::for_test("ram:///indexes/test-index");
+        let storage_resolver = StorageResolver::for_test();
+        let storage = storage_resolver.resolve(&storage_uri).await.unwrap();
+        storage
+            .put(
+                Path::new("split-1.split"),
+                Box::new(b"split-bodyfooter".to_vec()),
+            )
+            .await
+            .unwrap();
+        let searcher_context = Arc::new(SearcherContext::new(
+            SearcherConfig::default(),
+            None,
+            None,
+            storage_resolver,
+        ));
+        // Published split warmup is disabled by default.
+        assert!(SplitFooterWarmer::spawn_if_enabled(searcher_context.clone()).is_none());
+
+        let warmer = SplitFooterWarmer::spawn(searcher_context.clone(), ByteSize::b(4));
+        warmer.warm_splits(&[
+            ReportSplit {
+                split_id: "split-1".to_string(),
+                storage_uri: storage_uri.to_string(),
+                split_footer_start: Some(10),
+                split_footer_end: Some(16),
+            },
+            // Splits reported at staging time have no footer offsets and are not warmed.
+            ReportSplit {
+                split_id: "split-2".to_string(),
+                storage_uri: storage_uri.to_string(),
+                split_footer_start: None,
+                split_footer_end: None,
+            },
+        ]);
+        for _ in 0..100 {
+            if searcher_context.split_footer_cache.get("split-1").is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let footer_bytes = searcher_context.split_footer_cache.get("split-1").unwrap();
+        assert_eq!(footer_bytes.as_slice(), b"footer");
+        assert!(searcher_context.split_footer_cache.get("split-2").is_none());
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SQL queries.
+//!
+//! A practical subset of SQL `SELECT` statements is translated into search requests: the `WHERE`
+//! clause becomes a [`QueryAst`](quickwit_query::query_ast::QueryAst), `GROUP BY` becomes nested
+//! terms and date histogram aggregations, and the hits or the buckets of the response are
+//! converted back into rows.
+
+mod parser;
+mod planner;
+
+use std::collections::HashSet;
+
+use quickwit_proto::metastore::MetastoreServiceClient;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use self::parser::parse_sql_query;
+use self::planner::plan_sql_query;
+use crate::{resolve_index_patterns, SearchError, SearchService};
+
+/// Output format of the SQL endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Request of the SQL endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SqlRequest {
+    /// SQL `SELECT` statement.
+    pub query: String,
+    /// Output format of the rows.
+    #[serde(default)]
+    pub format: SqlFormat,
+}
+
+/// Rows returned by a SQL query.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SqlResponse {
+    /// Names of the columns.
+    pub columns: Vec<String>,
+    /// Rows, holding one value per column.
+    #[schema(value_type = Vec<Vec<Object>>)]
+    pub rows: Vec<Vec<JsonValue>>,
+    /// Number of documents matching the `WHERE` clause.
+    pub num_hits: u64,
+}
+
+impl SqlResponse {
+    /// Formats the columns and the rows as CSV, starting with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let header = self.columns.iter().map(|column| csv_field(column));
+        write_csv_line(&mut csv, header);
+        for row in &self.rows {
+            let fields = row.iter().map(|value| match value {
+                JsonValue::Null => String::new(),
+                JsonValue::String(value_str) => csv_field(value_str),
+                other => csv_field(&other.to_string()),
+            });
+            write_csv_line(&mut csv, fields);
+        }
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv_line(csv: &mut String, fields: impl Iterator<Item = String>) {
+    for (field_idx, field) in fields.enumerate() {
+        if field_idx > 0 {
+            csv.push(',');
+        }
+        csv.push_str(&field);
+    }
+    csv.push('\n');
+}
+
+/// Runs a SQL query.
+pub async fn sql(
+    sql_request: SqlRequest,
+    search_service: &dyn SearchService,
+    mut metastore: MetastoreServiceClient,
+) -> crate::Result<SqlResponse> {
+    let sql_query = parse_sql_query(&sql_request.query)
+        .map_err(|error| SearchError::InvalidQuery(format!("invalid SQL query: {error}")))?;
+    let indexes_metadata =
+        resolve_index_patterns(&sql_query.index_id_patterns, &mut metastore).await?;
+    // The time range can only be pushed down if the indexes share the same timestamp field.
+    let timestamp_fields: HashSet<Option<&str>> = indexes_metadata
+        .iter()
+        .map(|index_metadata| {
+            index_metadata
+                .index_config
+                .doc_mapping
+                .timestamp_field
+                .as_deref()
+        })
+        .collect();
+    let timestamp_field_opt = if timestamp_fields.len() == 1 {
+        timestamp_fields.into_iter().next().flatten()
+    } else {
+        None
+    };
+    let sql_plan = plan_sql_query(sql_query, timestamp_field_opt)
+        .map_err(|error| SearchError::InvalidQuery(format!("invalid SQL query: {error}")))?;
+    let search_response = search_service
+        .root_search(sql_plan.search_request.clone())
+        .await?;
+    sql_plan.into_response(search_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_sql_response_to_csv() {
+        let sql_response = SqlResponse {
+            columns: vec!["service".to_string(), "COUNT(*)".to_string()],
+            rows: vec![
+                vec![json!("api"), json!(3)],
+                vec![json!("say \"hi\", db"), json!(1.5)],
+                vec![JsonValue::Null, json!({"a": true})],
+            ],
+            num_hits: 4,
+        };
+        assert_eq!(
+            sql_response.to_csv(),
+            "service,COUNT(*)\napi,3\n\"say \"\"hi\"\", db\",1.5\n,\"{\"\"a\"\":true}\"\n"
+        );
+    }
+}
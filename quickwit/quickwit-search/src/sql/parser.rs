@@ -0,0 +1,921 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of the subset of SQL supported by the SQL endpoint.
+
+use anyhow::{bail, Context};
+use quickwit_proto::search::SortOrder;
+
+const RESERVED_KEYWORDS: &[&str] = &[
+    "AND", "AS", "ASC", "BETWEEN", "BY", "DESC", "DISTINCT", "FALSE", "FROM", "GROUP", "IN", "IS",
+    "LIKE", "LIMIT", "NOT", "NULL", "OFFSET", "OR", "ORDER", "SELECT", "TRUE", "WHERE",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SqlQuery {
+    pub select_items: Vec<SelectItem>,
+    pub index_id_patterns: Vec<String>,
+    pub where_clause: Option<Condition>,
+    pub group_by: Vec<Expr>,
+    pub order_by: Vec<OrderByItem>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SelectItem {
+    Wildcard,
+    Expr { expr: Expr, alias: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name.to_ascii_uppercase().as_str() {
+            "COUNT" => AggregateFunction::Count,
+            "SUM" => AggregateFunction::Sum,
+            "AVG" => AggregateFunction::Avg,
+            "MIN" => AggregateFunction::Min,
+            "MAX" => AggregateFunction::Max,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Expr {
+    Field(String),
+    /// `field` is `None` for `COUNT(*)`.
+    Aggregate {
+        function: AggregateFunction,
+        field: Option<String>,
+        distinct: bool,
+    },
+    /// `DATE_HISTOGRAM(field, '1h')` or `DATE_TRUNC('hour', field)`.
+    DateHistogram {
+        field: String,
+        fixed_interval: String,
+    },
+}
+
+impl Expr {
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self, Expr::Aggregate { .. })
+    }
+
+    /// Name of the column of the expression when it has no alias.
+    pub fn column_name(&self) -> String {
+        match self {
+            Expr::Field(field) => field.clone(),
+            Expr::Aggregate {
+                function,
+                field,
+                distinct,
+            } => {
+                let distinct_str = if *distinct { "DISTINCT " } else { "" };
+                let field_str = field.as_deref().unwrap_or("*");
+                format!("{}({distinct_str}{field_str})", function.name())
+            }
+            Expr::DateHistogram {
+                field,
+                fixed_interval,
+            } => format!("DATE_HISTOGRAM({field}, '{fixed_interval}')"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum OrderByTarget {
+    Expr(Expr),
+    /// 1-based position of a selected column.
+    Position(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OrderByItem {
+    pub target: OrderByTarget,
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Literal {
+    String(String),
+    Number(serde_json::Number),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl CompareOp {
+    /// Returns the operator to use when the operands are swapped.
+    fn flip(self) -> Self {
+        match self {
+            CompareOp::Eq => CompareOp::Eq,
+            CompareOp::NotEq => CompareOp::NotEq,
+            CompareOp::Lt => CompareOp::Gt,
+            CompareOp::Lte => CompareOp::Gte,
+            CompareOp::Gt => CompareOp::Lt,
+            CompareOp::Gte => CompareOp::Lte,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Condition {
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    Not(Box<Condition>),
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: Literal,
+    },
+    Between {
+        field: String,
+        negated: bool,
+        low: Literal,
+        high: Literal,
+    },
+    In {
+        field: String,
+        negated: bool,
+        values: Vec<Literal>,
+    },
+    Like {
+        field: String,
+        negated: bool,
+        pattern: String,
+    },
+    IsNull {
+        field: String,
+        negated: bool,
+    },
+    /// `QUERY('user query')` or `MATCH(field, 'user query')`.
+    UserQuery {
+        user_text: String,
+        default_fields: Option<Vec<String>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    QuotedIdent(String),
+    String(String),
+    Number(String),
+    Star,
+    Comma,
+    LeftParen,
+    RightParen,
+    Minus,
+    Semicolon,
+    Op(CompareOp),
+}
+
+fn is_ident_start(chr: char) -> bool {
+    chr.is_ascii_alphabetic() || chr == '_' || chr == '@'
+}
+
+fn is_ident_char(chr: char) -> bool {
+    chr.is_ascii_alphanumeric() || matches!(chr, '_' | '@' | '.' | '-' | '*')
+}
+
+fn tokenize(sql: &str) -> anyhow::Result<Vec<Token>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let chr = chars[pos];
+        if chr.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+        if chr == '-' && chars.get(pos + 1) == Some(&'-') {
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+            continue;
+        }
+        if is_ident_start(chr) {
+            let start = pos;
+            while pos < chars.len() && is_ident_char(chars[pos]) {
+                pos += 1;
+            }
+            tokens.push(Token::Ident(chars[start..pos].iter().collect()));
+            continue;
+        }
+        if chr.is_ascii_digit() {
+            let start = pos;
+            let skip_digits = |mut pos: usize| {
+                while pos < chars.len() && chars[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                pos
+            };
+            pos = skip_digits(pos);
+            if chars.get(pos) == Some(&'.') {
+                pos = skip_digits(pos + 1);
+            }
+            if matches!(chars.get(pos), Some('e' | 'E')) {
+                pos += 1;
+                if matches!(chars.get(pos), Some('+' | '-')) {
+                    pos += 1;
+                }
+                pos = skip_digits(pos);
+            }
+            tokens.push(Token::Number(chars[start..pos].iter().collect()));
+            continue;
+        }
+        if matches!(chr, '\'' | '"' | '`') {
+            let mut value = String::new();
+            pos += 1;
+            loop {
+                let Some(&next_chr) = chars.get(pos) else {
+                    bail!("unterminated quoted string `{chr}{value}`");
+                };
+                pos += 1;
+                if next_chr == chr {
+                    // A doubled quote is an escaped quote.
+                    if chars.get(pos) == Some(&chr) {
+                        value.push(chr);
+                        pos += 1;
+                        continue;
+                    }
+                    break;
+                }
+                value.push(next_chr);
+            }
+            let token = if chr == '\'' {
+                Token::String(value)
+            } else {
+                Token::QuotedIdent(value)
+            };
+            tokens.push(token);
+            continue;
+        }
+        let next_chr = chars.get(pos + 1).copied();
+        let (token, len) = match (chr, next_chr) {
+            ('<', Some('=')) => (Token::Op(CompareOp::Lte), 2),
+            ('>', Some('=')) => (Token::Op(CompareOp::Gte), 2),
+            ('<', Some('>')) | ('!', Some('=')) => (Token::Op(CompareOp::NotEq), 2),
+            ('<', _) => (Token::Op(CompareOp::Lt), 1),
+            ('>', _) => (Token::Op(CompareOp::Gt), 1),
+            ('=', _) => (Token::Op(CompareOp::Eq), 1),
+            ('*', _) => (Token::Star, 1),
+            (',', _) => (Token::Comma, 1),
+            ('(', _) => (Token::LeftParen, 1),
+            (')', _) => (Token::RightParen, 1),
+            ('-', _) => (Token::Minus, 1),
+            (';', _) => (Token::Semicolon, 1),
+            _ => bail!("unexpected character `{chr}`"),
+        };
+        tokens.push(token);
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_nth(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn describe_next(&self) -> String {
+        match self.peek() {
+            Some(Token::Ident(ident)) => format!("`{ident}`"),
+            Some(Token::QuotedIdent(ident)) => format!("`\"{ident}\"`"),
+            Some(Token::String(value)) => format!("`'{value}'`"),
+            Some(Token::Number(number)) => format!("`{number}`"),
+            Some(Token::Star) => "`*`".to_string(),
+            Some(Token::Comma) => "`,`".to_string(),
+            Some(Token::LeftParen) => "`(`".to_string(),
+            Some(Token::RightParen) => "`)`".to_string(),
+            Some(Token::Minus) => "`-`".to_string(),
+            Some(Token::Semicolon) => "`;`".to_string(),
+            Some(Token::Op(_)) => "comparison operator".to_string(),
+            None => "end of query".to_string(),
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword))
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> anyhow::Result<()> {
+        if !self.consume_keyword(keyword) {
+            bail!("expected `{keyword}`, found {}", self.describe_next());
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: &Token, description: &str) -> anyhow::Result<()> {
+        if !self.consume(token) {
+            bail!("expected {description}, found {}", self.describe_next());
+        }
+        Ok(())
+    }
+
+    fn parse_identifier(&mut self) -> anyhow::Result<String> {
+        match self.peek() {
+            Some(Token::Ident(ident)) if !is_reserved_keyword(ident) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            Some(Token::QuotedIdent(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => bail!("expected identifier, found {}", self.describe_next()),
+        }
+    }
+
+    fn parse_string(&mut self) -> anyhow::Result<String> {
+        match self.next_token() {
+            Some(Token::String(value)) => Ok(value),
+            _ => {
+                self.pos -= 1;
+                bail!("expected string, found {}", self.describe_next())
+            }
+        }
+    }
+
+    fn parse_unsigned_integer(&mut self, clause: &str) -> anyhow::Result<u64> {
+        match self.next_token() {
+            Some(Token::Number(number)) => number
+                .parse()
+                .with_context(|| format!("invalid {clause} `{number}`")),
+            _ => {
+                self.pos -= 1;
+                bail!("expected number, found {}", self.describe_next())
+            }
+        }
+    }
+
+    fn parse_query(&mut self) -> anyhow::Result<SqlQuery> {
+        self.expect_keyword("SELECT")?;
+        let select_items = self.parse_comma_separated(Self::parse_select_item)?;
+        self.expect_keyword("FROM")?;
+        let index_id_patterns = self.parse_comma_separated(Self::parse_identifier)?;
+        let where_clause = if self.consume_keyword("WHERE") {
+            Some(self.parse_condition()?)
+        } else {
+            None
+        };
+        let group_by = if self.consume_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            self.parse_comma_separated(Self::parse_expr)?
+        } else {
+            Vec::new()
+        };
+        let order_by = if self.consume_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            self.parse_comma_separated(Self::parse_order_by_item)?
+        } else {
+            Vec::new()
+        };
+        let limit = if self.consume_keyword("LIMIT") {
+            Some(self.parse_unsigned_integer("limit")?)
+        } else {
+            None
+        };
+        let offset = if self.consume_keyword("OFFSET") {
+            Some(self.parse_unsigned_integer("offset")?)
+        } else {
+            None
+        };
+        self.consume(&Token::Semicolon);
+        if self.peek().is_some() {
+            bail!(
+                "unexpected {} at the end of the query",
+                self.describe_next()
+            );
+        }
+        Ok(SqlQuery {
+            select_items,
+            index_id_patterns,
+            where_clause,
+            group_by,
+            order_by,
+            limit,
+            offset,
+        })
+    }
+
+    fn parse_comma_separated<T>(
+        &mut self,
+        parse_fn: impl Fn(&mut Self) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        let mut items = vec![parse_fn(self)?];
+        while self.consume(&Token::Comma) {
+            items.push(parse_fn(self)?);
+        }
+        Ok(items)
+    }
+
+    fn parse_select_item(&mut self) -> anyhow::Result<SelectItem> {
+        if self.consume(&Token::Star) {
+            return Ok(SelectItem::Wildcard);
+        }
+        let expr = self.parse_expr()?;
+        let alias = if self.consume_keyword("AS") {
+            Some(self.parse_identifier()?)
+        } else if matches!(self.peek(), Some(Token::Ident(ident)) if !is_reserved_keyword(ident))
+            || matches!(self.peek(), Some(Token::QuotedIdent(_)))
+        {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        Ok(SelectItem::Expr { expr, alias })
+    }
+
+    fn parse_expr(&mut self) -> anyhow::Result<Expr> {
+        let Some(Token::Ident(name)) = self.peek().cloned() else {
+            return Ok(Expr::Field(self.parse_identifier()?));
+        };
+        if self.peek_nth(1) != Some(&Token::LeftParen) {
+            return Ok(Expr::Field(self.parse_identifier()?));
+        }
+        self.pos += 2;
+        let expr = if let Some(function) = AggregateFunction::from_name(&name) {
+            let distinct = self.consume_keyword("DISTINCT");
+            let field = if self.consume(&Token::Star) {
+                if function != AggregateFunction::Count || distinct {
+                    bail!("`*` is only supported in `COUNT(*)`");
+                }
+                None
+            } else {
+                Some(self.parse_identifier()?)
+            };
+            Expr::Aggregate {
+                function,
+                field,
+                distinct,
+            }
+        } else if name.eq_ignore_ascii_case("DATE_HISTOGRAM") {
+            let field = self.parse_identifier()?;
+            self.expect(&Token::Comma, "`,`")?;
+            let fixed_interval = self.parse_string()?;
+            Expr::DateHistogram {
+                field,
+                fixed_interval,
+            }
+        } else if name.eq_ignore_ascii_case("DATE_TRUNC") {
+            let unit = self.parse_string()?;
+            let fixed_interval = match unit.to_ascii_lowercase().as_str() {
+                "millisecond" | "milliseconds" => "1ms",
+                "second" | "seconds" => "1s",
+                "minute" | "minutes" => "1m",
+                "hour" | "hours" => "1h",
+                "day" | "days" => "1d",
+                _ => bail!(
+                    "unsupported `DATE_TRUNC` unit `{unit}`: supported units are `millisecond`, \
+                     `second`, `minute`, `hour` and `day`"
+                ),
+            };
+            self.expect(&Token::Comma, "`,`")?;
+            let field = self.parse_identifier()?;
+            Expr::DateHistogram {
+                field,
+                fixed_interval: fixed_interval.to_string(),
+            }
+        } else {
+            bail!("unknown function `{name}`");
+        };
+        self.expect(&Token::RightParen, "`)`")?;
+        Ok(expr)
+    }
+
+    fn parse_order_by_item(&mut self) -> anyhow::Result<OrderByItem> {
+        let target = if let Some(Token::Number(_)) = self.peek() {
+            let position = self.parse_unsigned_integer("ORDER BY position")?;
+            OrderByTarget::Position(position as usize)
+        } else {
+            OrderByTarget::Expr(self.parse_expr()?)
+        };
+        let order = if self.consume_keyword("DESC") {
+            SortOrder::Desc
+        } else {
+            self.consume_keyword("ASC");
+            SortOrder::Asc
+        };
+        Ok(OrderByItem { target, order })
+    }
+
+    fn parse_condition(&mut self) -> anyhow::Result<Condition> {
+        let mut conditions = vec![self.parse_and_condition()?];
+        while self.consume_keyword("OR") {
+            conditions.push(self.parse_and_condition()?);
+        }
+        if conditions.len() == 1 {
+            return Ok(conditions.pop().unwrap());
+        }
+        Ok(Condition::Or(conditions))
+    }
+
+    fn parse_and_condition(&mut self) -> anyhow::Result<Condition> {
+        let mut conditions = vec![self.parse_not_condition()?];
+        while self.consume_keyword("AND") {
+            conditions.push(self.parse_not_condition()?);
+        }
+        if conditions.len() == 1 {
+            return Ok(conditions.pop().unwrap());
+        }
+        Ok(Condition::And(conditions))
+    }
+
+    fn parse_not_condition(&mut self) -> anyhow::Result<Condition> {
+        if self.consume_keyword("NOT") {
+            let condition = self.parse_not_condition()?;
+            return Ok(Condition::Not(Box::new(condition)));
+        }
+        if self.consume(&Token::LeftParen) {
+            let condition = self.parse_condition()?;
+            self.expect(&Token::RightParen, "`)`")?;
+            return Ok(condition);
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_literal_opt(&mut self) -> anyhow::Result<Option<Literal>> {
+        let literal = match self.peek().cloned() {
+            Some(Token::String(value)) => Literal::String(value),
+            Some(Token::Number(number)) => Literal::Number(parse_number(&number)?),
+            Some(Token::Minus) => {
+                let Some(Token::Number(number)) = self.peek_nth(1).cloned() else {
+                    bail!("expected number after `-`");
+                };
+                self.pos += 1;
+                Literal::Number(parse_number(&format!("-{number}"))?)
+            }
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case("TRUE") => Literal::Bool(true),
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case("FALSE") => {
+                Literal::Bool(false)
+            }
+            _ => return Ok(None),
+        };
+        self.pos += 1;
+        Ok(Some(literal))
+    }
+
+    fn parse_literal(&mut self) -> anyhow::Result<Literal> {
+        match self.parse_literal_opt()? {
+            Some(literal) => Ok(literal),
+            None => bail!("expected value, found {}", self.describe_next()),
+        }
+    }
+
+    fn parse_predicate(&mut self) -> anyhow::Result<Condition> {
+        if let Some(Token::Ident(name)) = self.peek().cloned() {
+            if self.peek_nth(1) == Some(&Token::LeftParen) {
+                if name.eq_ignore_ascii_case("QUERY") {
+                    self.pos += 2;
+                    let user_text = self.parse_string()?;
+                    self.expect(&Token::RightParen, "`)`")?;
+                    return Ok(Condition::UserQuery {
+                        user_text,
+                        default_fields: None,
+                    });
+                }
+                if name.eq_ignore_ascii_case("MATCH") {
+                    self.pos += 2;
+                    let field = self.parse_identifier()?;
+                    self.expect(&Token::Comma, "`,`")?;
+                    let user_text = self.parse_string()?;
+                    self.expect(&Token::RightParen, "`)`")?;
+                    return Ok(Condition::UserQuery {
+                        user_text,
+                        default_fields: Some(vec![field]),
+                    });
+                }
+                bail!("unknown function `{name}` in `WHERE` clause");
+            }
+        }
+        // `value op field`
+        if let Some(value) = self.parse_literal_opt()? {
+            let Some(Token::Op(op)) = self.next_token() else {
+                self.pos -= 1;
+                bail!(
+                    "expected comparison operator, found {}",
+                    self.describe_next()
+                );
+            };
+            let field = self.parse_identifier()?;
+            return Ok(Condition::Compare {
+                field,
+                op: op.flip(),
+                value,
+            });
+        }
+        let field = self.parse_identifier()?;
+        if let Some(Token::Op(op)) = self.peek().cloned() {
+            self.pos += 1;
+            let value = self.parse_literal()?;
+            return Ok(Condition::Compare { field, op, value });
+        }
+        if self.consume_keyword("IS") {
+            let negated = self.consume_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Condition::IsNull { field, negated });
+        }
+        let negated = self.consume_keyword("NOT");
+        if self.consume_keyword("BETWEEN") {
+            let low = self.parse_literal()?;
+            self.expect_keyword("AND")?;
+            let high = self.parse_literal()?;
+            return Ok(Condition::Between {
+                field,
+                negated,
+                low,
+                high,
+            });
+        }
+        if self.consume_keyword("IN") {
+            self.expect(&Token::LeftParen, "`(`")?;
+            let values = self.parse_comma_separated(Self::parse_literal)?;
+            self.expect(&Token::RightParen, "`)`")?;
+            return Ok(Condition::In {
+                field,
+                negated,
+                values,
+            });
+        }
+        if self.consume_keyword("LIKE") {
+            let pattern = self.parse_string()?;
+            return Ok(Condition::Like {
+                field,
+                negated,
+                pattern,
+            });
+        }
+        bail!(
+            "expected comparison operator, `BETWEEN`, `IN`, `LIKE` or `IS` after `{field}`, found \
+             {}",
+            self.describe_next()
+        )
+    }
+}
+
+fn is_reserved_keyword(ident: &str) -> bool {
+    RESERVED_KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(ident))
+}
+
+fn parse_number(number: &str) -> anyhow::Result<serde_json::Number> {
+    serde_json::from_str(number).with_context(|| format!("invalid number `{number}`"))
+}
+
+/// Parses a SQL `SELECT` statement.
+pub(crate) fn parse_sql_query(sql: &str) -> anyhow::Result<SqlQuery> {
+    let tokens = tokenize(sql)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    parser.parse_query()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str) -> Expr {
+        Expr::Field(name.to_string())
+    }
+
+    #[test]
+    fn test_parse_sql_query_select_fields() {
+        let sql_query = parse_sql_query(
+            "select timestamp, \"severity text\" AS severity, body FROM logs-*, `other` WHERE \
+             severity_number >= 9 AND (service IN ('api', 'db') OR NOT body LIKE 'err%') ORDER BY \
+             timestamp DESC LIMIT 10 OFFSET 20;",
+        )
+        .unwrap();
+        assert_eq!(
+            sql_query,
+            SqlQuery {
+                select_items: vec![
+                    SelectItem::Expr {
+                        expr: field("timestamp"),
+                        alias: None,
+                    },
+                    SelectItem::Expr {
+                        expr: field("severity text"),
+                        alias: Some("severity".to_string()),
+                    },
+                    SelectItem::Expr {
+                        expr: field("body"),
+                        alias: None,
+                    },
+                ],
+                index_id_patterns: vec!["logs-*".to_string(), "other".to_string()],
+                where_clause: Some(Condition::And(vec![
+                    Condition::Compare {
+                        field: "severity_number".to_string(),
+                        op: CompareOp::Gte,
+                        value: Literal::Number(9.into()),
+                    },
+                    Condition::Or(vec![
+                        Condition::In {
+                            field: "service".to_string(),
+                            negated: false,
+                            values: vec![
+                                Literal::String("api".to_string()),
+                                Literal::String("db".to_string()),
+                            ],
+                        },
+                        Condition::Not(Box::new(Condition::Like {
+                            field: "body".to_string(),
+                            negated: false,
+                            pattern: "err%".to_string(),
+                        })),
+                    ]),
+                ])),
+                group_by: Vec::new(),
+                order_by: vec![OrderByItem {
+                    target: OrderByTarget::Expr(field("timestamp")),
+                    order: SortOrder::Desc,
+                }],
+                limit: Some(10),
+                offset: Some(20),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_sql_query_aggregations() {
+        let sql_query = parse_sql_query(
+            "SELECT DATE_TRUNC('hour', ts) hour, service, COUNT(*), AVG(latency) AS avg_latency, \
+             COUNT(DISTINCT user.id) FROM traces WHERE ts BETWEEN '2024-01-01T00:00:00Z' AND \
+             '2024-01-02T00:00:00Z' AND -1.5 < score AND user.id IS NOT NULL GROUP BY hour, \
+             service ORDER BY 3 DESC, avg_latency",
+        )
+        .unwrap();
+        assert_eq!(
+            sql_query.select_items,
+            vec![
+                SelectItem::Expr {
+                    expr: Expr::DateHistogram {
+                        field: "ts".to_string(),
+                        fixed_interval: "1h".to_string(),
+                    },
+                    alias: Some("hour".to_string()),
+                },
+                SelectItem::Expr {
+                    expr: field("service"),
+                    alias: None,
+                },
+                SelectItem::Expr {
+                    expr: Expr::Aggregate {
+                        function: AggregateFunction::Count,
+                        field: None,
+                        distinct: false,
+                    },
+                    alias: None,
+                },
+                SelectItem::Expr {
+                    expr: Expr::Aggregate {
+                        function: AggregateFunction::Avg,
+                        field: Some("latency".to_string()),
+                        distinct: false,
+                    },
+                    alias: Some("avg_latency".to_string()),
+                },
+                SelectItem::Expr {
+                    expr: Expr::Aggregate {
+                        function: AggregateFunction::Count,
+                        field: Some("user.id".to_string()),
+                        distinct: true,
+                    },
+                    alias: None,
+                },
+            ]
+        );
+        assert_eq!(
+            sql_query.where_clause,
+            Some(Condition::And(vec![
+                Condition::Between {
+                    field: "ts".to_string(),
+                    negated: false,
+                    low: Literal::String("2024-01-01T00:00:00Z".to_string()),
+                    high: Literal::String("2024-01-02T00:00:00Z".to_string()),
+                },
+                Condition::Compare {
+                    field: "score".to_string(),
+                    op: CompareOp::Gt,
+                    value: Literal::Number(serde_json::Number::from_f64(-1.5).unwrap()),
+                },
+                Condition::IsNull {
+                    field: "user.id".to_string(),
+                    negated: true,
+                },
+            ]))
+        );
+        assert_eq!(sql_query.group_by, vec![field("hour"), field("service")]);
+        assert_eq!(
+            sql_query.order_by,
+            vec![
+                OrderByItem {
+                    target: OrderByTarget::Position(3),
+                    order: SortOrder::Desc,
+                },
+                OrderByItem {
+                    target: OrderByTarget::Expr(field("avg_latency")),
+                    order: SortOrder::Asc,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sql_query_errors() {
+        let error = parse_sql_query("SELECT * FROM").unwrap_err();
+        assert_eq!(error.to_string(), "expected identifier, found end of query");
+        let error = parse_sql_query("SELECT * FROM logs WHERE body = 'unterminated").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unterminated quoted string `'unterminated`"
+        );
+        let error = parse_sql_query("SELECT SUM(*) FROM logs").unwrap_err();
+        assert_eq!(error.to_string(), "`*` is only supported in `COUNT(*)`");
+        let error = parse_sql_query("SELECT * FROM logs LIMIT 10 foo").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unexpected `foo` at the end of the query"
+        );
+        let error = parse_sql_query("SELECT * FROM logs WHERE LOWER(body) = 'a'").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown function `LOWER` in `WHERE` clause"
+        );
+    }
+}
@@ -0,0 +1,1199 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Translation of SQL queries into search requests, and of search responses into rows.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
+
+use anyhow::bail;
+use quickwit_proto::search::{CountHits, SearchRequest, SearchResponse, SortField, SortOrder};
+use quickwit_query::query_ast::{
+    BoolQuery, FieldPresenceQuery, QueryAst, RangeQuery, TermQuery, TermSetQuery, UserInputQuery,
+    WildcardQuery,
+};
+use quickwit_query::{BooleanOperand, JsonLiteral};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+use super::parser::{
+    AggregateFunction, CompareOp, Condition, Expr, Literal, OrderByTarget, SelectItem, SqlQuery,
+};
+use super::SqlResponse;
+use crate::root::refine_start_end_timestamp_from_ast;
+use crate::SearchError;
+
+/// Maximum number of rows returned by a SQL query without `LIMIT`.
+const DEFAULT_SQL_LIMIT: u64 = 1_000;
+
+/// Number of buckets requested for each `GROUP BY` field when the `ORDER BY` and `LIMIT` clauses
+/// cannot be pushed down to the terms aggregation. Queries yielding more groups are rejected
+/// rather than silently truncated.
+const MAX_GROUPS_PER_FIELD: u64 = 1_000;
+
+/// A SQL query translated into a search request.
+pub(crate) struct SqlPlan {
+    pub search_request: SearchRequest,
+    output: SqlOutput,
+}
+
+enum SqlOutput {
+    Hits(Vec<HitColumn>),
+    Groups(GroupsOutput),
+}
+
+enum HitColumn {
+    /// Expands into one column per top-level field of the hits.
+    Wildcard,
+    Field {
+        name: String,
+        path: String,
+    },
+}
+
+struct GroupsOutput {
+    num_group_keys: usize,
+    /// For each `GROUP BY` key, the field name if its buckets are capped at
+    /// [`MAX_GROUPS_PER_FIELD`].
+    capped_group_fields: Vec<Option<String>>,
+    /// The visible columns, followed by the columns only used to sort the rows.
+    columns: Vec<GroupColumn>,
+    column_names: Vec<String>,
+    order_by: Vec<(usize, SortOrder)>,
+    offset: usize,
+    limit: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GroupColumn {
+    GroupKey(usize),
+    Count,
+    Metric {
+        name: String,
+        can_order_buckets: bool,
+    },
+}
+
+/// Translates a parsed SQL query into a search request.
+///
+/// If `timestamp_field_opt` is set, the time range implied by the `WHERE` clause is pushed down
+/// into the `start_timestamp` and `end_timestamp` of the request.
+pub(crate) fn plan_sql_query(
+    sql_query: SqlQuery,
+    timestamp_field_opt: Option<&str>,
+) -> anyhow::Result<SqlPlan> {
+    let query_ast = match &sql_query.where_clause {
+        Some(condition) => condition_to_query_ast(condition)?,
+        None => QueryAst::MatchAll,
+    };
+    let mut start_timestamp = None;
+    let mut end_timestamp = None;
+    if let Some(timestamp_field) = timestamp_field_opt {
+        refine_start_end_timestamp_from_ast(
+            &query_ast,
+            timestamp_field,
+            &mut start_timestamp,
+            &mut end_timestamp,
+        );
+    }
+    let search_request = SearchRequest {
+        index_id_patterns: sql_query.index_id_patterns.clone(),
+        query_ast: serde_json::to_string(&query_ast)?,
+        start_timestamp,
+        end_timestamp,
+        count_hits: CountHits::CountAll as i32,
+        ..Default::default()
+    };
+    let is_aggregation = !sql_query.group_by.is_empty()
+        || sql_query.select_items.iter().any(
+            |select_item| matches!(select_item, SelectItem::Expr { expr, .. } if expr.is_aggregate()),
+        );
+    if is_aggregation {
+        plan_groups(sql_query, search_request)
+    } else {
+        plan_hits(sql_query, search_request)
+    }
+}
+
+fn plan_hits(sql_query: SqlQuery, mut search_request: SearchRequest) -> anyhow::Result<SqlPlan> {
+    let mut columns = Vec::with_capacity(sql_query.select_items.len());
+    for select_item in sql_query.select_items {
+        let column = match select_item {
+            SelectItem::Wildcard => HitColumn::Wildcard,
+            SelectItem::Expr {
+                expr: Expr::Field(path),
+                alias,
+            } => HitColumn::Field {
+                name: alias.unwrap_or_else(|| path.clone()),
+                path,
+            },
+            SelectItem::Expr { expr, .. } => {
+                bail!("`{}` requires a `GROUP BY` clause", expr.column_name())
+            }
+        };
+        columns.push(column);
+    }
+    for order_by_item in sql_query.order_by {
+        let field_name = match order_by_item.target {
+            OrderByTarget::Position(position) => match position
+                .checked_sub(1)
+                .and_then(|column_idx| columns.get(column_idx))
+            {
+                Some(HitColumn::Field { path, .. }) => path.clone(),
+                Some(HitColumn::Wildcard) => bail!("cannot sort by `*`"),
+                None => bail!("`ORDER BY` position {position} is out of range"),
+            },
+            OrderByTarget::Expr(Expr::Field(field_name)) => columns
+                .iter()
+                .find_map(|column| match column {
+                    HitColumn::Field { name, path } if *name == field_name => Some(path.clone()),
+                    _ => None,
+                })
+                .unwrap_or(field_name),
+            OrderByTarget::Expr(expr) => {
+                bail!("cannot sort by `{}` without `GROUP BY`", expr.column_name())
+            }
+        };
+        search_request.sort_fields.push(SortField {
+            field_name,
+            sort_order: order_by_item.order as i32,
+            sort_datetime_format: None,
+        });
+    }
+    if !columns
+        .iter()
+        .any(|column| matches!(column, HitColumn::Wildcard))
+    {
+        search_request.fetch_fields = columns
+            .iter()
+            .filter_map(|column| match column {
+                HitColumn::Field { path, .. } => Some(path.clone()),
+                HitColumn::Wildcard => None,
+            })
+            .collect();
+    }
+    search_request.max_hits = sql_query.limit.unwrap_or(DEFAULT_SQL_LIMIT);
+    search_request.start_offset = sql_query.offset.unwrap_or(0);
+    Ok(SqlPlan {
+        search_request,
+        output: SqlOutput::Hits(columns),
+    })
+}
+
+fn plan_groups(sql_query: SqlQuery, mut search_request: SearchRequest) -> anyhow::Result<SqlPlan> {
+    let aliases: HashMap<&str, &Expr> = sql_query
+        .select_items
+        .iter()
+        .filter_map(|select_item| match select_item {
+            SelectItem::Expr {
+                expr,
+                alias: Some(alias),
+            } => Some((alias.as_str(), expr)),
+            _ => None,
+        })
+        .collect();
+    let resolve_alias = |expr: Expr| -> Expr {
+        if let Expr::Field(field_name) = &expr {
+            if let Some(aliased_expr) = aliases.get(field_name.as_str()) {
+                return (*aliased_expr).clone();
+            }
+        }
+        expr
+    };
+    let mut group_keys = Vec::with_capacity(sql_query.group_by.len());
+    for expr in sql_query.group_by.iter().cloned().map(resolve_alias) {
+        if expr.is_aggregate() {
+            bail!(
+                "aggregate function `{}` is not allowed in `GROUP BY`",
+                expr.column_name()
+            );
+        }
+        group_keys.push(expr);
+    }
+    let mut metrics: Vec<(Expr, JsonValue)> = Vec::new();
+    let mut columns = Vec::new();
+    let mut column_exprs = Vec::new();
+    let mut column_names = Vec::new();
+    for select_item in &sql_query.select_items {
+        let SelectItem::Expr { expr, alias } = select_item else {
+            bail!("`SELECT *` cannot be used with `GROUP BY` or aggregate functions");
+        };
+        columns.push(group_column(expr, &group_keys, &mut metrics)?);
+        column_exprs.push(expr.clone());
+        column_names.push(alias.clone().unwrap_or_else(|| expr.column_name()));
+    }
+    let mut order_by = Vec::with_capacity(sql_query.order_by.len());
+    for order_by_item in sql_query.order_by {
+        let column_idx = match order_by_item.target {
+            OrderByTarget::Position(position) => {
+                if position == 0 || position > column_names.len() {
+                    bail!("`ORDER BY` position {position} is out of range");
+                }
+                position - 1
+            }
+            OrderByTarget::Expr(expr) => {
+                let alias_column_idx_opt = match &expr {
+                    Expr::Field(field_name) => sql_query.select_items.iter().position(
+                        |select_item| matches!(select_item, SelectItem::Expr { alias: Some(alias), .. } if alias == field_name),
+                    ),
+                    _ => None,
+                };
+                if let Some(column_idx) = alias_column_idx_opt.or_else(|| {
+                    column_exprs
+                        .iter()
+                        .position(|column_expr| *column_expr == expr)
+                }) {
+                    column_idx
+                } else {
+                    // The rows are sorted by a column that is not selected.
+                    columns.push(group_column(&expr, &group_keys, &mut metrics)?);
+                    column_exprs.push(expr);
+                    columns.len() - 1
+                }
+            }
+        };
+        order_by.push((column_idx, order_by_item.order));
+    }
+    let offset = sql_query.offset.unwrap_or(0);
+    let limit = sql_query.limit.unwrap_or(DEFAULT_SQL_LIMIT);
+
+    let mut capped_group_fields = vec![None; group_keys.len()];
+    let mut aggregations: JsonMap<String, JsonValue> = metrics
+        .into_iter()
+        .enumerate()
+        .map(|(metric_idx, (_, metric_agg))| (metric_name(metric_idx), metric_agg))
+        .collect();
+    for (depth, group_key) in group_keys.iter().enumerate().rev() {
+        let mut bucket_agg = match group_key {
+            Expr::Field(field_name) => {
+                let mut terms_agg = json!({
+                    "field": field_name,
+                    "size": MAX_GROUPS_PER_FIELD,
+                });
+                // With a single `GROUP BY` field, the buckets are the rows, so sorting and
+                // limiting them is exact.
+                let bucket_order_opt = if group_keys.len() == 1 {
+                    bucket_order(&columns, &order_by)
+                } else {
+                    None
+                };
+                if let Some(bucket_order) = bucket_order_opt {
+                    terms_agg["size"] = json!(offset.saturating_add(limit));
+                    if let Some(bucket_order) = bucket_order {
+                        terms_agg["order"] = bucket_order;
+                    }
+                } else {
+                    capped_group_fields[depth] = Some(field_name.clone());
+                }
+                json!({ "terms": terms_agg })
+            }
+            Expr::DateHistogram {
+                field,
+                fixed_interval,
+            } => json!({
+                "date_histogram": {
+                    "field": field,
+                    "fixed_interval": fixed_interval,
+                    "min_doc_count": 1,
+                }
+            }),
+            Expr::Aggregate { .. } => unreachable!("aggregates are rejected in `GROUP BY`"),
+        };
+        if !aggregations.is_empty() {
+            bucket_agg["aggs"] = JsonValue::Object(aggregations);
+        }
+        aggregations = JsonMap::new();
+        aggregations.insert(group_key_name(depth), bucket_agg);
+    }
+    if !aggregations.is_empty() {
+        search_request.aggregation_request = Some(serde_json::to_string(&aggregations)?);
+    }
+    search_request.max_hits = 0;
+    Ok(SqlPlan {
+        search_request,
+        output: SqlOutput::Groups(GroupsOutput {
+            num_group_keys: group_keys.len(),
+            capped_group_fields,
+            columns,
+            column_names,
+            order_by,
+            offset: offset as usize,
+            limit: limit as usize,
+        }),
+    })
+}
+
+fn group_key_name(depth: usize) -> String {
+    format!("group_{depth}")
+}
+
+fn metric_name(metric_idx: usize) -> String {
+    format!("metric_{metric_idx}")
+}
+
+fn group_column(
+    expr: &Expr,
+    group_keys: &[Expr],
+    metrics: &mut Vec<(Expr, JsonValue)>,
+) -> anyhow::Result<GroupColumn> {
+    if let Some(group_key_idx) = group_keys.iter().position(|group_key| group_key == expr) {
+        return Ok(GroupColumn::GroupKey(group_key_idx));
+    }
+    let Expr::Aggregate {
+        function,
+        field: field_opt,
+        distinct,
+    } = expr
+    else {
+        bail!(
+            "`{}` must appear in the `GROUP BY` clause or be used in an aggregate function",
+            expr.column_name()
+        );
+    };
+    let Some(field) = field_opt else {
+        return Ok(GroupColumn::Count);
+    };
+    let metric_agg = match (function, distinct) {
+        (AggregateFunction::Count, true) => json!({"cardinality": {"field": field}}),
+        (AggregateFunction::Count, false) => json!({"value_count": {"field": field}}),
+        (AggregateFunction::Sum, false) => json!({"sum": {"field": field}}),
+        (AggregateFunction::Avg, false) => json!({"avg": {"field": field}}),
+        (AggregateFunction::Min, false) => json!({"min": {"field": field}}),
+        (AggregateFunction::Max, false) => json!({"max": {"field": field}}),
+        (_, true) => bail!("`DISTINCT` is only supported in `COUNT(DISTINCT field)`"),
+    };
+    let metric_idx = if let Some(metric_idx) = metrics
+        .iter()
+        .position(|(metric_expr, _)| metric_expr == expr)
+    {
+        metric_idx
+    } else {
+        metrics.push((expr.clone(), metric_agg));
+        metrics.len() - 1
+    };
+    Ok(GroupColumn::Metric {
+        name: metric_name(metric_idx),
+        can_order_buckets: !distinct,
+    })
+}
+
+/// Returns the order of the terms buckets matching the `ORDER BY` clause, or `None` if the
+/// clause cannot be pushed down to the terms aggregation.
+fn bucket_order(
+    columns: &[GroupColumn],
+    order_by: &[(usize, SortOrder)],
+) -> Option<Option<JsonValue>> {
+    let [(column_idx, sort_order)] = order_by else {
+        // Without `ORDER BY`, the rows have no particular order.
+        return order_by.is_empty().then_some(None);
+    };
+    let sort_order_str = match sort_order {
+        SortOrder::Asc => "asc",
+        SortOrder::Desc => "desc",
+    };
+    let order_key = match &columns[*column_idx] {
+        GroupColumn::GroupKey(_) => "_key",
+        GroupColumn::Count => "_count",
+        GroupColumn::Metric {
+            name,
+            can_order_buckets: true,
+        } => name.as_str(),
+        GroupColumn::Metric { .. } => return None,
+    };
+    Some(Some(json!({ order_key: sort_order_str })))
+}
+
+fn condition_to_query_ast(condition: &Condition) -> anyhow::Result<QueryAst> {
+    let query_ast: QueryAst = match condition {
+        Condition::And(conditions) => BoolQuery {
+            must: conditions
+                .iter()
+                .map(condition_to_query_ast)
+                .collect::<anyhow::Result<_>>()?,
+            ..Default::default()
+        }
+        .into(),
+        Condition::Or(conditions) => BoolQuery {
+            should: conditions
+                .iter()
+                .map(condition_to_query_ast)
+                .collect::<anyhow::Result<_>>()?,
+            ..Default::default()
+        }
+        .into(),
+        Condition::Not(condition) => negate(condition_to_query_ast(condition)?),
+        Condition::Compare { field, op, value } => {
+            let json_literal = literal_to_json_literal(value);
+            let range_query =
+                |lower_bound: Bound<JsonLiteral>, upper_bound: Bound<JsonLiteral>| -> QueryAst {
+                    RangeQuery {
+                        field: field.clone(),
+                        lower_bound,
+                        upper_bound,
+                    }
+                    .into()
+                };
+            match op {
+                CompareOp::Eq => term_query(field, value),
+                CompareOp::NotEq => negate(term_query(field, value)),
+                CompareOp::Lt => range_query(Bound::Unbounded, Bound::Excluded(json_literal)),
+                CompareOp::Lte => range_query(Bound::Unbounded, Bound::Included(json_literal)),
+                CompareOp::Gt => range_query(Bound::Excluded(json_literal), Bound::Unbounded),
+                CompareOp::Gte => range_query(Bound::Included(json_literal), Bound::Unbounded),
+            }
+        }
+        Condition::Between {
+            field,
+            negated,
+            low,
+            high,
+        } => {
+            let range_query = RangeQuery {
+                field: field.clone(),
+                lower_bound: Bound::Included(literal_to_json_literal(low)),
+                upper_bound: Bound::Included(literal_to_json_literal(high)),
+            }
+            .into();
+            negate_if(range_query, *negated)
+        }
+        Condition::In {
+            field,
+            negated,
+            values,
+        } => {
+            let terms = values.iter().map(literal_to_term).collect();
+            let term_set_query = TermSetQuery {
+                terms_per_field: HashMap::from([(field.clone(), terms)]),
+            }
+            .into();
+            negate_if(term_set_query, *negated)
+        }
+        Condition::Like {
+            field,
+            negated,
+            pattern,
+        } => {
+            let wildcard_query = WildcardQuery {
+                field: field.clone(),
+                value: like_pattern_to_wildcard(pattern),
+                lenient: false,
+            }
+            .into();
+            negate_if(wildcard_query, *negated)
+        }
+        Condition::IsNull { field, negated } => {
+            let field_presence_query = FieldPresenceQuery {
+                field: field.clone(),
+            }
+            .into();
+            negate_if(field_presence_query, !negated)
+        }
+        Condition::UserQuery {
+            user_text,
+            default_fields,
+        } => UserInputQuery {
+            user_text: user_text.clone(),
+            default_fields: default_fields.clone(),
+            default_operator: BooleanOperand::And,
+            lenient: false,
+        }
+        .into(),
+    };
+    Ok(query_ast)
+}
+
+fn negate(query_ast: QueryAst) -> QueryAst {
+    BoolQuery {
+        must_not: vec![query_ast],
+        ..Default::default()
+    }
+    .into()
+}
+
+fn negate_if(query_ast: QueryAst, negated: bool) -> QueryAst {
+    if negated {
+        negate(query_ast)
+    } else {
+        query_ast
+    }
+}
+
+fn term_query(field: &str, value: &Literal) -> QueryAst {
+    TermQuery {
+        field: field.to_string(),
+        value: literal_to_term(value),
+    }
+    .into()
+}
+
+fn literal_to_term(literal: &Literal) -> String {
+    match literal {
+        Literal::String(value) => value.clone(),
+        Literal::Number(number) => number.to_string(),
+        Literal::Bool(value) => value.to_string(),
+    }
+}
+
+fn literal_to_json_literal(literal: &Literal) -> JsonLiteral {
+    match literal {
+        Literal::String(value) => JsonLiteral::String(value.clone()),
+        Literal::Number(number) => JsonLiteral::Number(number.clone()),
+        Literal::Bool(value) => JsonLiteral::Bool(*value),
+    }
+}
+
+/// Converts a `LIKE` pattern, where `%` and `_` match any sequence of characters and any single
+/// character, into a wildcard pattern.
+fn like_pattern_to_wildcard(pattern: &str) -> String {
+    let mut wildcard = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '%' => wildcard.push('*'),
+            '_' => wildcard.push('?'),
+            '\\' => {
+                if let Some(escaped_chr) = chars.next() {
+                    if matches!(escaped_chr, '*' | '?' | '\\') {
+                        wildcard.push('\\');
+                    }
+                    wildcard.push(escaped_chr);
+                }
+            }
+            '*' | '?' => {
+                wildcard.push('\\');
+                wildcard.push(chr);
+            }
+            _ => wildcard.push(chr),
+        }
+    }
+    wildcard
+}
+
+impl SqlPlan {
+    /// Converts the response of the search request into rows.
+    pub fn into_response(self, search_response: SearchResponse) -> crate::Result<SqlResponse> {
+        let num_hits = search_response.num_hits;
+        let (columns, rows) = match self.output {
+            SqlOutput::Hits(hit_columns) => {
+                let json_docs: Vec<JsonValue> = search_response
+                    .hits
+                    .iter()
+                    .map(|hit| serde_json::from_str(&hit.json))
+                    .collect::<Result<_, _>>()?;
+                hits_to_rows(hit_columns, &json_docs)
+            }
+            SqlOutput::Groups(groups_output) => {
+                let aggregations: JsonValue = match &search_response.aggregation {
+                    Some(aggregation_json) => serde_json::from_str(aggregation_json)?,
+                    None => JsonValue::Null,
+                };
+                groups_to_rows(groups_output, &aggregations, num_hits)?
+            }
+        };
+        Ok(SqlResponse {
+            columns,
+            rows,
+            num_hits,
+        })
+    }
+}
+
+fn hits_to_rows(
+    hit_columns: Vec<HitColumn>,
+    json_docs: &[JsonValue],
+) -> (Vec<String>, Vec<Vec<JsonValue>>) {
+    let mut column_names = Vec::new();
+    let mut column_paths = Vec::new();
+    for hit_column in hit_columns {
+        match hit_column {
+            HitColumn::Wildcard => {
+                let mut top_level_fields = HashSet::new();
+                for json_doc in json_docs {
+                    let Some(json_obj) = json_doc.as_object() else {
+                        continue;
+                    };
+                    for field_name in json_obj.keys() {
+                        if top_level_fields.insert(field_name.clone()) {
+                            column_names.push(field_name.clone());
+                            column_paths.push(field_name.clone());
+                        }
+                    }
+                }
+            }
+            HitColumn::Field { name, path } => {
+                column_names.push(name);
+                column_paths.push(path);
+            }
+        }
+    }
+    let rows = json_docs
+        .iter()
+        .map(|json_doc| {
+            column_paths
+                .iter()
+                .map(|path| {
+                    json_value_at_path(json_doc, path)
+                        .cloned()
+                        .unwrap_or(JsonValue::Null)
+                })
+                .collect()
+        })
+        .collect();
+    (column_names, rows)
+}
+
+/// Returns the value at `path`, where the dots separate the keys of nested objects unless the
+/// key contains a dot itself.
+fn json_value_at_path<'a>(json_value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    if let Some(value) = json_value.get(path) {
+        return Some(value);
+    }
+    for (dot_idx, _) in path.match_indices('.') {
+        let Some(child) = json_value.get(&path[..dot_idx]) else {
+            continue;
+        };
+        if let Some(value) = json_value_at_path(child, &path[dot_idx + 1..]) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn groups_to_rows(
+    groups_output: GroupsOutput,
+    aggregations: &JsonValue,
+    num_hits: u64,
+) -> crate::Result<(Vec<String>, Vec<Vec<JsonValue>>)> {
+    let mut rows = Vec::new();
+    collect_group_rows(
+        &groups_output,
+        aggregations,
+        num_hits,
+        &mut Vec::new(),
+        &mut rows,
+    )?;
+    rows.sort_by(|left_row, right_row| compare_rows(left_row, right_row, &groups_output.order_by));
+    let num_columns = groups_output.column_names.len();
+    let rows = rows
+        .into_iter()
+        .skip(groups_output.offset)
+        .take(groups_output.limit)
+        .map(|mut row| {
+            row.truncate(num_columns);
+            row
+        })
+        .collect();
+    Ok((groups_output.column_names, rows))
+}
+
+fn collect_group_rows(
+    groups_output: &GroupsOutput,
+    bucket: &JsonValue,
+    doc_count: u64,
+    group_key_values: &mut Vec<JsonValue>,
+    rows: &mut Vec<Vec<JsonValue>>,
+) -> crate::Result<()> {
+    let depth = group_key_values.len();
+    if depth == groups_output.num_group_keys {
+        let row = groups_output
+            .columns
+            .iter()
+            .map(|column| match column {
+                GroupColumn::GroupKey(group_key_idx) => group_key_values[*group_key_idx].clone(),
+                GroupColumn::Count => JsonValue::from(doc_count),
+                GroupColumn::Metric { name, .. } => bucket
+                    .get(name)
+                    .and_then(|metric| metric.get("value"))
+                    .cloned()
+                    .unwrap_or(JsonValue::Null),
+            })
+            .collect();
+        rows.push(row);
+        return Ok(());
+    }
+    let Some(bucket_agg) = bucket.get(group_key_name(depth)) else {
+        return Ok(());
+    };
+    if let Some(field_name) = &groups_output.capped_group_fields[depth] {
+        // The terms aggregation counts the documents of the buckets it dropped.
+        let sum_other_doc_count = bucket_agg
+            .get("sum_other_doc_count")
+            .and_then(JsonValue::as_u64)
+            .unwrap_or(0);
+        if sum_other_doc_count > 0 {
+            return Err(SearchError::InvalidQuery(format!(
+                "invalid SQL query: `GROUP BY` field `{field_name}` has more than \
+                 {MAX_GROUPS_PER_FIELD} distinct values, narrow down the `WHERE` clause or sort \
+                 and limit the groups by a single `GROUP BY` field"
+            )));
+        }
+    }
+    let Some(sub_buckets) = bucket_agg.get("buckets").and_then(JsonValue::as_array) else {
+        return Ok(());
+    };
+    for sub_bucket in sub_buckets {
+        let group_key_value = sub_bucket
+            .get("key_as_string")
+            .or_else(|| sub_bucket.get("key"))
+            .cloned()
+            .unwrap_or(JsonValue::Null);
+        let sub_bucket_doc_count = sub_bucket
+            .get("doc_count")
+            .and_then(JsonValue::as_u64)
+            .unwrap_or(0);
+        group_key_values.push(group_key_value);
+        collect_group_rows(
+            groups_output,
+            sub_bucket,
+            sub_bucket_doc_count,
+            group_key_values,
+            rows,
+        )?;
+        group_key_values.pop();
+    }
+    Ok(())
+}
+
+/// Compares rows according to the `ORDER BY` clause. Null values come last.
+fn compare_rows(
+    left_row: &[JsonValue],
+    right_row: &[JsonValue],
+    order_by: &[(usize, SortOrder)],
+) -> Ordering {
+    for (column_idx, sort_order) in order_by {
+        let ordering = match (&left_row[*column_idx], &right_row[*column_idx]) {
+            (JsonValue::Null, JsonValue::Null) => Ordering::Equal,
+            (JsonValue::Null, _) => Ordering::Greater,
+            (_, JsonValue::Null) => Ordering::Less,
+            (left_value, right_value) => {
+                let ordering = compare_json_values(left_value, right_value);
+                match sort_order {
+                    SortOrder::Asc => ordering,
+                    SortOrder::Desc => ordering.reverse(),
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_json_values(left_value: &JsonValue, right_value: &JsonValue) -> Ordering {
+    match (left_value, right_value) {
+        (JsonValue::Number(left_number), JsonValue::Number(right_number)) => left_number
+            .as_f64()
+            .partial_cmp(&right_number.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (JsonValue::String(left_str), JsonValue::String(right_str)) => left_str.cmp(right_str),
+        (JsonValue::Bool(left_bool), JsonValue::Bool(right_bool)) => left_bool.cmp(right_bool),
+        _ => left_value.to_string().cmp(&right_value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::search::Hit;
+
+    use super::*;
+    use crate::sql::parser::parse_sql_query;
+
+    fn plan(sql: &str) -> SqlPlan {
+        let sql_query = parse_sql_query(sql).unwrap();
+        plan_sql_query(sql_query, Some("timestamp")).unwrap()
+    }
+
+    fn plan_error(sql: &str) -> String {
+        let sql_query = parse_sql_query(sql).unwrap();
+        plan_sql_query(sql_query, Some("timestamp"))
+            .err()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_plan_sql_query_hits() {
+        let sql_plan = plan(
+            "SELECT timestamp AS ts, body FROM logs WHERE timestamp >= '2024-01-01T00:00:00Z' AND \
+             timestamp < '2024-01-02T00:00:00Z' AND severity != 'DEBUG' ORDER BY ts DESC LIMIT 10 \
+             OFFSET 5",
+        );
+        let search_request = &sql_plan.search_request;
+        assert_eq!(search_request.index_id_patterns, vec!["logs".to_string()]);
+        assert_eq!(search_request.start_timestamp, Some(1_704_067_200));
+        assert_eq!(search_request.end_timestamp, Some(1_704_153_600));
+        assert_eq!(search_request.max_hits, 10);
+        assert_eq!(search_request.start_offset, 5);
+        assert_eq!(
+            search_request.sort_fields,
+            vec![SortField {
+                field_name: "timestamp".to_string(),
+                sort_order: SortOrder::Desc as i32,
+                sort_datetime_format: None,
+            }]
+        );
+        assert_eq!(
+            search_request.fetch_fields,
+            vec!["timestamp".to_string(), "body".to_string()]
+        );
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![
+                RangeQuery {
+                    field: "timestamp".to_string(),
+                    lower_bound: Bound::Included(JsonLiteral::String(
+                        "2024-01-01T00:00:00Z".to_string(),
+                    )),
+                    upper_bound: Bound::Unbounded,
+                }
+                .into(),
+                RangeQuery {
+                    field: "timestamp".to_string(),
+                    lower_bound: Bound::Unbounded,
+                    upper_bound: Bound::Excluded(JsonLiteral::String(
+                        "2024-01-02T00:00:00Z".to_string(),
+                    )),
+                }
+                .into(),
+                negate(
+                    TermQuery {
+                        field: "severity".to_string(),
+                        value: "DEBUG".to_string(),
+                    }
+                    .into(),
+                ),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let search_response = SearchResponse {
+            num_hits: 42,
+            hits: vec![
+                Hit {
+                    json: r#"{"timestamp": 2, "body": "b"}"#.to_string(),
+                    ..Default::default()
+                },
+                Hit {
+                    json: r#"{"timestamp": 1}"#.to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let sql_response = sql_plan.into_response(search_response).unwrap();
+        assert_eq!(sql_response.columns, vec!["ts", "body"]);
+        assert_eq!(
+            sql_response.rows,
+            vec![vec![json!(2), json!("b")], vec![json!(1), JsonValue::Null]]
+        );
+        assert_eq!(sql_response.num_hits, 42);
+    }
+
+    #[test]
+    fn test_plan_sql_query_wildcard_and_nested_fields() {
+        let sql_plan = plan(
+            "SELECT *, attributes.http.status FROM logs WHERE service IN ('api', 'db') AND body \
+             NOT LIKE '100\\%_%' AND trace_id IS NULL",
+        );
+        assert!(sql_plan.search_request.fetch_fields.is_empty());
+        assert_eq!(sql_plan.search_request.max_hits, DEFAULT_SQL_LIMIT);
+        let query_ast: QueryAst = serde_json::from_str(&sql_plan.search_request.query_ast).unwrap();
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![
+                TermSetQuery {
+                    terms_per_field: HashMap::from([(
+                        "service".to_string(),
+                        ["api".to_string(), "db".to_string()].into_iter().collect(),
+                    )]),
+                }
+                .into(),
+                negate(
+                    WildcardQuery {
+                        field: "body".to_string(),
+                        value: "100%?*".to_string(),
+                        lenient: false,
+                    }
+                    .into(),
+                ),
+                negate(
+                    FieldPresenceQuery {
+                        field: "trace_id".to_string(),
+                    }
+                    .into(),
+                ),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let search_response = SearchResponse {
+            num_hits: 1,
+            hits: vec![Hit {
+                json: r#"{"service": "api", "attributes": {"http.status": 200}}"#.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let sql_response = sql_plan.into_response(search_response).unwrap();
+        assert_eq!(
+            sql_response.columns,
+            vec!["service", "attributes", "attributes.http.status"]
+        );
+        assert_eq!(
+            sql_response.rows,
+            vec![vec![json!("api"), json!({"http.status": 200}), json!(200)]]
+        );
+    }
+
+    #[test]
+    fn test_plan_sql_query_group_by() {
+        let sql_plan = plan(
+            "SELECT DATE_TRUNC('hour', timestamp) AS hour, service, COUNT(*), AVG(latency) FROM \
+             traces GROUP BY hour, service ORDER BY MAX(latency) DESC LIMIT 2",
+        );
+        let search_request = &sql_plan.search_request;
+        assert_eq!(search_request.max_hits, 0);
+        let aggregations: JsonValue =
+            serde_json::from_str(search_request.aggregation_request.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            aggregations,
+            json!({
+                "group_0": {
+                    "date_histogram": {
+                        "field": "timestamp",
+                        "fixed_interval": "1h",
+                        "min_doc_count": 1,
+                    },
+                    "aggs": {
+                        "group_1": {
+                            "terms": {"field": "service", "size": 1_000},
+                            "aggs": {
+                                "metric_0": {"avg": {"field": "latency"}},
+                                "metric_1": {"max": {"field": "latency"}},
+                            }
+                        }
+                    }
+                }
+            })
+        );
+        let aggregation_response = json!({
+            "group_0": {
+                "buckets": [
+                    {
+                        "key": 0.0,
+                        "key_as_string": "1970-01-01T00:00:00Z",
+                        "doc_count": 3,
+                        "group_1": {
+                            "buckets": [
+                                {
+                                    "key": "api",
+                                    "doc_count": 2,
+                                    "metric_0": {"value": 15.0},
+                                    "metric_1": {"value": 20.0},
+                                },
+                                {
+                                    "key": "db",
+                                    "doc_count": 1,
+                                    "metric_0": {"value": 50.0},
+                                    "metric_1": {"value": 50.0},
+                                }
+                            ]
+                        }
+                    },
+                    {
+                        "key": 3_600_000.0,
+                        "key_as_string": "1970-01-01T01:00:00Z",
+                        "doc_count": 1,
+                        "group_1": {
+                            "buckets": [
+                                {
+                                    "key": "api",
+                                    "doc_count": 1,
+                                    "metric_0": {"value": 30.0},
+                                    "metric_1": {"value": 30.0},
+                                }
+                            ]
+                        }
+                    }
+                ]
+            }
+        });
+        let search_response = SearchResponse {
+            num_hits: 4,
+            aggregation: Some(aggregation_response.to_string()),
+            ..Default::default()
+        };
+        let sql_response = sql_plan.into_response(search_response).unwrap();
+        assert_eq!(
+            sql_response.columns,
+            vec!["hour", "service", "COUNT(*)", "AVG(latency)"]
+        );
+        assert_eq!(
+            sql_response.rows,
+            vec![
+                vec![
+                    json!("1970-01-01T00:00:00Z"),
+                    json!("db"),
+                    json!(1),
+                    json!(50.0)
+                ],
+                vec![
+                    json!("1970-01-01T01:00:00Z"),
+                    json!("api"),
+                    json!(1),
+                    json!(30.0)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_sql_query_group_by_order_pushdown() {
+        let sql_plan = plan(
+            "SELECT service, COUNT(DISTINCT user_id) AS users FROM logs GROUP BY service ORDER BY \
+             COUNT(*) DESC LIMIT 5 OFFSET 5",
+        );
+        let aggregations: JsonValue = serde_json::from_str(
+            sql_plan
+                .search_request
+                .aggregation_request
+                .as_ref()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            aggregations,
+            json!({
+                "group_0": {
+                    "terms": {"field": "service", "size": 10, "order": {"_count": "desc"}},
+                    "aggs": {"metric_0": {"cardinality": {"field": "user_id"}}}
+                }
+            })
+        );
+        // Ordering by a cardinality cannot be pushed down.
+        let sql_plan =
+            plan("SELECT service FROM logs GROUP BY service ORDER BY COUNT(DISTINCT user_id)");
+        let aggregations: JsonValue = serde_json::from_str(
+            sql_plan
+                .search_request
+                .aggregation_request
+                .as_ref()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            aggregations["group_0"]["terms"],
+            json!({"field": "service", "size": 1_000})
+        );
+    }
+
+    #[test]
+    fn test_plan_sql_query_group_by_too_many_groups() {
+        let terms_response = |num_buckets: u64, sum_other_doc_count: u64| {
+            let buckets: Vec<JsonValue> = (0..num_buckets)
+                .map(|bucket_idx| json!({"key": format!("service-{bucket_idx}"), "doc_count": 1}))
+                .collect();
+            let aggregation_response = json!({
+                "group_0": {
+                    "buckets": buckets,
+                    "sum_other_doc_count": sum_other_doc_count,
+                    "doc_count_error_upper_bound": 0,
+                }
+            });
+            SearchResponse {
+                num_hits: num_buckets + sum_other_doc_count,
+                aggregation: Some(aggregation_response.to_string()),
+                ..Default::default()
+            }
+        };
+        let sql = "SELECT service, COUNT(DISTINCT user_id) FROM logs GROUP BY service ORDER BY \
+                   COUNT(DISTINCT user_id) DESC";
+
+        let sql_response = plan(sql)
+            .into_response(terms_response(MAX_GROUPS_PER_FIELD, 0))
+            .unwrap();
+        assert_eq!(sql_response.rows.len(), 1_000);
+
+        // 1,001 distinct services: the terms aggregation returns 1,000 buckets and counts the
+        // documents of the last one in `sum_other_doc_count`.
+        let error = plan(sql)
+            .into_response(terms_response(MAX_GROUPS_PER_FIELD, 1))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid SQL query: `GROUP BY` field `service` has more than 1000 distinct values, \
+             narrow down the `WHERE` clause or sort and limit the groups by a single `GROUP BY` \
+             field"
+        );
+
+        // The buckets are expected to be truncated when the limit is pushed down.
+        let sql_response =
+            plan("SELECT service FROM logs GROUP BY service ORDER BY COUNT(*) LIMIT 2")
+                .into_response(terms_response(2, 999))
+                .unwrap();
+        assert_eq!(sql_response.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_plan_sql_query_aggregations_without_group_by() {
+        let sql_plan = plan("SELECT COUNT(*) AS total, SUM(bytes) FROM logs WHERE QUERY('error')");
+        let query_ast: QueryAst = serde_json::from_str(&sql_plan.search_request.query_ast).unwrap();
+        assert_eq!(
+            query_ast,
+            UserInputQuery {
+                user_text: "error".to_string(),
+                default_fields: None,
+                default_operator: BooleanOperand::And,
+                lenient: false,
+            }
+            .into()
+        );
+        let search_response = SearchResponse {
+            num_hits: 12,
+            aggregation: Some(r#"{"metric_0": {"value": 1024.0}}"#.to_string()),
+            ..Default::default()
+        };
+        let sql_response = sql_plan.into_response(search_response).unwrap();
+        assert_eq!(sql_response.columns, vec!["total", "SUM(bytes)"]);
+        assert_eq!(sql_response.rows, vec![vec![json!(12), json!(1024.0)]]);
+    }
+
+    #[test]
+    fn test_plan_sql_query_errors() {
+        assert_eq!(
+            plan_error("SELECT service, COUNT(*) FROM logs"),
+            "`service` must appear in the `GROUP BY` clause or be used in an aggregate function"
+        );
+        assert_eq!(
+            plan_error("SELECT * FROM logs GROUP BY service"),
+            "`SELECT *` cannot be used with `GROUP BY` or aggregate functions"
+        );
+        assert_eq!(
+            plan_error("SELECT SUM(DISTINCT bytes) FROM logs"),
+            "`DISTINCT` is only supported in `COUNT(DISTINCT field)`"
+        );
+        assert_eq!(
+            plan_error("SELECT body FROM logs ORDER BY 2"),
+            "`ORDER BY` position 2 is out of range"
+        );
+        assert_eq!(
+            plan_error("SELECT DATE_TRUNC('day', timestamp) FROM logs"),
+            "`DATE_HISTOGRAM(timestamp, '1d')` requires a `GROUP BY` clause"
+        );
+    }
+}
@@ -0,0 +1,1110 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `significant_terms` and `rare_terms` aggregations.
+//!
+//! These aggregations select the terms of a field by how frequent they are, rather than by their
+//! number of documents like the `terms` aggregation:
+//! - `significant_terms` returns the terms that are over-represented in the matching documents, the
+//!   foreground set, compared with a background set. The background set is the whole index, in
+//!   which case the background frequencies are the document frequencies of the term dictionary, or
+//!   the documents matching a background filter.
+//! - `rare_terms` returns the terms matching at most `max_doc_count` documents.
+//!
+//! The frequencies are computed per split and summed when merging the leaf results. As with
+//! Elasticsearch shards, a split only reports the background frequencies of the terms present in
+//! its foreground set, so the background frequencies are lower bounds.
+//!
+//! These aggregations are only supported at the top level of the aggregation request, on text
+//! fields, and do not support sub-aggregations.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use quickwit_doc_mapper::{DocMapper, WarmupInfo};
+use quickwit_query::block_join::num_root_docs;
+use quickwit_query::query_ast::QueryAst;
+use quickwit_query::ElasticQueryDsl;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
+use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use tantivy::aggregation::{AggregationLimitsGuard, AggregationSegmentCollector};
+use tantivy::collector::SegmentCollector;
+use tantivy::columnar::StrColumn;
+use tantivy::query::{EnableScoring, Query};
+use tantivy::schema::Schema;
+use tantivy::{
+    DocId, DocSet, Score, SegmentOrdinal, SegmentReader, TantivyError, Term, TERMINATED,
+};
+
+use crate::SearchError;
+
+const DEFAULT_SIGNIFICANT_TERMS_SIZE: usize = 10;
+
+const DEFAULT_SIGNIFICANT_TERMS_MIN_DOC_COUNT: u64 = 3;
+
+const DEFAULT_RARE_TERMS_MAX_DOC_COUNT: u64 = 1;
+
+// Same as Elasticsearch: rare terms are expected to be really rare.
+const MAX_RARE_TERMS_MAX_DOC_COUNT: u64 = 100;
+
+// Same as the default bucket limit of tantivy aggregations.
+const MAX_RARE_TERMS_BUCKETS: usize = 65_000;
+
+/// Background filters of the `significant_terms` aggregations, by aggregation name, built for a
+/// split.
+pub(crate) type BackgroundQueries = HashMap<String, Arc<dyn Query>>;
+
+/// Aggregation request containing at least one top-level `significant_terms` or `rare_terms`
+/// aggregation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermFrequencyAggregations {
+    /// Aggregations other than the `significant_terms` and `rare_terms` aggregations.
+    pub root: Aggregations,
+    /// `significant_terms` aggregations, by name.
+    pub significant_terms: BTreeMap<String, SignificantTermsAggregation>,
+    /// `rare_terms` aggregations, by name.
+    pub rare_terms: BTreeMap<String, RareTermsAggregation>,
+}
+
+/// Aggregation returning the terms of a field that are over-represented in the matching
+/// documents compared with a background set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignificantTermsAggregation {
+    pub field: String,
+    /// Maximum number of buckets returned.
+    pub size: usize,
+    /// Maximum number of candidate terms returned by each split.
+    pub shard_size: usize,
+    /// Minimum number of matching documents of the returned terms.
+    pub min_doc_count: u64,
+    /// Query selecting the background set. Defaults to all the documents.
+    pub background_filter: Option<QueryAst>,
+    pub heuristic: SignificanceHeuristic,
+}
+
+/// Scoring of the significance of a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignificanceHeuristic {
+    /// Product of the absolute and relative changes of the frequency of the term.
+    Jlh,
+    ChiSquare {
+        /// If false, the terms that are under-represented in the matching documents are
+        /// discarded.
+        include_negatives: bool,
+        /// Whether the background set contains the matching documents.
+        background_is_superset: bool,
+    },
+}
+
+/// Aggregation returning the terms of a field matching at most `max_doc_count` documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RareTermsAggregation {
+    pub field: String,
+    pub max_doc_count: u64,
+}
+
+fn default_significant_terms_size() -> usize {
+    DEFAULT_SIGNIFICANT_TERMS_SIZE
+}
+
+fn default_significant_terms_min_doc_count() -> u64 {
+    DEFAULT_SIGNIFICANT_TERMS_MIN_DOC_COUNT
+}
+
+fn default_rare_terms_max_doc_count() -> u64 {
+    DEFAULT_RARE_TERMS_MAX_DOC_COUNT
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JlhParams {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChiSquareParams {
+    #[serde(default)]
+    include_negatives: bool,
+    #[serde(default = "default_true")]
+    background_is_superset: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignificantTermsParams {
+    field: String,
+    #[serde(default = "default_significant_terms_size")]
+    size: usize,
+    #[serde(default)]
+    shard_size: Option<usize>,
+    #[serde(default = "default_significant_terms_min_doc_count")]
+    min_doc_count: u64,
+    #[serde(default)]
+    background_filter: Option<JsonValue>,
+    #[serde(default)]
+    jlh: Option<JlhParams>,
+    #[serde(default)]
+    chi_square: Option<ChiSquareParams>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignificantTermsAggregationJson {
+    significant_terms: SignificantTermsParams,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RareTermsParams {
+    field: String,
+    #[serde(default = "default_rare_terms_max_doc_count")]
+    max_doc_count: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RareTermsAggregationJson {
+    rare_terms: RareTermsParams,
+}
+
+impl TryFrom<JsonMap<String, JsonValue>> for TermFrequencyAggregations {
+    type Error = anyhow::Error;
+
+    fn try_from(aggregations_json: JsonMap<String, JsonValue>) -> anyhow::Result<Self> {
+        let mut root_json = JsonMap::new();
+        let mut significant_terms = BTreeMap::new();
+        let mut rare_terms = BTreeMap::new();
+        for (name, aggregation_json) in aggregations_json {
+            if aggregation_json.get("significant_terms").is_some() {
+                let significant_terms_aggregation =
+                    parse_significant_terms_aggregation(aggregation_json).with_context(|| {
+                        format!("invalid significant_terms aggregation `{name}`")
+                    })?;
+                significant_terms.insert(name, significant_terms_aggregation);
+            } else if aggregation_json.get("rare_terms").is_some() {
+                let rare_terms_aggregation = parse_rare_terms_aggregation(aggregation_json)
+                    .with_context(|| format!("invalid rare_terms aggregation `{name}`"))?;
+                rare_terms.insert(name, rare_terms_aggregation);
+            } else {
+                root_json.insert(name, aggregation_json);
+            }
+        }
+        if significant_terms.is_empty() && rare_terms.is_empty() {
+            bail!("no significant_terms or rare_terms aggregation");
+        }
+        let root = serde_json::from_value(JsonValue::Object(root_json))?;
+        Ok(TermFrequencyAggregations {
+            root,
+            significant_terms,
+            rare_terms,
+        })
+    }
+}
+
+fn parse_significant_terms_aggregation(
+    aggregation_json: JsonValue,
+) -> anyhow::Result<SignificantTermsAggregation> {
+    if aggregation_json.get("aggs").is_some() || aggregation_json.get("aggregations").is_some() {
+        bail!("sub-aggregations are not supported");
+    }
+    let SignificantTermsAggregationJson { significant_terms } =
+        serde_json::from_value(aggregation_json)?;
+    if significant_terms.size == 0 {
+        bail!("`size` must be greater than 0");
+    }
+    // Same default as Elasticsearch.
+    let shard_size = significant_terms
+        .shard_size
+        .unwrap_or(significant_terms.size * 3 / 2 + 10)
+        .max(significant_terms.size);
+    let heuristic = match (significant_terms.jlh, significant_terms.chi_square) {
+        (None, None) | (Some(_), None) => SignificanceHeuristic::Jlh,
+        (None, Some(chi_square)) => SignificanceHeuristic::ChiSquare {
+            include_negatives: chi_square.include_negatives,
+            background_is_superset: chi_square.background_is_superset,
+        },
+        (Some(_), Some(_)) => bail!("only one significance heuristic can be set"),
+    };
+    let background_filter = significant_terms
+        .background_filter
+        .map(parse_background_filter)
+        .transpose()
+        .context("invalid `background_filter`")?;
+    Ok(SignificantTermsAggregation {
+        field: significant_terms.field,
+        size: significant_terms.size,
+        shard_size,
+        min_doc_count: significant_terms.min_doc_count,
+        background_filter,
+        heuristic,
+    })
+}
+
+/// Parses a background filter, written either in the Elasticsearch query DSL or as a query AST.
+fn parse_background_filter(query_json: JsonValue) -> anyhow::Result<QueryAst> {
+    if let Ok(query_ast) = serde_json::from_value(query_json.clone()) {
+        return Ok(query_ast);
+    }
+    let elastic_query_dsl: ElasticQueryDsl = serde_json::from_value(query_json)?;
+    elastic_query_dsl.try_into()
+}
+
+fn parse_rare_terms_aggregation(
+    aggregation_json: JsonValue,
+) -> anyhow::Result<RareTermsAggregation> {
+    if aggregation_json.get("aggs").is_some() || aggregation_json.get("aggregations").is_some() {
+        bail!("sub-aggregations are not supported");
+    }
+    let RareTermsAggregationJson { rare_terms } = serde_json::from_value(aggregation_json)?;
+    if rare_terms.max_doc_count == 0 || rare_terms.max_doc_count > MAX_RARE_TERMS_MAX_DOC_COUNT {
+        bail!("`max_doc_count` must be between 1 and {MAX_RARE_TERMS_MAX_DOC_COUNT}");
+    }
+    Ok(RareTermsAggregation {
+        field: rare_terms.field,
+        max_doc_count: rare_terms.max_doc_count,
+    })
+}
+
+impl<'de> Deserialize<'de> for TermFrequencyAggregations {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let aggregations_json = JsonMap::<String, JsonValue>::deserialize(deserializer)?;
+        TermFrequencyAggregations::try_from(aggregations_json).map_err(serde::de::Error::custom)
+    }
+}
+
+impl TermFrequencyAggregations {
+    /// Returns the list of fast fields that should be loaded for the aggregation.
+    pub fn fast_field_names(&self) -> HashSet<String> {
+        let mut fast_field_names = get_fast_field_names(&self.root);
+        for significant_terms_aggregation in self.significant_terms.values() {
+            fast_field_names.insert(significant_terms_aggregation.field.clone());
+        }
+        for rare_terms_aggregation in self.rare_terms.values() {
+            fast_field_names.insert(rare_terms_aggregation.field.clone());
+        }
+        fast_field_names
+    }
+
+    /// Builds the background filters of the `significant_terms` aggregations for a split.
+    ///
+    /// The returned warmup info covers the background filters, and the term dictionaries used as
+    /// background by the aggregations without background filter.
+    pub(crate) fn build_background_queries(
+        &self,
+        doc_mapper: &DocMapper,
+        split_schema: &Schema,
+    ) -> crate::Result<(BackgroundQueries, WarmupInfo)> {
+        let mut background_queries = BackgroundQueries::new();
+        let mut warmup_info = WarmupInfo::default();
+        for (name, significant_terms_aggregation) in &self.significant_terms {
+            let Some(background_filter) = &significant_terms_aggregation.background_filter else {
+                if let Ok(field) = split_schema.get_field(&significant_terms_aggregation.field) {
+                    warmup_info.term_dict_fields.insert(field);
+                }
+                continue;
+            };
+            let query_ast = background_filter
+                .clone()
+                .parse_user_query(doc_mapper.default_search_fields())
+                .map_err(|error| SearchError::InvalidQuery(error.to_string()))?;
+            let (query, query_warmup_info) =
+                doc_mapper.query(split_schema.clone(), &query_ast, false)?;
+            warmup_info.merge(query_warmup_info);
+            background_queries.insert(name.clone(), Arc::from(query));
+        }
+        Ok((background_queries, warmup_info))
+    }
+}
+
+impl SignificanceHeuristic {
+    /// Scores a term matching `subset_df` of the `subset_size` foreground documents and
+    /// `superset_df` of the `superset_size` background documents. Only the terms with a
+    /// positive score are significant.
+    fn score(&self, subset_df: u64, subset_size: u64, superset_df: u64, superset_size: u64) -> f64 {
+        match *self {
+            SignificanceHeuristic::Jlh => {
+                if subset_size == 0 || superset_size == 0 {
+                    return 0.0;
+                }
+                let subset_probability = subset_df as f64 / subset_size as f64;
+                let superset_probability = superset_df.max(1) as f64 / superset_size as f64;
+                let absolute_probability_change = subset_probability - superset_probability;
+                if absolute_probability_change <= 0.0 {
+                    return 0.0;
+                }
+                let relative_probability_change = subset_probability / superset_probability;
+                absolute_probability_change * relative_probability_change
+            }
+            SignificanceHeuristic::ChiSquare {
+                include_negatives,
+                background_is_superset,
+            } => {
+                // `n_xy` is the number of documents containing the term if `x` is 1, and in the
+                // foreground set if `y` is 1.
+                let (n_00, n_01, n_10, n_11) = if background_is_superset {
+                    (
+                        superset_size
+                            .saturating_sub(superset_df)
+                            .saturating_sub(subset_size.saturating_sub(subset_df)),
+                        subset_size.saturating_sub(subset_df),
+                        superset_df.saturating_sub(subset_df),
+                        subset_df,
+                    )
+                } else {
+                    (
+                        superset_size.saturating_sub(superset_df),
+                        subset_size.saturating_sub(subset_df),
+                        superset_df,
+                        subset_df,
+                    )
+                };
+                let [n_00, n_01, n_10, n_11] = [n_00, n_01, n_10, n_11].map(|n| n as f64);
+                let n_0_ = n_00 + n_01;
+                let n_1_ = n_10 + n_11;
+                let n__0 = n_00 + n_10;
+                let n__1 = n_01 + n_11;
+                let n = n_0_ + n_1_;
+                if !include_negatives && n_11 / n__1 < n_10 / n__0 {
+                    return f64::NEG_INFINITY;
+                }
+                let denominator = n__1 * n_1_ * n_0_ * n__0;
+                if denominator == 0.0 {
+                    return 0.0;
+                }
+                n * (n_11 * n_00 - n_01 * n_10).powi(2) / denominator
+            }
+        }
+    }
+}
+
+/// Foreground and background document frequencies of a term.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct TermFrequencies {
+    subset_df: u64,
+    superset_df: u64,
+}
+
+/// Intermediate results of a [`SignificantTermsAggregation`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SignificantTermsIntermediateResult {
+    /// Number of foreground documents.
+    subset_size: u64,
+    /// Number of background documents.
+    superset_size: u64,
+    /// Frequencies of the candidate terms.
+    terms: BTreeMap<String, TermFrequencies>,
+}
+
+impl SignificantTermsIntermediateResult {
+    fn score(&self, term_frequencies: &TermFrequencies, heuristic: SignificanceHeuristic) -> f64 {
+        heuristic.score(
+            term_frequencies.subset_df,
+            self.subset_size,
+            term_frequencies.superset_df,
+            self.superset_size,
+        )
+    }
+
+    /// Returns the terms sorted by decreasing score.
+    fn sorted_terms(self, heuristic: SignificanceHeuristic) -> Vec<(String, TermFrequencies, f64)> {
+        let mut scored_terms: Vec<(String, TermFrequencies, f64)> = self
+            .terms
+            .iter()
+            .map(|(term, term_frequencies)| {
+                let score = self.score(term_frequencies, heuristic);
+                (term.clone(), *term_frequencies, score)
+            })
+            .collect();
+        scored_terms.sort_by(|(left_term, _, left_score), (right_term, _, right_score)| {
+            right_score
+                .total_cmp(left_score)
+                .then_with(|| left_term.cmp(right_term))
+        });
+        scored_terms
+    }
+
+    /// Only keeps the `shard_size` terms with the highest scores.
+    fn truncate(&mut self, aggregation: &SignificantTermsAggregation) {
+        if self.terms.len() <= aggregation.shard_size {
+            return;
+        }
+        let subset_size = self.subset_size;
+        let superset_size = self.superset_size;
+        let sorted_terms = std::mem::take(self).sorted_terms(aggregation.heuristic);
+        self.subset_size = subset_size;
+        self.superset_size = superset_size;
+        self.terms = sorted_terms
+            .into_iter()
+            .take(aggregation.shard_size)
+            .map(|(term, term_frequencies, _)| (term, term_frequencies))
+            .collect();
+    }
+
+    fn merge_fruits(
+        &mut self,
+        other: SignificantTermsIntermediateResult,
+        aggregation: &SignificantTermsAggregation,
+    ) {
+        self.subset_size += other.subset_size;
+        self.superset_size += other.superset_size;
+        for (term, other_term_frequencies) in other.terms {
+            let term_frequencies = self.terms.entry(term).or_default();
+            term_frequencies.subset_df += other_term_frequencies.subset_df;
+            term_frequencies.superset_df += other_term_frequencies.superset_df;
+        }
+        self.truncate(aggregation);
+    }
+
+    fn into_final_result(self, aggregation: &SignificantTermsAggregation) -> JsonValue {
+        let subset_size = self.subset_size;
+        let superset_size = self.superset_size;
+        let buckets_json: Vec<JsonValue> = self
+            .sorted_terms(aggregation.heuristic)
+            .into_iter()
+            .filter(|(_, term_frequencies, score)| {
+                *score > 0.0 && term_frequencies.subset_df >= aggregation.min_doc_count
+            })
+            .take(aggregation.size)
+            .map(|(term, term_frequencies, score)| {
+                serde_json::json!({
+                    "key": term,
+                    "doc_count": term_frequencies.subset_df,
+                    "bg_count": term_frequencies.superset_df,
+                    "score": score,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "doc_count": subset_size,
+            "bg_count": superset_size,
+            "buckets": buckets_json,
+        })
+    }
+}
+
+/// Intermediate results of a [`RareTermsAggregation`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RareTermsIntermediateResult {
+    /// Number of documents of each term, capped at `max_doc_count + 1`: the terms matching more
+    /// documents are kept to know that they are not rare in the other splits either.
+    doc_counts: BTreeMap<String, u64>,
+}
+
+impl RareTermsIntermediateResult {
+    fn merge_fruits(
+        &mut self,
+        other: RareTermsIntermediateResult,
+        aggregation: &RareTermsAggregation,
+    ) {
+        for (term, other_doc_count) in other.doc_counts {
+            let doc_count = self.doc_counts.entry(term).or_default();
+            *doc_count = (*doc_count + other_doc_count).min(aggregation.max_doc_count + 1);
+        }
+    }
+
+    fn into_final_result(self, aggregation: &RareTermsAggregation) -> tantivy::Result<JsonValue> {
+        let mut rare_terms: Vec<(String, u64)> = self
+            .doc_counts
+            .into_iter()
+            .filter(|(_, doc_count)| *doc_count <= aggregation.max_doc_count)
+            .collect();
+        if rare_terms.len() > MAX_RARE_TERMS_BUCKETS {
+            return Err(TantivyError::InvalidArgument(format!(
+                "rare_terms aggregation on field `{}` returned more than {MAX_RARE_TERMS_BUCKETS} \
+                 buckets",
+                aggregation.field
+            )));
+        }
+        // The terms are already sorted, and the sort is stable.
+        rare_terms.sort_by_key(|(_, doc_count)| *doc_count);
+        let buckets_json: Vec<JsonValue> = rare_terms
+            .into_iter()
+            .map(|(term, doc_count)| serde_json::json!({"key": term, "doc_count": doc_count}))
+            .collect();
+        Ok(serde_json::json!({ "buckets": buckets_json }))
+    }
+}
+
+/// Intermediate results of [`TermFrequencyAggregations`].
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct TermFrequencyIntermediateAggregationResults {
+    root: IntermediateAggregationResults,
+    significant_terms: BTreeMap<String, SignificantTermsIntermediateResult>,
+    rare_terms: BTreeMap<String, RareTermsIntermediateResult>,
+}
+
+impl TermFrequencyIntermediateAggregationResults {
+    pub fn merge_fruits(
+        &mut self,
+        other: TermFrequencyIntermediateAggregationResults,
+        aggregations: &TermFrequencyAggregations,
+    ) -> tantivy::Result<()> {
+        self.root.merge_fruits(other.root)?;
+        for (name, other_result) in other.significant_terms {
+            let Some(aggregation) = aggregations.significant_terms.get(&name) else {
+                continue;
+            };
+            self.significant_terms
+                .entry(name)
+                .or_default()
+                .merge_fruits(other_result, aggregation);
+        }
+        for (name, other_result) in other.rare_terms {
+            let Some(aggregation) = aggregations.rare_terms.get(&name) else {
+                continue;
+            };
+            self.rare_terms
+                .entry(name)
+                .or_default()
+                .merge_fruits(other_result, aggregation);
+        }
+        Ok(())
+    }
+
+    /// Converts the intermediate results into the final JSON results.
+    pub fn into_final_result(
+        mut self,
+        aggregations: TermFrequencyAggregations,
+        limits: AggregationLimitsGuard,
+    ) -> tantivy::Result<JsonValue> {
+        let final_root_result = self.root.into_final_result(aggregations.root, limits)?;
+        let JsonValue::Object(mut final_result_json) =
+            serde_json::to_value(final_root_result).map_err(map_error)?
+        else {
+            return Err(TantivyError::InternalError(
+                "aggregation results should serialize to a JSON object".to_string(),
+            ));
+        };
+        for (name, aggregation) in aggregations.significant_terms {
+            let result = self.significant_terms.remove(&name).unwrap_or_default();
+            final_result_json.insert(name, result.into_final_result(&aggregation));
+        }
+        for (name, aggregation) in aggregations.rare_terms {
+            let result = self.rare_terms.remove(&name).unwrap_or_default();
+            final_result_json.insert(name, result.into_final_result(&aggregation)?);
+        }
+        Ok(JsonValue::Object(final_result_json))
+    }
+}
+
+fn map_error(err: serde_json::Error) -> TantivyError {
+    TantivyError::InternalError(format!("failed to serialize aggregation results: {err}"))
+}
+
+/// Counts the documents of each term of a column.
+#[derive(Default)]
+struct TermOrdCounts {
+    doc_counts: HashMap<u64, u64>,
+}
+
+impl TermOrdCounts {
+    fn collect(&mut self, column: &StrColumn, doc: DocId) {
+        for term_ord in column.term_ords(doc) {
+            *self.doc_counts.entry(term_ord).or_default() += 1;
+        }
+    }
+}
+
+struct SignificantTermsSegmentCollector {
+    name: String,
+    aggregation: SignificantTermsAggregation,
+    background_query_opt: Option<Arc<dyn Query>>,
+    /// `None` if the segment does not contain any value for the field.
+    column_opt: Option<StrColumn>,
+    subset_size: u64,
+    subset_dfs: TermOrdCounts,
+}
+
+impl SignificantTermsSegmentCollector {
+    fn harvest(
+        self,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<SignificantTermsIntermediateResult> {
+        let mut superset_dfs: HashMap<u64, u64> = HashMap::new();
+        let superset_size = if let Some(background_query) = &self.background_query_opt {
+            let weight = background_query
+                .weight(EnableScoring::disabled_from_schema(segment_reader.schema()))?;
+            let mut scorer = weight.scorer(segment_reader, 1.0)?;
+            let alive_bitset_opt = segment_reader.alive_bitset();
+            let mut superset_size = 0;
+            let mut doc = scorer.doc();
+            while doc != TERMINATED {
+                if alive_bitset_opt.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                    superset_size += 1;
+                    if let Some(column) = &self.column_opt {
+                        for term_ord in column.term_ords(doc) {
+                            if self.subset_dfs.doc_counts.contains_key(&term_ord) {
+                                *superset_dfs.entry(term_ord).or_default() += 1;
+                            }
+                        }
+                    }
+                }
+                doc = scorer.advance();
+            }
+            superset_size
+        } else {
+            num_root_docs(segment_reader)? as u64
+        };
+        let mut result = SignificantTermsIntermediateResult {
+            subset_size: self.subset_size,
+            superset_size,
+            terms: BTreeMap::new(),
+        };
+        let Some(column) = &self.column_opt else {
+            return Ok(result);
+        };
+        let field_opt = segment_reader
+            .schema()
+            .get_field(&self.aggregation.field)
+            .ok();
+        let mut term_buffer = String::new();
+        for (&term_ord, &subset_df) in &self.subset_dfs.doc_counts {
+            term_buffer.clear();
+            column.ord_to_str(term_ord, &mut term_buffer)?;
+            let superset_df = if self.background_query_opt.is_some() {
+                superset_dfs.get(&term_ord).copied().unwrap_or_default()
+            } else if let Some(field) = field_opt {
+                // Without background filter, the background frequency of the term is its
+                // document frequency.
+                let term = Term::from_field_text(field, &term_buffer);
+                segment_reader.inverted_index(field)?.doc_freq(&term)? as u64
+            } else {
+                0
+            };
+            let term_frequencies = TermFrequencies {
+                subset_df,
+                superset_df,
+            };
+            result.terms.insert(term_buffer.clone(), term_frequencies);
+        }
+        result.truncate(&self.aggregation);
+        Ok(result)
+    }
+}
+
+struct RareTermsSegmentCollector {
+    name: String,
+    aggregation: RareTermsAggregation,
+    /// `None` if the segment does not contain any value for the field.
+    column_opt: Option<StrColumn>,
+    doc_counts: TermOrdCounts,
+}
+
+impl RareTermsSegmentCollector {
+    fn harvest(self) -> tantivy::Result<RareTermsIntermediateResult> {
+        let mut result = RareTermsIntermediateResult::default();
+        let Some(column) = &self.column_opt else {
+            return Ok(result);
+        };
+        let mut term_buffer = String::new();
+        for (&term_ord, &doc_count) in &self.doc_counts.doc_counts {
+            term_buffer.clear();
+            column.ord_to_str(term_ord, &mut term_buffer)?;
+            let doc_count = doc_count.min(self.aggregation.max_doc_count + 1);
+            result.doc_counts.insert(term_buffer.clone(), doc_count);
+        }
+        Ok(result)
+    }
+}
+
+/// Segment collector of [`TermFrequencyAggregations`].
+pub(crate) struct TermFrequencyAggregationsSegmentCollector {
+    root: AggregationSegmentCollector,
+    significant_terms: Vec<SignificantTermsSegmentCollector>,
+    rare_terms: Vec<RareTermsSegmentCollector>,
+    segment_reader: SegmentReader,
+}
+
+impl TermFrequencyAggregationsSegmentCollector {
+    pub fn from_agg_req_and_reader(
+        aggregations: &TermFrequencyAggregations,
+        background_queries: &BackgroundQueries,
+        segment_reader: &SegmentReader,
+        segment_ord: SegmentOrdinal,
+        limits: &AggregationLimitsGuard,
+    ) -> tantivy::Result<Self> {
+        let root = AggregationSegmentCollector::from_agg_req_and_reader(
+            &aggregations.root,
+            segment_reader,
+            segment_ord,
+            limits,
+        )?;
+        let mut significant_terms = Vec::with_capacity(aggregations.significant_terms.len());
+        for (name, aggregation) in &aggregations.significant_terms {
+            let background_query_opt = background_queries.get(name).cloned();
+            if aggregation.background_filter.is_some() && background_query_opt.is_none() {
+                return Err(TantivyError::InternalError(format!(
+                    "background filter of significant_terms aggregation `{name}` was not built"
+                )));
+            }
+            significant_terms.push(SignificantTermsSegmentCollector {
+                name: name.clone(),
+                aggregation: aggregation.clone(),
+                background_query_opt,
+                column_opt: segment_reader.fast_fields().str(&aggregation.field)?,
+                subset_size: 0,
+                subset_dfs: TermOrdCounts::default(),
+            });
+        }
+        let mut rare_terms = Vec::with_capacity(aggregations.rare_terms.len());
+        for (name, aggregation) in &aggregations.rare_terms {
+            rare_terms.push(RareTermsSegmentCollector {
+                name: name.clone(),
+                aggregation: aggregation.clone(),
+                column_opt: segment_reader.fast_fields().str(&aggregation.field)?,
+                doc_counts: TermOrdCounts::default(),
+            });
+        }
+        Ok(TermFrequencyAggregationsSegmentCollector {
+            root,
+            significant_terms,
+            rare_terms,
+            segment_reader: segment_reader.clone(),
+        })
+    }
+
+    pub fn collect_block(&mut self, docs: &[DocId]) {
+        for &doc in docs {
+            self.collect(doc, 0.0);
+        }
+    }
+
+    pub fn collect(&mut self, doc: DocId, score: Score) {
+        self.root.collect(doc, score);
+        for significant_terms_collector in &mut self.significant_terms {
+            significant_terms_collector.subset_size += 1;
+            if let Some(column) = &significant_terms_collector.column_opt {
+                significant_terms_collector.subset_dfs.collect(column, doc);
+            }
+        }
+        for rare_terms_collector in &mut self.rare_terms {
+            if let Some(column) = &rare_terms_collector.column_opt {
+                rare_terms_collector.doc_counts.collect(column, doc);
+            }
+        }
+    }
+
+    pub fn harvest(self) -> tantivy::Result<TermFrequencyIntermediateAggregationResults> {
+        let root = self.root.harvest()?;
+        let mut significant_terms = BTreeMap::new();
+        for significant_terms_collector in self.significant_terms {
+            let name = significant_terms_collector.name.clone();
+            let result = significant_terms_collector.harvest(&self.segment_reader)?;
+            significant_terms.insert(name, result);
+        }
+        let mut rare_terms = BTreeMap::new();
+        for rare_terms_collector in self.rare_terms {
+            let name = rare_terms_collector.name.clone();
+            rare_terms.insert(name, rare_terms_collector.harvest()?);
+        }
+        Ok(TermFrequencyIntermediateAggregationResults {
+            root,
+            significant_terms,
+            rare_terms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tantivy::query::TermQuery;
+    use tantivy::schema::{Field, IndexRecordOption, FAST, STRING};
+    use tantivy::{doc, Index};
+
+    use super::*;
+    use crate::collector::QuickwitAggregations;
+
+    #[test]
+    fn test_term_frequency_aggregations_serde() {
+        let aggregations: QuickwitAggregations = serde_json::from_value(json!({
+            "num_services": {"value_count": {"field": "service"}},
+            "significant_tags": {
+                "significant_terms": {
+                    "field": "tag",
+                    "min_doc_count": 1,
+                    "background_filter": {"term": {"service": {"value": "db"}}},
+                    "chi_square": {"include_negatives": true}
+                }
+            },
+            "rare_tags": {"rare_terms": {"field": "tag", "max_doc_count": 2}}
+        }))
+        .unwrap();
+        let QuickwitAggregations::TermFrequencyAggregations(term_frequency_aggregations) =
+            aggregations
+        else {
+            panic!("expected term frequency aggregations");
+        };
+        assert_eq!(term_frequency_aggregations.root.len(), 1);
+        let significant_terms_aggregation =
+            &term_frequency_aggregations.significant_terms["significant_tags"];
+        assert_eq!(significant_terms_aggregation.field, "tag");
+        assert_eq!(significant_terms_aggregation.size, 10);
+        assert_eq!(significant_terms_aggregation.shard_size, 25);
+        assert_eq!(significant_terms_aggregation.min_doc_count, 1);
+        assert!(matches!(
+            significant_terms_aggregation.background_filter,
+            Some(QueryAst::Term(_))
+        ));
+        assert_eq!(
+            significant_terms_aggregation.heuristic,
+            SignificanceHeuristic::ChiSquare {
+                include_negatives: true,
+                background_is_superset: true,
+            }
+        );
+        assert_eq!(
+            term_frequency_aggregations.rare_terms["rare_tags"],
+            RareTermsAggregation {
+                field: "tag".to_string(),
+                max_doc_count: 2,
+            }
+        );
+        assert_eq!(
+            term_frequency_aggregations.fast_field_names(),
+            HashSet::from(["service".to_string(), "tag".to_string()])
+        );
+
+        let aggregations: QuickwitAggregations =
+            serde_json::from_value(json!({"tags": {"terms": {"field": "tag"}}})).unwrap();
+        assert!(matches!(
+            aggregations,
+            QuickwitAggregations::TantivyAggregations(_)
+        ));
+    }
+
+    #[test]
+    fn test_term_frequency_aggregations_invalid() {
+        for aggregations_json in [
+            json!({
+                "tags": {
+                    "significant_terms": {"field": "tag"},
+                    "aggs": {"num_services": {"value_count": {"field": "service"}}}
+                }
+            }),
+            json!({"tags": {"significant_terms": {"field": "tag", "jlh": {}, "chi_square": {}}}}),
+            json!({"tags": {"significant_terms": {"field": "tag", "gnd": {}}}}),
+            json!({"tags": {"rare_terms": {"field": "tag", "max_doc_count": 0}}}),
+            json!({"tags": {"rare_terms": {"field": "tag", "max_doc_count": 101}}}),
+        ] {
+            let aggregations_json = aggregations_json.as_object().unwrap().clone();
+            TermFrequencyAggregations::try_from(aggregations_json).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_significance_heuristic_score() {
+        let jlh_score = SignificanceHeuristic::Jlh.score(2, 3, 2, 6);
+        assert!((jlh_score - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(SignificanceHeuristic::Jlh.score(1, 3, 2, 6), 0.0);
+
+        let chi_square = SignificanceHeuristic::ChiSquare {
+            include_negatives: false,
+            background_is_superset: true,
+        };
+        assert_eq!(chi_square.score(2, 3, 2, 6), 3.0);
+        assert_eq!(chi_square.score(0, 3, 3, 6), f64::NEG_INFINITY);
+        let chi_square_with_negatives = SignificanceHeuristic::ChiSquare {
+            include_negatives: true,
+            background_is_superset: true,
+        };
+        assert_eq!(chi_square_with_negatives.score(0, 3, 3, 6), 6.0);
+    }
+
+    fn create_index() -> (Index, Field) {
+        let mut schema_builder = Schema::builder();
+        let service = schema_builder.add_text_field("service", STRING | FAST);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for (service_val, tag_val) in [
+            ("api", "error"),
+            ("api", "error"),
+            ("api", "timeout"),
+            ("db", "ok"),
+            ("db", "ok"),
+            ("db", "ok"),
+        ] {
+            index_writer
+                .add_document(doc!(service => service_val, tag => tag_val))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        (index, service)
+    }
+
+    fn collect(
+        index: &Index,
+        aggregations: &TermFrequencyAggregations,
+        background_queries: &BackgroundQueries,
+        docs: &[DocId],
+    ) -> TermFrequencyIntermediateAggregationResults {
+        let searcher = index.reader().unwrap().searcher();
+        let mut collector = TermFrequencyAggregationsSegmentCollector::from_agg_req_and_reader(
+            aggregations,
+            background_queries,
+            searcher.segment_reader(0),
+            0,
+            &AggregationLimitsGuard::default(),
+        )
+        .unwrap();
+        collector.collect_block(docs);
+        collector.harvest().unwrap()
+    }
+
+    #[test]
+    fn test_significant_terms_collector() {
+        let (index, service) = create_index();
+        let aggregations_json = json!({
+            "significant_tags": {"significant_terms": {"field": "tag", "min_doc_count": 1}}
+        });
+        let aggregations: TermFrequencyAggregations =
+            serde_json::from_value(aggregations_json).unwrap();
+        // The foreground set is the documents of the `api` service.
+        let intermediate_results = collect(&index, &aggregations, &HashMap::new(), &[0, 1, 2]);
+        let results = intermediate_results
+            .into_final_result(aggregations, AggregationLimitsGuard::default())
+            .unwrap();
+        let significant_tags = &results["significant_tags"];
+        assert_eq!(significant_tags["doc_count"], 3);
+        assert_eq!(significant_tags["bg_count"], 6);
+        let buckets = significant_tags["buckets"].as_array().unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0]["key"], "error");
+        assert_eq!(buckets[0]["doc_count"], 2);
+        assert_eq!(buckets[0]["bg_count"], 2);
+        assert!((buckets[0]["score"].as_f64().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(buckets[1]["key"], "timeout");
+        assert_eq!(buckets[1]["bg_count"], 1);
+
+        let aggregations_json = json!({
+            "significant_tags": {
+                "significant_terms": {
+                    "field": "tag",
+                    "min_doc_count": 1,
+                    "background_filter": {"term": {"service": {"value": "db"}}}
+                }
+            }
+        });
+        let aggregations: TermFrequencyAggregations =
+            serde_json::from_value(aggregations_json).unwrap();
+        let background_query: Arc<dyn Query> = Arc::new(TermQuery::new(
+            Term::from_field_text(service, "db"),
+            IndexRecordOption::Basic,
+        ));
+        let background_queries =
+            HashMap::from([("significant_tags".to_string(), background_query)]);
+        // Two leaves collecting the same documents.
+        let mut intermediate_results =
+            collect(&index, &aggregations, &background_queries, &[0, 1, 2]);
+        intermediate_results
+            .merge_fruits(
+                collect(&index, &aggregations, &background_queries, &[0, 1, 2]),
+                &aggregations,
+            )
+            .unwrap();
+        let results = intermediate_results
+            .into_final_result(aggregations, AggregationLimitsGuard::default())
+            .unwrap();
+        // The background frequencies are summed over the leaves.
+        let significant_tags = &results["significant_tags"];
+        assert_eq!(significant_tags["doc_count"], 6);
+        assert_eq!(significant_tags["bg_count"], 6);
+        let buckets = significant_tags["buckets"].as_array().unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0]["key"], "error");
+        assert_eq!(buckets[0]["doc_count"], 4);
+        assert_eq!(buckets[0]["bg_count"], 0);
+        assert!((buckets[0]["score"].as_f64().unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(buckets[1]["key"], "timeout");
+        assert_eq!(buckets[1]["doc_count"], 2);
+        assert_eq!(buckets[1]["bg_count"], 0);
+    }
+
+    #[test]
+    fn test_significant_terms_background_filter_not_built() {
+        let (index, _) = create_index();
+        let aggregations_json = json!({
+            "significant_tags": {
+                "significant_terms": {
+                    "field": "tag",
+                    "background_filter": {"term": {"service": {"value": "db"}}}
+                }
+            }
+        });
+        let aggregations: TermFrequencyAggregations =
+            serde_json::from_value(aggregations_json).unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        TermFrequencyAggregationsSegmentCollector::from_agg_req_and_reader(
+            &aggregations,
+            &HashMap::new(),
+            searcher.segment_reader(0),
+            0,
+            &AggregationLimitsGuard::default(),
+        )
+        .err()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_rare_terms_collector() {
+        let (index, _) = create_index();
+        let aggregations_json = json!({
+            "services": {"terms": {"field": "service"}},
+            "rare_tags": {"rare_terms": {"field": "tag"}}
+        });
+        let aggregations: TermFrequencyAggregations =
+            serde_json::from_value(aggregations_json).unwrap();
+        let intermediate_results = collect(&index, &aggregations, &HashMap::new(), &[0, 2, 3]);
+        assert_eq!(
+            intermediate_results.rare_terms["rare_tags"].doc_counts,
+            BTreeMap::from([
+                ("error".to_string(), 1),
+                ("ok".to_string(), 1),
+                ("timeout".to_string(), 1),
+            ])
+        );
+        let mut intermediate_results = collect(&index, &aggregations, &HashMap::new(), &[0, 1]);
+        intermediate_results
+            .merge_fruits(
+                collect(&index, &aggregations, &HashMap::new(), &[2, 3, 4, 5]),
+                &aggregations,
+            )
+            .unwrap();
+        // The document counts are capped at `max_doc_count + 1`.
+        assert_eq!(
+            intermediate_results.rare_terms["rare_tags"].doc_counts,
+            BTreeMap::from([
+                ("error".to_string(), 2),
+                ("ok".to_string(), 2),
+                ("timeout".to_string(), 1),
+            ])
+        );
+        let results = intermediate_results
+            .into_final_result(aggregations, AggregationLimitsGuard::default())
+            .unwrap();
+        assert_eq!(results["services"]["buckets"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            results["rare_tags"],
+            json!({"buckets": [{"key": "timeout", "doc_count": 1}]})
+        );
+    }
+}
@@ -15,7 +15,6 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use assert_json_diff::{assert_json_eq, assert_json_include};
-use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
 use quickwit_doc_mapper::DocMapper;
 use quickwit_indexing::TestSandbox;
@@ -1044,8 +1043,7 @@ async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
         max_hits: 100,
         ..Default::default()
     });
-    let searcher_context: Arc<SearcherContext> =
-        Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+    let searcher_context: Arc<SearcherContext> = Arc::new(SearcherContext::for_test());
 
     let agg_limits = searcher_context.get_aggregation_limits();
 
@@ -1681,7 +1679,7 @@ async fn test_single_node_list_terms() -> anyhow::Result<()> {
         .into_iter()
         .map(|split| extract_split_and_footer_offsets(&split.split_metadata))
         .collect();
-    let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+    let searcher_context = Arc::new(SearcherContext::for_test());
 
     {
         let request = ListTermsRequest {
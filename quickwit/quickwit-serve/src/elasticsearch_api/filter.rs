@@ -20,7 +20,7 @@ use warp::{Filter, Rejection};
 
 use super::model::{
     CatIndexQueryParams, DeleteQueryParams, FieldCapabilityQueryParams, FieldCapabilityRequestBody,
-    MultiSearchQueryParams, SearchQueryParamsCount,
+    MultiSearchQueryParams, ReindexBody, SearchQueryParamsCount,
 };
 use crate::decompression::get_body_bytes;
 use crate::elasticsearch_api::model::{
@@ -186,6 +186,15 @@ pub(crate) fn elastic_delete_index_filter(
         .and(serde_qs::warp::query(serde_qs::Config::default()))
 }
 
+#[utoipa::path(post, tag = "Indexes", path = "/_reindex")]
+pub(crate) fn elastic_reindex_filter(
+) -> impl Filter<Extract = (ReindexBody,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_reindex")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(BODY_LENGTH_LIMIT.as_u64()))
+        .and(warp::body::json())
+}
+
 // No support for any query parameters for now.
 #[utoipa::path(get, tag = "Search", path = "/{index}/_stats")]
 pub(crate) fn elastic_index_stats_filter(
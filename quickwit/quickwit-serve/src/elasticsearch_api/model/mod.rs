@@ -18,6 +18,7 @@ mod cat_indices;
 mod error;
 mod field_capability;
 mod multi_search;
+mod reindex;
 mod scroll;
 mod search_body;
 mod search_query_params;
@@ -39,6 +40,7 @@ pub use multi_search::{
     MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse, MultiSearchSingleResponse,
 };
 use quickwit_proto::search::{SortDatetimeFormat, SortOrder};
+pub use reindex::{ElasticsearchReindexResponse, ReindexBody};
 pub use scroll::ScrollQueryParams;
 pub use search_body::SearchBody;
 pub use search_query_params::{DeleteQueryParams, SearchQueryParams, SearchQueryParamsCount};
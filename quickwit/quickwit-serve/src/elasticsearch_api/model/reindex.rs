@@ -0,0 +1,95 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_query::ElasticQueryDsl;
+use serde::{Deserialize, Serialize};
+
+/// Body of an Elasticsearch `_reindex` request.
+///
+/// Only VRL scripts are supported: they are applied to the documents as the transform of the
+/// reindex source.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReindexBody {
+    pub source: ReindexSourceBody,
+    pub dest: ReindexDestBody,
+    #[serde(default)]
+    pub script: Option<ReindexScript>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReindexSourceBody {
+    pub index: String,
+    #[serde(default)]
+    pub query: Option<ElasticQueryDsl>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReindexDestBody {
+    pub index: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReindexScript {
+    #[serde(default)]
+    pub lang: Option<String>,
+    pub source: String,
+}
+
+/// Response of an Elasticsearch `_reindex` request. The reindex always runs in the background
+/// as if `wait_for_completion=false` was set: the task identifies the reindex source created on
+/// the destination index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchReindexResponse {
+    pub task: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reindex_body_deserialization() {
+        let reindex_body: ReindexBody = serde_json::from_str(
+            r#"{
+                "source": {
+                    "index": "logs",
+                    "query": {"term": {"severity": "ERROR"}}
+                },
+                "dest": {"index": "logs-v2"},
+                "script": {"lang": "vrl", "source": ".severity = downcase!(.severity)"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(reindex_body.source.index, "logs");
+        assert!(reindex_body.source.query.is_some());
+        assert_eq!(reindex_body.dest.index, "logs-v2");
+        let script = reindex_body.script.unwrap();
+        assert_eq!(script.lang.as_deref(), Some("vrl"));
+
+        let reindex_body: ReindexBody =
+            serde_json::from_str(r#"{"source": {"index": "logs"}, "dest": {"index": "logs-v2"}}"#)
+                .unwrap();
+        assert!(reindex_body.source.query.is_none());
+        assert!(reindex_body.script.is_none());
+
+        serde_json::from_str::<ReindexBody>(
+            r#"{"source": {"index": "logs"}, "dest": {"index": "logs-v2"}, "max_docs": 10}"#,
+        )
+        .unwrap_err();
+    }
+}
@@ -76,6 +76,8 @@ pub struct SearchBody {
     #[serde(default)]
     pub aggs: serde_json::Map<String, serde_json::Value>,
     #[serde(default)]
+    pub runtime_mappings: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
     pub track_total_hits: Option<TrackTotalHits>,
     #[serde(default)]
     pub stored_fields: Option<BTreeSet<String>>,
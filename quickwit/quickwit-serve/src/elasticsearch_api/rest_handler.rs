@@ -25,7 +25,7 @@ use hyper::StatusCode;
 use itertools::Itertools;
 use quickwit_cluster::Cluster;
 use quickwit_common::truncate_str;
-use quickwit_config::{validate_index_id_pattern, NodeConfig};
+use quickwit_config::{validate_index_id_pattern, NodeConfig, TransformConfig};
 use quickwit_index_management::IndexService;
 use quickwit_metastore::*;
 use quickwit_proto::metastore::MetastoreServiceClient;
@@ -49,17 +49,19 @@ use super::filter::{
     elastic_delete_index_filter, elastic_field_capabilities_filter,
     elastic_index_cat_indices_filter, elastic_index_count_filter,
     elastic_index_field_capabilities_filter, elastic_index_search_filter,
-    elastic_index_stats_filter, elastic_multi_search_filter, elastic_resolve_index_filter,
-    elastic_scroll_filter, elastic_stats_filter, elasticsearch_filter,
+    elastic_index_stats_filter, elastic_multi_search_filter, elastic_reindex_filter,
+    elastic_resolve_index_filter, elastic_scroll_filter, elastic_stats_filter,
+    elasticsearch_filter,
 };
 use super::model::{
     build_list_field_request_for_es_api, convert_to_es_field_capabilities_response,
-    CatIndexQueryParams, DeleteQueryParams, ElasticsearchCatIndexResponse, ElasticsearchError,
-    ElasticsearchResolveIndexEntryResponse, ElasticsearchResolveIndexResponse,
-    ElasticsearchResponse, ElasticsearchStatsResponse, FieldCapabilityQueryParams,
-    FieldCapabilityRequestBody, FieldCapabilityResponse, MultiSearchHeader, MultiSearchQueryParams,
-    MultiSearchResponse, MultiSearchSingleResponse, ScrollQueryParams, SearchBody,
-    SearchQueryParams, SearchQueryParamsCount, StatsResponseEntry,
+    CatIndexQueryParams, DeleteQueryParams, ElasticException, ElasticsearchCatIndexResponse,
+    ElasticsearchError, ElasticsearchReindexResponse, ElasticsearchResolveIndexEntryResponse,
+    ElasticsearchResolveIndexResponse, ElasticsearchResponse, ElasticsearchStatsResponse,
+    FieldCapabilityQueryParams, FieldCapabilityRequestBody, FieldCapabilityResponse,
+    MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse, MultiSearchSingleResponse,
+    ReindexBody, ScrollQueryParams, SearchBody, SearchQueryParams, SearchQueryParamsCount,
+    StatsResponseEntry,
 };
 use super::{make_elastic_api_response, TrackTotalHits};
 use crate::format::BodyFormat;
@@ -138,6 +140,17 @@ pub fn es_compat_delete_index_handler(
         .boxed()
 }
 
+/// POST _elastic/_reindex
+pub fn es_compat_reindex_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_reindex_filter()
+        .and(with_arg(index_service))
+        .then(es_compat_reindex)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .boxed()
+}
+
 /// GET _elastic/_stats
 pub fn es_compat_stats_handler(
     metastore_service: MetastoreServiceClient,
@@ -354,6 +367,11 @@ fn build_request_for_es_api(
     } else {
         serde_json::to_string(&search_body.aggs).ok()
     };
+    let runtime_mappings: Option<String> = if search_body.runtime_mappings.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&search_body.runtime_mappings).ok()
+    };
 
     let max_hits = search_params.size.or(search_body.size).unwrap_or(10);
     let start_offset = search_params.from.or(search_body.from).unwrap_or(0);
@@ -394,6 +412,7 @@ fn build_request_for_es_api(
 
     let has_doc_id_field = sort_fields.iter().any(is_doc_field);
     let search_after = partial_hit_from_search_after_param(search_body.search_after, &sort_fields)?;
+    let fetch_fields = fetch_fields_from_source_includes(search_params._source_includes.as_deref());
 
     Ok((
         quickwit_proto::search::SearchRequest {
@@ -409,11 +428,25 @@ fn build_request_for_es_api(
             scroll_ttl_secs,
             search_after,
             count_hits,
+            fetch_fields,
+            runtime_mappings,
         },
         has_doc_id_field,
     ))
 }
 
+/// Returns the paths of the fields to fetch for the `_source_includes` parameter. The hits are
+/// filtered afterwards, so the whole documents are fetched when a path contains a wildcard.
+fn fetch_fields_from_source_includes(source_includes_opt: Option<&[String]>) -> Vec<String> {
+    let Some(source_includes) = source_includes_opt else {
+        return Vec::new();
+    };
+    if source_includes.iter().any(|path| path.contains('*')) {
+        return Vec::new();
+    }
+    source_includes.to_vec()
+}
+
 fn is_doc_field(field: &quickwit_proto::search::SortField) -> bool {
     field.field_name == "_shard_doc" || field.field_name == "_doc"
 }
@@ -553,6 +586,46 @@ async fn es_compat_delete_index(
     Ok(ElasticsearchDeleteResponse { acknowledged: true })
 }
 
+async fn es_compat_reindex(
+    reindex_body: ReindexBody,
+    mut index_service: IndexService,
+) -> Result<ElasticsearchReindexResponse, ElasticsearchError> {
+    let query_ast = if let Some(query_dsl) = reindex_body.source.query {
+        query_dsl.try_into().map_err(|error: anyhow::Error| {
+            ElasticsearchError::new(
+                StatusCode::BAD_REQUEST,
+                error.to_string(),
+                Some(ElasticException::IllegalArgument),
+            )
+        })?
+    } else {
+        QueryAst::MatchAll
+    };
+    let transform_config_opt = if let Some(script) = reindex_body.script {
+        if let Some(lang) = script.lang.filter(|lang| lang != "vrl") {
+            return Err(ElasticsearchError::new(
+                StatusCode::BAD_REQUEST,
+                format!("unsupported script language `{lang}`, only `vrl` is supported"),
+                Some(ElasticException::IllegalArgument),
+            ));
+        }
+        Some(TransformConfig::new(script.source, None))
+    } else {
+        None
+    };
+    let source_config = index_service
+        .reindex(
+            &reindex_body.source.index,
+            &reindex_body.dest.index,
+            &query_ast,
+            transform_config_opt,
+        )
+        .await?;
+    Ok(ElasticsearchReindexResponse {
+        task: format!("{}:{}", reindex_body.dest.index, source_config.source_id),
+    })
+}
+
 async fn es_compat_stats(
     metastore: MetastoreServiceClient,
 ) -> Result<ElasticsearchStatsResponse, ElasticsearchError> {
@@ -1131,6 +1204,19 @@ mod tests {
         assert_eq!(generate_path_variants_with_suffix(input), expected);
     }
 
+    #[test]
+    fn test_fetch_fields_from_source_includes() {
+        assert!(fetch_fields_from_source_includes(None).is_empty());
+
+        let source_includes = ["app.id".to_string(), "user".to_string()];
+        assert_eq!(
+            fetch_fields_from_source_includes(Some(&source_includes)),
+            ["app.id", "user"]
+        );
+        let source_includes = ["app.id".to_string(), "user.*".to_string()];
+        assert!(fetch_fields_from_source_includes(Some(&source_includes)).is_empty());
+    }
+
     #[test]
     fn test_include_fields1() {
         let mut fields = json!({
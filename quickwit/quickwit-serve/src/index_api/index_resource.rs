@@ -17,9 +17,12 @@ use std::sync::Arc;
 use bytes::Bytes;
 use quickwit_common::uri::Uri;
 use quickwit_config::{
-    load_index_config_update, validate_index_id_pattern, ConfigFormat, NodeConfig,
+    load_index_config_update, validate_index_id_pattern, ConfigFormat, IndexConfig, NodeConfig,
+    SourceConfig, TransformConfig, HOT_STORAGE_TIER_NAME,
+};
+use quickwit_index_management::{
+    IndexService, IndexServiceError, IndexSnapshotSummary, SplitFilesMode,
 };
-use quickwit_index_management::{IndexService, IndexServiceError};
 use quickwit_metastore::{
     IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
     ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo, SplitState,
@@ -30,11 +33,12 @@ use quickwit_proto::metastore::{
     MetastoreResult, MetastoreService, MetastoreServiceClient, UpdateIndexRequest,
 };
 use quickwit_proto::types::IndexId;
+use quickwit_query::query_ast::query_ast_from_user_text;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 use warp::{Filter, Rejection};
 
-use super::rest_handler::log_failure;
+use super::rest_handler::{json_body, log_failure};
 use crate::format::{extract_config_format, extract_format_from_qs};
 use crate::rest_api_response::into_rest_api_response;
 use crate::simple_list::from_simple_list;
@@ -103,6 +107,78 @@ pub struct IndexStats {
     pub timestamp_field_name: Option<String>,
     pub min_timestamp: Option<i64>,
     pub max_timestamp: Option<i64>,
+    /// Breakdown of the published splits per storage tier, reported for the indexes with a
+    /// tiering policy.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub storage_tiers: Vec<StorageTierStats>,
+}
+
+/// Describes the published splits stored in a storage tier of an index.
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StorageTierStats {
+    pub name: String,
+    #[schema(value_type = String)]
+    pub storage_uri: Uri,
+    pub num_published_splits: usize,
+    pub size_published_splits: u64,
+}
+
+/// Computes the number and size of the published splits stored in each storage tier of an index,
+/// starting with the hot tier, i.e. the index URI. The splits stored in a storage that is no
+/// longer part of the tiering policy are reported under the URI of the storage.
+///
+/// Returns an empty list if the index does not have a tiering policy.
+pub fn storage_tier_stats(
+    index_config: &IndexConfig,
+    published_splits: &[Split],
+) -> Vec<StorageTierStats> {
+    let Some(tiering_policy) = &index_config.tiering_policy_opt else {
+        return Vec::new();
+    };
+    let hot_tier_stats = StorageTierStats {
+        name: HOT_STORAGE_TIER_NAME.to_string(),
+        storage_uri: index_config.index_uri.clone(),
+        num_published_splits: 0,
+        size_published_splits: 0,
+    };
+    let mut storage_tiers: Vec<StorageTierStats> = std::iter::once(hot_tier_stats)
+        .chain(tiering_policy.tiers.iter().map(|tier| StorageTierStats {
+            name: tier.name.clone(),
+            storage_uri: tier.storage_uri.clone(),
+            num_published_splits: 0,
+            size_published_splits: 0,
+        }))
+        .collect();
+
+    for split in published_splits {
+        let storage_uri_opt = split.split_metadata.storage_uri.as_ref();
+
+        let tier_ord = if let Some(tier_rank) = tiering_policy.tier_rank(storage_uri_opt) {
+            tier_rank
+        } else {
+            let storage_uri = storage_uri_opt.expect("hot tier should have rank 0");
+
+            if let Some(tier_ord) = storage_tiers
+                .iter()
+                .position(|tier_stats| tier_stats.storage_uri == *storage_uri)
+            {
+                tier_ord
+            } else {
+                storage_tiers.push(StorageTierStats {
+                    name: storage_uri.to_string(),
+                    storage_uri: storage_uri.clone(),
+                    num_published_splits: 0,
+                    size_published_splits: 0,
+                });
+                storage_tiers.len() - 1
+            }
+        };
+        let tier_stats = &mut storage_tiers[tier_ord];
+        tier_stats.num_published_splits += 1;
+        tier_stats.size_published_splits += split.split_metadata.footer_offsets.end;
+    }
+    storage_tiers
 }
 
 #[utoipa::path(
@@ -160,6 +236,7 @@ pub async fn describe_index(
     }
 
     let index_config = index_metadata.into_index_config();
+    let storage_tiers = storage_tier_stats(&index_config, &published_splits);
     let index_stats = IndexStats {
         index_id,
         index_uri: index_config.index_uri.clone(),
@@ -170,6 +247,7 @@ pub async fn describe_index(
         timestamp_field_name: index_config.doc_mapping.timestamp_field,
         min_timestamp,
         max_timestamp,
+        storage_tiers,
     };
 
     Ok(index_stats)
@@ -344,6 +422,8 @@ pub async fn update_index(
         index_uid,
         &new_index_config.search_settings,
         &new_index_config.retention_policy_opt,
+        &new_index_config.rollup_policy_opt,
+        &new_index_config.tiering_policy_opt,
         &new_index_config.indexing_settings,
         &new_index_config.doc_mapping,
     )?;
@@ -384,6 +464,186 @@ pub async fn clear_index(
     index_service.clear_index(&index_id).await
 }
 
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotIndexRequest {
+    /// URI of the storage receiving the snapshot.
+    #[schema(value_type = String)]
+    pub snapshot_uri: Uri,
+    /// Whether the split files are copied to the snapshot URI (`copy`) or only referenced
+    /// (`reference`).
+    #[schema(value_type = String, default = "copy")]
+    #[serde(default)]
+    pub split_files_mode: SplitFilesMode,
+}
+
+pub fn snapshot_index_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "snapshot")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .then(snapshot_index)
+        .map(log_failure("failed to snapshot index"))
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+        .boxed()
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/snapshot",
+    request_body = SnapshotIndexRequest,
+    responses(
+        (status = 200, description = "Successfully took a snapshot of the index.")
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to snapshot."),
+    )
+)]
+/// Takes a snapshot of the index metadata, its checkpoints, and its published splits.
+pub async fn snapshot_index(
+    index_id: IndexId,
+    snapshot_index_request: SnapshotIndexRequest,
+    index_service: IndexService,
+) -> Result<IndexSnapshotSummary, IndexServiceError> {
+    info!(index_id = %index_id, snapshot_uri = %snapshot_index_request.snapshot_uri, "snapshot-index");
+    index_service
+        .snapshot_index(
+            &index_id,
+            &snapshot_index_request.snapshot_uri,
+            snapshot_index_request.split_files_mode,
+        )
+        .await
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RestoreIndexRequest {
+    /// URI of the snapshot to restore.
+    #[schema(value_type = String)]
+    pub snapshot_uri: Uri,
+    /// ID of the restored index. Defaults to the ID of the snapshotted index.
+    #[serde(default)]
+    pub index_id: Option<IndexId>,
+    /// URI of the restored index. Defaults to the URI of the snapshotted index if the index ID is
+    /// unchanged, to the default index root URI of the node otherwise.
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    pub index_uri: Option<Uri>,
+}
+
+pub fn restore_index_handler(
+    index_service: IndexService,
+    node_config: Arc<NodeConfig>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / "restore")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .and(with_arg(node_config))
+        .then(restore_index)
+        .map(log_failure("failed to restore index"))
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+        .boxed()
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/restore",
+    request_body = RestoreIndexRequest,
+    responses(
+        (status = 200, description = "Successfully restored the index.", body = VersionedIndexMetadata)
+    ),
+)]
+/// Restores an index from a snapshot and registers its splits in the metastore.
+pub async fn restore_index(
+    restore_index_request: RestoreIndexRequest,
+    index_service: IndexService,
+    node_config: Arc<NodeConfig>,
+) -> Result<IndexMetadata, IndexServiceError> {
+    info!(snapshot_uri = %restore_index_request.snapshot_uri, index_id = ?restore_index_request.index_id, "restore-index");
+    index_service
+        .restore_index(
+            &restore_index_request.snapshot_uri,
+            restore_index_request.index_id,
+            restore_index_request.index_uri,
+            &node_config.default_index_root_uri,
+        )
+        .await
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReindexRequest {
+    /// ID of the index whose documents are reindexed.
+    pub source_index_id: IndexId,
+    /// Query selecting the documents to reindex. The query language is that of tantivy.
+    #[schema(default = "*")]
+    #[serde(default = "default_reindex_query")]
+    pub query: String,
+    /// Fields searched by the query when none is specified. Defaults to the default search
+    /// fields of the source index.
+    #[serde(default)]
+    pub search_fields: Option<Vec<String>>,
+    /// VRL transform applied to the documents before they are indexed.
+    #[serde(default)]
+    pub transform: Option<TransformConfig>,
+}
+
+fn default_reindex_query() -> String {
+    "*".to_string()
+}
+
+pub fn reindex_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "reindex")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .then(reindex)
+        .map(log_failure("failed to reindex"))
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+        .boxed()
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/reindex",
+    request_body = ReindexRequest,
+    responses(
+        (status = 200, description = "Successfully started the reindex.", body = SourceConfig)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID receiving the reindexed documents."),
+    )
+)]
+/// Reindexes the documents of another index matching a query into the index. The documents are
+/// read by the indexers through the returned reindex source.
+pub async fn reindex(
+    index_id: IndexId,
+    reindex_request: ReindexRequest,
+    mut index_service: IndexService,
+) -> Result<SourceConfig, IndexServiceError> {
+    info!(index_id = %index_id, source_index_id = %reindex_request.source_index_id, query = %reindex_request.query, "reindex");
+    let query_ast = query_ast_from_user_text(&reindex_request.query, reindex_request.search_fields);
+    index_service
+        .reindex(
+            &reindex_request.source_index_id,
+            &index_id,
+            &query_ast,
+            reindex_request.transform,
+        )
+        .await
+}
+
 #[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
 #[into_params(parameter_in = Query)]
 pub struct DeleteIndexQueryParam {
@@ -17,6 +17,6 @@ mod rest_handler;
 mod source_resource;
 mod split_resource;
 
-pub use self::index_resource::get_index_metadata_handler;
+pub use self::index_resource::{get_index_metadata_handler, storage_tier_stats, StorageTierStats};
 pub use self::rest_handler::{index_management_handlers, IndexApi};
 pub use self::split_resource::{ListSplitsQueryParams, ListSplitsResponse};
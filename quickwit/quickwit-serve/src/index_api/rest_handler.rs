@@ -25,10 +25,12 @@ use warp::{Filter, Rejection};
 
 use super::get_index_metadata_handler;
 use super::index_resource::{
-    __path_clear_index, __path_create_index, __path_delete_index, __path_list_indexes_metadata,
+    __path_clear_index, __path_create_index, __path_delete_index, __path_describe_index,
+    __path_list_indexes_metadata, __path_reindex, __path_restore_index, __path_snapshot_index,
     __path_update_index, clear_index_handler, create_index_handler, delete_index_handler,
-    describe_index_handler, list_indexes_metadata_handler, update_index_handler, IndexStats,
-    __path_describe_index,
+    describe_index_handler, list_indexes_metadata_handler, reindex_handler, restore_index_handler,
+    snapshot_index_handler, update_index_handler, IndexStats, ReindexRequest, RestoreIndexRequest,
+    SnapshotIndexRequest, StorageTierStats,
 };
 use super::source_resource::{
     __path_create_source, __path_delete_source, __path_reset_source_checkpoint,
@@ -52,6 +54,9 @@ use crate::simple_list::from_simple_list;
         update_index,
         clear_index,
         delete_index,
+        snapshot_index,
+        restore_index,
+        reindex,
         list_indexes_metadata,
         list_splits,
         describe_index,
@@ -62,7 +67,15 @@ use crate::simple_list::from_simple_list;
         toggle_source,
         delete_source,
     ),
-    components(schemas(ToggleSource, SplitsForDeletion, IndexStats))
+    components(schemas(
+        ToggleSource,
+        SplitsForDeletion,
+        IndexStats,
+        StorageTierStats,
+        SnapshotIndexRequest,
+        RestoreIndexRequest,
+        ReindexRequest
+    ))
 )]
 pub struct IndexApi;
 
@@ -89,10 +102,16 @@ pub fn index_management_handlers(
     // Indexes handlers.
     get_index_metadata_handler(index_service.metastore())
         .or(list_indexes_metadata_handler(index_service.metastore()))
-        .or(create_index_handler(index_service.clone(), node_config))
+        .or(create_index_handler(
+            index_service.clone(),
+            node_config.clone(),
+        ))
         .or(update_index_handler(index_service.metastore()))
         .or(clear_index_handler(index_service.clone()))
         .or(delete_index_handler(index_service.clone()))
+        .or(snapshot_index_handler(index_service.clone()))
+        .or(restore_index_handler(index_service.clone(), node_config))
+        .or(reindex_handler(index_service.clone()))
         .boxed()
         // Splits handlers
         .or(list_splits_handler(index_service.metastore()))
@@ -423,6 +442,84 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_describe_index_with_tiering_policy() {
+        let mut mock_metastore = MockMetastoreService::new();
+        let mut index_metadata =
+            IndexMetadata::for_test("quickwit-demo-index", "ram:///indexes/quickwit-demo-index");
+        let tiering_policy_json = serde_json::json!({
+            "tiers": [{
+                "name": "cold",
+                "storage_uri": "ram:///cold/quickwit-demo-index",
+                "move_after": "30 days",
+            }],
+        });
+        index_metadata.index_config.tiering_policy_opt =
+            Some(serde_json::from_value(tiering_policy_json).unwrap());
+        let index_uid = index_metadata.index_uid.clone();
+        mock_metastore
+            .expect_index_metadata()
+            .return_once(move |_| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(&index_metadata).unwrap())
+            });
+        let split_1 = MockSplitBuilder::new("split_1")
+            .with_index_uid(&index_uid)
+            .build();
+        let mut split_2 = MockSplitBuilder::new("split_2")
+            .with_index_uid(&index_uid)
+            .build();
+        split_2.split_metadata.storage_uri = Some(Uri::for_test("ram:///cold/quickwit-demo-index"));
+        let mut split_3 = MockSplitBuilder::new("split_3")
+            .with_index_uid(&index_uid)
+            .build();
+        split_3.split_metadata.storage_uri =
+            Some(Uri::for_test("ram:///archive/quickwit-demo-index"));
+        mock_metastore.expect_list_splits().return_once(move |_| {
+            let splits = vec![split_1, split_2, split_3];
+            let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits)]))
+        });
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from_mock(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/describe")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "num_published_splits": 3,
+            "size_published_splits": 2400,
+            "storage_tiers": [
+                {
+                    "name": "hot",
+                    "storage_uri": "ram:///indexes/quickwit-demo-index",
+                    "num_published_splits": 1,
+                    "size_published_splits": 800,
+                },
+                {
+                    "name": "cold",
+                    "storage_uri": "ram:///cold/quickwit-demo-index",
+                    "num_published_splits": 1,
+                    "size_published_splits": 800,
+                },
+                {
+                    "name": "ram:///archive/quickwit-demo-index",
+                    "storage_uri": "ram:///archive/quickwit-demo-index",
+                    "num_published_splits": 1,
+                    "size_published_splits": 800,
+                },
+            ],
+        });
+        assert_json_include!(actual: actual_response_json, expected: expected_response_json);
+    }
+
     #[tokio::test]
     async fn test_get_all_splits() {
         let mut mock_metastore = MockMetastoreService::new();
@@ -730,6 +827,128 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_snapshot_and_restore_index() {
+        let metastore = metastore_for_test();
+        let index_service = IndexService::new(metastore.clone(), StorageResolver::for_test());
+        let mut node_config = NodeConfig::for_test();
+        node_config.default_index_root_uri = Uri::for_test("ram:///indexes");
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(node_config));
+
+        let resp = warp::test::request()
+            .path("/indexes")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"version": "0.7", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/snapshot")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"snapshot_uri": "ram:///snapshots/hdfs-logs"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "index_id": "hdfs-logs",
+            "snapshot_uri": "ram:///snapshots/hdfs-logs",
+            "split_files_mode": "copy",
+            "num_splits": 0,
+            "num_bytes": 0,
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .path("/indexes/restore")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"snapshot_uri": "ram:///snapshots/hdfs-logs", "index_id": "hdfs-logs-restored"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "index_config": {
+                "index_id": "hdfs-logs-restored",
+                "index_uri": "ram:///indexes/hdfs-logs-restored",
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .path("/indexes/restore")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"snapshot_uri": "ram:///snapshots/missing"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_reindex() {
+        let metastore = metastore_for_test();
+        let index_service = IndexService::new(metastore.clone(), StorageResolver::for_test());
+        let mut node_config = NodeConfig::for_test();
+        node_config.default_index_root_uri = Uri::for_test("ram:///indexes");
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(node_config));
+
+        for index_id in ["hdfs-logs", "hdfs-logs-v2"] {
+            let resp = warp::test::request()
+                .path("/indexes")
+                .method("POST")
+                .json(&true)
+                .body(format!(r#"{{"version": "0.7", "index_id": "{index_id}", "doc_mapping": {{"field_mappings":[{{"name": "severity", "type": "text", "tokenizer": "raw"}}]}}}}"#))
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+        }
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs-v2/reindex")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"source_index_id": "hdfs-logs", "query": "severity:ERROR", "transform": {"script": ".severity = downcase!(.severity)"}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "source_type": "reindex",
+            "transform": {
+                "script": ".severity = downcase!(.severity)",
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        assert!(resp_json["source_id"]
+            .as_str()
+            .unwrap()
+            .starts_with("reindex-"));
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/reindex")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"source_index_id": "hdfs-logs"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 403);
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs-v2/reindex")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"source_index_id": "missing-index"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
     #[tokio::test]
     async fn test_create_delete_index_and_source() {
         let metastore = metastore_for_test();
@@ -15,7 +15,7 @@
 use bytes::Bytes;
 use quickwit_config::{
     load_source_config_from_user_config, load_source_config_update, ConfigFormat, FileSourceParams,
-    SourceConfig, SourceParams, CLI_SOURCE_ID, INGEST_API_SOURCE_ID,
+    SourceConfig, SourceParams, CLI_SOURCE_ID, INGEST_API_SOURCE_ID, ROLLUP_SOURCE_ID,
 };
 use quickwit_index_management::{IndexService, IndexServiceError};
 use quickwit_metastore::IndexMetadataResponseExt;
@@ -283,7 +283,7 @@ pub async fn toggle_source(
         .await?
         .deserialize_index_metadata()?
         .index_uid;
-    if [CLI_SOURCE_ID, INGEST_API_SOURCE_ID].contains(&source_id.as_str()) {
+    if [CLI_SOURCE_ID, INGEST_API_SOURCE_ID, ROLLUP_SOURCE_ID].contains(&source_id.as_str()) {
         return Err(IndexServiceError::OperationNotAllowed(format!(
             "source `{source_id}` is managed by Quickwit, you cannot enable or disable a source \
              managed by Quickwit"
@@ -335,7 +335,7 @@ pub async fn delete_source(
         .await?
         .deserialize_index_metadata()?
         .index_uid;
-    if [INGEST_API_SOURCE_ID, CLI_SOURCE_ID].contains(&source_id.as_str()) {
+    if [INGEST_API_SOURCE_ID, CLI_SOURCE_ID, ROLLUP_SOURCE_ID].contains(&source_id.as_str()) {
         return Err(IndexServiceError::OperationNotAllowed(format!(
             "source `{source_id}` is managed by Quickwit, you cannot delete a source managed by \
              Quickwit"
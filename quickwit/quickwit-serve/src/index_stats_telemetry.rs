@@ -0,0 +1,153 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use quickwit_metastore::{
+    ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt,
+    SplitState,
+};
+use quickwit_proto::metastore::{
+    ListIndexesMetadataRequest, ListSplitsRequest, MetastoreResult, MetastoreService,
+    MetastoreServiceClient,
+};
+use quickwit_telemetry::payload::TelemetryEvent;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Delay before the first index stats report, leaving time for the cluster to start.
+const INDEX_STATS_TELEMETRY_INITIAL_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Interval at which the index stats are reported.
+const INDEX_STATS_TELEMETRY_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60); // 12h
+
+/// Periodically sends a [`TelemetryEvent::IndexStats`] event summarizing the published splits of
+/// all the indexes. Does nothing if the telemetry loop is not running.
+pub(crate) fn start_index_stats_telemetry_task(metastore: MetastoreServiceClient) {
+    if !quickwit_telemetry::is_telemetry_loop_running() {
+        return;
+    }
+    let mut interval = tokio::time::interval_at(
+        Instant::now() + INDEX_STATS_TELEMETRY_INITIAL_DELAY,
+        INDEX_STATS_TELEMETRY_INTERVAL,
+    );
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            match index_stats_event(&metastore).await {
+                Ok(index_stats_event) => {
+                    quickwit_telemetry::send_telemetry_event(index_stats_event).await;
+                }
+                Err(error) => {
+                    warn!(%error, "failed to compute index stats telemetry event");
+                }
+            }
+        }
+    });
+}
+
+async fn index_stats_event(metastore: &MetastoreServiceClient) -> MetastoreResult<TelemetryEvent> {
+    let num_indexes = metastore
+        .list_indexes_metadata(ListIndexesMetadataRequest::all())
+        .await?
+        .deserialize_indexes_metadata()
+        .await?
+        .len();
+
+    let mut num_published_splits = 0;
+    let mut num_published_docs = 0;
+    let mut size_published_splits = 0;
+    let mut size_published_docs_uncompressed = 0;
+
+    let list_splits_query =
+        ListSplitsQuery::for_all_indexes().with_split_state(SplitState::Published);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&list_splits_query)?;
+    let mut splits_stream = metastore.list_splits(list_splits_request).await?;
+
+    while let Some(list_splits_response) = splits_stream.try_next().await? {
+        for split_metadata in list_splits_response.deserialize_splits_metadata().await? {
+            num_published_splits += 1;
+            num_published_docs += split_metadata.num_docs as u64;
+            size_published_splits += split_metadata.footer_offsets.end;
+            size_published_docs_uncompressed += split_metadata.uncompressed_docs_size_in_bytes;
+        }
+    }
+    Ok(TelemetryEvent::IndexStats {
+        num_indexes,
+        num_published_splits,
+        num_published_docs,
+        size_published_splits,
+        size_published_docs_uncompressed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::ServiceStream;
+    use quickwit_indexing::MockSplitBuilder;
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_proto::metastore::{
+        ListIndexesMetadataResponse, ListSplitsResponse, MockMetastoreService,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_index_stats_event() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_| {
+                Ok(ListIndexesMetadataResponse::for_test(vec![
+                    IndexMetadata::for_test("test-index-1", "ram:///indexes/test-index-1"),
+                    IndexMetadata::for_test("test-index-2", "ram:///indexes/test-index-2"),
+                ]))
+            });
+        mock_metastore
+            .expect_list_splits()
+            .return_once(|list_splits_request| {
+                let list_splits_query =
+                    list_splits_request.deserialize_list_splits_query().unwrap();
+                assert!(list_splits_query.index_uids.is_none());
+                assert_eq!(list_splits_query.split_states, [SplitState::Published]);
+
+                let splits = ["split-1", "split-2", "split-3"].map(|split_id| {
+                    let mut split = MockSplitBuilder::new(split_id).build();
+                    split.split_metadata.num_docs = 10;
+                    split.split_metadata.footer_offsets = 700..800;
+                    split.split_metadata.uncompressed_docs_size_in_bytes = 1_000;
+                    split
+                });
+                let (first_splits, last_splits) = splits.split_at(2);
+                Ok(ServiceStream::from(vec![
+                    Ok(ListSplitsResponse::try_from_splits(first_splits.to_vec()).unwrap()),
+                    Ok(ListSplitsResponse::try_from_splits(last_splits.to_vec()).unwrap()),
+                ]))
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let index_stats_event = index_stats_event(&metastore).await.unwrap();
+        assert_eq!(
+            index_stats_event,
+            TelemetryEvent::IndexStats {
+                num_indexes: 2,
+                num_published_splits: 3,
+                num_published_docs: 30,
+                size_published_splits: 2_400,
+                size_published_docs_uncompressed: 3_000,
+            }
+        );
+    }
+}
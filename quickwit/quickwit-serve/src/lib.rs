@@ -24,12 +24,15 @@ mod format;
 mod grpc;
 mod health_check_api;
 mod index_api;
+mod index_stats_telemetry;
 mod indexing_api;
 mod ingest_api;
 mod jaeger_api;
 mod load_shield;
+mod loki_api;
 mod metrics;
 mod metrics_api;
+mod monitor_api;
 mod node_info_handler;
 mod openapi;
 mod otlp_api;
@@ -78,6 +81,7 @@ use quickwit_config::{ClusterConfig, IngestApiConfig, NodeConfig};
 use quickwit_control_plane::control_plane::{ControlPlane, ControlPlaneEventSubscriber};
 use quickwit_control_plane::{IndexerNodeInfo, IndexerPool};
 use quickwit_index_management::{IndexService as IndexManager, IndexServiceError};
+pub use quickwit_index_management::{IndexSnapshotSummary, SplitFilesMode};
 use quickwit_indexing::actors::IndexingService;
 use quickwit_indexing::models::ShardPositionsService;
 use quickwit_indexing::start_indexing_service;
@@ -102,15 +106,15 @@ use quickwit_proto::ingest::router::IngestRouterServiceClient;
 use quickwit_proto::ingest::{IngestV2Error, RateLimitingCause};
 use quickwit_proto::metastore::{
     EntityKind, ListIndexesMetadataRequest, MetastoreError, MetastoreService,
-    MetastoreServiceClient,
+    MetastoreServiceClient, PublishSplitsRequest,
 };
 use quickwit_proto::search::ReportSplitsRequest;
 use quickwit_proto::types::NodeId;
 use quickwit_search::{
-    create_search_client_from_channel, start_searcher_service, SearchJobPlacer, SearchService,
-    SearchServiceClient, SearcherContext, SearcherPool,
+    create_search_client_from_channel, start_searcher_service, PublishedSplitsReporter,
+    SearchJobPlacer, SearchService, SearchServiceClient, SearcherContext, SearcherPool,
 };
-use quickwit_storage::{SplitCache, StorageResolver};
+use quickwit_storage::{DiskSliceCache, SplitCache, StorageResolver};
 use tcp_listener::TcpListenerResolver;
 use tokio::sync::oneshot;
 use tower::timeout::Timeout;
@@ -119,7 +123,10 @@ use tracing::{debug, error, info, warn};
 use warp::{Filter, Rejection};
 
 pub use crate::build_info::{BuildInfo, RuntimeInfo};
-pub use crate::index_api::{ListSplitsQueryParams, ListSplitsResponse};
+pub use crate::index_api::{
+    storage_tier_stats, ListSplitsQueryParams, ListSplitsResponse, StorageTierStats,
+};
+use crate::index_stats_telemetry::start_index_stats_telemetry_task;
 pub use crate::ingest_api::{RestIngestResponse, RestParseFailure};
 pub use crate::metrics::SERVE_METRICS;
 use crate::rate_modulator::RateModulator;
@@ -205,6 +212,7 @@ struct QuickwitServices {
     /// notifications. Otherwise, the subscriptions are dropped.
     _local_shards_update_listener_handle_opt: Option<ListenerHandle>,
     _report_splits_subscription_handle_opt: Option<EventSubscriptionHandle>,
+    _publish_splits_subscription_handle_opt: Option<EventSubscriptionHandle>,
 }
 
 impl QuickwitServices {
@@ -436,7 +444,8 @@ pub async fn serve_quickwit(
                 .stack_delete_index_layer(broker_layer.clone())
                 .stack_add_source_layer(broker_layer.clone())
                 .stack_delete_source_layer(broker_layer.clone())
-                .stack_toggle_source_layer(broker_layer)
+                .stack_toggle_source_layer(broker_layer.clone())
+                .stack_publish_splits_layer(broker_layer)
                 .build(metastore);
             Some(metastore)
         } else {
@@ -592,9 +601,13 @@ pub async fn serve_quickwit(
             None
         };
 
+    let disk_slice_cache_opt = setup_disk_slice_cache(&node_config)?;
+
     let searcher_context = Arc::new(SearcherContext::new(
         node_config.searcher_config.clone(),
         split_cache_opt,
+        disk_slice_cache_opt,
+        storage_resolver.clone(),
     ));
 
     let (search_job_placer, search_service) = setup_searcher(
@@ -630,6 +643,15 @@ pub async fn serve_quickwit(
             None
         };
 
+    // The metastore node always reports the published splits to their owning searchers. Each
+    // searcher decides whether to warm them based on its own configuration.
+    let publish_splits_subscription_handle_opt =
+        metastore_server_opt.as_ref().map(|metastore_server| {
+            let published_splits_reporter =
+                PublishedSplitsReporter::new(metastore_server.clone(), search_job_placer.clone());
+            event_broker.subscribe::<PublishSplitsRequest>(published_splits_reporter)
+        });
+
     let janitor_service_opt = if node_config.is_service_enabled(QuickwitService::Janitor) {
         let janitor_service = start_janitor_service(
             &universe,
@@ -642,6 +664,9 @@ pub async fn serve_quickwit(
         )
         .await
         .context("failed to start janitor service")?;
+        // The janitor runs on a single node of the cluster, which makes it a good fit for
+        // reporting cluster-wide statistics.
+        start_index_stats_telemetry_task(metastore_through_control_plane.clone());
         Some(janitor_service)
     } else {
         None
@@ -689,6 +714,7 @@ pub async fn serve_quickwit(
         control_plane_client,
         _local_shards_update_listener_handle_opt: local_shards_update_listener_handle_opt,
         _report_splits_subscription_handle_opt: report_splits_subscription_handle_opt,
+        _publish_splits_subscription_handle_opt: publish_splits_subscription_handle_opt,
         index_manager,
         indexing_service_opt,
         ingest_router_opt: Some(ingest_router),
@@ -1092,6 +1118,25 @@ async fn setup_control_plane(
     Ok(control_plane_mailbox)
 }
 
+/// Opens the searcher disk slice cache if it is configured. The cache is disabled on nodes with
+/// encryption enabled, since it would persist the slices of encrypted splits in clear.
+fn setup_disk_slice_cache(node_config: &NodeConfig) -> anyhow::Result<Option<Arc<DiskSliceCache>>> {
+    let Some(capacity) = node_config.searcher_config.disk_slice_cache_capacity else {
+        return Ok(None);
+    };
+    if node_config.encryption_config.keyfile_path_opt.is_some() {
+        warn!("disk slice cache is disabled because encryption is enabled on this node");
+        return Ok(None);
+    }
+    let disk_slice_cache = DiskSliceCache::open(
+        node_config.data_dir_path.join("searcher-slice-cache"),
+        capacity.as_u64(),
+        &quickwit_storage::STORAGE_METRICS.searcher_disk_slice_cache,
+    )
+    .context("failed to load searcher disk slice cache")?;
+    Ok(Some(disk_slice_cache))
+}
+
 fn setup_indexer_pool(
     node_config: &NodeConfig,
     cluster_change_stream: ClusterChangeStream,
@@ -1471,13 +1516,32 @@ mod tests {
         assert!(indexer_pool.is_empty());
     }
 
+    #[test]
+    fn test_setup_disk_slice_cache() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut node_config = NodeConfig::for_test();
+        node_config.data_dir_path = data_dir.path().to_path_buf();
+        assert!(setup_disk_slice_cache(&node_config).unwrap().is_none());
+
+        node_config.searcher_config.disk_slice_cache_capacity = Some(ByteSize::mb(1));
+        assert!(setup_disk_slice_cache(&node_config).unwrap().is_some());
+
+        node_config.encryption_config.keyfile_path_opt = Some(PathBuf::from("keys.json"));
+        assert!(setup_disk_slice_cache(&node_config).unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_setup_searcher() {
         let node_config = NodeConfig::for_test();
-        let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+        let storage_resolver = StorageResolver::unconfigured();
+        let searcher_context = Arc::new(SearcherContext::new(
+            SearcherConfig::default(),
+            None,
+            None,
+            storage_resolver.clone(),
+        ));
         let metastore = metastore_for_test();
         let (change_stream, change_stream_tx) = ClusterChangeStream::new_unbounded();
-        let storage_resolver = StorageResolver::unconfigured();
         let (search_job_placer, _searcher_service) = setup_searcher(
             &node_config,
             change_stream,
@@ -0,0 +1,717 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of the subset of LogQL supported by the Loki API, and its translation into query ASTs.
+//!
+//! ```text
+//! query          := log_query | metric_query
+//! log_query      := '{' matcher (',' matcher)* '}' line_filter*
+//! matcher        := label ('=' | '!=' | '=~' | '!~') string
+//! line_filter    := ('|=' | '!=' | '|~' | '!~') string
+//! metric_query   := range_function | 'sum' [grouping] '(' range_function ')' [grouping]
+//! range_function := ('count_over_time' | 'rate') '(' log_query '[' duration ']' ')'
+//! grouping       := 'by' '(' label (',' label)* ')'
+//! ```
+
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use quickwit_query::query_ast::{
+    BoolQuery, FieldPresenceQuery, FullTextMode, FullTextParams, FullTextQuery, QueryAst,
+    RegexQuery, TermQuery,
+};
+use quickwit_query::MatchAllOrNone;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum LogqlQuery {
+    Log(LogQuery),
+    Metric(MetricQuery),
+}
+
+impl LogqlQuery {
+    /// Returns the log query selecting the documents, for metric queries as well.
+    pub fn log_query(&self) -> &LogQuery {
+        match self {
+            LogqlQuery::Log(log_query) => log_query,
+            LogqlQuery::Metric(metric_query) => &metric_query.log_query,
+        }
+    }
+}
+
+/// A stream selector followed by line filters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LogQuery {
+    pub matchers: Vec<LabelMatcher>,
+    pub line_filters: Vec<LineFilter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LabelMatcher {
+    pub label: String,
+    pub op: MatchOp,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LineFilter {
+    pub op: MatchOp,
+    pub text: String,
+}
+
+/// Operator of a label matcher or a line filter. For line filters, `Eq` stands for `|=`, `Neq`
+/// for `!=`, `Re` for `|~` and `Nre` for `!~`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum MatchOp {
+    Eq,
+    Neq,
+    Re,
+    Nre,
+}
+
+impl MatchOp {
+    fn is_negated(&self) -> bool {
+        matches!(self, MatchOp::Neq | MatchOp::Nre)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct MetricQuery {
+    pub range_function: RangeFunction,
+    pub log_query: LogQuery,
+    pub range: Duration,
+    /// Labels of the `sum` aggregation wrapping the range function, if any.
+    pub sum_by: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RangeFunction {
+    CountOverTime,
+    Rate,
+}
+
+impl MetricQuery {
+    /// Returns the labels identifying the series: the labels of the `sum` aggregation, or the
+    /// labels that the stream selector requires to be set when there is no `sum` aggregation.
+    pub fn series_labels(&self) -> Vec<String> {
+        if let Some(sum_by) = &self.sum_by {
+            return sum_by.clone();
+        }
+        let mut series_labels: Vec<String> = Vec::new();
+        for matcher in &self.log_query.matchers {
+            let is_label_set = match matcher.op {
+                MatchOp::Eq => !matcher.value.is_empty(),
+                MatchOp::Re => true,
+                MatchOp::Neq | MatchOp::Nre => false,
+            };
+            if is_label_set && !series_labels.contains(&matcher.label) {
+                series_labels.push(matcher.label.clone());
+            }
+        }
+        series_labels
+    }
+}
+
+impl LogQuery {
+    /// Translates the log query into a query AST. Label matchers target the field named after the
+    /// label, and line filters target the `line_filter_fields`.
+    pub fn to_query_ast(&self, line_filter_fields: &[String]) -> anyhow::Result<QueryAst> {
+        let mut bool_query = BoolQuery::default();
+        for matcher in &self.matchers {
+            let (query_ast, is_negated) = match matcher.op {
+                // An empty value matches the streams without the label.
+                MatchOp::Eq | MatchOp::Neq if matcher.value.is_empty() => {
+                    let field_presence_query = FieldPresenceQuery {
+                        field: matcher.label.clone(),
+                    }
+                    .into();
+                    (field_presence_query, matcher.op == MatchOp::Eq)
+                }
+                MatchOp::Eq | MatchOp::Neq => {
+                    let term_query = TermQuery {
+                        field: matcher.label.clone(),
+                        value: matcher.value.clone(),
+                    }
+                    .into();
+                    (term_query, matcher.op.is_negated())
+                }
+                MatchOp::Re | MatchOp::Nre => {
+                    let regex_query = RegexQuery {
+                        field: matcher.label.clone(),
+                        regex: matcher.value.clone(),
+                    }
+                    .into();
+                    (regex_query, matcher.op.is_negated())
+                }
+            };
+            if is_negated {
+                bool_query.must_not.push(query_ast);
+            } else {
+                bool_query.must.push(query_ast);
+            }
+        }
+        for line_filter in &self.line_filters {
+            // Grafana's query builder appends an empty line filter by default.
+            if line_filter.text.is_empty() {
+                continue;
+            }
+            if line_filter_fields.is_empty() {
+                bail!("line filters require the index to have default search fields")
+            }
+            let mut field_queries: Vec<QueryAst> = line_filter_fields
+                .iter()
+                .map(|field| line_filter_field_query(line_filter, field))
+                .collect();
+            let query_ast = if field_queries.len() == 1 {
+                field_queries.pop().unwrap()
+            } else {
+                BoolQuery {
+                    should: field_queries,
+                    ..Default::default()
+                }
+                .into()
+            };
+            if line_filter.op.is_negated() {
+                bool_query.must_not.push(query_ast);
+            } else {
+                bool_query.must.push(query_ast);
+            }
+        }
+        if bool_query.must.is_empty() && bool_query.must_not.is_empty() {
+            return Ok(QueryAst::MatchAll);
+        }
+        Ok(bool_query.into())
+    }
+}
+
+fn line_filter_field_query(line_filter: &LineFilter, field: &str) -> QueryAst {
+    match line_filter.op {
+        MatchOp::Eq | MatchOp::Neq => FullTextQuery {
+            field: field.to_string(),
+            text: line_filter.text.clone(),
+            params: FullTextParams {
+                tokenizer: None,
+                mode: FullTextMode::PhraseFallbackToIntersection,
+                zero_terms_query: MatchAllOrNone::MatchAll,
+            },
+            lenient: true,
+        }
+        .into(),
+        MatchOp::Re | MatchOp::Nre => RegexQuery {
+            field: field.to_string(),
+            regex: line_filter.text.clone(),
+        }
+        .into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Range(String),
+    LeftBrace,
+    RightBrace,
+    LeftParen,
+    RightParen,
+    Comma,
+    Eq,
+    Neq,
+    Re,
+    Nre,
+    PipeEq,
+    PipeRe,
+    Pipe,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(ident) => format!("`{ident}`"),
+            Token::String(value) => format!("string {value:?}"),
+            Token::Range(range) => format!("`[{range}]`"),
+            Token::LeftBrace => "`{`".to_string(),
+            Token::RightBrace => "`}`".to_string(),
+            Token::LeftParen => "`(`".to_string(),
+            Token::RightParen => "`)`".to_string(),
+            Token::Comma => "`,`".to_string(),
+            Token::Eq => "`=`".to_string(),
+            Token::Neq => "`!=`".to_string(),
+            Token::Re => "`=~`".to_string(),
+            Token::Nre => "`!~`".to_string(),
+            Token::PipeEq => "`|=`".to_string(),
+            Token::PipeRe => "`|~`".to_string(),
+            Token::Pipe => "`|`".to_string(),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(chr) = chars.next() {
+        let token = match chr {
+            chr if chr.is_whitespace() => continue,
+            '{' => Token::LeftBrace,
+            '}' => Token::RightBrace,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '=' if chars.next_if_eq(&'~').is_some() => Token::Re,
+            '=' => Token::Eq,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Neq,
+            '!' if chars.next_if_eq(&'~').is_some() => Token::Nre,
+            '|' if chars.next_if_eq(&'=').is_some() => Token::PipeEq,
+            '|' if chars.next_if_eq(&'~').is_some() => Token::PipeRe,
+            '|' => Token::Pipe,
+            '[' => {
+                let mut range = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(chr) => range.push(chr),
+                        None => bail!("unterminated range `[{range}`"),
+                    }
+                }
+                Token::Range(range.trim().to_string())
+            }
+            '`' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('`') => break,
+                        Some(chr) => value.push(chr),
+                        None => bail!("unterminated string `{value}"),
+                    }
+                }
+                Token::String(value)
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('r') => value.push('\r'),
+                            Some(chr) => value.push(chr),
+                            None => bail!("unterminated string \"{value}"),
+                        },
+                        Some(chr) => value.push(chr),
+                        None => bail!("unterminated string \"{value}"),
+                    }
+                }
+                Token::String(value)
+            }
+            chr if is_ident_char(chr) => {
+                let mut ident = chr.to_string();
+                while let Some(chr) = chars.next_if(|chr| is_ident_char(*chr)) {
+                    ident.push(chr);
+                }
+                Token::Ident(ident)
+            }
+            chr => bail!("unexpected character `{chr}`"),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Labels are field paths, so dots are accepted on top of the characters of Loki label names.
+fn is_ident_char(chr: char) -> bool {
+    chr.is_ascii_alphanumeric() || chr == '_' || chr == '.'
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token_opt = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token_opt
+    }
+
+    fn peek_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(peeked)) if peeked == ident)
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next_token() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!(
+                "expected {}, found {}",
+                expected.describe(),
+                token.describe()
+            ),
+            None => bail!("expected {}, found end of query", expected.describe()),
+        }
+    }
+
+    fn expect_ident(&mut self) -> anyhow::Result<String> {
+        match self.next_token() {
+            Some(Token::Ident(ident)) => Ok(ident),
+            Some(token) => bail!("expected label, found {}", token.describe()),
+            None => bail!("expected label, found end of query"),
+        }
+    }
+
+    fn expect_string(&mut self) -> anyhow::Result<String> {
+        match self.next_token() {
+            Some(Token::String(value)) => Ok(value),
+            Some(token) => bail!("expected string, found {}", token.describe()),
+            None => bail!("expected string, found end of query"),
+        }
+    }
+
+    fn parse_query(&mut self) -> anyhow::Result<LogqlQuery> {
+        let query = match self.peek() {
+            Some(Token::Ident(_)) => LogqlQuery::Metric(self.parse_metric_query()?),
+            _ => LogqlQuery::Log(self.parse_log_query()?),
+        };
+        if let Some(token) = self.peek() {
+            bail!("unexpected {} after the end of the query", token.describe());
+        }
+        Ok(query)
+    }
+
+    fn parse_metric_query(&mut self) -> anyhow::Result<MetricQuery> {
+        let function = self.expect_ident()?;
+        if function != "sum" {
+            return self.parse_range_function(&function);
+        }
+        let mut sum_by = self.parse_grouping()?;
+        self.expect(Token::LeftParen)?;
+        let function = self.expect_ident()?;
+        let mut metric_query = self.parse_range_function(&function)?;
+        self.expect(Token::RightParen)?;
+        if sum_by.is_none() {
+            sum_by = self.parse_grouping()?;
+        }
+        metric_query.sum_by = Some(sum_by.unwrap_or_default());
+        Ok(metric_query)
+    }
+
+    fn parse_grouping(&mut self) -> anyhow::Result<Option<Vec<String>>> {
+        if self.peek_ident("without") {
+            bail!("`without` grouping is not supported, use `by` instead");
+        }
+        if !self.peek_ident("by") {
+            return Ok(None);
+        }
+        self.next_token();
+        self.expect(Token::LeftParen)?;
+        let mut labels = Vec::new();
+        loop {
+            labels.push(self.expect_ident()?);
+            match self.next_token() {
+                Some(Token::Comma) => continue,
+                Some(Token::RightParen) => break,
+                Some(token) => bail!("expected `,` or `)`, found {}", token.describe()),
+                None => bail!("expected `,` or `)`, found end of query"),
+            }
+        }
+        Ok(Some(labels))
+    }
+
+    fn parse_range_function(&mut self, function: &str) -> anyhow::Result<MetricQuery> {
+        let range_function = match function {
+            "count_over_time" => RangeFunction::CountOverTime,
+            "rate" => RangeFunction::Rate,
+            _ => bail!(
+                "unsupported function `{function}`, only `count_over_time`, `rate` and `sum` are \
+                 supported"
+            ),
+        };
+        self.expect(Token::LeftParen)?;
+        let log_query = self.parse_log_query()?;
+        let range = match self.next_token() {
+            Some(Token::Range(range_str)) => humantime::parse_duration(&range_str)
+                .with_context(|| format!("invalid range `[{range_str}]`"))?,
+            Some(token) => bail!("expected range, found {}", token.describe()),
+            None => bail!("expected range, found end of query"),
+        };
+        if range.is_zero() {
+            bail!("range must be positive");
+        }
+        self.expect(Token::RightParen)?;
+        Ok(MetricQuery {
+            range_function,
+            log_query,
+            range,
+            sum_by: None,
+        })
+    }
+
+    fn parse_log_query(&mut self) -> anyhow::Result<LogQuery> {
+        self.expect(Token::LeftBrace)?;
+        let mut matchers = Vec::new();
+        loop {
+            let label = self.expect_ident()?;
+            let op = match self.next_token() {
+                Some(Token::Eq) => MatchOp::Eq,
+                Some(Token::Neq) => MatchOp::Neq,
+                Some(Token::Re) => MatchOp::Re,
+                Some(Token::Nre) => MatchOp::Nre,
+                Some(token) => bail!("expected label matcher, found {}", token.describe()),
+                None => bail!("expected label matcher, found end of query"),
+            };
+            let value = self.expect_string()?;
+            matchers.push(LabelMatcher { label, op, value });
+            match self.next_token() {
+                Some(Token::Comma) => continue,
+                Some(Token::RightBrace) => break,
+                Some(token) => bail!("expected `,` or `}}`, found {}", token.describe()),
+                None => bail!("expected `,` or `}}`, found end of query"),
+            }
+        }
+        let mut line_filters = Vec::new();
+        loop {
+            let op = match self.peek() {
+                Some(Token::PipeEq) => MatchOp::Eq,
+                Some(Token::Neq) => MatchOp::Neq,
+                Some(Token::PipeRe) => MatchOp::Re,
+                Some(Token::Nre) => MatchOp::Nre,
+                Some(Token::Pipe) => {
+                    let stage = match self.tokens.get(self.position + 1) {
+                        Some(token) => token.describe(),
+                        None => "``".to_string(),
+                    };
+                    bail!("unsupported pipeline stage {stage}, only line filters are supported");
+                }
+                _ => break,
+            };
+            self.next_token();
+            let text = self.expect_string()?;
+            line_filters.push(LineFilter { op, text });
+        }
+        Ok(LogQuery {
+            matchers,
+            line_filters,
+        })
+    }
+}
+
+/// Parses a LogQL query.
+pub(super) fn parse_logql_query(query: &str) -> anyhow::Result<LogqlQuery> {
+    let tokens = tokenize(query)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+    parser.parse_query()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(label: &str, op: MatchOp, value: &str) -> LabelMatcher {
+        LabelMatcher {
+            label: label.to_string(),
+            op,
+            value: value.to_string(),
+        }
+    }
+
+    fn line_filter(op: MatchOp, text: &str) -> LineFilter {
+        LineFilter {
+            op,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_logql_log_query() {
+        let logql_query = parse_logql_query(
+            r#"{app="api", env=~"prod|staging", host!="", resource.region !~ `eu-.*`} |= "error" != "timeout" |~ "5.." |= ``"#,
+        )
+        .unwrap();
+        let expected_log_query = LogQuery {
+            matchers: vec![
+                matcher("app", MatchOp::Eq, "api"),
+                matcher("env", MatchOp::Re, "prod|staging"),
+                matcher("host", MatchOp::Neq, ""),
+                matcher("resource.region", MatchOp::Nre, "eu-.*"),
+            ],
+            line_filters: vec![
+                line_filter(MatchOp::Eq, "error"),
+                line_filter(MatchOp::Neq, "timeout"),
+                line_filter(MatchOp::Re, "5.."),
+                line_filter(MatchOp::Eq, ""),
+            ],
+        };
+        assert_eq!(logql_query, LogqlQuery::Log(expected_log_query));
+
+        let logql_query = parse_logql_query(r#"{msg="say \"hi\"\n"}"#).unwrap();
+        assert_eq!(
+            logql_query.log_query().matchers,
+            vec![matcher("msg", MatchOp::Eq, "say \"hi\"\n")]
+        );
+    }
+
+    #[test]
+    fn test_parse_logql_metric_query() {
+        let logql_query =
+            parse_logql_query(r#"count_over_time({app="api"} |= "error" [5m])"#).unwrap();
+        let LogqlQuery::Metric(metric_query) = logql_query else {
+            panic!("expected a metric query");
+        };
+        assert_eq!(metric_query.range_function, RangeFunction::CountOverTime);
+        assert_eq!(metric_query.range, Duration::from_secs(300));
+        assert_eq!(metric_query.sum_by, None);
+        assert_eq!(
+            metric_query.log_query.line_filters,
+            vec![line_filter(MatchOp::Eq, "error")]
+        );
+        assert_eq!(metric_query.series_labels(), vec!["app".to_string()]);
+
+        let logql_query =
+            parse_logql_query(r#"sum by (level, host) (rate({app="api"}[1h30m]))"#).unwrap();
+        let LogqlQuery::Metric(metric_query) = logql_query else {
+            panic!("expected a metric query");
+        };
+        assert_eq!(metric_query.range_function, RangeFunction::Rate);
+        assert_eq!(metric_query.range, Duration::from_secs(5_400));
+        assert_eq!(
+            metric_query.series_labels(),
+            vec!["level".to_string(), "host".to_string()]
+        );
+
+        let logql_query = parse_logql_query(r#"sum(rate({app="api"}[1m])) by (level)"#).unwrap();
+        let LogqlQuery::Metric(metric_query) = logql_query else {
+            panic!("expected a metric query");
+        };
+        assert_eq!(metric_query.sum_by, Some(vec!["level".to_string()]));
+
+        let logql_query = parse_logql_query(r#"sum(count_over_time({app="api"}[1m]))"#).unwrap();
+        let LogqlQuery::Metric(metric_query) = logql_query else {
+            panic!("expected a metric query");
+        };
+        assert!(metric_query.series_labels().is_empty());
+    }
+
+    #[test]
+    fn test_parse_logql_query_errors() {
+        let parse_error = |query: &str| parse_logql_query(query).unwrap_err().to_string();
+        assert_eq!(
+            parse_error(r#"{app="api"} | json"#),
+            "unsupported pipeline stage `json`, only line filters are supported"
+        );
+        assert_eq!(
+            parse_error(r#"avg_over_time({app="api"}[5m])"#),
+            "unsupported function `avg_over_time`, only `count_over_time`, `rate` and `sum` are \
+             supported"
+        );
+        assert_eq!(
+            parse_error(r#"sum without (app) (rate({app="api"}[5m]))"#),
+            "`without` grouping is not supported, use `by` instead"
+        );
+        assert_eq!(
+            parse_error(r#"{app="api""#),
+            "expected `,` or `}`, found end of query"
+        );
+        assert_eq!(parse_error(r#"{app=api}"#), "expected string, found `api`");
+        assert_eq!(
+            parse_error(r#"rate({app="api"}[0s])"#),
+            "range must be positive"
+        );
+        assert_eq!(
+            parse_error(r#"{app="api"} "error""#),
+            "unexpected string \"error\" after the end of the query"
+        );
+        assert_eq!(parse_error(r#"{app="api}"#), "unterminated string \"api}");
+    }
+
+    #[test]
+    fn test_log_query_to_query_ast() {
+        let log_query = parse_logql_query(
+            r#"{app="api", env=~"prod.*", host=""} |= "connection reset" !~ "debug" |= """#,
+        )
+        .unwrap()
+        .log_query()
+        .clone();
+        let query_ast = log_query
+            .to_query_ast(&["body".to_string(), "attributes.message".to_string()])
+            .unwrap();
+        let full_text_query = |field: &str| -> QueryAst {
+            FullTextQuery {
+                field: field.to_string(),
+                text: "connection reset".to_string(),
+                params: FullTextParams {
+                    tokenizer: None,
+                    mode: FullTextMode::PhraseFallbackToIntersection,
+                    zero_terms_query: MatchAllOrNone::MatchAll,
+                },
+                lenient: true,
+            }
+            .into()
+        };
+        let regex_query = |field: &str| -> QueryAst {
+            RegexQuery {
+                field: field.to_string(),
+                regex: "debug".to_string(),
+            }
+            .into()
+        };
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![
+                TermQuery {
+                    field: "app".to_string(),
+                    value: "api".to_string(),
+                }
+                .into(),
+                RegexQuery {
+                    field: "env".to_string(),
+                    regex: "prod.*".to_string(),
+                }
+                .into(),
+                BoolQuery {
+                    should: vec![
+                        full_text_query("body"),
+                        full_text_query("attributes.message"),
+                    ],
+                    ..Default::default()
+                }
+                .into(),
+            ],
+            must_not: vec![
+                FieldPresenceQuery {
+                    field: "host".to_string(),
+                }
+                .into(),
+                BoolQuery {
+                    should: vec![regex_query("body"), regex_query("attributes.message")],
+                    ..Default::default()
+                }
+                .into(),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let error = log_query.to_query_ast(&[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line filters require the index to have default search fields"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+pub(super) const DEFAULT_LIMIT: u64 = 100;
+
+/// Labels identifying a stream or a series.
+pub(super) type LokiLabels = BTreeMap<String, String>;
+
+/// Envelope of the Loki API responses.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LokiResponseBody<T> {
+    pub status: &'static str,
+    pub data: T,
+}
+
+impl<T> LokiResponseBody<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            status: "success",
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Forward,
+    #[default]
+    Backward,
+}
+
+// Grafana sends parameters that are not used here, such as `step` for log queries, so unknown
+// parameters are ignored.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QueryRangeQueryParams {
+    /// LogQL query.
+    pub query: String,
+    /// Start of the time range, as a Unix timestamp in nanoseconds, a Unix timestamp in seconds
+    /// with a fractional part, or an RFC 3339 datetime. Defaults to one hour before `end`.
+    pub start: Option<String>,
+    /// End of the time range, in the same formats as `start`. Defaults to now.
+    pub end: Option<String>,
+    /// Maximum number of log lines returned by a log query.
+    pub limit: Option<u64>,
+    /// Order of the log lines: `forward` or `backward`.
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LabelsQueryParams {
+    /// Start of the time range. Defaults to one hour before `end`.
+    pub start: Option<String>,
+    /// End of the time range. Defaults to now.
+    pub end: Option<String>,
+    /// Log query selecting the streams to get the label values of.
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SeriesQueryParams {
+    /// Stream selectors. The series matching any of them are returned.
+    #[serde(rename = "match", default)]
+    #[param(rename = "match[]")]
+    pub matches: Vec<String>,
+    /// Start of the time range. Defaults to one hour before `end`.
+    pub start: Option<String>,
+    /// End of the time range. Defaults to now.
+    pub end: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "resultType", content = "result", rename_all = "snake_case")]
+pub enum QueryRangeData {
+    Streams(Vec<LokiStream>),
+    Matrix(Vec<LokiSeries>),
+}
+
+/// Log lines of a stream, as pairs of timestamp in nanoseconds and line.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LokiStream {
+    pub stream: LokiLabels,
+    pub values: Vec<(String, String)>,
+}
+
+/// Samples of a series, as pairs of timestamp in seconds and value.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LokiSeries {
+    pub metric: LokiLabels,
+    pub values: Vec<(f64, String)>,
+}
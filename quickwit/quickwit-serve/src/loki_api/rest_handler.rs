@@ -0,0 +1,904 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use quickwit_datetime::{parse_date_time_str, DateTimeInputFormat};
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::search::sort_by_value::SortValue;
+use quickwit_proto::search::{
+    CountHits, Hit, SearchRequest, SortDatetimeFormat, SortField, SortOrder,
+};
+use quickwit_query::query_ast::QueryAst;
+use quickwit_search::{resolve_index_patterns, SearchError, SearchService};
+use serde_json::{json, Value as JsonValue};
+use warp::{Filter, Rejection};
+
+use super::logql::{parse_logql_query, LogQuery, LogqlQuery, MetricQuery, RangeFunction};
+use super::model::{
+    Direction, LabelsQueryParams, LokiLabels, LokiResponseBody, LokiSeries, LokiStream,
+    QueryRangeData, QueryRangeQueryParams, SeriesQueryParams, DEFAULT_LIMIT,
+};
+use crate::rest::recover_fn;
+use crate::rest_api_response::into_rest_api_response;
+use crate::search_api::extract_index_id_patterns;
+use crate::{with_arg, BodyFormat};
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Time range queried when the request does not set a start.
+const DEFAULT_LOOKBACK_NANOS: i64 = 3_600 * NANOS_PER_SEC;
+
+/// Maximum number of label values returned, and of values per label in metric queries.
+const MAX_LABEL_VALUES: u64 = 1_000;
+
+/// Number of most recent log lines from which the series are collected.
+const MAX_SERIES_LOG_LINES: u64 = 1_000;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(paths(
+    loki_query_range_handler,
+    loki_labels_handler,
+    loki_label_values_handler,
+    loki_series_handler
+))]
+pub(crate) struct LokiApi;
+
+/// Setup Loki API handlers
+///
+/// This is where all Loki handlers
+/// should be registered.
+pub(crate) fn loki_api_handlers(
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    loki_query_range_handler(search_service.clone(), metastore.clone())
+        .or(loki_labels_handler(metastore.clone()))
+        .or(loki_label_values_handler(
+            search_service.clone(),
+            metastore.clone(),
+        ))
+        .or(loki_series_handler(search_service, metastore))
+        .recover(recover_fn)
+        .boxed()
+}
+
+fn loki_api_path_filter() -> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
+    warp::path!(String / "loki" / "api" / "v1" / ..)
+        .and(warp::get())
+        .and_then(extract_index_id_patterns)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Loki",
+    path = "/{index_id}/loki/api/v1/query_range",
+    responses(
+        (status = 200, description = "Successfully executed the LogQL query.", body = LokiResponseBody)
+    ),
+    params(
+        QueryRangeQueryParams,
+        ("index_id" = String, Path, description = "The index ID or index ID patterns to query."),
+    )
+)]
+/// LogQL Query
+///
+/// Runs a LogQL log query or metric query over a time range.
+pub fn loki_query_range_handler(
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    loki_api_path_filter()
+        .and(warp::path!("query_range"))
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(search_service))
+        .and(with_arg(metastore))
+        .then(loki_query_range)
+        .map(|result| into_rest_api_response(result, BodyFormat::default()))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Loki",
+    path = "/{index_id}/loki/api/v1/labels",
+    responses(
+        (status = 200, description = "Successfully fetched the label names.", body = LokiResponseBody)
+    ),
+    params(
+        LabelsQueryParams,
+        ("index_id" = String, Path, description = "The index ID or index ID patterns to get the labels of."),
+    )
+)]
+/// Label Names
+///
+/// Returns the labels of the streams, which are the tag fields of the indexes.
+pub fn loki_labels_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    loki_api_path_filter()
+        .and(warp::path!("labels"))
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(metastore))
+        .then(loki_labels)
+        .map(|result| into_rest_api_response(result, BodyFormat::default()))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Loki",
+    path = "/{index_id}/loki/api/v1/label/{name}/values",
+    responses(
+        (status = 200, description = "Successfully fetched the label values.", body = LokiResponseBody)
+    ),
+    params(
+        LabelsQueryParams,
+        ("index_id" = String, Path, description = "The index ID or index ID patterns to get the label values of."),
+        ("name" = String, Path, description = "The label to get the values of."),
+    )
+)]
+/// Label Values
+///
+/// Returns the values of a label over a time range.
+pub fn loki_label_values_handler(
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    loki_api_path_filter()
+        .and(warp::path!("label" / String / "values"))
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(search_service))
+        .and(with_arg(metastore))
+        .then(loki_label_values)
+        .map(|result| into_rest_api_response(result, BodyFormat::default()))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Loki",
+    path = "/{index_id}/loki/api/v1/series",
+    responses(
+        (status = 200, description = "Successfully fetched the series.", body = LokiResponseBody)
+    ),
+    params(
+        SeriesQueryParams,
+        ("index_id" = String, Path, description = "The index ID or index ID patterns to get the series of."),
+    )
+)]
+/// Series
+///
+/// Returns the label sets of the streams matching stream selectors.
+pub fn loki_series_handler(
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    loki_api_path_filter()
+        .and(warp::path!("series"))
+        // `match[]` is usually sent with percent-encoded brackets.
+        .and(serde_qs::warp::query(serde_qs::Config::new(5, false)))
+        .and(with_arg(search_service))
+        .and(with_arg(metastore))
+        .then(loki_series)
+        .map(|result| into_rest_api_response(result, BodyFormat::default()))
+}
+
+/// Fields of the queried indexes that the Loki API relies on.
+#[derive(Debug)]
+struct LokiFields {
+    timestamp_field: String,
+    /// Tag fields of the indexes, exposed as labels.
+    label_fields: Vec<String>,
+    /// Default search fields of the indexes, targeted by line filters.
+    line_filter_fields: Vec<String>,
+}
+
+async fn resolve_loki_fields(
+    index_id_patterns: &[String],
+    mut metastore: MetastoreServiceClient,
+) -> Result<LokiFields, SearchError> {
+    let indexes_metadata = resolve_index_patterns(index_id_patterns, &mut metastore).await?;
+    let mut timestamp_fields = BTreeSet::new();
+    let mut label_fields = BTreeSet::new();
+    let mut line_filter_fields = BTreeSet::new();
+
+    for index_metadata in &indexes_metadata {
+        let index_config = &index_metadata.index_config;
+        let Some(timestamp_field) = &index_config.doc_mapping.timestamp_field else {
+            return Err(SearchError::InvalidArgument(format!(
+                "index `{}` has no timestamp field",
+                index_config.index_id
+            )));
+        };
+        timestamp_fields.insert(timestamp_field.clone());
+        label_fields.extend(index_config.doc_mapping.tag_fields.iter().cloned());
+        line_filter_fields.extend(
+            index_config
+                .search_settings
+                .default_search_fields
+                .iter()
+                .cloned(),
+        );
+    }
+    if timestamp_fields.len() > 1 {
+        return Err(SearchError::InvalidArgument(
+            "the queried indexes must have the same timestamp field".to_string(),
+        ));
+    }
+    let Some(timestamp_field) = timestamp_fields.pop_first() else {
+        return Err(SearchError::IndexesNotFound {
+            index_ids: index_id_patterns.to_vec(),
+        });
+    };
+    Ok(LokiFields {
+        timestamp_field,
+        label_fields: label_fields.into_iter().collect(),
+        line_filter_fields: line_filter_fields.into_iter().collect(),
+    })
+}
+
+fn parse_query(query: &str) -> Result<LogqlQuery, SearchError> {
+    parse_logql_query(query)
+        .map_err(|error| SearchError::InvalidQuery(format!("invalid LogQL query: {error}")))
+}
+
+fn log_query_ast(log_query: &LogQuery, loki_fields: &LokiFields) -> Result<String, SearchError> {
+    let query_ast = log_query
+        .to_query_ast(&loki_fields.line_filter_fields)
+        .map_err(|error| SearchError::InvalidQuery(format!("invalid LogQL query: {error}")))?;
+    Ok(serde_json::to_string(&query_ast)?)
+}
+
+/// Parses a timestamp of the Loki API into nanoseconds. Integers are Unix timestamps in
+/// nanoseconds and decimal numbers are Unix timestamps in seconds.
+fn parse_timestamp_nanos(timestamp_str: &str) -> Result<i64, SearchError> {
+    if let Ok(timestamp_nanos) = timestamp_str.parse::<i64>() {
+        return Ok(timestamp_nanos);
+    }
+    if let Ok(timestamp_secs) = timestamp_str.parse::<f64>() {
+        return Ok((timestamp_secs * NANOS_PER_SEC as f64) as i64);
+    }
+    parse_date_time_str(timestamp_str, &[DateTimeInputFormat::Rfc3339])
+        .map(|date_time| date_time.into_timestamp_nanos())
+        .map_err(|_| SearchError::InvalidArgument(format!("invalid timestamp `{timestamp_str}`")))
+}
+
+/// Returns the `[start, end]` time range of the request in nanoseconds.
+fn parse_time_range(
+    start_opt: Option<&str>,
+    end_opt: Option<&str>,
+) -> Result<(i64, i64), SearchError> {
+    let end_nanos = match end_opt {
+        Some(end_str) => parse_timestamp_nanos(end_str)?,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as i64)
+            .unwrap_or_default(),
+    };
+    let start_nanos = match start_opt {
+        Some(start_str) => parse_timestamp_nanos(start_str)?,
+        None => end_nanos - DEFAULT_LOOKBACK_NANOS,
+    };
+    if start_nanos > end_nanos {
+        return Err(SearchError::InvalidArgument(
+            "the start of the time range must not be after its end".to_string(),
+        ));
+    }
+    Ok((start_nanos, end_nanos))
+}
+
+/// Builds a search request over the time range, rounded to the enclosing seconds.
+fn search_request_for_time_range(
+    index_id_patterns: Vec<String>,
+    query_ast: String,
+    (start_nanos, end_nanos): (i64, i64),
+) -> SearchRequest {
+    SearchRequest {
+        index_id_patterns,
+        query_ast,
+        start_timestamp: Some(start_nanos.div_euclid(NANOS_PER_SEC)),
+        end_timestamp: Some(end_nanos.div_euclid(NANOS_PER_SEC) + 1),
+        count_hits: CountHits::Underestimate as i32,
+        ..Default::default()
+    }
+}
+
+/// Sorts the hits by timestamp and returns the timestamps in nanoseconds as sort values.
+fn sort_by_timestamp(search_request: &mut SearchRequest, timestamp_field: &str, order: SortOrder) {
+    search_request.sort_fields = vec![SortField {
+        field_name: timestamp_field.to_string(),
+        sort_order: order as i32,
+        sort_datetime_format: Some(SortDatetimeFormat::UnixTimestampNanos as i32),
+    }];
+}
+
+async fn loki_query_range(
+    index_id_patterns: Vec<String>,
+    query_params: QueryRangeQueryParams,
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> Result<LokiResponseBody<QueryRangeData>, SearchError> {
+    let logql_query = parse_query(&query_params.query)?;
+    let time_range = parse_time_range(query_params.start.as_deref(), query_params.end.as_deref())?;
+    let loki_fields = resolve_loki_fields(&index_id_patterns, metastore).await?;
+    let query_ast = log_query_ast(logql_query.log_query(), &loki_fields)?;
+    let mut search_request =
+        search_request_for_time_range(index_id_patterns, query_ast, time_range);
+
+    let data = match logql_query {
+        LogqlQuery::Log(_) => {
+            let sort_order = match query_params.direction {
+                Direction::Forward => SortOrder::Asc,
+                Direction::Backward => SortOrder::Desc,
+            };
+            sort_by_timestamp(
+                &mut search_request,
+                &loki_fields.timestamp_field,
+                sort_order,
+            );
+            search_request.max_hits = query_params.limit.unwrap_or(DEFAULT_LIMIT);
+            let search_response = search_service.root_search(search_request).await?;
+            let streams = hits_to_streams(&search_response.hits, &loki_fields.label_fields)?;
+            QueryRangeData::Streams(streams)
+        }
+        LogqlQuery::Metric(metric_query) => {
+            let series_labels = metric_query.series_labels();
+            let aggregation =
+                metric_aggregation(&metric_query, &series_labels, &loki_fields.timestamp_field);
+            search_request.aggregation_request = Some(serde_json::to_string(&aggregation)?);
+            let search_response = search_service.root_search(search_request).await?;
+            let aggregation_result: JsonValue = match &search_response.aggregation {
+                Some(aggregation_json) => serde_json::from_str(aggregation_json)?,
+                None => JsonValue::Null,
+            };
+            let mut series = Vec::new();
+            collect_series(
+                &metric_query,
+                &series_labels,
+                &aggregation_result,
+                &mut LokiLabels::new(),
+                &mut series,
+            );
+            QueryRangeData::Matrix(series)
+        }
+    };
+    Ok(LokiResponseBody::success(data))
+}
+
+async fn loki_labels(
+    index_id_patterns: Vec<String>,
+    _query_params: LabelsQueryParams,
+    metastore: MetastoreServiceClient,
+) -> Result<LokiResponseBody<Vec<String>>, SearchError> {
+    let loki_fields = resolve_loki_fields(&index_id_patterns, metastore).await?;
+    Ok(LokiResponseBody::success(loki_fields.label_fields))
+}
+
+async fn loki_label_values(
+    index_id_patterns: Vec<String>,
+    label: String,
+    query_params: LabelsQueryParams,
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> Result<LokiResponseBody<Vec<String>>, SearchError> {
+    let logql_query_opt = query_params.query.as_deref().map(parse_query).transpose()?;
+    let time_range = parse_time_range(query_params.start.as_deref(), query_params.end.as_deref())?;
+    let loki_fields = resolve_loki_fields(&index_id_patterns, metastore).await?;
+    let query_ast = match &logql_query_opt {
+        Some(logql_query) => log_query_ast(logql_query.log_query(), &loki_fields)?,
+        None => serde_json::to_string(&QueryAst::MatchAll)?,
+    };
+    let mut search_request =
+        search_request_for_time_range(index_id_patterns, query_ast, time_range);
+    let aggregation = json!({
+        "values": {
+            "terms": {
+                "field": label,
+                "size": MAX_LABEL_VALUES,
+            }
+        }
+    });
+    search_request.aggregation_request = Some(serde_json::to_string(&aggregation)?);
+    let search_response = search_service.root_search(search_request).await?;
+    let aggregation_result: JsonValue = match &search_response.aggregation {
+        Some(aggregation_json) => serde_json::from_str(aggregation_json)?,
+        None => JsonValue::Null,
+    };
+    let label_values: BTreeSet<String> = aggregation_result["values"]["buckets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|bucket| label_value(&bucket["key"]))
+        .collect();
+    Ok(LokiResponseBody::success(
+        label_values.into_iter().collect(),
+    ))
+}
+
+async fn loki_series(
+    index_id_patterns: Vec<String>,
+    query_params: SeriesQueryParams,
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> Result<LokiResponseBody<Vec<LokiLabels>>, SearchError> {
+    if query_params.matches.is_empty() {
+        return Err(SearchError::InvalidArgument(
+            "at least one `match[]` stream selector is required".to_string(),
+        ));
+    }
+    let logql_queries: Vec<LogqlQuery> = query_params
+        .matches
+        .iter()
+        .map(|selector| parse_query(selector))
+        .collect::<Result<_, _>>()?;
+    let time_range = parse_time_range(query_params.start.as_deref(), query_params.end.as_deref())?;
+    let loki_fields = resolve_loki_fields(&index_id_patterns, metastore).await?;
+    let mut series: BTreeSet<LokiLabels> = BTreeSet::new();
+
+    for logql_query in &logql_queries {
+        let query_ast = log_query_ast(logql_query.log_query(), &loki_fields)?;
+        let mut search_request =
+            search_request_for_time_range(index_id_patterns.clone(), query_ast, time_range);
+        sort_by_timestamp(
+            &mut search_request,
+            &loki_fields.timestamp_field,
+            SortOrder::Desc,
+        );
+        search_request.max_hits = MAX_SERIES_LOG_LINES;
+        let search_response = search_service.root_search(search_request).await?;
+
+        for hit in &search_response.hits {
+            let json_doc: JsonValue = serde_json::from_str(&hit.json)?;
+            let labels = extract_labels(&json_doc, &loki_fields.label_fields);
+            if !labels.is_empty() {
+                series.insert(labels);
+            }
+        }
+    }
+    Ok(LokiResponseBody::success(series.into_iter().collect()))
+}
+
+/// Groups the hits into streams identified by the values of the label fields, preserving the
+/// order of the hits.
+fn hits_to_streams(hits: &[Hit], label_fields: &[String]) -> Result<Vec<LokiStream>, SearchError> {
+    let mut streams: Vec<LokiStream> = Vec::new();
+    let mut stream_ordinals: HashMap<LokiLabels, usize> = HashMap::new();
+
+    for hit in hits {
+        let json_doc: JsonValue = serde_json::from_str(&hit.json)?;
+        let labels = extract_labels(&json_doc, label_fields);
+        let timestamp_nanos = hit_timestamp_nanos(hit).unwrap_or_default();
+        let stream_ordinal = *stream_ordinals.entry(labels.clone()).or_insert_with(|| {
+            streams.push(LokiStream {
+                stream: labels,
+                values: Vec::new(),
+            });
+            streams.len() - 1
+        });
+        streams[stream_ordinal]
+            .values
+            .push((timestamp_nanos.to_string(), hit.json.clone()));
+    }
+    Ok(streams)
+}
+
+fn hit_timestamp_nanos(hit: &Hit) -> Option<i64> {
+    let sort_value = hit.partial_hit.as_ref()?.sort_value.as_ref()?;
+    match sort_value.sort_value.as_ref()? {
+        SortValue::I64(timestamp_nanos) => Some(*timestamp_nanos),
+        SortValue::U64(timestamp_nanos) => Some(*timestamp_nanos as i64),
+        _ => None,
+    }
+}
+
+fn extract_labels(json_doc: &JsonValue, label_fields: &[String]) -> LokiLabels {
+    label_fields
+        .iter()
+        .filter_map(|label_field| {
+            let value = label_value(json_value_at_path(json_doc, label_field)?)?;
+            Some((label_field.clone(), value))
+        })
+        .collect()
+}
+
+fn label_value(json_value: &JsonValue) -> Option<String> {
+    match json_value {
+        JsonValue::String(value) => Some(value.clone()),
+        JsonValue::Bool(_) | JsonValue::Number(_) => Some(json_value.to_string()),
+        JsonValue::Null | JsonValue::Array(_) | JsonValue::Object(_) => None,
+    }
+}
+
+/// Returns the value at `path`, where the dots separate the keys of nested objects unless the
+/// key contains a dot itself.
+fn json_value_at_path<'a>(json_value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    if let Some(value) = json_value.get(path) {
+        return Some(value);
+    }
+    for (dot_idx, _) in path.match_indices('.') {
+        let Some(child) = json_value.get(&path[..dot_idx]) else {
+            continue;
+        };
+        if let Some(value) = json_value_at_path(child, &path[dot_idx + 1..]) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Builds nested terms aggregations on the series labels, over a date histogram whose buckets
+/// span the range of the range function.
+fn metric_aggregation(
+    metric_query: &MetricQuery,
+    series_labels: &[String],
+    timestamp_field: &str,
+) -> JsonValue {
+    let mut aggregation = json!({
+        "samples": {
+            "date_histogram": {
+                "field": timestamp_field,
+                "fixed_interval": format!("{}ms", metric_query.range.as_millis()),
+            }
+        }
+    });
+    for label in series_labels.iter().rev() {
+        aggregation = json!({
+            "series": {
+                "terms": {
+                    "field": label,
+                    "size": MAX_LABEL_VALUES,
+                },
+                "aggs": aggregation,
+            }
+        });
+    }
+    aggregation
+}
+
+fn collect_series(
+    metric_query: &MetricQuery,
+    series_labels: &[String],
+    aggregation_result: &JsonValue,
+    labels: &mut LokiLabels,
+    series: &mut Vec<LokiSeries>,
+) {
+    let Some((label, remaining_labels)) = series_labels.split_first() else {
+        let range_millis = metric_query.range.as_millis() as f64;
+        let values: Vec<(f64, String)> = aggregation_result["samples"]["buckets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|bucket| {
+                let bucket_start_millis = bucket["key"].as_f64()?;
+                let doc_count = bucket["doc_count"].as_u64()?;
+                if doc_count == 0 {
+                    return None;
+                }
+                // Samples are timestamped at the end of their range, like in Loki.
+                let timestamp_secs = (bucket_start_millis + range_millis) / 1_000.0;
+                let value = match metric_query.range_function {
+                    RangeFunction::CountOverTime => doc_count.to_string(),
+                    RangeFunction::Rate => {
+                        (doc_count as f64 / metric_query.range.as_secs_f64()).to_string()
+                    }
+                };
+                Some((timestamp_secs, value))
+            })
+            .collect();
+        if !values.is_empty() {
+            series.push(LokiSeries {
+                metric: labels.clone(),
+                values,
+            });
+        }
+        return;
+    };
+    for bucket in aggregation_result["series"]["buckets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let Some(value) = label_value(&bucket["key"]) else {
+            continue;
+        };
+        labels.insert(label.clone(), value);
+        collect_series(metric_query, remaining_labels, bucket, labels, series);
+    }
+    labels.remove(label);
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt};
+    use quickwit_proto::metastore::{ListIndexesMetadataResponse, MockMetastoreService};
+    use quickwit_proto::search::{PartialHit, SearchResponse, SortByValue};
+    use quickwit_search::MockSearchService;
+
+    use super::*;
+
+    fn metastore_for_test() -> MetastoreServiceClient {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_| {
+                // The test index has the `timestamp` timestamp field, the `owner` tag field, and
+                // `body` among its default search fields.
+                let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+                Ok(ListIndexesMetadataResponse::for_test(vec![index_metadata]))
+            });
+        MetastoreServiceClient::from_mock(mock_metastore)
+    }
+
+    fn hit(timestamp_nanos: i64, json: &str) -> Hit {
+        Hit {
+            json: json.to_string(),
+            partial_hit: Some(PartialHit {
+                sort_value: Some(SortByValue {
+                    sort_value: Some(SortValue::I64(timestamp_nanos)),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_timestamp_nanos() {
+        assert_eq!(
+            parse_timestamp_nanos("1700000000123456789").unwrap(),
+            1_700_000_000_123_456_789
+        );
+        assert_eq!(
+            parse_timestamp_nanos("1700000000.5").unwrap(),
+            1_700_000_000_500_000_000
+        );
+        assert_eq!(
+            parse_timestamp_nanos("2023-11-14T22:13:20Z").unwrap(),
+            1_700_000_000 * NANOS_PER_SEC
+        );
+        parse_timestamp_nanos("yesterday").unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_loki_query_range_streams() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.index_id_patterns == vec!["test-index".to_string()]
+                    && search_request.start_timestamp == Some(1_700_000_000)
+                    && search_request.end_timestamp == Some(1_700_003_601)
+                    && search_request.max_hits == 10
+                    && search_request.sort_fields[0].field_name == "timestamp"
+                    && search_request.sort_fields[0].sort_order == SortOrder::Desc as i32
+                    && search_request.query_ast.contains(r#""field":"owner""#)
+            })
+            .return_once(|_| {
+                Ok(SearchResponse {
+                    num_hits: 3,
+                    hits: vec![
+                        hit(3, r#"{"owner":"foo","body":"error 3"}"#),
+                        hit(2, r#"{"owner":"bar","body":"error 2"}"#),
+                        hit(1, r#"{"owner":"foo","body":"error 1"}"#),
+                    ],
+                    ..Default::default()
+                })
+            });
+        let loki_api_handler =
+            loki_api_handlers(Arc::new(mock_search_service), metastore_for_test());
+        let resp = warp::test::request()
+            .path(
+                "/test-index/loki/api/v1/query_range?query=%7Bowner%3D~%22foo%7Cbar%22%7D%20%7C%\
+                 3D%20%22error%22&start=1700000000000000000&end=1700003600000000000&limit=10",
+            )
+            .reply(&loki_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_resp_json = json!({
+            "status": "success",
+            "data": {
+                "resultType": "streams",
+                "result": [
+                    {
+                        "stream": {"owner": "foo"},
+                        "values": [
+                            ["3", r#"{"owner":"foo","body":"error 3"}"#],
+                            ["1", r#"{"owner":"foo","body":"error 1"}"#],
+                        ]
+                    },
+                    {
+                        "stream": {"owner": "bar"},
+                        "values": [["2", r#"{"owner":"bar","body":"error 2"}"#]]
+                    }
+                ]
+            }
+        });
+        assert_eq!(resp_json, expected_resp_json);
+    }
+
+    #[tokio::test]
+    async fn test_loki_query_range_matrix() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                let aggregation: JsonValue =
+                    serde_json::from_str(search_request.aggregation_request.as_ref().unwrap())
+                        .unwrap();
+                search_request.max_hits == 0
+                    && aggregation
+                        == json!({
+                            "series": {
+                                "terms": {"field": "owner", "size": 1_000},
+                                "aggs": {
+                                    "samples": {
+                                        "date_histogram": {
+                                            "field": "timestamp",
+                                            "fixed_interval": "60000ms",
+                                        }
+                                    }
+                                }
+                            }
+                        })
+            })
+            .return_once(|_| {
+                let aggregation = json!({
+                    "series": {
+                        "buckets": [
+                            {
+                                "key": "foo",
+                                "doc_count": 6,
+                                "samples": {
+                                    "buckets": [
+                                        {"key": 1_700_000_000_000.0, "doc_count": 6},
+                                        {"key": 1_700_000_060_000.0, "doc_count": 0},
+                                    ]
+                                }
+                            },
+                            {
+                                "key": "bar",
+                                "doc_count": 3,
+                                "samples": {
+                                    "buckets": [
+                                        {"key": 1_700_000_060_000.0, "doc_count": 3},
+                                    ]
+                                }
+                            }
+                        ]
+                    }
+                });
+                Ok(SearchResponse {
+                    num_hits: 9,
+                    aggregation: Some(aggregation.to_string()),
+                    ..Default::default()
+                })
+            });
+        let loki_api_handler =
+            loki_api_handlers(Arc::new(mock_search_service), metastore_for_test());
+        let resp = warp::test::request()
+            .path(
+                "/test-index/loki/api/v1/query_range?query=sum%20by%20(owner)%20(rate(%7Bowner%\
+                 3D~%22.%2B%22%7D%5B1m%5D))&start=1700000000000000000&end=1700000120000000000",
+            )
+            .reply(&loki_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_resp_json = json!({
+            "status": "success",
+            "data": {
+                "resultType": "matrix",
+                "result": [
+                    {"metric": {"owner": "foo"}, "values": [[1_700_000_060.0, "0.1"]]},
+                    {"metric": {"owner": "bar"}, "values": [[1_700_000_120.0, "0.05"]]},
+                ]
+            }
+        });
+        assert_eq!(resp_json, expected_resp_json);
+    }
+
+    #[tokio::test]
+    async fn test_loki_labels_and_label_values() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request
+                    .aggregation_request
+                    .as_ref()
+                    .unwrap()
+                    .contains(r#""field":"owner""#)
+            })
+            .return_once(|_| {
+                let aggregation = json!({
+                    "values": {
+                        "buckets": [
+                            {"key": "foo", "doc_count": 2},
+                            {"key": "bar", "doc_count": 1},
+                        ]
+                    }
+                });
+                Ok(SearchResponse {
+                    aggregation: Some(aggregation.to_string()),
+                    ..Default::default()
+                })
+            });
+        let loki_api_handler =
+            loki_api_handlers(Arc::new(mock_search_service), metastore_for_test());
+        let resp = warp::test::request()
+            .path("/test-index/loki/api/v1/labels")
+            .reply(&loki_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, json!({"status": "success", "data": ["owner"]}));
+
+        let resp = warp::test::request()
+            .path("/test-index/loki/api/v1/label/owner/values")
+            .reply(&loki_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            resp_json,
+            json!({"status": "success", "data": ["bar", "foo"]})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_loki_series() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().return_once(|_| {
+            Ok(SearchResponse {
+                num_hits: 3,
+                hits: vec![
+                    hit(3, r#"{"owner":"foo"}"#),
+                    hit(2, r#"{"body":"no owner"}"#),
+                    hit(1, r#"{"owner":"foo"}"#),
+                ],
+                ..Default::default()
+            })
+        });
+        let loki_api_handler =
+            loki_api_handlers(Arc::new(mock_search_service), metastore_for_test());
+        let resp = warp::test::request()
+            .path("/test-index/loki/api/v1/series?match%5B%5D=%7Bowner%3D%22foo%22%7D")
+            .reply(&loki_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            resp_json,
+            json!({"status": "success", "data": [{"owner": "foo"}]})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_loki_query_range_invalid_query() {
+        let loki_api_handler =
+            loki_api_handlers(Arc::new(MockSearchService::new()), metastore_for_test());
+        let resp = warp::test::request()
+            .path("/test-index/loki/api/v1/query_range?query=%7Bowner%3D%22foo%22%7D%20%7C%20json")
+            .reply(&loki_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            resp_json["message"],
+            "invalid LogQL query: unsupported pipeline stage `json`, only line filters are \
+             supported"
+        );
+    }
+}
@@ -0,0 +1,434 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::type_name;
+
+use bytes::Bytes;
+use quickwit_config::{ConfigFormat, MonitorConfig, MonitorId, VersionedMonitorConfig};
+use quickwit_metastore::{
+    CreateMonitorRequestExt, GetMonitorResponseExt, ListMonitorsResponseExt, MonitorMetadata,
+};
+use quickwit_proto::metastore::{
+    serde_utils, CreateMonitorRequest, DeleteMonitorsRequest, GetMonitorRequest,
+    ListMonitorsRequest, MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
+};
+use serde_json::Value as JsonValue;
+use warp::reject::Rejection;
+use warp::{Filter, Reply};
+
+use crate::format::{extract_config_format, extract_format_from_qs};
+use crate::rest::recover_fn;
+use crate::rest_api_response::into_rest_api_response;
+use crate::with_arg;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(
+        create_monitor,
+        get_monitor,
+        update_monitor,
+        delete_monitor,
+        list_monitors,
+    ),
+    components(schemas(VersionedMonitorConfig))
+)]
+pub(crate) struct MonitorApi;
+
+pub(crate) fn monitor_api_handlers(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    create_monitor_handler(metastore.clone())
+        .or(get_monitor_handler(metastore.clone()))
+        .or(update_monitor_handler(metastore.clone()))
+        .or(delete_monitor_handler(metastore.clone()))
+        .or(list_monitors_handler(metastore.clone()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+fn validate_monitor_config(monitor_config: &MonitorConfig) -> MetastoreResult<()> {
+    monitor_config.validate().map_err(|error| {
+        let message = format!("invalid monitor config: {error:#}");
+        MetastoreError::InvalidArgument { message }
+    })
+}
+
+fn create_monitor_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("monitors")
+        .and(warp::post())
+        .and(warp::filters::body::bytes())
+        .and(extract_config_format())
+        .and(with_arg(metastore))
+        .then(create_monitor)
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Monitors",
+    path = "/monitors",
+    request_body = VersionedMonitorConfig,
+    responses(
+        (status = 200, description = "The monitor was successfully created.")
+    ),
+)]
+/// Creates a new monitor.
+async fn create_monitor(
+    body: Bytes,
+    config_format: ConfigFormat,
+    metastore: MetastoreServiceClient,
+) -> MetastoreResult<MonitorConfig> {
+    let monitor_config: MonitorConfig =
+        config_format
+            .parse(&body)
+            .map_err(|error| MetastoreError::JsonDeserializeError {
+                struct_name: type_name::<MonitorConfig>().to_string(),
+                message: error.to_string(),
+            })?;
+    validate_monitor_config(&monitor_config)?;
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&monitor_config, false)?;
+    metastore.create_monitor(create_monitor_request).await?;
+    Ok(monitor_config)
+}
+
+fn get_monitor_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("monitors" / String)
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_monitor)
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Monitors",
+    path = "/monitors/{monitor_id}",
+    responses(
+        (status = 200, description = "The monitor was successfully retrieved."),
+        (status = 404, description = "The monitor was not found.")
+    ),
+)]
+/// Retrieves the configuration and the evaluation state of the monitor identified by
+/// `monitor_id`.
+async fn get_monitor(
+    monitor_id: MonitorId,
+    metastore: MetastoreServiceClient,
+) -> MetastoreResult<MonitorMetadata> {
+    let get_monitor_request = GetMonitorRequest { monitor_id };
+    let monitor_metadata = metastore
+        .get_monitor(get_monitor_request)
+        .await?
+        .deserialize_monitor_metadata()?;
+    Ok(monitor_metadata)
+}
+
+fn update_monitor_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("monitors" / String)
+        .and(warp::put())
+        .and(warp::filters::body::bytes())
+        .and(extract_config_format())
+        .and(with_arg(metastore))
+        .then(update_monitor)
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Monitors",
+    path = "/monitors/{monitor_id}",
+    request_body = VersionedMonitorConfig,
+    responses(
+        (status = 200, description = "The monitor was successfully updated."),
+    ),
+)]
+/// Creates or updates the monitor identified by `monitor_id`. The evaluation state of an existing
+/// monitor is preserved.
+async fn update_monitor(
+    monitor_id: MonitorId,
+    body: Bytes,
+    config_format: ConfigFormat,
+    metastore: MetastoreServiceClient,
+) -> MetastoreResult<MonitorConfig> {
+    let mut json_value: JsonValue =
+        config_format
+            .parse(&body)
+            .map_err(|error| MetastoreError::JsonDeserializeError {
+                struct_name: type_name::<MonitorConfig>().to_string(),
+                message: error.to_string(),
+            })?;
+    json_value["monitor_id"] = JsonValue::String(monitor_id);
+
+    if let Some(JsonValue::Number(number)) = json_value.get("version") {
+        json_value["version"] = JsonValue::String(number.to_string());
+    }
+    let monitor_config: MonitorConfig = serde_utils::from_json_value(json_value)?;
+    validate_monitor_config(&monitor_config)?;
+    let create_monitor_request =
+        CreateMonitorRequest::try_from_monitor_config(&monitor_config, true)?;
+    metastore.create_monitor(create_monitor_request).await?;
+    Ok(monitor_config)
+}
+
+fn delete_monitor_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("monitors" / String)
+        .and(warp::delete())
+        .and(with_arg(metastore))
+        .then(delete_monitor)
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Monitors",
+    path = "/monitors/{monitor_id}",
+    responses(
+        (status = 200, description = "The monitor was successfully deleted."),
+    ),
+)]
+/// Deletes the monitor identified by the provided `monitor_id`.
+async fn delete_monitor(
+    monitor_id: MonitorId,
+    metastore: MetastoreServiceClient,
+) -> MetastoreResult<()> {
+    let monitor_ids = vec![monitor_id];
+    let delete_monitors_request = DeleteMonitorsRequest { monitor_ids };
+    metastore.delete_monitors(delete_monitors_request).await?;
+    Ok(())
+}
+
+fn list_monitors_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("monitors")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_monitors)
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Monitors",
+    path = "/monitors",
+    responses(
+        (status = 200, description = "The monitors were successfully retrieved."),
+    ),
+)]
+/// Retrieves all the monitors stored in the metastore along with their evaluation state.
+async fn list_monitors(metastore: MetastoreServiceClient) -> MetastoreResult<Vec<MonitorMetadata>> {
+    let list_monitors_request = ListMonitorsRequest {};
+    let monitors_metadata = metastore
+        .list_monitors(list_monitors_request)
+        .await?
+        .deserialize_monitors_metadata()?;
+    Ok(monitors_metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::metastore::{
+        EmptyResponse, EntityKind, GetMonitorResponse, ListMonitorsResponse, MockMetastoreService,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_monitor() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_create_monitor()
+            .return_once(|request| {
+                assert!(!request.overwrite);
+
+                let monitor_config = request.deserialize_monitor_config().unwrap();
+                assert_eq!(monitor_config.monitor_id, "test-monitor-foo");
+                assert_eq!(monitor_config.index_id, "test-index-foo");
+
+                Ok(EmptyResponse {})
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let create_monitor_handler = create_monitor_handler(metastore);
+        let response = warp::test::request()
+            .path("/monitors")
+            .method("POST")
+            .json(&json!({
+                "version": "0.9",
+                "monitor_id": "test-monitor-foo",
+                "index_id": "test-index-foo",
+                "query": "severity_text:ERROR",
+                "schedule": "hourly",
+                "condition": {"operator": "gt", "threshold": 0},
+                "actions": [{"type": "log"}],
+            }))
+            .reply(&create_monitor_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+
+        let response = warp::test::request()
+            .path("/monitors")
+            .method("POST")
+            .json(&json!({
+                "version": "0.9",
+                "monitor_id": "test-monitor-foo",
+                "index_id": "test-index-foo",
+                "query": "severity_text:ERROR",
+                "schedule": "every full moon",
+                "condition": {"operator": "gt", "threshold": 0},
+                "actions": [{"type": "log"}],
+            }))
+            .reply(&create_monitor_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_get_monitor() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_get_monitor()
+            .withf(|request| request.monitor_id == "test-monitor-foo")
+            .return_once(|request| {
+                let error = MetastoreError::NotFound(EntityKind::Monitor {
+                    monitor_id: request.monitor_id,
+                });
+                Err(error)
+            });
+        mock_metastore
+            .expect_get_monitor()
+            .withf(|request| request.monitor_id == "test-monitor-bar")
+            .return_once(|_request| {
+                let monitor_config = MonitorConfig::for_test("test-monitor-bar", "test-index");
+                let monitor_metadata = MonitorMetadata::new(monitor_config);
+                let response =
+                    GetMonitorResponse::try_from_monitor_metadata(&monitor_metadata).unwrap();
+                Ok(response)
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let get_monitor_handler = get_monitor_handler(metastore);
+
+        let response = warp::test::request()
+            .path("/monitors/test-monitor-foo")
+            .reply(&get_monitor_handler)
+            .await;
+        assert_eq!(response.status(), 404);
+
+        let response = warp::test::request()
+            .path("/monitors/test-monitor-bar")
+            .reply(&get_monitor_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+
+        let monitor_metadata: MonitorMetadata = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(monitor_metadata.monitor_id(), "test-monitor-bar");
+    }
+
+    #[tokio::test]
+    async fn test_update_monitor() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_create_monitor()
+            .return_once(|request| {
+                assert!(request.overwrite);
+
+                let monitor_config = request.deserialize_monitor_config().unwrap();
+                assert_eq!(monitor_config.monitor_id, "test-monitor-foo");
+                assert_eq!(monitor_config.query, "severity_text:FATAL");
+
+                Ok(EmptyResponse {})
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let update_monitor_handler = update_monitor_handler(metastore);
+        let response = warp::test::request()
+            .path("/monitors/test-monitor-foo")
+            .method("PUT")
+            .json(&json!({
+                "version": 0.9,
+                "monitor_id": "test-monitor-bar", // This `monitor_id` should be ignored and overridden by the path parameter.
+                "index_id": "test-index-foo",
+                "query": "severity_text:FATAL",
+                "schedule": "hourly",
+                "condition": {"operator": "gt", "threshold": 0},
+                "actions": [{"type": "log"}],
+            }))
+            .reply(&update_monitor_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_delete_monitor() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_delete_monitors()
+            .return_once(|request| {
+                assert_eq!(request.monitor_ids, ["test-monitor-foo"]);
+                Ok(EmptyResponse {})
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let delete_monitor_handler = delete_monitor_handler(metastore);
+        let response = warp::test::request()
+            .path("/monitors/test-monitor-foo")
+            .method("DELETE")
+            .reply(&delete_monitor_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_list_monitors() {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_monitors()
+            .return_once(|_request| {
+                let monitors_metadata = [
+                    MonitorMetadata::new(MonitorConfig::for_test("test-monitor-foo", "test-index")),
+                    MonitorMetadata::new(MonitorConfig::for_test("test-monitor-bar", "test-index")),
+                ];
+                let response =
+                    ListMonitorsResponse::try_from_monitors_metadata(&monitors_metadata).unwrap();
+                Ok(response)
+            });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let list_monitors_handler = list_monitors_handler(metastore);
+        let response = warp::test::request()
+            .path("/monitors")
+            .method("GET")
+            .reply(&list_monitors_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+
+        let mut monitors_metadata: Vec<MonitorMetadata> =
+            serde_json::from_slice(response.body()).unwrap();
+        monitors_metadata.sort_unstable_by(|left, right| left.monitor_id().cmp(right.monitor_id()));
+
+        assert_eq!(monitors_metadata.len(), 2);
+        assert_eq!(monitors_metadata[0].monitor_id(), "test-monitor-bar");
+        assert_eq!(monitors_metadata[1].monitor_id(), "test-monitor-foo");
+    }
+}
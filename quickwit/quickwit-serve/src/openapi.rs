@@ -31,7 +31,9 @@ use crate::index_api::IndexApi;
 use crate::indexing_api::IndexingApi;
 use crate::ingest_api::{IngestApi, IngestApiSchemas};
 use crate::jaeger_api::JaegerApi;
+use crate::loki_api::LokiApi;
 use crate::metrics_api::MetricsApi;
+use crate::monitor_api::MonitorApi;
 use crate::node_info_handler::NodeInfoApi;
 use crate::otlp_api::OtlpApi;
 use crate::search_api::SearchApi;
@@ -73,7 +75,9 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
         Tag::new("Node Info"),
         Tag::new("Indexing"),
         Tag::new("Splits"),
+        Tag::new("Monitors"),
         Tag::new("Jaeger"),
+        Tag::new("Loki"),
         Tag::new("Open Telemetry"),
         Tag::new("Debug"),
     ];
@@ -93,7 +97,9 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
     docs_base.merge_components_and_paths(IndexTemplateApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(IngestApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(JaegerApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(LokiApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(MetricsApi::openapi().with_path_prefix("/metrics"));
+    docs_base.merge_components_and_paths(MonitorApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(NodeInfoApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(SearchApi::openapi().with_path_prefix("/api/v1"));
 
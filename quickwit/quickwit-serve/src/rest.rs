@@ -19,6 +19,7 @@ use hyper::http::HeaderValue;
 use hyper::{http, Method, StatusCode};
 use quickwit_common::tower::BoxFutureInfaillible;
 use quickwit_config::{disable_ingest_v1, enable_ingest_v2};
+use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_search::SearchService;
 use tokio::net::TcpListener;
 use tower::make::Shared;
@@ -40,13 +41,15 @@ use crate::index_api::index_management_handlers;
 use crate::indexing_api::indexing_get_handler;
 use crate::ingest_api::ingest_api_handlers;
 use crate::jaeger_api::jaeger_api_handlers;
+use crate::loki_api::loki_api_handlers;
 use crate::metrics_api::metrics_handler;
+use crate::monitor_api::monitor_api_handlers;
 use crate::node_info_handler::node_info_handler;
 use crate::otlp_api::otlp_ingest_api_handlers;
 use crate::rest_api_response::{RestApiError, RestApiResponse};
 use crate::search_api::{
-    search_get_handler, search_plan_get_handler, search_plan_post_handler, search_post_handler,
-    search_stream_handler,
+    percolate_handler, search_get_handler, search_plan_get_handler, search_plan_post_handler,
+    search_post_handler, search_stream_handler, sql_handler,
 };
 use crate::template_api::index_template_api_handlers;
 use crate::ui_handler::ui_handler;
@@ -237,12 +240,15 @@ pub(crate) async fn start_rest_server(
 
 fn search_routes(
     search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_get_handler(search_service.clone())
         .or(search_post_handler(search_service.clone()))
         .or(search_plan_get_handler(search_service.clone()))
         .or(search_plan_post_handler(search_service.clone()))
-        .or(search_stream_handler(search_service))
+        .or(search_stream_handler(search_service.clone()))
+        .or(percolate_handler(search_service.clone(), metastore.clone()))
+        .or(sql_handler(search_service, metastore))
         .recover(recover_fn)
         .boxed()
 }
@@ -275,7 +281,10 @@ fn api_v1_routes(
             quickwit_services.indexing_service_opt.clone(),
         ))
         .boxed()
-        .or(search_routes(quickwit_services.search_service.clone()))
+        .or(search_routes(
+            quickwit_services.search_service.clone(),
+            quickwit_services.metastore_client.clone(),
+        ))
         .boxed()
         .or(ingest_api_handlers(
             quickwit_services.ingest_router_service.clone(),
@@ -303,9 +312,18 @@ fn api_v1_routes(
             quickwit_services.jaeger_service_opt.clone(),
         ))
         .boxed()
+        .or(loki_api_handlers(
+            quickwit_services.search_service.clone(),
+            quickwit_services.metastore_client.clone(),
+        ))
+        .boxed()
         .or(index_template_api_handlers(
             quickwit_services.metastore_client.clone(),
         ))
+        .boxed()
+        .or(monitor_api_handlers(
+            quickwit_services.metastore_client.clone(),
+        ))
         .boxed(),
     )
 }
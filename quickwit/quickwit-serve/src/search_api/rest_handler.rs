@@ -20,11 +20,15 @@ use hyper::header::HeaderValue;
 use hyper::HeaderMap;
 use percent_encoding::percent_decode_str;
 use quickwit_config::validate_index_id_pattern;
+use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_proto::search::{CountHits, OutputFormat, SortField, SortOrder};
 use quickwit_proto::types::IndexId;
 use quickwit_proto::ServiceError;
 use quickwit_query::query_ast::query_ast_from_user_text;
-use quickwit_search::{SearchError, SearchPlanResponseRest, SearchResponseRest, SearchService};
+use quickwit_search::{
+    percolate, sql, PercolateRequest, PercolateResponse, SearchError, SearchPlanResponseRest,
+    SearchResponseRest, SearchService, SqlFormat, SqlRequest, SqlResponse,
+};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
 use tracing::info;
@@ -44,16 +48,23 @@ use crate::{with_arg, BodyFormat};
         search_stream_handler,
         search_plan_get_handler,
         search_plan_post_handler,
+        percolate_handler,
+        sql_handler,
     ),
     components(schemas(
         BodyFormat,
         OutputFormat,
+        PercolateRequest,
+        PercolateResponse,
         SearchRequestQueryString,
         SearchResponseRest,
         SearchPlanResponseRest,
         SortBy,
         SortField,
         SortOrder,
+        SqlFormat,
+        SqlRequest,
+        SqlResponse,
     ),)
 )]
 pub struct SearchApi;
@@ -186,6 +197,11 @@ pub struct SearchRequestQueryString {
     /// The aggregation JSON string.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggs: Option<JsonValue>,
+    #[param(value_type = Object)]
+    #[schema(value_type = Object)]
+    /// The runtime fields JSON string, computed at query time from the fast fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_mappings: Option<JsonValue>,
     // Fields to search on
     #[param(rename = "search_field")]
     #[schema(rename = "search_field")]
@@ -292,6 +308,10 @@ pub fn search_request_from_api_request(
         scroll_ttl_secs: None,
         search_after: None,
         count_hits: search_request.count_all.into(),
+        fetch_fields: Vec::new(),
+        runtime_mappings: search_request.runtime_mappings.map(|runtime_mappings| {
+            serde_json::to_string(&runtime_mappings).expect("could not serialize JsonValue")
+        }),
     };
     Ok(search_request)
 }
@@ -355,6 +375,21 @@ fn search_plan_post_filter(
         .and(warp::body::json())
 }
 
+fn percolate_filter(
+) -> impl Filter<Extract = (IndexId, PercolateRequest), Error = Rejection> + Clone {
+    warp::path!(String / "percolate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(10 * 1024 * 1024))
+        .and(warp::body::json())
+}
+
+fn sql_filter() -> impl Filter<Extract = (SqlRequest,), Error = Rejection> + Clone {
+    warp::path!("_sql")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+}
+
 async fn search(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
@@ -497,6 +532,80 @@ pub fn search_plan_post_handler(
         .then(search_plan)
 }
 
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/{index_id}/percolate",
+    request_body = PercolateRequest,
+    responses(
+        (status = 200, description = "Successfully percolated the documents.", body = PercolateResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The ID of the percolator index holding the queries."),
+    )
+)]
+/// Percolate Documents
+///
+/// Returns the IDs of the queries stored in the percolator index matched by each document.
+pub fn percolate_handler(
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    percolate_filter()
+        .and(with_arg(search_service))
+        .and(with_arg(metastore))
+        .then(
+            |index_id: IndexId,
+             percolate_request: PercolateRequest,
+             search_service: Arc<dyn SearchService>,
+             metastore: MetastoreServiceClient| async move {
+                info!(index_id=%index_id, "percolate");
+                let result =
+                    percolate(index_id, percolate_request, &*search_service, metastore).await;
+                into_rest_api_response(result, BodyFormat::default())
+            },
+        )
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/_sql",
+    request_body = SqlRequest,
+    responses(
+        (status = 200, description = "Successfully executed the SQL query.", body = SqlResponse)
+    ),
+)]
+/// SQL Query
+///
+/// Runs a SQL `SELECT` statement and returns the rows as JSON or CSV.
+pub fn sql_handler(
+    search_service: Arc<dyn SearchService>,
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    sql_filter()
+        .and(with_arg(search_service))
+        .and(with_arg(metastore))
+        .then(
+            |sql_request: SqlRequest,
+             search_service: Arc<dyn SearchService>,
+             metastore: MetastoreServiceClient| async move {
+                info!(query=%sql_request.query, "sql");
+                let format = sql_request.format;
+                let result = sql(sql_request, &*search_service, metastore).await;
+                match (format, result) {
+                    (SqlFormat::Csv, Ok(sql_response)) => {
+                        reply::with_header(sql_response.to_csv(), CONTENT_TYPE, "text/csv")
+                            .into_response()
+                    }
+                    (_, result) => {
+                        into_rest_api_response(result, BodyFormat::default()).into_response()
+                    }
+                }
+            },
+        )
+}
+
 /// This struct represents the search stream query passed to
 /// the REST API.
 #[derive(Deserialize, Debug, Eq, PartialEq, utoipa::IntoParams)]
@@ -0,0 +1,513 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::OsStr;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use lru::LruCache;
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::cache::{StorageCache, StorageWithCache};
+use crate::metrics::CacheMetrics;
+use crate::{OwnedBytes, Storage};
+
+const SLICE_FILE_EXTENSION: &str = "slice";
+const TEMP_FILE_EXTENSION: &str = "temp";
+
+/// Path used to cache the footer of a split, hotcache included.
+pub const SPLIT_FOOTER_CACHE_PATH: &str = "footer";
+
+/// Suffix of the files of a split cached by [`DiskSliceCache::wrap_split_storage`].
+const FAST_FIELD_FILE_SUFFIX: &str = ".fast";
+
+/// On disk, size-bounded cache of slices of split files for searchers.
+///
+/// Slices are indexed by split ID, file path, and byte range. Each slice is stored in its own file,
+/// whose name encodes its key, so that the cache can be reloaded when the searcher restarts.
+///
+/// The least recently used slices are evicted first. Recency is reset to the last modification
+/// time of the slice files upon restart.
+pub struct DiskSliceCache {
+    root_path: PathBuf,
+    inner: Mutex<InnerDiskSliceCache>,
+    cache_metrics: &'static CacheMetrics,
+}
+
+struct InnerDiskSliceCache {
+    // Slice file name -> slice num bytes.
+    lru_cache: LruCache<String, u64>,
+    num_bytes: u64,
+    capacity_in_bytes: u64,
+}
+
+impl InnerDiskSliceCache {
+    fn record_item(&mut self, file_name: String, num_bytes: u64, cache_metrics: &CacheMetrics) {
+        if let Some(previous_num_bytes) = self.lru_cache.put(file_name, num_bytes) {
+            self.num_bytes -= previous_num_bytes;
+            cache_metrics.in_cache_count.dec();
+            cache_metrics
+                .in_cache_num_bytes
+                .sub(previous_num_bytes as i64);
+        }
+        self.num_bytes += num_bytes;
+        cache_metrics.in_cache_count.inc();
+        cache_metrics.in_cache_num_bytes.add(num_bytes as i64);
+    }
+
+    fn forget_item(&mut self, file_name: &str, cache_metrics: &CacheMetrics) {
+        if let Some(num_bytes) = self.lru_cache.pop(file_name) {
+            self.num_bytes -= num_bytes;
+            cache_metrics.in_cache_count.dec();
+            cache_metrics.in_cache_num_bytes.sub(num_bytes as i64);
+        }
+    }
+
+    /// Evicts the least recently used items until the cache fits in its capacity, and returns the
+    /// names of the evicted files.
+    fn evict_items_if_necessary(&mut self, cache_metrics: &CacheMetrics) -> Vec<String> {
+        let mut evicted_file_names = Vec::new();
+
+        while self.num_bytes > self.capacity_in_bytes {
+            let Some((file_name, num_bytes)) = self.lru_cache.pop_lru() else {
+                break;
+            };
+            self.num_bytes -= num_bytes;
+            cache_metrics.in_cache_count.dec();
+            cache_metrics.in_cache_num_bytes.sub(num_bytes as i64);
+            cache_metrics.evict_num_items.inc();
+            cache_metrics.evict_num_bytes.inc_by(num_bytes);
+            evicted_file_names.push(file_name);
+        }
+        evicted_file_names
+    }
+}
+
+impl DiskSliceCache {
+    /// Opens the cache stored in `root_path`, reloading the slices cached before a restart.
+    pub fn open(
+        root_path: PathBuf,
+        capacity_in_bytes: u64,
+        cache_metrics: &'static CacheMetrics,
+    ) -> io::Result<Arc<DiskSliceCache>> {
+        std::fs::create_dir_all(&root_path)?;
+        let mut existing_slices: Vec<(SystemTime, String, u64)> = Vec::new();
+
+        for dir_entry_res in std::fs::read_dir(&root_path)? {
+            let dir_entry = dir_entry_res?;
+            let path = dir_entry.path();
+            let metadata = dir_entry.metadata()?;
+
+            if metadata.is_dir() {
+                continue;
+            }
+            let extension = path.extension().and_then(OsStr::to_str).unwrap_or("");
+
+            match extension {
+                TEMP_FILE_EXTENSION => {
+                    // This slice was being written when Quickwit was stopped.
+                    remove_file_if_exists(&path);
+                }
+                SLICE_FILE_EXTENSION => {
+                    let Some(file_name) = path.file_name().and_then(OsStr::to_str) else {
+                        warn!(path=%path.display(), "slice file with invalid name in disk slice cache directory, ignoring");
+                        continue;
+                    };
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    existing_slices.push((modified, file_name.to_string(), metadata.len()));
+                }
+                _ => {
+                    warn!(path=%path.display(), "unknown file in disk slice cache directory, ignoring");
+                }
+            }
+        }
+        // The least recently written slices are inserted first so that they are evicted first.
+        existing_slices.sort();
+
+        let mut inner = InnerDiskSliceCache {
+            lru_cache: LruCache::unbounded(),
+            num_bytes: 0,
+            capacity_in_bytes,
+        };
+        let num_existing_slices = existing_slices.len();
+
+        for (_, file_name, num_bytes) in existing_slices {
+            inner.record_item(file_name, num_bytes, cache_metrics);
+        }
+        // In case of a setting change, it could be useful to evict some slices on startup.
+        let evicted_file_names = inner.evict_items_if_necessary(cache_metrics);
+
+        for file_name in &evicted_file_names {
+            remove_file_if_exists(&root_path.join(file_name));
+        }
+        info!(
+            num_slices = num_existing_slices - evicted_file_names.len(),
+            num_bytes = inner.num_bytes,
+            "loaded disk slice cache"
+        );
+        Ok(Arc::new(DiskSliceCache {
+            root_path,
+            inner: Mutex::new(inner),
+            cache_metrics,
+        }))
+    }
+
+    /// Returns a cached slice of a split file.
+    pub async fn get(
+        &self,
+        split_id: &str,
+        path: &Path,
+        byte_range: Range<usize>,
+    ) -> Option<OwnedBytes> {
+        let file_name = slice_file_name(split_id, path, &byte_range)?;
+        let is_cached = self
+            .inner
+            .lock()
+            .expect("Mutex poisoned")
+            .lru_cache
+            .get(&file_name)
+            .is_some();
+
+        if !is_cached {
+            self.cache_metrics.misses_num_items.inc();
+            return None;
+        }
+        match tokio::fs::read(self.root_path.join(&file_name)).await {
+            Ok(slice_bytes) => {
+                self.cache_metrics.hits_num_items.inc();
+                self.cache_metrics
+                    .hits_num_bytes
+                    .inc_by(slice_bytes.len() as u64);
+                Some(OwnedBytes::new(slice_bytes))
+            }
+            Err(io_error) => {
+                if io_error.kind() != io::ErrorKind::NotFound {
+                    error!(error=?io_error, %file_name, "failed to read slice from disk slice cache");
+                }
+                self.inner
+                    .lock()
+                    .expect("Mutex poisoned")
+                    .forget_item(&file_name, self.cache_metrics);
+                self.cache_metrics.misses_num_items.inc();
+                None
+            }
+        }
+    }
+
+    /// Stores a slice of a split file, evicting the least recently used slices if necessary.
+    pub async fn put(
+        &self,
+        split_id: &str,
+        path: &Path,
+        byte_range: Range<usize>,
+        bytes: OwnedBytes,
+    ) {
+        let Some(file_name) = slice_file_name(split_id, path, &byte_range) else {
+            return;
+        };
+        let num_bytes = bytes.len() as u64;
+
+        if num_bytes > self.inner.lock().expect("Mutex poisoned").capacity_in_bytes {
+            return;
+        }
+        // The slice is first written to a temporary file and then renamed, so that a partially
+        // written slice is never served, even after a crash.
+        let temp_path = self
+            .root_path
+            .join(format!("{file_name}.{}.{TEMP_FILE_EXTENSION}", Ulid::new()));
+        let slice_path = self.root_path.join(&file_name);
+
+        let write_result = async {
+            tokio::fs::write(&temp_path, bytes.as_slice()).await?;
+            tokio::fs::rename(&temp_path, &slice_path).await
+        }
+        .await;
+
+        if let Err(io_error) = write_result {
+            error!(error=?io_error, %file_name, "failed to write slice to disk slice cache");
+            remove_file_if_exists(&temp_path);
+            return;
+        }
+        let evicted_file_names = {
+            let mut inner_guard = self.inner.lock().expect("Mutex poisoned");
+            inner_guard.record_item(file_name, num_bytes, self.cache_metrics);
+            inner_guard.evict_items_if_necessary(self.cache_metrics)
+        };
+        for evicted_file_name in evicted_file_names {
+            if let Err(io_error) =
+                tokio::fs::remove_file(self.root_path.join(&evicted_file_name)).await
+            {
+                if io_error.kind() != io::ErrorKind::NotFound {
+                    error!(error=?io_error, file_name=%evicted_file_name, "failed to remove slice from disk slice cache");
+                }
+            }
+        }
+    }
+
+    /// Wraps the storage of a split bundle with the cache. Only fast field slices are cached.
+    pub fn wrap_split_storage(
+        self: &Arc<Self>,
+        split_id: String,
+        storage: Arc<dyn Storage>,
+    ) -> Arc<dyn Storage> {
+        let split_slice_cache = SplitSliceCache {
+            disk_slice_cache: self.clone(),
+            split_id,
+        };
+        Arc::new(StorageWithCache {
+            storage,
+            cache: Arc::new(split_slice_cache),
+        })
+    }
+}
+
+/// Returns the name of the file storing a slice, or `None` if the slice cannot be cached.
+///
+/// Split IDs and split file names are made of ASCII alphanumeric characters, dots, dashes, and
+/// underscores. Other keys are not cached, since they could map to the same file name.
+fn slice_file_name(split_id: &str, path: &Path, byte_range: &Range<usize>) -> Option<String> {
+    let path_str = path.to_str()?;
+    let is_valid_component = |component: &str| {
+        !component.is_empty()
+            && component
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || "._-".contains(character))
+    };
+    if !is_valid_component(split_id) || split_id.contains('.') || !is_valid_component(path_str) {
+        return None;
+    }
+    Some(format!(
+        "{split_id}.{path_str}.{}-{}.{SLICE_FILE_EXTENSION}",
+        byte_range.start, byte_range.end
+    ))
+}
+
+fn remove_file_if_exists(path: &Path) {
+    if let Err(io_error) = std::fs::remove_file(path) {
+        if io_error.kind() != io::ErrorKind::NotFound {
+            error!(path=%path.display(), error=?io_error, "failed to remove file from disk slice cache");
+        }
+    }
+}
+
+/// Adapter exposing the slices of the fast fields of a split as a [`StorageCache`].
+struct SplitSliceCache {
+    disk_slice_cache: Arc<DiskSliceCache>,
+    split_id: String,
+}
+
+impl SplitSliceCache {
+    fn is_cached(path: &Path) -> bool {
+        path.to_string_lossy().ends_with(FAST_FIELD_FILE_SUFFIX)
+    }
+}
+
+#[async_trait]
+impl StorageCache for SplitSliceCache {
+    async fn get(&self, path: &Path, byte_range: Range<usize>) -> Option<OwnedBytes> {
+        if !Self::is_cached(path) {
+            return None;
+        }
+        self.disk_slice_cache
+            .get(&self.split_id, path, byte_range)
+            .await
+    }
+
+    async fn get_all(&self, _path: &Path) -> Option<OwnedBytes> {
+        None
+    }
+
+    async fn put(&self, path: PathBuf, byte_range: Range<usize>, bytes: OwnedBytes) {
+        if Self::is_cached(&path) {
+            self.disk_slice_cache
+                .put(&self.split_id, &path, byte_range, bytes)
+                .await;
+        }
+    }
+
+    async fn put_all(&self, _path: PathBuf, _bytes: OwnedBytes) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::RamStorage;
+
+    static TEST_CACHE_METRICS: Lazy<CacheMetrics> =
+        Lazy::new(|| CacheMetrics::for_component("disk_slice_test"));
+
+    const SPLIT_ID: &str = "01HQZ3X4Y6Z7A8B9C0D1E2F3G4";
+
+    fn list_slice_files(root_path: &Path) -> Vec<String> {
+        let mut file_names: Vec<String> = std::fs::read_dir(root_path)
+            .unwrap()
+            .map(|dir_entry| dir_entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        file_names.sort();
+        file_names
+    }
+
+    #[test]
+    fn test_slice_file_name() {
+        assert_eq!(
+            slice_file_name(SPLIT_ID, Path::new("segment.fast"), &(10..20)).unwrap(),
+            format!("{SPLIT_ID}.segment.fast.10-20.slice")
+        );
+        assert!(slice_file_name(SPLIT_ID, Path::new("dir/segment.fast"), &(10..20)).is_none());
+        assert!(slice_file_name(SPLIT_ID, Path::new("../segment.fast"), &(10..20)).is_none());
+        assert!(slice_file_name("split.id", Path::new("segment.fast"), &(10..20)).is_none());
+        assert!(slice_file_name("", Path::new("segment.fast"), &(10..20)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disk_slice_cache_get_put() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let disk_slice_cache =
+            DiskSliceCache::open(temp_dir.path().to_path_buf(), 100, &TEST_CACHE_METRICS).unwrap();
+        let path = Path::new("segment.fast");
+
+        assert!(disk_slice_cache.get(SPLIT_ID, path, 0..10).await.is_none());
+        disk_slice_cache
+            .put(SPLIT_ID, path, 0..10, OwnedBytes::new(vec![1u8; 10]))
+            .await;
+        assert_eq!(
+            disk_slice_cache
+                .get(SPLIT_ID, path, 0..10)
+                .await
+                .unwrap()
+                .as_slice(),
+            &[1u8; 10]
+        );
+        assert!(disk_slice_cache.get(SPLIT_ID, path, 0..5).await.is_none());
+        assert!(disk_slice_cache
+            .get("01HQZ3X4Y6Z7A8B9C0D1E2F3G5", path, 0..10)
+            .await
+            .is_none());
+
+        // Slices larger than the capacity are not cached.
+        disk_slice_cache
+            .put(SPLIT_ID, path, 0..101, OwnedBytes::new(vec![1u8; 101]))
+            .await;
+        assert!(disk_slice_cache.get(SPLIT_ID, path, 0..101).await.is_none());
+        assert_eq!(list_slice_files(temp_dir.path()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_disk_slice_cache_eviction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let disk_slice_cache =
+            DiskSliceCache::open(temp_dir.path().to_path_buf(), 100, &TEST_CACHE_METRICS).unwrap();
+        let path = Path::new("segment.fast");
+
+        for start in [0, 40, 80] {
+            disk_slice_cache
+                .put(
+                    SPLIT_ID,
+                    path,
+                    start..start + 40,
+                    OwnedBytes::new(vec![0u8; 40]),
+                )
+                .await;
+            if start == 40 {
+                // Makes `0..40` the most recently used slice.
+                assert!(disk_slice_cache.get(SPLIT_ID, path, 0..40).await.is_some());
+            }
+        }
+        assert!(disk_slice_cache.get(SPLIT_ID, path, 0..40).await.is_some());
+        assert!(disk_slice_cache.get(SPLIT_ID, path, 40..80).await.is_none());
+        assert!(disk_slice_cache
+            .get(SPLIT_ID, path, 80..120)
+            .await
+            .is_some());
+        assert_eq!(
+            list_slice_files(temp_dir.path()),
+            [
+                format!("{SPLIT_ID}.segment.fast.0-40.slice"),
+                format!("{SPLIT_ID}.segment.fast.80-120.slice"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disk_slice_cache_reopen() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = Path::new("segment.fast");
+        {
+            let disk_slice_cache =
+                DiskSliceCache::open(temp_dir.path().to_path_buf(), 100, &TEST_CACHE_METRICS)
+                    .unwrap();
+            disk_slice_cache
+                .put(SPLIT_ID, path, 0..40, OwnedBytes::new(vec![2u8; 40]))
+                .await;
+            disk_slice_cache
+                .put(SPLIT_ID, path, 40..80, OwnedBytes::new(vec![3u8; 40]))
+                .await;
+        }
+        std::fs::write(temp_dir.path().join("slice.temp"), b"partial").unwrap();
+
+        let disk_slice_cache =
+            DiskSliceCache::open(temp_dir.path().to_path_buf(), 100, &TEST_CACHE_METRICS).unwrap();
+        assert_eq!(
+            disk_slice_cache
+                .get(SPLIT_ID, path, 40..80)
+                .await
+                .unwrap()
+                .as_slice(),
+            &[3u8; 40]
+        );
+        assert_eq!(list_slice_files(temp_dir.path()).len(), 2);
+
+        // Reopening with a lower capacity evicts slices.
+        drop(disk_slice_cache);
+        let disk_slice_cache =
+            DiskSliceCache::open(temp_dir.path().to_path_buf(), 50, &TEST_CACHE_METRICS).unwrap();
+        assert_eq!(list_slice_files(temp_dir.path()).len(), 1);
+        assert_eq!(disk_slice_cache.inner.lock().unwrap().num_bytes, 40);
+    }
+
+    #[tokio::test]
+    async fn test_disk_slice_cache_wrap_split_storage() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let disk_slice_cache =
+            DiskSliceCache::open(temp_dir.path().to_path_buf(), 100, &TEST_CACHE_METRICS).unwrap();
+        let ram_storage = RamStorage::builder()
+            .put("segment.fast", b"fast field data")
+            .put("segment.idx", b"posting list data")
+            .build();
+        let storage =
+            disk_slice_cache.wrap_split_storage(SPLIT_ID.to_string(), Arc::new(ram_storage));
+
+        let fast_field_slice = storage
+            .get_slice(Path::new("segment.fast"), 0..4)
+            .await
+            .unwrap();
+        assert_eq!(fast_field_slice.as_slice(), b"fast");
+        storage
+            .get_slice(Path::new("segment.idx"), 0..7)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            list_slice_files(temp_dir.path()),
+            [format!("{SPLIT_ID}.segment.fast.0-4.slice")]
+        );
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 mod byte_range_cache;
+mod disk_slice_cache;
 mod memory_sized_cache;
 mod quickwit_cache;
 mod slice_address;
@@ -28,6 +29,7 @@ pub use quickwit_cache::QuickwitCache;
 pub use storage_with_cache::StorageWithCache;
 
 pub use self::byte_range_cache::ByteRangeCache;
+pub use self::disk_slice_cache::{DiskSliceCache, SPLIT_FOOTER_CACHE_PATH};
 pub use self::memory_sized_cache::MemorySizedCache;
 use crate::{OwnedBytes, Storage};
 
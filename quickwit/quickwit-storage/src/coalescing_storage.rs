@@ -0,0 +1,484 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, mem};
+
+use async_trait::async_trait;
+use quickwit_common::uri::Uri;
+use tokio::io::AsyncRead;
+use tokio::sync::oneshot;
+use tracing::Instrument;
+
+use crate::storage::SendableAsync;
+use crate::{
+    BulkDeleteError, OwnedBytes, PutPayload, Storage, StorageErrorKind, StorageResult,
+    STORAGE_METRICS,
+};
+
+/// The read planner merges the byte ranges of a file that are adjacent, overlapping, or separated
+/// by at most `max_gap` bytes into a single range request, as long as the merged range does not
+/// exceed `max_range_num_bytes`.
+///
+/// On object storages, the latency of a request dominates its cost: fetching and discarding a few
+/// extra bytes is cheaper than issuing another request. Past a certain size, however, a single
+/// request is slower than several concurrent ones.
+#[derive(Clone, Copy, Debug)]
+pub struct ReadPlanner {
+    max_gap: usize,
+    max_range_num_bytes: usize,
+}
+
+/// A range request covering one or several of the byte ranges passed to [`ReadPlanner::plan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoalescedRead {
+    /// The byte range to fetch.
+    pub byte_range: Range<usize>,
+    /// The indexes of the planned byte ranges covered by this request.
+    pub read_ids: Vec<usize>,
+    /// The number of bytes fetched between the planned byte ranges, and discarded.
+    pub num_gap_bytes: usize,
+}
+
+impl ReadPlanner {
+    /// Creates a read planner merging byte ranges separated by at most `max_gap` bytes into
+    /// range requests of at most `max_range_num_bytes` bytes. A byte range larger than
+    /// `max_range_num_bytes` is fetched on its own.
+    pub fn new(max_gap: usize, max_range_num_bytes: usize) -> ReadPlanner {
+        ReadPlanner {
+            max_gap,
+            max_range_num_bytes,
+        }
+    }
+
+    /// Returns the range requests to issue to fetch all of the `byte_ranges`, sorted by offset.
+    pub fn plan(&self, byte_ranges: &[Range<usize>]) -> Vec<CoalescedRead> {
+        let mut read_ids: Vec<usize> = (0..byte_ranges.len()).collect();
+        read_ids.sort_by_key(|read_id| byte_ranges[*read_id].start);
+
+        let mut coalesced_reads: Vec<CoalescedRead> = Vec::new();
+
+        for read_id in read_ids {
+            let byte_range = &byte_ranges[read_id];
+
+            if let Some(last_coalesced_read) = coalesced_reads.last_mut() {
+                let last_start = last_coalesced_read.byte_range.start;
+                let last_end = last_coalesced_read.byte_range.end;
+                let merged_end = last_end.max(byte_range.end);
+
+                if byte_range.start <= last_end.saturating_add(self.max_gap)
+                    && merged_end - last_start <= self.max_range_num_bytes
+                {
+                    last_coalesced_read.byte_range.end = merged_end;
+                    last_coalesced_read.read_ids.push(read_id);
+                    last_coalesced_read.num_gap_bytes += byte_range.start.saturating_sub(last_end);
+                    continue;
+                }
+            }
+            coalesced_reads.push(CoalescedRead {
+                byte_range: byte_range.clone(),
+                read_ids: vec![read_id],
+                num_gap_bytes: 0,
+            });
+        }
+        coalesced_reads
+    }
+}
+
+struct PendingRead {
+    byte_range: Range<usize>,
+    result_tx: oneshot::Sender<StorageResult<OwnedBytes>>,
+}
+
+/// The coalescing storage wraps another storage and coalesces the slice reads issued
+/// concurrently, typically during the warmup phase of a search, into fewer range requests.
+///
+/// It is meant to sit right above the split storage, below the caches, so that only the reads
+/// missing from the caches are coalesced.
+///
+/// The first read of a batch yields once to the executor, which lets the other reads polled by
+/// the same task join the batch. The batch is then planned by the [`ReadPlanner`] and the
+/// resulting range requests are issued concurrently to the underlying storage.
+///
+/// Use with care, `CoalescingStorage` is read-only.
+#[derive(Clone)]
+pub struct CoalescingStorage {
+    underlying: Arc<dyn Storage>,
+    read_planner: ReadPlanner,
+    pending_reads: Arc<Mutex<HashMap<PathBuf, Vec<PendingRead>>>>,
+}
+
+impl CoalescingStorage {
+    /// Creates a new CoalescingStorage.
+    pub fn new(underlying: Arc<dyn Storage>, read_planner: ReadPlanner) -> CoalescingStorage {
+        CoalescingStorage {
+            underlying,
+            read_planner,
+            pending_reads: Arc::default(),
+        }
+    }
+
+    /// Adds a read to the current batch. Returns true if the read is the first one of the batch.
+    fn enqueue_read(&self, path: &Path, pending_read: PendingRead) -> bool {
+        let mut pending_reads_guard = self.pending_reads.lock().expect("Mutex poisoned");
+        let is_first_read = pending_reads_guard.is_empty();
+
+        pending_reads_guard
+            .entry(path.to_path_buf())
+            .or_default()
+            .push(pending_read);
+        is_first_read
+    }
+
+    /// Plans the reads of the current batch and spawns the resulting range requests.
+    fn flush(&self) {
+        let pending_reads = mem::take(&mut *self.pending_reads.lock().expect("Mutex poisoned"));
+
+        for (path, reads) in pending_reads {
+            let byte_ranges: Vec<Range<usize>> = reads
+                .iter()
+                .map(|pending_read| pending_read.byte_range.clone())
+                .collect();
+            let coalesced_reads = self.read_planner.plan(&byte_ranges);
+
+            STORAGE_METRICS
+                .coalesced_reads_total
+                .inc_by(coalesced_reads.len() as u64);
+            STORAGE_METRICS
+                .coalesced_reads_saved_total
+                .inc_by((reads.len() - coalesced_reads.len()) as u64);
+
+            let mut reads: Vec<Option<PendingRead>> = reads.into_iter().map(Some).collect();
+
+            for coalesced_read in coalesced_reads {
+                STORAGE_METRICS
+                    .coalesced_reads_gap_num_bytes
+                    .inc_by(coalesced_read.num_gap_bytes as u64);
+
+                let covered_reads: Vec<PendingRead> = coalesced_read
+                    .read_ids
+                    .iter()
+                    .flat_map(|read_id| reads[*read_id].take())
+                    .collect();
+                let underlying = self.underlying.clone();
+                let path = path.clone();
+
+                tokio::spawn(
+                    async move {
+                        let read_result = underlying
+                            .get_slice(&path, coalesced_read.byte_range.clone())
+                            .await;
+                        dispatch_read_result(
+                            coalesced_read.byte_range.start,
+                            read_result,
+                            covered_reads,
+                        );
+                    }
+                    .in_current_span(),
+                );
+            }
+        }
+    }
+}
+
+/// Sends to each covered read its slice of the coalesced read.
+fn dispatch_read_result(
+    coalesced_read_start: usize,
+    read_result: StorageResult<OwnedBytes>,
+    covered_reads: Vec<PendingRead>,
+) {
+    match read_result {
+        Ok(coalesced_bytes) => {
+            for pending_read in covered_reads {
+                let start = pending_read.byte_range.start - coalesced_read_start;
+                let end = pending_read.byte_range.end - coalesced_read_start;
+
+                let slice_result = if end <= coalesced_bytes.len() {
+                    Ok(coalesced_bytes.slice(start..end))
+                } else {
+                    Err(StorageErrorKind::Io.with_error(anyhow::anyhow!(
+                        "coalesced read returned {} bytes, expected at least {end}",
+                        coalesced_bytes.len()
+                    )))
+                };
+                let _ = pending_read.result_tx.send(slice_result);
+            }
+        }
+        Err(error) => {
+            for pending_read in covered_reads {
+                let _ = pending_read.result_tx.send(Err(error.clone()));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CoalescingStorage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CoalescingStorage({:?})", self.underlying)
+    }
+}
+
+/// Flushes the batch of pending reads when dropped, so that the batch is flushed even if the
+/// first read is cancelled while yielding.
+struct FlushGuard<'a>(&'a CoalescingStorage);
+
+impl Drop for FlushGuard<'_> {
+    fn drop(&mut self) {
+        self.0.flush();
+    }
+}
+
+#[async_trait]
+impl Storage for CoalescingStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    async fn put(&self, path: &Path, _payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        unimplemented!("CoalescingStorage is readonly. Failed to put {:?}", path)
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        self.underlying.copy_to(path, output).await
+    }
+
+    async fn get_slice(&self, path: &Path, byte_range: Range<usize>) -> StorageResult<OwnedBytes> {
+        if byte_range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let (result_tx, result_rx) = oneshot::channel();
+        let pending_read = PendingRead {
+            byte_range,
+            result_tx,
+        };
+        let is_first_read = self.enqueue_read(path, pending_read);
+
+        if is_first_read {
+            let _flush_guard = FlushGuard(self);
+            tokio::task::yield_now().await;
+        }
+        result_rx.await.map_err(|_| {
+            StorageErrorKind::Internal.with_error(anyhow::anyhow!(
+                "coalesced read of `{}` was dropped",
+                path.display()
+            ))
+        })?
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        byte_range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        self.underlying.get_slice_stream(path, byte_range).await
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        self.underlying.get_all(path).await
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        unimplemented!("Failed to delete file `{path:?}`. `CoalescingStorage` is read-only.")
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        unimplemented!("Failed to delete files `{paths:?}`. `CoalescingStorage` is read-only.")
+    }
+
+    async fn exists(&self, path: &Path) -> StorageResult<bool> {
+        self.underlying.exists(path).await
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        self.underlying.file_num_bytes(path).await
+    }
+
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockStorage, MockStorageCache};
+
+    #[test]
+    fn test_read_planner() {
+        let read_planner = ReadPlanner::new(4, usize::MAX);
+        assert!(read_planner.plan(&[]).is_empty());
+
+        let coalesced_reads = read_planner.plan(&[50..60, 0..10, 12..20, 8..15, 64..70, 75..80]);
+        assert_eq!(
+            coalesced_reads,
+            [
+                CoalescedRead {
+                    byte_range: 0..20,
+                    read_ids: vec![1, 3, 2],
+                    num_gap_bytes: 0,
+                },
+                CoalescedRead {
+                    byte_range: 50..70,
+                    read_ids: vec![0, 4],
+                    num_gap_bytes: 4,
+                },
+                CoalescedRead {
+                    byte_range: 75..80,
+                    read_ids: vec![5],
+                    num_gap_bytes: 0,
+                },
+            ]
+        );
+        let coalesced_reads = ReadPlanner::new(0, usize::MAX).plan(&[0..10, 10..20, 21..30]);
+        assert_eq!(coalesced_reads.len(), 2);
+        assert_eq!(coalesced_reads[0].byte_range, 0..20);
+        assert_eq!(coalesced_reads[1].byte_range, 21..30);
+    }
+
+    #[test]
+    fn test_read_planner_max_range_num_bytes() {
+        let read_planner = ReadPlanner::new(4, 20);
+        let byte_ranges = vec![0..10, 10..20, 20..30, 30..70, 70..75, 75..80];
+        let planned_byte_ranges: Vec<Range<usize>> = read_planner
+            .plan(&byte_ranges)
+            .into_iter()
+            .map(|coalesced_read| coalesced_read.byte_range)
+            .collect();
+        // The range `30..70` exceeds the maximum size: it is fetched on its own.
+        assert_eq!(planned_byte_ranges, [0..20, 20..30, 30..70, 70..80]);
+    }
+
+    type RecordedReads = Arc<Mutex<Vec<(PathBuf, Range<usize>)>>>;
+
+    fn make_test_storage() -> (MockStorage, RecordedReads) {
+        let recorded_reads = RecordedReads::default();
+        let recorded_reads_clone = recorded_reads.clone();
+        let payload: Vec<u8> = (0..100u8).collect();
+        let mut mock_storage = MockStorage::default();
+        mock_storage
+            .expect_get_slice()
+            .returning(move |path, byte_range| {
+                recorded_reads_clone
+                    .lock()
+                    .unwrap()
+                    .push((path.to_path_buf(), byte_range.clone()));
+                Ok(OwnedBytes::new(payload[byte_range].to_vec()))
+            });
+        (mock_storage, recorded_reads)
+    }
+
+    fn drain_sorted(recorded_reads: &RecordedReads) -> Vec<(PathBuf, Range<usize>)> {
+        let mut reads = mem::take(&mut *recorded_reads.lock().unwrap());
+        reads.sort_by_key(|(path, byte_range)| (path.clone(), byte_range.start));
+        reads
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_storage_concurrent_reads() {
+        let (mock_storage, recorded_reads) = make_test_storage();
+        let coalescing_storage =
+            CoalescingStorage::new(Arc::new(mock_storage), ReadPlanner::new(4, usize::MAX));
+        let file_1 = Path::new("file-1");
+        let file_2 = Path::new("file-2");
+
+        let (bytes_0, bytes_1, bytes_2, bytes_3, bytes_4) = tokio::join!(
+            coalescing_storage.get_slice(file_1, 0..10),
+            coalescing_storage.get_slice(file_1, 12..20),
+            coalescing_storage.get_slice(file_1, 50..60),
+            coalescing_storage.get_slice(file_1, 8..15),
+            coalescing_storage.get_slice(file_2, 30..40),
+        );
+        assert_eq!(bytes_0.unwrap().as_slice(), &(0..10).collect::<Vec<u8>>());
+        assert_eq!(bytes_1.unwrap().as_slice(), &(12..20).collect::<Vec<u8>>());
+        assert_eq!(bytes_2.unwrap().as_slice(), &(50..60).collect::<Vec<u8>>());
+        assert_eq!(bytes_3.unwrap().as_slice(), &(8..15).collect::<Vec<u8>>());
+        assert_eq!(bytes_4.unwrap().as_slice(), &(30..40).collect::<Vec<u8>>());
+
+        assert_eq!(
+            drain_sorted(&recorded_reads),
+            [
+                (PathBuf::from("file-1"), 0..20),
+                (PathBuf::from("file-1"), 50..60),
+                (PathBuf::from("file-2"), 30..40),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_storage_sequential_reads() {
+        let (mock_storage, recorded_reads) = make_test_storage();
+        let coalescing_storage =
+            CoalescingStorage::new(Arc::new(mock_storage), ReadPlanner::new(4, usize::MAX));
+        let file_1 = Path::new("file-1");
+
+        let bytes_0 = coalescing_storage.get_slice(file_1, 0..10).await.unwrap();
+        assert_eq!(bytes_0.as_slice(), &(0..10).collect::<Vec<u8>>());
+        let bytes_1 = coalescing_storage.get_slice(file_1, 12..20).await.unwrap();
+        assert_eq!(bytes_1.as_slice(), &(12..20).collect::<Vec<u8>>());
+        let empty_bytes = coalescing_storage.get_slice(file_1, 5..5).await.unwrap();
+        assert!(empty_bytes.is_empty());
+
+        assert_eq!(
+            drain_sorted(&recorded_reads),
+            [
+                (PathBuf::from("file-1"), 0..10),
+                (PathBuf::from("file-1"), 12..20),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_storage_below_cache_skips_cached_ranges() {
+        let (mock_storage, recorded_reads) = make_test_storage();
+        let coalescing_storage: Arc<dyn Storage> = Arc::new(CoalescingStorage::new(
+            Arc::new(mock_storage),
+            ReadPlanner::new(4, usize::MAX),
+        ));
+        let mut mock_cache = MockStorageCache::default();
+        mock_cache
+            .expect_get()
+            .returning(|_path, byte_range: Range<usize>| {
+                // The range `12..20` is already cached.
+                (byte_range == (12..20)).then(|| OwnedBytes::new((12..20).collect::<Vec<u8>>()))
+            });
+        mock_cache
+            .expect_put()
+            .returning(|_path, _byte_range, _bytes| {});
+        let storage_with_cache =
+            crate::wrap_storage_with_cache(Arc::new(mock_cache), coalescing_storage);
+        let file_1 = Path::new("file-1");
+
+        let (bytes_0, bytes_1, bytes_2) = tokio::join!(
+            storage_with_cache.get_slice(file_1, 0..10),
+            storage_with_cache.get_slice(file_1, 12..20),
+            storage_with_cache.get_slice(file_1, 22..30),
+        );
+        assert_eq!(bytes_0.unwrap().as_slice(), &(0..10).collect::<Vec<u8>>());
+        assert_eq!(bytes_1.unwrap().as_slice(), &(12..20).collect::<Vec<u8>>());
+        assert_eq!(bytes_2.unwrap().as_slice(), &(22..30).collect::<Vec<u8>>());
+
+        // The cached range is not fetched again: the gap between the two missing ranges is too
+        // large for them to be coalesced.
+        assert_eq!(
+            drain_sorted(&recorded_reads),
+            [
+                (PathBuf::from("file-1"), 0..10),
+                (PathBuf::from("file-1"), 22..30),
+            ]
+        );
+    }
+}
@@ -0,0 +1,897 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encrypted files are made of a header followed by the file content, split into chunks of
+//! `chunk_size` bytes encrypted independently with AES-256-GCM so that byte ranges can be read
+//! without fetching and decrypting the whole file.
+//!
+//! ```text
+//! [magic: 5 bytes][format version: u8][header len: u32]
+//! [chunk size: u32][plaintext len: u64][key ID len: u8][key ID][wrapped key len: u16][wrapped key]
+//! [chunk 0 ciphertext][chunk 0 tag]...[chunk N ciphertext][chunk N tag]
+//! ```
+//!
+//! Every file is encrypted with its own data key, so the nonce of a chunk is simply its index.
+//! The header is authenticated as the associated data of every chunk. All integers are
+//! little-endian.
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::{fmt, io};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use quickwit_common::uri::Uri;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+use super::{EncryptionKeyring, DATA_KEY_LEN};
+use crate::storage::SendableAsync;
+use crate::{
+    BulkDeleteError, OwnedBytes, PutPayload, Storage, StorageError, StorageErrorKind, StorageResult,
+};
+
+const MAGIC: &[u8; 5] = b"QWENC";
+
+const FORMAT_VERSION: u8 = 1;
+
+/// Length of the magic number, format version, and header length.
+const HEADER_PREFIX_LEN: usize = MAGIC.len() + 1 + 4;
+
+/// Number of bytes fetched to read the header of a file.
+const HEADER_PROBE_LEN: usize = 1024;
+
+/// Length of the authentication tag appended to every chunk.
+const TAG_LEN: usize = 16;
+
+/// Size of the plaintext chunks. Reading a byte range requires fetching and decrypting all the
+/// chunks it overlaps.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Layout of a file read through an [`EncryptedStorage`].
+pub(crate) enum FileLayout {
+    Plaintext,
+    Encrypted(EncryptedFile),
+}
+
+/// Header and data key of an encrypted file.
+pub(crate) struct EncryptedFile {
+    header: Vec<u8>,
+    chunk_size: usize,
+    plaintext_len: u64,
+    data_key: LessSafeKey,
+}
+
+impl EncryptedFile {
+    fn new(
+        chunk_size: usize,
+        plaintext_len: u64,
+        key_id: &str,
+        plaintext_key: &[u8; DATA_KEY_LEN],
+        wrapped_key: &[u8],
+    ) -> StorageResult<Self> {
+        let header_len = HEADER_PREFIX_LEN + 4 + 8 + 1 + key_id.len() + 2 + wrapped_key.len();
+        let key_id_len = u8::try_from(key_id.len()).map_err(|_| {
+            StorageErrorKind::Internal.with_error(anyhow!("key ID `{key_id}` is too long"))
+        })?;
+        let wrapped_key_len = u16::try_from(wrapped_key.len()).map_err(|_| {
+            StorageErrorKind::Internal.with_error(anyhow!("wrapped data key is too long"))
+        })?;
+        let mut header = Vec::with_capacity(header_len);
+        header.extend_from_slice(MAGIC);
+        header.push(FORMAT_VERSION);
+        header.extend_from_slice(&(header_len as u32).to_le_bytes());
+        header.extend_from_slice(&(chunk_size as u32).to_le_bytes());
+        header.extend_from_slice(&plaintext_len.to_le_bytes());
+        header.push(key_id_len);
+        header.extend_from_slice(key_id.as_bytes());
+        header.extend_from_slice(&wrapped_key_len.to_le_bytes());
+        header.extend_from_slice(wrapped_key);
+
+        Ok(Self {
+            header,
+            chunk_size,
+            plaintext_len,
+            data_key: data_key(plaintext_key),
+        })
+    }
+
+    async fn open(keyring: &EncryptionKeyring, header: Vec<u8>) -> StorageResult<Self> {
+        let mut cursor = &header[HEADER_PREFIX_LEN..];
+        let chunk_size = read_u32(&mut cursor)? as usize;
+        let plaintext_len = read_u64(&mut cursor)?;
+        let key_id_len = read_bytes(&mut cursor, 1)?[0] as usize;
+        let key_id = std::str::from_utf8(read_bytes(&mut cursor, key_id_len)?)
+            .map_err(|_| corrupted_header_error())?;
+        let wrapped_key_len = u16::from_le_bytes(
+            read_bytes(&mut cursor, 2)?
+                .try_into()
+                .expect("slice should be 2 bytes long"),
+        ) as usize;
+        let wrapped_key = read_bytes(&mut cursor, wrapped_key_len)?;
+
+        if chunk_size == 0 || !cursor.is_empty() {
+            return Err(corrupted_header_error());
+        }
+        let plaintext_key = keyring
+            .key_provider
+            .decrypt_data_key(key_id, wrapped_key)
+            .await?;
+
+        Ok(Self {
+            chunk_size,
+            plaintext_len,
+            data_key: data_key(&plaintext_key),
+            header,
+        })
+    }
+
+    fn header_len(&self) -> u64 {
+        self.header.len() as u64
+    }
+
+    fn encrypted_chunk_size(&self) -> u64 {
+        (self.chunk_size + TAG_LEN) as u64
+    }
+
+    fn num_chunks(&self) -> u64 {
+        self.plaintext_len.div_ceil(self.chunk_size as u64)
+    }
+
+    fn encrypted_len(&self) -> u64 {
+        self.header_len() + self.plaintext_len + self.num_chunks() * TAG_LEN as u64
+    }
+
+    /// Returns the range of chunks overlapping the given plaintext byte range.
+    fn chunk_range(&self, plaintext_range: &Range<u64>) -> Range<u64> {
+        let chunk_size = self.chunk_size as u64;
+        plaintext_range.start / chunk_size..plaintext_range.end.div_ceil(chunk_size)
+    }
+
+    /// Returns the byte range of the given chunks in the encrypted file.
+    fn encrypted_range(&self, chunk_range: &Range<u64>) -> Range<u64> {
+        let start = self.header_len() + chunk_range.start * self.encrypted_chunk_size();
+        let end = (self.header_len() + chunk_range.end * self.encrypted_chunk_size())
+            .min(self.encrypted_len());
+        start..end
+    }
+
+    fn nonce(chunk_idx: u64) -> Nonce {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        nonce_bytes[NONCE_LEN - 8..].copy_from_slice(&chunk_idx.to_be_bytes());
+        Nonce::assume_unique_for_key(nonce_bytes)
+    }
+
+    /// Encrypts consecutive plaintext chunks, starting with chunk `first_chunk_idx`.
+    fn seal_chunks(&self, first_chunk_idx: u64, plaintext: &[u8]) -> StorageResult<Vec<u8>> {
+        let num_chunks = plaintext.len().div_ceil(self.chunk_size);
+        let mut ciphertext = Vec::with_capacity(plaintext.len() + num_chunks * TAG_LEN);
+
+        for (chunk_ord, plaintext_chunk) in plaintext.chunks(self.chunk_size).enumerate() {
+            let chunk_idx = first_chunk_idx + chunk_ord as u64;
+            let mut chunk = plaintext_chunk.to_vec();
+            self.data_key
+                .seal_in_place_append_tag(
+                    Self::nonce(chunk_idx),
+                    Aad::from(self.header.as_slice()),
+                    &mut chunk,
+                )
+                .map_err(|_| {
+                    StorageErrorKind::Internal.with_error(anyhow!("failed to encrypt chunk"))
+                })?;
+            ciphertext.extend_from_slice(&chunk);
+        }
+        Ok(ciphertext)
+    }
+
+    /// Decrypts consecutive encrypted chunks, starting with chunk `first_chunk_idx`.
+    fn open_chunks(&self, first_chunk_idx: u64, ciphertext: &[u8]) -> StorageResult<Vec<u8>> {
+        let encrypted_chunk_size = self.encrypted_chunk_size() as usize;
+        let num_chunks = ciphertext.len().div_ceil(encrypted_chunk_size);
+        let mut plaintext =
+            Vec::with_capacity(ciphertext.len().saturating_sub(num_chunks * TAG_LEN));
+
+        for (chunk_ord, encrypted_chunk) in ciphertext.chunks(encrypted_chunk_size).enumerate() {
+            let chunk_idx = first_chunk_idx + chunk_ord as u64;
+            plaintext.extend_from_slice(&self.open_chunk(chunk_idx, encrypted_chunk)?);
+        }
+        Ok(plaintext)
+    }
+
+    fn open_chunk(&self, chunk_idx: u64, encrypted_chunk: &[u8]) -> StorageResult<Vec<u8>> {
+        let mut chunk = encrypted_chunk.to_vec();
+        let plaintext_len = self
+            .data_key
+            .open_in_place(
+                Self::nonce(chunk_idx),
+                Aad::from(self.header.as_slice()),
+                &mut chunk,
+            )
+            .map_err(|_| {
+                StorageErrorKind::Internal.with_error(anyhow!(
+                    "failed to decrypt chunk {chunk_idx}: the file is corrupted"
+                ))
+            })?
+            .len();
+        chunk.truncate(plaintext_len);
+        Ok(chunk)
+    }
+}
+
+fn data_key(plaintext_key: &[u8; DATA_KEY_LEN]) -> LessSafeKey {
+    let unbound_key = UnboundKey::new(&AES_256_GCM, plaintext_key)
+        .expect("data key should be a valid AES-256 key");
+    LessSafeKey::new(unbound_key)
+}
+
+fn corrupted_header_error() -> StorageError {
+    StorageErrorKind::Internal.with_error(anyhow!("encryption header is corrupted"))
+}
+
+fn plaintext_file_error(path: &Path) -> StorageError {
+    StorageErrorKind::Internal.with_error(anyhow!(
+        "file `{}` is not encrypted, but encryption is required",
+        path.display()
+    ))
+}
+
+fn read_bytes<'a>(cursor: &mut &'a [u8], num_bytes: usize) -> StorageResult<&'a [u8]> {
+    if cursor.len() < num_bytes {
+        return Err(corrupted_header_error());
+    }
+    let (bytes, rest) = cursor.split_at(num_bytes);
+    *cursor = rest;
+    Ok(bytes)
+}
+
+fn read_u32(cursor: &mut &[u8]) -> StorageResult<u32> {
+    let bytes = read_bytes(cursor, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(cursor: &mut &[u8]) -> StorageResult<u64> {
+    let bytes = read_bytes(cursor, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Returns the length of the header if the given bytes start with an encryption header prefix.
+fn parse_header_prefix(bytes: &[u8]) -> StorageResult<Option<usize>> {
+    if bytes.len() < HEADER_PREFIX_LEN || !bytes.starts_with(MAGIC) {
+        return Ok(None);
+    }
+    let format_version = bytes[MAGIC.len()];
+
+    if format_version != FORMAT_VERSION {
+        return Err(StorageErrorKind::Internal.with_error(anyhow!(
+            "unsupported encryption format version {format_version}"
+        )));
+    }
+    let header_len = u32::from_le_bytes(
+        bytes[MAGIC.len() + 1..HEADER_PREFIX_LEN]
+            .try_into()
+            .expect("slice should be 4 bytes long"),
+    ) as usize;
+
+    if header_len < HEADER_PREFIX_LEN {
+        return Err(corrupted_header_error());
+    }
+    Ok(Some(header_len))
+}
+
+/// Payload encrypting the wrapped payload on the fly, chunk by chunk, so that multipart uploads
+/// only encrypt the chunks of the part being uploaded.
+#[derive(Clone)]
+struct EncryptedPayload {
+    payload: Box<dyn PutPayload>,
+    encrypted_file: Arc<EncryptedFile>,
+}
+
+#[async_trait]
+impl PutPayload for EncryptedPayload {
+    fn len(&self) -> u64 {
+        self.encrypted_file.encrypted_len()
+    }
+
+    async fn range_byte_stream(&self, range: Range<u64>) -> io::Result<ByteStream> {
+        let encrypted_file = &self.encrypted_file;
+        let header_len = encrypted_file.header_len();
+        let mut bytes = Vec::with_capacity((range.end - range.start) as usize);
+
+        if range.start < header_len {
+            let header_range = range.start as usize..range.end.min(header_len) as usize;
+            bytes.extend_from_slice(&encrypted_file.header[header_range]);
+        }
+        if range.end > header_len {
+            let body_range = range.start.max(header_len) - header_len..range.end - header_len;
+            let encrypted_chunk_size = encrypted_file.encrypted_chunk_size();
+            let chunk_range = body_range.start / encrypted_chunk_size
+                ..body_range.end.div_ceil(encrypted_chunk_size);
+            let plaintext_range = chunk_range.start * encrypted_file.chunk_size as u64
+                ..(chunk_range.end * encrypted_file.chunk_size as u64)
+                    .min(encrypted_file.plaintext_len);
+
+            let mut plaintext =
+                Vec::with_capacity((plaintext_range.end - plaintext_range.start) as usize);
+            self.payload
+                .range_byte_stream(plaintext_range)
+                .await?
+                .into_async_read()
+                .read_to_end(&mut plaintext)
+                .await?;
+            let ciphertext = encrypted_file.seal_chunks(chunk_range.start, &plaintext)?;
+            let skip = (body_range.start - chunk_range.start * encrypted_chunk_size) as usize;
+            let take = (body_range.end - body_range.start) as usize;
+            bytes.extend_from_slice(&ciphertext[skip..skip + take]);
+        }
+        Ok(ByteStream::from(bytes))
+    }
+}
+
+/// Storage encrypting the files written through it and decrypting the files read through it.
+/// See [`super::wrap_storage_with_encryption`].
+pub struct EncryptedStorage {
+    storage: Arc<dyn Storage>,
+    keyring: Arc<EncryptionKeyring>,
+    key_id_opt: Option<String>,
+    require_encryption: bool,
+    chunk_size: usize,
+}
+
+impl fmt::Debug for EncryptedStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedStorage")
+            .field("uri", self.storage.uri())
+            .field("key_id", &self.key_id_opt)
+            .field("require_encryption", &self.require_encryption)
+            .finish()
+    }
+}
+
+impl EncryptedStorage {
+    pub(super) fn new(
+        keyring: Arc<EncryptionKeyring>,
+        storage: Arc<dyn Storage>,
+        key_id_opt: Option<String>,
+        require_encryption: bool,
+    ) -> Self {
+        Self {
+            storage,
+            keyring,
+            key_id_opt,
+            require_encryption,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Returns the layout of the file, fetching and parsing its header if it is not cached.
+    async fn file_layout(&self, path: &Path) -> StorageResult<Arc<FileLayout>> {
+        let storage_uri = self.storage.uri();
+
+        if let Some(file_layout) = self.keyring.get_file_layout(storage_uri, path) {
+            self.check_file_layout(path, &file_layout)?;
+            return Ok(file_layout);
+        }
+        // Headers usually fit in the first bytes of the file, so we fetch them in one request.
+        let file_num_bytes = self.storage.file_num_bytes(path).await?;
+        let probe_len = file_num_bytes.min(HEADER_PROBE_LEN as u64) as usize;
+        let probe = self.storage.get_slice(path, 0..probe_len).await?;
+
+        let file_layout = match parse_header_prefix(&probe)? {
+            Some(header_len) => {
+                let header = if header_len <= probe.len() {
+                    probe.slice(0..header_len)
+                } else {
+                    self.storage.get_slice(path, 0..header_len).await?
+                };
+                let encrypted_file = EncryptedFile::open(&self.keyring, header.to_vec()).await?;
+                FileLayout::Encrypted(encrypted_file)
+            }
+            None => FileLayout::Plaintext,
+        };
+        let file_layout = Arc::new(file_layout);
+        self.keyring
+            .put_file_layout(storage_uri, path, file_layout.clone());
+        self.check_file_layout(path, &file_layout)?;
+        Ok(file_layout)
+    }
+
+    /// Rejects the files written in clear if this storage requires encryption.
+    fn check_file_layout(&self, path: &Path, file_layout: &FileLayout) -> StorageResult<()> {
+        if self.require_encryption && matches!(file_layout, FileLayout::Plaintext) {
+            return Err(plaintext_file_error(path));
+        }
+        Ok(())
+    }
+
+    async fn get_slice_encrypted(
+        &self,
+        path: &Path,
+        encrypted_file: &EncryptedFile,
+        range: Range<usize>,
+    ) -> StorageResult<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let plaintext_range = range.start as u64..range.end as u64;
+
+        if plaintext_range.end > encrypted_file.plaintext_len {
+            return Err(StorageErrorKind::Internal.with_error(anyhow!(
+                "range {:?} is out of bounds for file `{}` of {} bytes",
+                range,
+                path.display(),
+                encrypted_file.plaintext_len
+            )));
+        }
+        let chunk_range = encrypted_file.chunk_range(&plaintext_range);
+        let encrypted_range = encrypted_file.encrypted_range(&chunk_range);
+        let ciphertext = self
+            .storage
+            .get_slice(
+                path,
+                encrypted_range.start as usize..encrypted_range.end as usize,
+            )
+            .await?;
+        let plaintext = encrypted_file.open_chunks(chunk_range.start, &ciphertext)?;
+        let offset = range.start - chunk_range.start as usize * encrypted_file.chunk_size;
+        Ok(OwnedBytes::new(plaintext).slice(offset..offset + range.len()))
+    }
+}
+
+#[async_trait]
+impl Storage for EncryptedStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.storage.check_connectivity().await
+    }
+
+    async fn put(&self, path: &Path, payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        self.keyring.evict_file_layout(self.storage.uri(), path);
+
+        let Some(key_id) = &self.key_id_opt else {
+            return self.storage.put(path, payload).await;
+        };
+        let data_key = self.keyring.key_provider.generate_data_key(key_id).await?;
+        let encrypted_file = EncryptedFile::new(
+            self.chunk_size,
+            payload.len(),
+            key_id,
+            &data_key.plaintext_key,
+            &data_key.wrapped_key,
+        )?;
+        let encrypted_payload = EncryptedPayload {
+            payload,
+            encrypted_file: Arc::new(encrypted_file),
+        };
+        self.storage.put(path, Box::new(encrypted_payload)).await
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        let file_layout = self.file_layout(path).await?;
+
+        let FileLayout::Encrypted(encrypted_file) = file_layout.as_ref() else {
+            return self.storage.copy_to(path, output).await;
+        };
+        let num_chunks = encrypted_file.num_chunks();
+
+        if num_chunks > 0 {
+            let encrypted_range = encrypted_file.encrypted_range(&(0..num_chunks));
+            let mut encrypted_stream = self
+                .storage
+                .get_slice_stream(
+                    path,
+                    encrypted_range.start as usize..encrypted_range.end as usize,
+                )
+                .await?;
+            let mut encrypted_chunk = vec![0u8; encrypted_file.encrypted_chunk_size() as usize];
+
+            for chunk_idx in 0..num_chunks {
+                let chunk_range = encrypted_file.encrypted_range(&(chunk_idx..chunk_idx + 1));
+                let encrypted_chunk_len = (chunk_range.end - chunk_range.start) as usize;
+                encrypted_stream
+                    .read_exact(&mut encrypted_chunk[..encrypted_chunk_len])
+                    .await?;
+                let chunk = encrypted_file
+                    .open_chunk(chunk_idx, &encrypted_chunk[..encrypted_chunk_len])?;
+                output.write_all(&chunk).await?;
+            }
+        }
+        output.flush().await?;
+        Ok(())
+    }
+
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        let file_layout = self.file_layout(path).await?;
+
+        match file_layout.as_ref() {
+            FileLayout::Plaintext => self.storage.get_slice(path, range).await,
+            FileLayout::Encrypted(encrypted_file) => {
+                self.get_slice_encrypted(path, encrypted_file, range).await
+            }
+        }
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        let file_layout = self.file_layout(path).await?;
+
+        match file_layout.as_ref() {
+            FileLayout::Plaintext => self.storage.get_slice_stream(path, range).await,
+            FileLayout::Encrypted(encrypted_file) => {
+                let bytes = self
+                    .get_slice_encrypted(path, encrypted_file, range)
+                    .await?;
+                Ok(Box::new(io::Cursor::new(bytes)))
+            }
+        }
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        let bytes = self.storage.get_all(path).await?;
+
+        let Some(header_len) = parse_header_prefix(&bytes)? else {
+            if self.require_encryption {
+                return Err(plaintext_file_error(path));
+            }
+            return Ok(bytes);
+        };
+        if bytes.len() < header_len {
+            return Err(corrupted_header_error());
+        }
+        let encrypted_file =
+            EncryptedFile::open(&self.keyring, bytes[..header_len].to_vec()).await?;
+
+        if bytes.len() as u64 != encrypted_file.encrypted_len() {
+            return Err(StorageErrorKind::Internal
+                .with_error(anyhow!("encrypted file `{}` is truncated", path.display())));
+        }
+        let plaintext = encrypted_file.open_chunks(0, &bytes[header_len..])?;
+        Ok(OwnedBytes::new(plaintext))
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        self.keyring.evict_file_layout(self.storage.uri(), path);
+        self.storage.delete(path).await
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        for path in paths {
+            self.keyring.evict_file_layout(self.storage.uri(), path);
+        }
+        self.storage.bulk_delete(paths).await
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        let file_layout = self.file_layout(path).await?;
+
+        match file_layout.as_ref() {
+            FileLayout::Plaintext => self.storage.file_num_bytes(path).await,
+            FileLayout::Encrypted(encrypted_file) => Ok(encrypted_file.plaintext_len),
+        }
+    }
+
+    fn uri(&self) -> &Uri {
+        self.storage.uri()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::encryption::keyring_for_test;
+    use crate::RamStorage;
+
+    fn encrypted_storage_for_test(
+        storage: Arc<dyn Storage>,
+        key_id_opt: Option<&str>,
+    ) -> EncryptedStorage {
+        EncryptedStorage {
+            storage,
+            keyring: keyring_for_test(),
+            key_id_opt: key_id_opt.map(ToString::to_string),
+            require_encryption: false,
+            chunk_size: 10,
+        }
+    }
+
+    fn test_content(num_bytes: usize) -> Vec<u8> {
+        (0..num_bytes).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_round_trip() {
+        let ram_storage: Arc<dyn Storage> = Arc::new(RamStorage::default());
+        let encrypted_storage = encrypted_storage_for_test(ram_storage.clone(), Some("key-1"));
+        let path = Path::new("split.split");
+        let content = test_content(95);
+
+        encrypted_storage
+            .put(path, Box::new(content.clone()))
+            .await
+            .unwrap();
+
+        let raw_bytes = ram_storage.get_all(path).await.unwrap();
+        assert!(raw_bytes.starts_with(MAGIC));
+        assert!(!raw_bytes
+            .windows(10)
+            .any(|window| window == &content[20..30]));
+
+        assert_eq!(
+            encrypted_storage.file_num_bytes(path).await.unwrap(),
+            content.len() as u64
+        );
+        assert_eq!(
+            encrypted_storage.get_all(path).await.unwrap().as_slice(),
+            &content[..]
+        );
+        for range in [
+            0..1,
+            0..10,
+            3..17,
+            10..20,
+            19..21,
+            25..95,
+            90..95,
+            0..95,
+            42..42,
+        ] {
+            let bytes = encrypted_storage
+                .get_slice(path, range.clone())
+                .await
+                .unwrap();
+            assert_eq!(bytes.as_slice(), &content[range]);
+        }
+        let mut stream = encrypted_storage
+            .get_slice_stream(path, 12..48)
+            .await
+            .unwrap();
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes).await.unwrap();
+        assert_eq!(&bytes[..], &content[12..48]);
+
+        let mut output = Vec::new();
+        encrypted_storage.copy_to(path, &mut output).await.unwrap();
+        assert_eq!(output, content);
+
+        encrypted_storage.get_slice(path, 90..96).await.unwrap_err();
+
+        // Another storage wrapped without key ID can still read the file.
+        let decrypting_storage = encrypted_storage_for_test(ram_storage, None);
+        assert_eq!(
+            decrypting_storage
+                .get_slice(path, 5..15)
+                .await
+                .unwrap()
+                .as_slice(),
+            &content[5..15]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_empty_file() {
+        let ram_storage: Arc<dyn Storage> = Arc::new(RamStorage::default());
+        let encrypted_storage = encrypted_storage_for_test(ram_storage, Some("key-1"));
+        let path = Path::new("empty");
+
+        encrypted_storage
+            .put(path, Box::new(Vec::new()))
+            .await
+            .unwrap();
+
+        assert_eq!(encrypted_storage.file_num_bytes(path).await.unwrap(), 0);
+        assert!(encrypted_storage.get_all(path).await.unwrap().is_empty());
+
+        let mut output = Vec::new();
+        encrypted_storage.copy_to(path, &mut output).await.unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_plaintext_files() {
+        let ram_storage: Arc<dyn Storage> = Arc::new(
+            RamStorage::builder()
+                .put("plaintext", b"hello world")
+                .put("tiny", b"hi")
+                .build(),
+        );
+        let encrypted_storage = encrypted_storage_for_test(ram_storage, Some("key-1"));
+
+        assert_eq!(
+            encrypted_storage
+                .get_slice(Path::new("plaintext"), 6..11)
+                .await
+                .unwrap()
+                .as_slice(),
+            b"world"
+        );
+        assert_eq!(
+            encrypted_storage
+                .get_all(Path::new("tiny"))
+                .await
+                .unwrap()
+                .as_slice(),
+            b"hi"
+        );
+        assert_eq!(
+            encrypted_storage
+                .get_slice(Path::new("tiny"), 0..1)
+                .await
+                .unwrap()
+                .as_slice(),
+            b"h"
+        );
+        assert_eq!(
+            encrypted_storage
+                .file_num_bytes(Path::new("plaintext"))
+                .await
+                .unwrap(),
+            11
+        );
+        let error = encrypted_storage
+            .get_slice(Path::new("missing"), 0..1)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), StorageErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_require_encryption() {
+        let ram_storage: Arc<dyn Storage> = Arc::new(
+            RamStorage::builder()
+                .put("plaintext", b"hello world")
+                .build(),
+        );
+        let keyring = keyring_for_test();
+        let lenient_storage =
+            EncryptedStorage::new(keyring.clone(), ram_storage.clone(), None, false);
+        let strict_storage =
+            EncryptedStorage::new(keyring, ram_storage, Some("key-1".to_string()), true);
+
+        // The layout of the file is cached by the keyring shared by both storages.
+        assert_eq!(
+            lenient_storage
+                .get_all(Path::new("plaintext"))
+                .await
+                .unwrap()
+                .as_slice(),
+            b"hello world"
+        );
+        lenient_storage
+            .get_slice(Path::new("plaintext"), 0..5)
+            .await
+            .unwrap();
+
+        let error = strict_storage
+            .get_slice(Path::new("plaintext"), 0..5)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), StorageErrorKind::Internal);
+        assert!(error.to_string().contains("is not encrypted"));
+
+        strict_storage
+            .get_all(Path::new("plaintext"))
+            .await
+            .unwrap_err();
+        strict_storage
+            .file_num_bytes(Path::new("plaintext"))
+            .await
+            .unwrap_err();
+
+        let mut output = Vec::new();
+        strict_storage
+            .copy_to(Path::new("plaintext"), &mut output)
+            .await
+            .unwrap_err();
+
+        strict_storage
+            .put(Path::new("encrypted"), Box::new(b"hello world".to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(
+            strict_storage
+                .get_slice(Path::new("encrypted"), 6..11)
+                .await
+                .unwrap()
+                .as_slice(),
+            b"world"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_detects_tampering() {
+        let ram_storage = Arc::new(RamStorage::default());
+        let encrypted_storage = encrypted_storage_for_test(ram_storage.clone(), Some("key-1"));
+        let path = Path::new("split.split");
+        let content = test_content(30);
+
+        encrypted_storage
+            .put(path, Box::new(content))
+            .await
+            .unwrap();
+
+        let mut raw_bytes = ram_storage.get_all(path).await.unwrap().to_vec();
+        let last_byte = raw_bytes.len() - 1;
+        raw_bytes[last_byte] ^= 1;
+        ram_storage.put(path, Box::new(raw_bytes)).await.unwrap();
+
+        // The file layout is still cached by the keyring.
+        encrypted_storage.get_slice(path, 0..10).await.unwrap();
+        let error = encrypted_storage.get_slice(path, 20..30).await.unwrap_err();
+        assert_eq!(error.kind(), StorageErrorKind::Internal);
+        encrypted_storage.get_all(path).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_payload_range_byte_stream() {
+        let content = test_content(57);
+        let data_key = keyring_for_test()
+            .key_provider
+            .generate_data_key("key-2")
+            .await
+            .unwrap();
+        let encrypted_file = EncryptedFile::new(
+            10,
+            content.len() as u64,
+            "key-2",
+            &data_key.plaintext_key,
+            &data_key.wrapped_key,
+        )
+        .unwrap();
+        let encrypted_payload = EncryptedPayload {
+            payload: Box::new(content.clone()),
+            encrypted_file: Arc::new(encrypted_file),
+        };
+        let encrypted_len = encrypted_payload.len();
+        let encrypted_bytes = encrypted_payload.read_all().await.unwrap();
+        assert_eq!(encrypted_bytes.len() as u64, encrypted_len);
+
+        // Multipart uploads fetch arbitrary ranges of the payload.
+        for part_len in [1, 7, 26, 100] {
+            let mut parts = Vec::new();
+            let mut start = 0;
+
+            while start < encrypted_len {
+                let end = (start + part_len).min(encrypted_len);
+                let mut part = Vec::new();
+                encrypted_payload
+                    .range_byte_stream(start..end)
+                    .await
+                    .unwrap()
+                    .into_async_read()
+                    .read_to_end(&mut part)
+                    .await
+                    .unwrap();
+                parts.extend(part);
+                start = end;
+            }
+            assert_eq!(&parts[..], encrypted_bytes.as_slice());
+        }
+        let ram_storage = Arc::new(
+            RamStorage::builder()
+                .put("split.split", encrypted_bytes.as_slice())
+                .build(),
+        );
+        let encrypted_storage = encrypted_storage_for_test(ram_storage, None);
+        let bytes = encrypted_storage
+            .get_all(&PathBuf::from("split.split"))
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_slice(), &content[..]);
+    }
+}
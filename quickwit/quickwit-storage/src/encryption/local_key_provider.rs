@@ -0,0 +1,190 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use super::{DataKey, KeyProvider, DATA_KEY_LEN};
+use crate::{StorageErrorKind, StorageResult};
+
+/// Key provider backed by a local keyfile.
+///
+/// The keyfile is a JSON object mapping key IDs to base64-encoded 256-bit master keys:
+/// ```json
+/// {
+///     "logs-2024": "q0kcb4sQmXW4Vr1xO6L4lLn1cZ7mIEmbK1+mM9vC1yU="
+/// }
+/// ```
+/// Data keys are encrypted with AES-256-GCM under the master key. Several master keys can be
+/// declared so that files encrypted with a retired key remain readable.
+pub struct LocalKeyProvider {
+    master_keys: HashMap<String, LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl fmt::Debug for LocalKeyProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKeyProvider")
+            .field("key_ids", &self.master_keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl LocalKeyProvider {
+    /// Loads the master keys from the keyfile located at `keyfile_path`.
+    pub fn from_keyfile(keyfile_path: &Path) -> anyhow::Result<Self> {
+        let keyfile_content = std::fs::read(keyfile_path)
+            .with_context(|| format!("failed to read keyfile `{}`", keyfile_path.display()))?;
+        Self::from_json(&keyfile_content)
+            .with_context(|| format!("failed to parse keyfile `{}`", keyfile_path.display()))
+    }
+
+    pub(crate) fn from_json(keyfile_content: &[u8]) -> anyhow::Result<Self> {
+        let encoded_master_keys: HashMap<String, String> = serde_json::from_slice(keyfile_content)?;
+        let mut master_keys = HashMap::with_capacity(encoded_master_keys.len());
+
+        for (key_id, encoded_master_key) in encoded_master_keys {
+            let master_key_bytes = BASE64_STANDARD
+                .decode(encoded_master_key)
+                .with_context(|| format!("master key `{key_id}` is not valid base64"))?;
+            if master_key_bytes.len() != DATA_KEY_LEN {
+                anyhow::bail!("master key `{key_id}` must be {DATA_KEY_LEN} bytes long");
+            }
+            let unbound_key = UnboundKey::new(&AES_256_GCM, &master_key_bytes)
+                .map_err(|_| anyhow!("master key `{key_id}` is invalid"))?;
+            master_keys.insert(key_id, LessSafeKey::new(unbound_key));
+        }
+        Ok(Self {
+            master_keys,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    fn master_key(&self, key_id: &str) -> StorageResult<&LessSafeKey> {
+        self.master_keys.get(key_id).ok_or_else(|| {
+            StorageErrorKind::Unauthorized
+                .with_error(anyhow!("master key `{key_id}` not found in keyfile"))
+        })
+    }
+}
+
+#[async_trait]
+impl KeyProvider for LocalKeyProvider {
+    async fn generate_data_key(&self, key_id: &str) -> StorageResult<DataKey> {
+        let master_key = self.master_key(key_id)?;
+
+        let mut plaintext_key = [0u8; DATA_KEY_LEN];
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut plaintext_key)
+            .and_then(|_| self.rng.fill(&mut nonce_bytes))
+            .map_err(|_| {
+                StorageErrorKind::Internal.with_error(anyhow!("failed to generate data key"))
+            })?;
+        let mut sealed_key = plaintext_key.to_vec();
+        master_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::from(key_id.as_bytes()),
+                &mut sealed_key,
+            )
+            .map_err(|_| {
+                StorageErrorKind::Internal.with_error(anyhow!("failed to encrypt data key"))
+            })?;
+        // The wrapped key is made of the nonce followed by the encrypted key and its tag.
+        let mut wrapped_key = Vec::with_capacity(NONCE_LEN + sealed_key.len());
+        wrapped_key.extend_from_slice(&nonce_bytes);
+        wrapped_key.extend_from_slice(&sealed_key);
+
+        let data_key = DataKey {
+            plaintext_key,
+            wrapped_key,
+        };
+        Ok(data_key)
+    }
+
+    async fn decrypt_data_key(
+        &self,
+        key_id: &str,
+        wrapped_key: &[u8],
+    ) -> StorageResult<[u8; DATA_KEY_LEN]> {
+        let master_key = self.master_key(key_id)?;
+
+        if wrapped_key.len() < NONCE_LEN {
+            return Err(
+                StorageErrorKind::Internal.with_error(anyhow!("wrapped data key is truncated"))
+            );
+        }
+        let (nonce_bytes, sealed_key) = wrapped_key.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+            .expect("nonce should be `NONCE_LEN` bytes long");
+        let mut sealed_key = sealed_key.to_vec();
+        let plaintext_key = master_key
+            .open_in_place(nonce, Aad::from(key_id.as_bytes()), &mut sealed_key)
+            .map_err(|_| {
+                StorageErrorKind::Unauthorized.with_error(anyhow!(
+                    "failed to decrypt data key with master key `{key_id}`"
+                ))
+            })?;
+        plaintext_key.try_into().map_err(|_| {
+            StorageErrorKind::Internal
+                .with_error(anyhow!("data key must be {DATA_KEY_LEN} bytes long"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::TEST_KEYFILE;
+
+    #[tokio::test]
+    async fn test_local_key_provider() {
+        let key_provider = LocalKeyProvider::from_json(TEST_KEYFILE.as_bytes()).unwrap();
+
+        let data_key = key_provider.generate_data_key("key-1").await.unwrap();
+        assert_eq!(data_key.wrapped_key.len(), NONCE_LEN + DATA_KEY_LEN + 16);
+
+        let plaintext_key = key_provider
+            .decrypt_data_key("key-1", &data_key.wrapped_key)
+            .await
+            .unwrap();
+        assert_eq!(plaintext_key, data_key.plaintext_key);
+
+        let error = key_provider
+            .decrypt_data_key("key-2", &data_key.wrapped_key)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), StorageErrorKind::Unauthorized);
+
+        let error = key_provider.generate_data_key("key-3").await.err().unwrap();
+        assert_eq!(error.kind(), StorageErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn test_local_key_provider_invalid_keyfile() {
+        let error = LocalKeyProvider::from_json(br#"{"key-1": "not base64!"}"#).unwrap_err();
+        assert!(error.to_string().contains("not valid base64"));
+
+        let error = LocalKeyProvider::from_json(br#"{"key-1": "AAECAwQ="}"#).unwrap_err();
+        assert!(error.to_string().contains("must be 32 bytes long"));
+    }
+}
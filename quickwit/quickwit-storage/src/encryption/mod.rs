@@ -0,0 +1,134 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod encrypted_storage;
+mod local_key_provider;
+
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+pub use encrypted_storage::EncryptedStorage;
+use encrypted_storage::FileLayout;
+pub use local_key_provider::LocalKeyProvider;
+use lru::LruCache;
+use quickwit_common::uri::Uri;
+
+use crate::{Storage, StorageResult};
+
+/// Length of the data keys in bytes. Files are encrypted with AES-256-GCM.
+pub const DATA_KEY_LEN: usize = 32;
+
+/// Maximum number of file layouts kept in the cache of an [`EncryptionKeyring`].
+const FILE_LAYOUT_CACHE_CAPACITY: usize = 10_000;
+
+/// Data key generated by a [`KeyProvider`] to encrypt a single file.
+pub struct DataKey {
+    /// Data key in plaintext, used to encrypt the file and never persisted.
+    pub plaintext_key: [u8; DATA_KEY_LEN],
+    /// Data key encrypted with a master key of the key provider, stored in the header of the
+    /// encrypted file.
+    pub wrapped_key: Vec<u8>,
+}
+
+/// The `KeyProvider` trait abstracts the service holding the master keys used for envelope
+/// encryption: a local keyfile, a key management service (KMS), etc. Master keys never leave the
+/// provider, which generates and decrypts the data keys on behalf of the storage.
+#[async_trait]
+pub trait KeyProvider: Send + Sync + 'static {
+    /// Generates a new data key, encrypted with the master key `key_id`.
+    async fn generate_data_key(&self, key_id: &str) -> StorageResult<DataKey>;
+
+    /// Decrypts a data key encrypted with the master key `key_id`.
+    async fn decrypt_data_key(
+        &self,
+        key_id: &str,
+        wrapped_key: &[u8],
+    ) -> StorageResult<[u8; DATA_KEY_LEN]>;
+}
+
+/// Holds the key provider of a node along with a cache of the layouts of the files read through
+/// it. The keyring is shared by all the storages wrapped with encryption so that the header of
+/// a file is fetched and its data key decrypted only once.
+pub struct EncryptionKeyring {
+    key_provider: Arc<dyn KeyProvider>,
+    file_layout_cache: Mutex<LruCache<(Uri, PathBuf), Arc<FileLayout>>>,
+}
+
+impl EncryptionKeyring {
+    /// Creates a new keyring backed by the given key provider.
+    pub fn new(key_provider: Arc<dyn KeyProvider>) -> Self {
+        let capacity = NonZeroUsize::new(FILE_LAYOUT_CACHE_CAPACITY).unwrap();
+        Self {
+            key_provider,
+            file_layout_cache: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    fn get_file_layout(&self, storage_uri: &Uri, path: &Path) -> Option<Arc<FileLayout>> {
+        let cache_key = (storage_uri.clone(), path.to_path_buf());
+        self.file_layout_cache
+            .lock()
+            .unwrap()
+            .get(&cache_key)
+            .cloned()
+    }
+
+    fn put_file_layout(&self, storage_uri: &Uri, path: &Path, file_layout: Arc<FileLayout>) {
+        let cache_key = (storage_uri.clone(), path.to_path_buf());
+        self.file_layout_cache
+            .lock()
+            .unwrap()
+            .put(cache_key, file_layout);
+    }
+
+    fn evict_file_layout(&self, storage_uri: &Uri, path: &Path) {
+        let cache_key = (storage_uri.clone(), path.to_path_buf());
+        self.file_layout_cache.lock().unwrap().pop(&cache_key);
+    }
+}
+
+/// Wraps the given storage with client-side encryption.
+///
+/// Files written through the returned storage are encrypted with a fresh data key, itself
+/// encrypted with the master key `key_id_opt`. If `key_id_opt` is `None`, files are written as-is.
+/// Encrypted files are decrypted transparently on read, whatever master key they were encrypted
+/// with, while files written without encryption are returned as-is, unless `require_encryption`
+/// is set, in which case reading them fails.
+pub fn wrap_storage_with_encryption(
+    keyring: Arc<EncryptionKeyring>,
+    storage: Arc<dyn Storage>,
+    key_id_opt: Option<String>,
+    require_encryption: bool,
+) -> Arc<dyn Storage> {
+    Arc::new(EncryptedStorage::new(
+        keyring,
+        storage,
+        key_id_opt,
+        require_encryption,
+    ))
+}
+
+#[cfg(test)]
+const TEST_KEYFILE: &str = r#"{
+    "key-1": "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+    "key-2": "HxweHRwbGhkYFxYVFBMSERAPDg0MCwoJCAcGBQQDAgE="
+}"#;
+
+#[cfg(test)]
+fn keyring_for_test() -> Arc<EncryptionKeyring> {
+    let key_provider = LocalKeyProvider::from_json(TEST_KEYFILE.as_bytes()).unwrap();
+    Arc::new(EncryptionKeyring::new(Arc::new(key_provider)))
+}
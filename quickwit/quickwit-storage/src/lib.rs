@@ -25,7 +25,9 @@
 //! - etc.
 //!
 //! The `BundleStorage` bundles together multiple files into a single file.
+//! The `CoalescingStorage` merges nearby concurrent reads into fewer range requests.
 mod cache;
+mod coalescing_storage;
 mod debouncer;
 mod file_descriptor_cache;
 mod metrics;
@@ -39,11 +41,17 @@ pub use self::payload::PutPayload;
 pub use self::storage::Storage;
 
 mod bundle_storage;
+mod encryption;
 mod error;
 
 mod local_file_storage;
 mod object_storage;
-#[cfg(feature = "gcs")]
+#[cfg(any(
+    feature = "gcs",
+    feature = "hdfs",
+    feature = "http",
+    feature = "webdav"
+))]
 mod opendal_storage;
 mod payload;
 mod prefix_storage;
@@ -63,7 +71,13 @@ pub use self::bundle_storage::{BundleStorage, BundleStorageFileOffsets};
 #[cfg(any(test, feature = "testsuite"))]
 pub use self::cache::MockStorageCache;
 pub use self::cache::{
-    wrap_storage_with_cache, ByteRangeCache, MemorySizedCache, QuickwitCache, StorageCache,
+    wrap_storage_with_cache, ByteRangeCache, DiskSliceCache, MemorySizedCache, QuickwitCache,
+    StorageCache, SPLIT_FOOTER_CACHE_PATH,
+};
+pub use self::coalescing_storage::{CoalescedRead, CoalescingStorage, ReadPlanner};
+pub use self::encryption::{
+    wrap_storage_with_encryption, DataKey, EncryptedStorage, EncryptionKeyring, KeyProvider,
+    LocalKeyProvider, DATA_KEY_LEN,
 };
 pub use self::local_file_storage::{LocalFileStorage, LocalFileStorageFactory};
 #[cfg(feature = "azure")]
@@ -75,8 +89,14 @@ pub use self::object_storage::{
 pub use self::opendal_storage::new_emulated_google_cloud_storage;
 #[cfg(feature = "gcs")]
 pub use self::opendal_storage::GoogleCloudStorageFactory;
+#[cfg(feature = "hdfs")]
+pub use self::opendal_storage::HdfsStorageFactory;
+#[cfg(feature = "http")]
+pub use self::opendal_storage::HttpStorageFactory;
+#[cfg(feature = "webdav")]
+pub use self::opendal_storage::WebdavStorageFactory;
 pub use self::ram_storage::{RamStorage, RamStorageBuilder};
-pub use self::split::{SplitPayload, SplitPayloadBuilder};
+pub use self::split::{FilePayload, SplitPayload, SplitPayloadBuilder, StorageFilePayload};
 #[cfg(any(test, feature = "testsuite"))]
 pub use self::storage::MockStorage;
 #[cfg(any(test, feature = "testsuite"))]
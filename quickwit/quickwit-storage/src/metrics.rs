@@ -28,8 +28,12 @@ pub struct StorageMetrics {
     pub fast_field_cache: CacheMetrics,
     pub split_footer_cache: CacheMetrics,
     pub searcher_split_cache: CacheMetrics,
+    pub searcher_disk_slice_cache: CacheMetrics,
     pub get_slice_timeout_successes: [IntCounter; 3],
     pub get_slice_timeout_all_timeouts: IntCounter,
+    pub coalesced_reads_total: IntCounter,
+    pub coalesced_reads_saved_total: IntCounter,
+    pub coalesced_reads_gap_num_bytes: IntCounter,
     pub object_storage_get_total: IntCounter,
     pub object_storage_get_errors_total: IntCounterVec<1>,
     pub object_storage_put_total: IntCounter,
@@ -91,10 +95,29 @@ impl Default for StorageMetrics {
             fd_cache_metrics: CacheMetrics::for_component("fd"),
             partial_request_cache: CacheMetrics::for_component("partial_request"),
             searcher_split_cache: CacheMetrics::for_component("searcher_split"),
+            searcher_disk_slice_cache: CacheMetrics::for_component("searcher_disk_slice"),
             shortlived_cache: CacheMetrics::for_component("shortlived"),
             split_footer_cache: CacheMetrics::for_component("splitfooter"),
             get_slice_timeout_successes,
             get_slice_timeout_all_timeouts,
+            coalesced_reads_total: new_counter(
+                "coalesced_reads_total",
+                "Number of range requests issued after coalescing nearby reads.",
+                "storage",
+                &[],
+            ),
+            coalesced_reads_saved_total: new_counter(
+                "coalesced_reads_saved_total",
+                "Number of range requests saved by coalescing nearby reads.",
+                "storage",
+                &[],
+            ),
+            coalesced_reads_gap_num_bytes: new_counter(
+                "coalesced_reads_gap_num_bytes",
+                "Number of bytes fetched in the gaps between coalesced reads, and discarded.",
+                "storage",
+                &[],
+            ),
             object_storage_get_total: new_counter(
                 "object_storage_gets_total",
                 "Number of objects fetched.",
@@ -51,11 +51,20 @@ impl fmt::Debug for OpendalStorage {
 
 impl OpendalStorage {
     /// Create a new google cloud storage.
+    #[cfg(feature = "gcs")]
     pub fn new_google_cloud_storage(
         uri: Uri,
         cfg: opendal::services::Gcs,
     ) -> Result<Self, StorageResolverError> {
-        let op = Operator::new(cfg)?.finish();
+        Self::from_builder(uri, cfg)
+    }
+
+    /// Create a new storage backed by the OpenDAL service configured by `builder`.
+    pub(super) fn from_builder<B: opendal::Builder>(
+        uri: Uri,
+        builder: B,
+    ) -> Result<Self, StorageResolverError> {
+        let op = Operator::new(builder)?.finish();
         Ok(Self { uri, op })
     }
 }
@@ -63,6 +72,16 @@ impl OpendalStorage {
 #[async_trait]
 impl Storage for OpendalStorage {
     async fn check_connectivity(&self) -> anyhow::Result<()> {
+        // Some services, such as plain HTTP file servers, cannot list files: we check that the
+        // root of the storage is reachable instead.
+        if !self.op.info().full_capability().list {
+            match self.op.stat("/").await {
+                Ok(_) => {}
+                Err(error) if error.kind() == opendal::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+            return Ok(());
+        }
         self.op.check().await?;
         Ok(())
     }
@@ -199,7 +218,9 @@ impl From<opendal::Error> for StorageError {
         match err.kind() {
             opendal::ErrorKind::NotFound => StorageErrorKind::NotFound.with_error(err),
             opendal::ErrorKind::PermissionDenied => StorageErrorKind::Unauthorized.with_error(err),
-            opendal::ErrorKind::ConfigInvalid => StorageErrorKind::Service.with_error(err),
+            opendal::ErrorKind::ConfigInvalid | opendal::ErrorKind::Unsupported => {
+                StorageErrorKind::Service.with_error(err)
+            }
             _ => StorageErrorKind::Io.with_error(err),
         }
     }
@@ -0,0 +1,69 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use quickwit_common::uri::Uri;
+use quickwit_config::{HdfsStorageConfig, StorageBackend};
+
+use super::{parse_authority_uri, OpendalStorage};
+use crate::debouncer::DebouncedStorage;
+use crate::{Storage, StorageFactory, StorageResolverError};
+
+/// HDFS storage resolver. URIs are of the form `hdfs://<name node host>:<port>/<path>`.
+pub struct HdfsStorageFactory {
+    storage_config: HdfsStorageConfig,
+}
+
+impl HdfsStorageFactory {
+    /// Create a new HDFS storage factory via config.
+    pub fn new(storage_config: HdfsStorageConfig) -> Self {
+        Self { storage_config }
+    }
+}
+
+#[async_trait]
+impl StorageFactory for HdfsStorageFactory {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Hdfs
+    }
+
+    async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Storage>, StorageResolverError> {
+        let storage = from_uri(&self.storage_config, uri)?;
+        Ok(Arc::new(DebouncedStorage::new(storage)))
+    }
+}
+
+fn from_uri(
+    hdfs_storage_config: &HdfsStorageConfig,
+    uri: &Uri,
+) -> Result<OpendalStorage, StorageResolverError> {
+    let (name_node, root) = parse_authority_uri(uri).ok_or_else(|| {
+        let message = format!("failed to extract name node from HDFS URI: {uri}");
+        StorageResolverError::InvalidUri(message)
+    })?;
+    let mut cfg = opendal::services::Hdfs::default();
+    cfg.name_node(&format!("hdfs://{name_node}"));
+    cfg.root(&root);
+
+    if let Some(user) = hdfs_storage_config.resolve_user() {
+        cfg.user(&user);
+    }
+    if let Some(kerberos_ticket_cache_path) = &hdfs_storage_config.kerberos_ticket_cache_path {
+        cfg.kerberos_ticket_cache_path(kerberos_ticket_cache_path);
+    }
+    let store = OpendalStorage::from_builder(uri.clone(), cfg)?;
+    Ok(store)
+}
@@ -0,0 +1,98 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use quickwit_common::uri::Uri;
+use quickwit_config::{HttpStorageConfig, StorageBackend};
+
+use super::{parse_authority_uri, OpendalStorage};
+use crate::debouncer::DebouncedStorage;
+use crate::{Storage, StorageFactory, StorageResolverError};
+
+/// Read-only storage resolver for the files served by an HTTP(S) server, for instance a static
+/// file server exposing the splits of an index. URIs are of the form `http(s)://<host>/<path>`.
+///
+/// Writing or deleting files through the resolved storages fails with a
+/// [`crate::StorageErrorKind::Service`] error.
+pub struct HttpStorageFactory {
+    storage_config: HttpStorageConfig,
+}
+
+impl HttpStorageFactory {
+    /// Create a new HTTP storage factory via config.
+    pub fn new(storage_config: HttpStorageConfig) -> Self {
+        Self { storage_config }
+    }
+}
+
+#[async_trait]
+impl StorageFactory for HttpStorageFactory {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Http
+    }
+
+    async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Storage>, StorageResolverError> {
+        let storage = from_uri(&self.storage_config, uri)?;
+        Ok(Arc::new(DebouncedStorage::new(storage)))
+    }
+}
+
+fn from_uri(
+    http_storage_config: &HttpStorageConfig,
+    uri: &Uri,
+) -> Result<OpendalStorage, StorageResolverError> {
+    let (host, root) = parse_authority_uri(uri).ok_or_else(|| {
+        let message = format!("failed to extract host from HTTP URI: {uri}");
+        StorageResolverError::InvalidUri(message)
+    })?;
+    let mut cfg = opendal::services::Http::default();
+    cfg.endpoint(&format!("{}://{host}", uri.protocol()));
+    cfg.root(&root);
+
+    if let Some(username) = &http_storage_config.username {
+        cfg.username(username);
+    }
+    if let Some(password) = http_storage_config.resolve_password() {
+        cfg.password(&password);
+    }
+    if let Some(token) = http_storage_config.resolve_token() {
+        cfg.token(&token);
+    }
+    let store = OpendalStorage::from_builder(uri.clone(), cfg)?;
+    Ok(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::StorageErrorKind;
+
+    #[tokio::test]
+    async fn test_http_storage_is_read_only() {
+        let storage_factory = HttpStorageFactory::new(HttpStorageConfig::default());
+        let storage_uri = Uri::for_test("http://127.0.0.1:1/indexes/logs");
+        let storage = storage_factory.resolve(&storage_uri).await.unwrap();
+        assert_eq!(storage.uri(), &storage_uri);
+
+        let storage_error = storage
+            .put(Path::new("split.split"), Box::new(b"split".to_vec()))
+            .await
+            .unwrap_err();
+        assert_eq!(storage_error.kind(), StorageErrorKind::Service);
+    }
+}
@@ -15,8 +15,65 @@
 mod base;
 use base::OpendalStorage;
 
+#[cfg(feature = "gcs")]
 mod google_cloud_storage;
+#[cfg(feature = "hdfs")]
+mod hdfs_storage;
+#[cfg(feature = "http")]
+mod http_storage;
+#[cfg(feature = "webdav")]
+mod webdav_storage;
 
-#[cfg(feature = "integration-testsuite")]
+#[cfg(all(feature = "gcs", feature = "integration-testsuite"))]
 pub use google_cloud_storage::new_emulated_google_cloud_storage;
+#[cfg(feature = "gcs")]
 pub use google_cloud_storage::GoogleCloudStorageFactory;
+#[cfg(feature = "hdfs")]
+pub use hdfs_storage::HdfsStorageFactory;
+#[cfg(feature = "http")]
+pub use http_storage::HttpStorageFactory;
+#[cfg(feature = "webdav")]
+pub use webdav_storage::WebdavStorageFactory;
+
+/// Splits URIs of the form `<protocol>://<authority>/<path>` into the authority, i.e. host and
+/// optional port, and the absolute root path expected by OpenDAL services.
+#[cfg(any(feature = "hdfs", feature = "http", feature = "webdav"))]
+fn parse_authority_uri(uri: &quickwit_common::uri::Uri) -> Option<(String, String)> {
+    let (_protocol, authority_and_path) = uri.as_str().split_once("://")?;
+    let (authority, path) = authority_and_path
+        .split_once('/')
+        .unwrap_or((authority_and_path, ""));
+    if authority.is_empty() {
+        return None;
+    }
+    let root = format!("/{}", path.trim_end_matches('/'));
+    Some((authority.to_string(), root))
+}
+
+#[cfg(all(test, any(feature = "hdfs", feature = "http", feature = "webdav")))]
+mod tests {
+    use quickwit_common::uri::Uri;
+
+    use super::parse_authority_uri;
+
+    #[test]
+    fn test_parse_authority_uri() {
+        assert!(parse_authority_uri(&Uri::for_test("hdfs://")).is_none());
+
+        let (authority, root) =
+            parse_authority_uri(&Uri::for_test("hdfs://namenode:8020")).unwrap();
+        assert_eq!(authority, "namenode:8020");
+        assert_eq!(root, "/");
+
+        let (authority, root) =
+            parse_authority_uri(&Uri::for_test("https://splits.example.com/indexes/")).unwrap();
+        assert_eq!(authority, "splits.example.com");
+        assert_eq!(root, "/indexes");
+
+        let (authority, root) =
+            parse_authority_uri(&Uri::for_test("webdav://localhost:8080/dav/indexes/logs"))
+                .unwrap();
+        assert_eq!(authority, "localhost:8080");
+        assert_eq!(root, "/dav/indexes/logs");
+    }
+}
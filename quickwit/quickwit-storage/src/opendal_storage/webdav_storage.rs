@@ -0,0 +1,78 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use quickwit_common::uri::{Protocol, Uri};
+use quickwit_config::{StorageBackend, WebdavStorageConfig};
+
+use super::{parse_authority_uri, OpendalStorage};
+use crate::debouncer::DebouncedStorage;
+use crate::{Storage, StorageFactory, StorageResolverError};
+
+/// WebDAV storage resolver. URIs are of the form `webdav://<host>/<path>`, or
+/// `webdavs://<host>/<path>` to connect to the server over HTTPS.
+pub struct WebdavStorageFactory {
+    storage_config: WebdavStorageConfig,
+}
+
+impl WebdavStorageFactory {
+    /// Create a new WebDAV storage factory via config.
+    pub fn new(storage_config: WebdavStorageConfig) -> Self {
+        Self { storage_config }
+    }
+}
+
+#[async_trait]
+impl StorageFactory for WebdavStorageFactory {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Webdav
+    }
+
+    async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Storage>, StorageResolverError> {
+        let storage = from_uri(&self.storage_config, uri)?;
+        Ok(Arc::new(DebouncedStorage::new(storage)))
+    }
+}
+
+fn from_uri(
+    webdav_storage_config: &WebdavStorageConfig,
+    uri: &Uri,
+) -> Result<OpendalStorage, StorageResolverError> {
+    let (host, root) = parse_authority_uri(uri).ok_or_else(|| {
+        let message = format!("failed to extract host from WebDAV URI: {uri}");
+        StorageResolverError::InvalidUri(message)
+    })?;
+    let scheme = if uri.protocol() == Protocol::Webdavs {
+        "https"
+    } else {
+        "http"
+    };
+    let mut cfg = opendal::services::Webdav::default();
+    cfg.endpoint(&format!("{scheme}://{host}"));
+    cfg.root(&root);
+
+    if let Some(username) = &webdav_storage_config.username {
+        cfg.username(username);
+    }
+    if let Some(password) = webdav_storage_config.resolve_password() {
+        cfg.password(&password);
+    }
+    if let Some(token) = webdav_storage_config.resolve_token() {
+        cfg.token(&token);
+    }
+    let store = OpendalStorage::from_builder(uri.clone(), cfg)?;
+    Ok(store)
+}
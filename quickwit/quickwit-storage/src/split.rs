@@ -17,6 +17,7 @@ use std::io::{self, SeekFrom};
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use aws_sdk_s3::primitives::{ByteStream, SdkBody};
@@ -28,7 +29,7 @@ use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_util::io::ReaderStream;
 
 use crate::bundle_storage::BundleStorageFileOffsetsVersions;
-use crate::{BundleStorageFileOffsets, PutPayload, VersionedComponent};
+use crate::{BundleStorageFileOffsets, PutPayload, Storage, StorageResult, VersionedComponent};
 
 /// Payload of a split which builds the split bundle and hotcache on the fly and streams it to the
 /// storage.
@@ -100,12 +101,25 @@ impl PutPayload for SplitPayload {
     }
 }
 
+/// Payload streaming the content of a local file to the storage.
 #[derive(Clone)]
-struct FilePayload {
+pub struct FilePayload {
     len: u64,
     path: PathBuf,
 }
 
+impl FilePayload {
+    /// Creates a payload for the file located at `path`.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = std::fs::metadata(path)?;
+        let file_payload = FilePayload {
+            path: path.to_owned(),
+            len: file.len(),
+        };
+        Ok(file_payload)
+    }
+}
+
 #[async_trait]
 impl PutPayload for FilePayload {
     fn len(&self) -> u64 {
@@ -130,6 +144,46 @@ impl PutPayload for FilePayload {
     }
 }
 
+/// Payload of a file stored in another storage. The file is streamed from the source storage
+/// during the upload, which allows copying large files across storages without buffering them.
+#[derive(Clone)]
+pub struct StorageFilePayload {
+    storage: Arc<dyn Storage>,
+    path: PathBuf,
+    len: u64,
+}
+
+impl StorageFilePayload {
+    /// Creates a payload for the file located at `path` in `storage`.
+    pub async fn open(storage: Arc<dyn Storage>, path: &Path) -> StorageResult<Self> {
+        let len = storage.file_num_bytes(path).await?;
+        let storage_file_payload = StorageFilePayload {
+            storage,
+            path: path.to_owned(),
+            len,
+        };
+        Ok(storage_file_payload)
+    }
+}
+
+#[async_trait]
+impl PutPayload for StorageFilePayload {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    async fn range_byte_stream(&self, range: Range<u64>) -> io::Result<ByteStream> {
+        assert!(!range.is_empty());
+        assert!(range.end <= self.len);
+        let reader = self
+            .storage
+            .get_slice_stream(&self.path, range.start as usize..range.end as usize)
+            .await?;
+        let body = Body::wrap_stream(ReaderStream::new(reader));
+        Ok(ByteStream::new(SdkBody::from_body_0_4(body)))
+    }
+}
+
 /// SplitPayloadBuilder is used to create a `SplitPayload`.
 #[derive(Default)]
 pub struct SplitPayloadBuilder {
@@ -279,6 +333,37 @@ mod tests {
 
     use super::*;
 
+    #[tokio::test]
+    async fn test_storage_file_payload() {
+        let storage: Arc<dyn Storage> = Arc::new(crate::RamStorage::default());
+        let path = Path::new("source.split");
+        storage
+            .put(path, Box::new(b"hello world".to_vec()))
+            .await
+            .unwrap();
+
+        let payload = StorageFilePayload::open(storage.clone(), path)
+            .await
+            .unwrap();
+        assert_eq!(payload.len(), 11);
+
+        let bytes = payload.read_all().await.unwrap();
+        assert_eq!(bytes.as_slice(), b"hello world");
+
+        let mut reader = payload
+            .range_byte_stream(6..11)
+            .await
+            .unwrap()
+            .into_async_read();
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"world");
+
+        StorageFilePayload::open(storage, Path::new("missing.split"))
+            .await
+            .unwrap_err();
+    }
+
     #[tokio::test]
     async fn test_split_offset_computer() -> anyhow::Result<()> {
         let temp_dir = tempfile::tempdir()?;
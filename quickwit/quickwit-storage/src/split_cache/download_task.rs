@@ -36,6 +36,9 @@ async fn download_split(
     let split_filename = split_file(*split_ulid);
     let target_filepath = root_path.join(&split_filename);
     let storage = storage_resolver.resolve(storage_uri).await?;
+    // The split cache serves the split files as they are read by the searchers, so encrypted
+    // splits are decrypted before being written to disk.
+    let storage = storage_resolver.wrap_split_storage(storage, None, false)?;
     let num_bytes = storage
         .copy_to_file(Path::new(&split_filename), &target_filepath)
         .await?;
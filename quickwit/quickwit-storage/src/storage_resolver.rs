@@ -26,7 +26,16 @@ use crate::ram_storage::RamStorageFactory;
 use crate::AzureBlobStorageFactory;
 #[cfg(feature = "gcs")]
 use crate::GoogleCloudStorageFactory;
-use crate::{S3CompatibleObjectStorageFactory, Storage, StorageFactory, StorageResolverError};
+#[cfg(feature = "hdfs")]
+use crate::HdfsStorageFactory;
+#[cfg(feature = "http")]
+use crate::HttpStorageFactory;
+#[cfg(feature = "webdav")]
+use crate::WebdavStorageFactory;
+use crate::{
+    wrap_storage_with_encryption, EncryptionKeyring, KeyProvider, S3CompatibleObjectStorageFactory,
+    Storage, StorageFactory, StorageResolverError,
+};
 
 /// Returns the [`Storage`] instance associated with the protocol of a URI. The actual creation of
 /// storage objects is delegated to pre-registered [`StorageFactory`]. The resolver is only
@@ -34,11 +43,14 @@ use crate::{S3CompatibleObjectStorageFactory, Storage, StorageFactory, StorageRe
 #[derive(Clone)]
 pub struct StorageResolver {
     per_backend_factories: Arc<HashMap<StorageBackend, Box<dyn StorageFactory>>>,
+    encryption_keyring_opt: Option<Arc<EncryptionKeyring>>,
 }
 
 impl fmt::Debug for StorageResolver {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("StorageResolver").finish()
+        f.debug_struct("StorageResolver")
+            .field("encryption", &self.encryption_keyring_opt.is_some())
+            .finish()
     }
 }
 
@@ -56,6 +68,9 @@ impl StorageResolver {
             Protocol::Ram => StorageBackend::Ram,
             Protocol::S3 => StorageBackend::S3,
             Protocol::Google => StorageBackend::Google,
+            Protocol::Hdfs => StorageBackend::Hdfs,
+            Protocol::Http | Protocol::Https => StorageBackend::Http,
+            Protocol::Webdav | Protocol::Webdavs => StorageBackend::Webdav,
             _ => {
                 let message = format!(
                     "Quickwit does not support {} as a storage backend",
@@ -72,6 +87,51 @@ impl StorageResolver {
         Ok(storage)
     }
 
+    /// Returns a copy of this resolver that encrypts and decrypts split files with the master
+    /// keys held by the given key provider. See [`StorageResolver::wrap_split_storage`].
+    pub fn with_key_provider(mut self, key_provider: Arc<dyn KeyProvider>) -> Self {
+        self.encryption_keyring_opt = Some(Arc::new(EncryptionKeyring::new(key_provider)));
+        self
+    }
+
+    /// Wraps a storage holding split files with client-side encryption. Splits written through
+    /// the returned storage are encrypted with the master key `encryption_key_id_opt`, if any,
+    /// while encrypted splits are transparently decrypted on read. If `require_encryption` is set,
+    /// reading a split written in clear fails.
+    ///
+    /// The storage is returned as-is if this resolver has no key provider. Copies of split files
+    /// that should remain encrypted (snapshots, storage tiering, etc.) must use the raw storage.
+    pub fn wrap_split_storage(
+        &self,
+        storage: Arc<dyn Storage>,
+        encryption_key_id_opt: Option<&str>,
+        require_encryption: bool,
+    ) -> Result<Arc<dyn Storage>, StorageResolverError> {
+        let Some(encryption_keyring) = &self.encryption_keyring_opt else {
+            if let Some(encryption_key_id) = encryption_key_id_opt {
+                let message = format!(
+                    "failed to encrypt splits with key `{encryption_key_id}`: no keyfile is \
+                     configured for this node"
+                );
+                return Err(StorageResolverError::InvalidConfig(message));
+            }
+            if require_encryption {
+                let message = "failed to read splits requiring encryption: no keyfile is \
+                               configured for this node"
+                    .to_string();
+                return Err(StorageResolverError::InvalidConfig(message));
+            }
+            return Ok(storage);
+        };
+        let storage = wrap_storage_with_encryption(
+            encryption_keyring.clone(),
+            storage,
+            encryption_key_id_opt.map(ToString::to_string),
+            require_encryption,
+        );
+        Ok(storage)
+    }
+
     /// Creates and returns a default [`StorageResolver`] with the default storage configuration for
     /// each backend. Note that if the environment (env vars, instance metadata, ...) fails to
     /// provide the necessary credentials, the default Azure or S3 storage returned by this
@@ -122,6 +182,51 @@ impl StorageResolver {
                 "Quickwit was compiled without the `gcs` feature",
             ))
         }
+        #[cfg(feature = "hdfs")]
+        {
+            builder = builder.register(HdfsStorageFactory::new(
+                storage_configs.find_hdfs().cloned().unwrap_or_default(),
+            ));
+        }
+        #[cfg(not(feature = "hdfs"))]
+        {
+            use crate::storage_factory::UnsupportedStorage;
+
+            builder = builder.register(UnsupportedStorage::new(
+                StorageBackend::Hdfs,
+                "Quickwit was compiled without the `hdfs` feature",
+            ))
+        }
+        #[cfg(feature = "http")]
+        {
+            builder = builder.register(HttpStorageFactory::new(
+                storage_configs.find_http().cloned().unwrap_or_default(),
+            ));
+        }
+        #[cfg(not(feature = "http"))]
+        {
+            use crate::storage_factory::UnsupportedStorage;
+
+            builder = builder.register(UnsupportedStorage::new(
+                StorageBackend::Http,
+                "Quickwit was compiled without the `http` feature",
+            ))
+        }
+        #[cfg(feature = "webdav")]
+        {
+            builder = builder.register(WebdavStorageFactory::new(
+                storage_configs.find_webdav().cloned().unwrap_or_default(),
+            ));
+        }
+        #[cfg(not(feature = "webdav"))]
+        {
+            use crate::storage_factory::UnsupportedStorage;
+
+            builder = builder.register(UnsupportedStorage::new(
+                StorageBackend::Webdav,
+                "Quickwit was compiled without the `webdav` feature",
+            ))
+        }
         builder
             .build()
             .expect("storage factory and config backends should match")
@@ -156,6 +261,7 @@ impl StorageResolverBuilder {
     pub fn build(self) -> anyhow::Result<StorageResolver> {
         let storage_resolver = StorageResolver {
             per_backend_factories: Arc::new(self.per_backend_factories),
+            encryption_keyring_opt: None,
         };
         Ok(storage_resolver)
     }
@@ -166,7 +272,7 @@ mod tests {
     use std::path::Path;
 
     use super::*;
-    use crate::{MockStorageFactory, RamStorage};
+    use crate::{LocalKeyProvider, MockStorageFactory, RamStorage};
 
     #[tokio::test]
     async fn test_storage_resolver_simple() -> anyhow::Result<()> {
@@ -231,6 +337,57 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_storage_resolver_wrap_split_storage() {
+        let storage: Arc<dyn Storage> = Arc::new(RamStorage::default());
+        let split_path = Path::new("split.split");
+
+        let storage_resolver = StorageResolver::for_test();
+        let wrapped_storage = storage_resolver
+            .wrap_split_storage(storage.clone(), None, false)
+            .unwrap();
+        assert!(Arc::ptr_eq(&wrapped_storage, &storage));
+
+        let resolver_error = storage_resolver
+            .wrap_split_storage(storage.clone(), Some("key-1"), false)
+            .unwrap_err();
+        assert!(matches!(
+            resolver_error,
+            StorageResolverError::InvalidConfig(_)
+        ));
+        let resolver_error = storage_resolver
+            .wrap_split_storage(storage.clone(), None, true)
+            .unwrap_err();
+        assert!(matches!(
+            resolver_error,
+            StorageResolverError::InvalidConfig(_)
+        ));
+        let key_provider = LocalKeyProvider::from_json(
+            br#"{"key-1": "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8="}"#,
+        )
+        .unwrap();
+        let storage_resolver = storage_resolver.with_key_provider(Arc::new(key_provider));
+        let encrypting_storage = storage_resolver
+            .wrap_split_storage(storage.clone(), Some("key-1"), true)
+            .unwrap();
+        encrypting_storage
+            .put(split_path, Box::new(b"split_content".to_vec()))
+            .await
+            .unwrap();
+
+        let raw_bytes = storage.get_all(split_path).await.unwrap();
+        assert_ne!(raw_bytes.as_slice(), b"split_content");
+
+        let decrypting_storage = storage_resolver
+            .wrap_split_storage(storage, None, false)
+            .unwrap();
+        let bytes = decrypting_storage
+            .get_slice(split_path, 6..13)
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_slice(), b"content");
+    }
+
     #[tokio::test]
     async fn test_storage_resolver_unsupported_protocol() {
         let storage_resolver = StorageResolver::unconfigured();
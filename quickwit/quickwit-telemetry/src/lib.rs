@@ -15,10 +15,11 @@
 #![allow(clippy::bool_assert_comparison)]
 #![deny(clippy::disallowed_methods)]
 
+mod metrics;
 pub mod payload;
 /// This crate contains  the code responsible for sending usage data to Quickwit inc's server.
 mod sender;
-pub(crate) mod sink;
+pub mod sink;
 
 use once_cell::sync::OnceCell;
 use payload::QuickwitTelemetryInfo;
@@ -27,13 +28,20 @@ use tracing::info;
 use crate::payload::TelemetryEvent;
 pub use crate::sender::is_telemetry_disabled;
 use crate::sender::{TelemetryLoopHandle, TelemetrySender};
+use crate::sink::Sink;
 
 static TELEMETRY_SENDER: OnceCell<TelemetrySender> = OnceCell::new();
 
 /// Returns a `TelemetryLoopHandle` if the telemetry loop is not yet started.
-pub fn start_telemetry_loop(quickwit_info: QuickwitTelemetryInfo) -> Option<TelemetryLoopHandle> {
-    let telemetry_sender =
-        TELEMETRY_SENDER.get_or_init(|| TelemetrySender::from_quickwit_info(quickwit_info));
+///
+/// The events are sent to Quickwit's server, unless telemetry is disabled, and to the
+/// `extra_sinks`, which are fed regardless of [`DISABLE_TELEMETRY_ENV_KEY`].
+pub fn start_telemetry_loop(
+    quickwit_info: QuickwitTelemetryInfo,
+    extra_sinks: Vec<Box<dyn Sink>>,
+) -> Option<TelemetryLoopHandle> {
+    let telemetry_sender = TELEMETRY_SENDER
+        .get_or_init(|| TelemetrySender::from_quickwit_info(quickwit_info, extra_sinks));
     // This should not happen... unless telemetry is enabled and you are running tests in parallel
     // in the same process.
     if telemetry_sender.loop_started() {
@@ -43,7 +51,7 @@ pub fn start_telemetry_loop(quickwit_info: QuickwitTelemetryInfo) -> Option<Tele
     Some(telemetry_sender.start_loop())
 }
 
-/// Sends a telemetry event to Quickwit's server via HTTP.
+/// Sends a telemetry event to Quickwit's server via HTTP and to the extra telemetry sinks.
 ///
 /// Telemetry guarantees to send at most 1 request per minute.
 /// Each requests can ship at most 10 messages.
@@ -60,5 +68,13 @@ pub async fn send_telemetry_event(event: TelemetryEvent) {
     }
 }
 
+/// Returns true if the telemetry loop is running, i.e. the events sent with
+/// [`send_telemetry_event`] are delivered to at least one sink.
+pub fn is_telemetry_loop_running() -> bool {
+    TELEMETRY_SENDER
+        .get()
+        .map_or(false, |telemetry_sender| telemetry_sender.loop_started())
+}
+
 /// This environment variable can be set to disable sending telemetry events.
 pub const DISABLE_TELEMETRY_ENV_KEY: &str = "QW_DISABLE_TELEMETRY";
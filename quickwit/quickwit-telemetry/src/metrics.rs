@@ -0,0 +1,83 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use once_cell::sync::Lazy;
+use quickwit_common::metrics::{
+    new_counter, new_counter_vec, new_gauge, IntCounter, IntCounterVec, IntGauge,
+};
+
+pub struct TelemetryMetrics {
+    pub events_total: IntCounterVec<1>,
+    pub dropped_events_total: IntCounter,
+    pub num_indexes: IntGauge,
+    pub num_published_splits: IntGauge,
+    pub num_published_docs: IntGauge,
+    pub size_published_splits: IntGauge,
+    pub size_published_docs_uncompressed: IntGauge,
+}
+
+impl Default for TelemetryMetrics {
+    fn default() -> Self {
+        TelemetryMetrics {
+            events_total: new_counter_vec(
+                "events_total",
+                "Total number of telemetry events delivered to the Prometheus sink.",
+                "telemetry",
+                &[],
+                ["type"],
+            ),
+            dropped_events_total: new_counter(
+                "dropped_events_total",
+                "Total number of telemetry events dropped because the event queue was full.",
+                "telemetry",
+                &[],
+            ),
+            num_indexes: new_gauge(
+                "num_indexes",
+                "Number of indexes reported by the last index stats event.",
+                "telemetry",
+                &[],
+            ),
+            num_published_splits: new_gauge(
+                "num_published_splits",
+                "Number of published splits reported by the last index stats event.",
+                "telemetry",
+                &[],
+            ),
+            num_published_docs: new_gauge(
+                "num_published_docs",
+                "Number of published documents reported by the last index stats event.",
+                "telemetry",
+                &[],
+            ),
+            size_published_splits: new_gauge(
+                "size_published_splits_bytes",
+                "Size of the published splits reported by the last index stats event.",
+                "telemetry",
+                &[],
+            ),
+            size_published_docs_uncompressed: new_gauge(
+                "size_published_docs_uncompressed_bytes",
+                "Uncompressed size of the published documents reported by the last index stats \
+                 event.",
+                "telemetry",
+                &[],
+            ),
+        }
+    }
+}
+
+/// `TELEMETRY_METRICS` exposes the telemetry events received by the Prometheus sink through a
+/// prometheus endpoint.
+pub static TELEMETRY_METRICS: Lazy<TelemetryMetrics> = Lazy::new(TelemetryMetrics::default);
@@ -20,7 +20,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Represents the payload of the request sent with telemetry requests.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TelemetryPayload {
     /// Client information. See details in `[ClientInformation]`.
     pub client_info: ClientInfo,
@@ -32,7 +32,7 @@ pub struct TelemetryPayload {
     pub num_dropped_events: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EventWithTimestamp {
     /// Unix time in seconds.
     pub unixtime: u64,
@@ -61,7 +61,7 @@ impl From<TelemetryEvent> for EventWithTimestamp {
 }
 
 /// Represents a Telemetry Event send to Quickwit's telemetry server for usage information.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
 pub enum TelemetryEvent {
@@ -74,6 +74,28 @@ pub enum TelemetryEvent {
     Running,
     /// UI index.html was requested.
     UiIndexPageLoad,
+    /// Event sent every 12h by the janitor node with the statistics of the published splits of
+    /// all the indexes of the cluster.
+    IndexStats {
+        num_indexes: usize,
+        num_published_splits: usize,
+        num_published_docs: u64,
+        size_published_splits: u64,
+        size_published_docs_uncompressed: u64,
+    },
+}
+
+impl TelemetryEvent {
+    /// Returns the type of the event, as serialized in the `type` field.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            TelemetryEvent::RunCommand => "run_command",
+            TelemetryEvent::EndCommand { .. } => "end_command",
+            TelemetryEvent::Running => "running",
+            TelemetryEvent::UiIndexPageLoad => "ui_index_page_load",
+            TelemetryEvent::IndexStats { .. } => "index_stats",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -162,4 +184,25 @@ mod tests {
             r#"{"unixtime":0,"type":"end_command","return_code":0}"#
         );
     }
+
+    #[test]
+    fn test_event_type_matches_serialized_type() {
+        let events = [
+            TelemetryEvent::RunCommand,
+            TelemetryEvent::EndCommand { return_code: 0 },
+            TelemetryEvent::Running,
+            TelemetryEvent::UiIndexPageLoad,
+            TelemetryEvent::IndexStats {
+                num_indexes: 2,
+                num_published_splits: 3,
+                num_published_docs: 1_000,
+                size_published_splits: 10_000,
+                size_published_docs_uncompressed: 100_000,
+            },
+        ];
+        for event in events {
+            let json = serde_json::to_value(&event).unwrap();
+            assert_eq!(json["type"], event.event_type());
+        }
+    }
 }
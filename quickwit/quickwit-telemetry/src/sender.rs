@@ -149,7 +149,7 @@ impl Events {
 }
 
 pub(crate) struct Inner {
-    sink: Option<Box<dyn Sink>>,
+    sinks: Vec<Box<dyn Sink>>,
     client_info: ClientInfo,
     quickwit_info: QuickwitTelemetryInfo,
     /// This channel is just used to signal there are new items available.
@@ -160,7 +160,7 @@ pub(crate) struct Inner {
 
 impl Inner {
     pub fn is_disabled(&self) -> bool {
-        self.sink.is_none()
+        self.sinks.is_empty()
     }
 
     async fn create_telemetry_payload(&self) -> TelemetryPayload {
@@ -174,14 +174,16 @@ impl Inner {
     }
 
     /// Wait for events to be available (if there are pending events, then do not wait)
-    /// and then send them to the ingest API server.
+    /// and then send them to the sinks.
     ///
     /// If the requests fails, it fails silently.
     async fn send_pending_events(&self) {
-        if let Some(sink) = self.sink.as_ref() {
-            let payload = self.create_telemetry_payload().await;
-            sink.send_payload(payload).await;
+        if self.is_disabled() {
+            return;
         }
+        let payload = self.create_telemetry_payload().await;
+        let send_payload_futures = self.sinks.iter().map(|sink| sink.send_payload(&payload));
+        futures::future::join_all(send_payload_futures).await;
     }
 
     async fn send(&self, event: TelemetryEvent) {
@@ -221,28 +223,29 @@ impl TelemetryLoopHandle {
 }
 
 impl TelemetrySender {
-    pub fn from_quickwit_info(quickwit_info: QuickwitTelemetryInfo) -> Self {
-        let http_client = create_http_client();
+    /// Creates a sender delivering the events to Quickwit's telemetry server, unless telemetry is
+    /// disabled, and to the `extra_sinks`.
+    pub fn from_quickwit_info(
+        quickwit_info: QuickwitTelemetryInfo,
+        extra_sinks: Vec<Box<dyn Sink>>,
+    ) -> Self {
+        let mut sinks: Vec<Box<dyn Sink>> = Vec::with_capacity(extra_sinks.len() + 1);
+
+        if let Some(http_client) = create_http_client() {
+            sinks.push(Box::new(http_client));
+        }
+        sinks.extend(extra_sinks);
         TelemetrySender::new(
             quickwit_info,
-            http_client,
+            sinks,
             Clock::periodical(TELEMETRY_PUSH_COOLDOWN),
         )
     }
 
-    fn new<S: Sink>(
-        quickwit_info: QuickwitTelemetryInfo,
-        sink_opt: Option<S>,
-        clock: Clock,
-    ) -> Self {
-        let sink_opt: Option<Box<dyn Sink>> = if let Some(sink) = sink_opt {
-            Some(Box::new(sink))
-        } else {
-            None
-        };
+    fn new(quickwit_info: QuickwitTelemetryInfo, sinks: Vec<Box<dyn Sink>>, clock: Clock) -> Self {
         Self {
             inner: Arc::new(Inner {
-                sink: sink_opt,
+                sinks,
                 client_info: ClientInfo::default(),
                 quickwit_info,
                 events: Events::default(),
@@ -342,14 +345,14 @@ mod tests {
         // We group the two in a single test to ensure it happens on the same thread.
         env::set_var(crate::DISABLE_TELEMETRY_ENV_KEY, "");
         assert_eq!(
-            TelemetrySender::from_quickwit_info(QuickwitTelemetryInfo::default())
+            TelemetrySender::from_quickwit_info(QuickwitTelemetryInfo::default(), Vec::new())
                 .inner
                 .is_disabled(),
             true
         );
         env::remove_var(crate::DISABLE_TELEMETRY_ENV_KEY);
         assert_eq!(
-            TelemetrySender::from_quickwit_info(QuickwitTelemetryInfo::default())
+            TelemetrySender::from_quickwit_info(QuickwitTelemetryInfo::default(), Vec::new())
                 .inner
                 .is_disabled(),
             false
@@ -361,7 +364,7 @@ mod tests {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let (_clock_btn, clock) = Clock::manual().await;
         let telemetry_sender =
-            TelemetrySender::new(QuickwitTelemetryInfo::default(), Some(tx), clock);
+            TelemetrySender::new(QuickwitTelemetryInfo::default(), vec![Box::new(tx)], clock);
         let loop_handler = telemetry_sender.start_loop();
         telemetry_sender.send(TelemetryEvent::UiIndexPageLoad).await;
         let payload_opt = rx.recv().await;
@@ -371,12 +374,43 @@ mod tests {
         loop_handler.terminate_telemetry().await;
     }
 
+    #[tokio::test]
+    async fn test_telemetry_multiple_sinks() {
+        let (tx1, mut rx1) = tokio::sync::mpsc::unbounded_channel();
+        let (tx2, mut rx2) = tokio::sync::mpsc::unbounded_channel();
+        let (_clock_btn, clock) = Clock::manual().await;
+        let telemetry_sender = TelemetrySender::new(
+            QuickwitTelemetryInfo::default(),
+            vec![Box::new(tx1), Box::new(tx2)],
+            clock,
+        );
+        let loop_handler = telemetry_sender.start_loop();
+        telemetry_sender.send(TelemetryEvent::UiIndexPageLoad).await;
+        for rx in [&mut rx1, &mut rx2] {
+            let payload = rx.recv().await.unwrap();
+            assert_eq!(payload.events.len(), 1);
+            assert_eq!(payload.events[0].event, TelemetryEvent::UiIndexPageLoad);
+        }
+        loop_handler.terminate_telemetry().await;
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_disabled_without_sinks() {
+        let telemetry_sender =
+            TelemetrySender::from_quickwit_info(QuickwitTelemetryInfo::default(), Vec::new());
+        assert!(telemetry_sender.inner.is_disabled());
+        assert!(matches!(
+            telemetry_sender.start_loop(),
+            TelemetryLoopHandle::NoLoop
+        ));
+    }
+
     #[tokio::test]
     async fn test_telemetry_two_events() {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let (clock_btn, clock) = Clock::manual().await;
         let telemetry_sender =
-            TelemetrySender::new(QuickwitTelemetryInfo::default(), Some(tx), clock);
+            TelemetrySender::new(QuickwitTelemetryInfo::default(), vec![Box::new(tx)], clock);
         let loop_handler = telemetry_sender.start_loop();
         telemetry_sender.send(TelemetryEvent::UiIndexPageLoad).await;
         {
@@ -397,7 +431,7 @@ mod tests {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let (clock_btn, clock) = Clock::manual().await;
         let telemetry_sender =
-            TelemetrySender::new(QuickwitTelemetryInfo::default(), Some(tx), clock);
+            TelemetrySender::new(QuickwitTelemetryInfo::default(), vec![Box::new(tx)], clock);
         let loop_handler = telemetry_sender.start_loop();
         telemetry_sender.send(TelemetryEvent::UiIndexPageLoad).await;
         {
@@ -419,7 +453,7 @@ mod tests {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let (clock_btn, clock) = Clock::manual().await;
         let telemetry_sender =
-            TelemetrySender::new(QuickwitTelemetryInfo::default(), Some(tx), clock);
+            TelemetrySender::new(QuickwitTelemetryInfo::default(), vec![Box::new(tx)], clock);
         let loop_handler = telemetry_sender.start_loop();
         telemetry_sender.send(TelemetryEvent::UiIndexPageLoad).await;
         {
@@ -446,7 +480,7 @@ mod tests {
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let (_clock_btn, clock) = Clock::manual().await;
         let telemetry_sender =
-            TelemetrySender::new(QuickwitTelemetryInfo::default(), Some(tx), clock);
+            TelemetrySender::new(QuickwitTelemetryInfo::default(), vec![Box::new(tx)], clock);
         let loop_handler = telemetry_sender.start_loop();
         telemetry_sender.send(TelemetryEvent::UiIndexPageLoad).await;
         let payload = rx.recv().await.unwrap();
@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::redirect::Policy;
 use reqwest::Client;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
 
-use crate::payload::TelemetryPayload;
+use crate::metrics::TELEMETRY_METRICS;
+use crate::payload::{TelemetryEvent, TelemetryPayload};
 
 /// Telemetry ingest API URL
 const DEFAULT_TELEMETRY_INGEST_API_URL: &str = "https://telemetry.quickwit.io/";
@@ -32,26 +38,42 @@ fn telemetry_ingest_api_url() -> String {
     }
 }
 
+/// Destination of the telemetry payloads. Sinks must not fail: errors are either swallowed or
+/// logged.
 #[async_trait]
 pub trait Sink: Send + Sync + 'static {
-    async fn send_payload(&self, payload: TelemetryPayload);
+    async fn send_payload(&self, payload: &TelemetryPayload);
 }
+
+/// Posts the telemetry payloads as JSON to an HTTP endpoint.
 pub struct HttpClient {
     client: Client,
     endpoint: String,
 }
 
 impl HttpClient {
+    /// Creates a client for Quickwit's telemetry server.
     pub fn try_new() -> Option<Self> {
+        Self::try_with_endpoint(telemetry_ingest_api_url(), &BTreeMap::new())
+    }
+
+    /// Creates a client for a custom endpoint, sending `headers` with every request. Returns
+    /// `None` if a header is invalid.
+    pub fn try_with_endpoint(endpoint: String, headers: &BTreeMap<String, String>) -> Option<Self> {
+        let mut default_headers = HeaderMap::with_capacity(headers.len());
+
+        for (header_name, header_value) in headers {
+            let header_name = HeaderName::from_bytes(header_name.as_bytes()).ok()?;
+            let header_value = HeaderValue::from_str(header_value).ok()?;
+            default_headers.insert(header_name, header_value);
+        }
         let client = Client::builder()
             .redirect(Policy::limited(3))
             .timeout(Duration::from_secs(10))
+            .default_headers(default_headers)
             .build()
             .ok()?;
-        Some(HttpClient {
-            client,
-            endpoint: telemetry_ingest_api_url(),
-        })
+        Some(HttpClient { client, endpoint })
     }
 
     pub fn endpoint(&self) -> &str {
@@ -59,17 +81,180 @@ impl HttpClient {
     }
 }
 
+/// Appends the telemetry payloads as JSON lines to a local file.
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    async fn append_payload(&self, payload: &TelemetryPayload) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(payload)?;
+        line.push(b'\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await
+    }
+}
+
+/// Counts the telemetry events in the Prometheus metrics of the node and exposes the statistics
+/// carried by the index stats events as gauges.
+#[derive(Debug, Default)]
+pub struct PrometheusSink;
+
 #[async_trait]
 impl Sink for UnboundedSender<TelemetryPayload> {
-    async fn send_payload(&self, payload: TelemetryPayload) {
-        let _ = self.send(payload);
+    async fn send_payload(&self, payload: &TelemetryPayload) {
+        let _ = self.send(payload.clone());
     }
 }
 
 #[async_trait]
 impl Sink for HttpClient {
-    async fn send_payload(&self, payload: TelemetryPayload) {
+    async fn send_payload(&self, payload: &TelemetryPayload) {
         // Note that we swallow the error if any
-        let _ = self.client.post(&self.endpoint).json(&payload).send().await;
+        let _ = self.client.post(&self.endpoint).json(payload).send().await;
+    }
+}
+
+#[async_trait]
+impl Sink for FileSink {
+    async fn send_payload(&self, payload: &TelemetryPayload) {
+        if let Err(error) = self.append_payload(payload).await {
+            warn!(path=%self.path.display(), %error, "failed to write telemetry payload to file");
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for PrometheusSink {
+    async fn send_payload(&self, payload: &TelemetryPayload) {
+        TELEMETRY_METRICS
+            .dropped_events_total
+            .inc_by(payload.num_dropped_events as u64);
+
+        for event_with_timestamp in &payload.events {
+            let event = &event_with_timestamp.event;
+            TELEMETRY_METRICS
+                .events_total
+                .with_label_values([event.event_type()])
+                .inc();
+
+            if let TelemetryEvent::IndexStats {
+                num_indexes,
+                num_published_splits,
+                num_published_docs,
+                size_published_splits,
+                size_published_docs_uncompressed,
+            } = event
+            {
+                TELEMETRY_METRICS.num_indexes.set(*num_indexes as i64);
+                TELEMETRY_METRICS
+                    .num_published_splits
+                    .set(*num_published_splits as i64);
+                TELEMETRY_METRICS
+                    .num_published_docs
+                    .set(*num_published_docs as i64);
+                TELEMETRY_METRICS
+                    .size_published_splits
+                    .set(*size_published_splits as i64);
+                TELEMETRY_METRICS
+                    .size_published_docs_uncompressed
+                    .set(*size_published_docs_uncompressed as i64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::{ClientInfo, EventWithTimestamp, QuickwitTelemetryInfo};
+
+    fn payload_for_test(events: Vec<TelemetryEvent>) -> TelemetryPayload {
+        TelemetryPayload {
+            client_info: ClientInfo::default(),
+            quickwit_info: QuickwitTelemetryInfo::default(),
+            events: events.into_iter().map(EventWithTimestamp::from).collect(),
+            num_dropped_events: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_appends_json_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("telemetry.jsonl");
+        let file_sink = FileSink::new(path.clone());
+
+        file_sink
+            .send_payload(&payload_for_test(vec![TelemetryEvent::RunCommand]))
+            .await;
+        file_sink
+            .send_payload(&payload_for_test(vec![
+                TelemetryEvent::Running,
+                TelemetryEvent::UiIndexPageLoad,
+            ]))
+            .await;
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let payloads: Vec<TelemetryPayload> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0].events[0].event, TelemetryEvent::RunCommand);
+        assert_eq!(payloads[1].events.len(), 2);
+        assert_eq!(payloads[1].events[1].event, TelemetryEvent::UiIndexPageLoad);
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_sink() {
+        let ui_index_page_load_counter = TELEMETRY_METRICS
+            .events_total
+            .with_label_values(["ui_index_page_load"]);
+        let num_ui_index_page_loads_before = ui_index_page_load_counter.get();
+
+        let mut payload = payload_for_test(vec![
+            TelemetryEvent::UiIndexPageLoad,
+            TelemetryEvent::UiIndexPageLoad,
+            TelemetryEvent::IndexStats {
+                num_indexes: 3,
+                num_published_splits: 12,
+                num_published_docs: 1_000,
+                size_published_splits: 4_000,
+                size_published_docs_uncompressed: 16_000,
+            },
+        ]);
+        payload.num_dropped_events = 1;
+        PrometheusSink.send_payload(&payload).await;
+
+        assert_eq!(
+            ui_index_page_load_counter.get(),
+            num_ui_index_page_loads_before + 2
+        );
+        assert_eq!(TELEMETRY_METRICS.num_indexes.get(), 3);
+        assert_eq!(TELEMETRY_METRICS.num_published_splits.get(), 12);
+        assert_eq!(TELEMETRY_METRICS.size_published_splits.get(), 4_000);
+    }
+
+    #[test]
+    fn test_http_client_rejects_invalid_headers() {
+        let endpoint = "https://telemetry.example.com/".to_string();
+        let headers =
+            BTreeMap::from_iter([("Authorization".to_string(), "Bearer secret".to_string())]);
+        let http_client = HttpClient::try_with_endpoint(endpoint.clone(), &headers).unwrap();
+        assert_eq!(http_client.endpoint(), endpoint);
+
+        let invalid_headers =
+            BTreeMap::from_iter([("Invalid Header".to_string(), "value".to_string())]);
+        assert!(HttpClient::try_with_endpoint(endpoint, &invalid_headers).is_none());
     }
 }